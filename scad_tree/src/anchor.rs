@@ -0,0 +1,262 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::{
+    eval::{transform, transform_matrix},
+    prelude::*,
+    Mt4,
+};
+
+/// The middle of the object.
+pub const CENTER: Pt3 = Pt3::new(0.0, 0.0, 0.0);
+/// The +Z side.
+pub const TOP: Pt3 = Pt3::new(0.0, 0.0, 1.0);
+/// The -Z side.
+pub const BOTTOM: Pt3 = Pt3::new(0.0, 0.0, -1.0);
+/// The -X side.
+pub const LEFT: Pt3 = Pt3::new(-1.0, 0.0, 0.0);
+/// The +X side.
+pub const RIGHT: Pt3 = Pt3::new(1.0, 0.0, 0.0);
+/// The -Y side.
+pub const FRONT: Pt3 = Pt3::new(0.0, -1.0, 0.0);
+/// The +Y side.
+pub const BACK: Pt3 = Pt3::new(0.0, 1.0, 0.0);
+
+/// A point on an object and the direction things attach to it from.
+///
+/// The named anchors of an object are found by direction with Scad::anchor and
+/// Polyhedron::anchor, the directions can be added for edges and corners e.g.
+/// TOP + RIGHT. Other anchors are made with Anchor::new.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Anchor {
+    /// The point on the object.
+    pub position: Pt3,
+    /// The unit direction pointing out of the object.
+    pub direction: Pt3,
+    /// The angle in degrees things are turned around the direction when attached.
+    pub spin: f64,
+}
+
+impl Anchor {
+    /// Create an anchor, the direction is normalized.
+    pub fn new(position: Pt3, direction: Pt3, spin: f64) -> Self {
+        Self {
+            position,
+            direction: direction.normalized(),
+            spin,
+        }
+    }
+
+    /// The anchor on the side of a box in a direction.
+    fn on_box(min: Pt3, max: Pt3, direction: Pt3) -> Self {
+        let center = (min + max) / 2.0;
+        let half = (max - min) / 2.0;
+        Self {
+            position: center
+                + Pt3::new(
+                    half.x * side(direction.x),
+                    half.y * side(direction.y),
+                    half.z * side(direction.z),
+                ),
+            direction: out_direction(direction),
+            spin: 0.0,
+        }
+    }
+}
+
+/// Which side of the middle a direction is on, 0 for the middle.
+fn side(value: f64) -> f64 {
+    if value > 0.0 {
+        1.0
+    } else if value < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+/// The direction of an anchor, up for the center.
+fn out_direction(direction: Pt3) -> Pt3 {
+    if direction.len2() == 0.0 {
+        TOP
+    } else {
+        direction.normalized()
+    }
+}
+
+impl Scad {
+    /// Finds the anchor of the object in a direction.
+    ///
+    /// Cubes and polyhedrons anchor on their bounding box, spheres on their
+    /// surface and cylinders on their faces and sides. The anchors of a transform
+    /// with one child are the anchors of the child moved with it. Everything
+    /// else anchors on the box from Scad::bounds.
+    ///
+    /// direction: A named direction like TOP or a sum of them like TOP + RIGHT.
+    ///
+    /// return: The anchor or None when the bounds are unknown.
+    pub fn anchor(&self, direction: Pt3) -> Option<Anchor> {
+        match &self.op {
+            ScadOp::Cube { size, center } => {
+                let min = if *center {
+                    *size / -2.0
+                } else {
+                    Pt3::new(0.0, 0.0, 0.0)
+                };
+                Some(Anchor::on_box(min, min + *size, direction))
+            }
+            ScadOp::Sphere { radius, .. } => {
+                let position = if direction.len2() == 0.0 {
+                    CENTER
+                } else {
+                    direction.normalized() * *radius
+                };
+                Some(Anchor::new(position, out_direction(direction), 0.0))
+            }
+            ScadOp::Cylinder {
+                height,
+                radius1,
+                radius2,
+                center,
+                ..
+            } => {
+                let bottom = if *center { -height / 2.0 } else { 0.0 };
+                let z = bottom + height * (side(direction.z) + 1.0) / 2.0;
+                let radius = radius1 + (radius2 - radius1) * (z - bottom) / height;
+                let side = Pt3::new(direction.x, direction.y, 0.0);
+                let side = if side.len2() == 0.0 {
+                    side
+                } else {
+                    side.normalized() * radius
+                };
+                Some(Anchor::new(
+                    Pt3::new(side.x, side.y, z),
+                    out_direction(direction),
+                    0.0,
+                ))
+            }
+            ScadOp::Polyhedron { points, .. } => {
+                Some(Polyhedron::new(points.clone(), Faces::new()).anchor(direction))
+            }
+            op if self.children.len() == 1 && transform_matrix(op).is_some() => {
+                let matrix = transform_matrix(op)?;
+                let anchor = self.children[0].anchor(direction)?;
+                let origin = transform(&matrix, Pt3::new(0.0, 0.0, 0.0));
+                Some(Anchor::new(
+                    transform(&matrix, anchor.position),
+                    transform(&matrix, anchor.direction) - origin,
+                    anchor.spin,
+                ))
+            }
+            _ => {
+                let (min, max) = self.bounds()?;
+                Some(Anchor::on_box(min, max, direction))
+            }
+        }
+    }
+}
+
+impl Polyhedron {
+    /// Finds the anchor on the bounding box of the polyhedron in a direction.
+    ///
+    /// direction: A named direction like TOP or a sum of them like TOP + RIGHT.
+    ///
+    /// return: The anchor.
+    pub fn anchor(&self, direction: Pt3) -> Anchor {
+        let (min, max) = self.bounds();
+        Anchor::on_box(min, max, direction)
+    }
+}
+
+/// The rotations that turn a child anchor to face a parent anchor as (degrees, axis).
+fn attach_rotations(parent: &Anchor, child: &Anchor) -> Vec<(f64, Pt3)> {
+    let mut rotations = Vec::with_capacity(3);
+    if child.spin != 0.0 {
+        rotations.push((-child.spin, child.direction));
+    }
+    let target = -parent.direction;
+    let axis = child.direction.cross(target);
+    let cos = child.direction.dot(target);
+    if axis.len() > 1.0e-12 {
+        rotations.push((axis.len().atan2(cos).to_degrees(), axis.normalized()));
+    } else if cos < 0.0 {
+        // facing the same way, turn over around any perpendicular axis
+        let other = if child.direction.x.abs() < 0.9 {
+            RIGHT
+        } else {
+            BACK
+        };
+        rotations.push((180.0, child.direction.cross(other).normalized()));
+    }
+    if parent.spin != 0.0 {
+        rotations.push((parent.spin, parent.direction));
+    }
+    rotations
+}
+
+/// Attaches a child to a parent so the anchors touch and face each other.
+///
+/// The child is moved so its anchor is at the position of the parent anchor
+/// with the anchor directions opposite, then spun by the spin of the parent
+/// anchor.
+///
+/// parent_anchor: The anchor on the parent e.g. from Scad::anchor.
+///
+/// child: The object to move.
+///
+/// child_anchor: The anchor on the child.
+///
+/// return: The child moved into place.
+pub fn attach(parent_anchor: Anchor, child: Scad, child_anchor: Anchor) -> Scad {
+    let mut result = translate!([-child_anchor.position.x, -child_anchor.position.y, -child_anchor.position.z],
+        child;
+    );
+    for (degrees, axis) in attach_rotations(&parent_anchor, &child_anchor) {
+        result = rotate!(a=degrees, v=[axis.x, axis.y, axis.z], result;);
+    }
+    let p = parent_anchor.position;
+    translate!([p.x, p.y, p.z], result;)
+}
+
+/// Attaches a child polyhedron to a parent like attach.
+///
+/// parent_anchor: The anchor on the parent.
+///
+/// child: The polyhedron to move.
+///
+/// child_anchor: The anchor on the child e.g. from Polyhedron::anchor.
+///
+/// return: The child moved into place.
+pub fn attach_polyhedron(
+    parent_anchor: Anchor,
+    child: Polyhedron,
+    child_anchor: Anchor,
+) -> Polyhedron {
+    let mut child = child;
+    child.translate(-child_anchor.position);
+    for (degrees, axis) in attach_rotations(&parent_anchor, &child_anchor) {
+        child.apply_matrix(&Mt4::rot_vec(axis.x, axis.y, axis.z, degrees));
+    }
+    child.translate(parent_anchor.position);
+    child
+}
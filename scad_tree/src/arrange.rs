@@ -0,0 +1,161 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::prelude::*;
+
+/// The options for laying parts out on a build plate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArrangeOptions {
+    /// The gap between neighboring parts.
+    pub spacing: f64,
+    /// The size of the build plate, f64::INFINITY for no limit.
+    pub plate: Pt2,
+    /// Center the layout on the plate instead of starting at the origin.
+    pub center: bool,
+    /// Place the deepest parts first so the rows are packed tighter.
+    pub sort: bool,
+}
+
+impl Default for ArrangeOptions {
+    fn default() -> Self {
+        Self {
+            spacing: 5.0,
+            plate: Pt2::new(220.0, 220.0),
+            center: false,
+            sort: true,
+        }
+    }
+}
+
+/// Computes where each footprint goes on the plate.
+///
+/// The parts are placed in rows along the X axis starting at the origin, a new
+/// row is started when a part would go over the edge of the plate.
+///
+/// footprints: The minimum and maximum corners of the parts.
+///
+/// options: The spacing and plate size.
+///
+/// return: The translation for each part in the same order as the footprints,
+/// the translations also drop the parts onto the XY plane.
+pub fn placements(
+    footprints: &[(Pt3, Pt3)],
+    options: &ArrangeOptions,
+) -> Result<Vec<Pt3>, ScadError> {
+    let mut order: Vec<usize> = (0..footprints.len()).collect();
+    if options.sort {
+        order.sort_by(|a, b| {
+            let depth = |i: usize| footprints[i].1.y - footprints[i].0.y;
+            depth(*b).total_cmp(&depth(*a))
+        });
+    }
+
+    let mut offsets = vec![Pt3::new(0.0, 0.0, 0.0); footprints.len()];
+    let mut x = 0.0;
+    let mut y = 0.0;
+    let mut row_depth = 0.0f64;
+    let mut used = Pt2::new(0.0, 0.0);
+    for i in order {
+        let (min, max) = footprints[i];
+        let size = max - min;
+        if size.x > options.plate.x || size.y > options.plate.y {
+            return Err(ScadError::Invalid(format!(
+                "part {} is larger than the plate",
+                i
+            )));
+        }
+        if x > 0.0 && x + size.x > options.plate.x {
+            x = 0.0;
+            y += row_depth + options.spacing;
+            row_depth = 0.0;
+        }
+        if y + size.y > options.plate.y {
+            return Err(ScadError::Invalid(format!(
+                "no room on the plate for part {}",
+                i
+            )));
+        }
+        offsets[i] = Pt3::new(x - min.x, y - min.y, -min.z);
+        used = Pt2::new(used.x.max(x + size.x), used.y.max(y + size.y));
+        x += size.x + options.spacing;
+        row_depth = row_depth.max(size.y);
+    }
+
+    if options.center {
+        let shift = (options.plate - used) / 2.0;
+        for offset in offsets.iter_mut() {
+            *offset += shift.as_pt3(0.0);
+        }
+    }
+    Ok(offsets)
+}
+
+/// Lays the parts out on a build plate.
+///
+/// parts: The parts, each needs bounds from Scad::bounds.
+///
+/// options: The spacing and plate size.
+///
+/// return: The union of the placed parts and the translation of each part.
+pub fn arrange(parts: Vec<Scad>, options: &ArrangeOptions) -> Result<(Scad, Vec<Pt3>), ScadError> {
+    let footprints = parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            part.bounds()
+                .ok_or_else(|| ScadError::Invalid(format!("part {} has unknown bounds", i)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let offsets = placements(&footprints, options)?;
+    let scad = Scad {
+        op: ScadOp::Union,
+        children: parts
+            .into_iter()
+            .zip(offsets.iter())
+            .map(|(part, offset)| translate!([offset.x, offset.y, offset.z], part;))
+            .collect(),
+    };
+    Ok((scad, offsets))
+}
+
+/// Lays the polyhedrons out on a build plate.
+///
+/// parts: The polyhedrons, they are moved in place.
+///
+/// options: The spacing and plate size.
+///
+/// return: The translation of each polyhedron.
+pub fn arrange_polyhedrons(
+    parts: &mut [Polyhedron],
+    options: &ArrangeOptions,
+) -> Result<Vec<Pt3>, ScadError> {
+    let footprints: Vec<(Pt3, Pt3)> = parts.iter().map(|part| part.bounds()).collect();
+    if footprints.iter().any(|(min, max)| min.x > max.x) {
+        return Err(ScadError::Invalid("polyhedron has no points".to_string()));
+    }
+    let offsets = placements(&footprints, options)?;
+    for (part, offset) in parts.iter_mut().zip(offsets.iter()) {
+        part.translate(*offset);
+    }
+    Ok(offsets)
+}
@@ -0,0 +1,224 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Named parts of a multi-piece design exported together with a preview.
+
+use {
+    crate::{color_palette, prelude::*},
+    std::path::{Path, PathBuf},
+};
+
+/// A part of an assembly.
+#[derive(Clone)]
+pub struct AssemblyPart {
+    /// The name of the part, also its file name.
+    pub name: String,
+    /// The part where it sits in the assembly.
+    pub scad: Scad,
+    /// The color of the part in the preview, None for a color from color_palette.
+    pub color: Option<Pt4>,
+}
+
+/// The parts of a multi-piece design.
+///
+/// Each part is exported to its own file and a preview file shows all the
+/// parts colored and pulled apart.
+#[derive(Clone)]
+pub struct Assembly {
+    /// The name of the preview file.
+    pub name: String,
+    /// How far each part is moved from the one before it in the preview.
+    pub explode: Pt3,
+    /// The kind of file the parts are exported to, the preview is always Scad.
+    pub format: OutputFormat,
+    /// The header, quality settings and module deduplication of the files.
+    pub options: ScadFileOptions,
+    /// The kind of file the bill of materials is exported to, None for no file.
+    pub bom_format: Option<BomFormat>,
+    parts: Vec<AssemblyPart>,
+}
+
+impl Default for Assembly {
+    fn default() -> Self {
+        Self {
+            name: "assembly".to_string(),
+            explode: Pt3::new(0.0, 0.0, 0.0),
+            format: OutputFormat::Scad,
+            options: ScadFileOptions::default(),
+            bom_format: None,
+            parts: Vec::new(),
+        }
+    }
+}
+
+impl Assembly {
+    /// Creates an empty assembly.
+    ///
+    /// name: The name of the preview file.
+    ///
+    /// return: The assembly.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a part.
+    ///
+    /// name: The name of the part, it must not be used by another part or the preview.
+    ///
+    /// scad: The part where it sits in the assembly.
+    ///
+    /// return: self
+    pub fn add_part(&mut self, name: &str, scad: Scad) -> &mut Self {
+        assert!(
+            name != self.name && self.parts.iter().all(|p| p.name != name),
+            "duplicate part {}",
+            name
+        );
+        self.parts.push(AssemblyPart {
+            name: name.to_string(),
+            scad,
+            color: None,
+        });
+        self
+    }
+
+    /// Sets the preview color of a part.
+    ///
+    /// name: The name of the part.
+    ///
+    /// color: The red, green, blue and alpha from 0 to 1.
+    ///
+    /// return: self
+    pub fn set_color(&mut self, name: &str, color: Pt4) -> &mut Self {
+        let part = self
+            .parts
+            .iter_mut()
+            .find(|p| p.name == name)
+            .unwrap_or_else(|| panic!("no part named {}", name));
+        part.color = Some(color);
+        self
+    }
+
+    /// The parts in the order they were added.
+    pub fn parts(&self) -> &[AssemblyPart] {
+        &self.parts
+    }
+
+    /// The tree of a part.
+    ///
+    /// name: The name of the part.
+    ///
+    /// return: The part or None.
+    pub fn part(&self, name: &str) -> Option<&Scad> {
+        self.parts.iter().find(|p| p.name == name).map(|p| &p.scad)
+    }
+
+    /// The parts colored and exploded.
+    ///
+    /// return: A union of the parts, each moved by its index times explode.
+    pub fn preview(&self) -> Scad {
+        let palette = color_palette(self.parts.len());
+        let children = self
+            .parts
+            .iter()
+            .zip(palette)
+            .enumerate()
+            .map(|(i, (part, default))| {
+                let c = part.color.unwrap_or(default);
+                let colored = color!([c.x, c.y, c.z, c.w], part.scad.clone(););
+                let offset = self.explode * i as f64;
+                if offset.len() > 0.0 {
+                    translate!([offset.x, offset.y, offset.z], colored;)
+                } else {
+                    colored
+                }
+            })
+            .collect();
+        Scad {
+            op: ScadOp::Union,
+            children,
+        }
+    }
+
+    /// The bill of materials of the assembly.
+    ///
+    /// return: One of each part followed by the items marked in the parts.
+    pub fn bom(&self) -> Bom {
+        let mut bom = Bom::new();
+        for part in self.parts.iter() {
+            bom.add(&part.name, 1);
+        }
+        for part in self.parts.iter() {
+            bom.merge(&part.scad.bom());
+        }
+        bom
+    }
+
+    /// A manifest with a target for each part and one for the preview.
+    ///
+    /// dir: The directory of the files.
+    ///
+    /// return: The manifest or an error if the name of the preview is used by a part.
+    pub fn manifest<P: Into<PathBuf>>(&self, dir: P) -> Result<Manifest, ScadError> {
+        let mut manifest = Manifest::new(dir);
+        for part in self.parts.iter() {
+            let scad = part.scad.clone();
+            manifest.add(BuildTarget {
+                format: self.format,
+                options: self.options.clone(),
+                ..BuildTarget::new(&part.name, move || scad.clone())
+            })?;
+        }
+        let preview = self.preview();
+        manifest.add(BuildTarget {
+            options: self.options.clone(),
+            ..BuildTarget::new(&self.name, move || preview.clone())
+        })?;
+        Ok(manifest)
+    }
+
+    /// Writes each part to its own file, the preview to name.scad and the bill
+    /// of materials to name_bom when there is a bom_format.
+    ///
+    /// Formats other than Scad run OpenSCAD.
+    ///
+    /// dir: The directory of the files.
+    ///
+    /// return: The paths of the parts followed by the preview and the bill of
+    /// materials or the first error.
+    pub fn export<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<PathBuf>, ScadError> {
+        let mut paths = self.manifest(dir.as_ref())?.build_all()?;
+        if let Some(format) = self.bom_format {
+            let path = dir
+                .as_ref()
+                .join(format!("{}_bom", self.name))
+                .with_extension(format.extension());
+            std::fs::write(&path, self.bom().to_format(format))?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
@@ -0,0 +1,182 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::prelude::*;
+
+/// The length of an open belt around two pulleys.
+///
+/// diameter1: The pitch diameter of the first pulley.
+///
+/// diameter2: The pitch diameter of the second pulley.
+///
+/// center_distance: The distance between the axes of the pulleys.
+///
+/// return: The length of the belt.
+pub fn belt_length(diameter1: f64, diameter2: f64, center_distance: f64) -> f64 {
+    let difference = (diameter1 - diameter2).abs();
+    assert!(2.0 * center_distance > difference);
+    let straight = (center_distance * center_distance - difference * difference / 4.0).sqrt();
+    2.0 * straight
+        + std::f64::consts::PI * (diameter1 + diameter2) / 2.0
+        + difference * (difference / (2.0 * center_distance)).asin()
+}
+
+/// Solves the distance between two pulleys for a belt length.
+///
+/// diameter1: The pitch diameter of the first pulley.
+///
+/// diameter2: The pitch diameter of the second pulley.
+///
+/// length: The length of the belt.
+///
+/// return: The distance between the axes or None if the belt is too short to
+/// fit around the pulleys without them overlapping.
+pub fn center_distance(diameter1: f64, diameter2: f64, length: f64) -> Option<f64> {
+    let mut low = (diameter1 + diameter2) / 2.0;
+    if belt_length(diameter1, diameter2, low) > length {
+        return None;
+    }
+    // the length grows with the distance so bisect
+    let mut high = length / 2.0;
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if belt_length(diameter1, diameter2, mid) < length {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+/// The pitch diameter of a toothed pulley.
+///
+/// teeth: The number of teeth on the pulley.
+///
+/// pitch: The distance between the teeth of the belt, 2 for GT2.
+///
+/// return: The diameter.
+pub fn pulley_pitch_diameter(teeth: u64, pitch: f64) -> f64 {
+    teeth as f64 * pitch / std::f64::consts::PI
+}
+
+/// Solves the distance between two toothed pulleys for a stock belt.
+///
+/// teeth1: The teeth on the first pulley.
+///
+/// teeth2: The teeth on the second pulley.
+///
+/// belt_teeth: The teeth on the closed belt.
+///
+/// pitch: The distance between the teeth of the belt, 2 for GT2.
+///
+/// return: The distance between the axes or None if the belt is too short.
+pub fn toothed_center_distance(
+    teeth1: u64,
+    teeth2: u64,
+    belt_teeth: u64,
+    pitch: f64,
+) -> Option<f64> {
+    center_distance(
+        pulley_pitch_diameter(teeth1, pitch),
+        pulley_pitch_diameter(teeth2, pitch),
+        belt_teeth as f64 * pitch,
+    )
+}
+
+/// Finds the stock belt closest to a distance between two toothed pulleys.
+///
+/// teeth1: The teeth on the first pulley.
+///
+/// teeth2: The teeth on the second pulley.
+///
+/// pitch: The distance between the teeth of the belt, 2 for GT2.
+///
+/// target: The wanted distance between the axes.
+///
+/// stock: The teeth on the belts that can be bought.
+///
+/// return: The teeth on the belt and the distance it gives or None if no belt fits.
+pub fn nearest_stock_belt(
+    teeth1: u64,
+    teeth2: u64,
+    pitch: f64,
+    target: f64,
+    stock: &[u64],
+) -> Option<(u64, f64)> {
+    stock
+        .iter()
+        .filter_map(|&belt_teeth| {
+            toothed_center_distance(teeth1, teeth2, belt_teeth, pitch)
+                .map(|distance| (belt_teeth, distance))
+        })
+        .min_by(|a, b| (a.1 - target).abs().total_cmp(&(b.1 - target).abs()))
+}
+
+/// Creates a plate to mount two pulley shafts at a distance.
+///
+/// The first shaft is on the Z axis and the second is on the +X axis. The
+/// second hole is a slot when adjust is greater than zero so the belt can
+/// be tensioned.
+///
+/// center_distance: The distance between the shafts.
+///
+/// shaft_diameter: The diameter of the shaft holes.
+///
+/// boss_diameter: The width of the plate around the holes.
+///
+/// thickness: The thickness of the plate.
+///
+/// adjust: The length of the slot for the second shaft.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The plate.
+pub fn pulley_mount(
+    center_distance: f64,
+    shaft_diameter: f64,
+    boss_diameter: f64,
+    thickness: f64,
+    adjust: f64,
+    segments: u64,
+) -> Scad {
+    assert!(boss_diameter > shaft_diameter && adjust >= 0.0);
+    let plate = dim2::slot(
+        center_distance + adjust / 2.0,
+        boss_diameter / 2.0,
+        segments,
+        false,
+    );
+    let first = dim2::circle(shaft_diameter / 2.0, segments);
+    let mut second = if adjust > 0.0 {
+        dim2::slot(adjust, shaft_diameter / 2.0, segments, true)
+    } else {
+        dim2::circle(shaft_diameter / 2.0, segments)
+    };
+    second.translate(Pt2::new(center_distance, 0.0));
+    let (points, paths) = dim2::merge_outlines(&[plate, first, second]);
+    let convexity = 2;
+    linear_extrude!(height=thickness, center=false, convexity=convexity, twist=0.0, scale=1.0, slices=1,
+        polygon!(points, paths);
+    )
+}
@@ -0,0 +1,207 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Bills of materials collected from the items marked in Scad trees.
+
+use crate::prelude::*;
+
+/// The kind of file a bill of materials is written as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BomFormat {
+    /// Comma separated values with a header row.
+    Csv,
+    /// A Markdown table.
+    Markdown,
+}
+
+impl BomFormat {
+    /// The file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BomFormat::Csv => "csv",
+            BomFormat::Markdown => "md",
+        }
+    }
+}
+
+/// A line of a bill of materials.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BomLine {
+    /// The name of the item.
+    pub name: String,
+    /// How many of the item are needed.
+    pub quantity: u64,
+}
+
+/// A bill of materials, the items of a design and how many of each.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bom {
+    lines: Vec<BomLine>,
+}
+
+impl Bom {
+    /// Creates an empty bill of materials.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds items, items with the same name are counted together.
+    ///
+    /// name: The name of the item.
+    ///
+    /// quantity: The number of items.
+    ///
+    /// return: self
+    pub fn add(&mut self, name: &str, quantity: u64) -> &mut Self {
+        match self.lines.iter_mut().find(|l| l.name == name) {
+            Some(line) => line.quantity += quantity,
+            None => self.lines.push(BomLine {
+                name: name.to_string(),
+                quantity,
+            }),
+        }
+        self
+    }
+
+    /// Adds the items of another bill of materials.
+    pub fn merge(&mut self, other: &Bom) -> &mut Self {
+        for line in other.lines.iter() {
+            self.add(&line.name, line.quantity);
+        }
+        self
+    }
+
+    /// The lines in the order the items were first added.
+    pub fn lines(&self) -> &[BomLine] {
+        &self.lines
+    }
+
+    /// The number of an item.
+    ///
+    /// name: The name of the item.
+    ///
+    /// return: The quantity, 0 when the item is not in the list.
+    pub fn quantity(&self, name: &str) -> u64 {
+        self.lines
+            .iter()
+            .find(|l| l.name == name)
+            .map_or(0, |l| l.quantity)
+    }
+
+    /// True when there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Formats the list as CSV with quantity and item columns.
+    pub fn to_csv(&self) -> String {
+        let mut result = String::from("quantity,item\n");
+        for line in self.lines.iter() {
+            let name = if line.name.contains([',', '"', '\n']) {
+                format!("\"{}\"", line.name.replace('"', "\"\""))
+            } else {
+                line.name.clone()
+            };
+            result += &format!("{},{}\n", line.quantity, name);
+        }
+        result
+    }
+
+    /// Formats the list as a Markdown table with quantity and item columns.
+    pub fn to_markdown(&self) -> String {
+        let mut result = String::from("| Qty | Item |\n| ---: | --- |\n");
+        for line in self.lines.iter() {
+            result += &format!(
+                "| {} | {} |\n",
+                line.quantity,
+                line.name.replace('|', "\\|")
+            );
+        }
+        result
+    }
+
+    /// Formats the list.
+    ///
+    /// format: The kind of file.
+    ///
+    /// return: The contents of the file.
+    pub fn to_format(&self, format: BomFormat) -> String {
+        match format {
+            BomFormat::Csv => self.to_csv(),
+            BomFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    /// Saves the list.
+    ///
+    /// path: The path of the file.
+    ///
+    /// format: The kind of file.
+    ///
+    /// return: An error if the file could not be written.
+    pub fn save(&self, path: &str, format: BomFormat) -> Result<(), ScadError> {
+        std::fs::write(path, self.to_format(format))?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Bom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.lines.iter() {
+            writeln!(f, "{}x {}", line.quantity, line.name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Scad {
+    /// The bill of materials of the items marked with Scad::bom_item.
+    ///
+    /// Each copy of a marked subtree is counted and disabled subtrees are
+    /// left out. Items inside items are multiplied by the quantity of the
+    /// enclosing items, so a pin inside a hinge marked with quantity 2
+    /// counts twice. The bolt, screw and nut helpers of metric_thread mark
+    /// their output, so the Scad they return is a BomItem node with the
+    /// model as its only child.
+    ///
+    /// return: The items in the order they are found.
+    pub fn bom(&self) -> Bom {
+        fn collect(scad: &Scad, multiplier: u64, bom: &mut Bom) {
+            let mut multiplier = multiplier;
+            match &scad.op {
+                ScadOp::Modifier(ScadModifier::Disable) => return,
+                ScadOp::BomItem { name, quantity } => {
+                    multiplier *= *quantity;
+                    bom.add(name, multiplier);
+                }
+                _ => (),
+            }
+            for child in scad.children.iter() {
+                collect(child, multiplier, bom);
+            }
+        }
+        let mut bom = Bom::new();
+        collect(self, 1, &mut bom);
+        bom
+    }
+}
@@ -0,0 +1,249 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Estimates the bounding box of a Scad tree from the sizes of the primitives.
+
+use crate::{
+    eval::{transform, transform_matrix},
+    prelude::*,
+};
+
+/// The box around two boxes.
+fn union(a: (Pt3, Pt3), b: (Pt3, Pt3)) -> (Pt3, Pt3) {
+    (
+        Pt3::new(a.0.x.min(b.0.x), a.0.y.min(b.0.y), a.0.z.min(b.0.z)),
+        Pt3::new(a.1.x.max(b.1.x), a.1.y.max(b.1.y), a.1.z.max(b.1.z)),
+    )
+}
+
+/// The box around 2D points.
+fn points_bounds(points: impl Iterator<Item = Pt2>) -> Option<(Pt3, Pt3)> {
+    points.map(|p| (p.as_pt3(0.0), p.as_pt3(0.0))).reduce(union)
+}
+
+impl Scad {
+    /// Estimates the axis aligned box around the object.
+    ///
+    /// The sizes of the primitives are carried through the transforms, rotated
+    /// boxes are boxed again so the result can be larger than the object. The
+    /// first child of a difference is used as is, an intersection is the overlap
+    /// of the boxes and extrusions use the box of their profiles. 2D objects are
+    /// flat on the XY plane and text is estimated with TextParams::measure.
+    /// Import, surface, calls, raw code and background or disabled subtrees have
+    /// no bounds.
+    ///
+    /// return: The minimum and maximum corners or None when nothing is known.
+    pub fn bounds(&self) -> Option<(Pt3, Pt3)> {
+        let children = || {
+            self.children
+                .iter()
+                .filter_map(|c| c.bounds())
+                .reduce(union)
+        };
+        match &self.op {
+            ScadOp::Cube { size, center } => {
+                let min = if *center {
+                    *size / -2.0
+                } else {
+                    Pt3::new(0.0, 0.0, 0.0)
+                };
+                Some((min, min + *size))
+            }
+            ScadOp::Sphere { radius, .. } => {
+                let r = Pt3::new(*radius, *radius, *radius);
+                Some((-r, r))
+            }
+            ScadOp::Cylinder {
+                height,
+                radius1,
+                radius2,
+                center,
+                ..
+            } => {
+                let r = radius1.max(*radius2);
+                let z = if *center { -height / 2.0 } else { 0.0 };
+                Some((Pt3::new(-r, -r, z), Pt3::new(r, r, z + height)))
+            }
+            ScadOp::Polyhedron { points, .. } => points.iter().map(|p| (*p, *p)).reduce(union),
+            ScadOp::Circle { radius, .. } => Some((
+                Pt3::new(-radius, -radius, 0.0),
+                Pt3::new(*radius, *radius, 0.0),
+            )),
+            ScadOp::Square { size, center } => {
+                let min = if *center {
+                    *size / -2.0
+                } else {
+                    Pt2::new(0.0, 0.0)
+                };
+                Some((min.as_pt3(0.0), (min + *size).as_pt3(0.0)))
+            }
+            ScadOp::Polygon { points, .. } => points_bounds(points.iter().copied()),
+            ScadOp::Translate { .. }
+            | ScadOp::Rotate { .. }
+            | ScadOp::Scale { .. }
+            | ScadOp::Mirror { .. } => {
+                let matrix = transform_matrix(&self.op)?;
+                let (min, max) = children()?;
+                (0..8)
+                    .map(|i| {
+                        let corner = Pt3::new(
+                            if i & 1 == 0 { min.x } else { max.x },
+                            if i & 2 == 0 { min.y } else { max.y },
+                            if i & 4 == 0 { min.z } else { max.z },
+                        );
+                        let p = transform(&matrix, corner);
+                        (p, p)
+                    })
+                    .reduce(union)
+            }
+            ScadOp::Difference => self.children.first()?.bounds(),
+            ScadOp::Intersection => {
+                let (min, max) = self
+                    .children
+                    .iter()
+                    .map(|c| c.bounds())
+                    .collect::<Option<Vec<_>>>()?
+                    .into_iter()
+                    .reduce(|a, b| {
+                        (
+                            Pt3::new(a.0.x.max(b.0.x), a.0.y.max(b.0.y), a.0.z.max(b.0.z)),
+                            Pt3::new(a.1.x.min(b.1.x), a.1.y.min(b.1.y), a.1.z.min(b.1.z)),
+                        )
+                    })?;
+                if min.x > max.x || min.y > max.y || min.z > max.z {
+                    None
+                } else {
+                    Some((min, max))
+                }
+            }
+            ScadOp::Minkowski { .. } => self
+                .children
+                .iter()
+                .map(|c| c.bounds())
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .reduce(|a, b| (a.0 + b.0, a.1 + b.1)),
+            ScadOp::LinearExtrude {
+                height,
+                center,
+                twist,
+                scale,
+                ..
+            } => {
+                let (min, max) = children()?;
+                let (min, max) = if *twist != 0.0 {
+                    // any corner can turn to any side
+                    let r = [min.x, max.x]
+                        .iter()
+                        .flat_map(|x| [min.y, max.y].map(|y| Pt2::new(*x, y).len()))
+                        .fold(0.0, f64::max);
+                    (Pt3::new(-r, -r, 0.0), Pt3::new(r, r, 0.0))
+                } else {
+                    (min, max)
+                };
+                let top = (
+                    Pt3::new(min.x * scale.x, min.y * scale.y, 0.0),
+                    Pt3::new(max.x * scale.x, max.y * scale.y, 0.0),
+                );
+                let (min, max) = union((min, max), top);
+                let z = if *center { -height / 2.0 } else { 0.0 };
+                Some((
+                    Pt3::new(min.x, min.y, z),
+                    Pt3::new(max.x, max.y, z + height),
+                ))
+            }
+            ScadOp::RotateExtrude { .. } => {
+                let (min, max) = children()?;
+                let r = max.x.max(0.0);
+                Some((Pt3::new(-r, -r, min.y), Pt3::new(r, r, max.y)))
+            }
+            ScadOp::Projection { .. } => {
+                let (min, max) = children()?;
+                Some((Pt3::new(min.x, min.y, 0.0), Pt3::new(max.x, max.y, 0.0)))
+            }
+            ScadOp::Offset { r, delta, .. } => {
+                let (min, max) = children()?;
+                let d = r.or(*delta).unwrap_or(0.0);
+                Some((
+                    Pt3::new(min.x - d, min.y - d, 0.0),
+                    Pt3::new(max.x + d, max.y + d, 0.0),
+                ))
+            }
+            ScadOp::Resize { newsize, .. } => {
+                let (min, max) = children()?;
+                let size = max - min;
+                let factor = |new: f64, old: f64| {
+                    if new > 0.0 && old > 0.0 {
+                        new / old
+                    } else {
+                        1.0
+                    }
+                };
+                let f = Pt3::new(
+                    factor(newsize.x, size.x),
+                    factor(newsize.y, size.y),
+                    factor(newsize.z, size.z),
+                );
+                Some((
+                    Pt3::new(min.x * f.x, min.y * f.y, min.z * f.z),
+                    Pt3::new(max.x * f.x, max.y * f.y, max.z * f.z),
+                ))
+            }
+            ScadOp::Modifier(ScadModifier::Background | ScadModifier::Disable) => None,
+            ScadOp::IfPreview => self.children.get(1)?.bounds(),
+            ScadOp::Union
+            | ScadOp::Hull
+            | ScadOp::Color { .. }
+            | ScadOp::Render { .. }
+            | ScadOp::Modifier(_)
+            | ScadOp::Tag(_)
+            | ScadOp::BomItem { .. } => children(),
+            ScadOp::Text {
+                text,
+                size,
+                halign,
+                valign,
+                spacing,
+                direction,
+                ..
+            } => {
+                let params = TextParams {
+                    text: text.clone(),
+                    size: *size,
+                    halign: *halign,
+                    valign: *valign,
+                    spacing: *spacing,
+                    direction: *direction,
+                    ..Default::default()
+                };
+                let (min, max) = params.measure();
+                Some((min.as_pt3(0.0), max.as_pt3(0.0)))
+            }
+            ScadOp::Import { .. }
+            | ScadOp::Surface { .. }
+            | ScadOp::Call { .. }
+            | ScadOp::Comment(_)
+            | ScadOp::Raw(_) => None,
+        }
+    }
+}
@@ -0,0 +1,389 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Boolean operations on 2D polygons.
+//!
+//! The edges of both polygon sets are split where they cross, each piece is
+//! classified as inside or outside the other set and the pieces that bound the
+//! result are linked back together into closed polygons.
+
+use {
+    crate::{Pt2, Pt2s},
+    std::collections::HashMap,
+};
+
+const EPSILON: f64 = 1.0e-9;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Location {
+    Inside,
+    Outside,
+    /// On the boundary of the other set going the same direction.
+    Same,
+    /// On the boundary of the other set going the opposite direction.
+    Opposite,
+}
+
+#[derive(Clone, Copy)]
+struct Edge {
+    start: Pt2,
+    end: Pt2,
+}
+
+type Key = (i64, i64);
+
+fn key(p: Pt2) -> Key {
+    (
+        (p.x / EPSILON).round() as i64,
+        (p.y / EPSILON).round() as i64,
+    )
+}
+
+pub(crate) fn cross(a: Pt2, b: Pt2) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Twice the signed area, positive for counter clockwise polygons.
+pub(crate) fn signed_area(points: &[Pt2]) -> f64 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        area += cross(points[i], points[(i + 1) % points.len()]);
+    }
+    area
+}
+
+/// Even-odd point in polygon test over a set of polygons.
+pub(crate) fn contains(polygons: &[Vec<Pt2>], p: Pt2) -> bool {
+    let mut inside = false;
+    for polygon in polygons.iter() {
+        let mut j = polygon.len() - 1;
+        for i in 0..polygon.len() {
+            let (a, b) = (polygon[i], polygon[j]);
+            if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+            j = i;
+        }
+    }
+    inside
+}
+
+/// A point of polygon for testing if it is inside other.
+///
+/// Polygons that touch share points which are on the boundary of both so a
+/// point that isn't shared is used when there is one.
+pub(crate) fn probe(polygon: &[Pt2], other: &[Pt2]) -> Pt2 {
+    let shared: std::collections::HashSet<Key> = other.iter().map(|p| key(*p)).collect();
+    polygon
+        .iter()
+        .copied()
+        .find(|p| !shared.contains(&key(*p)))
+        .unwrap_or_else(|| polygon[0].lerp(polygon[1], 0.5))
+}
+
+/// Remove repeated points and orient outlines clockwise and holes counter clockwise.
+fn normalize(polygons: &[Pt2s]) -> Vec<Vec<Pt2>> {
+    let mut result: Vec<Vec<Pt2>> = Vec::with_capacity(polygons.len());
+    for polygon in polygons.iter() {
+        let mut points: Vec<Pt2> = Vec::with_capacity(polygon.len());
+        for p in polygon.iter() {
+            if points.last().map_or(true, |last| key(*last) != key(*p)) {
+                points.push(*p);
+            }
+        }
+        while points.len() > 1 && key(points[0]) == key(points[points.len() - 1]) {
+            points.pop();
+        }
+        if points.len() >= 3 && signed_area(&points).abs() > EPSILON {
+            result.push(points);
+        }
+    }
+    for i in 0..result.len() {
+        let depth = (0..result.len())
+            .filter(|j| *j != i && contains(&result[*j..*j + 1], probe(&result[i], &result[*j])))
+            .count();
+        let clockwise = signed_area(&result[i]) < 0.0;
+        if clockwise != (depth % 2 == 0) {
+            result[i].reverse();
+        }
+    }
+    result
+}
+
+fn edges(polygons: &[Vec<Pt2>]) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for polygon in polygons.iter() {
+        for i in 0..polygon.len() {
+            edges.push(Edge {
+                start: polygon[i],
+                end: polygon[(i + 1) % polygon.len()],
+            });
+        }
+    }
+    edges
+}
+
+/// Split the edges of a and b at every point where they cross or touch.
+fn split(a: &[Edge], b: &[Edge]) -> (Vec<Edge>, Vec<Edge>) {
+    let mut a_splits: Vec<Vec<(f64, Pt2)>> = vec![Vec::new(); a.len()];
+    let mut b_splits: Vec<Vec<(f64, Pt2)>> = vec![Vec::new(); b.len()];
+    let inner = |t: f64| t > EPSILON && t < 1.0 - EPSILON;
+    for (i, ea) in a.iter().enumerate() {
+        let d1 = ea.end - ea.start;
+        for (j, eb) in b.iter().enumerate() {
+            let d2 = eb.end - eb.start;
+            let denom = cross(d1, d2);
+            let offset = eb.start - ea.start;
+            if denom.abs() > EPSILON * d1.len() * d2.len() {
+                let t = cross(offset, d2) / denom;
+                let u = cross(offset, d1) / denom;
+                if !(-EPSILON..=1.0 + EPSILON).contains(&t)
+                    || !(-EPSILON..=1.0 + EPSILON).contains(&u)
+                {
+                    continue;
+                }
+                let p = if t <= EPSILON {
+                    ea.start
+                } else if t >= 1.0 - EPSILON {
+                    ea.end
+                } else if u <= EPSILON {
+                    eb.start
+                } else if u >= 1.0 - EPSILON {
+                    eb.end
+                } else {
+                    ea.start + d1 * t
+                };
+                if inner(t) {
+                    a_splits[i].push((t, p));
+                }
+                if inner(u) {
+                    b_splits[j].push((u, p));
+                }
+            } else if cross(offset, d1).abs() <= EPSILON * d1.len() {
+                // collinear, split each edge at the ends of the other
+                for p in [eb.start, eb.end] {
+                    let t = (p - ea.start).dot(d1) / d1.len2();
+                    if inner(t) {
+                        a_splits[i].push((t, p));
+                    }
+                }
+                for p in [ea.start, ea.end] {
+                    let u = (p - eb.start).dot(d2) / d2.len2();
+                    if inner(u) {
+                        b_splits[j].push((u, p));
+                    }
+                }
+            }
+        }
+    }
+    (apply_splits(a, a_splits), apply_splits(b, b_splits))
+}
+
+fn apply_splits(edges: &[Edge], mut splits: Vec<Vec<(f64, Pt2)>>) -> Vec<Edge> {
+    let mut result = Vec::with_capacity(edges.len());
+    for (edge, splits) in edges.iter().zip(splits.iter_mut()) {
+        splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut start = edge.start;
+        for (_, p) in splits.iter() {
+            if key(*p) != key(start) {
+                result.push(Edge { start, end: *p });
+                start = *p;
+            }
+        }
+        if key(edge.end) != key(start) {
+            result.push(Edge {
+                start,
+                end: edge.end,
+            });
+        }
+    }
+    result
+}
+
+/// Locate each edge relative to the other polygon set.
+fn locate(edges: &[Edge], other: &[Vec<Pt2>], other_edges: &[Edge]) -> Vec<Location> {
+    let mut boundary: HashMap<(Key, Key), bool> = HashMap::new();
+    for e in other_edges.iter() {
+        boundary.insert((key(e.start), key(e.end)), true);
+        boundary.entry((key(e.end), key(e.start))).or_insert(false);
+    }
+    edges
+        .iter()
+        .map(|e| match boundary.get(&(key(e.start), key(e.end))) {
+            Some(true) => Location::Same,
+            Some(false) => Location::Opposite,
+            None => {
+                if contains(other, e.start.lerp(e.end, 0.5)) {
+                    Location::Inside
+                } else {
+                    Location::Outside
+                }
+            }
+        })
+        .collect()
+}
+
+/// Link edges into closed polygons, dropping collinear points.
+fn link(edges: Vec<Edge>) -> Vec<Pt2s> {
+    let mut starts: HashMap<Key, Vec<usize>> = HashMap::new();
+    for (i, e) in edges.iter().enumerate() {
+        starts.entry(key(e.start)).or_default().push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut polygons = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        let mut points = Vec::new();
+        let mut current = first;
+        loop {
+            used[current] = true;
+            points.push(edges[current].start);
+            let end = key(edges[current].end);
+            if end == key(edges[first].start) {
+                break;
+            }
+            match starts
+                .get(&end)
+                .and_then(|next| next.iter().find(|i| !used[**i]))
+            {
+                Some(next) => current = *next,
+                None => {
+                    points.clear();
+                    break;
+                }
+            }
+        }
+        let mut simplified = Pt2s::with_capacity(points.len());
+        for i in 0..points.len() {
+            let prev = points[(i + points.len() - 1) % points.len()];
+            let next = points[(i + 1) % points.len()];
+            let p = points[i];
+            if cross(p - prev, next - p).abs() > EPSILON * (p - prev).len() * (next - p).len() {
+                simplified.push(p);
+            }
+        }
+        if simplified.len() >= 3 {
+            polygons.push(simplified);
+        }
+    }
+    polygons
+}
+
+fn boolean(a: &[Pt2s], b: &[Pt2s], op: Op) -> Vec<Pt2s> {
+    let a = normalize(a);
+    let b = normalize(b);
+    let (a_edges, b_edges) = split(&edges(&a), &edges(&b));
+    let a_locations = locate(&a_edges, &b, &b_edges);
+    let b_locations = locate(&b_edges, &a, &a_edges);
+
+    let mut result = Vec::new();
+    for (e, location) in a_edges.iter().zip(a_locations) {
+        let keep = match op {
+            Op::Union => location == Location::Outside || location == Location::Same,
+            Op::Intersection => location == Location::Inside || location == Location::Same,
+            Op::Difference => location == Location::Outside || location == Location::Opposite,
+        };
+        if keep {
+            result.push(*e);
+        }
+    }
+    for (e, location) in b_edges.iter().zip(b_locations) {
+        match op {
+            Op::Union if location == Location::Outside => result.push(*e),
+            Op::Intersection if location == Location::Inside => result.push(*e),
+            Op::Difference if location == Location::Inside => result.push(Edge {
+                start: e.end,
+                end: e.start,
+            }),
+            _ => {}
+        }
+    }
+    link(result)
+}
+
+/// The union of two sets of polygons.
+///
+/// Polygons inside an odd number of other polygons in the same set are holes.
+/// The result has clockwise outlines and counter clockwise holes.
+///
+/// a: The first set of polygons.
+///
+/// b: The second set of polygons.
+///
+/// return: The polygons of the union.
+pub fn union(a: &[Pt2s], b: &[Pt2s]) -> Vec<Pt2s> {
+    boolean(a, b, Op::Union)
+}
+
+/// The intersection of two sets of polygons.
+///
+/// Polygons inside an odd number of other polygons in the same set are holes.
+/// The result has clockwise outlines and counter clockwise holes.
+///
+/// a: The first set of polygons.
+///
+/// b: The second set of polygons.
+///
+/// return: The polygons of the intersection.
+pub fn intersection(a: &[Pt2s], b: &[Pt2s]) -> Vec<Pt2s> {
+    boolean(a, b, Op::Intersection)
+}
+
+/// The polygons of a with b removed.
+///
+/// Polygons inside an odd number of other polygons in the same set are holes.
+/// The result has clockwise outlines and counter clockwise holes.
+///
+/// a: The polygons to cut.
+///
+/// b: The polygons to remove.
+///
+/// return: The polygons of the difference.
+pub fn difference(a: &[Pt2s], b: &[Pt2s]) -> Vec<Pt2s> {
+    boolean(a, b, Op::Difference)
+}
+
+/// The area covered by exactly one of two sets of polygons.
+///
+/// Polygons inside an odd number of other polygons in the same set are holes.
+/// The result has clockwise outlines and counter clockwise holes.
+///
+/// a: The first set of polygons.
+///
+/// b: The second set of polygons.
+///
+/// return: The polygons of the exclusive or.
+pub fn xor(a: &[Pt2s], b: &[Pt2s]) -> Vec<Pt2s> {
+    let mut result = difference(a, b);
+    result.append(&mut difference(b, a));
+    result
+}
@@ -0,0 +1,288 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Conversions between the ways of giving a color and palettes of distinct colors.
+
+use crate::prelude::*;
+
+impl ScadColor {
+    /// The color as 0xRRGGBB, the values of the CSS color of the same name.
+    pub fn rgb(self) -> u32 {
+        match self {
+            ScadColor::Lavender => 0xE6E6FA,
+            ScadColor::Thistle => 0xD8BFD8,
+            ScadColor::Plum => 0xDDA0DD,
+            ScadColor::Violet => 0xEE82EE,
+            ScadColor::Orchid => 0xDA70D6,
+            ScadColor::Fuchsia => 0xFF00FF,
+            ScadColor::Magenta => 0xFF00FF,
+            ScadColor::MediumOrchid => 0xBA55D3,
+            ScadColor::MediumPurple => 0x9370DB,
+            ScadColor::BlueViolet => 0x8A2BE2,
+            ScadColor::DarkViolet => 0x9400D3,
+            ScadColor::DarkOrchid => 0x9932CC,
+            ScadColor::DarkMagenta => 0x8B008B,
+            ScadColor::Purple => 0x800080,
+            ScadColor::Indigo => 0x4B0082,
+            ScadColor::DarkSlateBlue => 0x483D8B,
+            ScadColor::SlateBlue => 0x6A5ACD,
+            ScadColor::MediumSlateBlue => 0x7B68EE,
+            ScadColor::Pink => 0xFFC0CB,
+            ScadColor::LightPink => 0xFFB6C1,
+            ScadColor::HotPink => 0xFF69B4,
+            ScadColor::DeepPink => 0xFF1493,
+            ScadColor::MediumVioletRed => 0xC71585,
+            ScadColor::PaleVioletRed => 0xDB7093,
+            ScadColor::Aqua => 0x00FFFF,
+            ScadColor::Cyan => 0x00FFFF,
+            ScadColor::LightCyan => 0xE0FFFF,
+            ScadColor::PaleTurquoise => 0xAFEEEE,
+            ScadColor::Aquamarine => 0x7FFFD4,
+            ScadColor::Turquoise => 0x40E0D0,
+            ScadColor::MediumTurquoise => 0x48D1CC,
+            ScadColor::DarkTurquoise => 0x00CED1,
+            ScadColor::CadetBlue => 0x5F9EA0,
+            ScadColor::SteelBlue => 0x4682B4,
+            ScadColor::LightSteelBlue => 0xB0C4DE,
+            ScadColor::PowderBlue => 0xB0E0E6,
+            ScadColor::LightBlue => 0xADD8E6,
+            ScadColor::SkyBlue => 0x87CEEB,
+            ScadColor::LightSkyBlue => 0x87CEFA,
+            ScadColor::DeepSkyBlue => 0x00BFFF,
+            ScadColor::DodgerBlue => 0x1E90FF,
+            ScadColor::CornflowerBlue => 0x6495ED,
+            ScadColor::RoyalBlue => 0x4169E1,
+            ScadColor::Blue => 0x0000FF,
+            ScadColor::MediumBlue => 0x0000CD,
+            ScadColor::DarkBlue => 0x00008B,
+            ScadColor::Navy => 0x000080,
+            ScadColor::MidnightBlue => 0x191970,
+            ScadColor::IndianRed => 0xCD5C5C,
+            ScadColor::LightCoral => 0xF08080,
+            ScadColor::Salmon => 0xFA8072,
+            ScadColor::DarkSalmon => 0xE9967A,
+            ScadColor::LightSalmon => 0xFFA07A,
+            ScadColor::Red => 0xFF0000,
+            ScadColor::Crimson => 0xDC143C,
+            ScadColor::FireBrick => 0xB22222,
+            ScadColor::DarkRed => 0x8B0000,
+            ScadColor::GreenYellow => 0xADFF2F,
+            ScadColor::Chartreuse => 0x7FFF00,
+            ScadColor::LawnGreen => 0x7CFC00,
+            ScadColor::Lime => 0x00FF00,
+            ScadColor::LimeGreen => 0x32CD32,
+            ScadColor::PaleGreen => 0x98FB98,
+            ScadColor::LightGreen => 0x90EE90,
+            ScadColor::MediumSpringGreen => 0x00FA9A,
+            ScadColor::SpringGreen => 0x00FF7F,
+            ScadColor::MediumSeaGreen => 0x3CB371,
+            ScadColor::SeaGreen => 0x2E8B57,
+            ScadColor::ForestGreen => 0x228B22,
+            ScadColor::Green => 0x008000,
+            ScadColor::DarkGreen => 0x006400,
+            ScadColor::YellowGreen => 0x9ACD32,
+            ScadColor::OliveDrab => 0x6B8E23,
+            ScadColor::Olive => 0x808000,
+            ScadColor::DarkOliveGreen => 0x556B2F,
+            ScadColor::MediumAquamarine => 0x66CDAA,
+            ScadColor::DarkSeaGreen => 0x8FBC8F,
+            ScadColor::LightSeaGreen => 0x20B2AA,
+            ScadColor::DarkCyan => 0x008B8B,
+            ScadColor::Teal => 0x008080,
+            ScadColor::Coral => 0xFF7F50,
+            ScadColor::Tomato => 0xFF6347,
+            ScadColor::OrangeRed => 0xFF4500,
+            ScadColor::DarkOrange => 0xFF8C00,
+            ScadColor::Orange => 0xFFA500,
+            ScadColor::Gold => 0xFFD700,
+            ScadColor::Yellow => 0xFFFF00,
+            ScadColor::LightYellow => 0xFFFFE0,
+            ScadColor::LemonChiffon => 0xFFFACD,
+            ScadColor::LightGoldenrodYellow => 0xFAFAD2,
+            ScadColor::PapayaWhip => 0xFFEFD5,
+            ScadColor::Moccasin => 0xFFE4B5,
+            ScadColor::PeachPuff => 0xFFDAB9,
+            ScadColor::PaleGoldenrod => 0xEEE8AA,
+            ScadColor::Khaki => 0xF0E68C,
+            ScadColor::DarkKhaki => 0xBDB76B,
+            // Not a CSS color, given the color of Brown.
+            ScadColor::Browns => 0xA52A2A,
+            ScadColor::Cornsilk => 0xFFF8DC,
+            ScadColor::BlanchedAlmond => 0xFFEBCD,
+            ScadColor::Bisque => 0xFFE4C4,
+            ScadColor::NavajoWhite => 0xFFDEAD,
+            ScadColor::Wheat => 0xF5DEB3,
+            ScadColor::BurlyWood => 0xDEB887,
+            ScadColor::Tan => 0xD2B48C,
+            ScadColor::RosyBrown => 0xBC8F8F,
+            ScadColor::SandyBrown => 0xF4A460,
+            ScadColor::Goldenrod => 0xDAA520,
+            ScadColor::DarkGoldenrod => 0xB8860B,
+            ScadColor::Peru => 0xCD853F,
+            ScadColor::Chocolate => 0xD2691E,
+            ScadColor::SaddleBrown => 0x8B4513,
+            ScadColor::Sienna => 0xA0522D,
+            ScadColor::Brown => 0xA52A2A,
+            ScadColor::Maroon => 0x800000,
+            ScadColor::White => 0xFFFFFF,
+            ScadColor::Snow => 0xFFFAFA,
+            ScadColor::Honeydew => 0xF0FFF0,
+            ScadColor::MintCream => 0xF5FFFA,
+            ScadColor::Azure => 0xF0FFFF,
+            ScadColor::AliceBlue => 0xF0F8FF,
+            ScadColor::GhostWhite => 0xF8F8FF,
+            ScadColor::WhiteSmoke => 0xF5F5F5,
+            ScadColor::Seashell => 0xFFF5EE,
+            ScadColor::Beige => 0xF5F5DC,
+            ScadColor::OldLace => 0xFDF5E6,
+            ScadColor::FloralWhite => 0xFFFAF0,
+            ScadColor::Ivory => 0xFFFFF0,
+            ScadColor::AntiqueWhite => 0xFAEBD7,
+            ScadColor::Linen => 0xFAF0E6,
+            ScadColor::LavenderBlush => 0xFFF0F5,
+            ScadColor::MistyRose => 0xFFE4E1,
+            ScadColor::Gainsboro => 0xDCDCDC,
+            ScadColor::LightGrey => 0xD3D3D3,
+            ScadColor::Silver => 0xC0C0C0,
+            ScadColor::DarkGray => 0xA9A9A9,
+            ScadColor::Gray => 0x808080,
+            ScadColor::DimGray => 0x696969,
+            ScadColor::LightSlateGray => 0x778899,
+            ScadColor::SlateGray => 0x708090,
+            ScadColor::DarkSlateGray => 0x2F4F4F,
+            ScadColor::Black => 0x000000,
+        }
+    }
+
+    /// The color as red, green, blue and alpha from 0 to 1.
+    ///
+    /// alpha: The alpha of the color.
+    ///
+    /// return: The color.
+    pub fn rgba(self, alpha: f64) -> Pt4 {
+        let rgb = self.rgb();
+        Pt4::new(
+            (rgb >> 16 & 0xFF) as f64 / 255.0,
+            (rgb >> 8 & 0xFF) as f64 / 255.0,
+            (rgb & 0xFF) as f64 / 255.0,
+            alpha,
+        )
+    }
+}
+
+impl From<ScadColor> for Pt4 {
+    fn from(color: ScadColor) -> Self {
+        color.rgba(1.0)
+    }
+}
+
+/// Parses a #rgb, #rgba, #rrggbb or #rrggbbaa hex color.
+///
+/// hex: The color, the # is optional.
+///
+/// return: The color as red, green, blue and alpha from 0 to 1 or None when
+/// the color is not valid.
+pub fn hex_to_rgba(hex: &str) -> Option<Pt4> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    let digits = hex
+        .chars()
+        .map(|c| c.to_digit(16))
+        .collect::<Option<Vec<u32>>>()?;
+    let channels: Vec<u32> = match digits.len() {
+        3 | 4 => digits.iter().map(|d| d * 17).collect(),
+        6 | 8 => digits.chunks(2).map(|d| d[0] * 16 + d[1]).collect(),
+        _ => return None,
+    };
+    let channel = |i: usize| channels.get(i).map_or(1.0, |c| *c as f64 / 255.0);
+    Some(Pt4::new(channel(0), channel(1), channel(2), channel(3)))
+}
+
+/// Distinct colors for telling parts apart.
+///
+/// The hues are a golden angle apart so neighbors in the list are never close
+/// and the value alternates to separate colors that wrap around to similar hues.
+///
+/// count: The number of colors.
+///
+/// return: The colors as red, green, blue and alpha from 0 to 1.
+pub fn color_palette(count: usize) -> Vec<Pt4> {
+    (0..count)
+        .map(|i| {
+            let v = if i % 2 == 0 { 0.95 } else { 0.75 };
+            Pt4::from_hsv(i as f64 * 137.507_764, 0.7, v, 1.0)
+        })
+        .collect()
+}
+
+impl Scad {
+    /// The color of a color node as red, green, blue and alpha from 0 to 1.
+    ///
+    /// return: The color or None when the node is not a color or the hex code
+    /// is not valid.
+    pub fn color_rgba(&self) -> Option<Pt4> {
+        match &self.op {
+            ScadOp::Color {
+                rgba,
+                color,
+                hex,
+                alpha,
+            } => {
+                if let Some(rgba) = rgba {
+                    Some(*rgba)
+                } else if let Some(color) = color {
+                    Some(color.rgba(alpha.unwrap_or(1.0)))
+                } else {
+                    hex.as_deref().and_then(hex_to_rgba)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Colors each child of a union a different color.
+    ///
+    /// Use it on an assembly to see where the parts are. Anything other than a
+    /// union is colored as a single part.
+    ///
+    /// return: The union with each child wrapped in a color from color_palette.
+    pub fn color_parts(self) -> Self {
+        match self.op {
+            ScadOp::Union => {
+                let palette = color_palette(self.children.len());
+                Scad {
+                    op: ScadOp::Union,
+                    children: self
+                        .children
+                        .into_iter()
+                        .zip(palette)
+                        .map(|(child, c)| color!([c.x, c.y, c.z, c.w], child;))
+                        .collect(),
+                }
+            }
+            _ => {
+                let c = color_palette(1)[0];
+                color!([c.x, c.y, c.z, c.w], self;)
+            }
+        }
+    }
+}
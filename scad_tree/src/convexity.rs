@@ -0,0 +1,171 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Estimates the convexity parameter of OpenSCAD operations.
+//!
+//! The convexity is the most times a ray can enter the object. We count the
+//! crossings of axis aligned rays with the outlines or faces and halve them.
+
+use crate::prelude::*;
+
+/// The number of rays along each axis when sampling a polyhedron.
+const RAYS_PER_AXIS: usize = 8;
+
+/// The most times a horizontal or vertical line enters the outlines.
+fn outline_convexity(outlines: &[Pt2s]) -> u64 {
+    let mut best = 1;
+    for axis in 0..2 {
+        let coord = |p: &Pt2| if axis == 0 { p.y } else { p.x };
+        let mut values: Vec<f64> = outlines.iter().flat_map(|o| o.iter().map(coord)).collect();
+        values.sort_by(|a, b| a.total_cmp(b));
+        values.dedup();
+        for pair in values.windows(2) {
+            let c = (pair[0] + pair[1]) / 2.0;
+            let mut crossings = 0;
+            for outline in outlines.iter() {
+                for i in 0..outline.len() {
+                    let a = coord(&outline[i]);
+                    let b = coord(&outline[(i + 1) % outline.len()]);
+                    if (a <= c) != (b <= c) {
+                        crossings += 1;
+                    }
+                }
+            }
+            best = best.max(crossings / 2);
+        }
+    }
+    best
+}
+
+/// The most times a ray along an axis enters the polyhedron.
+fn polyhedron_convexity(points: &Pt3s, faces: &Faces) -> u64 {
+    if points.is_empty() {
+        return 1;
+    }
+    let mut min = points[0];
+    let mut max = points[0];
+    for p in points.iter() {
+        min = Pt3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Pt3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+    }
+    let get = |p: Pt3, axis: usize| [p.x, p.y, p.z][axis];
+    let mut best = 1;
+    for axis in 0..3 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        for i in 0..RAYS_PER_AXIS {
+            for j in 0..RAYS_PER_AXIS {
+                // odd fractions keep the rays off the vertices of regular meshes
+                let fu = (i as f64 + 0.5 + 0.0137) / RAYS_PER_AXIS as f64;
+                let fv = (j as f64 + 0.5 + 0.0291) / RAYS_PER_AXIS as f64;
+                let ru = get(min, u) + (get(max, u) - get(min, u)) * fu;
+                let rv = get(min, v) + (get(max, v) - get(min, v)) * fv;
+                let mut crossings = 0;
+                for face in faces.iter() {
+                    for k in 1..face.len().saturating_sub(1) {
+                        let tri = [face[0], face[k], face[k + 1]]
+                            .map(|index| points[index as usize])
+                            .map(|p| Pt2::new(get(p, u), get(p, v)));
+                        if triangle_contains(tri, Pt2::new(ru, rv)) {
+                            crossings += 1;
+                        }
+                    }
+                }
+                best = best.max(crossings / 2);
+            }
+        }
+    }
+    best
+}
+
+fn triangle_contains(tri: [Pt2; 3], p: Pt2) -> bool {
+    let side = |a: Pt2, b: Pt2| (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    let d0 = side(tri[0], tri[1]);
+    let d1 = side(tri[1], tri[2]);
+    let d2 = side(tri[2], tri[0]);
+    (d0 > 0.0 && d1 > 0.0 && d2 > 0.0) || (d0 < 0.0 && d1 < 0.0 && d2 < 0.0)
+}
+
+fn polygon_outlines(points: &Pt2s, paths: &Option<Paths>) -> Vec<Pt2s> {
+    match paths {
+        Some(paths) => paths
+            .iter()
+            .map(|path| {
+                Pt2s::from_pt2s(
+                    path.iter()
+                        .filter_map(|&i| points.get(i as usize).copied())
+                        .collect(),
+                )
+            })
+            .collect(),
+        None => vec![points.clone()],
+    }
+}
+
+impl Scad {
+    /// Estimates the convexity of the tree.
+    ///
+    /// The convexity is the number of times a ray can enter the object,
+    /// OpenSCAD needs it to preview complicated shapes without artifacts.
+    /// The estimate is generous for unions and differences since a ray may
+    /// pass through every child.
+    ///
+    /// return: The convexity, at least 1.
+    pub fn estimate_convexity(&self) -> u64 {
+        let children = || self.children.iter().map(|child| child.estimate_convexity());
+        let estimate = match &self.op {
+            ScadOp::Polygon { points, paths, .. } => {
+                outline_convexity(&polygon_outlines(points, paths))
+            }
+            ScadOp::Polyhedron { points, faces, .. } => polyhedron_convexity(points, faces),
+            ScadOp::Union | ScadOp::Difference | ScadOp::Minkowski { .. } => children().sum(),
+            ScadOp::Intersection => children().min().unwrap_or(1),
+            ScadOp::Hull => 1,
+            // a ray through the axis crosses the profile on both sides
+            ScadOp::RotateExtrude { .. } => children().sum::<u64>() * 2,
+            ScadOp::IfPreview => children().max().unwrap_or(1),
+            _ => children().sum(),
+        };
+        estimate.max(1)
+    }
+
+    /// Raises the convexity of every operation in the tree to its estimate.
+    ///
+    /// Convexities already larger than the estimate are kept.
+    pub fn infer_convexity(&mut self) -> &mut Self {
+        for child in self.children.iter_mut() {
+            child.infer_convexity();
+        }
+        let estimate = self.estimate_convexity();
+        match &mut self.op {
+            ScadOp::Polygon { convexity, .. }
+            | ScadOp::Polyhedron { convexity, .. }
+            | ScadOp::LinearExtrude { convexity, .. }
+            | ScadOp::RotateExtrude { convexity, .. }
+            | ScadOp::Resize { convexity, .. }
+            | ScadOp::Minkowski { convexity }
+            | ScadOp::Render { convexity } => *convexity = (*convexity).max(estimate),
+            _ => (),
+        }
+        self
+    }
+}
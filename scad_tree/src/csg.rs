@@ -0,0 +1,432 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Mesh boolean operations using binary space partitioning trees.
+//!
+//! This is a port of the algorithm used by csg.js
+//! <https://github.com/evanw/csg.js>. Polygons are kept in counter clockwise
+//! order internally and converted back to clockwise order on output.
+
+use {
+    crate::{Faces, Indices, Polyhedron, Pt3, Pt3s},
+    std::collections::{HashMap, HashSet},
+};
+
+const EPSILON: f64 = 1.0e-5;
+
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+#[derive(Clone, Copy)]
+struct Plane {
+    normal: Pt3,
+    w: f64,
+}
+
+impl Plane {
+    fn from_points(a: Pt3, b: Pt3, c: Pt3) -> Option<Self> {
+        let n = (b - a).cross(c - a);
+        if n.len() < 1.0e-12 {
+            return None;
+        }
+        let normal = n.normalized();
+        Some(Self {
+            normal,
+            w: normal.dot(a),
+        })
+    }
+
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+
+    /// Split polygon by this plane if needed, then put the polygon or
+    /// polygon fragments in the appropriate lists.
+    fn split_polygon(
+        &self,
+        polygon: Polygon,
+        coplanar_front: &mut Vec<Polygon>,
+        coplanar_back: &mut Vec<Polygon>,
+        front: &mut Vec<Polygon>,
+        back: &mut Vec<Polygon>,
+    ) {
+        let mut polygon_type = COPLANAR;
+        let mut types = Vec::with_capacity(polygon.vertices.len());
+        for v in polygon.vertices.iter() {
+            let t = self.normal.dot(*v) - self.w;
+            let vertex_type = if t < -EPSILON {
+                BACK
+            } else if t > EPSILON {
+                FRONT
+            } else {
+                COPLANAR
+            };
+            polygon_type |= vertex_type;
+            types.push(vertex_type);
+        }
+
+        match polygon_type {
+            COPLANAR => {
+                if self.normal.dot(polygon.plane.normal) > 0.0 {
+                    coplanar_front.push(polygon);
+                } else {
+                    coplanar_back.push(polygon);
+                }
+            }
+            FRONT => front.push(polygon),
+            BACK => back.push(polygon),
+            _ => {
+                let mut f = Vec::new();
+                let mut b = Vec::new();
+                let n = polygon.vertices.len();
+                for i in 0..n {
+                    let j = (i + 1) % n;
+                    let ti = types[i];
+                    let tj = types[j];
+                    let vi = polygon.vertices[i];
+                    let vj = polygon.vertices[j];
+                    if ti != BACK {
+                        f.push(vi);
+                    }
+                    if ti != FRONT {
+                        b.push(vi);
+                    }
+                    if (ti | tj) == SPANNING {
+                        let t = (self.w - self.normal.dot(vi)) / self.normal.dot(vj - vi);
+                        let v = vi.lerp(vj, t);
+                        f.push(v);
+                        b.push(v);
+                    }
+                }
+                if f.len() >= 3 {
+                    front.push(Polygon {
+                        vertices: f,
+                        plane: polygon.plane,
+                        group: polygon.group,
+                    });
+                }
+                if b.len() >= 3 {
+                    back.push(Polygon {
+                        vertices: b,
+                        plane: polygon.plane,
+                        group: polygon.group,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Polygon {
+    vertices: Vec<Pt3>,
+    plane: Plane,
+    group: u64,
+}
+
+impl Polygon {
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane.flip();
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    plane: Option<Plane>,
+    front: Option<Box<Node>>,
+    back: Option<Box<Node>>,
+    polygons: Vec<Polygon>,
+}
+
+impl Node {
+    fn new(polygons: Vec<Polygon>) -> Self {
+        let mut node = Self::default();
+        node.build(polygons);
+        node
+    }
+
+    /// Convert solid space to empty space and empty space to solid space.
+    fn invert(&mut self) {
+        for polygon in self.polygons.iter_mut() {
+            polygon.flip();
+        }
+        if let Some(plane) = self.plane.as_mut() {
+            plane.flip();
+        }
+        if let Some(front) = self.front.as_mut() {
+            front.invert();
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Remove all polygons in the list that are inside this tree.
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let plane = match self.plane {
+            Some(plane) => plane,
+            None => return polygons,
+        };
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for polygon in polygons {
+            let mut coplanar_front = Vec::new();
+            let mut coplanar_back = Vec::new();
+            plane.split_polygon(
+                polygon,
+                &mut coplanar_front,
+                &mut coplanar_back,
+                &mut front,
+                &mut back,
+            );
+            front.append(&mut coplanar_front);
+            back.append(&mut coplanar_back);
+        }
+        let mut front = match self.front.as_ref() {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        let mut back = match self.back.as_ref() {
+            Some(node) => node.clip_polygons(back),
+            None => Vec::new(),
+        };
+        front.append(&mut back);
+        front
+    }
+
+    /// Remove all polygons in this tree that are inside the other tree.
+    fn clip_to(&mut self, other: &Node) {
+        self.polygons = other.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(front) = self.front.as_mut() {
+            front.clip_to(other);
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.clip_to(other);
+        }
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        let mut polygons = self.polygons.clone();
+        if let Some(front) = self.front.as_ref() {
+            polygons.append(&mut front.all_polygons());
+        }
+        if let Some(back) = self.back.as_ref() {
+            polygons.append(&mut back.all_polygons());
+        }
+        polygons
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        if polygons.is_empty() {
+            return;
+        }
+        let plane = *self.plane.get_or_insert(polygons[0].plane);
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for polygon in polygons {
+            let mut coplanar_front = Vec::new();
+            let mut coplanar_back = Vec::new();
+            plane.split_polygon(
+                polygon,
+                &mut coplanar_front,
+                &mut coplanar_back,
+                &mut front,
+                &mut back,
+            );
+            self.polygons.append(&mut coplanar_front);
+            self.polygons.append(&mut coplanar_back);
+        }
+        if !front.is_empty() {
+            self.front.get_or_insert_with(Default::default).build(front);
+        }
+        if !back.is_empty() {
+            self.back.get_or_insert_with(Default::default).build(back);
+        }
+    }
+}
+
+/// Convert the clockwise faces of a polyhedron to counter clockwise triangles.
+fn to_polygons(polyhedron: &Polyhedron) -> Vec<Polygon> {
+    let mut polygons = Vec::with_capacity(polyhedron.faces.len());
+    for (i, face) in polyhedron.faces.iter().enumerate() {
+        let group = polyhedron.face_group(i);
+        let vertices: Vec<Pt3> = face
+            .iter()
+            .rev()
+            .map(|i| polyhedron.points[*i as usize])
+            .collect();
+        for j in 1..vertices.len().saturating_sub(1) {
+            let (a, b, c) = (vertices[0], vertices[j], vertices[j + 1]);
+            if let Some(plane) = Plane::from_points(a, b, c) {
+                polygons.push(Polygon {
+                    vertices: vec![a, b, c],
+                    plane,
+                    group,
+                });
+            }
+        }
+    }
+    polygons
+}
+
+/// Convert counter clockwise polygons to a polyhedron with clockwise faces,
+/// merging vertices that are within EPSILON of each other.
+fn from_polygons(polygons: Vec<Polygon>, grouped: bool) -> Polyhedron {
+    let mut points = Pt3s::new();
+    let mut faces = Faces::with_capacity(polygons.len());
+    let mut face_groups = Vec::new();
+    let mut lookup: HashMap<(i64, i64, i64), u64> = HashMap::new();
+    for polygon in polygons {
+        let mut face = Indices::new();
+        for v in polygon.vertices.iter().rev() {
+            let key = (
+                (v.x / EPSILON).round() as i64,
+                (v.y / EPSILON).round() as i64,
+                (v.z / EPSILON).round() as i64,
+            );
+            let index = *lookup.entry(key).or_insert_with(|| {
+                points.push(*v);
+                points.len() as u64 - 1
+            });
+            if face.last() != Some(&index) && face.first() != Some(&index) {
+                face.push(index);
+            }
+        }
+        if face.len() >= 3 {
+            faces.push(face);
+            if grouped {
+                face_groups.push(polygon.group);
+            }
+        }
+    }
+    fix_t_junctions(&points, &mut faces);
+    let mut polyhedron = Polyhedron::new(points, faces);
+    polyhedron.face_groups = face_groups;
+    polyhedron
+}
+
+/// Splitting leaves vertices that lie on the edges of neighboring faces,
+/// insert those vertices into the edges so the mesh is closed.
+fn fix_t_junctions(points: &Pt3s, faces: &mut Faces) {
+    let mut edges = HashSet::new();
+    for face in faces.iter() {
+        for i in 0..face.len() {
+            edges.insert((face[i], face[(i + 1) % face.len()]));
+        }
+    }
+    let open: Vec<(u64, u64)> = edges
+        .iter()
+        .filter(|(a, b)| !edges.contains(&(*b, *a)))
+        .copied()
+        .collect();
+    if open.is_empty() {
+        return;
+    }
+    let mut candidates: Vec<u64> = open.iter().flat_map(|(a, b)| [*a, *b]).collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    let open: HashSet<(u64, u64)> = open.into_iter().collect();
+
+    for face in faces.iter_mut() {
+        let mut fixed = Indices::new();
+        for i in 0..face.len() {
+            let a = face[i];
+            let b = face[(i + 1) % face.len()];
+            fixed.push(a);
+            if !open.contains(&(a, b)) {
+                continue;
+            }
+            let pa = points[a as usize];
+            let ab = points[b as usize] - pa;
+            let ab_len2 = ab.len2();
+            let mut on_edge = Vec::new();
+            for c in candidates.iter() {
+                if *c == a || *c == b {
+                    continue;
+                }
+                let ac = points[*c as usize] - pa;
+                let t = ac.dot(ab) / ab_len2;
+                if t > 0.0 && t < 1.0 && (ac - ab * t).len() < EPSILON {
+                    on_edge.push((t, *c));
+                }
+            }
+            on_edge.sort_by(|x, y| x.0.total_cmp(&y.0));
+            fixed.extend(on_edge.iter().map(|(_, c)| *c));
+        }
+        *face = fixed;
+    }
+}
+
+fn finish(a: &Polyhedron, b: &Polyhedron, polygons: Vec<Polygon>) -> Polyhedron {
+    let grouped = !a.face_groups.is_empty() || !b.face_groups.is_empty();
+    let mut result = from_polygons(polygons, grouped);
+    result.group_colors = a.group_colors.clone();
+    for (group, color) in b.group_colors.iter() {
+        result.group_colors.entry(*group).or_insert(*color);
+    }
+    result
+}
+
+pub(crate) fn union(a: &Polyhedron, b: &Polyhedron) -> Polyhedron {
+    let mut na = Node::new(to_polygons(a));
+    let mut nb = Node::new(to_polygons(b));
+    na.clip_to(&nb);
+    nb.clip_to(&na);
+    nb.invert();
+    nb.clip_to(&na);
+    nb.invert();
+    na.build(nb.all_polygons());
+    finish(a, b, na.all_polygons())
+}
+
+pub(crate) fn difference(a: &Polyhedron, b: &Polyhedron) -> Polyhedron {
+    let mut na = Node::new(to_polygons(a));
+    let mut nb = Node::new(to_polygons(b));
+    na.invert();
+    na.clip_to(&nb);
+    nb.clip_to(&na);
+    nb.invert();
+    nb.clip_to(&na);
+    nb.invert();
+    na.build(nb.all_polygons());
+    na.invert();
+    finish(a, b, na.all_polygons())
+}
+
+pub(crate) fn intersection(a: &Polyhedron, b: &Polyhedron) -> Polyhedron {
+    let mut na = Node::new(to_polygons(a));
+    let mut nb = Node::new(to_polygons(b));
+    na.invert();
+    nb.clip_to(&na);
+    nb.invert();
+    na.clip_to(&nb);
+    nb.clip_to(&na);
+    na.build(nb.all_polygons());
+    na.invert();
+    finish(a, b, na.all_polygons())
+}
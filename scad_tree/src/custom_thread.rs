@@ -0,0 +1,346 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::{
+    dtan, metric_thread::ThreadFit, prelude::*, thread::tapered_helical_ridge, MM_PER_INCH,
+};
+
+/// The outline of one ridge of a thread.
+///
+/// The flanks can lean by different angles and the crest and root can be
+/// rounded by different radii, e.g. for the buttress like threads of bottles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThreadProfile {
+    /// The distance between neighboring ridges.
+    pub pitch: f64,
+    /// The radial height of a ridge.
+    pub depth: f64,
+    /// The angle from the axis to the flank on the -Z side of a ridge, 90 is square to the axis.
+    pub lower_flank_degrees: f64,
+    /// The angle from the axis to the flank on the +Z side of a ridge, 90 is square to the axis.
+    pub upper_flank_degrees: f64,
+    /// The width of the flat top of a ridge.
+    pub crest_width: f64,
+    /// The radius of the rounding where the flanks meet the crest.
+    pub crest_radius: f64,
+    /// The radius of the rounding where the flanks meet the root.
+    pub root_radius: f64,
+    /// The number of segments in each rounding.
+    pub arc_segments: u64,
+}
+
+impl ThreadProfile {
+    /// Creates a 60 degree profile with a crest an eighth of the pitch wide and
+    /// no rounding.
+    ///
+    /// pitch: The distance between neighboring ridges.
+    ///
+    /// depth: The radial height of a ridge.
+    ///
+    /// return: The profile.
+    pub fn new(pitch: f64, depth: f64) -> Self {
+        Self {
+            pitch,
+            depth,
+            lower_flank_degrees: 60.0,
+            upper_flank_degrees: 60.0,
+            crest_width: pitch / 8.0,
+            crest_radius: 0.0,
+            root_radius: 0.0,
+            arc_segments: 4,
+        }
+    }
+
+    /// The width of a ridge where it meets the root.
+    pub fn base_width(&self) -> f64 {
+        self.crest_width
+            + self.depth
+                * (1.0 / dtan(self.lower_flank_degrees) + 1.0 / dtan(self.upper_flank_degrees))
+    }
+
+    /// The profile of the mating thread, its ridges fill the grooves of this one.
+    ///
+    /// The flanks trade places, the crest is as wide as the gap between the
+    /// ridges of this profile and the crest and root roundings swap.
+    pub fn complement(&self) -> Self {
+        Self {
+            lower_flank_degrees: self.upper_flank_degrees,
+            upper_flank_degrees: self.lower_flank_degrees,
+            crest_width: self.pitch - self.base_width(),
+            crest_radius: self.root_radius,
+            root_radius: self.crest_radius,
+            ..*self
+        }
+    }
+
+    /// The clockwise (radius, z) outline of a ridge for the thread sweep.
+    ///
+    /// The ridge starts at z = 0 and is less than a pitch tall. The root side
+    /// reaches a tenth of the depth into the core so the ridge overlaps it.
+    ///
+    /// root: The radius of the root.
+    ///
+    /// inward: The ridge points toward the axis like an internal thread.
+    ///
+    /// return: The outline.
+    pub fn points(&self, root: f64, inward: bool) -> Pt2s {
+        assert!(self.pitch > 0.0 && self.depth > 0.0 && self.crest_width >= 0.0);
+        assert!(self.lower_flank_degrees > 0.0 && self.lower_flank_degrees <= 90.0);
+        assert!(self.upper_flank_degrees > 0.0 && self.upper_flank_degrees <= 90.0);
+        let gap = self.pitch - self.base_width();
+        assert!(gap > 0.0, "the ridges are wider than the pitch");
+        let sign = if inward { -1.0 } else { 1.0 };
+        let x = |offset: f64| root + sign * offset;
+        let land = if self.root_radius > 0.0 {
+            gap * 0.45
+        } else {
+            0.0
+        };
+        let lower_root = land;
+        let lower_crest = lower_root + self.depth / dtan(self.lower_flank_degrees);
+        let upper_crest = lower_crest + self.crest_width;
+        let upper_root = upper_crest + self.depth / dtan(self.upper_flank_degrees);
+        let top = upper_root + land;
+
+        // (point, rounding radius) around the outline.
+        let mut corners = vec![(Pt2::new(x(-self.depth * 0.1), top), 0.0)];
+        if land > 0.0 {
+            corners.push((Pt2::new(x(0.0), top), 0.0));
+        }
+        corners.push((Pt2::new(x(0.0), upper_root), self.root_radius));
+        corners.push((Pt2::new(x(self.depth), upper_crest), self.crest_radius));
+        if self.crest_width > 0.0 {
+            corners.push((Pt2::new(x(self.depth), lower_crest), self.crest_radius));
+        }
+        corners.push((Pt2::new(x(0.0), lower_root), self.root_radius));
+        if land > 0.0 {
+            corners.push((Pt2::new(x(0.0), 0.0), 0.0));
+        }
+        corners.push((Pt2::new(x(-self.depth * 0.1), 0.0), 0.0));
+
+        let n = corners.len();
+        let mut points = Vec::new();
+        for i in 0..n {
+            let (corner, radius) = corners[i];
+            if radius > 0.0 {
+                // Halfway to the neighbors so the roundings of a side never overlap.
+                let prev = corners[(i + n - 1) % n].0;
+                let next = corners[(i + 1) % n].0;
+                points.extend(
+                    dim2::fillet_between(
+                        (prev + corner) / 2.0,
+                        corner,
+                        (next + corner) / 2.0,
+                        radius,
+                        self.arc_segments,
+                    )
+                    .iter(),
+                );
+            } else {
+                points.push(corner);
+            }
+        }
+        if inward {
+            points.reverse();
+        }
+        Pt2s::from_pt2s(points)
+    }
+}
+
+/// A thread with a custom profile, any number of starts and a taper.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CustomThread {
+    /// The outline of a ridge of the external thread.
+    pub profile: ThreadProfile,
+    /// The major diameter of the external thread at the bottom.
+    pub diameter: f64,
+    /// The length of the thread.
+    pub length: f64,
+    /// The change of the diameter for each unit of length, negative narrows
+    /// toward the top, e.g. -1/16 for pipe threads.
+    pub taper: f64,
+    /// The number of ridges winding around together.
+    pub starts: u64,
+    /// The angle the ridges take to grow to full depth at the bottom.
+    pub lead_in_degrees: f64,
+    /// The angle the ridges take to shrink away at the top.
+    pub lead_out_degrees: f64,
+    /// The number of segments in a full revolution.
+    pub segments: u64,
+    /// lefty tighty?
+    pub left_hand_thread: bool,
+    /// The clearance between the external and internal threads.
+    pub fit: ThreadFit,
+}
+
+impl CustomThread {
+    /// Creates a straight single start right hand thread.
+    ///
+    /// profile: The outline of a ridge of the external thread.
+    ///
+    /// diameter: The major diameter of the external thread.
+    ///
+    /// length: The length of the thread.
+    ///
+    /// return: The thread.
+    pub fn new(profile: ThreadProfile, diameter: f64, length: f64) -> Self {
+        Self {
+            profile,
+            diameter,
+            length,
+            taper: 0.0,
+            starts: 1,
+            lead_in_degrees: 90.0,
+            lead_out_degrees: 90.0,
+            segments: 72,
+            left_hand_thread: false,
+            fit: ThreadFit::Exact,
+        }
+    }
+
+    /// The approximate neck finish of PET soda bottles, 3 starts of a rounded
+    /// buttress thread. Check the dimensions against the PCO-1881 drawing before
+    /// relying on a seal.
+    ///
+    /// return: The thread with a length of 10.
+    pub fn pco_1881() -> Self {
+        Self {
+            starts: 3,
+            lead_in_degrees: 60.0,
+            lead_out_degrees: 60.0,
+            ..Self::new(
+                ThreadProfile {
+                    pitch: 2.7,
+                    depth: 1.18,
+                    lower_flank_degrees: 80.0,
+                    upper_flank_degrees: 60.0,
+                    crest_width: 0.6,
+                    crest_radius: 0.3,
+                    root_radius: 0.4,
+                    arc_segments: 4,
+                },
+                27.43,
+                10.0,
+            )
+        }
+    }
+
+    /// The US garden hose thread, 3/4" with 11.5 threads per inch and 60 degree
+    /// flanks.
+    ///
+    /// return: The thread with a length of 12.
+    pub fn garden_hose() -> Self {
+        let pitch = MM_PER_INCH / 11.5;
+        Self::new(
+            ThreadProfile {
+                root_radius: pitch * 0.144,
+                ..ThreadProfile::new(pitch, pitch * 0.61343)
+            },
+            26.99,
+            12.0,
+        )
+    }
+
+    /// The ridges of one part.
+    ///
+    /// root: The radius of the root at the bottom.
+    ///
+    /// profile: The outline of a ridge.
+    ///
+    /// inward: The ridges point toward the axis.
+    fn ridges(&self, root: f64, profile: &ThreadProfile, inward: bool) -> Vec<Scad> {
+        assert!(self.starts > 0 && self.length > 0.0);
+        let points = profile.points(root, inward);
+        let height = points.iter().fold(0.0f64, |h, p| h.max(p.y));
+        let lead = profile.pitch * self.starts as f64;
+        let convexity = (self.length / profile.pitch) as u64 + 1;
+        let ridge = tapered_helical_ridge(
+            &points,
+            root,
+            self.taper / 2.0,
+            lead,
+            self.length - height,
+            self.segments,
+            self.lead_in_degrees,
+            self.lead_out_degrees,
+            self.left_hand_thread,
+        );
+        (0..self.starts)
+            .map(|start| {
+                let mut ridge = ridge.clone();
+                ridge.rotate_z(360.0 * start as f64 / self.starts as f64);
+                ridge.into_scad_with_convexity(convexity)
+            })
+            .collect()
+    }
+
+    /// Creates the external thread, ridges on a core, standing on the XY plane.
+    ///
+    /// return: The thread.
+    pub fn external(&self) -> Scad {
+        let major = self.diameter - self.fit.clearance() / 2.0;
+        let root = major / 2.0 - self.profile.depth;
+        let mut children = self.ridges(root, &self.profile, false);
+        children.push(cylinder!(
+            h = self.length,
+            r1 = root + 0.0001,
+            r2 = root + 0.0001 + self.taper / 2.0 * self.length,
+            center = false,
+            fn = self.segments
+        ));
+        Scad {
+            op: ScadOp::Union,
+            children,
+        }
+    }
+
+    /// Creates the internal thread, a tube with ridges on the inside, standing
+    /// on the XY plane.
+    ///
+    /// The ridges fill the grooves of the external thread, see
+    /// ThreadProfile::complement.
+    ///
+    /// outer_diameter: The outside diameter of the tube.
+    ///
+    /// return: The thread.
+    pub fn internal(&self, outer_diameter: f64) -> Scad {
+        let root = (self.diameter + self.fit.clearance() / 2.0) / 2.0;
+        assert!(outer_diameter / 2.0 > root + self.taper.max(0.0) / 2.0 * self.length);
+        let taper = self.taper / 2.0;
+        let mut children = self.ridges(root, &self.profile.complement(), true);
+        let bore = translate!([0.0, 0.0, -1.0],
+            cylinder!(
+                h = self.length + 2.0,
+                r1 = root - 0.0001 - taper,
+                r2 = root - 0.0001 + taper * (self.length + 1.0),
+                center = false,
+                fn = self.segments
+            );
+        );
+        children.push(cylinder!(self.length, outer_diameter / 2.0, fn = self.segments) - bore);
+        Scad {
+            op: ScadOp::Union,
+            children,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::{dcos, dsin, prelude::*};
+
+/// A clock face or instrument dial.
+///
+/// Angles are measured clockwise from 12 o'clock. The default is a 12 hour clock face.
+#[derive(Clone)]
+pub struct Dial {
+    /// The radius of the face.
+    pub radius: f64,
+    /// The angle of the first major tick.
+    pub start_degrees: f64,
+    /// The angle the scale covers, 360 for a full circle.
+    pub degrees: f64,
+    /// The number of major ticks.
+    pub major_ticks: u64,
+    /// The number of minor divisions between major ticks, 0 or 1 for no minor ticks.
+    pub minor_divisions: u64,
+    pub major_tick_length: f64,
+    pub major_tick_width: f64,
+    pub minor_tick_length: f64,
+    pub minor_tick_width: f64,
+    /// The distance from the edge of the face to the outer end of the ticks.
+    pub tick_inset: f64,
+    /// The labels placed at the major ticks, may be shorter than major_ticks or empty.
+    pub numerals: Vec<String>,
+    pub numeral_size: f64,
+    /// The distance from the edge of the face to the center of the numerals.
+    pub numeral_inset: f64,
+    pub font: String,
+    /// The diameter of the hole in the center, 0 for no hole.
+    pub bore_diameter: f64,
+    /// The number of segments in a circle.
+    pub segments: u64,
+}
+
+impl Default for Dial {
+    fn default() -> Self {
+        Self {
+            radius: 50.0,
+            start_degrees: 0.0,
+            degrees: 360.0,
+            major_ticks: 12,
+            minor_divisions: 5,
+            major_tick_length: 6.0,
+            major_tick_width: 1.5,
+            minor_tick_length: 3.0,
+            minor_tick_width: 0.6,
+            tick_inset: 2.0,
+            numerals: (0..12)
+                .map(|i| if i == 0 { 12 } else { i })
+                .map(|i| i.to_string())
+                .collect(),
+            numeral_size: 6.0,
+            numeral_inset: 15.0,
+            font: "Liberation Sans".to_string(),
+            bore_diameter: 8.0,
+            segments: 128,
+        }
+    }
+}
+
+impl Dial {
+    /// The 2D ticks and numerals of the dial.
+    ///
+    /// return: The 2D marks.
+    pub fn marks(&self) -> Scad {
+        assert!(self.major_ticks > 1);
+        assert!(self.degrees > 0.0 && self.degrees <= 360.0);
+        let full_circle = self.degrees == 360.0;
+        let major_steps = if full_circle {
+            self.major_ticks
+        } else {
+            self.major_ticks - 1
+        };
+
+        let mut marks = Scad::polar_array(
+            &self.tick(self.major_tick_length, self.major_tick_width),
+            self.major_ticks,
+            self.degrees,
+        );
+        if self.minor_divisions > 1 {
+            let minor_ticks = if full_circle {
+                major_steps * self.minor_divisions
+            } else {
+                major_steps * self.minor_divisions + 1
+            };
+            marks = marks
+                + Scad::polar_array(
+                    &self.tick(self.minor_tick_length, self.minor_tick_width),
+                    minor_ticks,
+                    self.degrees,
+                );
+        }
+
+        let numeral_radius = self.radius - self.numeral_inset;
+        for (i, numeral) in self.numerals.iter().enumerate() {
+            let a = i as f64 * self.degrees / major_steps as f64;
+            marks = marks
+                + translate!([numeral_radius * dsin(a), numeral_radius * dcos(a), 0.0],
+                    rotate!(self.start_degrees,
+                        text!(numeral, self.numeral_size, &self.font, TextHalign::center,
+                            TextValign::center, TextDirection::ltr, fn=self.segments);
+                    );
+                );
+        }
+
+        rotate!(-self.start_degrees, marks;)
+    }
+
+    /// The 2D dial face with the marks and bore removed.
+    ///
+    /// return: The 2D face.
+    pub fn into_scad(&self) -> Scad {
+        let mut face = circle!(self.radius, fn=self.segments) - self.marks();
+        if self.bore_diameter > 0.0 {
+            face = face - circle!(d=self.bore_diameter, fn=self.segments);
+        }
+        face
+    }
+
+    /// An extruded dial face with engraved marks.
+    ///
+    /// thickness: The thickness of the face.
+    ///
+    /// engrave_depth: The depth of the engraved marks.
+    ///
+    /// return: The dial.
+    pub fn into_scad_extruded(&self, thickness: f64, engrave_depth: f64) -> Scad {
+        assert!(engrave_depth < thickness);
+        let mut face = cylinder!(thickness, self.radius, fn=self.segments)
+            - translate!([0.0, 0.0, thickness - engrave_depth],
+                linear_extrude!(engrave_depth + 1.0, self.marks(););
+            );
+        if self.bore_diameter > 0.0 {
+            face = face
+                - translate!([0.0, 0.0, -1.0],
+                    cylinder!(thickness + 2.0, self.bore_diameter / 2.0, fn=self.segments);
+                );
+        }
+        face
+    }
+
+    fn tick(&self, length: f64, width: f64) -> Scad {
+        translate!([0.0, self.radius - self.tick_inset - length / 2.0, 0.0],
+            square!([width, length], true);
+        )
+    }
+}
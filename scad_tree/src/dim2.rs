@@ -21,9 +21,28 @@
 // SOFTWARE.
 //
 
-use crate::{dcos, dsin, Pt2, Pt2s};
+use crate::{
+    clip::{contains, cross, probe, signed_area},
+    dcos, dsin, triangulate2d_holes, Indices, Paths, Pt2, Pt2s,
+};
+
+pub use crate::{
+    clip::{difference, intersection, union, xor},
+    dxf::{from_dxf, from_dxf_str},
+    gerber::{from_gerber, from_gerber_str},
+};
 
 /// Create a clockwise circle or part of a circle.
+///
+/// The arc is centered on the origin.
+///
+/// start: The first point of the arc.
+///
+/// degrees: The angle of the arc, 360 for a full circle.
+///
+/// segments: The number of segments in the arc.
+///
+/// return: The segments + 1 points of the arc, or segments points for a full circle.
 pub fn arc(start: Pt2, degrees: f64, segments: u64) -> Pt2s {
     assert!(degrees <= 360.0);
     let n_pts = if degrees == 360.0 {
@@ -39,27 +58,728 @@ pub fn arc(start: Pt2, degrees: f64, segments: u64) -> Pt2s {
     pts
 }
 
-/// Convenience function for circle creation.
+/// Create an arc around a center point.
+///
+/// The arc is clockwise when end_degrees is less than start_degrees.
+///
+/// center: The center of the arc.
+///
+/// radius: The radius of the arc.
+///
+/// start_degrees: The angle of the first point measured from the +X axis.
+///
+/// end_degrees: The angle of the last point measured from the +X axis.
+///
+/// segments: The number of segments in the arc.
+///
+/// return: The segments + 1 points of the arc.
+pub fn center_arc(
+    center: Pt2,
+    radius: f64,
+    start_degrees: f64,
+    end_degrees: f64,
+    segments: u64,
+) -> Pt2s {
+    assert!(segments > 0);
+    let mut pts = Pt2s::with_capacity(segments as usize + 1);
+    for i in 0..=segments {
+        let a = start_degrees + (end_degrees - start_degrees) * i as f64 / segments as f64;
+        pts.push(center + Pt2::new(dcos(a) * radius, dsin(a) * radius));
+    }
+    pts
+}
+
+/// The arc that rounds the corner between two line segments.
+///
+/// The radius is reduced if the arc would not fit on the segments.
+///
+/// p0: The far end of the incoming segment.
+///
+/// corner: The shared point of the segments.
+///
+/// p1: The far end of the outgoing segment.
+///
+/// radius: The radius of the fillet.
+///
+/// segments: The number of segments in the arc.
+///
+/// return: The points of the arc from the p0 side to the p1 side, or only
+/// the corner when the segments are parallel.
+pub fn fillet_between(p0: Pt2, corner: Pt2, p1: Pt2, radius: f64, segments: u64) -> Pt2s {
+    assert!(segments > 0);
+    let to_prev = p0 - corner;
+    let to_next = p1 - corner;
+    let (prev_len, next_len) = (to_prev.len(), to_next.len());
+    if prev_len == 0.0 || next_len == 0.0 {
+        return Pt2s::from_pt2s(vec![corner]);
+    }
+    let u = to_prev / prev_len;
+    let v = to_next / next_len;
+    let theta = u.dot(v).clamp(-1.0, 1.0).acos();
+    if !(1.0e-6..=std::f64::consts::PI - 1.0e-6).contains(&theta) {
+        return Pt2s::from_pt2s(vec![corner]);
+    }
+    let half_tan = (theta / 2.0).tan();
+    let d = (radius / half_tan).min(prev_len).min(next_len);
+    let r = d * half_tan;
+    let center = corner + (u + v).normalized() * (r / (theta / 2.0).sin());
+    let start = corner + u * d - center;
+    let sweep = (std::f64::consts::PI - theta).to_degrees() * -cross(u, v).signum();
+    let mut pts = Pt2s::with_capacity(segments as usize + 1);
+    for i in 0..=segments {
+        pts.push(center + start.rotated(sweep * i as f64 / segments as f64));
+    }
+    pts
+}
+
+/// The intersection of two infinite lines.
+///
+/// a0: A point on the first line.
+///
+/// a1: Another point on the first line.
+///
+/// b0: A point on the second line.
+///
+/// b1: Another point on the second line.
+///
+/// return: The intersection or None when the lines are parallel.
+pub fn line_intersection(a0: Pt2, a1: Pt2, b0: Pt2, b1: Pt2) -> Option<Pt2> {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let denominator = cross(da, db);
+    if denominator.abs() < 1.0e-12 {
+        return None;
+    }
+    Some(a0 + da * (cross(b0 - a0, db) / denominator))
+}
+
+/// The arc that joins two line segments that don't share a corner.
+///
+/// The segments are extended to their intersection and the corner is
+/// rounded like fillet_between.
+///
+/// a0: The start of the incoming segment.
+///
+/// a1: The end of the incoming segment.
+///
+/// b0: The start of the outgoing segment.
+///
+/// b1: The end of the outgoing segment.
+///
+/// radius: The radius of the fillet.
+///
+/// segments: The number of segments in the arc.
+///
+/// return: The points of the arc or None when the segments are parallel.
+pub fn fillet_lines(
+    a0: Pt2,
+    a1: Pt2,
+    b0: Pt2,
+    b1: Pt2,
+    radius: f64,
+    segments: u64,
+) -> Option<Pt2s> {
+    let corner = line_intersection(a0, a1, b0, b1)?;
+    Some(fillet_between(a0, corner, b1, radius, segments))
+}
+
+/// The circle that passes through three points.
+///
+/// a: The first point.
+///
+/// b: The second point.
+///
+/// c: The third point.
+///
+/// return: The center and radius or None when the points are in a line.
+pub fn circle_from_points(a: Pt2, b: Pt2, c: Pt2) -> Option<(Pt2, f64)> {
+    let d = 2.0 * cross(b - a, c - a);
+    if d.abs() < 1.0e-12 {
+        return None;
+    }
+    let ab = b - a;
+    let ac = c - a;
+    let center = a + Pt2::new(
+        ac.y * ab.dot(ab) - ab.y * ac.dot(ac),
+        ab.x * ac.dot(ac) - ac.x * ab.dot(ab),
+    ) / d;
+    Some((center, (a - center).len()))
+}
+
+/// An arc from start through mid to end.
+///
+/// start: The first point of the arc.
+///
+/// mid: A point on the arc between start and end.
+///
+/// end: The last point of the arc.
+///
+/// segments: The number of segments in the arc.
+///
+/// return: The segments + 1 points of the arc, or start and end when the
+/// points are in a line.
+pub fn arc_from_points(start: Pt2, mid: Pt2, end: Pt2, segments: u64) -> Pt2s {
+    let Some((center, radius)) = circle_from_points(start, mid, end) else {
+        return Pt2s::from_pt2s(vec![start, end]);
+    };
+    let angle = |p: Pt2| (p.y - center.y).atan2(p.x - center.x).to_degrees();
+    let start_degrees = angle(start);
+    let mut sweep = (angle(end) - start_degrees).rem_euclid(360.0);
+    if cross(mid - start, end - mid) < 0.0 {
+        // the points turn right so the arc is clockwise
+        sweep -= 360.0;
+    }
+    let mut pts = center_arc(
+        center,
+        radius,
+        start_degrees,
+        start_degrees + sweep,
+        segments,
+    );
+    pts[0] = start;
+    let last = pts.len() - 1;
+    pts[last] = end;
+    pts
+}
+
+/// An arc from start to end given by its bulge like in DXF polylines.
+///
+/// bulge: The tangent of a quarter of the included angle, positive for
+/// counter clockwise arcs, 1 is a half circle and 0 a straight line.
+///
+/// start: The first point of the arc.
+///
+/// end: The last point of the arc.
+///
+/// segments: The number of segments in the arc.
+///
+/// return: The segments + 1 points of the arc, or start and end for a straight line.
+pub fn bulge_arc(start: Pt2, end: Pt2, bulge: f64, segments: u64) -> Pt2s {
+    let chord = end - start;
+    if bulge.abs() < 1.0e-12 || chord.len() < 1.0e-12 {
+        return Pt2s::from_pt2s(vec![start, end]);
+    }
+    let theta = 4.0 * bulge.atan();
+    let left = Pt2::new(-chord.y, chord.x).normalized();
+    let center = start.lerp(end, 0.5) + left * (chord.len() / (2.0 * (theta / 2.0).tan()));
+    let from = start - center;
+    let mut pts = Pt2s::with_capacity(segments as usize + 1);
+    pts.push(start);
+    for i in 1..segments {
+        pts.push(center + from.rotated((theta * i as f64 / segments as f64).to_degrees()));
+    }
+    pts.push(end);
+    pts
+}
+
+/// The lines that touch two circles.
+///
+/// There are up to four, the two outer tangents followed by the two inner
+/// tangents that cross between the circles. Use a radius of 0 for the
+/// tangents from a point to a circle.
+///
+/// center0: The center of the first circle.
+///
+/// radius0: The radius of the first circle.
+///
+/// center1: The center of the second circle.
+///
+/// radius1: The radius of the second circle.
+///
+/// return: The (point on the first circle, point on the second circle) of each tangent.
+pub fn tangent_lines(center0: Pt2, radius0: f64, center1: Pt2, radius1: f64) -> Vec<(Pt2, Pt2)> {
+    let d = center1 - center0;
+    let distance = d.len();
+    let mut lines = Vec::with_capacity(4);
+    if distance < 1.0e-12 {
+        return lines;
+    }
+    let u = d / distance;
+    let perp = Pt2::new(-u.y, u.x);
+    // the normal n of a tangent satisfies n . d = radius0 -+ radius1
+    for r1 in [radius1, -radius1] {
+        let c = (radius0 - r1) / distance;
+        if c.abs() > 1.0 {
+            continue;
+        }
+        let h = (1.0 - c * c).max(0.0).sqrt();
+        for sign in [1.0, -1.0] {
+            let n = u * c + perp * (h * sign);
+            lines.push((center0 + n * radius0, center1 + n * r1));
+        }
+        if radius0 == 0.0 || radius1 == 0.0 {
+            // both passes give the same two lines
+            break;
+        }
+    }
+    lines
+}
+
+/// Round every corner of a closed profile.
+///
+/// Each fillet uses at most half of the adjacent edges.
+///
+/// points: The profile.
+///
+/// radius: The radius of the fillets.
+///
+/// segments: The number of segments in each fillet.
+///
+/// return: The rounded profile.
+pub fn fillet_corners(points: &Pt2s, radius: f64, segments: u64) -> Pt2s {
+    let n = points.len();
+    let mut pts = Pt2s::with_capacity(n * (segments as usize + 1));
+    for i in 0..n {
+        let prev = points[(i + n - 1) % n];
+        let next = points[(i + 1) % n];
+        let corner = points[i];
+        pts.append(&mut fillet_between(
+            prev.lerp(corner, 0.5),
+            corner,
+            corner.lerp(next, 0.5),
+            radius,
+            segments,
+        ));
+    }
+    pts
+}
+
+/// The angle the outline turns at each point of a closed profile.
+///
+/// points: The clockwise profile.
+///
+/// return: The turning angle in degrees of each point, positive at convex
+/// corners and negative at concave ones. Straight points and points on top
+/// of their neighbor are 0.
+pub fn turning_angles(points: &Pt2s) -> Vec<f64> {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let to_corner = points[i] - points[(i + n - 1) % n];
+            let from_corner = points[(i + 1) % n] - points[i];
+            // clockwise profiles turn right at convex corners
+            -cross(to_corner, from_corner)
+                .atan2(to_corner.dot(from_corner))
+                .to_degrees()
+        })
+        .collect()
+}
+
+/// Round only the corners of a closed profile that turn at least min_degrees.
+///
+/// Points on arcs and curves turn a little at each point so they are left
+/// alone while the sharp corners between them are filleted.
+///
+/// points: The clockwise profile.
+///
+/// min_degrees: The smallest turning angle, convex or concave, that is rounded.
+///
+/// radius: The radius of the fillets.
+///
+/// segments: The number of segments in each fillet.
+///
+/// return: The rounded profile.
+pub fn fillet_sharp_corners(points: &Pt2s, min_degrees: f64, radius: f64, segments: u64) -> Pt2s {
+    let n = points.len();
+    let angles = turning_angles(points);
+    let mut pts = Pt2s::with_capacity(n);
+    for i in 0..n {
+        let corner = points[i];
+        if angles[i].abs() < min_degrees {
+            pts.push(corner);
+            continue;
+        }
+        let prev = points[(i + n - 1) % n];
+        let next = points[(i + 1) % n];
+        pts.append(&mut fillet_between(
+            prev.lerp(corner, 0.5),
+            corner,
+            corner.lerp(next, 0.5),
+            radius,
+            segments,
+        ));
+    }
+    pts
+}
+
+/// Join outlines into one list of points with a path for each outline.
+///
+/// outlines: The outlines.
+///
+/// return: The points and paths for the polygon! macro.
+pub fn merge_outlines(outlines: &[Pt2s]) -> (Pt2s, Paths) {
+    let mut points = Pt2s::new();
+    let mut paths = Paths::with_capacity(outlines.len());
+    for outline in outlines.iter() {
+        let start = points.len() as u64;
+        paths.push(Indices::from_indices(
+            (start..start + outline.len() as u64).collect(),
+        ));
+        points.extend(outline.iter().copied());
+    }
+    (points, paths)
+}
+
+/// A closed profile with holes.
+#[derive(Clone, Default)]
+pub struct Profile {
+    /// The outer boundary, clockwise.
+    pub outline: Pt2s,
+    /// The holes, counter clockwise.
+    pub holes: Vec<Pt2s>,
+}
+
+impl From<Pt2s> for Profile {
+    fn from(outline: Pt2s) -> Self {
+        Self::new(outline)
+    }
+}
+
+impl Profile {
+    /// Create a profile without holes.
+    ///
+    /// outline: The outer boundary, it is made clockwise.
+    ///
+    /// return: The profile.
+    pub fn new(mut outline: Pt2s) -> Self {
+        if signed_area(&outline) > 0.0 {
+            outline.reverse();
+        }
+        Self {
+            outline,
+            holes: Vec::new(),
+        }
+    }
+
+    /// Add a hole to the profile.
+    ///
+    /// hole: The boundary of the hole, it is made counter clockwise.
+    ///
+    /// return: The profile.
+    pub fn with_hole(mut self, mut hole: Pt2s) -> Self {
+        if signed_area(&hole) < 0.0 {
+            hole.reverse();
+        }
+        self.holes.push(hole);
+        self
+    }
+
+    /// Group outlines into profiles.
+    ///
+    /// Each counter clockwise outline becomes a hole of the smallest clockwise
+    /// outline around it, the way the boolean functions and font outlines return
+    /// them. Holes that aren't inside an outline are dropped.
+    ///
+    /// outlines: The clockwise outlines and counter clockwise holes.
+    ///
+    /// return: The profiles.
+    pub fn from_outlines(outlines: &[Pt2s]) -> Vec<Profile> {
+        let mut profiles: Vec<Profile> = outlines
+            .iter()
+            .filter(|o| o.len() > 2 && signed_area(o) < 0.0)
+            .map(|o| Profile::new(o.clone()))
+            .collect();
+        for hole in outlines
+            .iter()
+            .filter(|o| o.len() > 2 && signed_area(o) > 0.0)
+        {
+            let parent = (0..profiles.len())
+                .filter(|i| {
+                    let outline = &profiles[*i].outline;
+                    contains(&[outline.to_vec()], probe(hole, outline))
+                })
+                .min_by(|a, b| {
+                    let area = |i: &usize| -signed_area(&profiles[*i].outline);
+                    area(a).total_cmp(&area(b))
+                });
+            if let Some(i) = parent {
+                profiles[i].holes.push(hole.clone());
+            }
+        }
+        profiles
+    }
+
+    /// The profile with the outline clockwise and the holes counter clockwise.
+    pub fn normalized(&self) -> Self {
+        self.holes
+            .iter()
+            .fold(Self::new(self.outline.clone()), |profile, hole| {
+                profile.with_hole(hole.clone())
+            })
+    }
+
+    /// The loops of the profile, the outline first.
+    pub fn loops(&self) -> impl Iterator<Item = &Pt2s> {
+        std::iter::once(&self.outline).chain(self.holes.iter())
+    }
+
+    /// The points of the outline followed by the points of the holes.
+    pub fn points(&self) -> Pt2s {
+        Pt2s::from_pt2s(self.loops().flat_map(|l| l.iter().copied()).collect())
+    }
+
+    /// The area of the outline less the holes.
+    pub fn area(&self) -> f64 {
+        (signed_area(&self.outline).abs()
+            - self.holes.iter().map(|h| signed_area(h).abs()).sum::<f64>())
+            / 2.0
+    }
+
+    /// Triangulate the profile.
+    ///
+    /// return: Clockwise triangles as indices into points.
+    pub fn triangulate(&self) -> Indices {
+        triangulate2d_holes(&self.outline, &self.holes)
+    }
+}
+
+/// The convex hull of a set of points.
+///
+/// Points on the edges of the hull are left out.
+///
+/// points: The points.
+///
+/// return: The clockwise hull.
+pub fn convex_hull(points: &[Pt2]) -> Pt2s {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup_by(|a, b| (*a - *b).len() < 1.0e-12);
+    if sorted.len() < 3 {
+        return Pt2s::from_pt2s(sorted);
+    }
+    // Andrew's monotone chain, the upper chain from the left is clockwise
+    let chain = |points: &mut dyn Iterator<Item = &Pt2>| {
+        let mut hull: Vec<Pt2> = Vec::new();
+        for p in points {
+            while hull.len() >= 2
+                && cross(
+                    hull[hull.len() - 1] - hull[hull.len() - 2],
+                    *p - hull[hull.len() - 2],
+                ) >= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+        hull.pop();
+        hull
+    };
+    let mut hull = chain(&mut sorted.iter());
+    hull.extend(chain(&mut sorted.iter().rev()));
+    Pt2s::from_pt2s(hull)
+}
+
+/// True if a closed profile has no concave corners.
+///
+/// points: The profile, clockwise or counter clockwise.
+///
+/// return: True if the profile is convex.
+pub fn is_convex(points: &Pt2s) -> bool {
+    let n = points.len();
+    let turns: Vec<f64> = (0..n)
+        .map(|i| {
+            cross(
+                points[i] - points[(i + n - 1) % n],
+                points[(i + 1) % n] - points[i],
+            )
+        })
+        .collect();
+    turns.iter().all(|t| *t <= 1.0e-12) || turns.iter().all(|t| *t >= -1.0e-12)
+}
+
+/// The Minkowski sum of two closed profiles.
+///
+/// Convex profiles are summed directly. Concave profiles are split into
+/// triangles, the pieces are summed in pairs and the sums are joined.
+///
+/// a: The first profile.
+///
+/// b: The second profile, e.g. a circle centered on the origin to round a profile.
+///
+/// return: The clockwise outlines and counter clockwise holes of the sum.
+pub fn minkowski(a: &Pt2s, b: &Pt2s) -> Vec<Pt2s> {
+    let pieces = |points: &Pt2s| -> Vec<Vec<Pt2>> {
+        if is_convex(points) {
+            return vec![points.to_vec()];
+        }
+        Profile::new(points.clone())
+            .triangulate()
+            .chunks_exact(3)
+            .map(|t| t.iter().map(|i| points[*i as usize]).collect())
+            .collect()
+    };
+    let (a, b) = (pieces(a), pieces(b));
+    let mut sums = Vec::with_capacity(a.len() * b.len());
+    for piece_a in a.iter() {
+        for piece_b in b.iter() {
+            let points: Vec<Pt2> = piece_a
+                .iter()
+                .flat_map(|p| piece_b.iter().map(move |q| *p + *q))
+                .collect();
+            sums.push(convex_hull(&points));
+        }
+    }
+    let mut result: Vec<Pt2s> = Vec::new();
+    for sum in sums {
+        result = union(&result, &[sum]);
+    }
+    result
+}
+
+/// How the corners are joined when offsetting a profile.
+#[derive(Clone, Copy, PartialEq)]
+pub enum JoinType {
+    /// Extend the edges until they meet.
+    Miter,
+    /// Round the corners with the given number of segments in a full circle.
+    Round(u64),
+    /// Cut the corners off flat.
+    Bevel,
+}
+
+/// Offset a closed profile inward or outward.
+///
+/// Only the corners that open a gap when the edges are moved apart get the
+/// join, the others are trimmed to the point where the edges meet. Insets that
+/// are large enough to collapse features produce self intersecting profiles.
+///
+/// points: The profile, clockwise or counter clockwise.
+///
+/// distance: The distance to move each edge, positive is outward.
+///
+/// join: How the corners are filled.
+///
+/// return: The offset profile in the same winding as the input.
+pub fn offset(points: &Pt2s, distance: f64, join: JoinType) -> Pt2s {
+    let n = points.len();
+    assert!(n > 2);
+    // shift along the left hand normal of each edge
+    let shift = if signed_area(points) < 0.0 {
+        distance
+    } else {
+        -distance
+    };
+    let normal = |i: usize| {
+        let d = (points[(i + 1) % n] - points[i]).normalized();
+        Pt2::new(-d.y, d.x)
+    };
+
+    let mut result = Pt2s::with_capacity(n);
+    for i in 0..n {
+        let p = points[i];
+        let n0 = normal((i + n - 1) % n);
+        let n1 = normal(i);
+        let turn = cross(n0, n1);
+        let dot = n0.dot(n1);
+        if turn.abs() < 1.0e-9 && dot > 0.0 {
+            result.push(p + n1 * shift);
+        } else if turn * shift > 0.0 || join == JoinType::Miter {
+            result.push(p + (n0 + n1) * (shift / (1.0 + dot)));
+        } else if let JoinType::Round(segments) = join {
+            let degrees = turn.atan2(dot).to_degrees();
+            let steps = ((degrees.abs() / 360.0 * segments as f64).ceil() as u64).max(1);
+            for j in 0..=steps {
+                result.push(p + n0.rotated(degrees * j as f64 / steps as f64) * shift);
+            }
+        } else {
+            result.push(p + n0 * shift);
+            result.push(p + n1 * shift);
+        }
+    }
+    result
+}
+
+/// Create a clockwise circle centered on the origin.
+///
+/// radius: The radius of the circle.
+///
+/// segments: The number of segments in the circle.
+///
+/// return: The circle.
 pub fn circle(radius: f64, segments: u64) -> Pt2s {
     arc(Pt2::new(radius, 0.0), 360.0, segments)
 }
 
-/// Create an inscribed polygon.
+/// Create a regular polygon inside a circle.
+///
+/// The first point is on the +X axis.
+///
+/// n_sides: The number of sides.
 ///
-/// radius: the radius of the circle surrounding the polygon
+/// radius: The radius of the circle surrounding the polygon.
+///
+/// return: The clockwise polygon.
 pub fn inscribed_polygon(n_sides: u64, radius: f64) -> Pt2s {
     circle(radius, n_sides)
 }
 
-/// Create a circumscribed polygon.
+/// Create a regular polygon around a circle.
+///
+/// Use this for holes that must fit a round part e.g. the hexagon of a nut
+/// across the flats.
+///
+/// n_sides: The number of sides.
+///
+/// radius: The radius of the circle inside the polygon.
 ///
-/// radius: the radius of the circle inside the polygon
+/// return: The clockwise polygon.
 pub fn circumscribed_polygon(n_sides: u64, radius: f64) -> Pt2s {
     let radius = radius / dcos(180.0 / n_sides as f64);
     inscribed_polygon(n_sides, radius)
 }
 
+/// Create a rectangle.
+///
+/// width: The size in the X direction.
+///
+/// height: The size in the Y direction.
+///
+/// center: Center on the origin, otherwise the lower left corner is at the origin.
+///
+/// return: The clockwise rectangle.
+pub fn rect(width: f64, height: f64, center: bool) -> Pt2s {
+    Pt2s::from_slice(&rect_points(width, height, center))
+}
+
+/// Create the corners of a rectangle.
+///
+/// The same points as rect in an array, e.g. for a fixed size buffer or
+/// `Pt2s::from_slice`. Use to_rust_const for profiles made at build time.
+///
+/// width: The size in the X direction.
+///
+/// height: The size in the Y direction.
+///
+/// center: Center on the origin, otherwise the lower left corner is at the origin.
+///
+/// return: The clockwise rectangle.
+pub fn rect_points(width: f64, height: f64, center: bool) -> [Pt2; 4] {
+    let (x, y) = if center {
+        (-width / 2.0, -height / 2.0)
+    } else {
+        (0.0, 0.0)
+    };
+    [
+        Pt2::new(x, y),
+        Pt2::new(x, y + height),
+        Pt2::new(x + width, y + height),
+        Pt2::new(x + width, y),
+    ]
+}
+
 /// Create a rectangle or square with rounded corners.
+///
+/// width: The size in the X direction.
+///
+/// height: The size in the Y direction.
+///
+/// radius: The radius of the corners.
+///
+/// segments: The number of segments in each corner.
+///
+/// center: Center on the origin, otherwise the lower left corner is at the origin.
+///
+/// return: The clockwise rectangle.
 pub fn rounded_rect(width: f64, height: f64, radius: f64, segments: u64, center: bool) -> Pt2s {
     let mut tr = arc(Pt2::new(0.0, radius), 90.0, segments);
     tr.translate(Pt2::new(width - radius, height - radius));
@@ -80,6 +800,27 @@ pub fn rounded_rect(width: f64, height: f64, radius: f64, segments: u64, center:
     tr
 }
 
+/// Writes a profile as the Rust source of a constant.
+///
+/// Use this in a build script to compute a profile that is slow to generate
+/// once, e.g. a gear with many teeth, and include! the file so the program
+/// starts with the points. The numbers are written so they read back exactly.
+///
+/// name: The name of the constant.
+///
+/// points: The profile, all coordinates must be finite.
+///
+/// return: A `pub const name: [Pt2; n] = [...];` item.
+pub fn to_rust_const(name: &str, points: &Pt2s) -> String {
+    assert!(points.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+    let mut source = format!("pub const {}: [Pt2; {}] = [\n", name, points.len());
+    for p in points.iter() {
+        source += &format!("    Pt2::new({:?}, {:?}),\n", p.x, p.y);
+    }
+    source += "];\n";
+    source
+}
+
 /// Creates a profile for chamfering edges at 45 degrees.
 ///
 /// The profile is a triangle when oversize is zero. With
@@ -87,9 +828,11 @@ pub fn rounded_rect(width: f64, height: f64, radius: f64, segments: u64, center:
 /// Extending the shape with oversize gives a cleaner preview
 /// in OpenSCAD.
 ///
-/// size: The vertcal and horizontal size of the chamfer.
+/// size: The vertical and horizontal size of the chamfer.
 ///
 /// oversize: The size of the non-chamfer part.
+///
+/// return: The clockwise profile.
 pub fn chamfer(size: f64, oversize: f64) -> Pt2s {
     Pt2s::from_pt2s(vec![
         Pt2::new(0.0, size + oversize),
@@ -102,9 +845,85 @@ pub fn chamfer(size: f64, oversize: f64) -> Pt2s {
     ])
 }
 
+/// Creates a profile for rounding edges.
+///
+/// Like chamfer the profile has strips of oversize along both axes. The
+/// quarter circle is centered at (radius + oversize, radius + oversize).
+///
+/// radius: The radius of the fillet.
+///
+/// oversize: The size of the non-fillet part.
+///
+/// segments: The number of segments in the quarter circle.
+///
+/// return: The clockwise profile.
+pub fn fillet(radius: f64, oversize: f64, segments: u64) -> Pt2s {
+    let mut points = Pt2s::from_pt2s(vec![Pt2::new(0.0, 0.0), Pt2::new(0.0, radius + oversize)]);
+    points.append(&mut center_arc(
+        Pt2::new(radius + oversize, radius + oversize),
+        radius,
+        180.0,
+        270.0,
+        segments.max(1),
+    ));
+    points.push(Pt2::new(radius + oversize, 0.0));
+    points
+}
+
+/// Create a clockwise ellipse centered on the origin.
+///
+/// x_radius: The radius in the X direction.
+///
+/// y_radius: The radius in the Y direction.
+///
+/// segments: The number of segments in the ellipse.
+///
+/// return: The ellipse.
+pub fn ellipse(x_radius: f64, y_radius: f64, segments: u64) -> Pt2s {
+    let mut points = circle(1.0, segments);
+    for p in points.iter_mut() {
+        p.x *= x_radius;
+        p.y *= y_radius;
+    }
+    points
+}
+
+/// Create a slot, a rectangle with round ends, along the X axis.
+///
+/// length: The distance between the centers of the round ends.
+///
+/// radius: Half the width of the slot.
+///
+/// segments: The number of segments in a full circle.
+///
+/// center: Center on the origin, otherwise the center of the left end is at the origin.
+///
+/// return: The clockwise slot.
+pub fn slot(length: f64, radius: f64, segments: u64, center: bool) -> Pt2s {
+    let half = (segments / 2).max(1);
+    let mut right = arc(Pt2::new(0.0, radius), 180.0, half);
+    right.translate(Pt2::new(length, 0.0));
+    let mut left = arc(Pt2::new(0.0, -radius), 180.0, half);
+    right.append(&mut left);
+    if center {
+        right.translate(Pt2::new(-length / 2.0, 0.0));
+    }
+    right
+}
+
 /// Yeilds the points of a quadratic bezier.
 ///
 /// If you want to use a Viewer use QuadraticBezier2D struct instead.
+///
+/// start: The first point of the curve.
+///
+/// control: The control point.
+///
+/// end: The last point of the curve.
+///
+/// segments: The number of segments in the curve.
+///
+/// return: The segments + 1 points of the curve.
 pub fn quadratic_bezier(start: Pt2, control: Pt2, end: Pt2, segments: u64) -> Pt2s {
     let delta = 1.0 / segments as f64;
     let mut points = Pt2s::new();
@@ -118,6 +937,18 @@ pub fn quadratic_bezier(start: Pt2, control: Pt2, end: Pt2, segments: u64) -> Pt
 /// Yeilds the points of a cubic bezier.
 ///
 /// If you want to use a Viewer use CubicBezier2D struct instead.
+///
+/// start: The first point of the curve.
+///
+/// control1: The control point for the start.
+///
+/// control2: The control point for the end.
+///
+/// end: The last point of the curve.
+///
+/// segments: The number of segments in the curve.
+///
+/// return: The segments + 1 points of the curve.
 pub fn cubic_bezier(start: Pt2, control1: Pt2, control2: Pt2, end: Pt2, segments: u64) -> Pt2s {
     let delta = 1.0 / segments as f64;
     let mut points = Pt2s::new();
@@ -133,7 +964,17 @@ pub fn cubic_bezier(start: Pt2, control1: Pt2, control2: Pt2, end: Pt2, segments
     points
 }
 
-/// Create a star profile.
+/// Create a clockwise star profile centered on the origin.
+///
+/// The first inner point is on the +X axis.
+///
+/// n_points: The number of points of the star.
+///
+/// inner_radius: The radius of the inner corners.
+///
+/// outer_radius: The radius of the points.
+///
+/// return: The star.
 pub fn star(n_points: usize, inner_radius: f64, outer_radius: f64) -> Pt2s {
     let angle = -360.0 / n_points as f64;
     let mut points = Pt2s::new();
@@ -150,7 +991,23 @@ pub fn star(n_points: usize, inner_radius: f64, outer_radius: f64) -> Pt2s {
     points
 }
 
-/// Create a star profile with rounded corners.
+/// Create a clockwise star profile with rounded corners centered on the origin.
+///
+/// The first point of the star is on the +X axis.
+///
+/// n_points: The number of points of the star.
+///
+/// inner_radius: The radius of the inner corners.
+///
+/// inner_handle_length: The length of the bezier handles at the inner corners.
+///
+/// outer_radius: The radius of the points.
+///
+/// outer_handle_length: The length of the bezier handles at the points.
+///
+/// segments: The number of segments in each curve.
+///
+/// return: The star.
 pub fn bezier_star(
     n_points: u64,
     inner_radius: f64,
@@ -21,31 +21,1103 @@
 // SOFTWARE.
 //
 
-use crate::{
-    dcos, dim2, dsin, polyhedron, triangulate2d, triangulate2d_rev, triangulate3d,
-    triangulate3d_rev, Faces, Indices, Mt4, Pt2s, Pt3, Pt3s, Scad, ScadOp,
+use {
+    crate::{
+        clip, color, csg, dcos, dim2, dsin, parallel, polyhedron, triangulate2d, triangulate2d_rev,
+        triangulate3d, triangulate3d_rev, Faces, Indices, Mt4, Pt2, Pt2s, Pt3, Pt3s, Scad,
+        ScadColor, ScadOp,
+    },
+    std::collections::{BTreeMap, BTreeSet, HashMap},
 };
 
+/// How the profile is oriented along the path of a sweep.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SweepFrame {
+    /// Keep the profile Y axis as close to the given vector as possible. Where
+    /// the path is parallel to the vector the previous orientation is kept.
+    Up(Pt3),
+    /// Follow the curvature of the path. Straight runs keep the previous
+    /// orientation.
+    Frenet,
+    /// Turn the profile as little as possible between path points.
+    RotationMinimizing,
+}
+
+/// The options for Polyhedron::sweep_with_options.
+#[derive(Clone)]
+pub struct SweepOptions {
+    /// The total twist of the profile along the path.
+    pub twist_degrees: f64,
+    /// The scale of the profile at each path point, empty for no scaling.
+    pub scales: Vec<f64>,
+    /// Connects the end of the path back to the start.
+    pub closed: bool,
+    /// Closes the ends of an open sweep.
+    pub caps: bool,
+    pub frame: SweepFrame,
+}
+
+impl Default for SweepOptions {
+    fn default() -> Self {
+        Self {
+            twist_degrees: 0.0,
+            scales: Vec::new(),
+            closed: false,
+            caps: true,
+            frame: SweepFrame::Up(Pt3::new(0.0, 0.0, 1.0)),
+        }
+    }
+}
+
+/// The side vector of the sweep frame at each path point.
+fn sweep_sides(path: &Pt3s, tangents: &[Pt3], frame: SweepFrame) -> Vec<Pt3> {
+    let first_side = |up: Pt3| {
+        let side = up.cross(tangents[0]);
+        if side.len() > 1.0e-9 {
+            side.normalized()
+        } else {
+            Pt3::new(0.0, 1.0, 0.0).cross(tangents[0]).normalized()
+        }
+    };
+    let mut sides = Vec::with_capacity(path.len());
+    match frame {
+        SweepFrame::Up(up) => {
+            sides.push(first_side(up));
+            for t in tangents.iter().skip(1) {
+                let side = up.cross(*t);
+                if side.len() > 1.0e-9 {
+                    sides.push(side.normalized());
+                } else {
+                    let prev = sides[sides.len() - 1];
+                    sides.push((prev - *t * prev.dot(*t)).normalized());
+                }
+            }
+        }
+        SweepFrame::Frenet => {
+            let binormal = |i: usize| {
+                if i == 0 || i == path.len() - 1 {
+                    return None;
+                }
+                let b = (path[i] - path[i - 1]).cross(path[i + 1] - path[i]);
+                if b.len() > 1.0e-9 {
+                    Some(b.normalized())
+                } else {
+                    None
+                }
+            };
+            let first = (0..path.len()).find_map(|i| binormal(i).map(|b| (i, b)));
+            let mut prev = match first {
+                Some((i, b)) => {
+                    let side = tangents[i].cross(b).normalized();
+                    (side - tangents[0] * side.dot(tangents[0])).normalized()
+                }
+                None => first_side(Pt3::new(0.0, 0.0, 1.0)),
+            };
+            for (i, t) in tangents.iter().enumerate() {
+                let side = match binormal(i) {
+                    Some(b) => t.cross(b).normalized(),
+                    None => (prev - *t * prev.dot(*t)).normalized(),
+                };
+                sides.push(side);
+                prev = side;
+            }
+        }
+        SweepFrame::RotationMinimizing => {
+            // double reflection method of Wang, Juttler, Zheng and Liu
+            sides.push(first_side(Pt3::new(0.0, 0.0, 1.0)));
+            for i in 0..path.len() - 1 {
+                let r = sides[i];
+                let v1 = path[i + 1] - path[i];
+                let c1 = v1.dot(v1);
+                if c1 < 1.0e-18 {
+                    sides.push(r);
+                    continue;
+                }
+                let r_l = r - v1 * (2.0 / c1 * v1.dot(r));
+                let t_l = tangents[i] - v1 * (2.0 / c1 * v1.dot(tangents[i]));
+                let v2 = tangents[i + 1] - t_l;
+                let c2 = v2.dot(v2);
+                let next = if c2 < 1.0e-18 {
+                    r_l
+                } else {
+                    r_l - v2 * (2.0 / c2 * v2.dot(r_l))
+                };
+                sides.push(next.normalized());
+            }
+        }
+    }
+    sides
+}
+
+/// The problems found by Polyhedron::validate.
+#[derive(Clone, Default, PartialEq)]
+pub struct MeshReport {
+    /// Edges that are not shared by exactly two faces, as sorted point indices.
+    /// Includes the edges around holes in the mesh.
+    pub non_manifold_edges: Vec<(u64, u64)>,
+    /// Edges where the two faces run the same direction, meaning one of the
+    /// faces is wound the wrong way.
+    pub inconsistent_edges: Vec<(u64, u64)>,
+    /// The mesh is closed and consistent but every face is wound the wrong way.
+    pub inverted: bool,
+    /// Pairs of points at the same location.
+    pub duplicate_points: Vec<(u64, u64)>,
+    /// Faces with repeated points, fewer than three points or no area.
+    pub degenerate_faces: Vec<usize>,
+    /// Faces that use points that do not exist.
+    pub invalid_indices: Vec<usize>,
+}
+
+impl MeshReport {
+    /// True when no problems were found.
+    pub fn is_valid(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// The points and faces of a polyhedron.
 ///
 /// Polyhedron exists so that meshes can be modified or created
 /// in Rust code. It also allows things that are not directly
 /// implemented in OpenSCAD like sweep and loft.
+///
+/// Faces can optionally be assigned to numbered groups and groups
+/// can be given a color. The groups follow the faces through
+/// transforms and merges.
+///
+/// Points can optionally carry a scalar, e.g. a wall thickness, that is
+/// written as a heat map of vertex colors by to_ply and to_obj.
+///
+/// Create a Polyhedron with Polyhedron::new, the groups and scalars are
+/// only reachable through the methods.
 #[derive(Clone)]
 pub struct Polyhedron {
     pub points: Pt3s,
     pub faces: Faces,
+    /// The group of each face, empty when the faces are not grouped.
+    pub(crate) face_groups: Vec<u64>,
+    /// The colors of the face groups.
+    pub(crate) group_colors: BTreeMap<u64, ScadColor>,
+    /// The scalar of each point, empty when the points have none.
+    pub(crate) vertex_scalars: Vec<f64>,
+}
+
+/// Summarizes the mesh with the number of points, faces and face groups.
+impl std::fmt::Debug for Polyhedron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Polyhedron")
+            .field("points", &self.points.len())
+            .field("faces", &self.faces.len())
+            .field(
+                "groups",
+                &self.face_groups.iter().collect::<BTreeSet<_>>().len(),
+            )
+            .finish()
+    }
 }
 
 impl Polyhedron {
+    /// Create a Polyhedron from points and faces.
+    pub fn new(points: Pt3s, faces: Faces) -> Self {
+        Self {
+            points,
+            faces,
+            face_groups: Vec::new(),
+            group_colors: BTreeMap::new(),
+            vertex_scalars: Vec::new(),
+        }
+    }
+
     /// Turn the Polyhedron into a Scad.
+    ///
+    /// If any face group has a color the result is a union of one polyhedron
+    /// per group, each with its color. The split polyhedrons are not closed so
+    /// this is meant for previewing.
     pub fn into_scad(self) -> Scad {
-        polyhedron!(self.points, self.faces)
+        self.into_scad_with_convexity(1)
     }
 
     /// Turn the Polyhedron into a Scad with the given convexity.
     pub fn into_scad_with_convexity(self, convexity: u64) -> Scad {
-        polyhedron!(self.points, self.faces, convexity)
+        if self.face_groups.is_empty() || self.group_colors.is_empty() {
+            return polyhedron!(self.points, self.faces, convexity);
+        }
+        let mut groups: BTreeMap<u64, Faces> = BTreeMap::new();
+        for (i, face) in self.faces.iter().enumerate() {
+            groups
+                .entry(self.face_group(i))
+                .or_default()
+                .push(face.clone());
+        }
+        let mut children = Vec::with_capacity(groups.len());
+        for (group, faces) in groups {
+            let child = polyhedron!(self.points.clone(), faces, convexity);
+            if let Some(c) = self.group_colors.get(&group) {
+                children.push(color!(c = *c, child;));
+            } else {
+                children.push(child);
+            }
+        }
+        Scad {
+            op: ScadOp::Union,
+            children,
+        }
+    }
+
+    /// Assign all the faces to a group.
+    pub fn set_group(&mut self, group: u64) -> &mut Self {
+        self.face_groups = vec![group; self.faces.len()];
+        self
+    }
+
+    /// Assign a face to a group.
+    ///
+    /// Faces that have not been assigned are in group 0.
+    pub fn set_face_group(&mut self, face: usize, group: u64) -> &mut Self {
+        self.face_groups.resize(self.faces.len(), 0);
+        self.face_groups[face] = group;
+        self
+    }
+
+    /// Set the color of a face group.
+    pub fn set_group_color(&mut self, group: u64, color: ScadColor) -> &mut Self {
+        self.group_colors.insert(group, color);
+        self
+    }
+
+    /// The group of the face, 0 if the faces are not grouped.
+    pub fn face_group(&self, face: usize) -> u64 {
+        self.face_groups.get(face).copied().unwrap_or(0)
+    }
+
+    /// The group of each face, empty when the faces are not grouped.
+    ///
+    /// May be shorter than the faces, the missing faces are in group 0.
+    pub fn face_groups(&self) -> &[u64] {
+        &self.face_groups
+    }
+
+    /// The colors of the face groups.
+    pub fn group_colors(&self) -> &BTreeMap<u64, ScadColor> {
+        &self.group_colors
+    }
+
+    /// Add the points and faces of another polyhedron to this one.
+    ///
+    /// Face groups and group colors are kept, colors of this polyhedron
+    /// win when both have a color for the same group. Points without a
+    /// scalar get NaN when the other polyhedron has scalars.
+    pub fn merge(&mut self, other: Polyhedron) -> &mut Self {
+        let offset = self.points.len() as u64;
+        if !self.face_groups.is_empty() || !other.face_groups.is_empty() {
+            self.face_groups.resize(self.faces.len(), 0);
+            let mut other_groups = other.face_groups;
+            other_groups.resize(other.faces.len(), 0);
+            self.face_groups.append(&mut other_groups);
+        }
+        if !self.vertex_scalars.is_empty() || !other.vertex_scalars.is_empty() {
+            self.vertex_scalars.resize(self.points.len(), f64::NAN);
+            let mut other_scalars = other.vertex_scalars;
+            other_scalars.resize(other.points.len(), f64::NAN);
+            self.vertex_scalars.append(&mut other_scalars);
+        }
+        self.points.extend(other.points.iter());
+        self.faces.append_offset(other.faces, offset);
+        for (group, color) in other.group_colors {
+            self.group_colors.entry(group).or_insert(color);
+        }
+        self
+    }
+
+    /// The union of this polyhedron and other computed on the mesh.
+    ///
+    /// Both polyhedrons should be closed. Faces with more than three points
+    /// are split into triangles which assumes they are convex. Face groups are kept.
+    ///
+    /// other: The polyhedron to add.
+    ///
+    /// return: The union.
+    pub fn union(&self, other: &Polyhedron) -> Polyhedron {
+        csg::union(self, other)
+    }
+
+    /// This polyhedron with other removed, computed on the mesh.
+    ///
+    /// other: The polyhedron to remove.
+    ///
+    /// return: The difference.
+    pub fn difference(&self, other: &Polyhedron) -> Polyhedron {
+        csg::difference(self, other)
+    }
+
+    /// The volume shared by this polyhedron and other, computed on the mesh.
+    ///
+    /// other: The polyhedron to intersect with.
+    ///
+    /// return: The intersection.
+    pub fn intersection(&self, other: &Polyhedron) -> Polyhedron {
+        csg::intersection(self, other)
+    }
+
+    /// The volume in either polyhedron but not both, computed on the mesh.
+    ///
+    /// other: The polyhedron to compare with.
+    ///
+    /// return: The symmetric difference.
+    pub fn xor(&self, other: &Polyhedron) -> Polyhedron {
+        self.difference(other).union(&other.difference(self))
+    }
+
+    /// The volume enclosed by the mesh, negative when the faces are inverted.
+    pub fn volume(&self) -> f64 {
+        let mut volume = 0.0;
+        self.for_each_triangle(|a, b, c| volume -= a.dot(b.cross(c)) / 6.0);
+        volume
+    }
+
+    /// The total area of the faces.
+    pub fn area(&self) -> f64 {
+        let mut area = 0.0;
+        self.for_each_triangle(|a, b, c| area += (b - a).cross(c - a).len() / 2.0);
+        area
+    }
+
+    /// The smallest axis aligned box around the points.
+    ///
+    /// return: The minimum and maximum corners, inverted infinite corners when there are no points.
+    pub fn bounds(&self) -> (Pt3, Pt3) {
+        let mut min = Pt3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = Pt3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in self.points.iter() {
+            min = Pt3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Pt3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        (min, max)
+    }
+
+    /// The outward area vector of each face, its length is the area of the face.
+    pub(crate) fn face_areas(&self) -> Vec<Pt3> {
+        self.faces
+            .iter()
+            .map(|face| {
+                let mut area = Pt3::new(0.0, 0.0, 0.0);
+                for i in 1..face.len().saturating_sub(1) {
+                    let a = self.points[face[0] as usize];
+                    let b = self.points[face[i] as usize];
+                    let c = self.points[face[i + 1] as usize];
+                    area += (c - a).cross(b - a) / 2.0;
+                }
+                area
+            })
+            .collect()
+    }
+
+    /// The angle between the faces on each edge.
+    ///
+    /// return: The points of each edge used by two faces and the angle in degrees
+    /// the surface bends across it, positive when convex.
+    pub fn edge_angles(&self) -> Vec<(u64, u64, f64)> {
+        let areas = self.face_areas();
+        let centers: Vec<Pt3> = self
+            .faces
+            .iter()
+            .map(|face| {
+                let sum = face.iter().fold(Pt3::new(0.0, 0.0, 0.0), |sum, &i| {
+                    sum + self.points[i as usize]
+                });
+                sum / face.len().max(1) as f64
+            })
+            .collect();
+        let mut edges: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                edges.entry((a.min(b), a.max(b))).or_default().push(f);
+            }
+        }
+        let mut angles: Vec<(u64, u64, f64)> = edges
+            .into_iter()
+            .filter(|(_, faces)| faces.len() == 2)
+            .map(|((a, b), faces)| {
+                let (n0, n1) = (areas[faces[0]], areas[faces[1]]);
+                if n0.len() == 0.0 || n1.len() == 0.0 {
+                    return (a, b, 0.0);
+                }
+                let (n0, n1) = (n0.normalized(), n1.normalized());
+                let angle = n0.dot(n1).clamp(-1.0, 1.0).acos().to_degrees();
+                // the other face is below the plane of a convex edge
+                if (centers[faces[1]] - centers[faces[0]]).dot(n0) > 0.0 {
+                    (a, b, -angle)
+                } else {
+                    (a, b, angle)
+                }
+            })
+            .collect();
+        angles.sort_by_key(|&(a, b, _)| (a, b));
+        angles
+    }
+
+    /// The edges where the surface bends sharply.
+    ///
+    /// Sharp convex edges print as thin ridges and sharp concave edges trap
+    /// support, both may need a fillet or chamfer.
+    ///
+    /// min_degrees: The smallest bend, convex or concave, that is reported.
+    ///
+    /// return: The points of each sharp edge and its angle in degrees, positive when convex.
+    pub fn sharp_edges(&self, min_degrees: f64) -> Vec<(u64, u64, f64)> {
+        self.edge_angles()
+            .into_iter()
+            .filter(|&(_, _, angle)| angle.abs() >= min_degrees)
+            .collect()
+    }
+
+    /// The draft angle of each face relative to the build direction.
+    ///
+    /// build_dir: The direction the part grows while printing, usually +Z.
+    ///
+    /// return: The angle in degrees of each face from the build direction, 0 for
+    /// walls, 90 for faces pointing along build_dir and -90 for faces pointing
+    /// against it.
+    pub fn draft_angles(&self, build_dir: Pt3) -> Vec<f64> {
+        let up = build_dir.normalized();
+        self.face_areas()
+            .into_iter()
+            .map(|area| {
+                if area.len() == 0.0 {
+                    0.0
+                } else {
+                    area.normalized()
+                        .dot(up)
+                        .clamp(-1.0, 1.0)
+                        .asin()
+                        .to_degrees()
+                }
+            })
+            .collect()
+    }
+
+    /// The faces that overhang more than a printer can bridge.
+    ///
+    /// Faces pointing against build_dir that lie on the lowest layer rest on the
+    /// build plate and are not overhangs.
+    ///
+    /// build_dir: The direction the part grows while printing, usually +Z.
+    ///
+    /// max_angle: The largest printable overhang in degrees from vertical, e.g. 45.
+    ///
+    /// return: The indices of the overhanging faces.
+    pub fn overhangs(&self, build_dir: Pt3, max_angle: f64) -> Vec<usize> {
+        let up = build_dir.normalized();
+        let bed = self
+            .points
+            .iter()
+            .map(|p| p.dot(up))
+            .fold(f64::INFINITY, f64::min);
+        self.draft_angles(build_dir)
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, draft)| {
+                let on_bed = self.faces[i]
+                    .iter()
+                    .all(|&p| self.points[p as usize].dot(up) - bed < 1.0e-9);
+                -draft > max_angle && !on_bed
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Marks the overhanging faces for previewing.
+    ///
+    /// build_dir: The direction the part grows while printing, usually +Z.
+    ///
+    /// max_angle: The largest printable overhang in degrees from vertical.
+    ///
+    /// return: An open surface of the overhanging faces in red, empty when there
+    /// are none.
+    pub fn overhang_marker(&self, build_dir: Pt3, max_angle: f64) -> Polyhedron {
+        let mut faces = Faces::new();
+        for i in self.overhangs(build_dir, max_angle) {
+            faces.push(self.faces[i].clone());
+        }
+        let mut marker = Polyhedron::new(self.points.clone(), faces);
+        marker.set_group(1).set_group_color(1, ScadColor::Red);
+        marker
+    }
+
+    /// The silhouette of the polyhedron seen along a direction.
+    ///
+    /// This is projection(cut=false) computed in Rust, e.g. to make a gasket or
+    /// a drill template that matches a part. The faces facing the viewer are
+    /// flattened and joined so the mesh should be closed.
+    ///
+    /// direction: The direction of view. Looking along it the X of the outline
+    /// is to the right and Y is up, -Z gives the X and Y of the polyhedron.
+    ///
+    /// return: The clockwise outlines and counter clockwise holes of the silhouette.
+    pub fn project_outline(&self, direction: Pt3) -> Vec<Pt2s> {
+        let d = direction.normalized();
+        let right = if d.x.abs() < 1.0e-9 && d.y.abs() < 1.0e-9 {
+            d.cross(Pt3::new(0.0, 1.0, 0.0)).normalized()
+        } else {
+            d.cross(Pt3::new(0.0, 0.0, 1.0)).normalized()
+        };
+        let up = right.cross(d);
+
+        let mut pieces: Vec<Vec<Pt2s>> = Vec::new();
+        for (face, area) in self.faces.iter().zip(self.face_areas()) {
+            if area.dot(d) >= 0.0 {
+                continue;
+            }
+            let mut outline = Pt2s::from_pt2s(
+                face.iter()
+                    .map(|&i| {
+                        let p = self.points[i as usize];
+                        Pt2::new(p.dot(right), p.dot(up))
+                    })
+                    .collect(),
+            );
+            let signed_area = clip::signed_area(&outline);
+            if signed_area.abs() < 1.0e-12 {
+                continue;
+            }
+            if signed_area > 0.0 {
+                outline.reverse();
+            }
+            pieces.push(vec![outline]);
+        }
+        // joining in pairs keeps the polygons being joined small
+        while pieces.len() > 1 {
+            let mut joined = Vec::with_capacity(pieces.len().div_ceil(2));
+            for pair in pieces.chunks(2) {
+                joined.push(match pair {
+                    [a, b] => clip::union(a, b),
+                    [a] => a.clone(),
+                    _ => unreachable!(),
+                });
+            }
+            pieces = joined;
+        }
+        pieces.pop().unwrap_or_default()
+    }
+
+    /// The approximate mean curvature at each point.
+    ///
+    /// Each edge adds its length times its bend in radians, divided by four
+    /// times the area around the point, which is an equal share of the area of
+    /// each face using the point. A sphere of radius r has a curvature of about
+    /// 1 / r everywhere.
+    ///
+    /// return: The curvature of each point, positive when convex. The result can
+    /// be given to set_vertex_scalars to view it as a heat map.
+    pub fn mean_curvature(&self) -> Vec<f64> {
+        let mut point_areas = vec![0.0; self.points.len()];
+        for (face, area) in self.faces.iter().zip(self.face_areas()) {
+            for &i in face.iter() {
+                point_areas[i as usize] += area.len() / face.len() as f64;
+            }
+        }
+        let mut bends = vec![0.0; self.points.len()];
+        for (a, b, angle) in self.edge_angles() {
+            let bend =
+                (self.points[a as usize] - self.points[b as usize]).len() * angle.to_radians();
+            bends[a as usize] += bend;
+            bends[b as usize] += bend;
+        }
+        bends
+            .iter()
+            .zip(point_areas.iter())
+            .map(|(&bend, &area)| if area > 0.0 { bend / (4.0 * area) } else { 0.0 })
+            .collect()
+    }
+
+    /// The center of mass of the enclosed volume, assuming uniform density.
+    pub fn centroid(&self) -> Pt3 {
+        let mut volume = 0.0;
+        let mut moment = Pt3::new(0.0, 0.0, 0.0);
+        self.for_each_triangle(|a, b, c| {
+            let v = -a.dot(b.cross(c)) / 6.0;
+            volume += v;
+            moment += (a + b + c) * (v / 4.0);
+        });
+        moment / volume
+    }
+
+    /// Format the mesh as an ASCII STL file.
+    ///
+    /// name: The name of the solid.
+    ///
+    /// return: The STL text.
+    pub fn to_stl(&self, name: &str) -> String {
+        let mut stl = format!("solid {}\n", name);
+        // STL triangles are counter clockwise from outside
+        self.for_each_triangle(|a, b, c| {
+            let mut normal = (c - a).cross(b - a);
+            if normal.len() > 0.0 {
+                normal.normalize();
+            }
+            stl.push_str(&format!(
+                "facet normal {} {} {}\nouter loop\n",
+                normal.x, normal.y, normal.z
+            ));
+            for p in [a, c, b] {
+                stl.push_str(&format!("vertex {} {} {}\n", p.x, p.y, p.z));
+            }
+            stl.push_str("endloop\nendfacet\n");
+        });
+        stl.push_str(&format!("endsolid {}\n", name));
+        stl
+    }
+
+    /// Save the mesh as an ASCII STL file.
+    ///
+    /// path: The path of the file.
+    pub fn save_stl(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_stl("scad_tree"))
+    }
+
+    /// Set the scalar of each point.
+    ///
+    /// scalars: One value per point.
+    ///
+    /// return: self
+    pub fn set_vertex_scalars(&mut self, scalars: Vec<f64>) -> &mut Self {
+        assert!(scalars.len() == self.points.len());
+        self.vertex_scalars = scalars;
+        self
+    }
+
+    /// The scalar of the point, NaN if the point has none.
+    pub fn vertex_scalar(&self, point: usize) -> f64 {
+        self.vertex_scalars.get(point).copied().unwrap_or(f64::NAN)
+    }
+
+    /// The scalar of each point, empty when the points have none.
+    ///
+    /// May be shorter than the points when points were added after the
+    /// scalars were set, the missing points have a NaN scalar.
+    pub fn vertex_scalars(&self) -> &[f64] {
+        &self.vertex_scalars
+    }
+
+    /// The heat map colors of the vertex scalars.
+    ///
+    /// The lowest scalar is blue and the highest is red, points with a NaN
+    /// scalar are gray.
+    ///
+    /// return: The red, green and blue from 0 to 1 of each point or an empty Vec
+    /// when the points have no scalars.
+    pub fn vertex_colors(&self) -> Vec<Pt3> {
+        let (min, max) = self
+            .vertex_scalars
+            .iter()
+            .filter(|s| s.is_finite())
+            .fold((f64::MAX, f64::MIN), |(min, max), &s| {
+                (min.min(s), max.max(s))
+            });
+        if self.vertex_scalars.is_empty() {
+            return Vec::new();
+        }
+        (0..self.points.len())
+            .map(|i| {
+                let s = self.vertex_scalar(i);
+                if !s.is_finite() {
+                    Pt3::new(0.5, 0.5, 0.5)
+                } else if max > min {
+                    heat_color((s - min) / (max - min))
+                } else {
+                    heat_color(0.0)
+                }
+            })
+            .collect()
+    }
+
+    /// Format the mesh as an ASCII PLY file.
+    ///
+    /// Vertex scalars are written as colors and as the quality property.
+    ///
+    /// return: The PLY text.
+    pub fn to_ply(&self) -> String {
+        let colors = self.vertex_colors();
+        let mut ply = format!(
+            "ply\nformat ascii 1.0\nelement vertex {}\nproperty float x\nproperty float y\nproperty float z\n",
+            self.points.len()
+        );
+        if !colors.is_empty() {
+            ply.push_str(
+                "property uchar red\nproperty uchar green\nproperty uchar blue\nproperty float quality\n",
+            );
+        }
+        ply.push_str(&format!(
+            "element face {}\nproperty list uchar int vertex_indices\nend_header\n",
+            self.faces.len()
+        ));
+        for (i, p) in self.points.iter().enumerate() {
+            ply.push_str(&format!("{} {} {}", p.x, p.y, p.z));
+            if let Some(c) = colors.get(i) {
+                ply.push_str(&format!(
+                    " {} {} {} {}",
+                    (c.x * 255.0).round(),
+                    (c.y * 255.0).round(),
+                    (c.z * 255.0).round(),
+                    self.vertex_scalar(i)
+                ));
+            }
+            ply.push('\n');
+        }
+        // PLY faces are counter clockwise from outside
+        for face in self.faces.iter() {
+            ply.push_str(&face.len().to_string());
+            for i in face.iter().rev() {
+                ply.push_str(&format!(" {}", i));
+            }
+            ply.push('\n');
+        }
+        ply
+    }
+
+    /// Save the mesh as an ASCII PLY file.
+    ///
+    /// path: The path of the file.
+    pub fn save_ply(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_ply())
+    }
+
+    /// Format the mesh as a Wavefront OBJ file.
+    ///
+    /// Vertex scalars are written as colors after the position of each vertex,
+    /// which MeshLab and Blender read.
+    ///
+    /// return: The OBJ text.
+    pub fn to_obj(&self) -> String {
+        let colors = self.vertex_colors();
+        let mut obj = String::new();
+        for (i, p) in self.points.iter().enumerate() {
+            obj.push_str(&format!("v {} {} {}", p.x, p.y, p.z));
+            if let Some(c) = colors.get(i) {
+                obj.push_str(&format!(" {} {} {}", c.x, c.y, c.z));
+            }
+            obj.push('\n');
+        }
+        // OBJ faces are counter clockwise from outside and indexed from 1
+        for face in self.faces.iter() {
+            obj.push('f');
+            for i in face.iter().rev() {
+                obj.push_str(&format!(" {}", i + 1));
+            }
+            obj.push('\n');
+        }
+        obj
+    }
+
+    /// Save the mesh as a Wavefront OBJ file.
+    ///
+    /// path: The path of the file.
+    pub fn save_obj(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_obj())
+    }
+
+    /// Calls f with the points of a fan triangulation of each face.
+    fn for_each_triangle<F: FnMut(Pt3, Pt3, Pt3)>(&self, mut f: F) {
+        for face in self.faces.iter() {
+            for i in 1..face.len().saturating_sub(1) {
+                f(
+                    self.points[face[0] as usize],
+                    self.points[face[i] as usize],
+                    self.points[face[i + 1] as usize],
+                );
+            }
+        }
+    }
+
+    /// Check the mesh for problems that OpenSCAD does not report.
+    ///
+    /// return: The problems found.
+    pub fn validate(&self) -> MeshReport {
+        let mut report = MeshReport::default();
+        let n_points = self.points.len() as u64;
+
+        let mut edges: BTreeMap<(u64, u64), (u64, u64)> = BTreeMap::new();
+        for (face_index, face) in self.faces.iter().enumerate() {
+            if face.iter().any(|&i| i >= n_points) {
+                report.invalid_indices.push(face_index);
+                continue;
+            }
+            let mut unique = face.to_vec();
+            unique.sort_unstable();
+            unique.dedup();
+            let mut normal = Pt3::new(0.0, 0.0, 0.0);
+            for i in 1..face.len().saturating_sub(1) {
+                let a = self.points[face[0] as usize];
+                let b = self.points[face[i] as usize];
+                let c = self.points[face[i + 1] as usize];
+                normal += (b - a).cross(c - a);
+            }
+            if unique.len() < 3 || unique.len() < face.len() || normal.len() < 1.0e-12 {
+                report.degenerate_faces.push(face_index);
+            }
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                let entry = edges.entry((a.min(b), a.max(b))).or_insert((0, 0));
+                if a < b {
+                    entry.0 += 1;
+                } else {
+                    entry.1 += 1;
+                }
+            }
+        }
+        for (&edge, &(forward, backward)) in edges.iter() {
+            if forward + backward != 2 {
+                report.non_manifold_edges.push(edge);
+            } else if forward != 1 {
+                report.inconsistent_edges.push(edge);
+            }
+        }
+
+        let mut order: Vec<usize> = (0..self.points.len()).collect();
+        order.sort_by(|&a, &b| self.points[a].x.total_cmp(&self.points[b].x));
+        for i in 0..order.len() {
+            let a = self.points[order[i]];
+            for &j in order.iter().skip(i + 1) {
+                let b = self.points[j];
+                if b.x - a.x > 1.0e-9 {
+                    break;
+                }
+                if (b - a).len() <= 1.0e-9 {
+                    let pair = (order[i].min(j) as u64, order[i].max(j) as u64);
+                    report.duplicate_points.push(pair);
+                }
+            }
+        }
+        report.duplicate_points.sort_unstable();
+
+        if report.non_manifold_edges.is_empty()
+            && report.inconsistent_edges.is_empty()
+            && report.invalid_indices.is_empty()
+        {
+            report.inverted = self.volume() < 0.0;
+        }
+        report
+    }
+
+    /// Merge points closer than epsilon and remove the faces that collapse.
+    ///
+    /// epsilon: The distance at which points are merged.
+    ///
+    /// return: self
+    pub fn weld(&mut self, epsilon: f64) -> &mut Self {
+        assert!(epsilon > 0.0);
+        let cell = |p: Pt3| {
+            (
+                (p.x / epsilon).floor() as i64,
+                (p.y / epsilon).floor() as i64,
+                (p.z / epsilon).floor() as i64,
+            )
+        };
+        let mut grid: HashMap<(i64, i64, i64), Vec<u64>> = HashMap::new();
+        let mut points = Pt3s::new();
+        let mut scalars = Vec::new();
+        let mut remap = Vec::with_capacity(self.points.len());
+        for (j, &p) in self.points.iter().enumerate() {
+            let (x, y, z) = cell(p);
+            let mut found = None;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        if let Some(candidates) = grid.get(&(x + dx, y + dy, z + dz)) {
+                            for &i in candidates {
+                                if (points[i as usize] - p).len() <= epsilon {
+                                    found = Some(i);
+                                    break 'search;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            let index = found.unwrap_or_else(|| {
+                points.push(p);
+                if !self.vertex_scalars.is_empty() {
+                    scalars.push(self.vertex_scalar(j));
+                }
+                let i = points.len() as u64 - 1;
+                grid.entry((x, y, z)).or_default().push(i);
+                i
+            });
+            remap.push(index);
+        }
+
+        let grouped = !self.face_groups.is_empty();
+        let mut faces = Faces::with_capacity(self.faces.len());
+        let mut face_groups = Vec::with_capacity(self.faces.len());
+        for (i, face) in self.faces.iter().enumerate() {
+            let group = self.face_group(i);
+            let mut indices: Vec<u64> = face.iter().map(|&i| remap[i as usize]).collect();
+            indices.dedup();
+            while indices.len() > 1 && indices[0] == indices[indices.len() - 1] {
+                indices.pop();
+            }
+            if indices.len() > 2 {
+                faces.push(Indices::from_indices(indices));
+                face_groups.push(group);
+            }
+        }
+        self.points = points;
+        if !self.vertex_scalars.is_empty() {
+            self.vertex_scalars = scalars;
+        }
+        self.faces = faces;
+        self.face_groups = if grouped { face_groups } else { Vec::new() };
+        self
+    }
+
+    /// Cap the holes in an open mesh.
+    ///
+    /// The edges used by only one face are chained into loops and each loop is
+    /// triangulated with triangulate3d and wound to match the faces around it.
+    /// Holes are assumed to be roughly planar. New faces are put in group 0.
+    ///
+    /// max_edge_count: Loops with more edges than this are left open.
+    ///
+    /// return: self
+    pub fn fill_holes(&mut self, max_edge_count: usize) -> &mut Self {
+        let mut edge_count: HashMap<(u64, u64), usize> = HashMap::new();
+        for face in self.faces.iter() {
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                *edge_count.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        // the cap runs each boundary edge backwards
+        let mut next: HashMap<u64, Vec<u64>> = HashMap::new();
+        for face in self.faces.iter() {
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                if edge_count[&(a.min(b), a.max(b))] == 1 {
+                    next.entry(b).or_default().push(a);
+                }
+            }
+        }
+        let mut starts: Vec<u64> = next.keys().copied().collect();
+        starts.sort_unstable();
+
+        let mut caps = Vec::new();
+        for start in starts {
+            while next.get(&start).is_some_and(|n| !n.is_empty()) {
+                let mut ring = vec![start];
+                let mut current = start;
+                let closed = loop {
+                    let Some(v) = next.get_mut(&current).and_then(|n| n.pop()) else {
+                        break false;
+                    };
+                    if v == start {
+                        break true;
+                    }
+                    ring.push(v);
+                    current = v;
+                };
+                if closed && ring.len() >= 3 && ring.len() <= max_edge_count {
+                    caps.push(ring);
+                }
+            }
+        }
+
+        for ring in caps {
+            let points = Pt3s::from_pt3s(ring.iter().map(|&i| self.points[i as usize]).collect());
+            let mut normal = Pt3::new(0.0, 0.0, 0.0);
+            for i in 0..points.len() {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                normal += Pt3::new(
+                    (a.y - b.y) * (a.z + b.z),
+                    (a.z - b.z) * (a.x + b.x),
+                    (a.x - b.x) * (a.y + b.y),
+                );
+            }
+            let triangles = triangulate3d(&points, normal.normalized());
+            for t in triangles.chunks(3) {
+                let mut face = vec![
+                    ring[t[0] as usize],
+                    ring[t[1] as usize],
+                    ring[t[2] as usize],
+                ];
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| points[i as usize]);
+                // the ring order is the winding the cap needs
+                if (b - a).cross(c - a).dot(normal) < 0.0 {
+                    face.reverse();
+                }
+                self.faces.push(Indices::from_indices(face));
+                if !self.face_groups.is_empty() {
+                    self.face_groups.push(0);
+                }
+            }
+        }
+        self
+    }
+
+    /// Make the winding of all faces consistent and facing outward.
+    ///
+    /// Faces are flipped to agree with their neighbors, then each connected
+    /// piece of the mesh is turned inside out if it encloses a negative volume.
+    ///
+    /// return: self
+    pub fn fix_winding(&mut self) -> &mut Self {
+        let mut edge_faces: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+        for (face_index, face) in self.faces.iter().enumerate() {
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                edge_faces
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push(face_index);
+            }
+        }
+        let runs_forward = |face: &Indices, a: u64, b: u64| {
+            (0..face.len()).any(|i| face[i] == a && face[(i + 1) % face.len()] == b)
+        };
+
+        let mut visited = vec![false; self.faces.len()];
+        for start in 0..self.faces.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut component = vec![start];
+            let mut queue = std::collections::VecDeque::from([start]);
+            while let Some(face_index) = queue.pop_front() {
+                let face = self.faces[face_index].clone();
+                for i in 0..face.len() {
+                    let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                    for &other in edge_faces[&(a.min(b), a.max(b))].iter() {
+                        if visited[other] {
+                            continue;
+                        }
+                        visited[other] = true;
+                        if runs_forward(&self.faces[other], a, b) {
+                            self.faces[other].reverse();
+                        }
+                        component.push(other);
+                        queue.push_back(other);
+                    }
+                }
+            }
+
+            let mut volume = 0.0;
+            for &face_index in component.iter() {
+                let face = &self.faces[face_index];
+                for i in 1..face.len().saturating_sub(1) {
+                    let a = self.points[face[0] as usize];
+                    let b = self.points[face[i] as usize];
+                    let c = self.points[face[i + 1] as usize];
+                    volume -= a.dot(b.cross(c));
+                }
+            }
+            if volume < 0.0 {
+                for &face_index in component.iter() {
+                    self.faces[face_index].reverse();
+                }
+            }
+        }
+        self
     }
 
     /// Translate the polyhedron.
@@ -87,73 +1159,57 @@ impl Polyhedron {
         }
 
         let mut faces = Faces::with_capacity((points.len() - 2) * 2 + points.len());
-        for i in (0..indices.len()).step_by(3) {
-            faces.push(Indices::from_indices(vec![
-                indices[i],
-                indices[i + 1],
-                indices[i + 2],
-            ]));
-        }
+        faces.append_offset(Faces::from_triangles(&indices), 0);
 
         let mut end_points = points.iter().map(|p| p.as_pt3(height)).collect();
         vertices.append(&mut end_points);
         let indices = triangulate2d(points);
-        for i in (0..indices.len()).step_by(3) {
-            faces.push(Indices::from_indices(vec![
-                indices[i] + points.len() as u64,
-                indices[i + 1] + points.len() as u64,
-                indices[i + 2] + points.len() as u64,
-            ]));
-        }
+        faces.append_offset(Faces::from_triangles(&indices), points.len() as u64);
 
-        for i in 0..points.len() {
-            let p0 = i;
-            let p1 = (i + 1) % points.len();
-            let p2 = (i + 1) % points.len() + points.len();
-            let p3 = i + points.len();
-
-            faces.push(Indices::from_indices(vec![
-                p0 as u64, p1 as u64, p2 as u64, p3 as u64,
-            ]));
-        }
+        let n = points.len() as u64;
+        let lower: Vec<u64> = (0..n).collect();
+        let upper: Vec<u64> = (n..n * 2).collect();
+        faces.push_strip(&lower, &upper);
 
-        Polyhedron {
-            points: vertices,
-            faces,
-        }
+        Polyhedron::new(vertices, faces)
     }
 
     /// Extrude a 2D profile into a polyhedron.
     ///
+    /// A full 360 degree turn joins the last segment back to the first profile,
+    /// so the ring is closed without a duplicated seam.
+    ///
     /// Most of the time you want the rotate_extrude macro instead of this.
     pub fn rotate_extrude(profile: &Pt2s, degrees: f64, segments: usize) -> Self {
-        assert!((0.0..360.0).contains(&degrees));
+        assert!((0.0..=360.0).contains(&degrees));
         assert!(segments >= 3);
         let not_closed = degrees != 360.0;
         let profile: Pt3s =
             Pt3s::from_pt3s(profile.iter().map(|p| Pt3::new(p.x, 0.0, p.y)).collect());
         let profile_len = profile.len();
         let a = degrees / segments as f64;
-        let mut points = profile.clone();
+        let n_rings = if not_closed { segments + 1 } else { segments };
+        let points = parallel::rings(n_rings, profile_len, |segment, points| {
+            if segment == 0 {
+                points.extend(profile.iter());
+                return;
+            }
+            let s = dsin(a * segment as f64);
+            let c = dcos(a * segment as f64);
+            for p in profile.iter() {
+                points.push(Pt3::new(p.x * c, p.x * s, p.z));
+            }
+        });
         let mut faces = Faces::new();
 
         if not_closed {
             // triangulate the starting face
             let triangles = triangulate3d(&profile, Pt3::new(0.0, -1.0, 0.0));
-            for i in (0..triangles.len()).step_by(3) {
-                faces.push(Indices::from_indices(vec![
-                    triangles[i] as u64,
-                    triangles[i + 1] as u64,
-                    triangles[i + 2] as u64,
-                ]));
-            }
+            faces.append_offset(Faces::from_triangles(&triangles), 0);
         }
 
         for segment in 1..segments {
-            let s = dsin(a * segment as f64);
-            let c = dcos(a * segment as f64);
             for p in 0..profile_len {
-                points.push(Pt3::new(profile[p].x * c, profile[p].x * s, profile[p].z));
                 let p0 = (segment - 1) * profile_len + p;
                 let p1 = (segment - 1) * profile_len + ((p + 1) % profile_len);
                 let p2 = segment * profile_len + ((p + 1) % profile_len);
@@ -165,10 +1221,7 @@ impl Polyhedron {
         }
 
         if not_closed {
-            let s = dsin(a * segments as f64);
-            let c = dcos(a * segments as f64);
             for p in 0..profile_len {
-                points.push(Pt3::new(profile[p].x * c, profile[p].x * s, profile[p].z));
                 let p0 = (segments - 1) * profile_len + p;
                 let p1 = (segments - 1) * profile_len + ((p + 1) % profile_len);
                 let p2 = segments * profile_len + ((p + 1) % profile_len);
@@ -179,13 +1232,10 @@ impl Polyhedron {
             }
             let nml = Pt3::new(0.0, -1.0, 0.0).rotated_z(degrees + 180.0);
             let triangles = triangulate3d_rev(&profile, nml);
-            for i in (0..triangles.len()).step_by(3) {
-                faces.push(Indices::from_indices(vec![
-                    triangles[i] as u64 + (segments * profile_len) as u64,
-                    triangles[i + 1] as u64 + (segments * profile_len) as u64,
-                    triangles[i + 2] as u64 + (segments * profile_len) as u64,
-                ]));
-            }
+            faces.append_offset(
+                Faces::from_triangles(&triangles),
+                (segments * profile_len) as u64,
+            );
         } else {
             for p in 0..profile_len {
                 let p0 = (segments - 1) * profile_len + p;
@@ -197,7 +1247,7 @@ impl Polyhedron {
                 ]));
             }
         }
-        Polyhedron { points, faces }
+        Polyhedron::new(points, faces)
     }
 
     /// Create a Polyhedron by connecting two 2D profiles.
@@ -222,33 +1272,147 @@ impl Polyhedron {
 
         let mut faces = Faces::with_capacity((n_pts - 2) * 2 + n_pts);
         let indices = triangulate2d_rev(lower_profile);
-        for i in (0..indices.len()).step_by(3) {
-            faces.push(Indices::from_indices(vec![
-                indices[i],
-                indices[i + 1],
-                indices[i + 2],
-            ]));
-        }
+        faces.append_offset(Faces::from_triangles(&indices), 0);
 
         let indices = triangulate2d(upper_profile);
-        for i in (0..indices.len()).step_by(3) {
-            faces.push(Indices::from_indices(vec![
-                indices[i] + n_pts as u64,
-                indices[i + 1] + n_pts as u64,
-                indices[i + 2] + n_pts as u64,
-            ]));
-        }
+        faces.append_offset(Faces::from_triangles(&indices), n_pts as u64);
 
-        for i in 0..n_pts {
-            faces.push(Indices::from_indices(vec![
-                i as u64,
-                ((i + 1) % n_pts) as u64,
-                ((i + 1) % n_pts + n_pts) as u64,
-                (i + n_pts) as u64,
-            ]));
+        let n = n_pts as u64;
+        let lower: Vec<u64> = (0..n).collect();
+        let upper: Vec<u64> = (n..n * 2).collect();
+        faces.push_strip(&lower, &upper);
+
+        Polyhedron::new(points, faces)
+    }
+
+    /// Sweeps a 2D profile along a path of 3D points with extra control over
+    /// the orientation, size and ends of the profile.
+    ///
+    /// The profile X axis points along the side vector of the frame and the Y
+    /// axis along the up vector.
+    ///
+    /// profile: The clockwise profile.
+    ///
+    /// path: The path of the profile origin.
+    ///
+    /// options: The twist, scales, caps and frame of the sweep.
+    ///
+    /// return: The polyhedron.
+    pub fn sweep_with_options(profile: &Pt2s, path: &Pt3s, options: &SweepOptions) -> Self {
+        let profile_len = profile.len();
+        let path_len = path.len();
+        assert!(profile_len > 2 && path_len > 1);
+        assert!(options.scales.is_empty() || options.scales.len() == path_len);
+        let closed = options.closed;
+        let twist_angle = if closed {
+            options.twist_degrees / path_len as f64
+        } else {
+            options.twist_degrees / (path_len - 1) as f64
+        };
+
+        let tangents: Vec<Pt3> = (0..path_len)
+            .map(|i| {
+                let prev = if i > 0 {
+                    path[i - 1]
+                } else if closed {
+                    path[path_len - 1]
+                } else {
+                    path[0]
+                };
+                let next = if i < path_len - 1 {
+                    path[i + 1]
+                } else if closed {
+                    path[0]
+                } else {
+                    path[path_len - 1]
+                };
+                (next - prev).normalized()
+            })
+            .collect();
+        let sides = sweep_sides(path, &tangents, options.frame);
+
+        let points = parallel::rings(path_len, profile_len, |i, points| {
+            let side = sides[i];
+            let up = tangents[i].cross(side);
+            let scale = if options.scales.is_empty() {
+                1.0
+            } else {
+                options.scales[i]
+            };
+            for p in profile.iter() {
+                let p = p.rotated(twist_angle * i as f64) * scale;
+                points.push(path[i] + side * p.x + up * p.y);
+            }
+        });
+
+        let mut faces = Faces::with_capacity(profile_len * path_len + 2);
+        let rings: Vec<Vec<u64>> = (0..path_len)
+            .map(|i| {
+                let a = (i * profile_len) as u64;
+                (a..a + profile_len as u64).collect()
+            })
+            .collect();
+        let n_strips = if closed { path_len } else { path_len - 1 };
+        for i in 0..n_strips {
+            faces.push_strip(&rings[i], &rings[(i + 1) % path_len]);
+        }
+        if !closed && options.caps {
+            let indices = triangulate2d_rev(profile);
+            faces.append_offset(Faces::from_triangles(&indices), 0);
+            let offset = ((path_len - 1) * profile_len) as u64;
+            let indices = triangulate2d(profile);
+            faces.append_offset(Faces::from_triangles(&indices), offset);
         }
+        Polyhedron::new(points, faces)
+    }
 
-        Polyhedron { points, faces }
+    /// Sweeps a 2D profile with holes along a path of 3D points.
+    ///
+    /// Each loop of the profile is swept with sweep_with_options and the ends are
+    /// capped with the holes left open.
+    ///
+    /// profile: The profile to sweep.
+    ///
+    /// path: The path that the profile follows.
+    ///
+    /// options: The sweep options.
+    ///
+    /// return: The polyhedron.
+    pub fn sweep_profile(profile: &dim2::Profile, path: &Pt3s, options: &SweepOptions) -> Self {
+        let profile = profile.normalized();
+        let open = SweepOptions {
+            caps: false,
+            ..options.clone()
+        };
+        let path_len = path.len() as u64;
+        let mut result = Polyhedron::new(Pt3s::new(), Faces::new());
+        // where the first ring of each loop starts and its length
+        let mut loops = Vec::new();
+        for l in profile.loops() {
+            loops.push((result.points.len() as u64, l.len() as u64));
+            result.merge(Polyhedron::sweep_with_options(l, path, &open));
+        }
+        if !options.closed && options.caps {
+            let index = |i: u64, ring: u64| {
+                let mut i = i;
+                for (start, len) in loops.iter() {
+                    if i < *len {
+                        return start + ring * len + i;
+                    }
+                    i -= len;
+                }
+                unreachable!()
+            };
+            for triangle in profile.triangulate().chunks_exact(3) {
+                result.faces.push(Indices::from_indices(
+                    triangle.iter().rev().map(|i| index(*i, 0)).collect(),
+                ));
+                result.faces.push(Indices::from_indices(
+                    triangle.iter().map(|i| index(*i, path_len - 1)).collect(),
+                ));
+            }
+        }
+        result
     }
 
     /// Sweeps a 2D profile along a path of 3D points to make a polyhedron.
@@ -276,24 +1440,24 @@ impl Polyhedron {
         }
         if !closed {
             let indices = triangulate3d_rev(&profile, path[1] - path[0]);
-            for i in (0..indices.len()).step_by(3) {
-                faces.push(Indices::from_indices(vec![
-                    indices[i],
-                    indices[i + 1],
-                    indices[i + 2],
-                ]));
-            }
+            faces.append_offset(Faces::from_triangles(&indices), 0);
         }
 
-        for path_index in 1..path_len - 1 {
+        let middle = parallel::rings(path_len - 2, profile_len, |i, points| {
+            let path_index = i + 1;
             let m = Mt4::look_at_matrix_lh(
                 path[path_index - 1],
                 path[path_index + 1],
                 Pt3::new(0.0, 0.0, 1.0),
             );
-            for profile_index in 0..profile_len {
-                let point = profile[profile_index].rotated_z(twist_angle * path_index as f64);
+            for p in profile.iter() {
+                let point = p.rotated_z(twist_angle * path_index as f64);
                 points.push((m * point.as_pt4(0.0)).as_pt3() + path[path_index]);
+            }
+        });
+        points.extend(middle.iter());
+        for path_index in 1..path_len - 1 {
+            for profile_index in 0..profile_len {
                 let p0 = (path_index - 1) * profile_len + profile_index;
                 let p1 = (path_index - 1) * profile_len + ((profile_index + 1) % profile_len);
                 let p2 = path_index * profile_len + ((profile_index + 1) % profile_len);
@@ -330,13 +1494,10 @@ impl Polyhedron {
 
         if !closed {
             let indices = triangulate3d(&last_points, path[path_len - 1] - path[path_len - 2]);
-            for i in (0..indices.len()).step_by(3) {
-                faces.push(Indices::from_indices(vec![
-                    indices[i] + points.len() as u64 - profile_len as u64,
-                    indices[i + 1] + points.len() as u64 - profile_len as u64,
-                    indices[i + 2] + points.len() as u64 - profile_len as u64,
-                ]));
-            }
+            faces.append_offset(
+                Faces::from_triangles(&indices),
+                points.len() as u64 - profile_len as u64,
+            );
         } else {
             for profile_index in 0..profile_len {
                 let p0 = (path_len - 1) * profile_len + profile_index;
@@ -349,13 +1510,327 @@ impl Polyhedron {
             }
         }
 
-        Self { points, faces }
+        Self::new(points, faces)
+    }
+
+    /// Extrude a 2D profile with holes into a polyhedron.
+    ///
+    /// profile: The profile to extrude.
+    ///
+    /// height: The height of the extrusion.
+    ///
+    /// return: The polyhedron.
+    pub fn linear_extrude_profile(profile: &dim2::Profile, height: f64) -> Polyhedron {
+        let profile = profile.normalized();
+        let points = profile.points();
+        let n = points.len() as u64;
+        let mut vertices = Pt3s::with_capacity(points.len() * 2);
+        vertices.extend(points.iter().map(|p| p.as_pt3(0.0)));
+        vertices.extend(points.iter().map(|p| p.as_pt3(height)));
+
+        let triangles = profile.triangulate();
+        let mut faces = Faces::from_triangles(&triangles);
+        for face in faces.iter_mut() {
+            face.reverse();
+        }
+        faces.append_offset(Faces::from_triangles(&triangles), n);
+        let mut offset = 0;
+        for l in profile.loops() {
+            let lower: Vec<u64> = (offset..offset + l.len() as u64).collect();
+            let upper: Vec<u64> = lower.iter().map(|i| i + n).collect();
+            faces.push_strip(&lower, &upper);
+            offset += l.len() as u64;
+        }
+        Self::new(vertices, faces)
     }
 
     /// Create a cylinder polyhedron.
     pub fn cylinder(radius: f64, height: f64, segments: u64) -> Self {
         Self::linear_extrude(&dim2::circle(radius, segments), height)
     }
+
+    /// Create a solid spherical cap with a flat base on the XY plane.
+    ///
+    /// radius: The radius of the base.
+    ///
+    /// depth: The height of the cap, may be larger than radius.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The cap.
+    pub fn dish(radius: f64, depth: f64, segments: u64) -> Self {
+        assert!(radius > 0.0 && depth > 0.0);
+        let sphere_radius = (radius * radius + depth * depth) / (2.0 * depth);
+        let max_degrees = radius.atan2(sphere_radius - depth).to_degrees();
+        let rings = (segments / 4).max(1);
+        let profile: Vec<(f64, f64)> = (1..=rings)
+            .map(|i| {
+                let a = max_degrees * i as f64 / rings as f64;
+                (
+                    sphere_radius * dsin(a),
+                    depth - sphere_radius + sphere_radius * dcos(a),
+                )
+            })
+            .collect();
+        Self::lathe(depth, &profile, segments)
+    }
+
+    /// Create a solid paraboloid cap with a flat base on the XY plane.
+    ///
+    /// radius: The radius of the base.
+    ///
+    /// depth: The height of the cap.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The cap.
+    pub fn paraboloid_cap(radius: f64, depth: f64, segments: u64) -> Self {
+        assert!(radius > 0.0 && depth > 0.0);
+        let rings = (segments / 4).max(1);
+        let profile: Vec<(f64, f64)> = (1..=rings)
+            .map(|i| {
+                let t = i as f64 / rings as f64;
+                (radius * t, depth * (1.0 - t * t))
+            })
+            .collect();
+        Self::lathe(depth, &profile, segments)
+    }
+
+    /// Create a solid half ellipsoid with a flat base on the XY plane.
+    ///
+    /// radius: The radius of the base.
+    ///
+    /// depth: The height of the cap.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The cap.
+    pub fn ellipsoid_cap(radius: f64, depth: f64, segments: u64) -> Self {
+        assert!(radius > 0.0 && depth > 0.0);
+        let rings = (segments / 4).max(1);
+        let profile: Vec<(f64, f64)> = (1..=rings)
+            .map(|i| {
+                let a = 90.0 * i as f64 / rings as f64;
+                (radius * dsin(a), depth * dcos(a))
+            })
+            .collect();
+        Self::lathe(depth, &profile, segments)
+    }
+
+    /// Revolve a profile around the Z axis closing it with a point at the top
+    /// and a flat base.
+    ///
+    /// top: The height of the point on the Z axis.
+    ///
+    /// profile: The (radius, height) of each ring from the top down to the base.
+    ///
+    /// segments: The number of segments in a circle.
+    fn lathe(top: f64, profile: &[(f64, f64)], segments: u64) -> Self {
+        assert!(segments >= 3);
+        let mut points = Pt3s::with_capacity(profile.len() * segments as usize + 2);
+        points.push(Pt3::new(0.0, 0.0, top));
+        for (r, z) in profile.iter() {
+            for j in 0..segments {
+                let a = 360.0 * j as f64 / segments as f64;
+                points.push(Pt3::new(r * dcos(a), r * dsin(a), *z));
+            }
+        }
+        let bottom = points.len() as u64;
+        points.push(Pt3::new(0.0, 0.0, profile[profile.len() - 1].1));
+
+        let rings: Vec<Vec<u64>> = (0..profile.len() as u64)
+            .map(|k| (0..segments).map(|j| 1 + k * segments + j).collect())
+            .collect();
+        let mut faces = Faces::with_capacity((profile.len() + 1) * segments as usize);
+        let top_ring: Vec<u64> = rings[0].iter().rev().copied().collect();
+        faces.push_fan(0, &top_ring);
+        for k in 0..rings.len() - 1 {
+            faces.push_strip(&rings[k], &rings[k + 1]);
+        }
+        faces.push_fan(bottom, &rings[rings.len() - 1]);
+        Self::new(points, faces)
+    }
+}
+
+/// A clockwise ring around a box with rounded corners.
+///
+/// size: The size of the box.
+///
+/// inset: The distance from the sides of the box to the centers of the corners.
+///
+/// radius: The radius of the corners, the ring is inset - radius inside the box.
+///
+/// corner_segments: The number of segments in each corner.
+///
+/// z: The height of the ring.
+fn box_ring(size: Pt2, inset: f64, radius: f64, corner_segments: u64, z: f64) -> Pt3s {
+    let cx = size.x / 2.0 - inset;
+    let cy = size.y / 2.0 - inset;
+    let mut ring = Pt3s::with_capacity(4 * (corner_segments as usize + 1));
+    for (i, (x, y)) in [(cx, cy), (cx, -cy), (-cx, -cy), (-cx, cy)]
+        .iter()
+        .enumerate()
+    {
+        for j in 0..=corner_segments {
+            let a = 90.0 - 90.0 * i as f64 - 90.0 * j as f64 / corner_segments as f64;
+            ring.push(Pt3::new(x + radius * dcos(a), y + radius * dsin(a), z));
+        }
+    }
+    ring
+}
+
+/// The color of t from 0 to 1 on a blue, cyan, green, yellow, red heat map.
+fn heat_color(t: f64) -> Pt3 {
+    let t = t.clamp(0.0, 1.0) * 4.0;
+    match t as u32 {
+        0 => Pt3::new(0.0, t, 1.0),
+        1 => Pt3::new(0.0, 1.0, 2.0 - t),
+        2 => Pt3::new(t - 2.0, 1.0, 0.0),
+        _ => Pt3::new(1.0, 4.0 - t, 0.0),
+    }
+}
+
+/// Connects clockwise rings of the same length from the bottom up and caps the ends.
+///
+/// Points that fall on top of each other are welded.
+fn stack_rings(rings: &[Pt3s], center: bool) -> Polyhedron {
+    assert!(rings.len() > 1);
+    let n = rings[0].len() as u64;
+    let mut points = Pt3s::with_capacity(rings.len() * n as usize + 2);
+    let mut faces = Faces::with_capacity((rings.len() + 1) * n as usize);
+    let mut indices: Vec<Vec<u64>> = Vec::with_capacity(rings.len());
+    for ring in rings.iter() {
+        assert!(ring.len() as u64 == n);
+        indices.push((points.len() as u64..points.len() as u64 + n).collect());
+        for p in ring.iter() {
+            points.push(*p);
+        }
+    }
+    for k in 0..indices.len() - 1 {
+        faces.push_strip(&indices[k], &indices[k + 1]);
+    }
+    let mut cap = |ring: &Pt3s, ring_indices: &[u64], reverse: bool| {
+        let center = ring.iter().fold(Pt3::new(0.0, 0.0, 0.0), |a, p| a + *p) / n as f64;
+        points.push(center);
+        let mut ring_indices = ring_indices.to_vec();
+        if reverse {
+            ring_indices.reverse();
+        }
+        faces.push_fan(points.len() as u64 - 1, &ring_indices);
+    };
+    cap(&rings[0], &indices[0], true);
+    cap(&rings[rings.len() - 1], &indices[indices.len() - 1], false);
+    let mut result = Polyhedron::new(points, faces);
+    result.weld(1.0e-9);
+    if center {
+        let height = rings[rings.len() - 1][0].z - rings[0][0].z;
+        result.translate(Pt3::new(0.0, 0.0, -height / 2.0));
+    }
+    result
+}
+
+/// Create a box with 45 degree chamfers on all edges.
+///
+/// size: The size of the box, centered on the Z axis.
+///
+/// chamfer: The size of the chamfers.
+///
+/// center: Center the box vertically.
+///
+/// return: The box.
+pub fn chamfered_cube(size: Pt3, chamfer: f64, center: bool) -> Polyhedron {
+    assert!(chamfer > 0.0 && chamfer * 2.0 <= size.x.min(size.y).min(size.z));
+    let plan = Pt2::new(size.x, size.y);
+    stack_rings(
+        &[
+            box_ring(plan, chamfer, 0.0, 1, 0.0),
+            box_ring(plan, chamfer, chamfer, 1, chamfer),
+            box_ring(plan, chamfer, chamfer, 1, size.z - chamfer),
+            box_ring(plan, chamfer, 0.0, 1, size.z),
+        ],
+        center,
+    )
+}
+
+/// Create a box with all edges and corners rounded.
+///
+/// size: The size of the box, centered on the Z axis.
+///
+/// radius: The radius of the rounded edges.
+///
+/// segments: The number of segments in a circle.
+///
+/// center: Center the box vertically.
+///
+/// return: The box.
+pub fn rounded_cube(size: Pt3, radius: f64, segments: u64, center: bool) -> Polyhedron {
+    assert!(radius > 0.0 && radius * 2.0 <= size.x.min(size.y).min(size.z));
+    let plan = Pt2::new(size.x, size.y);
+    let corner_segments = (segments / 4).max(1);
+    let mut rings = Vec::with_capacity(2 * corner_segments as usize + 2);
+    for i in 0..=corner_segments {
+        let a = 90.0 * i as f64 / corner_segments as f64;
+        rings.push(box_ring(
+            plan,
+            radius,
+            radius * dsin(a),
+            corner_segments,
+            radius - radius * dcos(a),
+        ));
+    }
+    for i in 0..=corner_segments {
+        let a = 90.0 - 90.0 * i as f64 / corner_segments as f64;
+        rings.push(box_ring(
+            plan,
+            radius,
+            radius * dsin(a),
+            corner_segments,
+            size.z - radius + radius * dcos(a),
+        ));
+    }
+    stack_rings(&rings, center)
+}
+
+/// Create a cylinder with 45 degree chamfers on the top and bottom edges.
+///
+/// The polyhedron version of Scad::chamfered_cylinder.
+///
+/// radius: The radius of the cylinder.
+///
+/// height: The height of the cylinder.
+///
+/// chamfer: The size of the chamfers.
+///
+/// segments: The number of segments in a circle.
+///
+/// center: Center the cylinder vertically.
+///
+/// return: The cylinder.
+pub fn chamfered_cylinder(
+    radius: f64,
+    height: f64,
+    chamfer: f64,
+    segments: u64,
+    center: bool,
+) -> Polyhedron {
+    assert!(chamfer > 0.0 && chamfer < radius && chamfer * 2.0 <= height);
+    let ring = |r: f64, z: f64| {
+        Pt3s::from_pt3s(
+            dim2::circle(r, segments)
+                .iter()
+                .map(|p| p.as_pt3(z))
+                .collect(),
+        )
+    };
+    stack_rings(
+        &[
+            ring(radius - chamfer, 0.0),
+            ring(radius, chamfer),
+            ring(radius, height - chamfer),
+            ring(radius - chamfer, height),
+        ],
+        center,
+    )
 }
 
 /// Yeilds the points of a quadratic bezier.
@@ -389,6 +1864,237 @@ pub fn cubic_bezier(start: Pt3, control1: Pt3, control2: Pt3, end: Pt3, segments
     points
 }
 
+/// Yields the points of a helix around the Z axis.
+///
+/// The helix starts on the X axis at z = 0 and turns counter clockwise seen from
+/// above, which is a right hand helix.
+///
+/// radius: The radius of the helix.
+///
+/// pitch: The rise of the helix per turn.
+///
+/// turns: The number of turns, may be fractional.
+///
+/// segments: The number of segments per turn.
+///
+/// return: The points of the helix.
+pub fn helix(radius: f64, pitch: f64, turns: f64, segments: u64) -> Pt3s {
+    conical_spiral(radius, radius, pitch, turns, segments)
+}
+
+/// Yields the points of a spiral around the Z axis whose radius changes linearly.
+///
+/// A pitch of 0 gives a flat Archimedean spiral.
+///
+/// radius1: The radius at the start.
+///
+/// radius2: The radius at the end.
+///
+/// pitch: The rise of the spiral per turn.
+///
+/// turns: The number of turns, may be fractional.
+///
+/// segments: The number of segments per turn.
+///
+/// return: The points of the spiral.
+pub fn conical_spiral(radius1: f64, radius2: f64, pitch: f64, turns: f64, segments: u64) -> Pt3s {
+    assert!(turns > 0.0 && segments >= 3);
+    let n = ((turns * segments as f64).round() as u64).max(1);
+    let mut points = Pt3s::with_capacity(n as usize + 1);
+    for i in 0..(n + 1) {
+        let t = i as f64 / n as f64;
+        let degrees = 360.0 * turns * t;
+        let radius = radius1 + (radius2 - radius1) * t;
+        points.push(Pt3::new(
+            radius * dcos(degrees),
+            radius * dsin(degrees),
+            pitch * turns * t,
+        ));
+    }
+    points
+}
+
+/// The convex hull of a set of points.
+///
+/// The hull is built one point at a time, each point replaces the faces it can
+/// see. Faces are triangles and points inside the hull or on its faces are left
+/// out.
+///
+/// points: The points, at least four that are not on one plane.
+///
+/// return: The hull.
+pub fn convex_hull(points: &[Pt3]) -> Polyhedron {
+    assert!(points.len() >= 4);
+    let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| {
+        (
+            Pt3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+            Pt3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+        )
+    });
+    let epsilon = (max - min).len() * 1.0e-9;
+
+    // start with a tetrahedron of points that are far apart
+    let a = 0;
+    let b = (0..points.len())
+        .max_by(|i, j| {
+            (points[*i] - points[a])
+                .len()
+                .total_cmp(&(points[*j] - points[a]).len())
+        })
+        .unwrap();
+    let line = (points[b] - points[a]).normalized();
+    let c = (0..points.len())
+        .max_by(|i, j| {
+            let distance = |k: &usize| (points[*k] - points[a]).cross(line).len();
+            distance(i).total_cmp(&distance(j))
+        })
+        .unwrap();
+    let normal = (points[b] - points[a])
+        .cross(points[c] - points[a])
+        .normalized();
+    let d = (0..points.len())
+        .max_by(|i, j| {
+            let distance = |k: &usize| (points[*k] - points[a]).dot(normal).abs();
+            distance(i).total_cmp(&distance(j))
+        })
+        .unwrap();
+    assert!(
+        (points[d] - points[a]).dot(normal).abs() > epsilon,
+        "the points are on one plane"
+    );
+
+    // faces are counter clockwise from outside while building
+    let mut faces: Vec<[usize; 3]> = if (points[d] - points[a]).dot(normal) < 0.0 {
+        vec![[a, b, c], [a, d, b], [b, d, c], [c, d, a]]
+    } else {
+        vec![[a, c, b], [a, b, d], [b, c, d], [c, a, d]]
+    };
+    let outside = |face: &[usize; 3], p: Pt3| {
+        let [i, j, k] = *face;
+        let n = (points[j] - points[i]).cross(points[k] - points[i]);
+        n.dot(p - points[i]) > epsilon * n.len()
+    };
+    for (index, p) in points.iter().enumerate() {
+        if [a, b, c, d].contains(&index) {
+            continue;
+        }
+        let (visible, kept): (Vec<[usize; 3]>, Vec<[usize; 3]>) =
+            faces.into_iter().partition(|face| outside(face, *p));
+        faces = kept;
+        if visible.is_empty() {
+            continue;
+        }
+        // ordered so the same points always give the same faces
+        let edges: BTreeSet<(usize, usize)> = visible
+            .iter()
+            .flat_map(|[i, j, k]| [(*i, *j), (*j, *k), (*k, *i)])
+            .collect();
+        for (i, j) in edges.iter() {
+            if !edges.contains(&(*j, *i)) {
+                faces.push([*i, *j, index]);
+            }
+        }
+    }
+
+    let mut used: BTreeMap<usize, u64> = BTreeMap::new();
+    for face in faces.iter() {
+        for i in face.iter() {
+            let next = used.len() as u64;
+            used.entry(*i).or_insert(next);
+        }
+    }
+    let mut hull_points = Pt3s::with_capacity(used.len());
+    hull_points.resize(used.len(), Pt3::new(0.0, 0.0, 0.0));
+    for (i, j) in used.iter() {
+        hull_points[*j as usize] = points[*i];
+    }
+    let mut hull_faces = Faces::with_capacity(faces.len());
+    for [i, j, k] in faces {
+        hull_faces.push(Indices::from_indices(vec![used[&k], used[&j], used[&i]]));
+    }
+    Polyhedron::new(hull_points, hull_faces)
+}
+
+/// How the ends of a compression spring are finished.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpringEnds {
+    /// The pitch is the same along the whole spring.
+    Open,
+    /// The first and last coils touch the next coil.
+    Closed,
+    /// Closed ends that are ground flat so the spring stands square.
+    ClosedAndGround,
+}
+
+/// Create a compression spring standing on the XY plane.
+///
+/// wire_diameter: The diameter of the wire.
+///
+/// outer_diameter: The outside diameter of the coils.
+///
+/// length: The free length of the spring.
+///
+/// coils: The total number of coils, counting closed end coils.
+///
+/// segments: The number of segments in a circle.
+///
+/// ends: How the ends are finished.
+///
+/// return: The spring.
+pub fn compression_spring(
+    wire_diameter: f64,
+    outer_diameter: f64,
+    length: f64,
+    coils: f64,
+    segments: u64,
+    ends: SpringEnds,
+) -> Polyhedron {
+    let radius = (outer_diameter - wire_diameter) / 2.0;
+    assert!(radius > 0.0 && coils > 0.0 && segments >= 3);
+    let (start, end) = if ends == SpringEnds::ClosedAndGround {
+        (0.0, length)
+    } else {
+        (wire_diameter / 2.0, length - wire_diameter / 2.0)
+    };
+    let end_coils = if ends == SpringEnds::Open { 0.0 } else { 1.0 };
+    assert!(coils > end_coils * 2.0);
+    let pitch = (end - start - end_coils * 2.0 * wire_diameter) / (coils - end_coils * 2.0);
+    assert!(pitch >= wire_diameter, "the coils of the spring overlap");
+
+    // with a unit pitch the height of each point is its number of turns
+    let mut path = helix(radius, 1.0, coils, segments);
+    for p in path.iter_mut() {
+        let t = p.z;
+        p.z = if t < end_coils {
+            start + t * wire_diameter
+        } else if t > coils - end_coils {
+            end - (coils - t) * wire_diameter
+        } else {
+            start + end_coils * wire_diameter + (t - end_coils) * pitch
+        };
+    }
+    let spring = Polyhedron::sweep(
+        &dim2::circle(wire_diameter / 2.0, segments),
+        &path,
+        0.0,
+        false,
+    );
+    if ends != SpringEnds::ClosedAndGround {
+        return spring;
+    }
+    let size = outer_diameter + wire_diameter;
+    let slab = Polyhedron::linear_extrude(
+        &Pt2s::from_pt2s(vec![
+            Pt2::new(-size, -size),
+            Pt2::new(-size, size),
+            Pt2::new(size, size),
+            Pt2::new(size, -size),
+        ]),
+        length,
+    );
+    spring.intersection(&slab)
+}
+
 /// A 3D quadratic bezier curve.
 #[derive(Clone, Copy)]
 pub struct QuadraticBezier3D {
@@ -0,0 +1,291 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Reads 2D profiles from the entities of ASCII DXF drawings.
+
+use crate::{clip::signed_area, dim2, Pt2, Pt2s, ScadError};
+
+const EPSILON: f64 = 1.0e-6;
+
+/// A DXF entity as its group codes and values.
+struct Entity {
+    kind: String,
+    groups: Vec<(i32, String)>,
+}
+
+impl Entity {
+    fn f64(&self, code: i32) -> f64 {
+        self.groups
+            .iter()
+            .find(|(c, _)| *c == code)
+            .and_then(|(_, v)| v.trim().parse().ok())
+            .unwrap_or(0.0)
+    }
+
+    fn closed(&self) -> bool {
+        self.f64(70) as i64 & 1 == 1
+    }
+}
+
+fn entities(text: &str) -> Result<Vec<Entity>, ScadError> {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() % 2 == 1 && !lines[lines.len() - 1].trim().is_empty() {
+        return Err(ScadError::Parse("dxf: odd number of lines".to_string()));
+    }
+    let mut pairs = Vec::with_capacity(lines.len() / 2);
+    for pair in lines.chunks(2) {
+        if pair.len() < 2 {
+            break;
+        }
+        let code = pair[0]
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| ScadError::Parse(format!("dxf: bad group code {:?}", pair[0].trim())))?;
+        pairs.push((code, pair[1].trim().to_string()));
+    }
+
+    let mut result = Vec::new();
+    let mut in_entities = false;
+    let mut i = 0;
+    while i < pairs.len() {
+        let (code, value) = &pairs[i];
+        i += 1;
+        if *code == 2 && i >= 2 && pairs[i - 2] == (0, "SECTION".to_string()) {
+            in_entities = value == "ENTITIES";
+            continue;
+        }
+        if *code != 0 || !in_entities || value == "SECTION" {
+            continue;
+        }
+        if value == "ENDSEC" {
+            in_entities = false;
+            continue;
+        }
+        let mut entity = Entity {
+            kind: value.clone(),
+            groups: Vec::new(),
+        };
+        while i < pairs.len() && pairs[i].0 != 0 {
+            entity.groups.push(pairs[i].clone());
+            i += 1;
+        }
+        result.push(entity);
+    }
+    Ok(result)
+}
+
+/// Appends the points of an arc from the last point of points to end.
+fn bulge_arc(points: &mut Pt2s, end: Pt2, bulge: f64, segments: u64) {
+    let start = points[points.len() - 1];
+    if (end - start).len() < EPSILON {
+        points.push(end);
+        return;
+    }
+    let theta = 4.0 * bulge.atan();
+    let n = ((theta.abs() / std::f64::consts::TAU * segments as f64).ceil() as u64).max(1);
+    points.extend(dim2::bulge_arc(start, end, bulge, n).iter().skip(1));
+}
+
+fn polyline(vertices: &[(Pt2, f64)], closed: bool, segments: u64) -> Pt2s {
+    let mut points = Pt2s::with_capacity(vertices.len());
+    if vertices.is_empty() {
+        return points;
+    }
+    points.push(vertices[0].0);
+    for i in 1..vertices.len() {
+        bulge_arc(&mut points, vertices[i].0, vertices[i - 1].1, segments);
+    }
+    if closed {
+        bulge_arc(
+            &mut points,
+            vertices[0].0,
+            vertices[vertices.len() - 1].1,
+            segments,
+        );
+    }
+    points
+}
+
+fn arc(center: Pt2, radius: f64, start: f64, end: f64, segments: u64) -> Pt2s {
+    let mut sweep = end - start;
+    while sweep <= 0.0 {
+        sweep += 360.0;
+    }
+    let n = ((sweep / 360.0 * segments as f64).ceil() as u64).max(1);
+    let from = Pt2::new(radius, 0.0).rotated(start);
+    Pt2s::from_pt2s(
+        (0..=n)
+            .map(|i| center + from.rotated(sweep * i as f64 / n as f64))
+            .collect(),
+    )
+}
+
+/// Joins pieces that share end points into longer paths.
+pub(crate) fn chain(mut pieces: Vec<Pt2s>) -> Vec<Pt2s> {
+    let close = |a: Pt2, b: Pt2| (a - b).len() < EPSILON;
+    let mut result = Vec::new();
+    while let Some(mut path) = pieces.pop() {
+        loop {
+            if close(path[0], path[path.len() - 1]) && path.len() > 2 {
+                break;
+            }
+            let end = path[path.len() - 1];
+            let start = path[0];
+            let found = pieces.iter().position(|p| {
+                close(p[0], end)
+                    || close(p[p.len() - 1], end)
+                    || close(p[0], start)
+                    || close(p[p.len() - 1], start)
+            });
+            let Some(index) = found else {
+                break;
+            };
+            let mut piece = pieces.swap_remove(index);
+            if close(piece[0], end) {
+                path.extend(piece.iter().skip(1).copied());
+            } else if close(piece[piece.len() - 1], end) {
+                piece.reverse();
+                path.extend(piece.iter().skip(1).copied());
+            } else {
+                if close(piece[0], start) {
+                    piece.reverse();
+                }
+                piece.pop();
+                piece.extend(path.iter().copied());
+                path = piece;
+            }
+        }
+        result.push(path);
+    }
+    result.reverse();
+    result
+}
+
+/// Reads the profiles in the text of an ASCII DXF drawing.
+///
+/// See from_dxf.
+pub fn from_dxf_str(text: &str, segments: u64) -> Result<Vec<Pt2s>, ScadError> {
+    let entities = entities(text)?;
+    let mut profiles = Vec::new();
+    let mut pieces = Vec::new();
+    let mut i = 0;
+    while i < entities.len() {
+        let e = &entities[i];
+        i += 1;
+        match e.kind.as_str() {
+            "LWPOLYLINE" => {
+                let mut vertices: Vec<(Pt2, f64)> = Vec::new();
+                let mut x = None;
+                for (code, value) in e.groups.iter() {
+                    let v = value.parse::<f64>().unwrap_or(0.0);
+                    match code {
+                        10 => x = Some(v),
+                        20 => {
+                            if let Some(x) = x.take() {
+                                vertices.push((Pt2::new(x, v), 0.0));
+                            }
+                        }
+                        42 => {
+                            if let Some(last) = vertices.last_mut() {
+                                last.1 = v;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                profiles.push((polyline(&vertices, e.closed(), segments), e.closed()));
+            }
+            "POLYLINE" => {
+                let mut vertices = Vec::new();
+                while i < entities.len() && entities[i].kind == "VERTEX" {
+                    let v = &entities[i];
+                    vertices.push((Pt2::new(v.f64(10), v.f64(20)), v.f64(42)));
+                    i += 1;
+                }
+                if i < entities.len() && entities[i].kind == "SEQEND" {
+                    i += 1;
+                }
+                profiles.push((polyline(&vertices, e.closed(), segments), e.closed()));
+            }
+            "LINE" => pieces.push(Pt2s::from_pt2s(vec![
+                Pt2::new(e.f64(10), e.f64(20)),
+                Pt2::new(e.f64(11), e.f64(21)),
+            ])),
+            "ARC" => pieces.push(arc(
+                Pt2::new(e.f64(10), e.f64(20)),
+                e.f64(40),
+                e.f64(50),
+                e.f64(51),
+                segments,
+            )),
+            "CIRCLE" => {
+                let mut circle = arc(
+                    Pt2::new(e.f64(10), e.f64(20)),
+                    e.f64(40),
+                    0.0,
+                    360.0,
+                    segments,
+                );
+                circle.pop();
+                profiles.push((circle, true));
+            }
+            _ => (),
+        }
+    }
+    for path in chain(pieces) {
+        profiles.push((path, false));
+    }
+
+    Ok(profiles
+        .into_iter()
+        .filter(|(points, _)| points.len() > 1)
+        .map(|(mut points, closed)| {
+            let n = points.len();
+            if n > 2 && (points[0] - points[n - 1]).len() < EPSILON {
+                points.pop();
+                if signed_area(&points) > 0.0 {
+                    points.reverse();
+                }
+            } else if closed && signed_area(&points) > 0.0 {
+                points.reverse();
+            }
+            points
+        })
+        .collect())
+}
+
+/// Reads the profiles in an ASCII DXF drawing.
+///
+/// LWPOLYLINE, POLYLINE, LINE, ARC and CIRCLE entities are read. Lines and
+/// arcs that share end points are joined into paths. Closed profiles are
+/// made clockwise and open paths are returned as drawn. Z coordinates are
+/// ignored.
+///
+/// path: The path of the DXF file.
+///
+/// segments: The number of segments in a full circle.
+///
+/// return: The profiles or an error.
+pub fn from_dxf(path: &str, segments: u64) -> Result<Vec<Pt2s>, ScadError> {
+    from_dxf_str(&std::fs::read_to_string(path)?, segments)
+}
@@ -0,0 +1,80 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+/// The errors that can be returned by the library.
+#[derive(Debug)]
+pub enum ScadError {
+    /// Reading or writing a file failed.
+    Io(std::io::Error),
+    /// The thread generating the output panicked.
+    ThreadPanic(String),
+    /// There was nothing to output.
+    Empty,
+    /// The contents of a file could not be understood.
+    Parse(String),
+    /// The Scad tree can not be written as valid OpenSCAD code.
+    Invalid(String),
+    /// OpenSCAD reported errors or failed to run.
+    OpenScad(String),
+}
+
+impl ScadError {
+    /// Create a ThreadPanic error from the payload of a panicked thread.
+    pub fn from_panic(payload: Box<dyn std::any::Any + Send>) -> Self {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            Self::ThreadPanic(message.to_string())
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            Self::ThreadPanic(message.clone())
+        } else {
+            Self::ThreadPanic("unknown panic".to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for ScadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScadError::Io(e) => write!(f, "io error: {}", e),
+            ScadError::ThreadPanic(message) => write!(f, "thread panicked: {}", message),
+            ScadError::Empty => write!(f, "nothing to output"),
+            ScadError::Parse(message) => write!(f, "parse error: {}", message),
+            ScadError::Invalid(message) => write!(f, "invalid scad: {}", message),
+            ScadError::OpenScad(message) => write!(f, "openscad error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ScadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScadError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ScadError {
+    fn from(value: std::io::Error) -> Self {
+        ScadError::Io(value)
+    }
+}
@@ -0,0 +1,472 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Evaluates a Scad tree into a Polyhedron without OpenSCAD.
+
+use crate::{dcos, dim2, dsin, prelude::*, Mt4};
+
+/// OpenSCAD's default minimum angle of a fragment.
+const DEFAULT_FA: f64 = 12.0;
+
+/// OpenSCAD's default minimum size of a fragment.
+const DEFAULT_FS: f64 = 2.0;
+
+impl Scad {
+    /// Evaluates the tree into one mesh in Rust.
+    ///
+    /// Supports the 3D primitives, polyhedron, linear_extrude and rotate_extrude of
+    /// circles, squares and polygons, the transforms and the boolean
+    /// operations. Text, import, surface, projection, resize, offset, hull,
+    /// minkowski, calls and raw code are not supported. Background and disabled
+    /// subtrees are left out like in an OpenSCAD render.
+    ///
+    /// quality: The number of segments in a circle when $fn is not given, 0 uses
+    /// OpenSCAD's $fa and $fs rules.
+    ///
+    /// return: The mesh.
+    pub fn to_polyhedron(&self, quality: u64) -> Result<Polyhedron, ScadError> {
+        eval3d(self, quality)?.ok_or(ScadError::Empty)
+    }
+}
+
+/// The number of fragments in a circle, the same as OpenSCAD's get_fragments_from_r.
+fn fragments(radius: f64, fa: Option<f64>, fs: Option<f64>, fn_: Option<u64>, quality: u64) -> u64 {
+    if let Some(fn_) = fn_.filter(|n| *n > 0) {
+        return fn_.max(3);
+    }
+    if quality > 0 {
+        return quality.max(3);
+    }
+    if radius < 1e-9 {
+        return 3;
+    }
+    let fa = fa.unwrap_or(DEFAULT_FA);
+    let fs = fs.unwrap_or(DEFAULT_FS);
+    (360.0 / fa)
+        .min(radius * 2.0 * std::f64::consts::PI / fs)
+        .max(5.0)
+        .ceil() as u64
+}
+
+fn unsupported(op: &str) -> ScadError {
+    ScadError::Invalid(format!("{} can't be evaluated in Rust", op))
+}
+
+/// The matrix of a transform op or None for other ops.
+pub(crate) fn transform_matrix(op: &ScadOp) -> Option<Mt4> {
+    match op {
+        ScadOp::Translate { v } => Some(Mt4::translate_matrix(v.x, v.y, v.z)),
+        ScadOp::Rotate { a, a_is_scalar, v } => Some(match a {
+            Some(a) if *a_is_scalar => Mt4::rot_z_matrix(*a),
+            Some(a) => {
+                let axis = v.normalized();
+                Mt4::rot_vec(axis.x, axis.y, axis.z, *a)
+            }
+            None => Mt4::rot_z_matrix(v.z) * Mt4::rot_y_matrix(v.y) * Mt4::rot_x_matrix(v.x),
+        }),
+        ScadOp::Scale { v } => Some(Mt4::scale_matrix(v.x, v.y, v.z)),
+        ScadOp::Mirror { v } => {
+            let n = v.normalized();
+            let mut m = Mt4::identity();
+            m.x = Pt4::new(
+                1.0 - 2.0 * n.x * n.x,
+                -2.0 * n.y * n.x,
+                -2.0 * n.z * n.x,
+                0.0,
+            );
+            m.y = Pt4::new(
+                -2.0 * n.x * n.y,
+                1.0 - 2.0 * n.y * n.y,
+                -2.0 * n.z * n.y,
+                0.0,
+            );
+            m.z = Pt4::new(
+                -2.0 * n.x * n.z,
+                -2.0 * n.y * n.z,
+                1.0 - 2.0 * n.z * n.z,
+                0.0,
+            );
+            Some(m)
+        }
+        _ => None,
+    }
+}
+
+/// Transforms a point by the rotation, scale and translation of a matrix.
+pub(crate) fn transform(matrix: &Mt4, point: Pt3) -> Pt3 {
+    *matrix * point + matrix.w.as_pt3()
+}
+
+/// The determinant of the 3x3 part of a matrix, negative when it mirrors.
+fn determinant(m: &Mt4) -> f64 {
+    m.x.x * (m.y.y * m.z.z - m.z.y * m.y.z) - m.y.x * (m.x.y * m.z.z - m.z.y * m.x.z)
+        + m.z.x * (m.x.y * m.y.z - m.y.y * m.x.z)
+}
+
+/// Evaluates the children of a node, None when they are all empty.
+fn eval3d_children(scad: &Scad, quality: u64) -> Result<Vec<Polyhedron>, ScadError> {
+    let mut result = Vec::with_capacity(scad.children.len());
+    for child in scad.children.iter() {
+        if let Some(polyhedron) = eval3d(child, quality)? {
+            result.push(polyhedron);
+        }
+    }
+    Ok(result)
+}
+
+fn union_all(polyhedrons: Vec<Polyhedron>) -> Option<Polyhedron> {
+    polyhedrons.into_iter().reduce(|a, b| a.union(&b))
+}
+
+/// Evaluates a node into a mesh, None when it is empty.
+fn eval3d(scad: &Scad, quality: u64) -> Result<Option<Polyhedron>, ScadError> {
+    if let Some(matrix) = transform_matrix(&scad.op) {
+        let mut polyhedron = match union_all(eval3d_children(scad, quality)?) {
+            Some(polyhedron) => polyhedron,
+            None => return Ok(None),
+        };
+        for p in polyhedron.points.iter_mut() {
+            *p = transform(&matrix, *p);
+        }
+        if determinant(&matrix) < 0.0 {
+            for face in polyhedron.faces.iter_mut() {
+                face.reverse();
+            }
+        }
+        return Ok(Some(polyhedron));
+    }
+    let polyhedron = match &scad.op {
+        ScadOp::Union
+        | ScadOp::Color { .. }
+        | ScadOp::Comment(_)
+        | ScadOp::Render { .. }
+        | ScadOp::Tag(_)
+        | ScadOp::BomItem { .. } => union_all(eval3d_children(scad, quality)?),
+        ScadOp::IfPreview => match scad.children.get(1) {
+            Some(render) => eval3d(render, quality)?,
+            None => None,
+        },
+        ScadOp::Modifier(modifier) => match modifier {
+            ScadModifier::Background | ScadModifier::Disable => None,
+            ScadModifier::Debug | ScadModifier::Root => union_all(eval3d_children(scad, quality)?),
+        },
+        ScadOp::Difference => {
+            let mut children = scad.children.iter();
+            match children.next() {
+                Some(first) => match eval3d(first, quality)? {
+                    Some(mut result) => {
+                        for child in children {
+                            if let Some(other) = eval3d(child, quality)? {
+                                result = result.difference(&other);
+                            }
+                        }
+                        Some(result)
+                    }
+                    None => None,
+                },
+                None => None,
+            }
+        }
+        ScadOp::Intersection => eval3d_children(scad, quality)?
+            .into_iter()
+            .reduce(|a, b| a.intersection(&b)),
+        ScadOp::Cube { size, center } => {
+            let mut cube = Polyhedron::linear_extrude(&dim2::rect(size.x, size.y, false), size.z);
+            if *center {
+                cube.translate(*size * -0.5);
+            }
+            Some(cube)
+        }
+        ScadOp::Sphere {
+            radius,
+            fa,
+            fs,
+            fn_,
+        } => Some(sphere(*radius, fragments(*radius, *fa, *fs, *fn_, quality))),
+        ScadOp::Cylinder {
+            height,
+            radius1,
+            radius2,
+            center,
+            fa,
+            fs,
+            fn_,
+        } => {
+            let segments = fragments(radius1.max(*radius2), *fa, *fs, *fn_, quality);
+            let mut cylinder = Polyhedron::loft(
+                &dim2::circle(*radius1, segments),
+                &dim2::circle(*radius2, segments),
+                *height,
+            );
+            if *radius1 == 0.0 || *radius2 == 0.0 {
+                cylinder.weld(1e-9);
+            }
+            if *center {
+                cylinder.translate(Pt3::new(0.0, 0.0, -height / 2.0));
+            }
+            Some(cylinder)
+        }
+        ScadOp::Polyhedron { points, faces, .. } => {
+            Some(Polyhedron::new(points.clone(), faces.clone()))
+        }
+        ScadOp::LinearExtrude {
+            height,
+            center,
+            twist,
+            scale,
+            slices,
+            ..
+        } => {
+            let slices = slices.unwrap_or(if *twist == 0.0 {
+                1
+            } else {
+                (twist.abs() / 5.0).ceil().max(1.0) as u64
+            });
+            let mut result: Option<Polyhedron> = None;
+            for profile in eval2d_children(scad, quality)? {
+                let mut extrusion = extrude(&profile, *height, *twist, *scale, slices);
+                if *center {
+                    extrusion.translate(Pt3::new(0.0, 0.0, -height / 2.0));
+                }
+                result = Some(match result {
+                    Some(result) => result.union(&extrusion),
+                    None => extrusion,
+                });
+            }
+            result
+        }
+        ScadOp::RotateExtrude {
+            angle, fa, fs, fn_, ..
+        } => {
+            let mut result: Option<Polyhedron> = None;
+            let angle = angle.clamp(-360.0, 360.0);
+            for profile in eval2d_children(scad, quality)? {
+                if profile.outline.iter().any(|p| p.x < 0.0) {
+                    return Err(ScadError::Invalid(
+                        "rotate_extrude profile crosses the Y axis".to_string(),
+                    ));
+                }
+                let radius = profile.outline.iter().fold(0.0_f64, |r, p| r.max(p.x));
+                let segments = fragments(radius, *fa, *fs, *fn_, quality);
+                let segments = ((segments as f64 * angle.abs() / 360.0).ceil() as usize).max(3);
+                let revolve = |points: &Pt2s| {
+                    let mut points = points.clone();
+                    points.reverse();
+                    let mut revolved = Polyhedron::rotate_extrude(&points, angle.abs(), segments);
+                    if angle < 0.0 {
+                        revolved.apply_matrix(&Mt4::scale_matrix(1.0, -1.0, 1.0));
+                        for face in revolved.faces.iter_mut() {
+                            face.reverse();
+                        }
+                    }
+                    revolved.weld(1e-9);
+                    revolved
+                };
+                let mut revolved = revolve(&profile.outline);
+                for hole in profile.holes.iter() {
+                    let mut hole = hole.clone();
+                    hole.reverse();
+                    revolved = revolved.difference(&revolve(&hole));
+                }
+                result = Some(match result {
+                    Some(result) => result.union(&revolved),
+                    None => revolved,
+                });
+            }
+            result
+        }
+        ScadOp::Circle { .. } | ScadOp::Square { .. } | ScadOp::Polygon { .. } => {
+            return Err(ScadError::Invalid(
+                "2D objects must be extruded to be evaluated".to_string(),
+            ))
+        }
+        ScadOp::Text { .. } => return Err(unsupported("text")),
+        ScadOp::Import { .. } => return Err(unsupported("import")),
+        ScadOp::Projection { .. } => return Err(unsupported("projection")),
+        ScadOp::Surface { .. } => return Err(unsupported("surface")),
+        ScadOp::Resize { .. } => return Err(unsupported("resize")),
+        ScadOp::Offset { .. } => return Err(unsupported("offset")),
+        ScadOp::Hull => return Err(unsupported("hull")),
+        ScadOp::Minkowski { .. } => return Err(unsupported("minkowski")),
+        ScadOp::Call { module, .. } => return Err(unsupported(module)),
+        ScadOp::Raw(_) => return Err(unsupported("raw code")),
+        ScadOp::Translate { .. }
+        | ScadOp::Rotate { .. }
+        | ScadOp::Scale { .. }
+        | ScadOp::Mirror { .. } => unreachable!(),
+    };
+    Ok(polyhedron)
+}
+
+/// A sphere with rings like OpenSCAD's.
+fn sphere(radius: f64, segments: u64) -> Polyhedron {
+    let n_rings = segments.div_ceil(2);
+    let mut points = Pt3s::with_capacity((n_rings * segments) as usize);
+    let mut rings = Vec::with_capacity(n_rings as usize);
+    for i in 0..n_rings {
+        let phi = 180.0 * (i as f64 + 0.5) / n_rings as f64;
+        let ring_radius = radius * dsin(phi);
+        let z = radius * dcos(phi);
+        let start = points.len() as u64;
+        for p in dim2::circle(ring_radius, segments).iter() {
+            points.push(p.as_pt3(z));
+        }
+        rings.push((start..start + segments).collect::<Vec<u64>>());
+    }
+    let mut faces = Faces::with_capacity((n_rings * segments) as usize + 2);
+    faces.push(Indices::from_indices(rings[0].clone()));
+    for i in 0..rings.len() - 1 {
+        faces.push_strip(&rings[i + 1], &rings[i]);
+    }
+    let mut bottom = rings[rings.len() - 1].clone();
+    bottom.reverse();
+    faces.push(Indices::from_indices(bottom));
+    Polyhedron::new(points, faces)
+}
+
+/// Extrudes a profile with twist and scale like linear_extrude.
+fn extrude(
+    profile: &dim2::Profile,
+    height: f64,
+    twist: f64,
+    scale: Pt2,
+    slices: u64,
+) -> Polyhedron {
+    let outline = profile.points();
+    let n = outline.len() as u64;
+    let mut points = Pt3s::with_capacity((n * (slices + 1)) as usize);
+    for slice in 0..=slices {
+        let t = slice as f64 / slices as f64;
+        let sx = 1.0 + (scale.x - 1.0) * t;
+        let sy = 1.0 + (scale.y - 1.0) * t;
+        for p in outline.iter() {
+            let p = p.rotated(-twist * t);
+            points.push(Pt3::new(p.x * sx, p.y * sy, height * t));
+        }
+    }
+    let triangles = profile.triangulate();
+    let mut faces = Faces::from_triangles(&triangles);
+    for face in faces.iter_mut() {
+        face.reverse();
+    }
+    faces.append_offset(Faces::from_triangles(&triangles), n * slices);
+    for slice in 0..slices {
+        let mut offset = n * slice;
+        for l in profile.loops() {
+            let lower: Vec<u64> = (offset..offset + l.len() as u64).collect();
+            let upper: Vec<u64> = lower.iter().map(|i| i + n).collect();
+            faces.push_strip(&lower, &upper);
+            offset += l.len() as u64;
+        }
+    }
+    let mut polyhedron = Polyhedron::new(points, faces);
+    if scale.x == 0.0 || scale.y == 0.0 {
+        polyhedron.weld(1e-9);
+    }
+    polyhedron
+}
+
+/// Evaluates the 2D children of an extrusion into profiles.
+fn eval2d_children(scad: &Scad, quality: u64) -> Result<Vec<dim2::Profile>, ScadError> {
+    let mut result = Vec::new();
+    for child in scad.children.iter() {
+        result = dim2::union(&result, &eval2d(child, quality)?);
+    }
+    Ok(dim2::Profile::from_outlines(&result))
+}
+
+/// Evaluates a 2D node into outlines.
+fn eval2d(scad: &Scad, quality: u64) -> Result<Vec<Pt2s>, ScadError> {
+    if let Some(matrix) = transform_matrix(&scad.op) {
+        let mut outlines = Vec::new();
+        for child in scad.children.iter() {
+            outlines = dim2::union(&outlines, &eval2d(child, quality)?);
+        }
+        for outline in outlines.iter_mut() {
+            for p in outline.iter_mut() {
+                let q = transform(&matrix, p.as_pt3(0.0));
+                *p = Pt2::new(q.x, q.y);
+            }
+            if determinant(&matrix) < 0.0 {
+                outline.reverse();
+            }
+        }
+        return Ok(outlines);
+    }
+    let children = |op: fn(&[Pt2s], &[Pt2s]) -> Vec<Pt2s>| -> Result<Vec<Pt2s>, ScadError> {
+        let mut result: Option<Vec<Pt2s>> = None;
+        for child in scad.children.iter() {
+            let outlines = eval2d(child, quality)?;
+            result = Some(match result {
+                Some(result) => op(&result, &outlines),
+                None => outlines,
+            });
+        }
+        Ok(result.unwrap_or_default())
+    };
+    match &scad.op {
+        ScadOp::Circle {
+            radius,
+            fa,
+            fs,
+            fn_,
+        } => Ok(vec![dim2::circle(
+            *radius,
+            fragments(*radius, *fa, *fs, *fn_, quality),
+        )]),
+        ScadOp::Square { size, center } => Ok(vec![dim2::rect(size.x, size.y, *center)]),
+        ScadOp::Polygon { points, paths, .. } => {
+            let outlines: Vec<Pt2s> = match paths {
+                Some(paths) => paths
+                    .iter()
+                    .map(|path| Pt2s::from_pt2s(path.iter().map(|&i| points[i as usize]).collect()))
+                    .collect(),
+                None => vec![points.clone()],
+            };
+            // the even-odd rule of OpenSCAD polygons
+            let mut result = Vec::new();
+            for outline in outlines {
+                result = dim2::xor(&result, &[outline]);
+            }
+            Ok(result)
+        }
+        ScadOp::Union
+        | ScadOp::Color { .. }
+        | ScadOp::Comment(_)
+        | ScadOp::Render { .. }
+        | ScadOp::Tag(_)
+        | ScadOp::BomItem { .. } => children(dim2::union),
+        ScadOp::IfPreview => match scad.children.get(1) {
+            Some(render) => eval2d(render, quality),
+            None => Ok(Vec::new()),
+        },
+        ScadOp::Modifier(ScadModifier::Background | ScadModifier::Disable) => Ok(Vec::new()),
+        ScadOp::Modifier(_) => children(dim2::union),
+        ScadOp::Difference => children(dim2::difference),
+        ScadOp::Intersection => children(dim2::intersection),
+        _ => Err(ScadError::Invalid(
+            "only circle, square, polygon, transforms and booleans can be extruded in Rust"
+                .to_string(),
+        )),
+    }
+}
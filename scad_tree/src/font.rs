@@ -0,0 +1,510 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! A minimal TrueType reader that turns text into profiles.
+
+use crate::{dim2, Paths, Pt2, Pt2s, ScadError};
+
+/// A TrueType font.
+///
+/// Only the glyph outlines, character map and horizontal metrics are read.
+/// Kerning, ligatures and hinting are ignored.
+#[derive(Clone)]
+pub struct Font {
+    data: Vec<u8>,
+    units_per_em: f64,
+    long_loca: bool,
+    num_glyphs: u16,
+    num_h_metrics: u16,
+    cmap: usize,
+    cmap_format: u16,
+    glyf: usize,
+    loca: usize,
+    hmtx: usize,
+}
+
+fn parse_error(message: &str) -> ScadError {
+    ScadError::Parse(format!("font: {}", message))
+}
+
+impl Font {
+    /// Load a font from a .ttf file.
+    ///
+    /// path: The path of the font file.
+    ///
+    /// return: The font or an error.
+    pub fn from_file(path: &str) -> Result<Self, ScadError> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Load a font from the bytes of a .ttf file.
+    ///
+    /// data: The contents of the font file.
+    ///
+    /// return: The font or an error.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ScadError> {
+        let mut font = Self {
+            data,
+            units_per_em: 0.0,
+            long_loca: false,
+            num_glyphs: 0,
+            num_h_metrics: 0,
+            cmap: 0,
+            cmap_format: 0,
+            glyf: 0,
+            loca: 0,
+            hmtx: 0,
+        };
+        let num_tables = font.u16(4)? as usize;
+        let mut tables = std::collections::HashMap::new();
+        for i in 0..num_tables {
+            let record = 12 + i * 16;
+            let tag = font
+                .data
+                .get(record..record + 4)
+                .ok_or(parse_error("truncated"))?;
+            tables.insert(tag.to_vec(), font.u32(record + 8)? as usize);
+        }
+        let table = |tag: &[u8]| {
+            tables.get(tag).copied().ok_or_else(|| {
+                parse_error(&format!("missing {} table", String::from_utf8_lossy(tag)))
+            })
+        };
+        let head = table(b"head")?;
+        font.units_per_em = font.u16(head + 18)? as f64;
+        font.long_loca = font.u16(head + 50)? == 1;
+        font.num_glyphs = font.u16(table(b"maxp")? + 4)?;
+        font.num_h_metrics = font.u16(table(b"hhea")? + 34)?;
+        font.glyf = table(b"glyf")?;
+        font.loca = table(b"loca")?;
+        font.hmtx = table(b"hmtx")?;
+
+        // prefer the full unicode map, then the basic multilingual plane
+        let cmap = table(b"cmap")?;
+        let mut best = None;
+        for i in 0..font.u16(cmap + 2)? as usize {
+            let record = cmap + 4 + i * 8;
+            let platform = font.u16(record)?;
+            let encoding = font.u16(record + 2)?;
+            let subtable = cmap + font.u32(record + 4)? as usize;
+            let format = font.u16(subtable)?;
+            let rank = match (platform, encoding, format) {
+                (3, 10, 12) | (0, _, 12) => 2,
+                (3, 1, 4) | (0, _, 4) => 1,
+                _ => 0,
+            };
+            if rank > 0 && best.map_or(true, |(r, _, _)| rank > r) {
+                best = Some((rank, subtable, format));
+            }
+        }
+        let (_, subtable, format) = best.ok_or(parse_error("no unicode character map"))?;
+        font.cmap = subtable;
+        font.cmap_format = format;
+        if font.units_per_em <= 0.0 {
+            return Err(parse_error("bad units per em"));
+        }
+        Ok(font)
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, ScadError> {
+        self.data
+            .get(offset..offset + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or(parse_error("truncated"))
+    }
+
+    fn i16(&self, offset: usize) -> Result<i16, ScadError> {
+        self.u16(offset).map(|v| v as i16)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, ScadError> {
+        self.data
+            .get(offset..offset + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or(parse_error("truncated"))
+    }
+
+    fn u8(&self, offset: usize) -> Result<u8, ScadError> {
+        self.data
+            .get(offset)
+            .copied()
+            .ok_or(parse_error("truncated"))
+    }
+
+    /// The glyph index of a character, 0 when the font doesn't have it.
+    fn glyph_index(&self, c: char) -> Result<u16, ScadError> {
+        let code = c as u32;
+        let t = self.cmap;
+        if self.cmap_format == 12 {
+            let n_groups = self.u32(t + 12)? as usize;
+            for i in 0..n_groups {
+                let group = t + 16 + i * 12;
+                let start = self.u32(group)?;
+                let end = self.u32(group + 4)?;
+                if code >= start && code <= end {
+                    let start_glyph = self.u32(group + 8)?;
+                    return code
+                        .checked_sub(start)
+                        .and_then(|offset| start_glyph.checked_add(offset))
+                        .and_then(|glyph| u16::try_from(glyph).ok())
+                        .ok_or(parse_error("glyph index out of range"));
+                }
+            }
+            return Ok(0);
+        }
+        if code > 0xffff {
+            return Ok(0);
+        }
+        let seg_count = self.u16(t + 6)? as usize / 2;
+        let ends = t + 14;
+        let starts = ends + seg_count * 2 + 2;
+        let deltas = starts + seg_count * 2;
+        let range_offsets = deltas + seg_count * 2;
+        for i in 0..seg_count {
+            let end = self.u16(ends + i * 2)? as u32;
+            if code > end {
+                continue;
+            }
+            let start = self.u16(starts + i * 2)? as u32;
+            if code < start {
+                return Ok(0);
+            }
+            let delta = self.u16(deltas + i * 2)?;
+            let range_offset = self.u16(range_offsets + i * 2)? as usize;
+            if range_offset == 0 {
+                return Ok((code as u16).wrapping_add(delta));
+            }
+            let address = range_offsets + i * 2 + range_offset + (code - start) as usize * 2;
+            let glyph = self.u16(address)?;
+            return Ok(if glyph == 0 {
+                0
+            } else {
+                glyph.wrapping_add(delta)
+            });
+        }
+        Ok(0)
+    }
+
+    /// The horizontal advance of a glyph in font units.
+    fn glyph_advance(&self, glyph: u16) -> Result<f64, ScadError> {
+        let metric = glyph.min(self.num_h_metrics.saturating_sub(1)) as usize;
+        Ok(self.u16(self.hmtx + metric * 4)? as f64)
+    }
+
+    /// The contours of a glyph in font units as (point, on curve) pairs.
+    fn glyph_contours(&self, glyph: u16, depth: u32) -> Result<Vec<Vec<(Pt2, bool)>>, ScadError> {
+        if glyph >= self.num_glyphs || depth > 8 {
+            return Ok(Vec::new());
+        }
+        let (start, end) = if self.long_loca {
+            (
+                self.u32(self.loca + glyph as usize * 4)? as usize,
+                self.u32(self.loca + glyph as usize * 4 + 4)? as usize,
+            )
+        } else {
+            (
+                self.u16(self.loca + glyph as usize * 2)? as usize * 2,
+                self.u16(self.loca + glyph as usize * 2 + 2)? as usize * 2,
+            )
+        };
+        if end <= start {
+            return Ok(Vec::new());
+        }
+        let g = self.glyf + start;
+        let n_contours = self.i16(g)?;
+        if n_contours < 0 {
+            return self.composite_contours(g + 10, depth);
+        }
+
+        let n_contours = n_contours as usize;
+        let mut end_points = Vec::with_capacity(n_contours);
+        for i in 0..n_contours {
+            end_points.push(self.u16(g + 10 + i * 2)? as usize);
+        }
+        let n_points = end_points.last().map_or(0, |e| e + 1);
+        let mut offset = g + 10 + n_contours * 2;
+        offset += 2 + self.u16(offset)? as usize;
+
+        let mut flags = Vec::with_capacity(n_points);
+        while flags.len() < n_points {
+            let flag = self.u8(offset)?;
+            offset += 1;
+            flags.push(flag);
+            if flag & 8 != 0 {
+                let repeat = self.u8(offset)?;
+                offset += 1;
+                for _ in 0..repeat {
+                    flags.push(flag);
+                }
+            }
+        }
+        let mut read_coords = |short: u8, same: u8| -> Result<Vec<f64>, ScadError> {
+            let mut value = 0i32;
+            let mut coords = Vec::with_capacity(n_points);
+            for &flag in flags.iter().take(n_points) {
+                if flag & short != 0 {
+                    let d = self.u8(offset)? as i32;
+                    offset += 1;
+                    value += if flag & same != 0 { d } else { -d };
+                } else if flag & same == 0 {
+                    value += self.i16(offset)? as i32;
+                    offset += 2;
+                }
+                coords.push(value as f64);
+            }
+            Ok(coords)
+        };
+        let xs = read_coords(2, 16)?;
+        let ys = read_coords(4, 32)?;
+
+        let mut contours = Vec::with_capacity(n_contours);
+        let mut first = 0;
+        for &last in end_points.iter() {
+            if last >= n_points || last < first {
+                return Err(parse_error("bad contour"));
+            }
+            contours.push(
+                (first..=last)
+                    .map(|i| (Pt2::new(xs[i], ys[i]), flags[i] & 1 != 0))
+                    .collect(),
+            );
+            first = last + 1;
+        }
+        Ok(contours)
+    }
+
+    /// The contours of a glyph made from other glyphs.
+    fn composite_contours(
+        &self,
+        mut offset: usize,
+        depth: u32,
+    ) -> Result<Vec<Vec<(Pt2, bool)>>, ScadError> {
+        let mut contours = Vec::new();
+        loop {
+            let flags = self.u16(offset)?;
+            let glyph = self.u16(offset + 2)?;
+            offset += 4;
+            let (dx, dy) = if flags & 1 != 0 {
+                offset += 4;
+                (self.i16(offset - 4)? as f64, self.i16(offset - 2)? as f64)
+            } else {
+                offset += 2;
+                (
+                    self.u8(offset - 2)? as i8 as f64,
+                    self.u8(offset - 1)? as i8 as f64,
+                )
+            };
+            // point matching offsets are not supported
+            let (dx, dy) = if flags & 2 != 0 { (dx, dy) } else { (0.0, 0.0) };
+            let f2dot14 = |o: usize| self.i16(o).map(|v| v as f64 / 16384.0);
+            let (a, b, c, d) = if flags & 8 != 0 {
+                offset += 2;
+                let s = f2dot14(offset - 2)?;
+                (s, 0.0, 0.0, s)
+            } else if flags & 0x40 != 0 {
+                offset += 4;
+                (f2dot14(offset - 4)?, 0.0, 0.0, f2dot14(offset - 2)?)
+            } else if flags & 0x80 != 0 {
+                offset += 8;
+                (
+                    f2dot14(offset - 8)?,
+                    f2dot14(offset - 6)?,
+                    f2dot14(offset - 4)?,
+                    f2dot14(offset - 2)?,
+                )
+            } else {
+                (1.0, 0.0, 0.0, 1.0)
+            };
+            for contour in self.glyph_contours(glyph, depth + 1)? {
+                contours.push(
+                    contour
+                        .into_iter()
+                        .map(|(p, on)| {
+                            (Pt2::new(a * p.x + c * p.y + dx, b * p.x + d * p.y + dy), on)
+                        })
+                        .collect(),
+                );
+            }
+            if flags & 0x20 == 0 {
+                break;
+            }
+        }
+        Ok(contours)
+    }
+
+    /// The width of a string.
+    ///
+    /// text: The text to measure.
+    ///
+    /// size: The em size of the font.
+    ///
+    /// return: The sum of the advances of the characters.
+    pub fn text_width(&self, text: &str, size: f64) -> Result<f64, ScadError> {
+        let mut width = 0.0;
+        for c in text.chars() {
+            width += self.glyph_advance(self.glyph_index(c)?)?;
+        }
+        Ok(width * size / self.units_per_em)
+    }
+
+    /// The outlines of a single character with the origin on the baseline.
+    ///
+    /// Outlines are clockwise and holes are counter clockwise.
+    ///
+    /// c: The character.
+    ///
+    /// size: The em size of the font.
+    ///
+    /// segments: The number of segments in each curve.
+    ///
+    /// return: The outlines.
+    pub fn char_outlines(&self, c: char, size: f64, segments: u64) -> Result<Vec<Pt2s>, ScadError> {
+        let scale = size / self.units_per_em;
+        let contours = self.glyph_contours(self.glyph_index(c)?, 0)?;
+        Ok(contours
+            .iter()
+            .map(|contour| flatten(contour, segments, scale))
+            .filter(|outline| outline.len() > 2)
+            .collect())
+    }
+
+    /// The outlines of a string with the origin at the start of the baseline.
+    ///
+    /// Outlines are clockwise and holes are counter clockwise.
+    ///
+    /// text: The text.
+    ///
+    /// size: The em size of the font.
+    ///
+    /// segments: The number of segments in each curve.
+    ///
+    /// return: The outlines.
+    pub fn text_outlines(
+        &self,
+        text: &str,
+        size: f64,
+        segments: u64,
+    ) -> Result<Vec<Pt2s>, ScadError> {
+        let mut outlines = Vec::new();
+        let mut x = 0.0;
+        for c in text.chars() {
+            for mut outline in self.char_outlines(c, size, segments)? {
+                outline.translate(Pt2::new(x, 0.0));
+                outlines.push(outline);
+            }
+            x += self.glyph_advance(self.glyph_index(c)?)? * size / self.units_per_em;
+        }
+        Ok(outlines)
+    }
+
+    /// The outlines of a string as points and paths for the polygon! macro.
+    ///
+    /// text: The text.
+    ///
+    /// size: The em size of the font.
+    ///
+    /// segments: The number of segments in each curve.
+    ///
+    /// return: The points and a path for each outline.
+    pub fn text_polygon(
+        &self,
+        text: &str,
+        size: f64,
+        segments: u64,
+    ) -> Result<(Pt2s, Paths), ScadError> {
+        Ok(dim2::merge_outlines(
+            &self.text_outlines(text, size, segments)?,
+        ))
+    }
+}
+
+/// Turn a contour of quadratic B-spline points into a polyline.
+fn flatten(contour: &[(Pt2, bool)], segments: u64, scale: f64) -> Pt2s {
+    let n = contour.len();
+    let mut points = Pt2s::new();
+    if n == 0 {
+        return points;
+    }
+    // start at an on curve point, or the implied one between two off curve points
+    let start_index = contour.iter().position(|&(_, on)| on);
+    let (start, first) = match start_index {
+        Some(i) => (contour[i].0, i + 1),
+        None => (contour[0].0.lerp(contour[1 % n].0, 0.5), 1),
+    };
+    points.push(start);
+    let mut control: Option<Pt2> = if start_index.is_none() {
+        Some(contour[1 % n].0)
+    } else {
+        None
+    };
+    let mut current = start;
+    let count = if start_index.is_none() { n - 1 } else { n };
+    for k in 0..count {
+        let (p, on) = contour[(first + k + usize::from(start_index.is_none())) % n];
+        match (control, on) {
+            (None, true) => {
+                points.push(p);
+                current = p;
+            }
+            (None, false) => control = Some(p),
+            (Some(c), true) => {
+                quadratic(&mut points, current, c, p, segments);
+                current = p;
+                control = None;
+            }
+            (Some(c), false) => {
+                let mid = c.lerp(p, 0.5);
+                quadratic(&mut points, current, c, mid, segments);
+                current = mid;
+                control = Some(p);
+            }
+        }
+    }
+    match control {
+        Some(c) => quadratic(&mut points, current, c, start, segments),
+        None => points.push(start),
+    }
+
+    let mut result = Pt2s::with_capacity(points.len());
+    for p in points.iter() {
+        let p = *p * scale;
+        if result
+            .last()
+            .map_or(true, |last: &Pt2| (*last - p).len() > 1.0e-9)
+        {
+            result.push(p);
+        }
+    }
+    while result.len() > 1 && (result[0] - result[result.len() - 1]).len() <= 1.0e-9 {
+        result.pop();
+    }
+    result
+}
+
+fn quadratic(points: &mut Pt2s, start: Pt2, control: Pt2, end: Pt2, segments: u64) {
+    for i in 1..=segments.max(1) {
+        let t = i as f64 / segments.max(1) as f64;
+        points
+            .push(start * (1.0 - t) * (1.0 - t) + control * (2.0 * t * (1.0 - t)) + end * (t * t));
+    }
+}
@@ -0,0 +1,498 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::prelude::*;
+
+/// The usual pressure angle of gears in degrees.
+pub const PRESSURE_ANGLE: f64 = 20.0;
+
+/// The involute function, the polar angle of an involute at pressure angle a.
+fn involute(a: f64) -> f64 {
+    a.tan() - a
+}
+
+/// The pitch diameter of a gear.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// teeth: The number of teeth.
+///
+/// return: The diameter.
+pub fn pitch_diameter(module: f64, teeth: u64) -> f64 {
+    module * teeth as f64
+}
+
+/// The outside diameter of a gear.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// teeth: The number of teeth.
+///
+/// return: The diameter.
+pub fn outside_diameter(module: f64, teeth: u64) -> f64 {
+    module * (teeth as f64 + 2.0)
+}
+
+/// Creates the profile of an involute spur gear.
+///
+/// The gear is centered on the origin with the first tooth on the +X axis.
+/// Fewer than 17 teeth at 20 degrees are undercut on real gears which is
+/// not modeled here.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// teeth: The number of teeth.
+///
+/// pressure_angle: The pressure angle in degrees, usually 20.
+///
+/// segments: The number of segments on each side of a tooth.
+///
+/// return: The clockwise profile.
+pub fn gear_profile(module: f64, teeth: u64, pressure_angle: f64, segments: u64) -> Pt2s {
+    assert!(teeth >= 3 && segments > 0);
+    let z = teeth as f64;
+    let pitch_radius = module * z / 2.0;
+    let base_radius = pitch_radius * pressure_angle.to_radians().cos();
+    let outside_radius = pitch_radius + module;
+    let root_radius = pitch_radius - 1.25 * module;
+    let start_radius = base_radius.max(root_radius);
+    let half_tooth = std::f64::consts::PI / (2.0 * z) + involute(pressure_angle.to_radians());
+    // the angle from the center of a tooth to its flank at radius r
+    let flank = |r: f64| half_tooth - involute((base_radius / r).min(1.0).acos());
+    let polar = |r: f64, a: f64| Pt2::new(r * a.cos(), r * a.sin());
+
+    let mut points = Pt2s::with_capacity(teeth as usize * (segments as usize * 2 + 4));
+    for tooth in 0..teeth {
+        let center = std::f64::consts::TAU * tooth as f64 / z;
+        if root_radius < base_radius {
+            points.push(polar(root_radius, center - flank(base_radius)));
+        }
+        for i in 0..=segments {
+            let r = start_radius + (outside_radius - start_radius) * i as f64 / segments as f64;
+            points.push(polar(r, center - flank(r)));
+        }
+        for i in (0..=segments).rev() {
+            let r = start_radius + (outside_radius - start_radius) * i as f64 / segments as f64;
+            points.push(polar(r, center + flank(r)));
+        }
+        if root_radius < base_radius {
+            points.push(polar(root_radius, center + flank(base_radius)));
+        }
+    }
+    points.reverse();
+    points
+}
+
+/// Creates an involute spur gear.
+///
+/// The gear sits on the XY plane centered on the Z axis.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// teeth: The number of teeth.
+///
+/// thickness: The thickness of the gear.
+///
+/// pressure_angle: The pressure angle in degrees, usually 20.
+///
+/// segments: The number of segments on each side of a tooth.
+///
+/// return: The gear.
+pub fn spur_gear(
+    module: f64,
+    teeth: u64,
+    thickness: f64,
+    pressure_angle: f64,
+    segments: u64,
+) -> Scad {
+    let convexity = 2;
+    linear_extrude!(height=thickness, center=false, convexity=convexity, twist=0.0, scale=1.0, slices=1,
+        polygon!(gear_profile(module, teeth, pressure_angle, segments));
+    )
+}
+
+/// The twist in degrees of a helical gear over a height.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// teeth: The number of teeth.
+///
+/// height: The height of the twist.
+///
+/// helix_angle: The angle of the teeth to the axis in degrees at the pitch diameter.
+///
+/// return: The twist as used by linear_extrude, positive is clockwise from above.
+pub fn helix_twist(module: f64, teeth: u64, height: f64, helix_angle: f64) -> f64 {
+    let pitch_radius = pitch_diameter(module, teeth) / 2.0;
+    (height * helix_angle.to_radians().tan() / pitch_radius).to_degrees()
+}
+
+/// Creates an involute helical gear.
+///
+/// The gear sits on the XY plane centered on the Z axis. A meshing gear on a
+/// parallel axis needs the opposite helix angle. The module is measured
+/// across the axis, not normal to the teeth.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// teeth: The number of teeth.
+///
+/// thickness: The thickness of the gear.
+///
+/// pressure_angle: The pressure angle in degrees, usually 20.
+///
+/// helix_angle: The angle of the teeth to the axis in degrees.
+///
+/// segments: The number of segments on each side of a tooth.
+///
+/// return: The gear.
+pub fn helical_gear(
+    module: f64,
+    teeth: u64,
+    thickness: f64,
+    pressure_angle: f64,
+    helix_angle: f64,
+    segments: u64,
+) -> Scad {
+    let convexity = 2;
+    let twist = helix_twist(module, teeth, thickness, helix_angle);
+    let slices = (twist.abs() / 2.0).ceil().max(1.0) as u64;
+    linear_extrude!(height=thickness, center=false, convexity=convexity, twist=twist, scale=1.0, slices=slices,
+        polygon!(gear_profile(module, teeth, pressure_angle, segments));
+    )
+}
+
+/// Creates an involute herringbone gear.
+///
+/// The lower half is a helical gear and the upper half is its mirror image
+/// so the axial thrust cancels.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// teeth: The number of teeth.
+///
+/// thickness: The thickness of the gear.
+///
+/// pressure_angle: The pressure angle in degrees, usually 20.
+///
+/// helix_angle: The angle of the teeth of the lower half to the axis in degrees.
+///
+/// segments: The number of segments on each side of a tooth.
+///
+/// return: The gear.
+pub fn herringbone_gear(
+    module: f64,
+    teeth: u64,
+    thickness: f64,
+    pressure_angle: f64,
+    helix_angle: f64,
+    segments: u64,
+) -> Scad {
+    let half = helical_gear(
+        module,
+        teeth,
+        thickness / 2.0,
+        pressure_angle,
+        helix_angle,
+        segments,
+    );
+    union!(
+        half.clone();
+        translate!([0.0, 0.0, thickness],
+            mirror!([0.0, 0.0, 1.0], half;);
+        );
+    )
+}
+
+/// A pair of meshing spur gears.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GearPair {
+    /// The pitch diameter divided by the number of teeth.
+    pub module: f64,
+    /// The teeth on the driving gear.
+    pub driver_teeth: u64,
+    /// The teeth on the driven gear.
+    pub driven_teeth: u64,
+}
+
+impl GearPair {
+    /// Create a pair of gears.
+    pub fn new(module: f64, driver_teeth: u64, driven_teeth: u64) -> Self {
+        Self {
+            module,
+            driver_teeth,
+            driven_teeth,
+        }
+    }
+
+    /// The speed of the driver divided by the speed of the driven gear.
+    pub fn ratio(&self) -> f64 {
+        self.driven_teeth as f64 / self.driver_teeth as f64
+    }
+
+    /// The distance between the axes of the gears.
+    pub fn center_distance(&self) -> f64 {
+        self.module * (self.driver_teeth + self.driven_teeth) as f64 / 2.0
+    }
+
+    /// The angle the driven gear is turned so its teeth fit between the
+    /// teeth of the driver.
+    fn driven_phase(&self) -> f64 {
+        if self.driven_teeth % 2 == 0 {
+            180.0 / self.driven_teeth as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Creates the gears in mesh.
+    ///
+    /// The driver is centered on the Z axis and the driven gear is on the +X axis.
+    ///
+    /// thickness: The thickness of the gears.
+    ///
+    /// pressure_angle: The pressure angle in degrees, usually 20.
+    ///
+    /// segments: The number of segments on each side of a tooth.
+    ///
+    /// return: The gears.
+    pub fn into_scad(self, thickness: f64, pressure_angle: f64, segments: u64) -> Scad {
+        let driver = spur_gear(
+            self.module,
+            self.driver_teeth,
+            thickness,
+            pressure_angle,
+            segments,
+        );
+        let driven = spur_gear(
+            self.module,
+            self.driven_teeth,
+            thickness,
+            pressure_angle,
+            segments,
+        );
+        self.mesh(driver, driven)
+    }
+
+    /// Creates the helical gears in mesh.
+    ///
+    /// The driven gear has the opposite helix angle of the driver.
+    ///
+    /// thickness: The thickness of the gears.
+    ///
+    /// pressure_angle: The pressure angle in degrees, usually 20.
+    ///
+    /// helix_angle: The helix angle of the driver in degrees.
+    ///
+    /// segments: The number of segments on each side of a tooth.
+    ///
+    /// return: The gears.
+    pub fn into_helical_scad(
+        self,
+        thickness: f64,
+        pressure_angle: f64,
+        helix_angle: f64,
+        segments: u64,
+    ) -> Scad {
+        let driver = helical_gear(
+            self.module,
+            self.driver_teeth,
+            thickness,
+            pressure_angle,
+            helix_angle,
+            segments,
+        );
+        let driven = helical_gear(
+            self.module,
+            self.driven_teeth,
+            thickness,
+            pressure_angle,
+            -helix_angle,
+            segments,
+        );
+        self.mesh(driver, driven)
+    }
+
+    /// Creates the herringbone gears in mesh.
+    ///
+    /// thickness: The thickness of the gears.
+    ///
+    /// pressure_angle: The pressure angle in degrees, usually 20.
+    ///
+    /// helix_angle: The helix angle of the lower half of the driver in degrees.
+    ///
+    /// segments: The number of segments on each side of a tooth.
+    ///
+    /// return: The gears.
+    pub fn into_herringbone_scad(
+        self,
+        thickness: f64,
+        pressure_angle: f64,
+        helix_angle: f64,
+        segments: u64,
+    ) -> Scad {
+        let driver = herringbone_gear(
+            self.module,
+            self.driver_teeth,
+            thickness,
+            pressure_angle,
+            helix_angle,
+            segments,
+        );
+        let driven = herringbone_gear(
+            self.module,
+            self.driven_teeth,
+            thickness,
+            pressure_angle,
+            -helix_angle,
+            segments,
+        );
+        self.mesh(driver, driven)
+    }
+
+    /// Positions the driven gear in mesh with the driver.
+    fn mesh(&self, driver: Scad, driven: Scad) -> Scad {
+        union!(
+            driver;
+            translate!([self.center_distance(), 0.0, 0.0],
+                rotate!(self.driven_phase(), driven;);
+            );
+        )
+    }
+}
+
+/// Finds the pair of tooth counts closest to a ratio.
+///
+/// Ties are broken by the smallest gears.
+///
+/// ratio: The speed of the driver divided by the speed of the driven gear,
+/// less than 1 speeds up.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// min_teeth: The fewest teeth on a gear, 17 avoids undercut at 20 degrees.
+///
+/// max_teeth: The most teeth on a gear.
+///
+/// return: The pair or None when no tooth counts are allowed.
+pub fn design_gear_pair(
+    ratio: f64,
+    module: f64,
+    min_teeth: u64,
+    max_teeth: u64,
+) -> Option<GearPair> {
+    assert!(ratio > 0.0);
+    let mut best: Option<(f64, GearPair)> = None;
+    for driver_teeth in min_teeth.max(3)..=max_teeth {
+        let driven_teeth = (driver_teeth as f64 * ratio).round() as u64;
+        if driven_teeth < min_teeth.max(3) || driven_teeth > max_teeth {
+            continue;
+        }
+        let pair = GearPair::new(module, driver_teeth, driven_teeth);
+        let error = (pair.ratio() / ratio - 1.0).abs();
+        if best.as_ref().map_or(true, |(e, _)| error < *e - 1.0e-12) {
+            best = Some((error, pair));
+        }
+    }
+    best.map(|(_, pair)| pair)
+}
+
+/// Designs a compound gear train with the fewest stages for a ratio.
+///
+/// Each stage after the first is driven by a gear on the same shaft as the
+/// driven gear of the stage before. Stages are added until the ratio is
+/// within tolerance.
+///
+/// ratio: The speed of the input divided by the speed of the output.
+///
+/// module: The pitch diameter divided by the number of teeth.
+///
+/// min_teeth: The fewest teeth on a gear.
+///
+/// max_teeth: The most teeth on a gear.
+///
+/// tolerance: The allowed relative error of the ratio e.g. 0.01 for 1%.
+///
+/// max_stages: The most stages to try.
+///
+/// return: The stages or None when the ratio can't be made.
+pub fn design_gear_train(
+    ratio: f64,
+    module: f64,
+    min_teeth: u64,
+    max_teeth: u64,
+    tolerance: f64,
+    max_stages: usize,
+) -> Option<Vec<GearPair>> {
+    for n_stages in 1..=max_stages {
+        let mut stages = Vec::with_capacity(n_stages);
+        let mut remaining = ratio;
+        for stage in 0..n_stages {
+            let target = remaining.powf(1.0 / (n_stages - stage) as f64);
+            let Some(pair) = design_gear_pair(target, module, min_teeth, max_teeth) else {
+                break;
+            };
+            remaining /= pair.ratio();
+            stages.push(pair);
+        }
+        if stages.len() == n_stages && (train_ratio(&stages) / ratio - 1.0).abs() <= tolerance {
+            return Some(stages);
+        }
+    }
+    None
+}
+
+/// The overall ratio of a compound gear train.
+pub fn train_ratio(stages: &[GearPair]) -> f64 {
+    stages.iter().map(|stage| stage.ratio()).product()
+}
+
+/// Creates the gears of a compound gear train in mesh.
+///
+/// The input gear is centered on the Z axis, each shaft is further along +X
+/// and each stage is one thickness higher than the one before.
+///
+/// stages: The stages of the train.
+///
+/// thickness: The thickness of the gears.
+///
+/// pressure_angle: The pressure angle in degrees, usually 20.
+///
+/// segments: The number of segments on each side of a tooth.
+///
+/// return: The gears.
+pub fn gear_train(stages: &[GearPair], thickness: f64, pressure_angle: f64, segments: u64) -> Scad {
+    let mut children = Vec::with_capacity(stages.len());
+    let mut x = 0.0;
+    for (i, stage) in stages.iter().enumerate() {
+        let z = i as f64 * thickness;
+        children.push(translate!([x, 0.0, z],
+            stage.into_scad(thickness, pressure_angle, segments);
+        ));
+        x += stage.center_distance();
+    }
+    Scad {
+        op: ScadOp::Union,
+        children,
+    }
+}
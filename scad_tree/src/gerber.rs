@@ -0,0 +1,347 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Reads drill holes from Excellon files and board outlines from Gerber files.
+
+use crate::{clip::signed_area, dxf::chain, units::MM_PER_INCH, PcbHole, Pt2, Pt2s, ScadError};
+
+const EPSILON: f64 = 1.0e-6;
+
+/// How the digits of a coordinate without a decimal point are read.
+#[derive(Clone, Copy)]
+struct NumberFormat {
+    integers: usize,
+    decimals: usize,
+    /// Leading zeros are left out, otherwise trailing zeros are.
+    omit_leading: bool,
+}
+
+impl NumberFormat {
+    fn parse(&self, text: &str) -> Option<f64> {
+        if text.contains('.') {
+            return text.parse().ok();
+        }
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => (-1.0, digits),
+            None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let value: f64 = if self.omit_leading {
+            digits.parse::<f64>().ok()? / 10f64.powi(self.decimals as i32)
+        } else {
+            let padded = format!("{:0<width$}", digits, width = self.integers + self.decimals);
+            padded.parse::<f64>().ok()? / 10f64.powi((padded.len() - self.integers) as i32)
+        };
+        Some(sign * value)
+    }
+}
+
+/// Splits a command like X1.5Y-2 into its letters and values.
+fn words(text: &str) -> Vec<(char, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        if c.is_ascii_alphabetic() {
+            if let Some((letter, from)) = start {
+                result.push((letter, &text[from..i]));
+            }
+            start = Some((c.to_ascii_uppercase(), i + 1));
+        }
+    }
+    if let Some((letter, from)) = start {
+        result.push((letter, &text[from..]));
+    }
+    result
+}
+
+fn bad(kind: &str, text: &str) -> ScadError {
+    ScadError::Parse(format!("{}: bad number in {:?}", kind, text))
+}
+
+impl PcbHole {
+    /// Reads the holes in the text of an Excellon drill file.
+    ///
+    /// See from_excellon.
+    pub fn from_excellon_str(text: &str) -> Result<Vec<PcbHole>, ScadError> {
+        let mut scale = 1.0;
+        let mut format = NumberFormat {
+            integers: 3,
+            decimals: 3,
+            omit_leading: true,
+        };
+        let mut format_given = false;
+        let mut tools: Vec<(u32, f64)> = Vec::new();
+        let mut diameter = 0.0;
+        let mut position = Pt2::new(0.0, 0.0);
+        let mut holes = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix(';') {
+                // KiCad and Altium write the digits of the format in a comment
+                if let Some(digits) = comment.trim().strip_prefix("FILE_FORMAT=") {
+                    if let Some((i, d)) = digits.trim().split_once(':') {
+                        format.integers = i.trim().parse().unwrap_or(format.integers);
+                        format.decimals = d.trim().parse().unwrap_or(format.decimals);
+                        format_given = true;
+                    }
+                }
+                continue;
+            }
+            let upper = line.to_ascii_uppercase();
+            if upper.starts_with("METRIC")
+                || upper.starts_with("INCH")
+                || upper == "M71"
+                || upper == "M72"
+            {
+                let inch = upper.starts_with("INCH") || upper == "M72";
+                scale = if inch { MM_PER_INCH } else { 1.0 };
+                if !format_given {
+                    format.integers = if inch { 2 } else { 3 };
+                    format.decimals = if inch { 4 } else { 3 };
+                }
+                if upper.contains(",LZ") {
+                    format.omit_leading = false;
+                } else if upper.contains(",TZ") {
+                    format.omit_leading = true;
+                }
+                if let Some(digits) = upper.split(',').find(|s| s.contains('.')) {
+                    if let Some((i, d)) = digits.split_once('.') {
+                        format.integers = i.len();
+                        format.decimals = d.len();
+                    }
+                }
+                continue;
+            }
+            let words = words(&upper);
+            match words.first() {
+                Some(('T', _)) => {
+                    let number: u32 = words[0].1.parse().map_err(|_| bad("excellon", line))?;
+                    if let Some((_, value)) = words.iter().find(|(c, _)| *c == 'C') {
+                        let value: f64 = value.parse().map_err(|_| bad("excellon", line))?;
+                        tools.push((number, value * scale));
+                    } else {
+                        diameter = tools
+                            .iter()
+                            .rev()
+                            .find(|(n, _)| *n == number)
+                            .map(|(_, d)| *d)
+                            .unwrap_or(0.0);
+                    }
+                }
+                Some(('X', _)) | Some(('Y', _)) => {
+                    // a routed slot is X Y G85 X Y, the hole is put in its middle
+                    let mut points = Vec::new();
+                    for (letter, value) in words.iter() {
+                        match letter {
+                            'X' => {
+                                position.x =
+                                    format.parse(value).ok_or_else(|| bad("excellon", line))?
+                                        * scale;
+                            }
+                            'Y' => {
+                                position.y =
+                                    format.parse(value).ok_or_else(|| bad("excellon", line))?
+                                        * scale;
+                            }
+                            'G' => points.push(position),
+                            _ => (),
+                        }
+                    }
+                    points.push(position);
+                    let center =
+                        points.iter().fold(Pt2::new(0.0, 0.0), |a, p| a + *p) / points.len() as f64;
+                    if diameter > 0.0 {
+                        holes.push(PcbHole {
+                            position: center,
+                            diameter,
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(holes)
+    }
+
+    /// Reads the holes in an Excellon drill file.
+    ///
+    /// The tool table and the drill hits are read in millimeters or inches with
+    /// decimal points or with the digits given by the header. Routed slots are
+    /// read as a hole in the middle of the slot. Positions and diameters are
+    /// returned in millimeters.
+    ///
+    /// path: The path of the drill file.
+    ///
+    /// return: The holes or an error.
+    pub fn from_excellon(path: &str) -> Result<Vec<PcbHole>, ScadError> {
+        Self::from_excellon_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Appends the points of an arc from the last point of path to end.
+fn arc(path: &mut Pt2s, end: Pt2, center: Pt2, clockwise: bool, segments: u64) {
+    let start = path[path.len() - 1];
+    let (a0, a1) = (start - center, end - center);
+    let mut sweep = a0.y.atan2(a0.x) - a1.y.atan2(a1.x);
+    if !clockwise {
+        sweep = -sweep;
+    }
+    while sweep <= EPSILON {
+        sweep += std::f64::consts::TAU;
+    }
+    let n = ((sweep / std::f64::consts::TAU * segments as f64).ceil() as u64).max(1);
+    let sign = if clockwise { -1.0 } else { 1.0 };
+    for i in 1..n {
+        path.push(center + a0.rotated((sign * sweep * i as f64 / n as f64).to_degrees()));
+    }
+    path.push(end);
+}
+
+/// Reads the profiles in the text of a Gerber file.
+///
+/// See from_gerber.
+pub fn from_gerber_str(text: &str, segments: u64) -> Result<Vec<Pt2s>, ScadError> {
+    let mut format = NumberFormat {
+        integers: 3,
+        decimals: 6,
+        omit_leading: true,
+    };
+    let mut scale = 1.0;
+    let mut clockwise = None;
+    let mut position = Pt2::new(0.0, 0.0);
+    let mut pieces: Vec<Pt2s> = Vec::new();
+    let mut path = Pt2s::new();
+    let mut finish = |path: &mut Pt2s| {
+        if path.len() > 1 {
+            pieces.push(std::mem::take(path));
+        }
+        path.clear();
+    };
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    for command in text.split('*') {
+        let command = command.trim_matches('%');
+        if let Some(spec) = command.strip_prefix("FS") {
+            let omit_leading = !spec.starts_with('T');
+            if let Some(x) = spec.find('X') {
+                let digits: Vec<usize> = spec[x + 1..]
+                    .chars()
+                    .take(2)
+                    .filter_map(|c| c.to_digit(10).map(|d| d as usize))
+                    .collect();
+                if digits.len() == 2 {
+                    format = NumberFormat {
+                        integers: digits[0],
+                        decimals: digits[1],
+                        omit_leading,
+                    };
+                }
+            }
+            continue;
+        }
+        match command {
+            "MOMM" => scale = 1.0,
+            "MOIN" => scale = MM_PER_INCH,
+            _ => (),
+        }
+        // skip comments, attributes and aperture definitions
+        let mut letters = command.chars().take(2);
+        let first = letters.next().unwrap_or(' ');
+        let second = letters.next().unwrap_or(' ');
+        if command.starts_with("G04")
+            || (first.is_ascii_alphabetic() && second.is_ascii_alphabetic())
+        {
+            continue;
+        }
+        let mut next = position;
+        let (mut i, mut j) = (0.0, 0.0);
+        let mut operation = None;
+        for (letter, value) in words(command) {
+            let number = || format.parse(value).ok_or_else(|| bad("gerber", command));
+            match letter {
+                'X' => next.x = number()? * scale,
+                'Y' => next.y = number()? * scale,
+                'I' => i = number()? * scale,
+                'J' => j = number()? * scale,
+                'G' => match value.parse::<u32>().unwrap_or(0) {
+                    1 => clockwise = None,
+                    2 => clockwise = Some(true),
+                    3 => clockwise = Some(false),
+                    _ => (),
+                },
+                'D' => operation = value.parse::<u32>().ok(),
+                _ => (),
+            }
+        }
+        match operation {
+            Some(1) => {
+                if path.is_empty() {
+                    path.push(position);
+                }
+                match clockwise {
+                    Some(cw) => arc(&mut path, next, position + Pt2::new(i, j), cw, segments),
+                    None => path.push(next),
+                }
+            }
+            Some(2) | Some(3) => finish(&mut path),
+            Some(_) => finish(&mut path),
+            None => (),
+        }
+        position = next;
+    }
+    finish(&mut path);
+
+    Ok(chain(pieces)
+        .into_iter()
+        .filter_map(|mut points| {
+            let n = points.len();
+            if n > 3 && (points[0] - points[n - 1]).len() < EPSILON {
+                points.pop();
+                if signed_area(&points) > 0.0 {
+                    points.reverse();
+                }
+                Some(points)
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+/// Reads the closed outlines drawn in a Gerber file, e.g. the board outline
+/// or Edge.Cuts layer.
+///
+/// Lines and arcs are followed as drawn, the widths of the apertures are
+/// ignored. Strokes that share end points are joined, closed outlines are
+/// made clockwise and open paths are dropped. Coordinates are returned in
+/// millimeters.
+///
+/// path: The path of the Gerber file.
+///
+/// segments: The number of segments in a full circle.
+///
+/// return: The outlines or an error.
+pub fn from_gerber(path: &str, segments: u64) -> Result<Vec<Pt2s>, ScadError> {
+    from_gerber_str(&std::fs::read_to_string(path)?, segments)
+}
@@ -0,0 +1,217 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::prelude::*;
+
+const GRID_SIZE: f64 = 42.0;
+const HEIGHT_UNIT: f64 = 7.0;
+const CLEARANCE: f64 = 0.5;
+const OUTER_RADIUS: f64 = 3.75;
+const BASE_HEIGHT: f64 = 4.75;
+const LIP_HEIGHT: f64 = 4.4;
+const HOLE_SPACING: f64 = 26.0;
+const MAGNET_DIAMETER: f64 = 6.5;
+const MAGNET_DEPTH: f64 = 2.4;
+const SCREW_DIAMETER: f64 = 3.0;
+const SCREW_DEPTH: f64 = 6.0;
+
+/// A Gridfinity style stackable storage bin.
+///
+/// The bin sits on the XY plane centered on the origin. Dimensions follow the
+/// Gridfinity spec, a 42mm grid with 7mm height units.
+#[derive(Clone)]
+pub struct GridfinityBin {
+    /// The number of grid units in the X direction.
+    pub grid_x: u64,
+    /// The number of grid units in the Y direction.
+    pub grid_y: u64,
+    /// The height of the bin in 7mm units, not counting the stacking lip.
+    pub height_units: u64,
+    pub wall_thickness: f64,
+    /// The thickness of the floor above the base.
+    pub floor_thickness: f64,
+    /// Adds 6.5x2.4mm magnet holes to the bottom of each base.
+    pub magnet_holes: bool,
+    /// Adds 3mm screw holes to the bottom of each base.
+    pub screw_holes: bool,
+    /// Adds a 45 degree label shelf along the back wall.
+    pub label_tab: bool,
+    /// The depth of the label shelf.
+    pub label_tab_width: f64,
+    /// Adds the stacking lip to the top of the bin.
+    pub stacking_lip: bool,
+    /// The number of segments in a circle.
+    pub segments: u64,
+}
+
+impl Default for GridfinityBin {
+    fn default() -> Self {
+        Self {
+            grid_x: 1,
+            grid_y: 1,
+            height_units: 3,
+            wall_thickness: 1.2,
+            floor_thickness: 1.2,
+            magnet_holes: false,
+            screw_holes: false,
+            label_tab: false,
+            label_tab_width: 13.0,
+            stacking_lip: true,
+            segments: 36,
+        }
+    }
+}
+
+impl GridfinityBin {
+    /// Create the bin.
+    pub fn into_scad(&self) -> Scad {
+        assert!(self.grid_x > 0 && self.grid_y > 0);
+        assert!(self.height_units > 0);
+        let width = self.grid_x as f64 * GRID_SIZE - CLEARANCE;
+        let depth = self.grid_y as f64 * GRID_SIZE - CLEARANCE;
+        let height = self.height_units as f64 * HEIGHT_UNIT;
+        assert!(height > BASE_HEIGHT + self.floor_thickness);
+        let inner_width = width - self.wall_thickness * 2.0;
+        let inner_depth = depth - self.wall_thickness * 2.0;
+        let inner_radius = (OUTER_RADIUS - self.wall_thickness).max(0.5);
+
+        let mut bin = translate!([0.0, 0.0, BASE_HEIGHT],
+            linear_extrude!(height - BASE_HEIGHT,
+                polygon!(self.rounded_rect(width, depth, OUTER_RADIUS));
+            );
+        );
+        for x in 0..self.grid_x {
+            for y in 0..self.grid_y {
+                bin = bin + translate!([self.cell_x(x), self.cell_y(y), 0.0], self.foot(););
+            }
+        }
+        if self.stacking_lip {
+            bin = bin + self.lip(width, depth, height);
+        }
+
+        let mut cavity = translate!([0.0, 0.0, BASE_HEIGHT + self.floor_thickness],
+            linear_extrude!(height + LIP_HEIGHT,
+                polygon!(self.rounded_rect(inner_width, inner_depth, inner_radius));
+            );
+        );
+        if self.label_tab {
+            // leave a 45 degree shelf along the back wall
+            let shelf = self.label_tab_width.min(inner_depth / 2.0);
+            let back = inner_depth / 2.0;
+            cavity = cavity
+                - translate!([-inner_width / 2.0, 0.0, 0.0],
+                    rotate!([90.0, 0.0, 90.0],
+                        linear_extrude!(inner_width,
+                            polygon!(Pt2s::from_pt2s(vec![
+                                Pt2::new(back + 1.0, height),
+                                Pt2::new(back + 1.0, height - shelf - 1.0),
+                                Pt2::new(back - shelf, height),
+                            ]));
+                        );
+                    );
+                );
+        }
+        bin = bin - cavity;
+
+        if self.magnet_holes || self.screw_holes {
+            let mut holes = Vec::new();
+            for x in 0..self.grid_x {
+                for y in 0..self.grid_y {
+                    for (hx, hy) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+                        holes.push(translate!([
+                            self.cell_x(x) + hx * HOLE_SPACING / 2.0,
+                            self.cell_y(y) + hy * HOLE_SPACING / 2.0,
+                            0.0
+                        ], self.hole();));
+                    }
+                }
+            }
+            bin = Scad {
+                op: ScadOp::Difference,
+                children: std::iter::once(bin).chain(holes).collect(),
+            };
+        }
+        bin
+    }
+
+    fn cell_x(&self, x: u64) -> f64 {
+        (x as f64 - (self.grid_x - 1) as f64 / 2.0) * GRID_SIZE
+    }
+
+    fn cell_y(&self, y: u64) -> f64 {
+        (y as f64 - (self.grid_y - 1) as f64 / 2.0) * GRID_SIZE
+    }
+
+    fn rounded_rect(&self, width: f64, depth: f64, radius: f64) -> Pt2s {
+        dim2::rounded_rect(width, depth, radius, self.segments / 4, true)
+    }
+
+    /// The stepped base under each grid cell.
+    fn foot(&self) -> Scad {
+        let size = GRID_SIZE - CLEARANCE;
+        let bottom = self.rounded_rect(size - 5.9, size - 5.9, 0.8);
+        let lower = self.rounded_rect(size - 4.3, size - 4.3, 1.6);
+        let top = self.rounded_rect(size, size, OUTER_RADIUS);
+        union!(
+            Polyhedron::loft(&bottom, &lower, 0.8).into_scad();
+            translate!([0.0, 0.0, 0.8], Polyhedron::loft(&lower, &lower, 1.8).into_scad(););
+            translate!([0.0, 0.0, 2.6], Polyhedron::loft(&lower, &top, 2.15).into_scad(););
+        )
+    }
+
+    /// The stacking lip that receives the base of the bin above.
+    fn lip(&self, width: f64, depth: f64, height: f64) -> Scad {
+        let outer = self.rounded_rect(width, depth, OUTER_RADIUS);
+        let bottom = self.rounded_rect(width - 5.2, depth - 5.2, OUTER_RADIUS - 2.6);
+        let lower = self.rounded_rect(width - 3.8, depth - 3.8, OUTER_RADIUS - 1.9);
+        let top = self.rounded_rect(width - 0.2, depth - 0.2, OUTER_RADIUS - 0.1);
+        translate!([0.0, 0.0, height],
+            difference!(
+                linear_extrude!(LIP_HEIGHT, polygon!(outer););
+                translate!([0.0, 0.0, -0.01],
+                    Polyhedron::loft(&bottom, &lower, 0.71).into_scad();
+                );
+                translate!([0.0, 0.0, 0.7], Polyhedron::loft(&lower, &lower, 1.8).into_scad(););
+                translate!([0.0, 0.0, 2.5], Polyhedron::loft(&lower, &top, 1.91).into_scad(););
+            );
+        )
+    }
+
+    fn hole(&self) -> Scad {
+        let mut hole = Vec::new();
+        if self.magnet_holes {
+            hole.push(translate!([0.0, 0.0, -1.0],
+                cylinder!(MAGNET_DEPTH + 1.0, MAGNET_DIAMETER / 2.0, fn=self.segments);
+            ));
+        }
+        if self.screw_holes {
+            hole.push(translate!([0.0, 0.0, -1.0],
+                cylinder!(SCREW_DEPTH + 1.0, SCREW_DIAMETER / 2.0, fn=self.segments);
+            ));
+        }
+        Scad {
+            op: ScadOp::Union,
+            children: hole,
+        }
+    }
+}
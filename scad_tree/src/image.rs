@@ -0,0 +1,409 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! A minimal image reader that turns grayscale images into heightmaps and lithophanes.
+//!
+//! Reads binary and text PGM and PPM files and uncompressed BMP files. Convert
+//! other formats with any image editor first.
+
+use crate::{Faces, Polyhedron, Pt3, Pt3s, ScadError};
+
+fn parse_error(message: &str) -> ScadError {
+    ScadError::Parse(format!("image: {}", message))
+}
+
+/// The brightness of a color from 0 to 1.
+fn luma(r: f64, g: f64, b: f64) -> f64 {
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+/// The brightness of each pixel of an image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heightmap {
+    /// The number of pixels in a row.
+    pub width: usize,
+    /// The number of rows.
+    pub height: usize,
+    /// The brightness from 0 to 1 of each pixel, row by row from the top left.
+    pub values: Vec<f64>,
+}
+
+impl Heightmap {
+    /// Creates a heightmap from brightness values.
+    ///
+    /// width: The number of pixels in a row.
+    ///
+    /// height: The number of rows.
+    ///
+    /// values: The brightness from 0 to 1 of each pixel, row by row from the top left.
+    ///
+    /// return: The heightmap.
+    pub fn new(width: usize, height: usize, values: Vec<f64>) -> Self {
+        assert!(width > 0 && height > 0 && width.checked_mul(height) == Some(values.len()));
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// Load an image from a .pgm, .ppm or .bmp file.
+    ///
+    /// path: The path of the image file.
+    ///
+    /// return: The heightmap or an error.
+    pub fn from_file(path: &str) -> Result<Self, ScadError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Load an image from the bytes of a .pgm, .ppm or .bmp file.
+    ///
+    /// The format is found from the first bytes of the data. Color images are
+    /// converted to their brightness.
+    ///
+    /// data: The bytes of the file.
+    ///
+    /// return: The heightmap or an error.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ScadError> {
+        match data {
+            [b'P', b'2' | b'3' | b'5' | b'6', ..] => Self::from_netpbm(data),
+            [b'B', b'M', ..] => Self::from_bmp(data),
+            _ => Err(parse_error("unsupported format")),
+        }
+    }
+
+    fn from_netpbm(data: &[u8]) -> Result<Self, ScadError> {
+        let kind = data[1];
+        let mut pos = 2;
+        // Reads the next whitespace separated number, skipping comments.
+        let mut number = || -> Result<usize, ScadError> {
+            loop {
+                match data.get(pos) {
+                    Some(b'#') => {
+                        while data.get(pos).is_some_and(|c| *c != b'\n') {
+                            pos += 1;
+                        }
+                    }
+                    Some(c) if c.is_ascii_whitespace() => pos += 1,
+                    Some(_) => break,
+                    None => return Err(parse_error("unexpected end of file")),
+                }
+            }
+            let start = pos;
+            while data.get(pos).is_some_and(|c| c.is_ascii_digit()) {
+                pos += 1;
+            }
+            std::str::from_utf8(&data[start..pos])
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| parse_error("bad number"))
+        };
+        let width = number()?;
+        let height = number()?;
+        let max = number()?;
+        if width == 0 || height == 0 || max == 0 || max > 65535 {
+            return Err(parse_error("bad header"));
+        }
+        let channels = if kind == b'3' || kind == b'6' { 3 } else { 1 };
+        let count = width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(channels))
+            .ok_or_else(|| parse_error("image too large"))?;
+        let samples: Vec<usize> = if kind == b'2' || kind == b'3' {
+            // each sample is at least a digit and a separator
+            if count > data.len() {
+                return Err(parse_error("not enough pixels"));
+            }
+            (0..count).map(|_| number()).collect::<Result<_, _>>()?
+        } else {
+            // A single whitespace byte separates the header from the pixels.
+            let start = pos + 1;
+            let bytes = if max > 255 { 2 } else { 1 };
+            let pixels = count
+                .checked_mul(bytes)
+                .and_then(|n| n.checked_add(start))
+                .and_then(|end| data.get(start..end))
+                .ok_or_else(|| parse_error("not enough pixels"))?;
+            if bytes == 2 {
+                pixels
+                    .chunks(2)
+                    .map(|b| (b[0] as usize) << 8 | b[1] as usize)
+                    .collect()
+            } else {
+                pixels.iter().map(|b| *b as usize).collect()
+            }
+        };
+        let max = max as f64;
+        let values = samples
+            .chunks(channels)
+            .map(|s| {
+                if channels == 3 {
+                    luma(s[0] as f64, s[1] as f64, s[2] as f64) / max
+                } else {
+                    s[0] as f64 / max
+                }
+            })
+            .collect();
+        Ok(Self::new(width, height, values))
+    }
+
+    fn from_bmp(data: &[u8]) -> Result<Self, ScadError> {
+        let u16_at = |i: usize| {
+            data.get(i..i + 2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .ok_or_else(|| parse_error("unexpected end of file"))
+        };
+        let u32_at = |i: usize| {
+            data.get(i..i + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or_else(|| parse_error("unexpected end of file"))
+        };
+        let offset = u32_at(10)? as usize;
+        let header_size = u32_at(14)? as usize;
+        let width = u32_at(18)? as i32;
+        let height = u32_at(22)? as i32;
+        let bits = u16_at(28)?;
+        let compression = u32_at(30)?;
+        if width <= 0 || height == 0 {
+            return Err(parse_error("bad header"));
+        }
+        if compression != 0 && !(compression == 3 && bits == 32) {
+            return Err(parse_error("compressed bitmaps are not supported"));
+        }
+        let (width, top_down) = (width as usize, height < 0);
+        let height = height.unsigned_abs() as usize;
+        let palette: Vec<f64> = if bits <= 8 {
+            let colors = match u32_at(46)? {
+                0 => 1 << bits,
+                n => n as usize,
+            };
+            let start = 14 + header_size;
+            (0..colors)
+                .map(|i| {
+                    let c = data
+                        .get(start + i * 4..start + i * 4 + 3)
+                        .ok_or_else(|| parse_error("bad palette"))?;
+                    Ok(luma(c[2] as f64, c[1] as f64, c[0] as f64) / 255.0)
+                })
+                .collect::<Result<_, ScadError>>()?
+        } else {
+            Vec::new()
+        };
+        let stride = width
+            .checked_mul(bits as usize)
+            .map(|row_bits| row_bits.div_ceil(32) * 4)
+            .ok_or_else(|| parse_error("image too large"))?;
+        let size = stride
+            .checked_mul(height)
+            .and_then(|n| n.checked_add(offset))
+            .ok_or_else(|| parse_error("image too large"))?;
+        if size > data.len() {
+            return Err(parse_error("not enough pixels"));
+        }
+        let mut values = Vec::with_capacity(width * height);
+        for row in 0..height {
+            let line = if top_down { row } else { height - 1 - row };
+            let start = offset + line * stride;
+            let bytes = data
+                .get(start..start + stride)
+                .ok_or_else(|| parse_error("not enough pixels"))?;
+            for x in 0..width {
+                let value = match bits {
+                    1 | 4 | 8 => {
+                        let bit = x * bits as usize;
+                        let index = (bytes[bit / 8] >> (8 - bits as usize - bit % 8))
+                            & ((1u16 << bits) - 1) as u8;
+                        *palette
+                            .get(index as usize)
+                            .ok_or_else(|| parse_error("bad palette index"))?
+                    }
+                    24 | 32 => {
+                        let p = &bytes[x * bits as usize / 8..];
+                        luma(p[2] as f64, p[1] as f64, p[0] as f64) / 255.0
+                    }
+                    _ => return Err(parse_error("unsupported bit depth")),
+                };
+                values.push(value);
+            }
+        }
+        Ok(Self::new(width, height, values))
+    }
+
+    /// The brightness of a pixel.
+    ///
+    /// x: The column, 0 is the left.
+    ///
+    /// y: The row, 0 is the top.
+    ///
+    /// return: The brightness from 0 to 1.
+    pub fn get(&self, x: usize, y: usize) -> f64 {
+        self.values[y * self.width + x]
+    }
+
+    /// Resamples the image to a new size with bilinear filtering.
+    ///
+    /// width: The new number of pixels in a row.
+    ///
+    /// height: The new number of rows.
+    ///
+    /// return: The resampled heightmap.
+    pub fn resized(&self, width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0);
+        let count = width
+            .checked_mul(height)
+            .expect("the heightmap is too large");
+        let sample = |t: usize, n: usize, size: usize| {
+            if n == 1 {
+                (0, 0, 0.0)
+            } else {
+                let f = t as f64 * (size - 1) as f64 / (n - 1) as f64;
+                let i = (f.floor() as usize).min(size - 1);
+                (i, (i + 1).min(size - 1), f - i as f64)
+            }
+        };
+        let mut values = Vec::with_capacity(count);
+        for y in 0..height {
+            let (y0, y1, ty) = sample(y, height, self.height);
+            for x in 0..width {
+                let (x0, x1, tx) = sample(x, width, self.width);
+                let top = self.get(x0, y0) * (1.0 - tx) + self.get(x1, y0) * tx;
+                let bottom = self.get(x0, y1) * (1.0 - tx) + self.get(x1, y1) * tx;
+                values.push(top * (1.0 - ty) + bottom * ty);
+            }
+        }
+        Self::new(width, height, values)
+    }
+
+    /// Formats the heightmap as a data file for the surface() primitive.
+    ///
+    /// The rows are written from the bottom of the image up so the image is
+    /// not flipped in OpenSCAD, with one unit between points.
+    ///
+    /// min_height: The height of black.
+    ///
+    /// max_height: The height of white.
+    ///
+    /// return: The contents of the .dat file.
+    pub fn to_surface_dat(&self, min_height: f64, max_height: f64) -> String {
+        let mut result = String::new();
+        for row in (0..self.height).rev() {
+            let line: Vec<String> = (0..self.width)
+                .map(|x| {
+                    let h = min_height + (max_height - min_height) * self.get(x, row);
+                    format!("{}", crate::Float(h))
+                })
+                .collect();
+            result.push_str(&line.join(" "));
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Saves the heightmap as a data file for the surface() primitive.
+    ///
+    /// path: The path of the .dat file.
+    ///
+    /// min_height: The height of black.
+    ///
+    /// max_height: The height of white.
+    ///
+    /// return: An error if the file could not be written.
+    pub fn save_surface_dat(
+        &self,
+        path: &str,
+        min_height: f64,
+        max_height: f64,
+    ) -> Result<(), ScadError> {
+        std::fs::write(path, self.to_surface_dat(min_height, max_height))?;
+        Ok(())
+    }
+
+    /// Creates a lithophane, a plate that shows the image when lit from behind.
+    ///
+    /// The plate lies on the XY plane with the lower left corner at the origin
+    /// and the relief on top. Dark pixels are thick and light pixels are thin.
+    /// There is a point for each pixel so resize large images first.
+    ///
+    /// width: The size of the plate in the X direction, the depth follows
+    /// from the aspect of the image.
+    ///
+    /// min_thickness: The thickness of white.
+    ///
+    /// max_thickness: The thickness of black.
+    ///
+    /// return: The lithophane.
+    pub fn lithophane(&self, width: f64, min_thickness: f64, max_thickness: f64) -> Polyhedron {
+        assert!(self.width > 1 && self.height > 1);
+        assert!(width > 0.0 && min_thickness > 0.0 && max_thickness >= min_thickness);
+        let (w, h) = (self.width, self.height);
+        let step = width / (w - 1) as f64;
+        let mut points = Pt3s::with_capacity(w * h * 2);
+        for z in [None, Some(0.0)] {
+            for row in 0..h {
+                for x in 0..w {
+                    let thickness =
+                        max_thickness - (max_thickness - min_thickness) * self.get(x, row);
+                    points.push(Pt3::new(
+                        x as f64 * step,
+                        (h - 1 - row) as f64 * step,
+                        z.unwrap_or(thickness),
+                    ));
+                }
+            }
+        }
+        let top = |x: usize, row: usize| (row * w + x) as u64;
+        let bottom = |x: usize, row: usize| (w * h + row * w + x) as u64;
+        let mut faces = Faces::with_capacity((w - 1) * (h - 1) * 4 + (w + h) * 4);
+        for row in 0..h - 1 {
+            for x in 0..w - 1 {
+                // Rows go down the image so row + 1 is toward -Y.
+                let (a, b, c, d) = (
+                    top(x, row + 1),
+                    top(x, row),
+                    top(x + 1, row),
+                    top(x + 1, row + 1),
+                );
+                faces.push_tri(a, b, c);
+                faces.push_tri(a, c, d);
+                let (a, b, c, d) = (
+                    bottom(x, row + 1),
+                    bottom(x, row),
+                    bottom(x + 1, row),
+                    bottom(x + 1, row + 1),
+                );
+                faces.push_tri(a, c, b);
+                faces.push_tri(a, d, c);
+            }
+        }
+        // The edge of the plate, clockwise seen from above.
+        let mut ring = Vec::with_capacity((w + h) * 2);
+        ring.extend((0..h).rev().map(|row| (0, row)));
+        ring.extend((1..w).map(|x| (x, 0)));
+        ring.extend((1..h).map(|row| (w - 1, row)));
+        ring.extend((1..w - 1).rev().map(|x| (x, h - 1)));
+        let ring_top: Vec<u64> = ring.iter().map(|(x, row)| top(*x, *row)).collect();
+        let ring_bottom: Vec<u64> = ring.iter().map(|(x, row)| bottom(*x, *row)).collect();
+        faces.push_strip(&ring_bottom, &ring_top);
+        Polyhedron::new(points, faces)
+    }
+}
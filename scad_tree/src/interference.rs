@@ -0,0 +1,322 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Finds collisions between the tagged parts of two trees.
+
+use crate::{eval::transform_matrix, prelude::*};
+
+/// The number of segments in a circle used to evaluate parts for the mesh distance.
+const QUALITY: u64 = 32;
+
+/// A pair of parts that are closer than the clearance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interference {
+    /// The part of the first tree.
+    pub a: Scad,
+    /// The tag of the part of the first tree.
+    pub a_tag: ScadTag,
+    /// The part of the second tree.
+    pub b: Scad,
+    /// The tag of the part of the second tree.
+    pub b_tag: ScadTag,
+    /// The distance between the meshes of the parts, 0 when they overlap, or
+    /// None when a part could not be evaluated and only the bounding boxes were
+    /// compared.
+    pub distance: Option<f64>,
+}
+
+impl Scad {
+    /// The tagged parts of the tree in world coordinates.
+    ///
+    /// Each part is the tagged subtree wrapped in the transforms above it. Tags
+    /// inside tagged subtrees, tags without children and tags in the subtracted
+    /// children of a difference are ignored. A tree without tags is one body.
+    ///
+    /// return: The tag and the part.
+    pub fn tagged_parts(&self) -> Vec<(ScadTag, Scad)> {
+        fn collect(scad: &Scad, transforms: &mut Vec<ScadOp>, parts: &mut Vec<(ScadTag, Scad)>) {
+            if let ScadOp::Tag(tag) = scad.op {
+                let mut part = match scad.children.len() {
+                    0 => return,
+                    1 => scad.children[0].clone(),
+                    _ => Scad {
+                        op: ScadOp::Union,
+                        children: scad.children.clone(),
+                    },
+                };
+                for op in transforms.iter().rev() {
+                    part = Scad {
+                        op: op.clone(),
+                        children: vec![part],
+                    };
+                }
+                parts.push((tag, part));
+                return;
+            }
+            let transform = transform_matrix(&scad.op).is_some();
+            if transform {
+                transforms.push(scad.op.clone());
+            }
+            let count = match scad.op {
+                ScadOp::Difference => 1,
+                _ => scad.children.len(),
+            };
+            for child in scad.children.iter().take(count) {
+                collect(child, transforms, parts);
+            }
+            if transform {
+                transforms.pop();
+            }
+        }
+        let mut parts = Vec::new();
+        collect(self, &mut Vec::new(), &mut parts);
+        if parts.is_empty() {
+            parts.push((ScadTag::Body, self.clone()));
+        }
+        parts
+    }
+
+    /// Checks two trees for parts that collide.
+    ///
+    /// Bodies must stay clearance away from the bodies and keep outs of the other
+    /// tree, keep outs may overlap each other. Parts that touch collide even with
+    /// no clearance. The bounding boxes are compared
+    /// first, then the meshes of the parts when both can be evaluated in Rust.
+    /// The mesh distance is a brute force search meant for tests.
+    ///
+    /// a: The first tree.
+    ///
+    /// b: The second tree.
+    ///
+    /// clearance: The smallest allowed distance between the parts.
+    ///
+    /// return: The pairs of parts that are too close, empty when there are none.
+    pub fn check_interference(a: &Scad, b: &Scad, clearance: f64) -> Vec<Interference> {
+        let b_parts = b.tagged_parts();
+        let mut result = Vec::new();
+        for (a_tag, a_part) in a.tagged_parts() {
+            let Some((a_min, a_max)) = a_part.bounds() else {
+                continue;
+            };
+            for (b_tag, b_part) in b_parts.iter() {
+                if a_tag == ScadTag::KeepOut && *b_tag == ScadTag::KeepOut {
+                    continue;
+                }
+                let Some((b_min, b_max)) = b_part.bounds() else {
+                    continue;
+                };
+                if a_min.x - clearance > b_max.x
+                    || a_min.y - clearance > b_max.y
+                    || a_min.z - clearance > b_max.z
+                    || b_min.x - clearance > a_max.x
+                    || b_min.y - clearance > a_max.y
+                    || b_min.z - clearance > a_max.z
+                {
+                    continue;
+                }
+                let distance = match (a_part.to_polyhedron(QUALITY), b_part.to_polyhedron(QUALITY))
+                {
+                    (Ok(a_mesh), Ok(b_mesh)) => {
+                        let distance = mesh_distance(&a_mesh, &b_mesh);
+                        if distance > 0.0 && distance >= clearance {
+                            continue;
+                        }
+                        Some(distance)
+                    }
+                    _ => None,
+                };
+                result.push(Interference {
+                    a: a_part.clone(),
+                    a_tag,
+                    b: b_part.clone(),
+                    b_tag: *b_tag,
+                    distance,
+                });
+            }
+        }
+        result
+    }
+}
+
+/// The triangles of a mesh, polygon faces are split into fans.
+fn triangles(mesh: &Polyhedron) -> Vec<[Pt3; 3]> {
+    let mut triangles = Vec::new();
+    for face in mesh.faces.iter() {
+        for i in 1..face.len().saturating_sub(1) {
+            triangles.push([
+                mesh.points[face[0] as usize],
+                mesh.points[face[i] as usize],
+                mesh.points[face[i + 1] as usize],
+            ]);
+        }
+    }
+    triangles
+}
+
+/// The distance between two closed meshes, 0 when they overlap.
+fn mesh_distance(a: &Polyhedron, b: &Polyhedron) -> f64 {
+    let a_triangles = triangles(a);
+    let b_triangles = triangles(b);
+    if a.points.iter().any(|p| inside(*p, &b_triangles))
+        || b.points.iter().any(|p| inside(*p, &a_triangles))
+    {
+        return 0.0;
+    }
+    let mut distance = f64::INFINITY;
+    for ta in a_triangles.iter() {
+        for tb in b_triangles.iter() {
+            distance = distance.min(triangle_distance(ta, tb));
+            if distance == 0.0 {
+                return 0.0;
+            }
+        }
+    }
+    distance
+}
+
+/// Tests if a point is inside a closed mesh by counting the crossings of a ray.
+fn inside(p: Pt3, triangles: &[[Pt3; 3]]) -> bool {
+    // an odd direction so the ray misses edges and points of typical meshes
+    let direction = Pt3::new(0.5773, 0.5774, 0.5775);
+    let crossings = triangles
+        .iter()
+        .filter(|t| ray_triangle(p, direction, t).is_some_and(|d| d > 0.0))
+        .count();
+    crossings % 2 == 1
+}
+
+/// The distance along a ray to a triangle, Möller–Trumbore.
+fn ray_triangle(origin: Pt3, direction: Pt3, t: &[Pt3; 3]) -> Option<f64> {
+    let e1 = t[1] - t[0];
+    let e2 = t[2] - t[0];
+    let p = direction.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < 1.0e-12 {
+        return None;
+    }
+    let s = origin - t[0];
+    let u = s.dot(p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = direction.dot(q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some(e2.dot(q) / det)
+}
+
+/// The distance between two triangles.
+fn triangle_distance(a: &[Pt3; 3], b: &[Pt3; 3]) -> f64 {
+    let mut distance = f64::INFINITY;
+    for i in 0..3 {
+        let (a0, a1) = (a[i], a[(i + 1) % 3]);
+        let (b0, b1) = (b[i], b[(i + 1) % 3]);
+        // an edge through the other triangle
+        if ray_triangle(a0, a1 - a0, b).is_some_and(|d| (0.0..=1.0).contains(&d))
+            || ray_triangle(b0, b1 - b0, a).is_some_and(|d| (0.0..=1.0).contains(&d))
+        {
+            return 0.0;
+        }
+        distance = distance
+            .min((a[i] - point_triangle(a[i], b)).len())
+            .min((b[i] - point_triangle(b[i], a)).len());
+        for j in 0..3 {
+            distance = distance.min(segment_distance(a0, a1, b[j], b[(j + 1) % 3]));
+        }
+    }
+    distance
+}
+
+/// The point of a triangle closest to p.
+fn point_triangle(p: Pt3, t: &[Pt3; 3]) -> Pt3 {
+    let (a, b, c) = (t[0], t[1], t[2]);
+    let normal = (b - a).cross(c - a);
+    let len2 = normal.len2();
+    if len2 > 0.0 {
+        let projected = p - normal * ((p - a).dot(normal) / len2);
+        let inside_edge = |u: Pt3, v: Pt3| (v - u).cross(projected - u).dot(normal) >= 0.0;
+        if inside_edge(a, b) && inside_edge(b, c) && inside_edge(c, a) {
+            return projected;
+        }
+    }
+    [(a, b), (b, c), (c, a)]
+        .iter()
+        .map(|(u, v)| segment_point(*u, *v, p))
+        .fold(a, |best, q| {
+            if (q - p).len2() < (best - p).len2() {
+                q
+            } else {
+                best
+            }
+        })
+}
+
+/// The point of a segment closest to p.
+fn segment_point(a: Pt3, b: Pt3, p: Pt3) -> Pt3 {
+    let ab = b - a;
+    let len2 = ab.len2();
+    if len2 == 0.0 {
+        return a;
+    }
+    a + ab * ((p - a).dot(ab) / len2).clamp(0.0, 1.0)
+}
+
+/// The distance between two segments.
+fn segment_distance(p0: Pt3, p1: Pt3, q0: Pt3, q1: Pt3) -> f64 {
+    let d1 = p1 - p0;
+    let d2 = q1 - q0;
+    let r = p0 - q0;
+    let a = d1.len2();
+    let e = d2.len2();
+    let f = d2.dot(r);
+    let (s, t) = if a == 0.0 && e == 0.0 {
+        (0.0, 0.0)
+    } else if a == 0.0 {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e == 0.0 {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            let mut s = if denom > 0.0 {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    ((p0 + d1 * s) - (q0 + d2 * t)).len()
+}
@@ -0,0 +1,210 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::prelude::*;
+
+/// A face of an axis aligned box.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BoxFace {
+    /// The +Z face.
+    Top,
+    /// The -Z face.
+    Bottom,
+    /// The -Y face.
+    Front,
+    /// The +Y face.
+    Back,
+    /// The -X face.
+    Left,
+    /// The +X face.
+    Right,
+}
+
+impl BoxFace {
+    /// The size of the face, its center and the rotation that turns +Z into
+    /// its normal.
+    pub(crate) fn frame(self, min: Pt3, max: Pt3) -> (f64, f64, Pt3, [f64; 3]) {
+        let size = max - min;
+        let center = (min + max) / 2.0;
+        match self {
+            BoxFace::Top => (
+                size.x,
+                size.y,
+                Pt3::new(center.x, center.y, max.z),
+                [0.0, 0.0, 0.0],
+            ),
+            BoxFace::Bottom => (
+                size.x,
+                size.y,
+                Pt3::new(center.x, center.y, min.z),
+                [0.0, 180.0, 0.0],
+            ),
+            BoxFace::Front => (
+                size.x,
+                size.z,
+                Pt3::new(center.x, min.y, center.z),
+                [90.0, 0.0, 0.0],
+            ),
+            BoxFace::Back => (
+                size.x,
+                size.z,
+                Pt3::new(center.x, max.y, center.z),
+                [90.0, 0.0, 180.0],
+            ),
+            BoxFace::Left => (
+                size.y,
+                size.z,
+                Pt3::new(min.x, center.y, center.z),
+                [90.0, 0.0, -90.0],
+            ),
+            BoxFace::Right => (
+                size.y,
+                size.z,
+                Pt3::new(max.x, center.y, center.z),
+                [90.0, 0.0, 90.0],
+            ),
+        }
+    }
+}
+
+/// A text or 2D label embossed on or engraved into a face of a part.
+///
+/// The label is centered on the face and sized to fit inside the margin. The
+/// text is read looking at the face from outside the part with +Z up, or +Y up
+/// for the top and bottom faces.
+#[derive(Clone)]
+pub struct Label {
+    pub text: String,
+    pub font: String,
+    /// The largest text size to use.
+    pub max_size: f64,
+    /// The average character advance as a fraction of the text size, used to
+    /// estimate the width of the text.
+    pub char_width: f64,
+    /// 2D content e.g. a QR code used instead of the text. The content is
+    /// centered on the origin and scaled to fit the face.
+    pub content: Option<Scad>,
+    /// The width of the content before scaling.
+    pub content_width: f64,
+    /// The height of the content before scaling.
+    pub content_height: f64,
+    /// The face the label is placed on.
+    pub face: BoxFace,
+    /// The height of an embossed label or the depth of an engraved one.
+    pub depth: f64,
+    /// The minimum distance from the label to the edges of the face.
+    pub margin: f64,
+    /// The number of segments in a circle.
+    pub segments: u64,
+}
+
+impl Default for Label {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            font: "Liberation Sans".to_string(),
+            max_size: 10.0,
+            char_width: 0.6,
+            content: None,
+            content_width: 0.0,
+            content_height: 0.0,
+            face: BoxFace::Top,
+            depth: 0.6,
+            margin: 2.0,
+            segments: 36,
+        }
+    }
+}
+
+impl Label {
+    /// The 2D label sized to fit an area centered on the origin.
+    ///
+    /// width: The width of the area.
+    ///
+    /// height: The height of the area.
+    ///
+    /// return: The 2D label.
+    pub fn into_scad_2d(&self, width: f64, height: f64) -> Scad {
+        let width = width - self.margin * 2.0;
+        let height = height - self.margin * 2.0;
+        assert!(width > 0.0 && height > 0.0);
+        if let Some(content) = &self.content {
+            assert!(self.content_width > 0.0 && self.content_height > 0.0);
+            let s = (width / self.content_width).min(height / self.content_height);
+            return scale!([s, s, 1.0], content.clone(););
+        }
+        assert!(!self.text.is_empty());
+        let n_chars = self.text.chars().count() as f64;
+        let size = self
+            .max_size
+            .min(height)
+            .min(width / (n_chars * self.char_width));
+        text!(
+            &self.text,
+            size,
+            &self.font,
+            TextHalign::center,
+            TextValign::center,
+            TextDirection::ltr,
+            fn = self.segments
+        )
+    }
+
+    /// Raise the label from the face of a part.
+    ///
+    /// part: The part to label.
+    ///
+    /// min: The minimum corner of the bounding box of the part.
+    ///
+    /// max: The maximum corner of the bounding box of the part.
+    ///
+    /// return: The labeled part.
+    pub fn emboss(&self, part: &Scad, min: Pt3, max: Pt3) -> Scad {
+        part.clone() + self.placed(min, max, 0.0, self.depth)
+    }
+
+    /// Cut the label into the face of a part.
+    ///
+    /// part: The part to label.
+    ///
+    /// min: The minimum corner of the bounding box of the part.
+    ///
+    /// max: The maximum corner of the bounding box of the part.
+    ///
+    /// return: The labeled part.
+    pub fn engrave(&self, part: &Scad, min: Pt3, max: Pt3) -> Scad {
+        part.clone() - self.placed(min, max, -self.depth, self.depth + 1.0)
+    }
+
+    /// The label extruded from z to z + height along the face normal.
+    fn placed(&self, min: Pt3, max: Pt3, z: f64, height: f64) -> Scad {
+        let (width, depth, position, [rx, ry, rz]) = self.face.frame(min, max);
+        translate!([position.x, position.y, position.z],
+            rotate!([rx, ry, rz],
+                translate!([0.0, 0.0, z],
+                    linear_extrude!(height, self.into_scad_2d(width, depth););
+                );
+            );
+        )
+    }
+}
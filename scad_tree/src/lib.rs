@@ -25,48 +25,135 @@
 //!
 //! Notes on usage:
 //! * 2D profiles for non-OpenSCAD functions/macros are specified by points in
-//!     clockwise order.
+//!   clockwise order.
 //! * Polyhedron faces are specified in clockwise order.
 
+/// Module for named anchors and attaching objects to each other.
+pub mod anchor;
+/// Module for laying parts out on a build plate.
+pub mod arrange;
+/// Module for belt and pulley drives.
+pub mod belt;
+/// Module for threads with custom profiles, tapers and multiple starts.
+pub mod custom_thread;
 /// Module for the creation of 2D profiles and curves.
 pub mod dim2;
 /// Module for the creation of 3D curves and polyhedrons.
 pub mod dim3;
+/// Module for converting text to profiles with TrueType fonts.
+#[cfg(feature = "font")]
+pub mod font;
+/// Module for involute spur gears and gear trains.
+pub mod gears;
+/// Module for reading grayscale images into heightmaps and lithophanes.
+#[cfg(feature = "image")]
+pub mod image;
+/// Module for edge chamfer and fillet masks.
+pub mod mask;
 /// Module for metric threaded rod, nuts and bolts.
 pub mod metric_thread;
+/// Module for running the OpenSCAD executable.
+pub mod openscad;
+/// Module for connector, switch and display cutouts in panels.
+pub mod panel;
+/// Module for honeycomb and grid infill patterns.
+pub mod patterns;
+/// Module for property testing geometry generators.
+pub mod testing;
+/// Module for trapezoidal leadscrews and nuts.
+pub mod trapezoidal_thread;
 
+mod assembly;
+mod bom;
+mod bounds;
+mod clip;
+mod color;
+mod convexity;
+mod csg;
+mod dial;
+mod dxf;
+mod error;
+mod eval;
+mod gerber;
+mod gridfinity;
+mod interference;
+mod label;
+mod manifest;
+mod optimize;
+mod orient;
+mod parallel;
+mod pcb;
 mod pipe;
+mod roughen;
 mod scad;
+mod scad_file;
+mod thread;
 mod triangulate;
+mod units;
+mod unroll;
 mod viewer;
+mod wheel;
 
 /// Module for quickly importing library types and macros.
 pub mod prelude {
     pub use {
         crate::{
-            circle, color, cube, cylinder, difference, dim2, dim3, fat_thread, hull, import,
-            intersection, linear_extrude, metric_thread, minkowski, mirror, offset, polygon,
-            polyhedron, projection, resize, rotate, rotate_extrude, scad_file, scale, sphere,
-            square, surface, text, translate, union, BezierStar, CubicBezier2D, CubicBezier3D,
-            CubicBezierChain2D, CubicBezierChain3D, Faces, Indices, Paths, Pipe, Polyhedron, Pt2,
-            Pt2s, Pt3, Pt3s, Pt4, QuadraticBezier2D, QuadraticBezier3D, Scad, ScadColor, ScadOp,
-            TextDirection, TextHalign, TextParams, TextValign, Viewer,
+            anchor, arrange, assert_manifold, assert_scad_snapshot, assert_volume_close, belt,
+            circle, color, comment, cube, cube_in, custom_thread, cylinder, cylinder_in,
+            difference, dim2, dim3, fat_thread, gears, hull, if_preview, import, intersection,
+            linear_extrude, mask, metric_thread, minkowski, mirror, offset, openscad, panel,
+            patterns, polygon, polygon_multi, polyhedron, projection, raw_scad, render, resize,
+            rotate, rotate_extrude, scad_file, scad_file_result, scale, sphere, square, surface,
+            testing, text, translate, trapezoidal_thread, union, xor, Anchor, Assembly,
+            AssemblyPart, BezierStar, Bom, BomFormat, BomLine, BoxFace, BuildTarget, CubicBezier2D,
+            CubicBezier3D, CubicBezierChain2D, CubicBezierChain3D, Dial, Faces, FlatPattern, Float,
+            FloatFormat, GridfinityBin, Indices, Interference, Label, Length, Manifest, MeshReport,
+            OutputFormat, Paths, Pcb, PcbHole, Pipe, Polyhedron, PrintOrientation, Profile, Pt2,
+            Pt2s, Pt3, Pt3s, Pt4, QuadraticBezier2D, QuadraticBezier3D, Scad, ScadColor, ScadError,
+            ScadExpr, ScadFileOptions, ScadFormat, ScadModifier, ScadOp, ScadTag, ScadVar,
+            SpokeStyle, SpringEnds, SweepFrame, SweepOptions, TextDirection, TextHalign,
+            TextParams, TextValign, TireProfile, Unit, Viewer, Viewport, WatchOptions, Wheel,
         },
         std::io::Write,
     };
 }
 
 pub use {
-    dim2::{BezierStar, CubicBezier2D, CubicBezierChain2D, QuadraticBezier2D},
-    dim3::{CubicBezier3D, CubicBezierChain3D, Polyhedron, QuadraticBezier3D},
+    anchor::Anchor,
+    assembly::{Assembly, AssemblyPart},
+    bom::{Bom, BomFormat, BomLine},
+    color::{color_palette, hex_to_rgba},
+    dial::Dial,
+    dim2::{BezierStar, CubicBezier2D, CubicBezierChain2D, Profile, QuadraticBezier2D},
+    dim3::{
+        CubicBezier3D, CubicBezierChain3D, MeshReport, Polyhedron, QuadraticBezier3D, SpringEnds,
+        SweepFrame, SweepOptions,
+    },
+    error::ScadError,
+    gridfinity::GridfinityBin,
+    interference::Interference,
+    label::{BoxFace, Label},
+    manifest::{BuildTarget, Manifest, OutputFormat},
+    orient::PrintOrientation,
+    pcb::{Pcb, PcbHole},
     pipe::Pipe,
-    scad::{Scad, ScadColor, ScadOp, TextDirection, TextHalign, TextParams, TextValign},
+    scad::{
+        Scad, ScadColor, ScadExpr, ScadFormat, ScadModifier, ScadOp, ScadTag, ScadVar,
+        TextDirection, TextHalign, TextParams, TextValign,
+    },
+    scad_file::{ScadFileOptions, Viewport},
     scad_tree_math::{
-        approx_eq, dacos, dasin, datan, dcos, dsin, dtan, MersenneTwister, Mt4, Pt2, Pt2s, Pt3,
-        Pt3s, Pt4, Pt4s,
+        approx_eq, dacos, dasin, datan, dcos, dsin, dtan, float_format, set_float_format,
+        with_float_format, Float, FloatFormat, MersenneTwister, Mt4, Pt2, Pt2s, Pt3, Pt3s, Pt4,
+        Pt4s,
     },
-    triangulate::{triangulate2d, triangulate2d_rev, triangulate3d, triangulate3d_rev},
-    viewer::Viewer,
+    triangulate::{
+        triangulate2d, triangulate2d_holes, triangulate2d_rev, triangulate3d, triangulate3d_rev,
+    },
+    units::{Length, Unit, MM_PER_INCH},
+    unroll::FlatPattern,
+    viewer::{Viewer, WatchOptions},
+    wheel::{SpokeStyle, TireProfile, Wheel},
 };
 
 /// Wraps a `Vec<u64>`.
@@ -92,10 +179,13 @@ impl std::ops::DerefMut for Indices {
 impl std::fmt::Display for Indices {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        for i in 0..self.len() - 1 {
-            write!(f, "{}, ", self[i])?;
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", item)?;
         }
-        write!(f, "{}]", self[self.len() - 1])
+        write!(f, "]")
     }
 }
 
@@ -136,10 +226,13 @@ impl std::ops::DerefMut for Paths {
 impl std::fmt::Display for Paths {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        for i in 0..self.len() - 1 {
-            write!(f, "{}, ", self[i])?;
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", item)?;
         }
-        write!(f, "{}]", self[self.len() - 1])
+        write!(f, "]")
     }
 }
 
@@ -169,6 +262,73 @@ impl Paths {
     pub fn from_faces(faces: Vec<Indices>) -> Self {
         Self { inner: faces }
     }
+
+    /// Create faces from a list of triangles, three indices per triangle.
+    ///
+    /// Used with the results of the triangulate functions.
+    pub fn from_triangles(triangles: &Indices) -> Self {
+        Self {
+            inner: triangles
+                .chunks_exact(3)
+                .map(|t| Indices::from_indices(t.to_vec()))
+                .collect(),
+        }
+    }
+
+    /// Append other to self adding offset to each index of other.
+    ///
+    /// Used when joining meshes, offset is the number of points
+    /// before the points of other.
+    pub fn append_offset(&mut self, other: Faces, offset: u64) {
+        self.reserve(other.len());
+        for mut face in other.inner.into_iter() {
+            for index in face.iter_mut() {
+                *index += offset;
+            }
+            self.push(face);
+        }
+    }
+
+    /// Add a triangle.
+    pub fn push_tri(&mut self, a: u64, b: u64, c: u64) {
+        self.push(Indices::from_indices(vec![a, b, c]));
+    }
+
+    /// Add a quad.
+    pub fn push_quad(&mut self, a: u64, b: u64, c: u64, d: u64) {
+        self.push(Indices::from_indices(vec![a, b, c, d]));
+    }
+
+    /// Add a fan of triangles from a center point to each edge of a closed ring.
+    ///
+    /// Each triangle is (center, ring\[i\], ring\[i + 1\]), reverse the ring
+    /// to flip the faces.
+    ///
+    /// center: The index of the center point.
+    ///
+    /// ring: The indices of the ring.
+    pub fn push_fan(&mut self, center: u64, ring: &[u64]) {
+        for i in 0..ring.len() {
+            self.push_tri(center, ring[i], ring[(i + 1) % ring.len()]);
+        }
+    }
+
+    /// Add a strip of quads joining two closed rings.
+    ///
+    /// Each quad is (a\[i\], a\[i + 1\], b\[i + 1\], b\[i\]), swap the rings
+    /// to flip the faces.
+    ///
+    /// ring_a: The indices of the first ring.
+    ///
+    /// ring_b: The indices of the second ring, the same length as ring_a.
+    pub fn push_strip(&mut self, ring_a: &[u64], ring_b: &[u64]) {
+        assert!(ring_a.len() == ring_b.len());
+        let n = ring_a.len();
+        for i in 0..n {
+            let j = (i + 1) % n;
+            self.push_quad(ring_a[i], ring_a[j], ring_b[j], ring_b[i]);
+        }
+    }
 }
 
 /// Alias for Paths.
@@ -0,0 +1,213 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Describes the files a project generates so they can be built together.
+
+use {
+    crate::{openscad, prelude::*},
+    std::path::{Path, PathBuf},
+};
+
+/// The kind of file a BuildTarget writes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// OpenSCAD code.
+    Scad,
+    /// An STL mesh rendered by OpenSCAD.
+    Stl,
+    /// A 3MF mesh rendered by OpenSCAD.
+    ThreeMf,
+    /// An OFF mesh rendered by OpenSCAD.
+    Off,
+    /// A DXF drawing of a 2D tree rendered by OpenSCAD.
+    Dxf,
+    /// An SVG drawing of a 2D tree rendered by OpenSCAD.
+    Svg,
+    /// A PNG image of the preview rendered by OpenSCAD.
+    Png,
+}
+
+impl OutputFormat {
+    /// The file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Scad => "scad",
+            OutputFormat::Stl => "stl",
+            OutputFormat::ThreeMf => "3mf",
+            OutputFormat::Off => "off",
+            OutputFormat::Dxf => "dxf",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Png => "png",
+        }
+    }
+}
+
+/// A file built from a Scad tree.
+pub struct BuildTarget {
+    /// The name of the part, also the file name when path is None.
+    pub name: String,
+    /// Creates the tree of the part.
+    pub builder: Box<dyn Fn() -> Scad>,
+    /// The output file, None for the name in the directory of the manifest. The
+    /// extension of the format is added when the file has none.
+    pub path: Option<PathBuf>,
+    /// The kind of file to write.
+    pub format: OutputFormat,
+    /// The header, quality settings and module deduplication of the code.
+    pub options: ScadFileOptions,
+}
+
+impl BuildTarget {
+    /// Creates a target that writes a .scad file with the default options.
+    ///
+    /// name: The name of the part.
+    ///
+    /// builder: Creates the tree of the part.
+    ///
+    /// return: The target.
+    pub fn new<F: Fn() -> Scad + 'static>(name: &str, builder: F) -> Self {
+        Self {
+            name: name.to_string(),
+            builder: Box::new(builder),
+            path: None,
+            format: OutputFormat::Scad,
+            options: ScadFileOptions::default(),
+        }
+    }
+
+    /// The file the target writes.
+    ///
+    /// dir: The directory used when the target has no path.
+    ///
+    /// return: The path, with the extension of the format when it has none.
+    pub fn output_path(&self, dir: &Path) -> PathBuf {
+        match &self.path {
+            Some(path) if path.extension().is_some() => path.clone(),
+            Some(path) => path.with_extension(self.format.extension()),
+            None => dir.join(format!("{}.{}", self.name, self.format.extension())),
+        }
+    }
+
+    /// Builds the tree and writes the file.
+    ///
+    /// Formats other than Scad run OpenSCAD.
+    ///
+    /// dir: The directory used when the target has no path.
+    ///
+    /// return: The path of the file or the first error.
+    pub fn build(&self, dir: &Path) -> Result<PathBuf, ScadError> {
+        let path = self.output_path(dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let scad = (self.builder)();
+        match self.format {
+            OutputFormat::Scad => self.options.save(
+                path.to_str()
+                    .ok_or_else(|| ScadError::Invalid(format!("path {:?}", path)))?,
+                vec![scad],
+            )?,
+            _ => {
+                openscad::export_code(&self.options.to_code(&[scad])?, &path)?;
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// The build targets of a project.
+///
+/// Replaces the list of scad_file! calls in a program that generates many files.
+pub struct Manifest {
+    /// The directory of targets without a path.
+    pub dir: PathBuf,
+    targets: Vec<BuildTarget>,
+}
+
+impl Manifest {
+    /// Creates an empty manifest.
+    ///
+    /// dir: The directory of targets without a path.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            targets: Vec::new(),
+        }
+    }
+
+    /// Adds a target.
+    ///
+    /// target: The target, its name must not be used by another target.
+    ///
+    /// return: self or an error if the name is already used.
+    pub fn add(&mut self, target: BuildTarget) -> Result<&mut Self, ScadError> {
+        if self.targets.iter().any(|t| t.name == target.name) {
+            return Err(ScadError::Invalid(format!(
+                "duplicate target {}",
+                target.name
+            )));
+        }
+        self.targets.push(target);
+        Ok(self)
+    }
+
+    /// Adds a target that writes name.scad with the default options.
+    ///
+    /// name: The name of the part.
+    ///
+    /// builder: Creates the tree of the part.
+    ///
+    /// return: self or an error if the name is already used.
+    pub fn part<F: Fn() -> Scad + 'static>(
+        &mut self,
+        name: &str,
+        builder: F,
+    ) -> Result<&mut Self, ScadError> {
+        self.add(BuildTarget::new(name, builder))
+    }
+
+    /// The names of the targets in the order they were added.
+    pub fn names(&self) -> Vec<&str> {
+        self.targets.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    /// Builds one target.
+    ///
+    /// name: The name of the target.
+    ///
+    /// return: The path of the file or an error.
+    pub fn build(&self, name: &str) -> Result<PathBuf, ScadError> {
+        self.targets
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| ScadError::Invalid(format!("no target named {}", name)))?
+            .build(&self.dir)
+    }
+
+    /// Builds every target in the order they were added.
+    ///
+    /// return: The paths of the files or the first error.
+    pub fn build_all(&self) -> Result<Vec<PathBuf>, ScadError> {
+        self.targets.iter().map(|t| t.build(&self.dir)).collect()
+    }
+}
@@ -0,0 +1,181 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::prelude::*;
+
+/// How far masks extend past the surfaces they cut for a cleaner preview.
+const OVERSIZE: f64 = 0.1;
+
+/// The 2D profile of a chamfer mask for an edge at the origin with the
+/// material in the +X +Y quadrant.
+fn chamfer_profile(size: f64) -> Pt2s {
+    Pt2s::from_pt2s(vec![
+        Pt2::new(-OVERSIZE, -OVERSIZE),
+        Pt2::new(-OVERSIZE, size),
+        Pt2::new(0.0, size),
+        Pt2::new(size, 0.0),
+        Pt2::new(size, -OVERSIZE),
+    ])
+}
+
+/// The 2D profile of a fillet mask for an edge at the origin with the
+/// material in the +X +Y quadrant.
+fn fillet_profile(radius: f64, segments: u64) -> Pt2s {
+    let mut profile = dim2::fillet(radius, OVERSIZE, (segments / 4).max(1));
+    profile.translate(Pt2::new(-OVERSIZE, -OVERSIZE));
+    profile
+}
+
+/// Move an edge profile to the bottom outside rim of a cylinder.
+fn rim_profile(edge_profile: Pt2s, radius: f64) -> Pt2s {
+    Pt2s::from_pt2s(
+        edge_profile
+            .iter()
+            .rev()
+            .map(|p| Pt2::new((radius - p.x).max(0.0), p.y))
+            .collect(),
+    )
+}
+
+fn edge_mask(profile: Pt2s, length: f64, center: bool) -> Scad {
+    let mut mask = linear_extrude!(length, polygon!(profile););
+    if center {
+        mask = translate!([0.0, 0.0, -length / 2.0], mask;);
+    }
+    mask
+}
+
+/// Creates a mask for chamfering a 90 degree edge.
+///
+/// The edge lies on the Z axis with the material in the +X +Y quadrant.
+/// Position the mask on the edge and subtract it.
+///
+/// size: The size of the chamfer.
+///
+/// length: The length of the edge.
+///
+/// center: Center along the edge.
+///
+/// return: The mask.
+pub fn chamfer_edge(size: f64, length: f64, center: bool) -> Scad {
+    edge_mask(chamfer_profile(size), length, center)
+}
+
+/// Creates a mask for rounding a 90 degree edge.
+///
+/// The edge lies on the Z axis with the material in the +X +Y quadrant.
+/// Position the mask on the edge and subtract it.
+///
+/// radius: The radius of the fillet.
+///
+/// length: The length of the edge.
+///
+/// segments: The number of segments in a circle.
+///
+/// center: Center along the edge.
+///
+/// return: The mask.
+pub fn fillet_edge(radius: f64, length: f64, segments: u64, center: bool) -> Scad {
+    edge_mask(fillet_profile(radius, segments), length, center)
+}
+
+/// Creates a mask for chamfering the corner where three 90 degree edges meet.
+///
+/// The corner is at the origin with the material in the +X +Y +Z octant.
+/// Use with chamfer_edge masks of the same size on the edges.
+///
+/// size: The size of the chamfer.
+///
+/// return: The mask.
+pub fn chamfer_corner(size: f64) -> Scad {
+    let far = size + OVERSIZE * 2.0;
+    Polyhedron::new(
+        Pt3s::from_pt3s(vec![
+            Pt3::new(-OVERSIZE, -OVERSIZE, -OVERSIZE),
+            Pt3::new(far, -OVERSIZE, -OVERSIZE),
+            Pt3::new(-OVERSIZE, far, -OVERSIZE),
+            Pt3::new(-OVERSIZE, -OVERSIZE, far),
+        ]),
+        Faces::from_faces(vec![
+            Indices::from_indices(vec![0, 1, 2]),
+            Indices::from_indices(vec![0, 3, 1]),
+            Indices::from_indices(vec![0, 2, 3]),
+            Indices::from_indices(vec![2, 1, 3]),
+        ]),
+    )
+    .into_scad()
+}
+
+/// Creates a mask for rounding the corner where three 90 degree edges meet.
+///
+/// The corner is at the origin with the material in the +X +Y +Z octant.
+/// Use with fillet_edge masks of the same radius on the edges.
+///
+/// radius: The radius of the fillet.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The mask.
+pub fn fillet_corner(radius: f64, segments: u64) -> Scad {
+    translate!([-OVERSIZE, -OVERSIZE, -OVERSIZE],
+        cube!(radius + OVERSIZE);
+    ) - translate!([radius, radius, radius],
+        sphere!(radius, fn=segments);
+    )
+}
+
+/// Creates a mask for chamfering the rim of a cylinder.
+///
+/// The mask cuts the bottom rim of a cylinder standing on the XY plane
+/// centered on the Z axis.
+///
+/// radius: The radius of the cylinder.
+///
+/// size: The size of the chamfer.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The mask.
+pub fn chamfer_cylinder(radius: f64, size: f64, segments: u64) -> Scad {
+    rotate_extrude!(angle=360.0, convexity=2, fn=segments,
+        polygon!(rim_profile(chamfer_profile(size), radius));
+    )
+}
+
+/// Creates a mask for rounding the rim of a cylinder.
+///
+/// The mask cuts the bottom rim of a cylinder standing on the XY plane
+/// centered on the Z axis.
+///
+/// radius: The radius of the cylinder.
+///
+/// fillet: The radius of the fillet.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The mask.
+pub fn fillet_cylinder(radius: f64, fillet: f64, segments: u64) -> Scad {
+    rotate_extrude!(angle=360.0, convexity=2, fn=segments,
+        polygon!(rim_profile(fillet_profile(fillet, segments), radius));
+    )
+}
@@ -21,38 +21,74 @@
 // SOFTWARE.
 //
 
-use {
-    crate::prelude::*,
-    scad_tree_math::{dcos, dsin},
-    std::collections::HashMap,
-};
-
-fn lerp(start: Pt3, end: Pt3, n_steps: usize, step: usize) -> Pt3 {
-    start + ((end - start) / n_steps as f64 * step as f64)
-}
+use crate::{prelude::*, thread::helical_ridge};
 
-/// Returns the dictionary for the given M size.
-///
-/// This function always returns a valid
-/// dictionary by giving the next smallest size if the requested size is not found. If
-/// a size smaller than the smallest is requested the smallest size in dict is returned.
+/// The fit between mating threads.
 ///
-/// m: The size of the thread you want dict for e.g. 6 for M6 screw threads.
-///
-/// return: The dictionary of thread attributes.
-fn m_table_lookup(m: i32) -> HashMap<&'static str, f64> {
-    let m_table = m_table();
-    let mut m = m;
-    if m < 2 {
-        m = 2;
-    }
-    loop {
-        if m_table.contains_key(&m) {
-            break;
+/// The clearance is split evenly between the parts, external threads are made
+/// smaller and internal threads are made larger by half the clearance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThreadFit {
+    /// The nominal ISO diameters.
+    Exact,
+    /// 0.1mm of diametral clearance.
+    Close,
+    /// 0.2mm of diametral clearance.
+    Normal,
+    /// 0.4mm of diametral clearance.
+    Loose,
+    /// The given diametral clearance in mm.
+    Custom(f64),
+}
+
+impl ThreadFit {
+    /// The total diametral clearance between mating threads in mm.
+    pub fn clearance(&self) -> f64 {
+        match self {
+            ThreadFit::Exact => 0.0,
+            ThreadFit::Close => 0.1,
+            ThreadFit::Normal => 0.2,
+            ThreadFit::Loose => 0.4,
+            ThreadFit::Custom(clearance) => *clearance,
         }
-        m -= 1;
     }
-    m_table[&m].clone()
+}
+
+/// The dimensions of an ISO metric coarse thread.
+///
+/// These are the numbers the rods, bolts, nuts and taps of this module are
+/// made from, use them to size custom parts like washers and standoffs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MetricThreadSpec {
+    /// The coarse pitch.
+    pub pitch: f64,
+    /// The major diameter of the external thread of a bolt.
+    pub external_d_maj: f64,
+    /// The major diameter of the internal thread of a nut.
+    pub internal_d_maj: f64,
+    /// The width across the flats of the nut and hex head.
+    pub nut_width: f64,
+    /// The size of the chamfer at the ends of the threads.
+    pub chamfer_size: f64,
+}
+
+impl MetricThreadSpec {
+    /// Returns the dimensions for the given M size.
+    ///
+    /// Always returns a spec by giving the next smaller size if the requested
+    /// size is not in the table. Sizes smaller than M2 give M2.
+    ///
+    /// m: The size of the thread e.g. 6 for M6 screw threads.
+    ///
+    /// return: The thread dimensions.
+    pub fn lookup(m: i32) -> Self {
+        M_TABLE
+            .iter()
+            .rev()
+            .find(|(size, _)| *size <= m)
+            .unwrap_or(&M_TABLE[0])
+            .1
+    }
 }
 
 /// Calculates the thread height from the given pitch.
@@ -108,279 +144,25 @@ fn threaded_cylinder(
     left_hand_thread: bool,
     center: bool,
 ) -> Scad {
-    let lead_in = lead_in_degrees > 0.0;
-    let lead_out = lead_out_degrees > 0.0;
     let thread_length = length - 0.7 * pitch;
-    let n_revolutions = thread_length / pitch;
-    let n_steps = (n_revolutions * segments as f64) as usize;
-    let z_step = thread_length / n_steps as f64;
-    let step_angle = 360.0 / segments as f64;
-    let n_lead_in_steps = (segments as f64 * lead_in_degrees / 360.0 + 2.0) as usize;
-    let n_lead_out_steps = (segments as f64 * lead_out_degrees / 360.0) as usize;
-    let mut lead_in_step = 2;
-    let mut lead_out_step = n_lead_out_steps;
-
-    let thread_profile0 = Pt3::new(d_min / 2.0, 0.0, 3.0 / 4.0 * pitch);
-    let thread_profile1 = Pt3::new(d_maj / 2.0, 0.0, 7.0 / 16.0 * pitch);
-    let thread_profile2 = Pt3::new(d_min / 2.0, 0.0, 0.0);
-    let thread_profile3 = Pt3::new(d_maj / 2.0, 0.0, 5.0 / 16.0 * pitch);
-
-    let lerp_profile1 = Pt3::new(d_min / 2.0, 0.0, 7.0 / 16.0 * pitch);
-    let lerp_profile3 = Pt3::new(d_min / 2.0, 0.0, 5.0 / 16.0 * pitch);
-
-    let lead_in_start_profile0 = thread_profile0;
-    let lead_in_start_profile2 = thread_profile2;
-    let lead_in_start_profile1 = lerp(
-        lerp_profile1,
-        thread_profile1,
-        n_lead_in_steps,
-        lead_in_step,
-    );
-    let lead_in_start_profile3 = lerp(
-        lerp_profile3,
-        thread_profile3,
-        n_lead_in_steps,
-        lead_in_step,
-    );
-    lead_in_step += 1;
-
-    let lead_out_end_profile1 = lerp(lerp_profile1, thread_profile1, n_lead_out_steps, 1);
-    let lead_out_end_profile3 = lerp(lerp_profile3, thread_profile3, n_lead_out_steps, 1);
-
-    let mut vertices: Vec<Pt3> = Vec::new();
-    let mut indices: Vec<usize> = Vec::new();
-
-    // Create the starting end face
-    vertices.push(lead_in_start_profile0);
-    vertices.push(lead_in_start_profile1);
-    vertices.push(lead_in_start_profile2);
-    vertices.push(lead_in_start_profile3);
-
-    if left_hand_thread {
-        indices.append(&mut vec![2, 1, 0]);
-        indices.append(&mut vec![3, 1, 2]);
-    } else {
-        indices.append(&mut vec![0, 1, 2]);
-        indices.append(&mut vec![2, 1, 3]);
-    }
-
-    // Vertices used for the middle sections
-    let mut p4;
-    let mut p5;
-    let mut p6;
-    let mut p7;
-
-    let lead_in_profile0 = lead_in_start_profile0;
-    let mut lead_in_profile1 = lead_in_start_profile1;
-    let lead_in_profile2 = lead_in_start_profile2;
-    let mut lead_in_profile3 = lead_in_start_profile3;
-
-    let lead_out_profile0 = thread_profile0;
-    let mut lead_out_profile1 = thread_profile1;
-    let lead_out_profile2 = thread_profile2;
-    let mut lead_out_profile3 = thread_profile3;
-
-    for step in 0..(n_steps - 1) {
-        let mut angle = step_angle * (step + 1) as f64;
-        if left_hand_thread {
-            angle *= -1.0;
-        }
-        let c = dcos(angle);
-        let s = dsin(angle);
-        if lead_in_step < n_lead_in_steps && lead_in {
-            p4 = Pt3::new(
-                c * lead_in_profile0.x,
-                s * lead_in_profile0.x,
-                z_step * step as f64 + lead_in_profile0.z,
-            );
-            p5 = Pt3::new(
-                c * lead_in_profile1.x,
-                s * lead_in_profile1.x,
-                z_step * step as f64 + lead_in_profile1.z,
-            );
-            p6 = Pt3::new(
-                c * lead_in_profile2.x,
-                s * lead_in_profile2.x,
-                z_step * step as f64 + lead_in_profile2.z,
-            );
-            p7 = Pt3::new(
-                c * lead_in_profile3.x,
-                s * lead_in_profile3.x,
-                z_step * step as f64 + lead_in_profile3.z,
-            );
-
-            lead_in_step += 1;
-            lead_in_profile1 = lerp(
-                lead_in_start_profile1,
-                thread_profile1,
-                n_lead_in_steps,
-                lead_in_step,
-            );
-            lead_in_profile3 = lerp(
-                lead_in_start_profile3,
-                thread_profile3,
-                n_lead_in_steps,
-                lead_in_step,
-            );
-        } else if lead_out_step > 0 && step >= n_steps - n_lead_out_steps && lead_out {
-            p4 = Pt3::new(
-                c * lead_out_profile0.x,
-                s * lead_out_profile0.x,
-                z_step * step as f64 + lead_out_profile0.z,
-            );
-            p5 = Pt3::new(
-                c * lead_out_profile1.x,
-                s * lead_out_profile1.x,
-                z_step * step as f64 + lead_out_profile1.z,
-            );
-            p6 = Pt3::new(
-                c * lead_out_profile2.x,
-                s * lead_out_profile2.x,
-                z_step * step as f64 + lead_out_profile2.z,
-            );
-            p7 = Pt3::new(
-                c * lead_out_profile3.x,
-                s * lead_out_profile3.x,
-                z_step * step as f64 + lead_out_profile3.z,
-            );
-            lead_out_step -= 1;
-            lead_out_profile1 = lerp(
-                thread_profile1,
-                lead_out_end_profile1,
-                n_lead_out_steps,
-                n_lead_out_steps - lead_out_step,
-            );
-            lead_out_profile3 = lerp(
-                thread_profile3,
-                lead_out_end_profile3,
-                n_lead_out_steps,
-                n_lead_out_steps - lead_out_step,
-            );
-        } else {
-            p4 = Pt3::new(
-                c * thread_profile0.x,
-                s * thread_profile0.x,
-                z_step * step as f64 + thread_profile0.z,
-            );
-            p5 = Pt3::new(
-                c * thread_profile1.x,
-                s * thread_profile1.x,
-                z_step * step as f64 + thread_profile1.z,
-            );
-            p6 = Pt3::new(
-                c * thread_profile2.x,
-                s * thread_profile2.x,
-                z_step * step as f64 + thread_profile2.z,
-            );
-            p7 = Pt3::new(
-                c * thread_profile3.x,
-                s * thread_profile3.x,
-                z_step * step as f64 + thread_profile3.z,
-            );
-        }
-
-        vertices.push(p4);
-        vertices.push(p5);
-        vertices.push(p6);
-        vertices.push(p7);
-
-        let index_offset = step * 4;
-        if left_hand_thread {
-            indices.append(&mut vec![
-                3 + index_offset,
-                5 + index_offset,
-                1 + index_offset,
-            ]);
-            indices.append(&mut vec![
-                7 + index_offset,
-                5 + index_offset,
-                3 + index_offset,
-            ]);
-            indices.append(&mut vec![1 + index_offset, 4 + index_offset, index_offset]);
-            indices.append(&mut vec![
-                5 + index_offset,
-                4 + index_offset,
-                1 + index_offset,
-            ]);
-            indices.append(&mut vec![index_offset, 6 + index_offset, 2 + index_offset]);
-            indices.append(&mut vec![4 + index_offset, 6 + index_offset, index_offset]);
-            indices.append(&mut vec![
-                2 + index_offset,
-                7 + index_offset,
-                3 + index_offset,
-            ]);
-            indices.append(&mut vec![
-                6 + index_offset,
-                7 + index_offset,
-                2 + index_offset,
-            ]);
-        } else {
-            indices.append(&mut vec![
-                1 + index_offset,
-                5 + index_offset,
-                3 + index_offset,
-            ]);
-            indices.append(&mut vec![
-                3 + index_offset,
-                5 + index_offset,
-                7 + index_offset,
-            ]);
-            indices.append(&mut vec![index_offset, 4 + index_offset, 1 + index_offset]);
-            indices.append(&mut vec![
-                1 + index_offset,
-                4 + index_offset,
-                5 + index_offset,
-            ]);
-            indices.append(&mut vec![2 + index_offset, 6 + index_offset, index_offset]);
-            indices.append(&mut vec![index_offset, 6 + index_offset, 4 + index_offset]);
-            indices.append(&mut vec![
-                3 + index_offset,
-                7 + index_offset,
-                2 + index_offset,
-            ]);
-            indices.append(&mut vec![
-                2 + index_offset,
-                7 + index_offset,
-                6 + index_offset,
-            ]);
-        }
-    } // end loop
-
-    let index_offset = (n_steps - 2) * 4;
-    if left_hand_thread {
-        indices.append(&mut vec![
-            5 + index_offset,
-            7 + index_offset,
-            6 + index_offset,
-        ]);
-        indices.append(&mut vec![
-            4 + index_offset,
-            5 + index_offset,
-            6 + index_offset,
-        ]);
-    } else {
-        indices.append(&mut vec![
-            6 + index_offset,
-            7 + index_offset,
-            5 + index_offset,
-        ]);
-        indices.append(&mut vec![
-            6 + index_offset,
-            5 + index_offset,
-            4 + index_offset,
-        ]);
-    }
-
-    let mut faces = Faces::with_capacity(indices.len() / 3);
-    for i in (0..indices.len()).step_by(3) {
-        faces.push(Indices::from_indices(vec![
-            indices[i] as u64,
-            indices[i + 1] as u64,
-            indices[i + 2] as u64,
-        ]));
-    }
+    let profile = Pt2s::from_pt2s(vec![
+        Pt2::new(d_min / 2.0, 3.0 / 4.0 * pitch),
+        Pt2::new(d_maj / 2.0, 7.0 / 16.0 * pitch),
+        Pt2::new(d_maj / 2.0, 5.0 / 16.0 * pitch),
+        Pt2::new(d_min / 2.0, 0.0),
+    ]);
     let convexity = (length / pitch) as u64 + 1;
-    let threads = polyhedron!(Pt3s::from_pt3s(vertices), faces, convexity);
+    let threads = helical_ridge(
+        &profile,
+        d_min / 2.0,
+        pitch,
+        thread_length,
+        segments,
+        lead_in_degrees,
+        lead_out_degrees,
+        left_hand_thread,
+    )
+    .into_scad_with_convexity(convexity);
 
     let rod = Polyhedron::cylinder(d_min / 2.0 + 0.0001, length, segments).into_scad();
 
@@ -418,9 +200,51 @@ pub fn threaded_rod(
     left_hand_thread: bool,
     center: bool,
 ) -> Scad {
-    let thread_info = m_table_lookup(m);
-    let pitch = thread_info["pitch"];
-    let d_maj = thread_info["external_dMaj"];
+    threaded_rod_with_fit(
+        m,
+        length,
+        segments,
+        lead_in_degrees,
+        lead_out_degrees,
+        left_hand_thread,
+        center,
+        ThreadFit::Exact,
+    )
+}
+
+/// Creates a threaded rod at the world origin with clearance for printing.
+///
+/// m: The metric size of the rod.
+///
+/// length: The length of the rod in mm.
+///
+/// segments: The number of segments in a circle.
+///
+/// lead_in_degrees: Span of the lead in.
+///
+/// lead_out_degrees: Span of the lead out.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// fit: The fit with the mating internal thread.
+///
+/// return: The threaded rod.
+#[allow(clippy::too_many_arguments)]
+pub fn threaded_rod_with_fit(
+    m: i32,
+    length: f64,
+    segments: u64,
+    lead_in_degrees: f64,
+    lead_out_degrees: f64,
+    left_hand_thread: bool,
+    center: bool,
+    fit: ThreadFit,
+) -> Scad {
+    let thread_info = MetricThreadSpec::lookup(m);
+    let pitch = thread_info.pitch;
+    let d_maj = thread_info.external_d_maj - fit.clearance() / 2.0;
     let d_min = d_min_from_d_maj_pitch(d_maj, pitch);
 
     threaded_cylinder(
@@ -436,6 +260,111 @@ pub fn threaded_rod(
     )
 }
 
+/// Options for threaded_rod_with_options.
+#[derive(Clone, Copy)]
+pub struct ThreadedRodOptions {
+    /// The number of segments in a circle.
+    pub segments: u64,
+    pub left_hand_thread: bool,
+    /// The fit with the mating internal thread.
+    pub fit: ThreadFit,
+    /// The axial length of the lead in.
+    pub lead_in_length: f64,
+    /// The axial length of the lead out.
+    pub lead_out_length: f64,
+    /// The height where the threads start.
+    pub thread_start: f64,
+    /// The height the threads occupy, 0 for up to the end of the rod. Lengths
+    /// under 1.75 pitches give a partial turn stub thread.
+    pub thread_length: f64,
+    /// Center vertically.
+    pub center: bool,
+    /// A fine pitch e.g. Some(1.0) for M8x1, None for the coarse pitch.
+    pub pitch: Option<f64>,
+}
+
+impl Default for ThreadedRodOptions {
+    fn default() -> Self {
+        Self {
+            segments: 36,
+            left_hand_thread: false,
+            fit: ThreadFit::Exact,
+            lead_in_length: 0.0,
+            lead_out_length: 0.0,
+            thread_start: 0.0,
+            thread_length: 0.0,
+            center: false,
+            pitch: None,
+        }
+    }
+}
+
+/// Creates a threaded rod at the world origin with threads cropped to a height range.
+///
+/// The lead in and lead out are shortened proportionally when they don't fit
+/// in the threads.
+///
+/// m: The metric size of the rod.
+///
+/// length: The length of the rod in mm.
+///
+/// options: The options for the rod.
+///
+/// return: The threaded rod.
+pub fn threaded_rod_with_options(m: i32, length: f64, options: &ThreadedRodOptions) -> Scad {
+    let thread_info = MetricThreadSpec::lookup(m);
+    let pitch = checked_pitch(m, options.pitch);
+    let d_maj = thread_info.external_d_maj - options.fit.clearance() / 2.0;
+    let d_min = d_min_from_d_maj_pitch(d_maj, pitch);
+    let thread_length = if options.thread_length > 0.0 {
+        options.thread_length
+    } else {
+        length - options.thread_start
+    };
+    assert!(options.thread_start >= 0.0);
+    assert!(options.thread_start + thread_length <= length + 1.0e-9);
+
+    // the ridge sticks up 3/4 of a pitch above the helix
+    let climb = thread_length - 0.75 * pitch;
+    let degrees = climb / pitch * 360.0;
+    assert!(
+        degrees * options.segments as f64 / 360.0 > 1.0,
+        "threads are too short for the pitch"
+    );
+    let mut lead_in_degrees = options.lead_in_length / pitch * 360.0;
+    let mut lead_out_degrees = options.lead_out_length / pitch * 360.0;
+    if lead_in_degrees + lead_out_degrees > degrees {
+        let scale = degrees / (lead_in_degrees + lead_out_degrees);
+        lead_in_degrees *= scale;
+        lead_out_degrees *= scale;
+    }
+
+    let profile = Pt2s::from_pt2s(vec![
+        Pt2::new(d_min / 2.0, 3.0 / 4.0 * pitch),
+        Pt2::new(d_maj / 2.0, 7.0 / 16.0 * pitch),
+        Pt2::new(d_maj / 2.0, 5.0 / 16.0 * pitch),
+        Pt2::new(d_min / 2.0, 0.0),
+    ]);
+    let threads = helical_ridge(
+        &profile,
+        d_min / 2.0,
+        pitch,
+        climb,
+        options.segments,
+        lead_in_degrees,
+        lead_out_degrees,
+        options.left_hand_thread,
+    )
+    .into_scad_with_convexity((thread_length / pitch) as u64 + 1);
+    let rod = Polyhedron::cylinder(d_min / 2.0 + 0.0001, length, options.segments).into_scad();
+
+    let mut result = rod + translate!([0.0, 0.0, options.thread_start], threads;);
+    if options.center {
+        result = translate!([0.0, 0.0, -length / 2.0], result;);
+    }
+    result
+}
+
 /// Create a hex head bolt at the world origin.
 ///
 /// m: The metric bolt size.
@@ -454,7 +383,7 @@ pub fn threaded_rod(
 ///
 /// center: Center vertically.
 ///
-/// return: The hex bolt.
+/// return: The hex bolt, marked as a bill of materials item.
 #[allow(clippy::too_many_arguments)]
 pub fn hex_bolt(
     m: i32,
@@ -466,10 +395,56 @@ pub fn hex_bolt(
     left_hand_thread: bool,
     center: bool,
 ) -> Scad {
-    let thread_info = m_table_lookup(m);
-    let pitch = thread_info["pitch"];
-    let d_maj = thread_info["external_dMaj"];
-    let head_diameter = thread_info["nut_width"];
+    hex_bolt_with_fit(
+        m,
+        length,
+        head_height,
+        segments,
+        lead_in_degrees,
+        chamfered,
+        left_hand_thread,
+        center,
+        ThreadFit::Exact,
+    )
+}
+
+/// Create a hex head bolt at the world origin with clearance for printing.
+///
+/// m: The metric bolt size.
+///
+/// length: The length of the threaded part.
+///
+/// head_height: The height of the hex head.
+///
+/// segments: The number of segments in a circle.
+///
+/// lead_in_degrees: The amount of degrees the tapered thread occupies.
+///
+/// chamfered: Whether or not to chamfer the top and bottom of the head.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// fit: The fit with the mating internal thread.
+///
+/// return: The hex bolt, marked as a bill of materials item.
+#[allow(clippy::too_many_arguments)]
+pub fn hex_bolt_with_fit(
+    m: i32,
+    length: f64,
+    head_height: f64,
+    segments: u64,
+    lead_in_degrees: f64,
+    chamfered: bool,
+    left_hand_thread: bool,
+    center: bool,
+    fit: ThreadFit,
+) -> Scad {
+    let thread_info = MetricThreadSpec::lookup(m);
+    let pitch = thread_info.pitch;
+    let d_maj = thread_info.external_d_maj - fit.clearance() / 2.0;
+    let head_diameter = thread_info.nut_width;
     let d_min = d_min_from_d_maj_pitch(d_maj, pitch);
 
     let mut rod = threaded_cylinder(
@@ -491,7 +466,7 @@ pub fn hex_bolt(
     )
     .into_scad();
     if chamfered {
-        let chamfer_size = thread_info["chamfer_size"];
+        let chamfer_size = thread_info.chamfer_size;
         head = head
             - Scad::external_cylinder_chamfer(
                 chamfer_size,
@@ -508,687 +483,1846 @@ pub fn hex_bolt(
     if center {
         bolt = translate!([0.0, 0.0, -((head_height + length) / 2.0)], bolt;);
     }
-    bolt
+    bolt.bom_item(
+        &hardware_name(m, Some(length), "hex bolt", left_hand_thread),
+        1,
+    )
 }
 
-/// Create a tap for making threaded holes in things.
+/// The bill of materials name of a bolt, screw or nut, e.g. M3x10 hex bolt.
+fn hardware_name(m: i32, length: Option<f64>, kind: &str, left_hand_thread: bool) -> String {
+    let mut name = format!("M{}", m);
+    if let Some(length) = length {
+        name += &format!("x{}", Float(length));
+    }
+    name += &format!(" {}", kind);
+    if left_hand_thread {
+        name += ", left hand";
+    }
+    name
+}
+
+/// Create a socket head cap screw at the world origin.
 ///
-/// m: The metric size of the tap.
+/// The head sits on the XY plane with the hex socket facing down and the
+/// threads extending up from the top of the head.
 ///
-/// length: The length of the tap.
+/// m: The metric screw size.
 ///
-/// segments: The number of segmentst in a circle.
+/// length: The length of the threaded part.
+///
+/// segments: The number of segments in a circle.
+///
+/// lead_in_degrees: The amount of degrees the tapered thread occupies.
 ///
 /// left_hand_thread: lefty tighty?
 ///
 /// center: Center vertically.
 ///
-/// return: The tap.
-pub fn tap(m: i32, length: f64, segments: u64, left_hand_thread: bool, center: bool) -> Scad {
-    let thread_info = m_table_lookup(m);
-    let pitch = thread_info["pitch"];
-    let d_maj = thread_info["internal_dMaj"];
-    let d_min = d_min_from_d_maj_pitch(d_maj, pitch);
-
-    threaded_cylinder(
-        d_min,
-        d_maj,
-        pitch,
+/// return: The screw, marked as a bill of materials item.
+pub fn socket_head_cap_screw(
+    m: i32,
+    length: f64,
+    segments: u64,
+    lead_in_degrees: f64,
+    left_hand_thread: bool,
+    center: bool,
+) -> Scad {
+    let head = socket_head_dimensions(m);
+    let chamfer = head.height * 0.1;
+    let blank =
+        Scad::chamfered_cylinder(head.diameter / 2.0, head.height, chamfer, segments, false);
+    screw(
+        m,
         length,
         segments,
-        0.0,
-        0.0,
+        lead_in_degrees,
         left_hand_thread,
         center,
+        blank,
+        &head,
+    )
+    .bom_item(
+        &hardware_name(m, Some(length), "socket head cap screw", left_hand_thread),
+        1,
     )
 }
 
-/// Create a hex nut.
+/// Create a countersunk screw at the world origin.
 ///
-/// m: The metric size of the nut.
+/// The flat top of the head sits on the XY plane with the hex socket facing
+/// down, the 90 degree cone and threads extend up.
 ///
-/// height: The height of the nut.
+/// m: The metric screw size.
+///
+/// length: The length of the threaded part, not including the head.
 ///
 /// segments: The number of segments in a circle.
 ///
-/// chamfered: Adds a chamfer to the nut.
+/// lead_in_degrees: The amount of degrees the tapered thread occupies.
 ///
 /// left_hand_thread: lefty tighty?
 ///
-/// center: Center horizontally.
+/// center: Center vertically.
 ///
-/// return: The nut.
-pub fn hex_nut(
+/// return: The screw, marked as a bill of materials item.
+pub fn countersunk_screw(
     m: i32,
-    height: f64,
+    length: f64,
     segments: u64,
-    chamfered: bool,
+    lead_in_degrees: f64,
     left_hand_thread: bool,
     center: bool,
 ) -> Scad {
-    let thread_info = m_table_lookup(m);
-    let nut_width = thread_info["nut_width"];
-
-    let mut nut_tap = tap(m, height + 20.0, segments, left_hand_thread, center);
-    nut_tap = translate!([0.0, 0.0, -10.0], nut_tap;);
+    let head = countersunk_head_dimensions(m);
+    let d_maj = MetricThreadSpec::lookup(m).external_d_maj;
+    let blank = cylinder!(
+        head.height,
+        head.diameter / 2.0,
+        d_maj / 2.0,
+        false,
+        fn = segments
+    );
+    screw(
+        m,
+        length,
+        segments,
+        lead_in_degrees,
+        left_hand_thread,
+        center,
+        blank,
+        &head,
+    )
+    .bom_item(
+        &hardware_name(m, Some(length), "countersunk screw", left_hand_thread),
+        1,
+    )
+}
 
-    let nut_blank =
-        Polyhedron::linear_extrude(&dim2::circumscribed_polygon(6, nut_width / 2.0), height)
-            .into_scad();
+/// Puts the threads on top of a head blank and cuts the socket.
+#[allow(clippy::too_many_arguments)]
+fn screw(
+    m: i32,
+    length: f64,
+    segments: u64,
+    lead_in_degrees: f64,
+    left_hand_thread: bool,
+    center: bool,
+    blank: Scad,
+    head: &HeadDimensions,
+) -> Scad {
+    let thread_info = MetricThreadSpec::lookup(m);
+    let pitch = thread_info.pitch;
+    let d_maj = thread_info.external_d_maj;
+    let d_min = d_min_from_d_maj_pitch(d_maj, pitch);
 
-    let mut nut = nut_blank - nut_tap;
-    if chamfered {
-        let chamfer_size = thread_info["chamfer_size"];
-        nut = nut
-            - Scad::external_cylinder_chamfer(
-                chamfer_size,
-                1.0,
-                (0.25 * nut_width * 0.25 * nut_width + 0.5 * nut_width * 0.5 * nut_width).sqrt(),
-                height,
-                segments,
-                center,
-            );
-    }
+    let mut rod = threaded_cylinder(
+        d_min,
+        d_maj,
+        pitch,
+        length,
+        segments,
+        0.0,
+        lead_in_degrees,
+        left_hand_thread,
+        false,
+    );
+    rod = translate!([0.0, 0.0, head.height], rod;);
 
+    let socket = translate!([0.0, 0.0, -1.0],
+        Polyhedron::linear_extrude(
+            &dim2::circumscribed_polygon(6, head.socket / 2.0),
+            head.socket_depth + 1.0,
+        )
+        .into_scad();
+    );
+    let mut screw = rod + (blank - socket);
     if center {
-        nut = translate!([0.0, 0.0, -height / 2.0], nut;);
+        screw = translate!([0.0, 0.0, -((head.height + length) / 2.0)], screw;);
     }
+    screw
+}
 
-    nut
+/// The dimensions of a screw head.
+struct HeadDimensions {
+    diameter: f64,
+    height: f64,
+    /// The size of the hex key across the flats.
+    socket: f64,
+    socket_depth: f64,
 }
 
-/// Returns the hashmap of iso metric thread profiles
-fn m_table() -> HashMap<i32, HashMap<&'static str, f64>> {
-    HashMap::from([
-        (
-            2,
-            HashMap::from([
-                ("pitch", 0.4),
-                ("external_dMaj", 1.886),
-                ("internal_dMaj", 2.148),
-                ("nut_width", 4.0),
-                ("chamfer_size", 1.45),
-            ]),
-        ),
-        (
-            3,
-            HashMap::from([
-                ("pitch", 0.5),
-                ("external_dMaj", 2.874),
-                ("internal_dMaj", 3.172),
-                ("nut_width", 5.5),
-                ("chamfer_size", 1.6),
-            ]),
-        ),
-        (
-            4,
-            HashMap::from([
-                ("pitch", 0.7),
-                ("external_dMaj", 3.838),
-                ("internal_dMaj", 4.219),
-                ("nut_width", 7.0),
-                ("chamfer_size", 1.8),
-            ]),
-        ),
-        (
-            5,
-            HashMap::from([
-                ("pitch", 0.8),
-                ("external_dMaj", 4.826),
-                ("internal_dMaj", 5.24),
-                ("nut_width", 8.0),
-                ("chamfer_size", 1.9),
-            ]),
-        ),
-        (
-            6,
-            HashMap::from([
-                ("pitch", 1.0),
-                ("external_dMaj", 5.794),
-                ("internal_dMaj", 6.294),
-                ("nut_width", 10.0),
-                ("chamfer_size", 2.1),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            7,
-            HashMap::from([
-                ("pitch", 1.0),
-                ("external_dMaj", 6.794),
-                ("internal_dMaj", 7.294),
-                ("nut_width", 13.0),
-                ("chamfer_size", 2.45),
-            ]),
-        ),
-        (
-            8,
-            HashMap::from([
-                ("pitch", 1.25),
-                ("external_dMaj", 7.76),
-                ("internal_dMaj", 8.34),
-                ("nut_width", 13.0),
-                ("chamfer_size", 2.45),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            9,
-            HashMap::from([
-                ("pitch", 1.25),
-                ("external_dMaj", 8.76),
-                ("internal_dMaj", 9.34),
-                ("nut_width", 16.0),
-                ("chamfer_size", 2.8),
-            ]),
-        ),
-        (
-            10,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 9.732),
-                ("internal_dMaj", 10.396),
-                ("nut_width", 16.0),
-                ("chamfer_size", 2.8),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            11,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 10.73),
-                ("internal_dMaj", 11.387),
-                ("nut_width", 18.0),
-                ("chamfer_size", 3.0),
-            ]),
-        ),
-        (
-            12,
-            HashMap::from([
-                ("pitch", 1.75),
-                ("external_dMaj", 11.7),
-                ("internal_dMaj", 12.453),
-                ("nut_width", 18.0),
-                ("chamfer_size", 3.0),
-            ]),
-        ),
-        (
-            14,
-            HashMap::from([
-                ("pitch", 2.0),
-                ("external_dMaj", 13.68),
-                ("internal_dMaj", 14.501),
-                ("nut_width", 21.0),
-                ("chamfer_size", 3.35),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            15,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 14.73),
-                ("internal_dMaj", 15.407),
-                ("nut_width", 24.0),
-                ("chamfer_size", 3.7),
-            ]),
-        ),
-        (
-            16,
-            HashMap::from([
-                ("pitch", 2.0),
-                ("external_dMaj", 15.68),
-                ("internal_dMaj", 16.501),
-                ("nut_width", 24.0),
-                ("chamfer_size", 3.7),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            17,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 16.73),
-                ("internal_dMaj", 17.407),
-                ("nut_width", 27.0),
-                ("chamfer_size", 3.9),
-            ]),
-        ),
-        (
-            18,
-            HashMap::from([
-                ("pitch", 2.5),
-                ("external_dMaj", 17.62),
-                ("internal_dMaj", 18.585),
-                ("nut_width", 27.0),
-                ("chamfer_size", 3.9),
-            ]),
-        ),
-        (
-            20,
-            HashMap::from([
-                ("pitch", 2.5),
-                ("external_dMaj", 19.62),
-                ("internal_dMaj", 20.585),
-                ("nut_width", 30.0),
-                ("chamfer_size", 4.25),
-            ]),
-        ),
-        (
-            22,
-            HashMap::from([
-                ("pitch", 3.0),
-                ("external_dMaj", 21.58),
-                ("internal_dMaj", 22.677),
-                ("nut_width", 34.0),
-                ("chamfer_size", 4.75),
-            ]),
-        ),
-        (
-            24,
-            HashMap::from([
-                ("pitch", 3.0),
-                ("external_dMaj", 23.58),
-                ("internal_dMaj", 24.698),
-                ("nut_width", 36.0),
-                ("chamfer_size", 4.9),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            25,
-            HashMap::from([
-                ("pitch", 2.0),
-                ("external_dMaj", 24.68),
-                ("internal_dMaj", 25.513),
-                ("nut_width", 41.0),
-                ("chamfer_size", 5.5),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            26,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 25.73),
-                ("internal_dMaj", 26.417),
-                ("nut_width", 41.0),
-                ("chamfer_size", 5.5),
-            ]),
-        ),
-        (
-            27,
-            HashMap::from([
-                ("pitch", 3.0),
-                ("external_dMaj", 26.58),
-                ("internal_dMaj", 27.698),
-                ("nut_width", 41.0),
-                ("chamfer_size", 5.5),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            28,
-            HashMap::from([
-                ("pitch", 2.0),
-                ("external_dMaj", 27.68),
-                ("internal_dMaj", 28.513),
-                ("nut_width", 46.0),
-                ("chamfer_size", 6.0),
-            ]),
-        ),
-        (
-            30,
-            HashMap::from([
-                ("pitch", 3.5),
-                ("external_dMaj", 29.52),
-                ("internal_dMaj", 30.785),
-                ("nut_width", 46.0),
-                ("chamfer_size", 6.0),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            32,
-            HashMap::from([
-                ("pitch", 2.0),
-                ("external_dMaj", 31.68),
-                ("internal_dMaj", 32.513),
-                ("nut_width", 49.0),
-                ("chamfer_size", 6.4),
-            ]),
-        ),
-        (
-            33,
-            HashMap::from([
-                ("pitch", 3.5),
-                ("external_dMaj", 32.54),
-                ("internal_dMaj", 33.785),
-                ("nut_width", 49.0),
-                ("chamfer_size", 6.4),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            35,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 34.73),
-                ("internal_dMaj", 35.416),
-                ("nut_width", 55.0),
-                ("chamfer_size", 7.0),
-            ]),
-        ),
-        (
-            36,
-            HashMap::from([
-                ("pitch", 4.0),
-                ("external_dMaj", 35.47),
-                ("internal_dMaj", 36.877),
-                ("nut_width", 55.0),
-                ("chamfer_size", 7.0),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            38,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 37.73),
-                ("internal_dMaj", 38.417),
-                ("nut_width", 60.0),
-                ("chamfer_size", 7.5),
-            ]),
-        ),
-        (
-            39,
-            HashMap::from([
-                ("pitch", 4.0),
-                ("external_dMaj", 38.47),
-                ("internal_dMaj", 39.877),
-                ("nut_width", 60.0),
-                ("chamfer_size", 7.5),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            40,
-            HashMap::from([
-                ("pitch", 3.0),
-                ("external_dMaj", 39.58),
-                ("internal_dMaj", 40.698),
-                ("nut_width", 65.0),
-                ("chamfer_size", 8.2),
-            ]),
-        ),
-        (
-            42,
-            HashMap::from([
-                ("pitch", 4.5),
-                ("external_dMaj", 41.44),
-                ("internal_dMaj", 42.965),
-                ("nut_width", 65.0),
-                ("chamfer_size", 8.2),
-            ]),
-        ),
-        (
-            45,
-            HashMap::from([
-                ("pitch", 4.5),
-                ("external_dMaj", 44.44),
-                ("internal_dMaj", 45.965),
-                ("nut_width", 70.0),
-                ("chamfer_size", 8.75),
-            ]),
-        ),
-        (
-            48,
-            HashMap::from([
-                ("pitch", 5.0),
-                ("external_dMaj", 47.4),
-                ("internal_dMaj", 49.057),
-                ("nut_width", 75.0),
-                ("chamfer_size", 9.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            50,
-            HashMap::from([
-                ("pitch", 4.0),
-                ("external_dMaj", 49.47),
-                ("internal_dMaj", 50.892),
-                ("nut_width", 80.0),
-                ("chamfer_size", 9.5),
-            ]),
-        ),
-        (
-            52,
-            HashMap::from([
-                ("pitch", 5.0),
-                ("external_dMaj", 51.4),
-                ("internal_dMaj", 53.037),
-                ("nut_width", 80.0),
-                ("chamfer_size", 9.5),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            55,
-            HashMap::from([
-                ("pitch", 4.0),
-                ("external_dMaj", 54.47),
-                ("internal_dMaj", 55.892),
-                ("nut_width", 85.0),
-                ("chamfer_size", 10.25),
-            ]),
-        ),
-        (
-            56,
-            HashMap::from([
-                ("pitch", 5.5),
-                ("external_dMaj", 55.37),
-                ("internal_dMaj", 57.149),
-                ("nut_width", 85.0),
-                ("chamfer_size", 10.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            58,
-            HashMap::from([
-                ("pitch", 4.0),
-                ("external_dMaj", 57.47),
-                ("internal_dMaj", 58.892),
-                ("nut_width", 90.0),
-                ("chamfer_size", 10.75),
-            ]),
-        ),
-        (
-            60,
-            HashMap::from([
-                ("pitch", 5.5),
-                ("external_dMaj", 59.37),
-                ("internal_dMaj", 61.149),
-                ("nut_width", 90.0),
-                ("chamfer_size", 10.75),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            62,
-            HashMap::from([
-                ("pitch", 4.0),
-                ("external_dMaj", 61.47),
-                ("internal_dMaj", 62.892),
-                ("nut_width", 95.0),
-                ("chamfer_size", 11.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            63,
-            HashMap::from([
-                ("pitch", 1.5),
-                ("external_dMaj", 62.73),
-                ("internal_dMaj", 63.429),
-                ("nut_width", 95.0),
-                ("chamfer_size", 11.25),
-            ]),
-        ),
-        (
-            64,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 63.32),
-                ("internal_dMaj", 65.421),
-                ("nut_width", 95.0),
-                ("chamfer_size", 11.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            65,
-            HashMap::from([
-                ("pitch", 4.0),
-                ("external_dMaj", 64.47),
-                ("internal_dMaj", 65.892),
-                ("nut_width", 100.0),
-                ("chamfer_size", 11.75),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            68,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 67.32),
-                ("internal_dMaj", 69.241),
-                ("nut_width", 100.0),
-                ("chamfer_size", 11.75),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            70,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 69.32),
-                ("internal_dMaj", 71.241),
-                ("nut_width", 100.0),
-                ("chamfer_size", 11.75),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            72,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 71.32),
-                ("internal_dMaj", 73.241),
-                ("nut_width", 110.0),
-                ("chamfer_size", 13.0),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            75,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 74.32),
-                ("internal_dMaj", 76.241),
-                ("nut_width", 110.0),
-                ("chamfer_size", 13.0),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            76,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 75.32),
-                ("internal_dMaj", 77.241),
-                ("nut_width", 110.0),
-                ("chamfer_size", 13.0),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            78,
-            HashMap::from([
-                ("pitch", 2.0),
-                ("external_dMaj", 77.68),
-                ("internal_dMaj", 78.525),
-                ("nut_width", 120.0),
-                ("chamfer_size", 14.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            80,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 79.32),
-                ("internal_dMaj", 81.241),
-                ("nut_width", 120.0),
-                ("chamfer_size", 14.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            82,
-            HashMap::from([
-                ("pitch", 2.0),
-                ("external_dMaj", 81.68),
-                ("internal_dMaj", 82.525),
-                ("nut_width", 120.0),
-                ("chamfer_size", 14.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            85,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 84.32),
-                ("internal_dMaj", 86.241),
-                ("nut_width", 130.0),
-                ("chamfer_size", 15.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            90,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 89.32),
-                ("internal_dMaj", 91.241),
-                ("nut_width", 130.0),
-                ("chamfer_size", 15.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            95,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 94.32),
-                ("internal_dMaj", 96.266),
-                ("nut_width", 130.0),
-                ("chamfer_size", 15.25),
-            ]),
-        ),
-        // nut_width made up for next entry
-        (
-            100,
-            HashMap::from([
-                ("pitch", 6.0),
-                ("external_dMaj", 99.32),
-                ("internal_dMaj", 101.27),
-                ("nut_width", 140.0),
-                ("chamfer_size", 16.5),
-            ]),
+/// Looks up the closest smaller size in a table of (m, values) and scales
+/// the values for sizes that are not in the table.
+fn head_table_lookup<const N: usize>(m: i32, table: &[(i32, [f64; N])]) -> [f64; N] {
+    let m = m.max(1);
+    let (size, values) = table
+        .iter()
+        .rev()
+        .find(|(size, _)| *size <= m)
+        .unwrap_or(&table[0]);
+    let scale = m as f64 / *size as f64;
+    values.map(|v| v * scale)
+}
+
+/// Returns the ISO 4762 head dimensions for the given M size.
+fn socket_head_dimensions(m: i32) -> HeadDimensions {
+    // (m, [head diameter, hex key size])
+    const TABLE: [(i32, [f64; 2]); 14] = [
+        (2, [3.8, 1.5]),
+        (3, [5.5, 2.5]),
+        (4, [7.0, 3.0]),
+        (5, [8.5, 4.0]),
+        (6, [10.0, 5.0]),
+        (8, [13.0, 6.0]),
+        (10, [16.0, 8.0]),
+        (12, [18.0, 10.0]),
+        (14, [21.0, 12.0]),
+        (16, [24.0, 14.0]),
+        (20, [30.0, 17.0]),
+        (24, [36.0, 19.0]),
+        (30, [45.0, 22.0]),
+        (36, [54.0, 27.0]),
+    ];
+    let [diameter, socket] = head_table_lookup(m, &TABLE);
+    HeadDimensions {
+        diameter,
+        height: m as f64,
+        socket,
+        socket_depth: m as f64 * 0.5,
+    }
+}
+
+/// Returns the ISO 10642 head dimensions for the given M size.
+fn countersunk_head_dimensions(m: i32) -> HeadDimensions {
+    // (m, [head diameter, head height, hex key size])
+    const TABLE: [(i32, [f64; 3]); 9] = [
+        (3, [6.72, 1.86, 2.0]),
+        (4, [8.96, 2.48, 2.5]),
+        (5, [11.2, 3.1, 3.0]),
+        (6, [13.44, 3.72, 4.0]),
+        (8, [17.92, 4.96, 5.0]),
+        (10, [22.4, 6.2, 6.0]),
+        (12, [26.88, 7.44, 8.0]),
+        (16, [33.6, 8.8, 10.0]),
+        (20, [40.32, 10.16, 12.0]),
+    ];
+    let [diameter, height, socket] = head_table_lookup(m, &TABLE);
+    HeadDimensions {
+        diameter,
+        height,
+        socket,
+        socket_depth: height * 0.6,
+    }
+}
+
+/// Returns the ISO 7380 head dimensions for the given M size.
+fn button_head_dimensions(m: i32) -> HeadDimensions {
+    // (m, [head diameter, head height, hex key size])
+    const TABLE: [(i32, [f64; 3]); 8] = [
+        (3, [5.7, 1.65, 2.0]),
+        (4, [7.6, 2.2, 2.5]),
+        (5, [9.5, 2.75, 3.0]),
+        (6, [10.5, 3.3, 4.0]),
+        (8, [14.0, 4.4, 5.0]),
+        (10, [17.5, 5.5, 6.0]),
+        (12, [21.0, 6.6, 8.0]),
+        (16, [28.0, 8.8, 10.0]),
+    ];
+    let [diameter, height, socket] = head_table_lookup(m, &TABLE);
+    HeadDimensions {
+        diameter,
+        height,
+        socket,
+        socket_depth: height * 0.6,
+    }
+}
+
+/// Returns the ISO 7045 head dimensions for the given M size.
+fn pan_head_dimensions(m: i32) -> HeadDimensions {
+    // (m, [head diameter, head height])
+    const TABLE: [(i32, [f64; 2]); 7] = [
+        (2, [4.0, 1.6]),
+        (3, [5.6, 2.1]),
+        (4, [8.0, 2.8]),
+        (5, [9.5, 3.5]),
+        (6, [12.0, 4.2]),
+        (8, [16.0, 5.6]),
+        (10, [20.0, 7.0]),
+    ];
+    let [diameter, height] = head_table_lookup(m, &TABLE);
+    HeadDimensions {
+        diameter,
+        height,
+        socket: 0.0,
+        socket_depth: 0.0,
+    }
+}
+
+/// Returns the ISO 4017 head dimensions for the given M size.
+///
+/// The diameter is the width across the flats.
+fn hex_head_dimensions(m: i32) -> HeadDimensions {
+    // (m, [head height])
+    const TABLE: [(i32, [f64; 1]); 10] = [
+        (2, [1.4]),
+        (3, [2.0]),
+        (4, [2.8]),
+        (5, [3.5]),
+        (6, [4.0]),
+        (8, [5.3]),
+        (10, [6.4]),
+        (12, [7.5]),
+        (16, [10.0]),
+        (20, [12.5]),
+    ];
+    let [height] = head_table_lookup(m, &TABLE);
+    HeadDimensions {
+        diameter: MetricThreadSpec::lookup(m).nut_width,
+        height,
+        socket: 0.0,
+        socket_depth: 0.0,
+    }
+}
+
+/// The style of a screw head.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HeadStyle {
+    /// ISO 4017 hex head.
+    Hex,
+    /// ISO 4762 socket head cap.
+    Socket,
+    /// ISO 7380 button head.
+    Button,
+    /// ISO 10642 countersunk flat head.
+    Flat,
+    /// ISO 7045 pan head.
+    Pan,
+}
+
+fn head_dimensions(style: HeadStyle, m: i32) -> HeadDimensions {
+    match style {
+        HeadStyle::Hex => hex_head_dimensions(m),
+        HeadStyle::Socket => socket_head_dimensions(m),
+        HeadStyle::Button => button_head_dimensions(m),
+        HeadStyle::Flat => countersunk_head_dimensions(m),
+        HeadStyle::Pan => pan_head_dimensions(m),
+    }
+}
+
+/// Creates a pocket for cutting a recess for a screw head into a surface at the XY plane.
+///
+/// The head sits flush with the XY plane and the pocket extends down into -Z.
+/// The pocket extends 1 unit above the XY plane for a cleaner preview. Flat
+/// heads get a 90 degree cone sunk by the clearance below a straight sided
+/// collar, the other styles a straight sided pocket. Union with a clearance hole
+/// for the shank.
+///
+/// style: The style of the head.
+///
+/// m: The metric screw size.
+///
+/// clearance: Added to the diameter and depth of the pocket.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The pocket.
+pub fn head_pocket(style: HeadStyle, m: i32, clearance: f64, segments: u64) -> Scad {
+    let head = head_dimensions(style, m);
+    let radius = (head.diameter + clearance) / 2.0;
+    match style {
+        HeadStyle::Hex => translate!([0.0, 0.0, -head.height - clearance],
+            Polyhedron::linear_extrude(
+                &dim2::circumscribed_polygon(6, radius),
+                head.height + clearance + 1.0,
+            )
+            .into_scad();
         ),
-    ])
+        HeadStyle::Flat => {
+            let bottom_radius = (MetricThreadSpec::lookup(m).external_d_maj + clearance) / 2.0;
+            translate!([0.0, 0.0, -head.height - clearance],
+                cylinder!(head.height, bottom_radius, radius, false, fn=segments);
+            ) + translate!([0.0, 0.0, -clearance],
+                cylinder!(clearance + 1.0, radius, fn=segments);
+            )
+        }
+        HeadStyle::Socket | HeadStyle::Button | HeadStyle::Pan => {
+            translate!([0.0, 0.0, -head.height - clearance],
+                cylinder!(head.height + clearance + 1.0, radius, fn=segments);
+            )
+        }
+    }
+}
+
+/// Create the negative of a hexagonal pocket that holds a nut.
+///
+/// The entrance is on the XY plane and the pocket extends down into -Z with the
+/// flats parallel to the X axis. The pocket extends 1 unit above the XY plane
+/// for a cleaner preview. Union with a clearance hole for the bolt.
+///
+/// m: The metric size of the nut.
+///
+/// depth: The depth of the pocket, usually the height of the nut.
+///
+/// clearance: Added to the width across the flats of the nut.
+///
+/// return: The pocket.
+pub fn nut_trap(m: i32, depth: f64, clearance: f64) -> Scad {
+    let width = MetricThreadSpec::lookup(m).nut_width + clearance;
+    translate!([0.0, 0.0, -depth],
+        Polyhedron::linear_extrude(&dim2::circumscribed_polygon(6, width / 2.0), depth + 1.0)
+            .into_scad();
+    )
+}
+
+/// Create the negative of a hole a bolt passes through.
+///
+/// The entrance is on the XY plane and the hole extends down into -Z. The hole
+/// extends 1 unit above the XY plane for a cleaner preview. The diameter is the
+/// ISO 273 medium clearance from clearance_hole_diameter.
+///
+/// m: The metric size of the bolt.
+///
+/// length: The depth of the hole.
+///
+/// head_recess: A pocket for the head at the entrance with 0.5 clearance or None
+/// for a plain hole.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The hole.
+pub fn bolt_clearance_hole(
+    m: i32,
+    length: f64,
+    head_recess: Option<HeadStyle>,
+    segments: u64,
+) -> Scad {
+    let hole = translate!([0.0, 0.0, -length],
+        cylinder!(length + 1.0, clearance_hole_diameter(m) / 2.0, fn=segments);
+    );
+    match head_recess {
+        Some(style) => hole + head_pocket(style, m, 0.5, segments),
+        None => hole,
+    }
+}
+
+/// The size of a heat-set brass insert and the hole it is pressed into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InsertDimensions {
+    /// The largest diameter of the knurled outside of the insert.
+    pub outer_diameter: f64,
+    /// The diameter of the hole printed for the insert, smaller than the insert
+    /// so the melted plastic grips the knurls.
+    pub hole_diameter: f64,
+    /// The length of the insert.
+    pub length: f64,
+}
+
+/// Returns the dimensions of common short heat-set inserts for the given M size.
+///
+/// Sizes between the table entries are scaled from the next smaller entry.
+///
+/// m: The metric size of the insert thread.
+///
+/// return: The dimensions.
+pub fn insert_dimensions(m: i32) -> InsertDimensions {
+    // (m, [outer diameter, hole diameter, length])
+    const TABLE: [(i32, [f64; 3]); 7] = [
+        (2, [3.6, 3.2, 4.0]),
+        (3, [4.6, 4.0, 5.7]),
+        (4, [6.3, 5.6, 8.1]),
+        (5, [7.1, 6.4, 9.5]),
+        (6, [8.7, 8.0, 12.7]),
+        (8, [10.1, 9.7, 12.7]),
+        (10, [12.7, 12.0, 12.7]),
+    ];
+    let [outer_diameter, hole_diameter, length] = head_table_lookup(m, &TABLE);
+    InsertDimensions {
+        outer_diameter,
+        hole_diameter,
+        length,
+    }
+}
+
+/// Create the negative of a hole for a heat-set insert.
+///
+/// The entrance is on the XY plane and the hole extends down into -Z. The hole
+/// extends 1 unit above the XY plane for a cleaner preview. A small chamfer at
+/// the entrance centers the insert and the hole is 1 unit deeper than the insert
+/// to take the plastic it pushes down.
+///
+/// m: The metric size of the insert thread.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The hole.
+pub fn insert_pocket(m: i32, segments: u64) -> Scad {
+    let insert = insert_dimensions(m);
+    let radius = insert.hole_diameter / 2.0;
+    let chamfer = (insert.outer_diameter - insert.hole_diameter) / 2.0 + 0.2;
+    let depth = insert.length + 1.0;
+    translate!([0.0, 0.0, -depth],
+        cylinder!(depth + 1.0, radius, fn=segments);
+    ) + translate!([0.0, 0.0, -chamfer],
+        cylinder!(chamfer, radius, radius + chamfer, false, fn=segments);
+    ) + cylinder!(1.0, radius + chamfer, fn=segments)
+}
+
+/// Create a round boss holding a heat-set insert.
+///
+/// The boss stands on the XY plane with the insert hole in the top.
+///
+/// m: The metric size of the insert thread.
+///
+/// height: The height of the boss, at least 1 more than the insert length.
+///
+/// wall: The thickness of the wall around the hole.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The boss.
+pub fn heat_set_boss(m: i32, height: f64, wall: f64, segments: u64) -> Scad {
+    let insert = insert_dimensions(m);
+    assert!(height >= insert.length + 1.0 && wall > 0.0);
+    let radius = insert.hole_diameter / 2.0 + wall;
+    cylinder!(height, radius, fn=segments)
+        - translate!([0.0, 0.0, height], insert_pocket(m, segments);)
+}
+
+/// Create a tap for making threaded holes in things.
+///
+/// m: The metric size of the tap.
+///
+/// length: The length of the tap.
+///
+/// segments: The number of segmentst in a circle.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// return: The tap.
+pub fn tap(m: i32, length: f64, segments: u64, left_hand_thread: bool, center: bool) -> Scad {
+    tap_with_fit(
+        m,
+        length,
+        segments,
+        left_hand_thread,
+        center,
+        ThreadFit::Exact,
+    )
+}
+
+/// Create a tap for making threaded holes in things with clearance for printing.
+///
+/// m: The metric size of the tap.
+///
+/// length: The length of the tap.
+///
+/// segments: The number of segmentst in a circle.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// fit: The fit with the mating external thread.
+///
+/// return: The tap.
+pub fn tap_with_fit(
+    m: i32,
+    length: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    center: bool,
+    fit: ThreadFit,
+) -> Scad {
+    tap_with_pitch(
+        m,
+        length,
+        segments,
+        left_hand_thread,
+        center,
+        fit,
+        MetricThreadSpec::lookup(m).pitch,
+    )
+}
+
+/// Creates a tap with the given pitch.
+fn tap_with_pitch(
+    m: i32,
+    length: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    center: bool,
+    fit: ThreadFit,
+    pitch: f64,
+) -> Scad {
+    let d_maj = MetricThreadSpec::lookup(m).internal_d_maj + fit.clearance() / 2.0;
+    let d_min = d_min_from_d_maj_pitch(d_maj, pitch);
+
+    threaded_cylinder(
+        d_min,
+        d_maj,
+        pitch,
+        length,
+        segments,
+        0.0,
+        0.0,
+        left_hand_thread,
+        center,
+    )
+}
+
+/// Create a hex nut.
+///
+/// m: The metric size of the nut.
+///
+/// height: The height of the nut.
+///
+/// segments: The number of segments in a circle.
+///
+/// chamfered: Adds a chamfer to the nut.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center horizontally.
+///
+/// return: The nut, marked as a bill of materials item.
+pub fn hex_nut(
+    m: i32,
+    height: f64,
+    segments: u64,
+    chamfered: bool,
+    left_hand_thread: bool,
+    center: bool,
+) -> Scad {
+    hex_nut_with_fit(
+        m,
+        height,
+        segments,
+        chamfered,
+        left_hand_thread,
+        center,
+        ThreadFit::Exact,
+    )
+}
+
+/// Create a hex nut with clearance for printing.
+///
+/// m: The metric size of the nut.
+///
+/// height: The height of the nut.
+///
+/// segments: The number of segments in a circle.
+///
+/// chamfered: Adds a chamfer to the nut.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center horizontally.
+///
+/// fit: The fit with the mating external thread.
+///
+/// return: The nut, marked as a bill of materials item.
+pub fn hex_nut_with_fit(
+    m: i32,
+    height: f64,
+    segments: u64,
+    chamfered: bool,
+    left_hand_thread: bool,
+    center: bool,
+    fit: ThreadFit,
+) -> Scad {
+    let thread_info = MetricThreadSpec::lookup(m);
+    let nut_width = thread_info.nut_width;
+
+    let mut nut_tap = tap_with_fit(m, height + 20.0, segments, left_hand_thread, center, fit);
+    nut_tap = translate!([0.0, 0.0, -10.0], nut_tap;);
+
+    let nut_blank =
+        Polyhedron::linear_extrude(&dim2::circumscribed_polygon(6, nut_width / 2.0), height)
+            .into_scad();
+
+    let mut nut = nut_blank - nut_tap;
+    if chamfered {
+        let chamfer_size = thread_info.chamfer_size;
+        nut = nut
+            - Scad::external_cylinder_chamfer(
+                chamfer_size,
+                1.0,
+                (0.25 * nut_width * 0.25 * nut_width + 0.5 * nut_width * 0.5 * nut_width).sqrt(),
+                height,
+                segments,
+                center,
+            );
+    }
+
+    if center {
+        nut = translate!([0.0, 0.0, -height / 2.0], nut;);
+    }
+
+    nut.bom_item(&hardware_name(m, None, "hex nut", left_hand_thread), 1)
 }
+
+/// Options for threaded_hole.
+#[derive(Clone, Copy)]
+pub struct ThreadedHoleOptions {
+    /// The number of segments in a circle.
+    pub segments: u64,
+    pub left_hand_thread: bool,
+    /// The fit with the mating external thread.
+    pub fit: ThreadFit,
+    /// Adds a 45 degree chamfer, the size of the pitch, where the threads start.
+    pub chamfer: bool,
+    /// The diameter of the clearance counterbore, 0 for no counterbore.
+    pub counterbore_diameter: f64,
+    /// The depth of the clearance counterbore.
+    pub counterbore_depth: f64,
+    /// A fine pitch e.g. Some(1.0) for M8x1, None for the coarse pitch.
+    pub pitch: Option<f64>,
+}
+
+impl Default for ThreadedHoleOptions {
+    fn default() -> Self {
+        Self {
+            segments: 36,
+            left_hand_thread: false,
+            fit: ThreadFit::Exact,
+            chamfer: true,
+            counterbore_diameter: 0.0,
+            counterbore_depth: 0.0,
+            pitch: None,
+        }
+    }
+}
+
+/// Create a threaded hole for subtracting from things.
+///
+/// The entrance of the hole is at the XY plane and the hole extends down into -Z.
+/// The tool extends 1 unit above the XY plane for a cleaner preview.
+///
+/// m: The metric size of the hole.
+///
+/// depth: The depth of the hole measured from the entrance.
+///
+/// options: The options for the hole.
+///
+/// return: The hole.
+pub fn threaded_hole(m: i32, depth: f64, options: &ThreadedHoleOptions) -> Scad {
+    let thread_info = MetricThreadSpec::lookup(m);
+    let pitch = checked_pitch(m, options.pitch);
+    let d_maj = thread_info.internal_d_maj + options.fit.clearance() / 2.0;
+    let counterbore = options.counterbore_diameter > 0.0 && options.counterbore_depth > 0.0;
+    let thread_start = if counterbore {
+        assert!(options.counterbore_diameter > d_maj);
+        assert!(options.counterbore_depth < depth);
+        -options.counterbore_depth
+    } else {
+        0.0
+    };
+
+    let mut hole = translate!([0.0, 0.0, -depth],
+        tap_with_pitch(
+            m,
+            depth + 1.0,
+            options.segments,
+            options.left_hand_thread,
+            false,
+            options.fit,
+            pitch,
+        );
+    );
+    if counterbore {
+        hole = hole
+            + translate!([0.0, 0.0, thread_start],
+                cylinder!(
+                    options.counterbore_depth + 1.0,
+                    options.counterbore_diameter / 2.0,
+                    fn = options.segments
+                );
+            );
+    }
+    if options.chamfer {
+        hole = hole
+            + translate!([0.0, 0.0, thread_start],
+                Scad::countersink(d_maj / 2.0, d_maj / 2.0 + pitch, 90.0, pitch, options.segments);
+            );
+    }
+    hole
+}
+
+/// Creates the threaded barrel of a turnbuckle.
+///
+/// The barrel is a hex bar standing on the XY plane. The hole in the bottom is
+/// right hand threaded and the hole in the top is left hand threaded, so turning
+/// the barrel pulls both ends in or pushes them out.
+///
+/// m: The metric size of the threads.
+///
+/// length: The length of the barrel.
+///
+/// thread_depth: The depth of each threaded hole.
+///
+/// segments: The number of segments in a circle.
+///
+/// fit: The fit with the threaded ends.
+///
+/// return: The barrel.
+pub fn turnbuckle_body(
+    m: i32,
+    length: f64,
+    thread_depth: f64,
+    segments: u64,
+    fit: ThreadFit,
+) -> Scad {
+    assert!(length > thread_depth * 2.0);
+    let nut_width = MetricThreadSpec::lookup(m).nut_width;
+    let body = Polyhedron::linear_extrude(&dim2::circumscribed_polygon(6, nut_width / 2.0), length)
+        .into_scad();
+    let options = ThreadedHoleOptions {
+        segments,
+        fit,
+        ..Default::default()
+    };
+    let right = rotate!([180.0, 0.0, 0.0], threaded_hole(m, thread_depth, &options););
+    let left = translate!([0.0, 0.0, length],
+        threaded_hole(
+            m,
+            thread_depth,
+            &ThreadedHoleOptions {
+                left_hand_thread: true,
+                ..options
+            },
+        );
+    );
+    body - right - left
+}
+
+/// Creates a threaded rod with a closed eye on top, for the ends of a turnbuckle.
+///
+/// The rod stands on the XY plane and the eye is in the XZ plane.
+///
+/// m: The metric size of the rod, also the thickness of the eye.
+///
+/// length: The length of the threaded rod.
+///
+/// eye_diameter: The inside diameter of the eye.
+///
+/// segments: The number of segments in a circle.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// fit: The fit with the mating internal thread.
+///
+/// return: The eye.
+pub fn threaded_eye(
+    m: i32,
+    length: f64,
+    eye_diameter: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    fit: ThreadFit,
+) -> Scad {
+    let wire = m as f64;
+    let radius = (eye_diameter + wire) / 2.0;
+    let rod = threaded_end_rod(m, length, segments, left_hand_thread, fit);
+    let eye = translate!([0.0, 0.0, length + radius],
+        rotate!([90.0, 0.0, 0.0],
+            rotate_extrude!(angle=360.0, convexity=2, fn=segments,
+                translate!([radius, 0.0, 0.0], circle!(wire / 2.0, fn=segments););
+            );
+        );
+    );
+    rod + eye
+}
+
+/// Creates a threaded rod with an open hook on top, for the ends of a turnbuckle.
+///
+/// The rod stands on the XY plane and the hook curls over towards +X in the XZ
+/// plane, ending below its center with the opening next to the rod.
+///
+/// m: The metric size of the rod, also the thickness of the hook.
+///
+/// length: The length of the threaded rod.
+///
+/// hook_diameter: The inside diameter of the hook.
+///
+/// segments: The number of segments in a circle.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// fit: The fit with the mating internal thread.
+///
+/// return: The hook.
+pub fn threaded_hook(
+    m: i32,
+    length: f64,
+    hook_diameter: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    fit: ThreadFit,
+) -> Scad {
+    let wire = m as f64;
+    let radius = (hook_diameter + wire) / 2.0;
+    let rod = threaded_end_rod(m, length, segments, left_hand_thread, fit);
+    // three quarters of a ring from the bottom around to the top of the rod
+    let hook = translate!([radius, 0.0, length],
+        rotate!([90.0, 0.0, 0.0],
+            rotate!([0.0, 0.0, -90.0],
+                rotate_extrude!(angle=270.0, convexity=2, fn=segments,
+                    translate!([radius, 0.0, 0.0], circle!(wire / 2.0, fn=segments););
+                );
+            );
+        );
+    );
+    let tip = translate!([radius, 0.0, length - radius], sphere!(wire / 2.0, fn=segments););
+    rod + hook + tip
+}
+
+/// Creates a turnbuckle with an eye screwed into each end of the barrel.
+///
+/// The bottom eye has a right hand thread and the top eye a left hand thread.
+/// The rods are screwed in to the full depth of the holes.
+///
+/// m: The metric size of the threads.
+///
+/// length: The length of the barrel.
+///
+/// thread_depth: The depth of each threaded hole.
+///
+/// end_length: The length of the threaded rod of each eye.
+///
+/// eye_diameter: The inside diameter of the eyes.
+///
+/// segments: The number of segments in a circle.
+///
+/// fit: The fit between the barrel and the eyes.
+///
+/// return: The turnbuckle.
+#[allow(clippy::too_many_arguments)]
+pub fn turnbuckle(
+    m: i32,
+    length: f64,
+    thread_depth: f64,
+    end_length: f64,
+    eye_diameter: f64,
+    segments: u64,
+    fit: ThreadFit,
+) -> Scad {
+    assert!(end_length > thread_depth);
+    let body = turnbuckle_body(m, length, thread_depth, segments, fit);
+    let bottom = translate!([0.0, 0.0, thread_depth],
+        rotate!([180.0, 0.0, 0.0],
+            threaded_eye(m, end_length, eye_diameter, segments, false, fit);
+        );
+    );
+    let top = translate!([0.0, 0.0, length - thread_depth],
+        threaded_eye(m, end_length, eye_diameter, segments, true, fit);
+    );
+    body + bottom + top
+}
+
+/// The threaded rod of a turnbuckle end with its threads ending at the top.
+fn threaded_end_rod(
+    m: i32,
+    length: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    fit: ThreadFit,
+) -> Scad {
+    let pitch = MetricThreadSpec::lookup(m).pitch;
+    threaded_rod_with_options(
+        m,
+        length,
+        &ThreadedRodOptions {
+            segments,
+            left_hand_thread,
+            fit,
+            lead_in_length: pitch,
+            ..Default::default()
+        },
+    )
+}
+
+/// Returns the ISO 273 medium clearance hole diameter for the given M size.
+///
+/// m: The metric size of the bolt.
+///
+/// return: The diameter of the hole.
+pub fn clearance_hole_diameter(m: i32) -> f64 {
+    match m {
+        i32::MIN..=1 => 1.3,
+        2 => 2.4,
+        3 => 3.4,
+        4 => 4.5,
+        5 => 5.5,
+        6 => 6.6,
+        8 => 9.0,
+        10 => 11.0,
+        12 => 13.5,
+        14 => 15.5,
+        16 => 17.5,
+        20 => 22.0,
+        24 => 26.0,
+        30 => 33.0,
+        36 => 39.0,
+        _ => m as f64 * 1.1,
+    }
+}
+
+/// Returns the coarse pitch of the given M size.
+///
+/// m: The metric size of the thread.
+///
+/// return: The pitch.
+pub fn pitch(m: i32) -> f64 {
+    MetricThreadSpec::lookup(m).pitch
+}
+
+/// Returns the ISO 261 fine pitches of the given M size.
+///
+/// m: The metric size of the thread.
+///
+/// return: The pitches from coarsest to finest, empty when the size has no fine series.
+pub fn fine_pitches(m: i32) -> &'static [f64] {
+    match m {
+        2 => &[0.25],
+        3 => &[0.35],
+        4 | 5 => &[0.5],
+        6 => &[0.75],
+        8 => &[1.0, 0.75],
+        10 => &[1.25, 1.0, 0.75],
+        12 | 14 => &[1.5, 1.25, 1.0],
+        16 => &[1.5, 1.0],
+        18 | 20 | 22 | 24 | 27 | 30 => &[2.0, 1.5, 1.0],
+        33 => &[2.0, 1.5],
+        36 | 39 => &[3.0, 2.0, 1.5],
+        42 | 45 | 48 => &[4.0, 3.0, 2.0, 1.5],
+        _ => &[],
+    }
+}
+
+/// Returns the pitch to use for an M size with an optional fine pitch.
+///
+/// Panics when the pitch is not the coarse pitch or in the ISO 261 fine series.
+fn checked_pitch(m: i32, pitch: Option<f64>) -> f64 {
+    let coarse = MetricThreadSpec::lookup(m).pitch;
+    match pitch {
+        Some(pitch) => {
+            assert!(
+                pitch == coarse || fine_pitches(m).contains(&pitch),
+                "M{}x{} is not an ISO 261 thread",
+                m,
+                pitch
+            );
+            pitch
+        }
+        None => coarse,
+    }
+}
+
+/// Returns the usual tap drill diameter for the given M size.
+///
+/// The diameter is the nominal diameter less the pitch which gives about 90%
+/// thread engagement.
+///
+/// m: The metric size of the thread.
+///
+/// return: The diameter of the drill.
+pub fn tap_drill_diameter(m: i32) -> f64 {
+    m as f64 - pitch(m)
+}
+
+/// Returns the pilot hole diameter for a percentage of thread engagement.
+///
+/// Printed parts and plastics are often tapped or screwed into at 60 to 75%
+/// engagement to make driving the screw easier.
+///
+/// m: The metric size of the thread.
+///
+/// engagement_percent: The percentage of the full thread depth that is cut.
+///
+/// return: The diameter of the hole.
+pub fn pilot_hole_diameter(m: i32, engagement_percent: f64) -> f64 {
+    m as f64 - 2.0 * 5.0 / 8.0 * thread_height_from_pitch(pitch(m)) * engagement_percent / 100.0
+}
+
+/// Returns the tensile stress area of a bolt of the given M size.
+///
+/// m: The metric size of the bolt.
+///
+/// return: The area in mm².
+pub fn tensile_stress_area(m: i32) -> f64 {
+    let d = m as f64 - 0.9382 * pitch(m);
+    std::f64::consts::PI / 4.0 * d * d
+}
+
+/// Returns the area that shears when the threads of a bolt strip.
+///
+/// m: The metric size of the bolt.
+///
+/// engagement: The length of thread engagement.
+///
+/// return: The area in mm².
+pub fn external_shear_area(m: i32, engagement: f64) -> f64 {
+    let d_min = d_min_from_d_maj_pitch(m as f64, pitch(m));
+    std::f64::consts::PI * d_min * engagement * 0.75
+}
+
+/// Returns the area that shears when the threads of a hole or nut strip.
+///
+/// m: The metric size of the thread.
+///
+/// engagement: The length of thread engagement.
+///
+/// return: The area in mm².
+pub fn internal_shear_area(m: i32, engagement: f64) -> f64 {
+    std::f64::consts::PI * m as f64 * engagement * 0.875
+}
+
+/// Returns the thread engagement needed for the bolt to break before the threads strip.
+///
+/// The shear strength of each material is taken as 0.577 of its tensile strength.
+/// For a steel bolt in a printed part the engagement is usually longer than can
+/// be had and a heat-set insert or nut should be used instead.
+///
+/// m: The metric size of the bolt.
+///
+/// bolt_strength: The tensile strength of the bolt material.
+///
+/// hole_strength: The tensile strength of the material of the hole, in the same units.
+///
+/// return: The length of engagement.
+pub fn min_engagement_length(m: i32, bolt_strength: f64, hole_strength: f64) -> f64 {
+    let load = tensile_stress_area(m) * bolt_strength;
+    let internal = load / (0.577 * hole_strength * internal_shear_area(m, 1.0));
+    let external = load / (0.577 * bolt_strength * external_shear_area(m, 1.0));
+    internal.max(external)
+}
+
+/// A layout of bolts e.g. for a flange.
+///
+/// The bolts are vertical and the clamped parts sit below the XY plane.
+#[derive(Clone)]
+pub struct BoltPattern {
+    /// The metric size of the bolts.
+    pub m: i32,
+    /// The center of each bolt.
+    pub positions: Vec<Pt2>,
+}
+
+impl BoltPattern {
+    /// Create a pattern from the center of each bolt.
+    pub fn new(m: i32, positions: Vec<Pt2>) -> Self {
+        Self { m, positions }
+    }
+
+    /// Clearance holes for subtracting from the clamped parts.
+    ///
+    /// The holes extend 1 unit past both surfaces for a cleaner preview.
+    ///
+    /// thickness: The total thickness of the clamped parts.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The holes.
+    pub fn holes(&self, thickness: f64, segments: u64) -> Scad {
+        let hole = translate!([0.0, 0.0, -thickness - 1.0],
+            cylinder!(thickness + 2.0, clearance_hole_diameter(self.m) / 2.0, fn=segments);
+        );
+        self.place(&hole)
+    }
+
+    /// Hex bolts with the heads resting on the XY plane and the threads pointing down.
+    ///
+    /// length: The length of the threaded part.
+    ///
+    /// head_height: The height of the hex head.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The bolts.
+    pub fn bolts(&self, length: f64, head_height: f64, segments: u64) -> Scad {
+        let bolt = translate!([0.0, 0.0, head_height],
+            rotate!([180.0, 0.0, 0.0],
+                hex_bolt(self.m, length, head_height, segments, 180.0, true, false, false);
+            );
+        );
+        self.place(&bolt)
+    }
+
+    /// Hex nuts resting against the bottom of the clamped parts.
+    ///
+    /// thickness: The total thickness of the clamped parts.
+    ///
+    /// height: The height of the nuts.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The nuts.
+    pub fn nuts(&self, thickness: f64, height: f64, segments: u64) -> Scad {
+        let nut = translate!([0.0, 0.0, -thickness - height],
+            hex_nut(self.m, height, segments, true, false, false);
+        );
+        self.place(&nut)
+    }
+
+    /// Place a copy of scad at each position of the pattern.
+    pub fn place(&self, scad: &Scad) -> Scad {
+        Scad {
+            op: ScadOp::Union,
+            children: self
+                .positions
+                .iter()
+                .map(|p| translate!([p.x, p.y, 0.0], scad.clone();))
+                .collect(),
+        }
+    }
+}
+
+/// Create a pattern of bolts evenly spaced around a circle.
+///
+/// The first bolt is on the +X axis and the rest follow clockwise.
+///
+/// m: The metric size of the bolts.
+///
+/// count: The number of bolts.
+///
+/// circle_diameter: The diameter of the circle through the bolt centers.
+///
+/// return: The pattern.
+pub fn bolt_circle(m: i32, count: u64, circle_diameter: f64) -> BoltPattern {
+    let start = Pt2::new(circle_diameter / 2.0, 0.0);
+    let positions = (0..count)
+        .map(|i| start.rotated(-360.0 * i as f64 / count as f64))
+        .collect();
+    BoltPattern::new(m, positions)
+}
+
+/// Create a rectangular grid of bolts centered on the origin.
+///
+/// A 2 x 2 grid gives the usual four bolt flange.
+///
+/// m: The metric size of the bolts.
+///
+/// count_x: The number of bolts in the X direction.
+///
+/// count_y: The number of bolts in the Y direction.
+///
+/// spacing_x: The distance between bolt centers in the X direction.
+///
+/// spacing_y: The distance between bolt centers in the Y direction.
+///
+/// return: The pattern.
+pub fn rectangular_flange(
+    m: i32,
+    count_x: u64,
+    count_y: u64,
+    spacing_x: f64,
+    spacing_y: f64,
+) -> BoltPattern {
+    let mut positions = Vec::with_capacity((count_x * count_y) as usize);
+    for x in 0..count_x {
+        for y in 0..count_y {
+            positions.push(Pt2::new(
+                (x as f64 - (count_x - 1) as f64 / 2.0) * spacing_x,
+                (y as f64 - (count_y - 1) as f64 / 2.0) * spacing_y,
+            ));
+        }
+    }
+    BoltPattern::new(m, positions)
+}
+
+/// The ISO metric thread profiles in ascending order of M size.
+const M_TABLE: [(i32, MetricThreadSpec); 56] = [
+    (
+        2,
+        MetricThreadSpec {
+            pitch: 0.4,
+            external_d_maj: 1.886,
+            internal_d_maj: 2.148,
+            nut_width: 4.0,
+            chamfer_size: 1.45,
+        },
+    ),
+    (
+        3,
+        MetricThreadSpec {
+            pitch: 0.5,
+            external_d_maj: 2.874,
+            internal_d_maj: 3.172,
+            nut_width: 5.5,
+            chamfer_size: 1.6,
+        },
+    ),
+    (
+        4,
+        MetricThreadSpec {
+            pitch: 0.7,
+            external_d_maj: 3.838,
+            internal_d_maj: 4.219,
+            nut_width: 7.0,
+            chamfer_size: 1.8,
+        },
+    ),
+    (
+        5,
+        MetricThreadSpec {
+            pitch: 0.8,
+            external_d_maj: 4.826,
+            internal_d_maj: 5.24,
+            nut_width: 8.0,
+            chamfer_size: 1.9,
+        },
+    ),
+    (
+        6,
+        MetricThreadSpec {
+            pitch: 1.0,
+            external_d_maj: 5.794,
+            internal_d_maj: 6.294,
+            nut_width: 10.0,
+            chamfer_size: 2.1,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        7,
+        MetricThreadSpec {
+            pitch: 1.0,
+            external_d_maj: 6.794,
+            internal_d_maj: 7.294,
+            nut_width: 13.0,
+            chamfer_size: 2.45,
+        },
+    ),
+    (
+        8,
+        MetricThreadSpec {
+            pitch: 1.25,
+            external_d_maj: 7.76,
+            internal_d_maj: 8.34,
+            nut_width: 13.0,
+            chamfer_size: 2.45,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        9,
+        MetricThreadSpec {
+            pitch: 1.25,
+            external_d_maj: 8.76,
+            internal_d_maj: 9.34,
+            nut_width: 16.0,
+            chamfer_size: 2.8,
+        },
+    ),
+    (
+        10,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 9.732,
+            internal_d_maj: 10.396,
+            nut_width: 16.0,
+            chamfer_size: 2.8,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        11,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 10.73,
+            internal_d_maj: 11.387,
+            nut_width: 18.0,
+            chamfer_size: 3.0,
+        },
+    ),
+    (
+        12,
+        MetricThreadSpec {
+            pitch: 1.75,
+            external_d_maj: 11.7,
+            internal_d_maj: 12.453,
+            nut_width: 18.0,
+            chamfer_size: 3.0,
+        },
+    ),
+    (
+        14,
+        MetricThreadSpec {
+            pitch: 2.0,
+            external_d_maj: 13.68,
+            internal_d_maj: 14.501,
+            nut_width: 21.0,
+            chamfer_size: 3.35,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        15,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 14.73,
+            internal_d_maj: 15.407,
+            nut_width: 24.0,
+            chamfer_size: 3.7,
+        },
+    ),
+    (
+        16,
+        MetricThreadSpec {
+            pitch: 2.0,
+            external_d_maj: 15.68,
+            internal_d_maj: 16.501,
+            nut_width: 24.0,
+            chamfer_size: 3.7,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        17,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 16.73,
+            internal_d_maj: 17.407,
+            nut_width: 27.0,
+            chamfer_size: 3.9,
+        },
+    ),
+    (
+        18,
+        MetricThreadSpec {
+            pitch: 2.5,
+            external_d_maj: 17.62,
+            internal_d_maj: 18.585,
+            nut_width: 27.0,
+            chamfer_size: 3.9,
+        },
+    ),
+    (
+        20,
+        MetricThreadSpec {
+            pitch: 2.5,
+            external_d_maj: 19.62,
+            internal_d_maj: 20.585,
+            nut_width: 30.0,
+            chamfer_size: 4.25,
+        },
+    ),
+    (
+        22,
+        MetricThreadSpec {
+            pitch: 3.0,
+            external_d_maj: 21.58,
+            internal_d_maj: 22.677,
+            nut_width: 34.0,
+            chamfer_size: 4.75,
+        },
+    ),
+    (
+        24,
+        MetricThreadSpec {
+            pitch: 3.0,
+            external_d_maj: 23.58,
+            internal_d_maj: 24.698,
+            nut_width: 36.0,
+            chamfer_size: 4.9,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        25,
+        MetricThreadSpec {
+            pitch: 2.0,
+            external_d_maj: 24.68,
+            internal_d_maj: 25.513,
+            nut_width: 41.0,
+            chamfer_size: 5.5,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        26,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 25.73,
+            internal_d_maj: 26.417,
+            nut_width: 41.0,
+            chamfer_size: 5.5,
+        },
+    ),
+    (
+        27,
+        MetricThreadSpec {
+            pitch: 3.0,
+            external_d_maj: 26.58,
+            internal_d_maj: 27.698,
+            nut_width: 41.0,
+            chamfer_size: 5.5,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        28,
+        MetricThreadSpec {
+            pitch: 2.0,
+            external_d_maj: 27.68,
+            internal_d_maj: 28.513,
+            nut_width: 46.0,
+            chamfer_size: 6.0,
+        },
+    ),
+    (
+        30,
+        MetricThreadSpec {
+            pitch: 3.5,
+            external_d_maj: 29.52,
+            internal_d_maj: 30.785,
+            nut_width: 46.0,
+            chamfer_size: 6.0,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        32,
+        MetricThreadSpec {
+            pitch: 2.0,
+            external_d_maj: 31.68,
+            internal_d_maj: 32.513,
+            nut_width: 49.0,
+            chamfer_size: 6.4,
+        },
+    ),
+    (
+        33,
+        MetricThreadSpec {
+            pitch: 3.5,
+            external_d_maj: 32.54,
+            internal_d_maj: 33.785,
+            nut_width: 49.0,
+            chamfer_size: 6.4,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        35,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 34.73,
+            internal_d_maj: 35.416,
+            nut_width: 55.0,
+            chamfer_size: 7.0,
+        },
+    ),
+    (
+        36,
+        MetricThreadSpec {
+            pitch: 4.0,
+            external_d_maj: 35.47,
+            internal_d_maj: 36.877,
+            nut_width: 55.0,
+            chamfer_size: 7.0,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        38,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 37.73,
+            internal_d_maj: 38.417,
+            nut_width: 60.0,
+            chamfer_size: 7.5,
+        },
+    ),
+    (
+        39,
+        MetricThreadSpec {
+            pitch: 4.0,
+            external_d_maj: 38.47,
+            internal_d_maj: 39.877,
+            nut_width: 60.0,
+            chamfer_size: 7.5,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        40,
+        MetricThreadSpec {
+            pitch: 3.0,
+            external_d_maj: 39.58,
+            internal_d_maj: 40.698,
+            nut_width: 65.0,
+            chamfer_size: 8.2,
+        },
+    ),
+    (
+        42,
+        MetricThreadSpec {
+            pitch: 4.5,
+            external_d_maj: 41.44,
+            internal_d_maj: 42.965,
+            nut_width: 65.0,
+            chamfer_size: 8.2,
+        },
+    ),
+    (
+        45,
+        MetricThreadSpec {
+            pitch: 4.5,
+            external_d_maj: 44.44,
+            internal_d_maj: 45.965,
+            nut_width: 70.0,
+            chamfer_size: 8.75,
+        },
+    ),
+    (
+        48,
+        MetricThreadSpec {
+            pitch: 5.0,
+            external_d_maj: 47.4,
+            internal_d_maj: 49.057,
+            nut_width: 75.0,
+            chamfer_size: 9.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        50,
+        MetricThreadSpec {
+            pitch: 4.0,
+            external_d_maj: 49.47,
+            internal_d_maj: 50.892,
+            nut_width: 80.0,
+            chamfer_size: 9.5,
+        },
+    ),
+    (
+        52,
+        MetricThreadSpec {
+            pitch: 5.0,
+            external_d_maj: 51.4,
+            internal_d_maj: 53.037,
+            nut_width: 80.0,
+            chamfer_size: 9.5,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        55,
+        MetricThreadSpec {
+            pitch: 4.0,
+            external_d_maj: 54.47,
+            internal_d_maj: 55.892,
+            nut_width: 85.0,
+            chamfer_size: 10.25,
+        },
+    ),
+    (
+        56,
+        MetricThreadSpec {
+            pitch: 5.5,
+            external_d_maj: 55.37,
+            internal_d_maj: 57.149,
+            nut_width: 85.0,
+            chamfer_size: 10.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        58,
+        MetricThreadSpec {
+            pitch: 4.0,
+            external_d_maj: 57.47,
+            internal_d_maj: 58.892,
+            nut_width: 90.0,
+            chamfer_size: 10.75,
+        },
+    ),
+    (
+        60,
+        MetricThreadSpec {
+            pitch: 5.5,
+            external_d_maj: 59.37,
+            internal_d_maj: 61.149,
+            nut_width: 90.0,
+            chamfer_size: 10.75,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        62,
+        MetricThreadSpec {
+            pitch: 4.0,
+            external_d_maj: 61.47,
+            internal_d_maj: 62.892,
+            nut_width: 95.0,
+            chamfer_size: 11.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        63,
+        MetricThreadSpec {
+            pitch: 1.5,
+            external_d_maj: 62.73,
+            internal_d_maj: 63.429,
+            nut_width: 95.0,
+            chamfer_size: 11.25,
+        },
+    ),
+    (
+        64,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 63.32,
+            internal_d_maj: 65.421,
+            nut_width: 95.0,
+            chamfer_size: 11.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        65,
+        MetricThreadSpec {
+            pitch: 4.0,
+            external_d_maj: 64.47,
+            internal_d_maj: 65.892,
+            nut_width: 100.0,
+            chamfer_size: 11.75,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        68,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 67.32,
+            internal_d_maj: 69.241,
+            nut_width: 100.0,
+            chamfer_size: 11.75,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        70,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 69.32,
+            internal_d_maj: 71.241,
+            nut_width: 100.0,
+            chamfer_size: 11.75,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        72,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 71.32,
+            internal_d_maj: 73.241,
+            nut_width: 110.0,
+            chamfer_size: 13.0,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        75,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 74.32,
+            internal_d_maj: 76.241,
+            nut_width: 110.0,
+            chamfer_size: 13.0,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        76,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 75.32,
+            internal_d_maj: 77.241,
+            nut_width: 110.0,
+            chamfer_size: 13.0,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        78,
+        MetricThreadSpec {
+            pitch: 2.0,
+            external_d_maj: 77.68,
+            internal_d_maj: 78.525,
+            nut_width: 120.0,
+            chamfer_size: 14.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        80,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 79.32,
+            internal_d_maj: 81.241,
+            nut_width: 120.0,
+            chamfer_size: 14.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        82,
+        MetricThreadSpec {
+            pitch: 2.0,
+            external_d_maj: 81.68,
+            internal_d_maj: 82.525,
+            nut_width: 120.0,
+            chamfer_size: 14.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        85,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 84.32,
+            internal_d_maj: 86.241,
+            nut_width: 130.0,
+            chamfer_size: 15.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        90,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 89.32,
+            internal_d_maj: 91.241,
+            nut_width: 130.0,
+            chamfer_size: 15.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        95,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 94.32,
+            internal_d_maj: 96.266,
+            nut_width: 130.0,
+            chamfer_size: 15.25,
+        },
+    ),
+    // nut_width made up for next entry
+    (
+        100,
+        MetricThreadSpec {
+            pitch: 6.0,
+            external_d_maj: 99.32,
+            internal_d_maj: 101.27,
+            nut_width: 140.0,
+            chamfer_size: 16.5,
+        },
+    ),
+];
@@ -0,0 +1,175 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::prelude::*;
+
+/// Finds the OpenSCAD executable.
+///
+/// The OPENSCAD environment variable is checked first, then the PATH and
+/// then the usual install locations.
+///
+/// return: The path of the executable if it was found.
+pub fn find_executable() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("OPENSCAD") {
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    let names = ["openscad", "openscad.exe", "openscad.com"];
+    if let Some(dirs) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&dirs) {
+            for name in names {
+                let path = dir.join(name);
+                if path.is_file() {
+                    return Some(path);
+                }
+            }
+        }
+    }
+    [
+        "/Applications/OpenSCAD.app/Contents/MacOS/OpenSCAD",
+        "C:\\Program Files\\OpenSCAD\\openscad.exe",
+        "C:\\Program Files (x86)\\OpenSCAD\\openscad.exe",
+        "/usr/bin/openscad",
+        "/usr/local/bin/openscad",
+        "/snap/bin/openscad",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .find(|path| path.is_file())
+}
+
+/// Creates a command that runs OpenSCAD.
+///
+/// return: The command or a not found error when OpenSCAD is not installed.
+pub fn command() -> Result<Command, ScadError> {
+    let executable = find_executable().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "the OpenSCAD executable was not found",
+        )
+    })?;
+    Ok(Command::new(executable))
+}
+
+/// Splits the messages OpenSCAD writes to stderr into warnings and errors.
+pub(crate) fn messages(stderr: &str) -> (Vec<String>, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+    for line in stderr.lines().map(str::trim) {
+        if line.starts_with("WARNING:") {
+            warnings.push(line.to_string());
+        } else if line.starts_with("ERROR:") || line.starts_with("Parser error") {
+            errors.push(line.to_string());
+        }
+    }
+    (warnings, errors)
+}
+
+/// Creates a path in the temporary directory that no other call uses.
+fn temp_path(extension: &str) -> PathBuf {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    let count = COUNT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "scad_tree_{}_{}.{}",
+        std::process::id(),
+        count,
+        extension
+    ))
+}
+
+/// Runs OpenSCAD on a tree writing the output to a file.
+///
+/// The format of the output is chosen by OpenSCAD from the extension of out.
+///
+/// scad: The tree to run.
+///
+/// out: The path of the output file.
+///
+/// return: The warnings or an OpenScad error with the error messages.
+pub fn export(scad: &Scad, out: &Path) -> Result<Vec<String>, ScadError> {
+    scad.validate()?;
+    export_code(&scad.to_string(), out)
+}
+
+/// Runs OpenSCAD on code writing the output to a file.
+///
+/// code: The OpenSCAD code of a whole file.
+///
+/// out: The path of the output file.
+///
+/// return: The warnings or an OpenScad error with the error messages.
+pub(crate) fn export_code(code: &str, out: &Path) -> Result<Vec<String>, ScadError> {
+    let input = temp_path("scad");
+    std::fs::write(&input, code)?;
+    let output = command()?.arg("-o").arg(out).arg(&input).output();
+    std::fs::remove_file(&input)?;
+    let output = output?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let (warnings, errors) = messages(&stderr);
+    if !errors.is_empty() {
+        Err(ScadError::OpenScad(errors.join("\n")))
+    } else if !output.status.success() {
+        Err(ScadError::OpenScad(format!(
+            "{}: {}",
+            output.status,
+            stderr.trim()
+        )))
+    } else {
+        Ok(warnings)
+    }
+}
+
+/// Renders a tree to an STL file with OpenSCAD.
+///
+/// The extension of out is replaced with stl.
+///
+/// scad: The tree to render.
+///
+/// out: The path of the STL file.
+///
+/// return: The warnings or an OpenScad error with the error messages.
+pub fn compile_to_stl(scad: &Scad, out: &Path) -> Result<Vec<String>, ScadError> {
+    export(scad, &out.with_extension("stl"))
+}
+
+/// Checks that OpenSCAD can parse a tree without rendering it.
+///
+/// scad: The tree to check.
+///
+/// return: The warnings or an OpenScad error with the error messages.
+pub fn check_syntax(scad: &Scad) -> Result<Vec<String>, ScadError> {
+    let out = temp_path("ast");
+    let result = export(scad, &out);
+    if out.exists() {
+        std::fs::remove_file(&out)?;
+    }
+    result
+}
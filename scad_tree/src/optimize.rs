@@ -0,0 +1,198 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Simplifies a Scad tree without changing the geometry it makes.
+
+use crate::{
+    eval::{transform, transform_matrix},
+    prelude::*,
+};
+
+impl Scad {
+    /// Simplifies the tree to shrink the output and speed up OpenSCAD.
+    ///
+    /// Nested unions are flattened, a translate, rotate or scale of a single
+    /// translate, rotate or scale of the same kind is merged into one, empty
+    /// groups are removed and identity transforms are replaced by their children.
+    ///
+    /// return: Self for chaining.
+    pub fn optimize(&mut self) -> &mut Self {
+        let scad = std::mem::replace(
+            self,
+            Scad {
+                op: ScadOp::Union,
+                children: Vec::new(),
+            },
+        );
+        *self = optimized(scad);
+        self
+    }
+}
+
+/// Ops whose children are implicitly unioned so a union child can be spliced in.
+fn is_implicit_union(op: &ScadOp) -> bool {
+    matches!(
+        op,
+        ScadOp::Union
+            | ScadOp::Translate { .. }
+            | ScadOp::Rotate { .. }
+            | ScadOp::Scale { .. }
+            | ScadOp::Mirror { .. }
+            | ScadOp::Color { .. }
+            | ScadOp::Render { .. }
+            | ScadOp::Hull
+    )
+}
+
+/// Ops that make nothing without children.
+fn is_group(op: &ScadOp) -> bool {
+    is_implicit_union(op)
+        || matches!(
+            op,
+            ScadOp::Difference
+                | ScadOp::Intersection
+                | ScadOp::Minkowski { .. }
+                | ScadOp::LinearExtrude { .. }
+                | ScadOp::RotateExtrude { .. }
+                | ScadOp::Projection { .. }
+                | ScadOp::Offset { .. }
+                | ScadOp::Resize { .. }
+                | ScadOp::Modifier(_)
+                | ScadOp::Tag(_)
+                | ScadOp::BomItem { .. }
+        )
+}
+
+/// Transforms that leave their children where they are.
+fn is_identity(op: &ScadOp) -> bool {
+    match op {
+        ScadOp::Translate { v } => *v == Pt3::new(0.0, 0.0, 0.0),
+        ScadOp::Rotate { a: Some(a), .. } => *a == 0.0,
+        ScadOp::Rotate { a: None, v, .. } => *v == Pt3::new(0.0, 0.0, 0.0),
+        ScadOp::Scale { v } => *v == Pt3::new(1.0, 1.0, 1.0),
+        _ => false,
+    }
+}
+
+/// The angle in degrees of a direction.
+fn datan2(y: f64, x: f64) -> f64 {
+    y.atan2(x).to_degrees()
+}
+
+/// Rounds tiny angles left by the decomposition to zero.
+fn snap(degrees: f64) -> f64 {
+    if degrees.abs() < 1.0e-9 {
+        0.0
+    } else {
+        degrees
+    }
+}
+
+/// Merges a transform with the same kind of transform as its only child.
+fn merge(parent: &ScadOp, child: &ScadOp) -> Option<ScadOp> {
+    match (parent, child) {
+        (ScadOp::Translate { v: a }, ScadOp::Translate { v: b }) => {
+            Some(ScadOp::Translate { v: *a + *b })
+        }
+        (ScadOp::Scale { v: a }, ScadOp::Scale { v: b }) => Some(ScadOp::Scale {
+            v: Pt3::new(a.x * b.x, a.y * b.y, a.z * b.z),
+        }),
+        (
+            ScadOp::Rotate {
+                a: Some(a),
+                a_is_scalar: true,
+                ..
+            },
+            ScadOp::Rotate {
+                a: Some(b),
+                a_is_scalar: true,
+                ..
+            },
+        ) => Some(ScadOp::Rotate {
+            a: Some(a + b),
+            a_is_scalar: true,
+            v: Pt3::new(0.0, 0.0, 0.0),
+        }),
+        (ScadOp::Rotate { .. }, ScadOp::Rotate { .. }) => {
+            let m = transform_matrix(parent)? * transform_matrix(child)?;
+            // columns of the rotation are the images of the axes
+            let x = transform(&m, Pt3::new(1.0, 0.0, 0.0));
+            let y = transform(&m, Pt3::new(0.0, 1.0, 0.0));
+            let z = transform(&m, Pt3::new(0.0, 0.0, 1.0));
+            // decompose m = rot_z(c) * rot_y(b) * rot_x(a) like OpenSCAD's rotate([a, b, c])
+            let v = if x.z.abs() < 1.0 - 1.0e-12 {
+                Pt3::new(
+                    datan2(y.z, z.z),
+                    datan2(-x.z, (x.x * x.x + x.y * x.y).sqrt()),
+                    datan2(x.y, x.x),
+                )
+            } else {
+                Pt3::new(0.0, -90.0 * x.z.signum(), datan2(-y.x, y.y))
+            };
+            Some(ScadOp::Rotate {
+                a: None,
+                a_is_scalar: false,
+                v: Pt3::new(snap(v.x), snap(v.y), snap(v.z)),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the simplified tree.
+fn optimized(scad: Scad) -> Scad {
+    let Scad { op, children } = scad;
+    let implicit = is_implicit_union(&op);
+    let mut kept = Vec::with_capacity(children.len());
+    for (i, child) in children.into_iter().enumerate() {
+        let child = optimized(child);
+        let empty = is_group(&child.op) && child.children.is_empty();
+        // an empty first child of a difference is the whole difference
+        let removable = implicit || (matches!(op, ScadOp::Difference) && i > 0);
+        if empty && removable {
+            continue;
+        }
+        if implicit && matches!(child.op, ScadOp::Union) {
+            kept.extend(child.children);
+        } else {
+            kept.push(child);
+        }
+    }
+    let mut scad = Scad { op, children: kept };
+    if scad.children.len() == 1 {
+        if let Some(op) = merge(&scad.op, &scad.children[0].op) {
+            let child = scad.children.pop().unwrap();
+            scad = Scad {
+                op,
+                children: child.children,
+            };
+        }
+    }
+    if is_identity(&scad.op) {
+        scad.op = ScadOp::Union;
+    }
+    if matches!(scad.op, ScadOp::Union) && scad.children.len() == 1 {
+        return scad.children.pop().unwrap();
+    }
+    scad
+}
@@ -0,0 +1,209 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Finds the way to lay a part on the build plate that needs the least support.
+
+use crate::{dcos, prelude::*, Mt4};
+
+/// The number of the largest face directions tried as the side facing the bed.
+const FACE_CANDIDATES: usize = 24;
+
+/// Face directions closer than this in degrees are treated as one.
+const SAME_DIRECTION: f64 = 1.0;
+
+/// A rotation that lays a part on the build plate with its support and base areas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrintOrientation {
+    /// The axis of the rotation.
+    pub axis: Pt3,
+    /// The angle of the rotation in degrees.
+    pub degrees: f64,
+    /// The area of the faces that overhang once rotated.
+    pub support_area: f64,
+    /// The area of the faces resting on the build plate once rotated.
+    pub base_area: f64,
+}
+
+impl PrintOrientation {
+    /// The rotation that turns down into -Z.
+    ///
+    /// down: The direction of the part that should face the build plate.
+    ///
+    /// return: The orientation with no areas measured.
+    pub fn facing_down(down: Pt3) -> Self {
+        let down = down.normalized();
+        let target = Pt3::new(0.0, 0.0, -1.0);
+        let axis = down.cross(target);
+        let (axis, degrees) = if axis.len() > 1.0e-9 {
+            (
+                axis.normalized(),
+                down.dot(target).clamp(-1.0, 1.0).acos().to_degrees(),
+            )
+        } else if down.z < 0.0 {
+            (Pt3::new(0.0, 0.0, 1.0), 0.0)
+        } else {
+            (Pt3::new(1.0, 0.0, 0.0), 180.0)
+        };
+        Self {
+            axis,
+            degrees,
+            support_area: 0.0,
+            base_area: 0.0,
+        }
+    }
+
+    /// The rotation matrix, the same as OpenSCAD rotate(a=degrees, v=axis).
+    pub fn matrix(&self) -> Mt4 {
+        Mt4::rot_vec(self.axis.x, self.axis.y, self.axis.z, self.degrees)
+    }
+}
+
+impl Polyhedron {
+    /// The area of the overhanging faces and of the faces on the bed when printed along +Z.
+    fn support_and_base(&self, max_angle: f64) -> (f64, f64) {
+        let areas = self.face_areas();
+        let support = self
+            .overhangs(Pt3::new(0.0, 0.0, 1.0), max_angle)
+            .into_iter()
+            .map(|i| areas[i].len())
+            .sum();
+        let bed = self.bounds().0.z;
+        let base = self
+            .faces
+            .iter()
+            .zip(areas.iter())
+            .filter(|(face, _)| {
+                face.iter()
+                    .all(|&p| self.points[p as usize].z - bed < 1.0e-9)
+            })
+            .map(|(_, area)| area.len())
+            .sum();
+        (support, base)
+    }
+
+    /// Searches for the rotation that needs the least support.
+    ///
+    /// The sides tried against the build plate are the six axis directions and
+    /// the directions of the largest faces. Each is scored by the support area
+    /// minus the base area, so with equal support the larger flat base wins.
+    /// The current orientation wins ties.
+    ///
+    /// max_angle: The largest printable overhang in degrees from vertical, e.g. 45.
+    ///
+    /// return: The best orientation found.
+    pub fn print_orientation(&self, max_angle: f64) -> PrintOrientation {
+        let mut directions = vec![
+            Pt3::new(0.0, 0.0, -1.0),
+            Pt3::new(0.0, 0.0, 1.0),
+            Pt3::new(1.0, 0.0, 0.0),
+            Pt3::new(-1.0, 0.0, 0.0),
+            Pt3::new(0.0, 1.0, 0.0),
+            Pt3::new(0.0, -1.0, 0.0),
+        ];
+        let mut faces: Vec<(Pt3, f64)> = Vec::new();
+        for area in self.face_areas() {
+            let len = area.len();
+            if len == 0.0 {
+                continue;
+            }
+            let normal = area / len;
+            match faces
+                .iter_mut()
+                .find(|(n, _)| n.dot(normal) > dcos(SAME_DIRECTION))
+            {
+                Some((_, total)) => *total += len,
+                None => faces.push((normal, len)),
+            }
+        }
+        faces.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (normal, _) in faces.into_iter().take(FACE_CANDIDATES) {
+            if directions
+                .iter()
+                .all(|d| d.dot(normal) <= dcos(SAME_DIRECTION))
+            {
+                directions.push(normal);
+            }
+        }
+
+        let mut best: Option<(f64, PrintOrientation)> = None;
+        for down in directions {
+            let mut orientation = PrintOrientation::facing_down(down);
+            let mut rotated = self.clone();
+            rotated.apply_matrix(&orientation.matrix());
+            let (support, base) = rotated.support_and_base(max_angle);
+            orientation.support_area = support;
+            orientation.base_area = base;
+            let score = support - base;
+            if best.map_or(true, |(best_score, _)| score < best_score - 1.0e-9) {
+                best = Some((score, orientation));
+            }
+        }
+        best.map(|(_, orientation)| orientation)
+            .unwrap_or_else(|| PrintOrientation::facing_down(Pt3::new(0.0, 0.0, -1.0)))
+    }
+
+    /// Rotates the polyhedron to the orientation that needs the least support
+    /// and moves it to rest on the XY plane.
+    ///
+    /// max_angle: The largest printable overhang in degrees from vertical, e.g. 45.
+    ///
+    /// return: The orientation that was applied.
+    pub fn orient_for_printing(&mut self, max_angle: f64) -> PrintOrientation {
+        let orientation = self.print_orientation(max_angle);
+        self.apply_matrix(&orientation.matrix());
+        let bed = self.bounds().0.z;
+        self.translate(Pt3::new(0.0, 0.0, -bed));
+        orientation
+    }
+}
+
+impl Scad {
+    /// Rotates the Scad to the orientation that needs the least support and
+    /// moves it to rest on the XY plane.
+    ///
+    /// quality: The number of segments in a circle used to evaluate the tree.
+    ///
+    /// max_angle: The largest printable overhang in degrees from vertical, e.g. 45.
+    ///
+    /// return: The rotated Scad and its orientation or an error if the tree
+    /// could not be evaluated.
+    pub fn orient_for_printing(
+        self,
+        quality: u64,
+        max_angle: f64,
+    ) -> Result<(Scad, PrintOrientation), ScadError> {
+        let mut mesh = self.to_polyhedron(quality)?;
+        let orientation = mesh.print_orientation(max_angle);
+        mesh.apply_matrix(&orientation.matrix());
+        let bed = mesh.bounds().0.z;
+        let rotated = Scad {
+            op: ScadOp::Rotate {
+                a: Some(orientation.degrees),
+                a_is_scalar: false,
+                v: orientation.axis,
+            },
+            children: vec![self],
+        };
+        Ok((translate!([0.0, 0.0, -bed], rotated;), orientation))
+    }
+}
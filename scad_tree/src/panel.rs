@@ -0,0 +1,215 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Cutouts for connectors, switches and displays mounted through a panel.
+//!
+//! The sizes are for common parts, check them against the datasheet of the
+//! part you have before cutting a panel.
+
+use crate::{dtan, prelude::*};
+
+/// A part that mounts through a panel.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PanelCutout {
+    /// A USB-C receptacle, 8.94x3.26 with round ends.
+    UsbC,
+    /// A full size HDMI receptacle, 14x4.55 with the lower corners cut.
+    Hdmi,
+    /// A panel mount DC barrel jack in an 8mm hole.
+    BarrelJack,
+    /// A DE-9 D-sub connector with its two mounting holes 25mm apart.
+    Db9,
+    /// A KCD1 style snap-in rocker switch, 19x13.
+    RockerSwitch,
+    /// The window of a 0.96 inch SSD1306 OLED module and its four M2 mounting holes.
+    Oled096,
+    /// The window of a 1.3 inch SH1106 OLED module and its four M3 mounting holes.
+    Oled130,
+    /// A round hole.
+    Round { diameter: f64 },
+    /// A rectangle with rounded corners.
+    Rect {
+        width: f64,
+        height: f64,
+        radius: f64,
+    },
+}
+
+/// A rounded rectangle centered on the origin, a rectangle when radius is 0.
+fn rounded(width: f64, height: f64, radius: f64, segments: u64) -> Pt2s {
+    let radius = radius.min(width / 2.0).min(height / 2.0);
+    if radius <= 1.0e-9 {
+        dim2::rect(width, height, true)
+    } else if radius >= height / 2.0 - 1.0e-9 {
+        dim2::slot(width - height, height / 2.0, segments, true)
+    } else {
+        dim2::rounded_rect(width, height, radius, (segments / 4).max(1), true)
+    }
+}
+
+/// A hole centered on a point.
+fn hole(diameter: f64, center: Pt2, segments: u64) -> Pt2s {
+    let mut hole = dim2::circle(diameter / 2.0, segments);
+    hole.translate(center);
+    hole
+}
+
+/// Four holes on the corners of a rectangle centered on center.
+fn corner_holes(diameter: f64, width: f64, height: f64, center: Pt2, segments: u64) -> Vec<Pt2s> {
+    [(-1.0, -1.0), (-1.0, 1.0), (1.0, 1.0), (1.0, -1.0)]
+        .iter()
+        .map(|(x, y)| {
+            hole(
+                diameter,
+                center + Pt2::new(x * width / 2.0, y * height / 2.0),
+                segments,
+            )
+        })
+        .collect()
+}
+
+impl PanelCutout {
+    /// The 2D profiles of the cutout centered on the opening.
+    ///
+    /// tolerance: The clearance added to each side of the opening.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The clockwise profiles of the opening and any mounting holes.
+    pub fn profiles(&self, tolerance: f64, segments: u64) -> Vec<Pt2s> {
+        let t = tolerance * 2.0;
+        match *self {
+            PanelCutout::UsbC => vec![rounded(8.94 + t, 3.26 + t, 1.63 + tolerance, segments)],
+            PanelCutout::Hdmi => {
+                let (w, h, cut) = (14.0 + t, 4.55 + t, 1.8);
+                vec![Pt2s::from_pt2s(vec![
+                    Pt2::new(-w / 2.0, -h / 2.0 + cut),
+                    Pt2::new(-w / 2.0, h / 2.0),
+                    Pt2::new(w / 2.0, h / 2.0),
+                    Pt2::new(w / 2.0, -h / 2.0 + cut),
+                    Pt2::new(w / 2.0 - cut, -h / 2.0),
+                    Pt2::new(-w / 2.0 + cut, -h / 2.0),
+                ])]
+            }
+            PanelCutout::BarrelJack => vec![hole(8.0 + t, Pt2::new(0.0, 0.0), segments)],
+            PanelCutout::Db9 => {
+                // the sides of a D-sub shell lean in 10 degrees
+                let (top, h) = (19.2 + t, 10.0 + t);
+                let bottom = top - 2.0 * h * dtan(10.0);
+                let d = Pt2s::from_pt2s(vec![
+                    Pt2::new(-bottom / 2.0, -h / 2.0),
+                    Pt2::new(-top / 2.0, h / 2.0),
+                    Pt2::new(top / 2.0, h / 2.0),
+                    Pt2::new(bottom / 2.0, -h / 2.0),
+                ]);
+                vec![
+                    dim2::fillet_corners(&d, 1.0, (segments / 4).max(1)),
+                    hole(3.1 + t, Pt2::new(-12.5, 0.0), segments),
+                    hole(3.1 + t, Pt2::new(12.5, 0.0), segments),
+                ]
+            }
+            PanelCutout::RockerSwitch => vec![rounded(19.0 + t, 13.0 + t, 0.0, segments)],
+            PanelCutout::Oled096 => {
+                let mut profiles = vec![rounded(23.0 + t, 12.0 + t, 0.0, segments)];
+                profiles.extend(corner_holes(
+                    2.2 + t,
+                    23.0,
+                    23.5,
+                    Pt2::new(0.0, -2.0),
+                    segments,
+                ));
+                profiles
+            }
+            PanelCutout::Oled130 => {
+                let mut profiles = vec![rounded(30.0 + t, 15.0 + t, 0.0, segments)];
+                profiles.extend(corner_holes(
+                    3.2 + t,
+                    30.4,
+                    28.0,
+                    Pt2::new(0.0, -2.0),
+                    segments,
+                ));
+                profiles
+            }
+            PanelCutout::Round { diameter } => {
+                vec![hole(diameter + t, Pt2::new(0.0, 0.0), segments)]
+            }
+            PanelCutout::Rect {
+                width,
+                height,
+                radius,
+            } => vec![rounded(width + t, height + t, radius + tolerance, segments)],
+        }
+    }
+
+    /// Create the negative of the cutout.
+    ///
+    /// The entrance is on the XY plane and the cutout extends down through the
+    /// panel into -Z. It extends 1 unit past both sides of the panel for a
+    /// cleaner preview.
+    ///
+    /// tolerance: The clearance added to each side of the opening.
+    ///
+    /// thickness: The thickness of the panel.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The cutout.
+    pub fn negative(&self, tolerance: f64, thickness: f64, segments: u64) -> Scad {
+        let (points, paths) = dim2::merge_outlines(&self.profiles(tolerance, segments));
+        translate!([0.0, 0.0, -thickness - 1.0],
+            linear_extrude!(thickness + 2.0, polygon!(points, paths););
+        )
+    }
+}
+
+/// Create the cutouts of a panel from its parts.
+///
+/// parts: The parts and the centers of their openings on the panel.
+///
+/// tolerance: The clearance added to each side of the openings.
+///
+/// thickness: The thickness of the panel.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The cutouts with their entrances on the XY plane, subtract them from
+/// the panel.
+pub fn cutouts(
+    parts: &[(PanelCutout, Pt2)],
+    tolerance: f64,
+    thickness: f64,
+    segments: u64,
+) -> Scad {
+    let mut profiles = Vec::new();
+    for (part, center) in parts.iter() {
+        for mut profile in part.profiles(tolerance, segments) {
+            profile.translate(*center);
+            profiles.push(profile);
+        }
+    }
+    let (points, paths) = dim2::merge_outlines(&profiles);
+    translate!([0.0, 0.0, -thickness - 1.0],
+        linear_extrude!(thickness + 2.0, polygon!(points, paths););
+    )
+}
@@ -0,0 +1,91 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Spreads the point generation of large meshes over threads with the parallel feature.
+
+use crate::Pt3s;
+
+/// Rings fewer than this are not worth starting threads for.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_RINGS: usize = 64;
+
+/// Generates the points of a mesh one ring at a time.
+///
+/// Without the parallel feature this is a loop over the rings pushing into
+/// one buffer sized for all of them. With the feature the rings are split into
+/// a chunk per core and generated on scoped threads. The points are the same
+/// either way.
+///
+/// n: The number of rings.
+///
+/// ring_len: The number of points in each ring, used to size the buffers.
+///
+/// ring: Pushes the points of a ring from its index.
+///
+/// return: The points of all the rings in order.
+pub(crate) fn rings<F>(n: usize, ring_len: usize, ring: F) -> Pt3s
+where
+    F: Fn(usize, &mut Pt3s) + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        let threads = std::thread::available_parallelism().map_or(1, |t| t.get());
+        if threads > 1 && n >= MIN_PARALLEL_RINGS {
+            let chunk = n.div_ceil(threads);
+            let ring = &ring;
+            let chunks: Vec<Pt3s> = std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..n)
+                    .step_by(chunk)
+                    .map(|start| {
+                        scope.spawn(move || {
+                            let end = (start + chunk).min(n);
+                            let mut points = Pt3s::with_capacity((end - start) * ring_len);
+                            for i in start..end {
+                                ring(i, &mut points);
+                            }
+                            points
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+                    })
+                    .collect()
+            });
+            let mut points = Pt3s::with_capacity(n * ring_len);
+            for chunk in chunks {
+                points.extend(chunk.iter());
+            }
+            return points;
+        }
+    }
+    let mut points = Pt3s::with_capacity(n * ring_len);
+    for i in 0..n {
+        ring(i, &mut points);
+    }
+    points
+}
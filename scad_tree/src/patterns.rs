@@ -0,0 +1,602 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::{approx_eq, clip::signed_area, prelude::*};
+
+/// Moves the cells so the pattern is centered in the area.
+fn center_cells(cells: &mut [Pt2s], width: f64, height: f64) {
+    let mut min = Pt2::new(f64::MAX, f64::MAX);
+    let mut max = Pt2::new(f64::MIN, f64::MIN);
+    for p in cells.iter().flat_map(|cell| cell.iter()) {
+        min = Pt2::new(min.x.min(p.x), min.y.min(p.y));
+        max = Pt2::new(max.x.max(p.x), max.y.max(p.y));
+    }
+    let shift = Pt2::new(width - max.x - min.x, height - max.y - min.y) / 2.0;
+    for cell in cells.iter_mut() {
+        cell.translate(shift);
+    }
+}
+
+fn cells_to_scad(cells: &[Pt2s]) -> Scad {
+    let (points, paths) = dim2::merge_outlines(cells);
+    polygon!(points, paths)
+}
+
+/// Creates the hexagonal cells of a honeycomb that fit in an area.
+///
+/// Only whole cells are made and the pattern is centered in the area
+/// from the origin to (width, height). The hexagons have flat tops.
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// cell_size: The distance across the flats of a cell.
+///
+/// wall: The thickness of the walls between cells.
+///
+/// return: The clockwise profiles of the cells.
+pub fn honeycomb_profiles(width: f64, height: f64, cell_size: f64, wall: f64) -> Vec<Pt2s> {
+    assert!(cell_size > 0.0 && wall >= 0.0);
+    let radius = cell_size / 3.0f64.sqrt();
+    let pitch = cell_size + wall;
+    let column_pitch = pitch * 3.0f64.sqrt() / 2.0;
+    let mut cells = Vec::new();
+    let mut column = 0;
+    loop {
+        let x = radius + column as f64 * column_pitch;
+        if x + radius > width {
+            break;
+        }
+        let mut y = cell_size / 2.0 + if column % 2 == 1 { pitch / 2.0 } else { 0.0 };
+        while y + cell_size / 2.0 <= height {
+            let mut cell = dim2::circle(radius, 6);
+            cell.translate(Pt2::new(x, y));
+            cells.push(cell);
+            y += pitch;
+        }
+        column += 1;
+    }
+    center_cells(&mut cells, width, height);
+    cells
+}
+
+/// Creates a honeycomb of holes that fit in an area.
+///
+/// Extrude the result and subtract it from a panel to make a vent or a light
+/// weight panel.
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// cell_size: The distance across the flats of a cell.
+///
+/// wall: The thickness of the walls between cells.
+///
+/// return: A polygon of the cells.
+pub fn honeycomb(width: f64, height: f64, cell_size: f64, wall: f64) -> Scad {
+    cells_to_scad(&honeycomb_profiles(width, height, cell_size, wall))
+}
+
+/// Creates the square cells of a grid that fit in an area.
+///
+/// Only whole cells are made and the pattern is centered in the area
+/// from the origin to (width, height).
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// cell_size: The width of a cell.
+///
+/// wall: The thickness of the walls between cells.
+///
+/// return: The clockwise profiles of the cells.
+pub fn grid_profiles(width: f64, height: f64, cell_size: f64, wall: f64) -> Vec<Pt2s> {
+    assert!(cell_size > 0.0 && wall >= 0.0);
+    let pitch = cell_size + wall;
+    let columns = ((width + wall) / pitch + 1e-9).floor() as usize;
+    let rows = ((height + wall) / pitch + 1e-9).floor() as usize;
+    let mut cells = Vec::with_capacity(columns * rows);
+    for column in 0..columns {
+        for row in 0..rows {
+            let mut cell = dim2::rect(cell_size, cell_size, false);
+            cell.translate(Pt2::new(column as f64 * pitch, row as f64 * pitch));
+            cells.push(cell);
+        }
+    }
+    center_cells(&mut cells, width, height);
+    cells
+}
+
+/// Creates a grid of square holes that fit in an area.
+///
+/// Extrude the result and subtract it from a panel to make a vent or a light
+/// weight panel.
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// cell_size: The width of a cell.
+///
+/// wall: The thickness of the walls between cells.
+///
+/// return: A polygon of the cells.
+pub fn grid(width: f64, height: f64, cell_size: f64, wall: f64) -> Scad {
+    cells_to_scad(&grid_profiles(width, height, cell_size, wall))
+}
+
+/// Creates the slots of a slotted vent that fit in an area.
+///
+/// Only whole slots are made and the pattern is centered in the area
+/// from the origin to (width, height). The slots run along the X axis.
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// slot_length: The overall length of a slot, the width of the area for a single column.
+///
+/// slot_width: The width of a slot.
+///
+/// wall: The thickness of the walls between slots.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The clockwise profiles of the slots.
+pub fn slot_profiles(
+    width: f64,
+    height: f64,
+    slot_length: f64,
+    slot_width: f64,
+    wall: f64,
+    segments: u64,
+) -> Vec<Pt2s> {
+    assert!(slot_length >= slot_width && slot_width > 0.0 && wall >= 0.0);
+    let columns = ((width + wall) / (slot_length + wall) + 1e-9).floor() as usize;
+    let rows = ((height + wall) / (slot_width + wall) + 1e-9).floor() as usize;
+    let radius = slot_width / 2.0;
+    let mut cells = Vec::with_capacity(columns * rows);
+    for column in 0..columns {
+        for row in 0..rows {
+            let mut cell = dim2::slot(slot_length - slot_width, radius, segments, false);
+            cell.translate(Pt2::new(
+                column as f64 * (slot_length + wall) + radius,
+                row as f64 * (slot_width + wall) + radius,
+            ));
+            cells.push(cell);
+        }
+    }
+    center_cells(&mut cells, width, height);
+    cells
+}
+
+/// Creates a slotted vent that fits in an area.
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// slot_length: The overall length of a slot, the width of the area for a single column.
+///
+/// slot_width: The width of a slot.
+///
+/// wall: The thickness of the walls between slots.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: A polygon of the slots.
+pub fn slots(
+    width: f64,
+    height: f64,
+    slot_length: f64,
+    slot_width: f64,
+    wall: f64,
+    segments: u64,
+) -> Scad {
+    cells_to_scad(&slot_profiles(
+        width,
+        height,
+        slot_length,
+        slot_width,
+        wall,
+        segments,
+    ))
+}
+
+/// Creates the round holes of a grill that fit in an area.
+///
+/// Only whole holes are made and the pattern is centered in the area
+/// from the origin to (width, height).
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// diameter: The diameter of a hole.
+///
+/// wall: The narrowest wall between holes.
+///
+/// staggered: Offset every other row by half a hole so the holes pack closer.
+///
+/// segments: The number of segments in a hole.
+///
+/// return: The clockwise profiles of the holes.
+pub fn circle_grid_profiles(
+    width: f64,
+    height: f64,
+    diameter: f64,
+    wall: f64,
+    staggered: bool,
+    segments: u64,
+) -> Vec<Pt2s> {
+    assert!(diameter > 0.0 && wall >= 0.0);
+    let radius = diameter / 2.0;
+    let pitch = diameter + wall;
+    let row_pitch = if staggered {
+        pitch * 3.0f64.sqrt() / 2.0
+    } else {
+        pitch
+    };
+    let mut cells = Vec::new();
+    let mut row = 0;
+    loop {
+        let y = radius + row as f64 * row_pitch;
+        if y + radius > height + 1e-9 {
+            break;
+        }
+        let mut x = radius
+            + if staggered && row % 2 == 1 {
+                pitch / 2.0
+            } else {
+                0.0
+            };
+        while x + radius <= width + 1e-9 {
+            let mut cell = dim2::circle(radius, segments);
+            cell.translate(Pt2::new(x, y));
+            cells.push(cell);
+            x += pitch;
+        }
+        row += 1;
+    }
+    center_cells(&mut cells, width, height);
+    cells
+}
+
+/// Creates a grill of round holes that fits in an area.
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// diameter: The diameter of a hole.
+///
+/// wall: The narrowest wall between holes.
+///
+/// staggered: Offset every other row by half a hole so the holes pack closer.
+///
+/// segments: The number of segments in a hole.
+///
+/// return: A polygon of the holes.
+pub fn circle_grid(
+    width: f64,
+    height: f64,
+    diameter: f64,
+    wall: f64,
+    staggered: bool,
+    segments: u64,
+) -> Scad {
+    cells_to_scad(&circle_grid_profiles(
+        width, height, diameter, wall, staggered, segments,
+    ))
+}
+
+/// The holes of a vent.
+#[derive(Clone, Copy, PartialEq)]
+pub enum VentPattern {
+    /// Slots with round ends along the width of the face.
+    Slots { length: f64, width: f64 },
+    /// Round holes, staggered rows pack closer.
+    Circles { diameter: f64, staggered: bool },
+    /// A honeycomb of hexagons with the size across the flats.
+    Hexagons { size: f64 },
+}
+
+/// Creates the holes of a vent that fit in an area.
+///
+/// pattern: The holes.
+///
+/// width: The width of the area.
+///
+/// height: The height of the area.
+///
+/// wall: The thickness of the walls between holes.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The clockwise profiles of the holes.
+pub fn vent_profiles(
+    pattern: VentPattern,
+    width: f64,
+    height: f64,
+    wall: f64,
+    segments: u64,
+) -> Vec<Pt2s> {
+    match pattern {
+        VentPattern::Slots {
+            length,
+            width: slot_width,
+        } => slot_profiles(width, height, length, slot_width, wall, segments),
+        VentPattern::Circles {
+            diameter,
+            staggered,
+        } => circle_grid_profiles(width, height, diameter, wall, staggered, segments),
+        VentPattern::Hexagons { size } => honeycomb_profiles(width, height, size, wall),
+    }
+}
+
+/// Creates the cutout of a vent through a face of a box shaped enclosure.
+///
+/// The holes are centered on the face and kept inside the margin. Subtract the
+/// result from the enclosure.
+///
+/// pattern: The holes.
+///
+/// face: The face of the enclosure.
+///
+/// min: The minimum corner of the bounding box of the enclosure.
+///
+/// max: The maximum corner of the bounding box of the enclosure.
+///
+/// wall: The thickness of the walls between holes.
+///
+/// margin: The minimum distance from the holes to the edges of the face.
+///
+/// thickness: The thickness of the enclosure wall the holes go through.
+///
+/// segments: The number of segments in a circle.
+///
+/// return: The holes extruded through the wall.
+#[allow(clippy::too_many_arguments)]
+pub fn vent(
+    pattern: VentPattern,
+    face: BoxFace,
+    min: Pt3,
+    max: Pt3,
+    wall: f64,
+    margin: f64,
+    thickness: f64,
+    segments: u64,
+) -> Scad {
+    let (width, height, position, [rx, ry, rz]) = face.frame(min, max);
+    let (width, height) = (width - margin * 2.0, height - margin * 2.0);
+    assert!(width > 0.0 && height > 0.0);
+    let mut cells = vent_profiles(pattern, width, height, wall, segments);
+    for cell in cells.iter_mut() {
+        cell.translate(Pt2::new(-width / 2.0, -height / 2.0));
+    }
+    translate!([position.x, position.y, position.z],
+        rotate!([rx, ry, rz],
+            translate!([0.0, 0.0, -thickness - 1.0],
+                linear_extrude!(thickness + 2.0, cells_to_scad(&cells););
+            );
+        );
+    )
+}
+
+/// The shape of the tiles of a tiling.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TileShape {
+    /// Squares in rows and columns.
+    Square,
+    /// Hexagons with flat tops, every other column shifted up half a tile.
+    Hexagon,
+    /// Triangles pointing up and down in turn along each row.
+    Triangle,
+}
+
+/// A tile of a tiling before it is fitted to the region.
+#[derive(Clone)]
+pub struct Tile {
+    /// The column of the tile in the tiling.
+    pub column: i64,
+    /// The row of the tile in the tiling.
+    pub row: i64,
+    /// The center of the tile.
+    pub center: Pt2,
+    /// The clockwise profile of the tile.
+    pub points: Pt2s,
+}
+
+/// Makes a tile by shrinking a clockwise polygon toward center by half the wall.
+fn tile(column: i64, row: i64, center: Pt2, corners: Vec<Pt2>, inradius: f64, wall: f64) -> Tile {
+    let scale = (inradius - wall / 2.0) / inradius;
+    let mut points = Pt2s::from_pt2s(
+        corners
+            .into_iter()
+            .map(|p| center + (p - center) * scale)
+            .collect(),
+    );
+    if signed_area(&points) > 0.0 {
+        points.reverse();
+    }
+    Tile {
+        column,
+        row,
+        center,
+        points,
+    }
+}
+
+/// Fills a region with tiles.
+///
+/// The tiling starts at the origin and covers the box around the region. Each
+/// tile is passed to cell which returns the profile to use, e.g. the tile
+/// scaled by its distance from the center for a speaker grille, or None to
+/// leave it out. The profiles are then fitted to the region.
+///
+/// region: The clockwise outlines and counter clockwise holes to fill.
+///
+/// shape: The shape of the tiles.
+///
+/// pitch: The distance between the centers of neighboring tiles, the side of a
+/// triangle.
+///
+/// wall: The gap between neighboring tiles.
+///
+/// clip: Cut the tiles on the edges of the region to fit, otherwise only tiles
+/// that are completely inside are kept.
+///
+/// cell: Returns the profile for a tile.
+///
+/// return: The clockwise profiles and counter clockwise holes of the tiles.
+pub fn tile_profiles<F: FnMut(&Tile) -> Option<Pt2s>>(
+    region: &[Pt2s],
+    shape: TileShape,
+    pitch: f64,
+    wall: f64,
+    clip: bool,
+    mut cell: F,
+) -> Vec<Pt2s> {
+    assert!(pitch > 0.0 && wall >= 0.0);
+    let mut points = region.iter().flat_map(|r| r.iter());
+    let Some(first) = points.next() else {
+        return Vec::new();
+    };
+    let (min, max) = points.fold((*first, *first), |(min, max), p| {
+        (
+            Pt2::new(min.x.min(p.x), min.y.min(p.y)),
+            Pt2::new(max.x.max(p.x), max.y.max(p.y)),
+        )
+    });
+    let sqrt3 = 3.0f64.sqrt();
+    let (column_pitch, row_pitch) = match shape {
+        TileShape::Square => (pitch, pitch),
+        TileShape::Hexagon => (pitch * sqrt3 / 2.0, pitch),
+        TileShape::Triangle => (pitch / 2.0, pitch * sqrt3 / 2.0),
+    };
+    let columns =
+        ((min.x / column_pitch).floor() as i64 - 2)..=((max.x / column_pitch).ceil() as i64 + 1);
+    let rows = ((min.y / row_pitch).floor() as i64 - 2)..=((max.y / row_pitch).ceil() as i64 + 1);
+
+    let mut result = Vec::new();
+    for row in rows {
+        for column in columns.clone() {
+            let (x, y) = (column as f64 * column_pitch, row as f64 * row_pitch);
+            let t = match shape {
+                TileShape::Square => {
+                    let h = pitch / 2.0;
+                    let c = Pt2::new(x, y);
+                    let corners = vec![
+                        c + Pt2::new(-h, -h),
+                        c + Pt2::new(-h, h),
+                        c + Pt2::new(h, h),
+                        c + Pt2::new(h, -h),
+                    ];
+                    tile(column, row, c, corners, h, wall)
+                }
+                TileShape::Hexagon => {
+                    let y = if column % 2 != 0 { y + pitch / 2.0 } else { y };
+                    let mut corners = dim2::circle(pitch / sqrt3, 6);
+                    corners.translate(Pt2::new(x, y));
+                    tile(
+                        column,
+                        row,
+                        Pt2::new(x, y),
+                        corners.to_vec(),
+                        pitch / 2.0,
+                        wall,
+                    )
+                }
+                TileShape::Triangle => {
+                    let (top, bottom) = (y + row_pitch, y);
+                    let corners = if (column + row) % 2 == 0 {
+                        vec![
+                            Pt2::new(x, bottom),
+                            Pt2::new(x + pitch / 2.0, top),
+                            Pt2::new(x + pitch, bottom),
+                        ]
+                    } else {
+                        vec![
+                            Pt2::new(x, top),
+                            Pt2::new(x + pitch, top),
+                            Pt2::new(x + pitch / 2.0, bottom),
+                        ]
+                    };
+                    let center = (corners[0] + corners[1] + corners[2]) / 3.0;
+                    tile(column, row, center, corners, pitch / (2.0 * sqrt3), wall)
+                }
+            };
+            let Some(profile) = cell(&t) else {
+                continue;
+            };
+            if profile.len() < 3 {
+                continue;
+            }
+            let fitted = dim2::intersection(std::slice::from_ref(&profile), region);
+            if clip {
+                result.extend(fitted);
+            } else {
+                let area =
+                    |outlines: &[Pt2s]| -outlines.iter().map(|o| signed_area(o)).sum::<f64>();
+                if approx_eq(area(&fitted), area(&[profile]), 1.0e-9 * pitch * pitch) {
+                    result.extend(fitted);
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Fills a region with tiles.
+///
+/// Extrude the result and subtract it from a panel to make a speaker grille or
+/// a decorative panel. See tile_profiles.
+///
+/// region: The clockwise outlines and counter clockwise holes to fill.
+///
+/// shape: The shape of the tiles.
+///
+/// pitch: The distance between the centers of neighboring tiles, the side of a
+/// triangle.
+///
+/// wall: The gap between neighboring tiles.
+///
+/// clip: Cut the tiles on the edges of the region to fit, otherwise only tiles
+/// that are completely inside are kept.
+///
+/// cell: Returns the profile for a tile or None to leave it out.
+///
+/// return: A polygon of the tiles.
+pub fn tiles<F: FnMut(&Tile) -> Option<Pt2s>>(
+    region: &[Pt2s],
+    shape: TileShape,
+    pitch: f64,
+    wall: f64,
+    clip: bool,
+    cell: F,
+) -> Scad {
+    cells_to_scad(&tile_profiles(region, shape, pitch, wall, clip, cell))
+}
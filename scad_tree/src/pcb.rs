@@ -0,0 +1,235 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Standoffs, screw holes and keep-out volumes for mounting circuit boards.
+
+use crate::prelude::*;
+
+/// A mounting hole of a circuit board.
+#[derive(Clone, Copy, PartialEq)]
+pub struct PcbHole {
+    /// The center of the hole from the origin of the board.
+    pub position: Pt2,
+    /// The diameter of the drilled hole.
+    pub diameter: f64,
+}
+
+impl PcbHole {
+    /// Create a PcbHole.
+    pub fn new(x: f64, y: f64, diameter: f64) -> Self {
+        Self {
+            position: Pt2::new(x, y),
+            diameter,
+        }
+    }
+}
+
+/// A circuit board mounted on standoffs in an enclosure.
+///
+/// Board coordinates are measured from the lower left corner of the board seen
+/// from above. The board is placed with that corner at origin, with origin.z on
+/// the top of the enclosure floor.
+#[derive(Clone)]
+pub struct Pcb {
+    /// The size of the board in X.
+    pub width: f64,
+    /// The size of the board in Y.
+    pub depth: f64,
+    pub thickness: f64,
+    pub holes: Vec<PcbHole>,
+    /// Where the lower left corner of the board sits on the enclosure floor.
+    pub origin: Pt3,
+    /// The height of the bottom of the board above the floor.
+    pub standoff_height: f64,
+    /// The wall around the screw hole of a standoff.
+    pub standoff_wall: f64,
+    /// Use a heat-set insert of this metric size in each standoff instead of a
+    /// pilot hole for a self tapping screw. The standoffs must be at least 1
+    /// taller than the insert.
+    pub insert: Option<i32>,
+    /// The pilot hole diameter as a fraction of the board hole diameter.
+    pub pilot_ratio: f64,
+    /// The gap around screw holes and the board edges.
+    pub clearance: f64,
+    /// The height of the tallest part on top of the board.
+    pub top_height: f64,
+    /// The height of the tallest part or pin under the board.
+    pub bottom_height: f64,
+    /// The number of segments in a circle.
+    pub segments: u64,
+}
+
+impl Default for Pcb {
+    fn default() -> Self {
+        Self {
+            width: 100.0,
+            depth: 80.0,
+            thickness: 1.6,
+            holes: Vec::new(),
+            origin: Pt3::new(0.0, 0.0, 0.0),
+            standoff_height: 5.0,
+            standoff_wall: 2.0,
+            insert: None,
+            pilot_ratio: 0.8,
+            clearance: 0.5,
+            top_height: 15.0,
+            bottom_height: 2.0,
+            segments: 36,
+        }
+    }
+}
+
+impl Pcb {
+    /// Create a board with holes and the default standoffs.
+    ///
+    /// width: The size of the board in X.
+    ///
+    /// depth: The size of the board in Y.
+    ///
+    /// holes: The mounting holes.
+    ///
+    /// return: The board.
+    pub fn new(width: f64, depth: f64, holes: Vec<PcbHole>) -> Self {
+        Self {
+            width,
+            depth,
+            holes,
+            ..Default::default()
+        }
+    }
+
+    /// Create a board from its outline and holes, e.g. read with dim2::from_gerber
+    /// and PcbHole::from_excellon.
+    ///
+    /// The size of the board is the box around the outlines. The holes are moved
+    /// so the lower left corner of the box is the origin of the board.
+    ///
+    /// outlines: The outlines of the board.
+    ///
+    /// holes: The holes in the coordinates of the outlines.
+    ///
+    /// return: The board or an error if the outlines have no points.
+    pub fn from_outline(outlines: &[Pt2s], holes: Vec<PcbHole>) -> Result<Self, ScadError> {
+        let mut points = outlines.iter().flat_map(|o| o.iter());
+        let first = *points
+            .next()
+            .ok_or_else(|| ScadError::Invalid("the board has no outline".to_string()))?;
+        let (min, max) = points.fold((first, first), |(min, max), p| {
+            (
+                Pt2::new(min.x.min(p.x), min.y.min(p.y)),
+                Pt2::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+        let holes = holes
+            .into_iter()
+            .map(|hole| PcbHole {
+                position: hole.position - min,
+                ..hole
+            })
+            .collect();
+        Ok(Self::new(max.x - min.x, max.y - min.y, holes))
+    }
+
+    /// Moves a part from board coordinates into the enclosure.
+    fn place(&self, position: Pt2, z: f64, part: Scad) -> Scad {
+        translate!([self.origin.x + position.x, self.origin.y + position.y, self.origin.z + z],
+            part;
+        )
+    }
+
+    /// The standoffs standing on the floor with a screw hole or insert pocket in
+    /// the top of each.
+    pub fn standoffs(&self) -> Scad {
+        let mut result = Vec::with_capacity(self.holes.len());
+        for hole in self.holes.iter() {
+            let height = self.standoff_height;
+            let standoff = match self.insert {
+                Some(m) => {
+                    metric_thread::heat_set_boss(m, height, self.standoff_wall, self.segments)
+                }
+                None => {
+                    let pilot = hole.diameter * self.pilot_ratio / 2.0;
+                    let radius = hole.diameter / 2.0 + self.standoff_wall;
+                    cylinder!(height, radius, fn=self.segments)
+                        - translate!([0.0, 0.0, 1.0],
+                            cylinder!(height, pilot, fn=self.segments);
+                        )
+                }
+            };
+            result.push(self.place(hole.position, 0.0, standoff));
+        }
+        Scad {
+            op: ScadOp::Union,
+            children: result,
+        }
+    }
+
+    /// The holes for screws through the floor and the standoffs, for bolting
+    /// the board from below.
+    ///
+    /// floor_thickness: The thickness of the enclosure floor.
+    pub fn clearance_holes(&self, floor_thickness: f64) -> Scad {
+        let length = floor_thickness + self.standoff_height + 2.0;
+        let mut result = Vec::with_capacity(self.holes.len());
+        for hole in self.holes.iter() {
+            let radius = hole.diameter / 2.0 + self.clearance;
+            result.push(self.place(
+                hole.position,
+                -floor_thickness - 1.0,
+                cylinder!(length, radius, fn=self.segments),
+            ));
+        }
+        Scad {
+            op: ScadOp::Union,
+            children: result,
+        }
+    }
+
+    /// The space the board and its parts fill with the clearance around the
+    /// edges, for checking the fit or cutting pockets.
+    pub fn keep_out(&self) -> Scad {
+        let z = self.standoff_height - self.bottom_height;
+        let height = self.bottom_height + self.thickness + self.top_height;
+        self.place(
+            Pt2::new(-self.clearance, -self.clearance),
+            z,
+            cube!([
+                self.width + self.clearance * 2.0,
+                self.depth + self.clearance * 2.0,
+                height
+            ]),
+        )
+    }
+
+    /// The board itself for previews.
+    pub fn board(&self) -> Scad {
+        let mut board = cube!([self.width, self.depth, self.thickness]);
+        for hole in self.holes.iter() {
+            board = board
+                - translate!([hole.position.x, hole.position.y, -1.0],
+                    cylinder!(self.thickness + 2.0, hole.diameter / 2.0, fn=self.segments);
+                );
+        }
+        self.place(Pt2::new(0.0, 0.0), self.standoff_height, board)
+    }
+}
@@ -131,4 +131,69 @@ impl Pipe {
     pub fn tapered_solid(od1: f64, od2: f64, length: f64, center: bool, fn_: u64) -> Scad {
         cylinder!(h=length, d1=od1, d2=od2, center=center, fn=fn_)
     }
+
+    /// Create a pipe that follows a path of waypoints.
+    ///
+    /// The corners of the path are rounded with bends before the tube is
+    /// swept along it. The bore extends 1 unit past both ends for a cleaner
+    /// preview in OpenSCAD.
+    ///
+    /// waypoints: The points the center of the pipe passes through.
+    ///
+    /// od: The outside diameter of the pipe.
+    ///
+    /// wall_thickness: The wall thickness of the pipe.
+    ///
+    /// bend_radius: The radius of the bends at the center of the pipe.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The pipe.
+    pub fn route(
+        waypoints: &Pt3s,
+        od: f64,
+        wall_thickness: f64,
+        bend_radius: f64,
+        segments: u64,
+    ) -> Scad {
+        assert!(od - wall_thickness * 2.0 > 0.0);
+        let path = Self::route_path(waypoints, bend_radius, segments);
+        let mut bore_path = path.clone();
+        let n = bore_path.len();
+        let start = bore_path[0] + (bore_path[0] - bore_path[1]).normalized();
+        let end = bore_path[n - 1] + (bore_path[n - 1] - bore_path[n - 2]).normalized();
+        bore_path.insert(0, start);
+        bore_path.push(end);
+        difference!(
+            Polyhedron::sweep(&dim2::circle(od / 2.0, segments), &path, 0.0, false).into_scad();
+            Polyhedron::sweep(
+                &dim2::circle(od / 2.0 - wall_thickness, segments),
+                &bore_path,
+                0.0,
+                false,
+            )
+            .into_scad();
+        )
+    }
+
+    /// Create a solid pipe that follows a path of waypoints.
+    ///
+    /// waypoints: The points the center of the pipe passes through.
+    ///
+    /// od: The outside diameter of the pipe.
+    ///
+    /// bend_radius: The radius of the bends at the center of the pipe.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The pipe.
+    pub fn route_solid(waypoints: &Pt3s, od: f64, bend_radius: f64, segments: u64) -> Scad {
+        let path = Self::route_path(waypoints, bend_radius, segments);
+        Polyhedron::sweep(&dim2::circle(od / 2.0, segments), &path, 0.0, false).into_scad()
+    }
+
+    fn route_path(waypoints: &Pt3s, bend_radius: f64, segments: u64) -> Pt3s {
+        assert!(waypoints.len() > 1);
+        waypoints.fillet_corners(bend_radius, (segments / 4).max(1))
+    }
 }
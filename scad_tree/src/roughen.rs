@@ -0,0 +1,153 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Band-limited noise for roughening the surfaces of polyhedrons.
+
+use crate::{MersenneTwister, Polyhedron, Pt3};
+
+/// Gradient noise with features about one unit across.
+struct GradientNoise {
+    perm: Vec<u8>,
+}
+
+impl GradientNoise {
+    /// Creates the noise from a shuffled permutation table.
+    fn new(rng: &mut MersenneTwister) -> Self {
+        let mut perm: Vec<u8> = (0..=255).collect();
+        for i in (1..perm.len()).rev() {
+            let j = (rng.u32() % (i as u32 + 1)) as usize;
+            perm.swap(i, j);
+        }
+        perm.extend_from_within(..);
+        Self { perm }
+    }
+
+    fn hash(&self, x: i64, y: i64, z: i64) -> u8 {
+        let p = |i: i64| self.perm[i.rem_euclid(256) as usize] as i64;
+        self.perm[(p(p(x) + y) + z).rem_euclid(256) as usize]
+    }
+
+    /// The dot product of one of the 12 edge gradients of a cube with the offset.
+    fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+        let h = hash & 15;
+        let u = if h < 8 { x } else { y };
+        let v = if h < 4 {
+            y
+        } else if h == 12 || h == 14 {
+            x
+        } else {
+            z
+        };
+        (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+    }
+
+    /// The noise at a point, about -1 to 1.
+    fn sample(&self, p: Pt3) -> f64 {
+        let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (fx, fy, fz) = (p.x - x0, p.y - y0, p.z - z0);
+        let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+        let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+        let corner = |dx: i64, dy: i64, dz: i64| {
+            Self::grad(
+                self.hash(x0 + dx, y0 + dy, z0 + dz),
+                fx - dx as f64,
+                fy - dy as f64,
+                fz - dz as f64,
+            )
+        };
+        lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+                v,
+            ),
+            w,
+        )
+    }
+}
+
+impl Polyhedron {
+    /// Moves the points of the surface in and out with noise for a grip texture.
+    ///
+    /// Each point is moved along its normal by noise with bumps about scale
+    /// across. Only points whose faces are all selected move so the texture
+    /// blends into the rest of the surface. There must be many points to make
+    /// a texture, e.g. a sweep or extrusion with segments smaller than scale.
+    ///
+    /// amplitude: The largest distance a point is moved.
+    ///
+    /// scale: The size of the bumps.
+    ///
+    /// group: The face group to roughen, None for all faces.
+    ///
+    /// rng: The source of the noise, the same seed gives the same texture.
+    ///
+    /// return: self
+    pub fn roughen(
+        &mut self,
+        amplitude: f64,
+        scale: f64,
+        group: Option<u64>,
+        rng: &mut MersenneTwister,
+    ) -> &mut Self {
+        assert!(amplitude >= 0.0 && scale > 0.0);
+        let noise = GradientNoise::new(rng);
+        let mut normals = vec![Pt3::new(0.0, 0.0, 0.0); self.points.len()];
+        let mut selected = vec![true; self.points.len()];
+        for (f, face) in self.faces.iter().enumerate() {
+            let is_selected = group.map_or(true, |g| self.face_group(f) == g);
+            let a = self.points[face[0] as usize];
+            let mut normal = Pt3::new(0.0, 0.0, 0.0);
+            for i in 1..face.len().saturating_sub(1) {
+                let b = self.points[face[i] as usize];
+                let c = self.points[face[i + 1] as usize];
+                normal += (c - a).cross(b - a);
+            }
+            for &i in face.iter() {
+                normals[i as usize] += normal;
+                selected[i as usize] &= is_selected;
+            }
+        }
+        // Each seed samples a different part of the noise.
+        let offset = Pt3::new(
+            rng.f64_minmax(0.0, 256.0),
+            rng.f64_minmax(0.0, 256.0),
+            rng.f64_minmax(0.0, 256.0),
+        );
+        for (i, point) in self.points.iter_mut().enumerate() {
+            if !selected[i] || normals[i].len() == 0.0 {
+                continue;
+            }
+            let n = noise.sample(*point / scale + offset).clamp(-1.0, 1.0);
+            *point += normals[i].normalized() * (n * amplitude);
+        }
+        self
+    }
+}
@@ -21,7 +21,10 @@
 // SOFTWARE.
 //
 
-use {crate::prelude::*, std::io::Write};
+use {
+    crate::{dcos, dsin, dtan, prelude::*, with_float_format},
+    std::io::Write,
+};
 
 /// The supported OpenSCAD operations.
 #[derive(Clone, PartialEq)]
@@ -145,6 +148,105 @@ pub enum ScadOp {
     Minkowski {
         convexity: u64,
     },
+    Call {
+        module: String,
+        args: Vec<(String, ScadExpr)>,
+    },
+    /// A comment, each line is written after //.
+    Comment(String),
+    /// OpenSCAD code written as is.
+    Raw(String),
+    /// A debug modifier character written before the children.
+    Modifier(ScadModifier),
+    /// Marks the child for Scad::check_interference, written as the child.
+    Tag(ScadTag),
+    /// Counts the child in the bill of materials of Scad::bom, written as the child.
+    BomItem {
+        name: String,
+        quantity: u64,
+    },
+    /// Forces a full render of the children in the preview.
+    Render {
+        convexity: u64,
+    },
+    /// The first child in the preview and the second child in the render.
+    IfPreview,
+}
+
+impl ScadOp {
+    /// Returns true if the operation always takes a block of children.
+    ///
+    /// A Call is false here, Scad::has_block decides from its children.
+    fn has_children(&self) -> bool {
+        match self {
+            ScadOp::Union
+            | ScadOp::Difference
+            | ScadOp::Intersection
+            | ScadOp::Projection { .. }
+            | ScadOp::LinearExtrude { .. }
+            | ScadOp::RotateExtrude { .. }
+            | ScadOp::Translate { .. }
+            | ScadOp::Rotate { .. }
+            | ScadOp::Scale { .. }
+            | ScadOp::Resize { .. }
+            | ScadOp::Mirror { .. }
+            | ScadOp::Color { .. }
+            | ScadOp::Offset { .. }
+            | ScadOp::Hull
+            | ScadOp::Minkowski { .. }
+            | ScadOp::Modifier(_)
+            | ScadOp::Tag(_)
+            | ScadOp::BomItem { .. }
+            | ScadOp::Render { .. }
+            | ScadOp::IfPreview => true,
+            ScadOp::Circle { .. }
+            | ScadOp::Square { .. }
+            | ScadOp::Polygon { .. }
+            | ScadOp::Text { .. }
+            | ScadOp::Import { .. }
+            | ScadOp::Sphere { .. }
+            | ScadOp::Cube { .. }
+            | ScadOp::Cylinder { .. }
+            | ScadOp::Polyhedron { .. }
+            | ScadOp::Surface { .. }
+            | ScadOp::Comment(_)
+            | ScadOp::Call { .. }
+            | ScadOp::Raw(_) => false,
+        }
+    }
+}
+
+/// The role of a subtree when an assembly is checked for collisions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScadTag {
+    /// A solid part that must not touch other parts.
+    Body,
+    /// A space that must stay empty e.g. the swing of a lid or room for a cable.
+    KeepOut,
+}
+
+/// The OpenSCAD modifier characters used when previewing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScadModifier {
+    /// # Highlight the object in transparent pink.
+    Debug,
+    /// % Show the object transparent and ignore it in the render.
+    Background,
+    /// ! Show only this object.
+    Root,
+    /// * Ignore the object.
+    Disable,
+}
+
+impl std::fmt::Display for ScadModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScadModifier::Debug => write!(f, "#"),
+            ScadModifier::Background => write!(f, "%"),
+            ScadModifier::Root => write!(f, "!"),
+            ScadModifier::Disable => write!(f, "*"),
+        }
+    }
 }
 
 /// A tree of OpenSCAD operations.
@@ -222,6 +324,304 @@ impl Scad {
         result
     }
 
+    /// Creates a 45 degree chamfer for the rim of a hole.
+    ///
+    /// The chamfer cuts the rim of a hole at the XY plane in material above it.
+    ///
+    /// size: The size of the angled part of the chamfer profile.
+    ///
+    /// oversize: How much the chamfer extends into the hole and below the XY plane.
+    ///
+    /// radius: The radius of the hole.
+    ///
+    /// degrees: The degrees of the arc that the chamfer is extruded through.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The mesh.
+    pub fn internal_circle_chamfer(
+        size: f64,
+        oversize: f64,
+        radius: f64,
+        degrees: f64,
+        segments: u64,
+    ) -> Self {
+        let inner = (radius - oversize).max(0.0);
+        rotate_extrude!(angle=degrees, convexity=5, fn=segments,
+            polygon!(Pt2s::from_pt2s(vec![
+                Pt2::new(inner, -oversize),
+                Pt2::new(inner, size),
+                Pt2::new(radius, size),
+                Pt2::new(radius + size, 0.0),
+                Pt2::new(radius + size, -oversize),
+            ]));
+        )
+    }
+
+    /// Creates two internal circle chamfers for chamfering both ends of a hole.
+    ///
+    /// size: The size of the angled part of the chamfer profile.
+    ///
+    /// oversize: How much the chamfers extend into the hole and past the ends.
+    ///
+    /// radius: The radius of the hole to be chamfered.
+    ///
+    /// height: The length of the hole to be chamfered.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// center: Center vertically.
+    ///
+    /// return: The mesh.
+    pub fn internal_cylinder_chamfer(
+        size: f64,
+        oversize: f64,
+        radius: f64,
+        height: f64,
+        segments: u64,
+        center: bool,
+    ) -> Self {
+        let mut result = union!(
+            Self::internal_circle_chamfer(size, oversize, radius, 360.0, segments);
+            translate!([0.0, 0.0, height],
+                rotate!([180.0, 0.0, 0.0],
+                    Self::internal_circle_chamfer(size, oversize, radius, 360.0, segments);
+                );
+            );
+        );
+        if center {
+            result = translate!([0.0, 0.0, -height / 2.0], result;);
+        }
+        result
+    }
+
+    /// Creates a countersunk hole for cutting into a surface at the XY plane.
+    ///
+    /// The cone starts at the XY plane and the hole extends down into -Z. The
+    /// countersink radius extends 1 unit above the XY plane for a cleaner preview.
+    ///
+    /// hole_radius: The radius of the hole.
+    ///
+    /// countersink_radius: The radius of the countersink at the surface.
+    ///
+    /// degrees: The included angle of the countersink, 90 for metric screws.
+    ///
+    /// depth: The depth of the hole measured from the surface.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The mesh.
+    pub fn countersink(
+        hole_radius: f64,
+        countersink_radius: f64,
+        degrees: f64,
+        depth: f64,
+        segments: u64,
+    ) -> Self {
+        assert!(countersink_radius > hole_radius);
+        assert!(degrees > 0.0 && degrees < 180.0);
+        let cone_height = (countersink_radius - hole_radius) / dtan(degrees / 2.0);
+        assert!(depth >= cone_height - 1.0e-9);
+        rotate_extrude!(angle=360.0, convexity=2, fn=segments,
+            polygon!(Pt2s::from_pt2s(vec![
+                Pt2::new(0.0, -depth),
+                Pt2::new(0.0, 1.0),
+                Pt2::new(countersink_radius, 1.0),
+                Pt2::new(countersink_radius, 0.0),
+                Pt2::new(hole_radius, -cone_height),
+                Pt2::new(hole_radius, -depth),
+            ]));
+        )
+    }
+
+    /// The union of two 3D objects with a fillet in the concave corners where they meet.
+    ///
+    /// The fillet is the region within radius of both objects that a ball of radius
+    /// can not reach. It is built from nested minkowski operations so it is slow
+    /// to render.
+    ///
+    /// a: The first object.
+    ///
+    /// b: The second object.
+    ///
+    /// radius: The radius of the fillet.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The filleted union.
+    pub fn fillet_union(a: &Scad, b: &Scad, radius: f64, segments: u64) -> Self {
+        let ball = sphere!(radius, fn=segments);
+        let band = intersection!(
+            minkowski!(a.clone(); ball.clone(););
+            minkowski!(b.clone(); ball.clone(););
+        );
+        let result = union!(a.clone(); b.clone(););
+        let fill = Self::fillet_fill(&result, band, ball);
+        result + fill
+    }
+
+    /// The difference of two 3D objects with a fillet in the concave corners left by the cut.
+    ///
+    /// The fillet is limited to the part of a within radius of b. It is built from
+    /// nested minkowski operations so it is slow to render.
+    ///
+    /// a: The object to cut.
+    ///
+    /// b: The object to remove.
+    ///
+    /// radius: The radius of the fillet.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// return: The filleted difference.
+    pub fn fillet_difference(a: &Scad, b: &Scad, radius: f64, segments: u64) -> Self {
+        let ball = sphere!(radius, fn=segments);
+        let band = intersection!(
+            a.clone();
+            minkowski!(b.clone(); ball.clone(););
+        );
+        let result = a.clone() - b.clone();
+        let fill = Self::fillet_fill(&result, band, ball);
+        result + fill
+    }
+
+    /// The part of the band that a closing of result by the ball would fill.
+    fn fillet_fill(result: &Scad, band: Scad, ball: Scad) -> Self {
+        let dilated = minkowski!(result.clone(); ball.clone(););
+        let outside = minkowski!(band.clone(); ball.clone();) - dilated;
+        band - minkowski!(outside; ball;)
+    }
+
+    /// The union of two 2D objects with a fillet in the concave corners where they meet.
+    ///
+    /// a: The first object.
+    ///
+    /// b: The second object.
+    ///
+    /// radius: The radius of the fillet.
+    ///
+    /// return: The filleted union.
+    pub fn fillet_union_2d(a: &Scad, b: &Scad, radius: f64) -> Self {
+        let band = intersection!(
+            offset!(radius, a.clone(););
+            offset!(radius, b.clone(););
+        );
+        let result = union!(a.clone(); b.clone(););
+        let fill = intersection!(
+            band;
+            offset!(-radius,
+                offset!(radius, result.clone(););
+            );
+        );
+        result + fill
+    }
+
+    /// The difference of two 2D objects with a fillet in the concave corners left by the cut.
+    ///
+    /// a: The object to cut.
+    ///
+    /// b: The object to remove.
+    ///
+    /// radius: The radius of the fillet.
+    ///
+    /// return: The filleted difference.
+    pub fn fillet_difference_2d(a: &Scad, b: &Scad, radius: f64) -> Self {
+        let band = intersection!(
+            a.clone();
+            offset!(radius, b.clone(););
+        );
+        let result = a.clone() - b.clone();
+        let fill = intersection!(
+            band;
+            offset!(-radius,
+                offset!(radius, result.clone(););
+            );
+        );
+        result + fill
+    }
+
+    /// Creates a cylinder with 45 degree chamfers on the top and bottom edges.
+    ///
+    /// radius: The radius of the cylinder.
+    ///
+    /// height: The height of the cylinder.
+    ///
+    /// chamfer: The size of the chamfers.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// center: Center the cylinder vertically.
+    ///
+    /// return: The cylinder.
+    pub fn chamfered_cylinder(
+        radius: f64,
+        height: f64,
+        chamfer: f64,
+        segments: u64,
+        center: bool,
+    ) -> Self {
+        assert!(chamfer > 0.0 && chamfer <= radius && chamfer * 2.0 <= height);
+        let profile = Pt2s::from_pt2s(vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(0.0, height),
+            Pt2::new(radius - chamfer, height),
+            Pt2::new(radius, height - chamfer),
+            Pt2::new(radius, chamfer),
+            Pt2::new(radius - chamfer, 0.0),
+        ]);
+        Self::lathe_cylinder(profile, height, segments, center)
+    }
+
+    /// Creates a cylinder with rounded top and bottom edges.
+    ///
+    /// radius: The radius of the cylinder.
+    ///
+    /// height: The height of the cylinder.
+    ///
+    /// fillet: The radius of the rounded edges.
+    ///
+    /// segments: The number of segments in a circle.
+    ///
+    /// center: Center the cylinder vertically.
+    ///
+    /// return: The cylinder.
+    pub fn rounded_cylinder(
+        radius: f64,
+        height: f64,
+        fillet: f64,
+        segments: u64,
+        center: bool,
+    ) -> Self {
+        assert!(fillet > 0.0 && fillet <= radius && fillet * 2.0 <= height);
+        let arc_segments = (segments / 4).max(1);
+        let mut profile = Pt2s::from_pt2s(vec![Pt2::new(0.0, 0.0), Pt2::new(0.0, height)]);
+        for i in 0..=arc_segments {
+            let a = 90.0 - 90.0 * i as f64 / arc_segments as f64;
+            profile.push(Pt2::new(
+                radius - fillet + fillet * dcos(a),
+                height - fillet + fillet * dsin(a),
+            ));
+        }
+        for i in 0..=arc_segments {
+            let a = -90.0 * i as f64 / arc_segments as f64;
+            profile.push(Pt2::new(
+                radius - fillet + fillet * dcos(a),
+                fillet + fillet * dsin(a),
+            ));
+        }
+        Self::lathe_cylinder(profile, height, segments, center)
+    }
+
+    fn lathe_cylinder(profile: Pt2s, height: f64, segments: u64, center: bool) -> Self {
+        let mut result = rotate_extrude!(angle=360.0, convexity=2, fn=segments,
+            polygon!(profile);
+        );
+        if center {
+            result = translate!([0.0, 0.0, -height / 2.0], result;);
+        }
+        result
+    }
+
     /// Create a circular array around the Z axis
     pub fn polar_array(scad: &Scad, count: u64, degrees: f64) -> Scad {
         assert!(degrees <= 360.0);
@@ -234,105 +634,477 @@ impl Scad {
         result
     }
 
-    pub fn save(&self, path: &str) {
-        let s = format!("{}", self);
-        let mut file = std::fs::File::create(path).unwrap();
-        file.write_all(s.as_bytes()).unwrap();
-        file.flush().unwrap();
+    /// Places the characters of a text string along a path.
+    ///
+    /// Each character is its own text object, horizontally centered on the path and
+    /// rotated to follow the tangent of the path. Characters that would fall past the
+    /// end of the path are dropped. Use a clockwise circle for text that reads around
+    /// the outside of a ring.
+    ///
+    /// params: The text parameters, halign is ignored and always center.
+    ///
+    /// path: The points of the path the text follows.
+    ///
+    /// start: The distance along the path to the center of the first character.
+    ///
+    /// char_spacing: The distance along the path between the centers of characters.
+    ///
+    /// return: The 2D text.
+    pub fn text_on_path(params: &TextParams, path: &Pt2s, start: f64, char_spacing: f64) -> Self {
+        let mut glyphs = Vec::new();
+        for (i, c) in params.text.chars().enumerate() {
+            let distance = start + i as f64 * char_spacing;
+            if c.is_whitespace() {
+                continue;
+            }
+            if let Some((position, tangent)) = path_point_and_tangent(path, distance) {
+                let mut glyph_params = params.clone();
+                glyph_params.text = c.to_string();
+                glyph_params.halign = TextHalign::center;
+                glyphs.push(translate!([position.x, position.y, 0.0],
+                    rotate!(tangent.y.atan2(tangent.x).to_degrees(),
+                        text!(text_params=glyph_params);
+                    );
+                ));
+            }
+        }
+        Self {
+            op: ScadOp::Union,
+            children: glyphs,
+        }
     }
-}
 
-impl std::ops::Sub for Scad {
-    type Output = Self;
+    /// Calls an OpenSCAD module by name.
+    ///
+    /// Unlike the other operations the arguments are expressions so they can
+    /// reference variables declared with ScadVar.
+    ///
+    /// module: The name of the module e.g. "cube".
+    ///
+    /// args: The named arguments of the call.
+    ///
+    /// children: The children of the call, may be empty.
+    ///
+    /// return: The call.
+    pub fn call(module: &str, args: Vec<(&str, ScadExpr)>, children: Vec<Scad>) -> Self {
+        Self {
+            op: ScadOp::Call {
+                module: module.to_string(),
+                args: args
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+            },
+            children,
+        }
+    }
 
-    fn sub(self, rhs: Self) -> Self::Output {
-        difference!(self; rhs;)
+    /// A circle whose radius may reference a ScadVar.
+    ///
+    /// radius: The radius of the circle.
+    ///
+    /// return: The circle.
+    pub fn circle_expr<R: Into<ScadExpr>>(radius: R) -> Self {
+        Self::call("circle", vec![("r", radius.into())], Vec::new())
     }
-}
 
-impl std::ops::Add for Scad {
-    type Output = Self;
+    /// A square whose size may reference ScadVars.
+    ///
+    /// size: The size of the square, a number or a 2D vector.
+    ///
+    /// center: Center the square at the origin.
+    ///
+    /// return: The square.
+    pub fn square_expr<S: Into<ScadExpr>>(size: S, center: bool) -> Self {
+        Self::call(
+            "square",
+            vec![("size", size.into()), ("center", center.into())],
+            Vec::new(),
+        )
+    }
 
-    fn add(self, rhs: Self) -> Self::Output {
-        union!(self; rhs;)
+    /// A sphere whose radius may reference a ScadVar.
+    ///
+    /// radius: The radius of the sphere.
+    ///
+    /// return: The sphere.
+    pub fn sphere_expr<R: Into<ScadExpr>>(radius: R) -> Self {
+        Self::call("sphere", vec![("r", radius.into())], Vec::new())
     }
-}
 
-/// Since we are outputting text we leverage the Display trait to format output.
-impl std::fmt::Display for Scad {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.op {
-            ScadOp::Union => {
-                writeln!(f, "union() {{")?;
-            }
-            ScadOp::Difference => {
-                writeln!(f, "difference() {{")?;
-            }
-            ScadOp::Intersection => {
-                writeln!(f, "intersection() {{")?;
-            }
-            ScadOp::Circle {
-                radius,
-                fa,
-                fs,
-                fn_,
-            } => {
-                write!(f, "circle(r={}", radius)?;
-                if let Some(fa) = fa {
-                    write!(f, ", $fa={}", fa)?;
-                }
-                if let Some(fs) = fs {
-                    write!(f, ", $fs={}", fs)?;
-                }
-                if let Some(fn_) = fn_ {
-                    write!(f, ", $fn={}", fn_)?;
-                }
-                write!(f, ");")?;
-            }
-            ScadOp::Square { size, center } => {
-                write!(
-                    f,
-                    "square(size=[{}, {}], center={});",
-                    size.x, size.y, center
-                )?;
-            }
-            ScadOp::Polygon {
-                points,
-                paths,
-                convexity,
-            } => {
-                if let Some(paths) = paths {
-                    write!(
-                        f,
-                        "polygon(points={}, paths={} convexity={});",
-                        points, paths, convexity
-                    )?;
-                } else {
-                    write!(
-                        f,
-                        "polygon(points={}, paths=undef, convexity={});",
-                        points, convexity
-                    )?;
-                }
-            }
-            ScadOp::Text {
-                text,
-                size,
-                font,
-                halign,
-                valign,
-                spacing,
-                direction,
-                language,
-                script,
+    /// A cube whose size may reference ScadVars.
+    ///
+    /// size: The size of the cube, a number or a 3D vector.
+    ///
+    /// center: Center the cube at the origin.
+    ///
+    /// return: The cube.
+    pub fn cube_expr<S: Into<ScadExpr>>(size: S, center: bool) -> Self {
+        Self::call(
+            "cube",
+            vec![("size", size.into()), ("center", center.into())],
+            Vec::new(),
+        )
+    }
+
+    /// A cylinder whose height and radius may reference ScadVars.
+    ///
+    /// height: The height of the cylinder.
+    ///
+    /// radius: The radius of the cylinder.
+    ///
+    /// center: Center the cylinder vertically.
+    ///
+    /// return: The cylinder.
+    pub fn cylinder_expr<H: Into<ScadExpr>, R: Into<ScadExpr>>(
+        height: H,
+        radius: R,
+        center: bool,
+    ) -> Self {
+        Self::call(
+            "cylinder",
+            vec![
+                ("h", height.into()),
+                ("r", radius.into()),
+                ("center", center.into()),
+            ],
+            Vec::new(),
+        )
+    }
+
+    /// Translates the children by a vector that may reference ScadVars.
+    ///
+    /// v: The translation vector.
+    ///
+    /// children: The children to translate.
+    ///
+    /// return: The translation.
+    pub fn translate_expr<V: Into<ScadExpr>>(v: V, children: Vec<Scad>) -> Self {
+        Self::call("translate", vec![("v", v.into())], children)
+    }
+
+    /// Rotates the children by angles that may reference ScadVars.
+    ///
+    /// a: The angle around z, or a vector of angles around x, y and z.
+    ///
+    /// children: The children to rotate.
+    ///
+    /// return: The rotation.
+    pub fn rotate_expr<A: Into<ScadExpr>>(a: A, children: Vec<Scad>) -> Self {
+        Self::call("rotate", vec![("a", a.into())], children)
+    }
+
+    /// Scales the children by a vector that may reference ScadVars.
+    ///
+    /// v: The scale vector.
+    ///
+    /// children: The children to scale.
+    ///
+    /// return: The scale.
+    pub fn scale_expr<V: Into<ScadExpr>>(v: V, children: Vec<Scad>) -> Self {
+        Self::call("scale", vec![("v", v.into())], children)
+    }
+
+    /// Extrudes the 2D children to a height that may reference a ScadVar.
+    ///
+    /// height: The height of the extrusion.
+    ///
+    /// children: The 2D children to extrude.
+    ///
+    /// return: The extrusion.
+    pub fn linear_extrude_expr<H: Into<ScadExpr>>(height: H, children: Vec<Scad>) -> Self {
+        Self::call("linear_extrude", vec![("height", height.into())], children)
+    }
+
+    /// Wraps the Scad in a modifier.
+    fn with_modifier(self, modifier: ScadModifier) -> Self {
+        Self {
+            op: ScadOp::Modifier(modifier),
+            children: vec![self],
+        }
+    }
+
+    /// Highlight the Scad in the preview, the # modifier.
+    pub fn debug(self) -> Self {
+        self.with_modifier(ScadModifier::Debug)
+    }
+
+    /// Show the Scad transparent in the preview and leave it out of the render, the % modifier.
+    pub fn background(self) -> Self {
+        self.with_modifier(ScadModifier::Background)
+    }
+
+    /// Show only the Scad, the ! modifier.
+    pub fn root(self) -> Self {
+        self.with_modifier(ScadModifier::Root)
+    }
+
+    /// Leave the Scad out of the output, the * modifier.
+    pub fn disable(self) -> Self {
+        self.with_modifier(ScadModifier::Disable)
+    }
+
+    /// Tags the Scad for Scad::check_interference, the output is unchanged.
+    pub fn tag(self, tag: ScadTag) -> Self {
+        Self {
+            op: ScadOp::Tag(tag),
+            children: vec![self],
+        }
+    }
+
+    /// Tags the Scad as a solid part for Scad::check_interference.
+    pub fn body(self) -> Self {
+        self.tag(ScadTag::Body)
+    }
+
+    /// Tags the Scad as a space that must stay empty for Scad::check_interference.
+    pub fn keep_out(self) -> Self {
+        self.tag(ScadTag::KeepOut)
+    }
+
+    /// Counts the Scad in the bill of materials of Scad::bom, the output is unchanged.
+    ///
+    /// name: The name of the item, e.g. "M3x10 socket head cap screw".
+    ///
+    /// quantity: The number of items the Scad stands for.
+    pub fn bom_item(self, name: &str, quantity: u64) -> Self {
+        Self {
+            op: ScadOp::BomItem {
+                name: name.to_string(),
+                quantity,
+            },
+            children: vec![self],
+        }
+    }
+
+    /// Save the Scad to a file, panics on failure.
+    pub fn save(&self, path: &str) {
+        self.try_save(path).unwrap();
+    }
+
+    /// Save the Scad to a file.
+    pub fn try_save(&self, path: &str) -> std::io::Result<()> {
+        self.validate()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut file)?;
+        file.flush()
+    }
+}
+
+/// Finds the point and unit tangent at the given distance along a path.
+fn path_point_and_tangent(path: &Pt2s, distance: f64) -> Option<(Pt2, Pt2)> {
+    if distance < 0.0 {
+        return None;
+    }
+    let mut travelled = 0.0;
+    for i in 1..path.len() {
+        let segment = path[i] - path[i - 1];
+        let length = segment.len();
+        if length == 0.0 {
+            continue;
+        }
+        if travelled + length >= distance {
+            let t = (distance - travelled) / length;
+            return Some((path[i - 1].lerp(path[i], t), segment / length));
+        }
+        travelled += length;
+    }
+    None
+}
+
+impl std::ops::Sub for Scad {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        difference!(self; rhs;)
+    }
+}
+
+impl std::ops::Add for Scad {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        union!(self; rhs;)
+    }
+}
+
+/// The symmetric difference, (self - rhs) + (rhs - self).
+impl std::ops::BitXor for Scad {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        union!(difference!(self.clone(); rhs.clone();); difference!(rhs; self;);)
+    }
+}
+
+impl Scad {
+    /// Checks that the tree can be written as valid OpenSCAD code.
+    ///
+    /// Polygons and polyhedrons must have points, polyhedrons must have faces,
+    /// and paths and faces must be non-empty and index existing points.
+    ///
+    /// return: The first problem found.
+    pub fn validate(&self) -> Result<(), ScadError> {
+        fn check_indices(
+            what: &str,
+            paths: &Paths,
+            point_count: usize,
+            min_len: usize,
+        ) -> Result<(), ScadError> {
+            for (i, path) in paths.iter().enumerate() {
+                if path.len() < min_len {
+                    return Err(ScadError::Invalid(format!(
+                        "{} {} has {} indices",
+                        what,
+                        i,
+                        path.len()
+                    )));
+                }
+                if let Some(index) = path.iter().find(|index| **index as usize >= point_count) {
+                    return Err(ScadError::Invalid(format!(
+                        "{} {} has index {} but there are {} points",
+                        what, i, index, point_count
+                    )));
+                }
+            }
+            Ok(())
+        }
+
+        match &self.op {
+            ScadOp::Polygon { points, paths, .. } => {
+                if points.is_empty() {
+                    return Err(ScadError::Invalid("polygon has no points".to_string()));
+                }
+                if let Some(paths) = paths {
+                    if paths.is_empty() {
+                        return Err(ScadError::Invalid("polygon has no paths".to_string()));
+                    }
+                    check_indices("polygon path", paths, points.len(), 1)?;
+                }
+            }
+            ScadOp::IfPreview if self.children.len() != 2 => {
+                return Err(ScadError::Invalid(format!(
+                    "if $preview has {} children",
+                    self.children.len()
+                )));
+            }
+            ScadOp::Modifier(modifier) if self.children.len() != 1 => {
+                return Err(ScadError::Invalid(format!(
+                    "modifier {} has {} children",
+                    modifier,
+                    self.children.len()
+                )));
+            }
+            ScadOp::Tag(tag) if self.children.len() != 1 => {
+                return Err(ScadError::Invalid(format!(
+                    "tag {:?} has {} children",
+                    tag,
+                    self.children.len()
+                )));
+            }
+            ScadOp::BomItem { name, .. } if self.children.len() != 1 => {
+                return Err(ScadError::Invalid(format!(
+                    "bom item {} has {} children",
+                    name,
+                    self.children.len()
+                )));
+            }
+            ScadOp::Polyhedron { points, faces, .. } => {
+                if points.is_empty() {
+                    return Err(ScadError::Invalid("polyhedron has no points".to_string()));
+                }
+                if faces.is_empty() {
+                    return Err(ScadError::Invalid("polyhedron has no faces".to_string()));
+                }
+                check_indices("polyhedron face", faces, points.len(), 3)?;
+            }
+            _ => (),
+        }
+        for child in self.children.iter() {
+            child.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the operation of this node without its children.
+    ///
+    /// Operations that take children end with an opening brace.
+    pub(crate) fn write_op<W: std::fmt::Write>(&self, f: &mut W) -> std::fmt::Result {
+        match &self.op {
+            ScadOp::Union => {
+                write!(f, "union() {{")?;
+            }
+            ScadOp::Difference => {
+                write!(f, "difference() {{")?;
+            }
+            ScadOp::Intersection => {
+                write!(f, "intersection() {{")?;
+            }
+            ScadOp::Circle {
+                radius,
+                fa,
+                fs,
+                fn_,
+            } => {
+                write!(f, "circle(r={}", Float(*radius))?;
+                if let Some(fa) = fa {
+                    write!(f, ", $fa={}", Float(*fa))?;
+                }
+                if let Some(fs) = fs {
+                    write!(f, ", $fs={}", Float(*fs))?;
+                }
+                if let Some(fn_) = fn_ {
+                    write!(f, ", $fn={}", fn_)?;
+                }
+                write!(f, ");")?;
+            }
+            ScadOp::Square { size, center } => {
+                write!(
+                    f,
+                    "square(size=[{}, {}], center={});",
+                    Float(size.x),
+                    Float(size.y),
+                    center
+                )?;
+            }
+            ScadOp::Polygon {
+                points,
+                paths,
+                convexity,
+            } => {
+                if let Some(paths) = paths {
+                    write!(
+                        f,
+                        "polygon(points={}, paths={}, convexity={});",
+                        points, paths, convexity
+                    )?;
+                } else {
+                    write!(
+                        f,
+                        "polygon(points={}, paths=undef, convexity={});",
+                        points, convexity
+                    )?;
+                }
+            }
+            ScadOp::Text {
+                text,
+                size,
+                font,
+                halign,
+                valign,
+                spacing,
+                direction,
+                language,
+                script,
                 fn_,
             } => {
                 write!(f, "text(text={:?}, ", text)?;
-                write!(f, "size={}, ", size)?;
+                write!(f, "size={}, ", Float(*size))?;
                 write!(f, "font={:?}, ", font)?;
                 write!(f, "halign=\"{:?}\", ", halign)?;
                 write!(f, "valign=\"{:?}\", ", valign)?;
-                write!(f, "spacing={}, ", spacing)?;
+                write!(f, "spacing={}, ", Float(*spacing))?;
                 write!(f, "direction=\"{:?}\", ", direction)?;
                 write!(f, "language={:?}, ", language)?;
                 write!(f, "script={:?}", script)?;
@@ -345,7 +1117,7 @@ impl std::fmt::Display for Scad {
                 write!(f, "import({:?}, {});", file, convexity)?;
             }
             ScadOp::Projection { cut } => {
-                writeln!(f, "projection(cut={}) {{", cut)?;
+                write!(f, "projection(cut={}) {{", cut)?;
             }
             ScadOp::Sphere {
                 radius,
@@ -353,12 +1125,12 @@ impl std::fmt::Display for Scad {
                 fs,
                 fn_,
             } => {
-                write!(f, "sphere(r={}", radius)?;
+                write!(f, "sphere(r={}", Float(*radius))?;
                 if let Some(fa) = fa {
-                    write!(f, ", $fa={}", fa)?;
+                    write!(f, ", $fa={}", Float(*fa))?;
                 }
                 if let Some(fs) = fs {
-                    write!(f, ", $fs={}", fs)?;
+                    write!(f, ", $fs={}", Float(*fs))?;
                 }
                 if let Some(fn_) = fn_ {
                     write!(f, ", $fn={}", fn_)?;
@@ -380,13 +1152,16 @@ impl std::fmt::Display for Scad {
                 write!(
                     f,
                     "cylinder(h={}, r1={}, r2={}, center={}",
-                    height, radius1, radius2, center
+                    Float(*height),
+                    Float(*radius1),
+                    Float(*radius2),
+                    center
                 )?;
                 if let Some(fa) = fa {
-                    write!(f, ", $fa={}", fa)?;
+                    write!(f, ", $fa={}", Float(*fa))?;
                 }
                 if let Some(fs) = fs {
-                    write!(f, ", $fs={}", fs)?;
+                    write!(f, ", $fs={}", Float(*fs))?;
                 }
                 if let Some(fn_) = fn_ {
                     write!(f, ", $fn={}", fn_)?;
@@ -416,7 +1191,11 @@ impl std::fmt::Display for Scad {
                 write!(
                     f,
                     "linear_extrude(height={}, center={}, convexity={}, twist={}, scale={}",
-                    height, center, convexity, twist, scale
+                    Float(*height),
+                    center,
+                    convexity,
+                    Float(*twist),
+                    scale
                 )?;
                 if let Some(slices) = slices {
                     write!(f, ", slices={}", slices)?;
@@ -424,7 +1203,7 @@ impl std::fmt::Display for Scad {
                 if let Some(fn_) = fn_ {
                     write!(f, ", $fn={}", fn_)?;
                 }
-                writeln!(f, ") {{")?;
+                write!(f, ") {{")?;
             }
             ScadOp::RotateExtrude {
                 angle,
@@ -433,17 +1212,22 @@ impl std::fmt::Display for Scad {
                 fs,
                 fn_,
             } => {
-                write!(f, "rotate_extrude(angle={}, convexity={}", angle, convexity)?;
+                write!(
+                    f,
+                    "rotate_extrude(angle={}, convexity={}",
+                    Float(*angle),
+                    convexity
+                )?;
                 if let Some(fa) = fa {
-                    write!(f, ", $fa={}", fa)?;
+                    write!(f, ", $fa={}", Float(*fa))?;
                 }
                 if let Some(fs) = fs {
-                    write!(f, ", $fs={}", fs)?;
+                    write!(f, ", $fs={}", Float(*fs))?;
                 }
                 if let Some(fn_) = fn_ {
                     write!(f, ", $fn={}", fn_)?;
                 }
-                writeln!(f, ") {{")?;
+                write!(f, ") {{")?;
             }
             ScadOp::Surface {
                 file,
@@ -458,21 +1242,21 @@ impl std::fmt::Display for Scad {
                 )?;
             }
             ScadOp::Translate { v } => {
-                writeln!(f, "translate(v={}) {{", v)?;
+                write!(f, "translate(v={}) {{", v)?;
             }
             ScadOp::Rotate { a, a_is_scalar, v } => {
                 if let Some(a) = a {
                     if *a_is_scalar {
-                        writeln!(f, "rotate(a={}) {{", a)?;
+                        write!(f, "rotate(a={}) {{", Float(*a))?;
                     } else {
-                        writeln!(f, "rotate(a={}, v={}) {{", a, v)?;
+                        write!(f, "rotate(a={}, v={}) {{", Float(*a), v)?;
                     }
                 } else {
-                    writeln!(f, "rotate(a={}) {{", v)?;
+                    write!(f, "rotate(a={}) {{", v)?;
                 }
             }
             ScadOp::Scale { v } => {
-                writeln!(f, "scale(v={}) {{", v)?;
+                write!(f, "scale(v={}) {{", v)?;
             }
             ScadOp::Resize {
                 newsize,
@@ -482,13 +1266,13 @@ impl std::fmt::Display for Scad {
                 convexity,
             } => {
                 if *auto_is_vec {
-                    writeln!(
+                    write!(
                         f,
                         "resize(newsize={}, auto={}, convexity={}) {{",
                         newsize, auto, convexity
                     )?;
                 } else {
-                    writeln!(
+                    write!(
                         f,
                         "resize(newsize={}, auto=[{}, {}, {}], convexity={}) {{",
                         newsize, autovec.0, autovec.1, autovec.2, convexity
@@ -496,7 +1280,7 @@ impl std::fmt::Display for Scad {
                 }
             }
             ScadOp::Mirror { v } => {
-                writeln!(f, "mirror(v={}) {{", v)?;
+                write!(f, "mirror(v={}) {{", v)?;
             }
             ScadOp::Color {
                 rgba,
@@ -505,74 +1289,418 @@ impl std::fmt::Display for Scad {
                 alpha,
             } => {
                 if let Some(rgba) = rgba {
-                    writeln!(f, "color(c={}) {{", rgba)?;
+                    write!(f, "color(c={}) {{", rgba)?;
                 } else if let Some(color) = color {
                     write!(f, "color(\"{:?}\"", color)?;
                     if let Some(alpha) = alpha {
-                        write!(f, ", alpha={}", alpha)?;
+                        write!(f, ", alpha={}", Float(*alpha))?;
                     }
-                    writeln!(f, ") {{")?;
+                    write!(f, ") {{")?;
                 } else if let Some(hex) = hex {
-                    writeln!(f, "color({:?}) {{", hex)?;
+                    write!(f, "color({:?}) {{", hex)?;
+                } else if let Some(alpha) = alpha {
+                    write!(f, "color(alpha={}) {{", Float(*alpha))?;
+                } else {
+                    write!(f, "color() {{")?;
                 }
             }
             ScadOp::Offset { r, delta, chamfer } => {
                 if let Some(r) = r {
-                    writeln!(f, "offset(r={}) {{", r)?;
+                    write!(f, "offset(r={}) {{", Float(*r))?;
                 } else if let Some(delta) = delta {
-                    writeln!(f, "offset(delta={}, chamfer={}) {{", delta, chamfer)?;
+                    write!(f, "offset(delta={}, chamfer={}) {{", Float(*delta), chamfer)?;
+                } else {
+                    // OpenSCAD defaults to a delta of 1
+                    write!(f, "offset(delta=0, chamfer={}) {{", chamfer)?;
                 }
             }
             ScadOp::Hull => {
-                writeln!(f, "hull() {{")?;
+                write!(f, "hull() {{")?;
             }
             ScadOp::Minkowski { convexity } => {
-                writeln!(f, "minkowski(convexity={}) {{", convexity)?;
+                write!(f, "minkowski(convexity={}) {{", convexity)?;
+            }
+            ScadOp::Call { module, args } => {
+                write!(f, "{}(", module)?;
+                for (i, (name, value)) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}={}", name, value)?;
+                }
+                if self.children.is_empty() {
+                    write!(f, ");")?;
+                } else {
+                    write!(f, ") {{")?;
+                }
+            }
+            ScadOp::Comment(text) => {
+                for (i, line) in text.lines().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "// {}", line)?;
+                }
+            }
+            ScadOp::Raw(code) => {
+                write!(f, "{}", code.trim_end())?;
+            }
+            ScadOp::Modifier(_) | ScadOp::Tag(_) | ScadOp::BomItem { .. } => (),
+            ScadOp::Render { convexity } => {
+                write!(f, "render(convexity={}) {{", convexity)?;
+            }
+            ScadOp::IfPreview => {
+                write!(f, "if ($preview) {{")?;
             }
         } // end match
-        for i in 0..self.children.len() {
-            write!(f, "{}", self.children[i])?;
+        Ok(())
+    }
+
+    /// Writes a short description of this node and its children for Debug.
+    ///
+    /// depth: The indent depth for multiline output or None for one line.
+    fn write_summary(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: Option<usize>,
+    ) -> std::fmt::Result {
+        // only the first few children of wide nodes are shown
+        const MAX_CHILDREN: usize = 8;
+
+        match &self.op {
+            ScadOp::Polygon { points, paths, .. } => {
+                write!(f, "polygon({} points", points.len())?;
+                if let Some(paths) = paths {
+                    write!(f, ", {} paths", paths.len())?;
+                }
+                write!(f, ")")?;
+            }
+            ScadOp::Polyhedron { points, faces, .. } => {
+                write!(
+                    f,
+                    "polyhedron({} points, {} faces)",
+                    points.len(),
+                    faces.len()
+                )?;
+            }
+            ScadOp::Text { text, size, .. } => {
+                write!(f, "text({:?}, size={})", text, size)?;
+            }
+            ScadOp::Comment(text) => write!(f, "comment({} lines)", text.lines().count())?,
+            ScadOp::Raw(code) => write!(f, "raw({} bytes)", code.len())?,
+            ScadOp::Modifier(modifier) => write!(f, "{}", modifier)?,
+            ScadOp::Tag(tag) => write!(f, "{:?}", tag)?,
+            ScadOp::BomItem { name, quantity } => write!(f, "bom({}x {})", quantity, name)?,
+            _ => {
+                let mut op = String::new();
+                self.write_op(&mut op)?;
+                write!(f, "{}", op.trim_end_matches(['{', ';', ' ']))?;
+            }
         }
-        if !self.children.is_empty() {
-            write!(f, "}}")?;
+        if self.children.is_empty() {
+            return Ok(());
+        }
+
+        match depth {
+            None => {
+                write!(f, " {{ ")?;
+                for (i, child) in self.children.iter().take(MAX_CHILDREN).enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    child.write_summary(f, None)?;
+                }
+                if self.children.len() > MAX_CHILDREN {
+                    write!(f, ", ... {} more", self.children.len() - MAX_CHILDREN)?;
+                }
+                write!(f, " }}")
+            }
+            Some(depth) => {
+                writeln!(f, " {{")?;
+                for child in self.children.iter().take(MAX_CHILDREN) {
+                    write!(f, "{}", "    ".repeat(depth + 1))?;
+                    child.write_summary(f, Some(depth + 1))?;
+                    writeln!(f)?;
+                }
+                if self.children.len() > MAX_CHILDREN {
+                    writeln!(
+                        f,
+                        "{}... {} more",
+                        "    ".repeat(depth + 1),
+                        self.children.len() - MAX_CHILDREN
+                    )?;
+                }
+                write!(f, "{}}}", "    ".repeat(depth))
+            }
         }
-        writeln!(f)
     }
-}
 
-/// Enum of all the named OpenSCAD colors
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum ScadColor {
-    Lavender,
-    Thistle,
-    Plum,
-    Violet,
-    Orchid,
-    Fuchsia,
-    Magenta,
-    MediumOrchid,
-    MediumPurple,
-    BlueViolet,
-    DarkViolet,
-    DarkOrchid,
-    DarkMagenta,
-    Purple,
-    Indigo,
-    DarkSlateBlue,
-    SlateBlue,
-    MediumSlateBlue,
-    Pink,
-    LightPink,
-    HotPink,
-    DeepPink,
-    MediumVioletRed,
-    PaleVioletRed,
-    Aqua,
-    Cyan,
-    LightCyan,
-    PaleTurquoise,
-    Aquamarine,
-    Turquoise,
+    /// Returns true if the node is written with a block of children.
+    ///
+    /// A Call only has a block when it is given children.
+    pub fn has_block(&self) -> bool {
+        match &self.op {
+            ScadOp::Call { .. } => !self.children.is_empty(),
+            op => op.has_children(),
+        }
+    }
+
+    /// Writes this node and its children indented by depth.
+    ///
+    /// The modifiers are written before the operation.
+    pub(crate) fn write_tree<W: std::fmt::Write>(
+        &self,
+        f: &mut W,
+        format: &ScadFormat,
+        depth: usize,
+        modifiers: &str,
+    ) -> std::fmt::Result {
+        if let ScadOp::Modifier(modifier) = &self.op {
+            let modifiers = format!("{}{}", modifiers, modifier);
+            for child in self.children.iter() {
+                child.write_tree(f, format, depth, &modifiers)?;
+            }
+            return Ok(());
+        }
+        if let ScadOp::Tag(_) | ScadOp::BomItem { .. } = &self.op {
+            for child in self.children.iter() {
+                child.write_tree(f, format, depth, modifiers)?;
+            }
+            return Ok(());
+        }
+        if let ScadOp::IfPreview = &self.op {
+            // modifiers can't be put on an if so they go on the branches
+            let indent = format.indent.repeat(depth);
+            write!(f, "{}if ($preview) {{{}", indent, format.line_break)?;
+            if let Some(preview) = self.children.first() {
+                preview.write_tree(f, format, depth + 1, modifiers)?;
+            }
+            write!(f, "{}}} else {{{}", indent, format.line_break)?;
+            for child in self.children.iter().skip(1) {
+                child.write_tree(f, format, depth + 1, modifiers)?;
+            }
+            return write!(f, "{}}}{}", indent, format.line_break);
+        }
+        if let ScadOp::Polygon { .. } | ScadOp::Polyhedron { .. } = &self.op {
+            // the points go straight to f so large meshes aren't copied to a String
+            for _ in 0..depth {
+                f.write_str(&format.indent)?;
+            }
+            f.write_str(modifiers)?;
+            self.write_op(f)?;
+            return f.write_str(&format.line_break);
+        }
+        let mut op = String::new();
+        self.write_op(&mut op)?;
+        for (i, line) in op.split('\n').enumerate() {
+            if i > 0 {
+                f.write_str(&format.line_break)?;
+            }
+            for _ in 0..depth {
+                f.write_str(&format.indent)?;
+            }
+            if i == 0 {
+                f.write_str(modifiers)?;
+            }
+            f.write_str(line)?;
+        }
+        if self.has_block() {
+            f.write_str(&format.line_break)?;
+            for child in self.children.iter() {
+                child.write_tree(f, format, depth + 1, "")?;
+            }
+            for _ in 0..depth {
+                f.write_str(&format.indent)?;
+            }
+            f.write_str("}")?;
+        }
+        f.write_str(&format.line_break)
+    }
+
+    /// Formats the Scad with the given indentation and line breaks.
+    ///
+    /// format: The format options.
+    ///
+    /// return: The OpenSCAD code.
+    pub fn format_with(&self, format: &ScadFormat) -> String {
+        format.scoped(|| {
+            let mut s = String::new();
+            self.write_tree(&mut s, format, 0, "").unwrap();
+            s
+        })
+    }
+
+    /// Formats the Scad with each level of children indented.
+    ///
+    /// indent: The number of spaces per level of indentation.
+    ///
+    /// return: The OpenSCAD code.
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        self.format_with(&ScadFormat {
+            indent: " ".repeat(indent),
+            ..Default::default()
+        })
+    }
+
+    /// Streams the OpenSCAD code to a writer without building it in memory.
+    ///
+    /// The code is the same as the Display output.
+    ///
+    /// w: The writer, wrap files in a BufWriter.
+    ///
+    /// return: The error of the writer.
+    pub fn write_to<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        self.write_to_with(
+            w,
+            &ScadFormat {
+                indent: String::new(),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Streams the OpenSCAD code to a writer with the given format.
+    ///
+    /// w: The writer, wrap files in a BufWriter.
+    ///
+    /// format: The format options.
+    ///
+    /// return: The error of the writer.
+    pub fn write_to_with<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        format: &ScadFormat,
+    ) -> std::io::Result<()> {
+        format.scoped(|| write_io(w, |f| self.write_tree(f, format, 0, "")))
+    }
+
+    /// Save the Scad to a file with the given format.
+    pub fn try_save_with(&self, path: &str, format: &ScadFormat) -> std::io::Result<()> {
+        self.validate()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to_with(&mut file, format)?;
+        file.flush()
+    }
+}
+
+/// Adapts an io::Write to the fmt::Write the tree is written with.
+pub(crate) struct IoWriter<'a, W: std::io::Write> {
+    inner: &'a mut W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            std::fmt::Error
+        })
+    }
+}
+
+/// Runs code that writes to w through an IoWriter.
+///
+/// return: The io error that stopped the code, if any.
+pub(crate) fn write_io<W: std::io::Write>(
+    w: &mut W,
+    code: impl FnOnce(&mut IoWriter<W>) -> std::fmt::Result,
+) -> std::io::Result<()> {
+    let mut writer = IoWriter {
+        inner: w,
+        error: None,
+    };
+    match code(&mut writer) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(writer
+            .error
+            .unwrap_or_else(|| std::io::Error::other("formatting failed"))),
+    }
+}
+
+/// Options for formatting OpenSCAD code.
+#[derive(Clone, PartialEq)]
+pub struct ScadFormat {
+    /// The string written once per level of depth e.g. four spaces or a tab.
+    pub indent: String,
+    /// The string written at the end of each line.
+    pub line_break: String,
+    /// The format of the floats or None to use float_format().
+    pub float_format: Option<FloatFormat>,
+}
+
+impl ScadFormat {
+    /// Runs code with the float format of this format if it has one.
+    pub(crate) fn scoped<R>(&self, code: impl FnOnce() -> R) -> R {
+        match self.float_format {
+            Some(float_format) => with_float_format(float_format, code),
+            None => code(),
+        }
+    }
+}
+
+impl Default for ScadFormat {
+    fn default() -> Self {
+        Self {
+            indent: "    ".to_string(),
+            line_break: "\n".to_string(),
+            float_format: None,
+        }
+    }
+}
+
+/// Since we are outputting text we leverage the Display trait to format output.
+impl std::fmt::Display for Scad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = ScadFormat {
+            indent: String::new(),
+            ..Default::default()
+        };
+        self.write_tree(f, &format, 0, "")
+    }
+}
+
+/// Summarizes the tree, one node per operation with the point data replaced
+/// by counts. The alternate form puts each node on its own line.
+impl std::fmt::Debug for Scad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_summary(f, if f.alternate() { Some(0) } else { None })
+    }
+}
+
+/// Enum of all the named OpenSCAD colors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScadColor {
+    Lavender,
+    Thistle,
+    Plum,
+    Violet,
+    Orchid,
+    Fuchsia,
+    Magenta,
+    MediumOrchid,
+    MediumPurple,
+    BlueViolet,
+    DarkViolet,
+    DarkOrchid,
+    DarkMagenta,
+    Purple,
+    Indigo,
+    DarkSlateBlue,
+    SlateBlue,
+    MediumSlateBlue,
+    Pink,
+    LightPink,
+    HotPink,
+    DeepPink,
+    MediumVioletRed,
+    PaleVioletRed,
+    Aqua,
+    Cyan,
+    LightCyan,
+    PaleTurquoise,
+    Aquamarine,
+    Turquoise,
     MediumTurquoise,
     DarkTurquoise,
     CadetBlue,
@@ -750,6 +1878,274 @@ impl Default for TextParams {
     }
 }
 
+/// The advances of the printable ASCII characters of Liberation Sans in
+/// thousandths of an em, starting at the space.
+const DEFAULT_ADVANCES: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// The height of the capitals of Liberation Sans as a fraction of an em.
+const DEFAULT_CAP_HEIGHT: f64 = 0.716;
+
+/// The depth of the descenders of Liberation Sans as a fraction of an em.
+const DEFAULT_DESCENT: f64 = 0.212;
+
+impl TextParams {
+    /// The advance of a character of Liberation Sans.
+    ///
+    /// Characters outside printable ASCII get the advance of a digit.
+    ///
+    /// c: The character.
+    ///
+    /// return: The advance as a fraction of an em.
+    pub fn default_advance(c: char) -> f64 {
+        let i = c as usize;
+        if (32..127).contains(&i) {
+            DEFAULT_ADVANCES[i - 32] as f64 / 1000.0
+        } else {
+            0.556
+        }
+    }
+
+    /// Estimates the box around the text as OpenSCAD renders it.
+    ///
+    /// Uses the metrics of Liberation Sans, other fonts of similar width give
+    /// similar results.
+    ///
+    /// return: The minimum and maximum corners of the text.
+    pub fn measure(&self) -> (Pt2, Pt2) {
+        self.measure_with(&Self::default_advance)
+    }
+
+    /// Estimates the box around the text as OpenSCAD renders it.
+    ///
+    /// OpenSCAD makes the em of the font size / 0.72 so capitals are about size
+    /// tall. Kerning is ignored and the vertical extent is the capital height
+    /// plus the descent when the text has characters that go below the
+    /// baseline.
+    ///
+    /// advance: The advance of a character as a fraction of an em, e.g. from the
+    /// font module with Font::text_width(&c.to_string(), 1.0).
+    ///
+    /// return: The minimum and maximum corners of the text.
+    pub fn measure_with(&self, advance: &dyn Fn(char) -> f64) -> (Pt2, Pt2) {
+        let em = self.size / 0.72;
+        let top = DEFAULT_CAP_HEIGHT * em;
+        let bottom = if self.text.chars().any(|c| "gjpqyQ,;()[]{}|@$_".contains(c)) {
+            -DEFAULT_DESCENT * em
+        } else {
+            0.0
+        };
+        let (width, min, max) = match self.direction {
+            TextDirection::ltr | TextDirection::rtl => {
+                let width: f64 = self.text.chars().map(advance).sum::<f64>() * em * self.spacing;
+                (width, Pt2::new(0.0, bottom), Pt2::new(width, top))
+            }
+            TextDirection::ttb | TextDirection::btt => {
+                // each character is on its own line
+                let width = self.text.chars().map(advance).fold(0.0, f64::max) * em;
+                let lines = self.text.chars().count().max(1) as f64;
+                let height = (lines - 1.0) * em * self.spacing;
+                (width, Pt2::new(0.0, bottom - height), Pt2::new(width, top))
+            }
+        };
+        let dx = match self.halign {
+            TextHalign::left => 0.0,
+            TextHalign::center => -width / 2.0,
+            TextHalign::right => -width,
+        };
+        let dy = match self.valign {
+            TextValign::baseline => 0.0,
+            TextValign::top => -max.y,
+            TextValign::center => -(min.y + max.y) / 2.0,
+            TextValign::bottom => -min.y,
+        };
+        let offset = Pt2::new(dx, dy);
+        (min + offset, max + offset)
+    }
+}
+
+/// An OpenSCAD expression used as an argument of ScadOp::Call or the value of a ScadVar.
+///
+/// The Scad::*_expr constructors take expressions for the fields that are plain numbers in
+/// the other operations, so a model can stay parametric in the OpenSCAD customizer.
+#[derive(Clone, PartialEq)]
+pub enum ScadExpr {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+    /// A reference to a variable by name.
+    Var(String),
+    Vector(Vec<ScadExpr>),
+    /// Any other expression e.g. "height / 2", written as is.
+    Raw(String),
+}
+
+impl std::fmt::Display for ScadExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScadExpr::Number(n) => write!(f, "{}", Float(*n)),
+            ScadExpr::Bool(b) => write!(f, "{}", b),
+            ScadExpr::Text(t) => write!(f, "{}", escape_string(t)),
+            ScadExpr::Var(name) => write!(f, "{}", name),
+            ScadExpr::Vector(v) => {
+                write!(f, "[")?;
+                for (i, e) in v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                write!(f, "]")
+            }
+            ScadExpr::Raw(r) => write!(f, "{}", r),
+        }
+    }
+}
+
+/// Quotes a string using the OpenSCAD escape sequences.
+fn escape_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // OpenSCAD has no escape for NUL.
+            '\0' => (),
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl From<f64> for ScadExpr {
+    fn from(value: f64) -> Self {
+        ScadExpr::Number(value)
+    }
+}
+
+impl From<bool> for ScadExpr {
+    fn from(value: bool) -> Self {
+        ScadExpr::Bool(value)
+    }
+}
+
+impl From<&str> for ScadExpr {
+    fn from(value: &str) -> Self {
+        ScadExpr::Text(value.to_string())
+    }
+}
+
+impl From<&ScadVar> for ScadExpr {
+    fn from(value: &ScadVar) -> Self {
+        ScadExpr::Var(value.name.clone())
+    }
+}
+
+impl From<Pt2> for ScadExpr {
+    fn from(value: Pt2) -> Self {
+        ScadExpr::Vector(vec![value.x.into(), value.y.into()])
+    }
+}
+
+impl From<Pt3> for ScadExpr {
+    fn from(value: Pt3) -> Self {
+        ScadExpr::Vector(vec![value.x.into(), value.y.into(), value.z.into()])
+    }
+}
+
+impl<T: Into<ScadExpr>> From<Vec<T>> for ScadExpr {
+    fn from(value: Vec<T>) -> Self {
+        ScadExpr::Vector(value.into_iter().map(|e| e.into()).collect())
+    }
+}
+
+/// A top level OpenSCAD variable, shown in the OpenSCAD customizer.
+///
+/// Reference the variable in a Scad::call or the Scad::*_expr constructors with
+/// `(&var).into()` or ScadVar::expr.
+#[derive(Clone, PartialEq)]
+pub struct ScadVar {
+    pub name: String,
+    pub value: ScadExpr,
+    /// The customizer widget comment e.g. `[1:100]`.
+    pub annotation: Option<String>,
+    /// Written as a comment above the variable, shown as the label in the customizer.
+    pub description: Option<String>,
+}
+
+impl std::fmt::Display for ScadVar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(description) = &self.description {
+            writeln!(f, "// {}", description)?;
+        }
+        write!(f, "{} = {};", self.name, self.value)?;
+        if let Some(annotation) = &self.annotation {
+            write!(f, " // {}", annotation)?;
+        }
+        writeln!(f)
+    }
+}
+
+impl ScadVar {
+    /// Create a variable.
+    ///
+    /// name: The name of the variable.
+    ///
+    /// value: The default value of the variable.
+    pub fn new<T: Into<ScadExpr>>(name: &str, value: T) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.into(),
+            annotation: None,
+            description: None,
+        }
+    }
+
+    /// Adds a customizer slider from min to max.
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.annotation = Some(format!("[{}:{}]", min, max));
+        self
+    }
+
+    /// Adds a customizer slider from min to max with the given step.
+    pub fn with_step_range(mut self, min: f64, step: f64, max: f64) -> Self {
+        self.annotation = Some(format!("[{}:{}:{}]", min, step, max));
+        self
+    }
+
+    /// Adds a customizer drop down of the given options.
+    pub fn with_options<T: Into<ScadExpr>>(mut self, options: Vec<T>) -> Self {
+        let options: Vec<String> = options
+            .into_iter()
+            .map(|o| format!("{}", o.into()))
+            .collect();
+        self.annotation = Some(format!("[{}]", options.join(", ")));
+        self
+    }
+
+    /// Adds a description of the variable.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// An expression referencing the variable.
+    pub fn expr(&self) -> ScadExpr {
+        ScadExpr::Var(self.name.clone())
+    }
+}
+
 /// Saves Scad objects to a file in a separate thread.
 ///
 /// Allows setting global $fa, $fs, or $fn. $fn overrides $fa and
@@ -767,6 +2163,10 @@ impl Default for TextParams {
 ///
 /// fn: The number of segments or faces in a circle.
 ///
+/// vars: ScadVars written at the top of the file for the OpenSCAD customizer.
+///
+/// viewport: The Viewport OpenSCAD opens the file with.
+///
 /// children: A list of one or more Scad objects separated and terminated with a semicolon.
 ///
 /// #patterns
@@ -780,117 +2180,206 @@ impl Default for TextParams {
 /// scad_file!('stack_size: usize', 'path: &str', fa='fa: f64', fs='fs: f64', 'children: Scad';);
 ///
 /// scad_file!('stack_size: usize', 'path: &str', fn='fn: u64', 'children: Scad';);
+///
+/// scad_file!('stack_size: usize', 'path: &str', vars=\['var: ScadVar', ...\], 'children: Scad';);
+///
+/// scad_file!('stack_size: usize', 'path: &str', viewport='viewport: Viewport', 'children: Scad';);
 #[macro_export]
 macro_rules! scad_file {
-    ($stack_size:expr, $path:expr, fa=$fa:expr, fs=$fs:expr, $($child:expr);+;) => {
+    (@write $stack_size:expr, $path:expr, $header:expr, $($child:expr);+;) => {
+        // the float format is thread local so carry it over to the new thread
+        let float_format = $crate::float_format();
         let t = fat_thread!($stack_size, {
-            let children = vec![$($child,)+];
-            let mut file = std::fs::File::create($path).unwrap();
-            file.write_all(format!("$fa={};\n", $fa).as_bytes()).unwrap();
-            file.write_all(format!("$fs={};\n", $fs).as_bytes()).unwrap();
-            for child in children {
-                let s = format!("{}", child);
-                file.write_all(s.as_bytes()).unwrap();
-            }
-            file.flush().unwrap();
+            $crate::with_float_format(float_format, || {
+                let header: String = $header;
+                let children = vec![$($child,)+];
+                let mut file = std::fs::File::create($path).unwrap();
+                file.write_all(header.as_bytes()).unwrap();
+                for child in children {
+                    let s = format!("{}", child);
+                    file.write_all(s.as_bytes()).unwrap();
+                }
+                file.flush().unwrap();
+            })
         });
         t.join().unwrap();
     };
+    ($stack_size:expr, $path:expr, viewport=$viewport:expr, $($child:expr);+;) => {
+        scad_file!(@write $stack_size, $path, Viewport::to_string(&$viewport), $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, vars=[$($var:expr),+ $(,)?], $($child:expr);+;) => {
+        scad_file!(@write $stack_size, $path, {
+            let vars: Vec<ScadVar> = vec![$($var,)+];
+            let mut header: String = vars.iter().map(|var| format!("{}", var)).collect();
+            header.push('\n');
+            header
+        }, $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, fa=$fa:expr, fs=$fs:expr, $($child:expr);+;) => {
+        scad_file!(@write $stack_size, $path, format!("$fa={};\n$fs={};\n", $fa, $fs), $($child);+;)
+    };
     ($stack_size:expr, $path:expr, fn=$fn:expr, $($child:expr);+;) => {
-        let t = fat_thread!($stack_size, {
-            let children = vec![$($child,)+];
-            let mut file = std::fs::File::create($path).unwrap();
-            file.write_all(format!("$fn={};\n", $fn).as_bytes()).unwrap();
-            for child in children {
-                let s = format!("{}", child);
-                file.write_all(s.as_bytes()).unwrap();
-            }
-            file.flush().unwrap();
-        });
-        t.join().unwrap();
+        scad_file!(@write $stack_size, $path, format!("$fn={};\n", $fn), $($child);+;)
     };
     ($stack_size:expr, $path:expr, fs=$fs:expr, $($child:expr);+;) => {
-        let t = fat_thread!($stack_size, {
-            let children = vec![$($child,)+];
-            let mut file = std::fs::File::create($path).unwrap();
-            file.write_all(format!("$fs={};\n", $fs).as_bytes()).unwrap();
-            for child in children {
-                let s = format!("{}", child);
-                file.write_all(s.as_bytes()).unwrap();
-            }
-            file.flush().unwrap();
-        });
-        t.join().unwrap();
+        scad_file!(@write $stack_size, $path, format!("$fs={};\n", $fs), $($child);+;)
     };
     ($stack_size:expr, $path:expr, fa=$fa:expr, $($child:expr);+;) => {
-        let t = fat_thread!($stack_size, {
-            let children = vec![$($child,)+];
-            let mut file = std::fs::File::create($path).unwrap();
-            file.write_all(format!("$fa={};\n", $fa).as_bytes()).unwrap();
-            for child in children {
-                let s = format!("{}", child);
-                file.write_all(s.as_bytes()).unwrap();
-            }
-            file.flush().unwrap();
-        });
-        t.join().unwrap();
+        scad_file!(@write $stack_size, $path, format!("$fa={};\n", $fa), $($child);+;)
     };
     ($stack_size:expr, $path:expr, $($child:expr);+;) => {
-        let t = fat_thread!($stack_size, {
-            let children = vec![$($child,)+];
-            let mut file = std::fs::File::create($path).unwrap();
-            for child in children {
-                let s = format!("{}", child);
-                file.write_all(s.as_bytes()).unwrap();
-            }
-            file.flush().unwrap();
-        });
-        t.join().unwrap();
+        scad_file!(@write $stack_size, $path, String::new(), $($child);+;)
     };
 }
 
-/// Constructive Solid Geometry union operation.
+/// Saves Scad objects to a file in a separate thread, returning any error.
 ///
-/// Combines multiple shapes into one.
+/// The same as scad_file! except the expansion is a `Result<(), ScadError>`
+/// instead of panicking when the file can't be written. Children are checked
+/// with Scad::validate before anything is written.
 ///
 /// #params
 ///
-/// Scad structs seperated by and ending with a seimicolon.
-#[macro_export]
-macro_rules! union {
-    ($($child:expr);+;) => {
-        Scad {
-            op: ScadOp::Union,
-            children: vec![$($child,)+],
-        }
-    };
-}
-
-/// Constructive Solid Geometry difference operation.
+/// stack_size: The size of the stack in megabytes.
 ///
-/// Subracts all subsequent shapes from the first shape.
+/// path: The path of the file to save.
 ///
-/// #params
+/// fa: The minimum angle between segments or faces.
 ///
-/// Scad structs seperated by and ending with a seimicolon.
-#[macro_export]
-macro_rules! difference {
-  ($($child:expr);+;) => {
-    Scad {
-      op: ScadOp::Difference,
-      children: vec![$($child,)+],
-    }
-  };
-}
-
-/// Constructive Solid Geometry intersection operation.
+/// fs: The minimum length of a segment or face.
 ///
-/// Yields the overlapping area of the given shapes.
+/// fn: The number of segments or faces in a circle.
 ///
-/// #params
+/// vars: ScadVars written at the top of the file for the OpenSCAD customizer.
 ///
-/// Scad structs seperated by and ending with a seimicolon.
-#[macro_export]
+/// viewport: The Viewport OpenSCAD opens the file with.
+///
+/// options: A ScadFileOptions with the stack size, header and module deduplication.
+/// The children are created on the calling thread with this pattern.
+///
+/// children: A list of one or more Scad objects separated and terminated with a semicolon.
+///
+/// #patterns
+///
+/// scad_file_result!('path: &str', options='options: ScadFileOptions', 'children: Scad';);
+///
+/// scad_file_result!('stack_size: usize', 'path: &str', 'children: Scad';);
+///
+/// scad_file_result!('stack_size: usize', 'path: &str', fa='fa: f64', 'children: Scad';);
+///
+/// scad_file_result!('stack_size: usize', 'path: &str', fs='fs: f64', 'children: Scad';);
+///
+/// scad_file_result!('stack_size: usize', 'path: &str', fa='fa: f64', fs='fs: f64', 'children: Scad';);
+///
+/// scad_file_result!('stack_size: usize', 'path: &str', fn='fn: u64', 'children: Scad';);
+///
+/// scad_file_result!('stack_size: usize', 'path: &str', vars=\['var: ScadVar', ...\], 'children: Scad';);
+///
+/// scad_file_result!('stack_size: usize', 'path: &str', viewport='viewport: Viewport', 'children: Scad';);
+#[macro_export]
+macro_rules! scad_file_result {
+    ($path:expr, options=$options:expr, $($child:expr);+;) => {
+        $options.save($path, vec![$($child,)+])
+    };
+    (@write $stack_size:expr, $path:expr, $header:expr, $($child:expr);+;) => {{
+        // the float format is thread local so carry it over to the new thread
+        let float_format = $crate::float_format();
+        match std::thread::Builder::new()
+            .stack_size($stack_size * 1024 * 1024)
+            .spawn(move || -> Result<(), ScadError> {
+                $crate::with_float_format(float_format, || {
+                    let header: String = $header;
+                    let children = vec![$($child,)+];
+                    for child in children.iter() {
+                        child.validate()?;
+                    }
+                    let mut file = std::fs::File::create($path)?;
+                    file.write_all(header.as_bytes())?;
+                    for child in children {
+                        let s = format!("{}", child);
+                        file.write_all(s.as_bytes())?;
+                    }
+                    file.flush()?;
+                    Ok(())
+                })
+            }) {
+            Ok(t) => match t.join() {
+                Ok(result) => result,
+                Err(payload) => Err(ScadError::from_panic(payload)),
+            },
+            Err(e) => Err(ScadError::from(e)),
+        }
+    }};
+    ($stack_size:expr, $path:expr, fa=$fa:expr, fs=$fs:expr, $($child:expr);+;) => {
+        scad_file_result!(@write $stack_size, $path, format!("$fa={};\n$fs={};\n", $fa, $fs), $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, fn=$fn:expr, $($child:expr);+;) => {
+        scad_file_result!(@write $stack_size, $path, format!("$fn={};\n", $fn), $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, fs=$fs:expr, $($child:expr);+;) => {
+        scad_file_result!(@write $stack_size, $path, format!("$fs={};\n", $fs), $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, fa=$fa:expr, $($child:expr);+;) => {
+        scad_file_result!(@write $stack_size, $path, format!("$fa={};\n", $fa), $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, vars=[$($var:expr),+ $(,)?], $($child:expr);+;) => {
+        scad_file_result!(@write $stack_size, $path, {
+            let vars: Vec<ScadVar> = vec![$($var,)+];
+            let mut header: String = vars.iter().map(|var| format!("{}", var)).collect();
+            header.push('\n');
+            header
+        }, $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, viewport=$viewport:expr, $($child:expr);+;) => {
+        scad_file_result!(@write $stack_size, $path, Viewport::to_string(&$viewport), $($child);+;)
+    };
+    ($stack_size:expr, $path:expr, $($child:expr);+;) => {
+        scad_file_result!(@write $stack_size, $path, String::new(), $($child);+;)
+    };
+}
+
+/// Constructive Solid Geometry union operation.
+///
+/// Combines multiple shapes into one.
+///
+/// #params
+///
+/// Scad structs seperated by and ending with a seimicolon.
+#[macro_export]
+macro_rules! union {
+    ($($child:expr);+;) => {
+        Scad {
+            op: ScadOp::Union,
+            children: vec![$($child,)+],
+        }
+    };
+}
+
+/// Constructive Solid Geometry difference operation.
+///
+/// Subracts all subsequent shapes from the first shape.
+///
+/// #params
+///
+/// Scad structs seperated by and ending with a seimicolon.
+#[macro_export]
+macro_rules! difference {
+  ($($child:expr);+;) => {
+    Scad {
+      op: ScadOp::Difference,
+      children: vec![$($child,)+],
+    }
+  };
+}
+
+/// Constructive Solid Geometry intersection operation.
+///
+/// Yields the overlapping area of the given shapes.
+///
+/// #params
+///
+/// Scad structs seperated by and ending with a seimicolon.
+#[macro_export]
 macro_rules! intersection {
     ($($child:expr);+;) => {
         Scad {
@@ -900,6 +2389,26 @@ macro_rules! intersection {
     };
 }
 
+/// Symmetric difference operation.
+///
+/// Yields the area in an odd number of the given shapes, useful for seeing what
+/// changed between two versions of a part. Written as unions of differences
+/// since OpenSCAD has no xor.
+///
+/// #params
+///
+/// Scad structs seperated by and ending with a seimicolon.
+#[macro_export]
+macro_rules! xor {
+    ($first:expr; $($child:expr);+;) => {
+        {
+            let mut result: Scad = $first;
+            $(result = result ^ $child;)+
+            result
+        }
+    };
+}
+
 /// Creates a circle.
 ///
 /// #params
@@ -1244,6 +2753,36 @@ macro_rules! polygon {
     };
 }
 
+/// Creates a polygon from several outlines e.g. a shape with holes.
+///
+/// The outlines are joined into one list of points and the paths are
+/// generated so each outline is its own path.
+///
+/// #params
+///
+/// outlines: The outlines that make up the polygon.
+///
+/// convexity: Number of inward curves, only for the preview.
+///
+/// expansion: The Scad struct literal.
+///
+/// #patterns
+///
+/// polygon_multi!('outline: Pt2s', ...);
+///
+/// polygon_multi!(convexity='convexity: u64', 'outline: Pt2s', ...);
+#[macro_export]
+macro_rules! polygon_multi {
+    (convexity=$convexity:expr, $($outline:expr),+ $(,)?) => {{
+        let (points, paths) = dim2::merge_outlines(&[$($outline),+]);
+        polygon!(points, paths, $convexity)
+    }};
+    ($($outline:expr),+ $(,)?) => {{
+        let (points, paths) = dim2::merge_outlines(&[$($outline),+]);
+        polygon!(points, paths)
+    }};
+}
+
 /// Creates text.
 ///
 /// #params
@@ -1811,6 +3350,52 @@ macro_rules! cube {
     };
 }
 
+/// Create a cube with sizes in inches.
+///
+/// The same as cube! with the sizes converted to millimeters.
+///
+/// #params
+///
+/// size: The size of a side of the cube in inches.
+///
+/// center: Whether to center the cube or leave in the first octant.
+///
+/// [x, y, z]: The dimensions of the cube in inches.
+///
+/// expansion: Scad struct literal.
+///
+/// #patterns
+///
+/// cube_in!('size: f64');
+///
+/// cube_in!('size: f64', 'center: bool');
+///
+/// cube_in!(\['x: f64', 'y: f64', 'z: f64'\]);
+///
+/// cube_in!(\['x: f64', 'y: f64', 'z: f64'\], 'center: bool');
+#[macro_export]
+macro_rules! cube_in {
+    ([$x:expr, $y:expr, $z:expr], $center:expr) => {
+        $crate::cube!(
+            [
+                ($x) * $crate::MM_PER_INCH,
+                ($y) * $crate::MM_PER_INCH,
+                ($z) * $crate::MM_PER_INCH
+            ],
+            $center
+        )
+    };
+    ([$x:expr, $y:expr, $z:expr]) => {
+        $crate::cube_in!([$x, $y, $z], false)
+    };
+    ($size:expr, $center:expr) => {
+        $crate::cube_in!([$size, $size, $size], $center)
+    };
+    ($size:expr) => {
+        $crate::cube_in!([$size, $size, $size], false)
+    };
+}
+
 /// Creates a cylinder.
 ///
 /// #params
@@ -2309,6 +3894,74 @@ macro_rules! cylinder {
     };
 }
 
+/// Creates a cylinder with sizes in inches.
+///
+/// The same as cylinder! with the sizes converted to millimeters.
+///
+/// #params
+///
+/// height: The height of the cylinder in inches.
+///
+/// radius: The radius of the cylinder in inches.
+///
+/// radius1: The radius at the bottom in inches.
+///
+/// radius2: The radius at the top in inches.
+///
+/// diameter: The diameter of the cylinder in inches.
+///
+/// center: Center the cylinder on the Z axis, otherwise the bottom is at Z = 0.
+///
+/// fn: The number of segments in a circle.
+///
+/// expansion: Scad struct literal.
+///
+/// #patterns
+///
+/// cylinder_in!('height: f64', 'radius: f64')
+///
+/// cylinder_in!('height: f64', 'radius: f64', fn='fn: u64')
+///
+/// cylinder_in!('height: f64', d='diameter: f64')
+///
+/// cylinder_in!('height: f64', d='diameter: f64', fn='fn: u64')
+///
+/// cylinder_in!('height: f64', 'radius1: f64', 'radius2: f64', 'center: bool')
+///
+/// cylinder_in!('height: f64', 'radius1: f64', 'radius2: f64', 'center: bool', fn='fn: u64')
+#[macro_export]
+macro_rules! cylinder_in {
+    ($height:expr, d=$diameter:expr, fn=$fn:expr) => {
+        $crate::cylinder_in!($height, ($diameter) / 2.0, ($diameter) / 2.0, false, fn=$fn)
+    };
+    ($height:expr, d=$diameter:expr) => {
+        $crate::cylinder_in!($height, ($diameter) / 2.0, ($diameter) / 2.0, false)
+    };
+    ($height:expr, $radius1:expr, $radius2:expr, $center:expr, fn=$fn:expr) => {
+        $crate::cylinder!(
+            ($height) * $crate::MM_PER_INCH,
+            ($radius1) * $crate::MM_PER_INCH,
+            ($radius2) * $crate::MM_PER_INCH,
+            $center,
+            fn=$fn
+        )
+    };
+    ($height:expr, $radius1:expr, $radius2:expr, $center:expr) => {
+        $crate::cylinder!(
+            ($height) * $crate::MM_PER_INCH,
+            ($radius1) * $crate::MM_PER_INCH,
+            ($radius2) * $crate::MM_PER_INCH,
+            $center
+        )
+    };
+    ($height:expr, $radius:expr, fn=$fn:expr) => {
+        $crate::cylinder_in!($height, $radius, $radius, false, fn=$fn)
+    };
+    ($height:expr, $radius:expr) => {
+        $crate::cylinder_in!($height, $radius, $radius, false)
+    };
+}
+
 /// Creates a polyhedron.
 ///
 /// #params
@@ -2498,7 +4151,7 @@ macro_rules! linear_extrude {
 /// rotate_extrude!(angle='angle: f64', convexity='convexity: u64', fs='fs: f64', 'children: Scad';);
 ///
 /// rotate_extrude!(angle='angle: f64', convexity='convexity: u64', fa='fa: f64', fs='fs: f64', 'children: Scad';);
-
+///
 /// rotate_extrude!(angle='angle: f64', convexity='convexity: u64', fn='fn: f64', 'children: Scad';);
 #[macro_export]
 macro_rules! rotate_extrude {
@@ -2692,7 +4345,7 @@ macro_rules! translate {
 /// rotate!('z: f64', 'children: Scad';);
 ///
 /// rotate!('a: f64, \['x: f64', 'y: f64', 'z: f64'\], 'children: Scad';);
-
+///
 /// rotate!(a='a: f64, v=\['x: f64', 'y: f64', 'z: f64'\], 'children: Scad';);
 #[macro_export]
 macro_rules! rotate {
@@ -3094,6 +4747,69 @@ macro_rules! offset {
     };
 }
 
+/// Forces OpenSCAD to fully render the children when previewing.
+///
+/// Used on heavy boolean subtrees that are slow or glitchy in the preview.
+///
+/// #params
+///
+/// convexity: The number of outside edges a ray might encounter when passing through the object.
+///
+/// children: A list of Scad objects separated and ending with a semicolon.
+///
+/// expansion: A Scad struct literal.
+///
+/// #patterns
+///
+/// render!('children: Scad';);
+///
+/// render!('convexity: u64', 'children: Scad';);
+#[macro_export]
+macro_rules! render {
+  ($convexity:expr, $($child:expr);+;) => {
+    Scad {
+        op: ScadOp::Render {
+            convexity: $convexity
+        },
+        children: vec![$($child,)+],
+    }
+  };
+  ($($child:expr);+;) => {
+    Scad {
+        op: ScadOp::Render {
+            convexity: 1
+        },
+        children: vec![$($child,)+],
+    }
+  };
+}
+
+/// Uses one Scad in the OpenSCAD preview and another in the render.
+///
+/// Useful for showing a cheap version of a part while previewing e.g. fewer
+/// segments or no threads.
+///
+/// #params
+///
+/// preview: The Scad used when $preview is true.
+///
+/// render: The Scad used when rendering.
+///
+/// expansion: A Scad struct literal.
+///
+/// #patterns
+///
+/// if_preview!('preview: Scad'; 'render: Scad';);
+#[macro_export]
+macro_rules! if_preview {
+    ($preview:expr; $render:expr;) => {
+        Scad {
+            op: ScadOp::IfPreview,
+            children: vec![$preview, $render],
+        }
+    };
+}
+
 /// Constructive Solid Geometry hull operation.
 ///
 /// Combines multiple shapes into one.
@@ -3145,13 +4861,64 @@ macro_rules! minkowski {
     }
   };
 }
+
+/// A comment in the OpenSCAD output.
+///
+/// Multi-line text is written as one comment per line.
+///
+/// #params
+///
+/// text: The text of the comment, a format string with arguments.
+///
+/// expansion: A Scad struct literal.
+///
+/// #patterns
+///
+/// comment!('text: &str');
+///
+/// comment!('format: &str', 'args', ...);
+#[macro_export]
+macro_rules! comment {
+  ($($arg:tt)+) => {
+    Scad {
+        op: ScadOp::Comment(format!($($arg)+)),
+        children: Vec::new(),
+    }
+  };
+}
+
+/// OpenSCAD code written to the output as is.
+///
+/// Used for OpenSCAD features the crate doesn't model.
+///
+/// #params
+///
+/// code: The OpenSCAD code, a format string with arguments.
+///
+/// expansion: A Scad struct literal.
+///
+/// #patterns
+///
+/// raw_scad!('code: &str');
+///
+/// raw_scad!('format: &str', 'args', ...);
+#[macro_export]
+macro_rules! raw_scad {
+  ($($arg:tt)+) => {
+    Scad {
+        op: ScadOp::Raw(format!($($arg)+)),
+        children: Vec::new(),
+    }
+  };
+}
+
 /***********************************************************
 * TESTING 1, 2, 3...
 ***********************************************************/
 
 #[cfg(test)]
 mod tests {
-    use crate::prelude::*;
+    use crate::{approx_eq, prelude::*};
     #[test]
     fn union_of_1() {
         let res = union!(circle!(1.0););
@@ -5790,4 +7557,3936 @@ mod tests {
                 }
         )
     }
+
+    #[test]
+    fn text_on_path_follows_path() {
+        let params = TextParams {
+            text: "a b".to_string(),
+            ..Default::default()
+        };
+        let path = Pt2s::from_pt2s(vec![Pt2::new(0.0, 0.0), Pt2::new(0.0, 10.0)]);
+        let res = Scad::text_on_path(&params, &path, 2.0, 4.0);
+        assert!(res.children.len() == 2);
+        assert!(
+            res.children[1].op
+                == ScadOp::Translate {
+                    v: Pt3::new(0.0, 10.0, 0.0)
+                }
+        );
+        assert!(
+            res.children[1].children[0].op
+                == ScadOp::Rotate {
+                    a: Some(90.0),
+                    a_is_scalar: true,
+                    v: Pt3::new(0.0, 0.0, 0.0)
+                }
+        );
+    }
+
+    #[test]
+    fn call_with_var() {
+        let height = ScadVar::new("height", 10.0).with_range(1.0, 100.0);
+        assert!(format!("{}", height) == "height = 10; // [1:100]\n");
+        let res = Scad::call(
+            "cube",
+            vec![(
+                "size",
+                vec![ScadExpr::from(2.0), 3.0.into(), (&height).into()].into(),
+            )],
+            Vec::new(),
+        );
+        assert!(format!("{}", res) == "cube(size=[2, 3, height]);\n");
+    }
+
+    #[test]
+    fn expression_fields() {
+        let width = ScadVar::new("width", 20.0).with_range(5.0, 50.0);
+        let height = ScadVar::new("height", 10.0);
+        let block = Scad::translate_expr(
+            vec![
+                ScadExpr::Raw("-width / 2".to_string()),
+                0.0.into(),
+                0.0.into(),
+            ],
+            vec![Scad::cube_expr(
+                vec![width.expr(), width.expr(), (&height).into()],
+                false,
+            )],
+        );
+        assert!(
+            block.to_string()
+                == "translate(v=[-width / 2, 0, 0]) {\ncube(size=[width, width, height], center=false);\n}\n"
+        );
+        assert!(
+            Scad::cylinder_expr(&height, width.expr(), true).to_string()
+                == "cylinder(h=height, r=width, center=true);\n"
+        );
+        assert!(Scad::circle_expr(&width).to_string() == "circle(r=width);\n");
+        assert!(
+            Scad::rotate_expr(
+                Pt3::new(0.0, 0.0, 45.0),
+                vec![Scad::square_expr(&width, true)]
+            )
+            .to_string()
+                == "rotate(a=[0, 0, 45]) {\nsquare(size=width, center=true);\n}\n"
+        );
+        let extruded = Scad::linear_extrude_expr(&height, vec![Scad::circle_expr(2.0)]);
+        assert!(extruded.to_string() == "linear_extrude(height=height) {\ncircle(r=2);\n}\n");
+        assert!(
+            Scad::scale_expr(vec![&width, &width], vec![Scad::sphere_expr(1.0)]).to_string()
+                == "scale(v=[width, width]) {\nsphere(r=1);\n}\n"
+        );
+
+        // Strings use the OpenSCAD escapes rather than the Rust ones.
+        let label = ScadVar::new("label", "say \"hi\"\\\n\u{1b}é");
+        assert!(label.to_string() == "label = \"say \\\"hi\\\"\\\\\\n\\x1bé\";\n");
+    }
+
+    #[test]
+    fn pt3s_path_utilities() {
+        let path = Pt3s::from_pt3s(vec![
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(10.0, 0.0, 0.0),
+            Pt3::new(10.0, 0.0, 5.0),
+        ]);
+        assert!(path.length() == 15.0);
+        assert!(path.cumulative_lengths() == vec![0.0, 10.0, 15.0]);
+        assert!(path.point_at_distance(12.0) == Pt3::new(10.0, 0.0, 2.0));
+        assert!(path.point_at_distance(20.0) == Pt3::new(10.0, 0.0, 5.0));
+        assert!(path.closest_point(Pt3::new(4.0, 3.0, -1.0)) == Pt3::new(4.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn polyhedron_csg() {
+        let square = Pt2s::from_pt2s(vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(0.0, 10.0),
+            Pt2::new(10.0, 10.0),
+            Pt2::new(10.0, 0.0),
+        ]);
+        let mut a = Polyhedron::linear_extrude(&square, 10.0);
+        a.set_group(1);
+        let mut b = Polyhedron::linear_extrude(&square, 10.0);
+        b.translate(Pt3::new(5.0, 5.0, 5.0));
+        b.set_group(2);
+
+        let res = a.intersection(&b);
+        assert!(res
+            .points
+            .iter()
+            .all(|p| (0..3).all(|i| p[i] >= 5.0 && p[i] <= 10.0)));
+        assert!(res.faces.len() == res.face_groups.len());
+        assert!(res.face_groups.contains(&1) && res.face_groups.contains(&2));
+
+        let res = a.union(&b);
+        assert!(res.points.iter().any(|p| *p == Pt3::new(15.0, 15.0, 15.0)));
+        assert!(res.points.iter().all(|p| *p != Pt3::new(10.0, 10.0, 10.0)));
+
+        let res = a.difference(&b);
+        assert!(res.points.iter().any(|p| *p == Pt3::new(5.0, 5.0, 5.0)));
+        assert!(res.points.iter().all(|p| (0..3).all(|i| p[i] <= 10.0)));
+    }
+
+    #[test]
+    fn pt3s_smooth_and_fillet() {
+        let path = Pt3s::from_pt3s(vec![
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(10.0, 0.0, 0.0),
+            Pt3::new(10.0, 10.0, 0.0),
+        ]);
+        let smoothed = path.smooth(1);
+        assert!(smoothed.len() == 4);
+        assert!(smoothed[0] == path[0] && smoothed[3] == path[2]);
+        assert!(smoothed[1] == Pt3::new(7.5, 0.0, 0.0));
+
+        let filleted = path.fillet_corners(2.0, 4);
+        assert!(filleted.len() == 7);
+        assert!(approx_eq(filleted[1].x, 8.0, 1.0e-9) && approx_eq(filleted[1].y, 0.0, 1.0e-9));
+        assert!(approx_eq(filleted[5].x, 10.0, 1.0e-9) && approx_eq(filleted[5].y, 2.0, 1.0e-9));
+        let center = Pt3::new(8.0, 2.0, 0.0);
+        assert!(filleted[1..6]
+            .iter()
+            .all(|p| approx_eq((*p - center).len(), 2.0, 1.0e-9)));
+    }
+
+    #[test]
+    fn dish_primitives() {
+        let volume = |p: &Polyhedron| {
+            let mut v = 0.0;
+            for f in p.faces.iter() {
+                for j in 1..f.len() - 1 {
+                    let a = p.points[f[0] as usize];
+                    let b = p.points[f[j] as usize];
+                    let c = p.points[f[j + 1] as usize];
+                    v -= a.dot(b.cross(c)) / 6.0;
+                }
+            }
+            v
+        };
+        let pi = std::f64::consts::PI;
+        let dish = Polyhedron::dish(10.0, 4.0, 128);
+        let sphere_radius = (100.0 + 16.0) / 8.0;
+        let expected = pi * 16.0 * (3.0 * sphere_radius - 4.0) / 3.0;
+        assert!(approx_eq(volume(&dish), expected, expected * 0.01));
+        assert!(dish.points[0] == Pt3::new(0.0, 0.0, 4.0));
+
+        let cap = Polyhedron::paraboloid_cap(10.0, 5.0, 128);
+        let expected = pi * 100.0 * 5.0 / 2.0;
+        assert!(approx_eq(volume(&cap), expected, expected * 0.01));
+
+        let cap = Polyhedron::ellipsoid_cap(10.0, 5.0, 128);
+        let expected = 2.0 * pi * 100.0 * 5.0 / 3.0;
+        assert!(approx_eq(volume(&cap), expected, expected * 0.01));
+    }
+
+    #[test]
+    fn chamfered_and_rounded_cylinder() {
+        let res = Scad::chamfered_cylinder(5.0, 10.0, 1.0, 36, false);
+        assert!(matches!(res.op, ScadOp::RotateExtrude { .. }));
+        match &res.children[0].op {
+            ScadOp::Polygon { points, .. } => {
+                assert!(points.len() == 6);
+                assert!(points[3] == Pt2::new(5.0, 9.0));
+            }
+            _ => panic!(),
+        }
+        let res = Scad::rounded_cylinder(5.0, 10.0, 1.0, 36, true);
+        assert!(matches!(res.op, ScadOp::Translate { .. }));
+        match &res.children[0].children[0].op {
+            ScadOp::Polygon { points, .. } => {
+                assert!(points.len() == 22);
+                assert!(points
+                    .iter()
+                    .all(|p| p.x <= 5.0 + 1.0e-9 && p.y <= 10.0 + 1.0e-9));
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn socket_and_countersunk_screws() {
+        let res = metric_thread::socket_head_cap_screw(3, 10.0, 36, 180.0, false, false);
+        assert!(res.bom().to_string() == "1x M3x10 socket head cap screw\n");
+        let res = &res.children[0];
+        assert!(res.op == ScadOp::Union);
+        assert!(res.children[1].op == ScadOp::Difference);
+        assert!(matches!(
+            res.children[1].children[0].op,
+            ScadOp::RotateExtrude { .. }
+        ));
+        let res = metric_thread::countersunk_screw(6, 10.0, 36, 180.0, false, true);
+        let res = &res.children[0];
+        assert!(
+            res.op
+                == ScadOp::Translate {
+                    v: Pt3::new(0.0, 0.0, -(3.72 + 10.0) / 2.0)
+                }
+        );
+        match res.children[0].children[1].children[0].op {
+            ScadOp::Cylinder {
+                height, radius1, ..
+            } => assert!(height == 3.72 && radius1 == 13.44 / 2.0),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn internal_chamfers() {
+        let res = Scad::internal_circle_chamfer(1.0, 0.5, 3.0, 360.0, 36);
+        match &res.children[0].op {
+            ScadOp::Polygon { points, .. } => {
+                assert!(points[2] == Pt2::new(3.0, 1.0));
+                assert!(points[3] == Pt2::new(4.0, 0.0));
+            }
+            _ => panic!(),
+        }
+        let res = Scad::internal_cylinder_chamfer(1.0, 0.5, 3.0, 10.0, 36, false);
+        assert!(res.op == ScadOp::Union && res.children.len() == 2);
+
+        let res = Scad::countersink(1.5, 3.0, 90.0, 10.0, 36);
+        match &res.children[0].op {
+            ScadOp::Polygon { points, .. } => {
+                assert!(approx_eq(points[4].y, -1.5, 1.0e-9));
+                assert!(points[5] == Pt2::new(1.5, -10.0));
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn thread_fit() {
+        let max_radius = |scad: &Scad| match &scad.children[0].op {
+            ScadOp::Polyhedron { points, .. } => points
+                .iter()
+                .map(|p| (p.x * p.x + p.y * p.y).sqrt())
+                .fold(0.0, f64::max),
+            _ => panic!(),
+        };
+        let exact = metric_thread::tap(6, 10.0, 36, false, false);
+        let loose =
+            metric_thread::tap_with_fit(6, 10.0, 36, false, false, metric_thread::ThreadFit::Loose);
+        assert!(approx_eq(
+            max_radius(&loose) - max_radius(&exact),
+            0.1,
+            1.0e-9
+        ));
+
+        let exact = metric_thread::threaded_rod(6, 10.0, 36, 0.0, 0.0, false, false);
+        let custom = metric_thread::threaded_rod_with_fit(
+            6,
+            10.0,
+            36,
+            0.0,
+            0.0,
+            false,
+            false,
+            metric_thread::ThreadFit::Custom(0.3),
+        );
+        assert!(approx_eq(
+            max_radius(&exact) - max_radius(&custom),
+            0.075,
+            1.0e-9
+        ));
+    }
+
+    #[test]
+    fn edge_masks() {
+        let res = mask::chamfer_edge(1.0, 10.0, true);
+        assert!(
+            res.op
+                == ScadOp::Translate {
+                    v: Pt3::new(0.0, 0.0, -5.0)
+                }
+        );
+        let res = mask::fillet_edge(2.0, 10.0, 16, false);
+        match &res.children[0].op {
+            ScadOp::Polygon { points, .. } => {
+                assert!(points.len() == 8);
+                assert!(approx_eq(points[2].x, 0.0, 1.0e-9) && approx_eq(points[2].y, 2.0, 1.0e-9));
+                assert!(approx_eq(points[6].x, 2.0, 1.0e-9) && approx_eq(points[6].y, 0.0, 1.0e-9));
+            }
+            _ => panic!(),
+        }
+        let res = mask::chamfer_cylinder(5.0, 1.0, 36);
+        match &res.children[0].op {
+            ScadOp::Polygon { points, .. } => {
+                assert!(points.iter().any(|p| *p == Pt2::new(4.0, 0.0)));
+                assert!(points.iter().any(|p| *p == Pt2::new(5.0, 1.0)));
+            }
+            _ => panic!(),
+        }
+        assert!(matches!(
+            mask::chamfer_corner(1.0).op,
+            ScadOp::Polyhedron { .. }
+        ));
+        assert!(mask::fillet_corner(1.0, 16).op == ScadOp::Difference);
+    }
+
+    #[test]
+    fn trapezoidal_leadscrew() {
+        let res = trapezoidal_thread::leadscrew(8.0, 2.0, 4, 20.0, 36, false, false);
+        assert!(res.op == ScadOp::Union && res.children.len() == 5);
+        match &res.children[1].op {
+            ScadOp::Polyhedron { points, .. } => {
+                let radii: Vec<f64> = points
+                    .iter()
+                    .map(|p| (p.x * p.x + p.y * p.y).sqrt())
+                    .collect();
+                assert!(approx_eq(
+                    radii.iter().fold(0.0, |a, b| b.max(a)),
+                    4.0,
+                    1.0e-9
+                ));
+                assert!(approx_eq(
+                    radii.iter().fold(4.0, |a, b| b.min(a)),
+                    2.75,
+                    1.0e-9
+                ));
+                assert!(points.iter().all(|p| p.z >= 0.0 && p.z <= 20.0 + 1.0e-9));
+            }
+            _ => panic!(),
+        }
+        let res = trapezoidal_thread::leadscrew_nut(8.0, 2.0, 4, 22.0, 10.0, 36, false, false);
+        assert!(res.op == ScadOp::Difference);
+    }
+
+    #[test]
+    fn fillet_booleans() {
+        let a = square!(10.0);
+        let b = translate!([5.0, 5.0, 0.0], circle!(3.0););
+        let res = Scad::fillet_union_2d(&a, &b, 1.0);
+        assert!(res.op == ScadOp::Union);
+        assert!(res.children[0] == union!(a.clone(); b.clone();));
+        assert!(
+            res.children[1].children[1].op
+                == ScadOp::Offset {
+                    r: Some(-1.0),
+                    delta: None,
+                    chamfer: false
+                }
+        );
+        let res = Scad::fillet_difference(&cube!(10.0), &cylinder!(20.0, 2.0), 1.0, 16);
+        assert!(res.op == ScadOp::Union);
+        assert!(res.children[0].op == ScadOp::Difference);
+        assert!(res.children[1].op == ScadOp::Difference);
+    }
+
+    #[test]
+    fn dim2_booleans() {
+        let area = |polygons: &[Pt2s]| -> f64 {
+            let mut area = 0.0;
+            for p in polygons.iter() {
+                for i in 0..p.len() {
+                    let (a, b) = (p[i], p[(i + 1) % p.len()]);
+                    area -= (a.x * b.y - a.y * b.x) / 2.0;
+                }
+            }
+            area
+        };
+        let square = |x: f64, y: f64, size: f64| {
+            Pt2s::from_pt2s(vec![
+                Pt2::new(x, y),
+                Pt2::new(x, y + size),
+                Pt2::new(x + size, y + size),
+                Pt2::new(x + size, y),
+            ])
+        };
+        let a = vec![square(0.0, 0.0, 10.0)];
+        let b = vec![square(5.0, 5.0, 10.0)];
+        assert!(area(&dim2::union(&a, &b)) == 175.0);
+        assert!(area(&dim2::intersection(&a, &b)) == 25.0);
+        assert!(area(&dim2::difference(&a, &b)) == 75.0);
+        assert!(area(&dim2::xor(&a, &b)) == 150.0);
+
+        let res = dim2::union(&a, &[square(10.0, 0.0, 10.0)]);
+        assert!(res.len() == 1 && res[0].len() == 4);
+
+        let res = dim2::difference(&a, &[square(2.0, 2.0, 3.0)]);
+        assert!(res.len() == 2 && area(&res) == 91.0);
+    }
+
+    #[test]
+    fn metric_threaded_hole() {
+        let options = metric_thread::ThreadedHoleOptions {
+            counterbore_diameter: 6.5,
+            counterbore_depth: 3.0,
+            ..Default::default()
+        };
+        let res = metric_thread::threaded_hole(5, 10.0, &options);
+        assert!(res.op == ScadOp::Union);
+        assert!(
+            res.children[1].op
+                == ScadOp::Translate {
+                    v: Pt3::new(0.0, 0.0, -3.0)
+                }
+        );
+        assert!(
+            res.children[0].children[0].op
+                == ScadOp::Translate {
+                    v: Pt3::new(0.0, 0.0, -10.0)
+                }
+        );
+        let options = metric_thread::ThreadedHoleOptions {
+            chamfer: false,
+            ..Default::default()
+        };
+        let res = metric_thread::threaded_hole(5, 10.0, &options);
+        assert!(
+            res.op
+                == ScadOp::Translate {
+                    v: Pt3::new(0.0, 0.0, -10.0)
+                }
+        );
+    }
+
+    #[test]
+    fn bolt_patterns() {
+        let pattern = metric_thread::bolt_circle(6, 4, 20.0);
+        assert!(pattern.positions.len() == 4);
+        assert!(pattern.positions[0] == Pt2::new(10.0, 0.0));
+        assert!(approx_eq(pattern.positions[1].y, -10.0, 1.0e-9));
+        let holes = pattern.holes(5.0, 36);
+        assert!(holes.children.len() == 4);
+        match holes.children[0].children[0].children[0].op {
+            ScadOp::Cylinder {
+                height, radius1, ..
+            } => assert!(height == 7.0 && radius1 == 3.3),
+            _ => panic!(),
+        }
+
+        let pattern = metric_thread::rectangular_flange(4, 2, 2, 30.0, 20.0);
+        assert!(pattern.positions.contains(&Pt2::new(-15.0, -10.0)));
+        assert!(pattern.positions.contains(&Pt2::new(15.0, 10.0)));
+        assert!(pattern.nuts(5.0, 3.0, 36).children.len() == 4);
+    }
+
+    #[test]
+    fn dim2_offset() {
+        let area = |p: &Pt2s| {
+            let mut a = 0.0;
+            for i in 0..p.len() {
+                let (a0, b0) = (p[i], p[(i + 1) % p.len()]);
+                a += a0.x * b0.y - a0.y * b0.x;
+            }
+            -a / 2.0
+        };
+        let square = dim2::rounded_rect(10.0, 10.0, 0.0, 1, true);
+        let square = Pt2s::from_pt2s(vec![square[0], square[2], square[4], square[6]]);
+        assert!(approx_eq(area(&square), 100.0, 1.0e-9));
+        let miter = dim2::offset(&square, 1.0, dim2::JoinType::Miter);
+        assert!(approx_eq(area(&miter), 144.0, 1.0e-9));
+        let bevel = dim2::offset(&square, 1.0, dim2::JoinType::Bevel);
+        assert!(bevel.len() == 8);
+        assert!(approx_eq(area(&bevel), 142.0, 1.0e-9));
+        let round = dim2::offset(&square, 1.0, dim2::JoinType::Round(360));
+        assert!(approx_eq(
+            area(&round),
+            140.0 + std::f64::consts::PI,
+            1.0e-3
+        ));
+        let inset = dim2::offset(&square, -1.0, dim2::JoinType::Round(36));
+        assert!(inset.len() == 4);
+        assert!(approx_eq(area(&inset), 64.0, 1.0e-9));
+    }
+
+    #[test]
+    fn dim2_arcs_and_fillets() {
+        let arc = dim2::center_arc(Pt2::new(1.0, 1.0), 2.0, 90.0, 0.0, 4);
+        assert!(arc.len() == 5);
+        assert!(approx_eq(arc[0].x, 1.0, 1.0e-9) && approx_eq(arc[0].y, 3.0, 1.0e-9));
+        assert!(approx_eq(arc[4].x, 3.0, 1.0e-9) && approx_eq(arc[4].y, 1.0, 1.0e-9));
+
+        let fillet = dim2::fillet_between(
+            Pt2::new(0.0, 10.0),
+            Pt2::new(0.0, 0.0),
+            Pt2::new(10.0, 0.0),
+            2.0,
+            8,
+        );
+        assert!(fillet.len() == 9);
+        assert!(approx_eq(fillet[0].y, 2.0, 1.0e-9) && approx_eq(fillet[8].x, 2.0, 1.0e-9));
+        assert!(approx_eq(fillet[0].x, 0.0, 1.0e-9) && approx_eq(fillet[8].y, 0.0, 1.0e-9));
+        assert!(approx_eq(fillet[4].x, 2.0 - 2.0f64.sqrt(), 1.0e-9));
+        for p in fillet.iter() {
+            assert!(approx_eq((*p - Pt2::new(2.0, 2.0)).len(), 2.0, 1.0e-9));
+        }
+
+        let square = Pt2s::from_pt2s(vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(0.0, 10.0),
+            Pt2::new(10.0, 10.0),
+            Pt2::new(10.0, 0.0),
+        ]);
+        let rounded = dim2::fillet_corners(&square, 20.0, 4);
+        assert!(rounded.len() == 20);
+        assert!(approx_eq(rounded[2].x, 5.0 - 5.0 * 0.5f64.sqrt(), 1.0e-9));
+    }
+
+    #[test]
+    fn pipe_route() {
+        let waypoints = Pt3s::from_pt3s(vec![
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(20.0, 0.0, 0.0),
+            Pt3::new(20.0, 20.0, 0.0),
+            Pt3::new(20.0, 20.0, 20.0),
+        ]);
+        let pipe = Pipe::route(&waypoints, 6.0, 1.0, 5.0, 16);
+        assert!(matches!(pipe.op, ScadOp::Difference));
+        assert!(pipe.children.len() == 2);
+        match &pipe.children[0].op {
+            ScadOp::Polyhedron { points, .. } => assert!(points.len() == (2 + 2 * 5) * 16),
+            _ => panic!(),
+        }
+        match &pipe.children[1].op {
+            ScadOp::Polyhedron { points, .. } => assert!(points.len() == (4 + 2 * 5) * 16),
+            _ => panic!(),
+        }
+        assert!(matches!(
+            Pipe::route_solid(&waypoints, 6.0, 5.0, 16).op,
+            ScadOp::Polyhedron { .. }
+        ));
+    }
+
+    #[test]
+    fn label_sizing() {
+        let label = Label {
+            text: "ABCD".to_string(),
+            margin: 1.0,
+            ..Default::default()
+        };
+        match label.into_scad_2d(14.0, 20.0).op {
+            ScadOp::Text { size, .. } => assert!(approx_eq(size, 5.0, 1.0e-9)),
+            _ => panic!(),
+        }
+        match label.into_scad_2d(100.0, 6.0).op {
+            ScadOp::Text { size, .. } => assert!(size == 4.0),
+            _ => panic!(),
+        }
+
+        let qr = Label {
+            content: Some(square!(10.0, true)),
+            content_width: 10.0,
+            content_height: 10.0,
+            face: BoxFace::Front,
+            ..Default::default()
+        };
+        let part = cube!([30.0, 10.0, 20.0]);
+        let engraved = qr.engrave(&part, Pt3::new(0.0, 0.0, 0.0), Pt3::new(30.0, 10.0, 20.0));
+        assert!(matches!(engraved.op, ScadOp::Difference));
+        let placed = &engraved.children[1];
+        match placed.op {
+            ScadOp::Translate { v } => assert!(v == Pt3::new(15.0, 0.0, 10.0)),
+            _ => panic!(),
+        }
+        let extrude = &placed.children[0].children[0].children[0];
+        match extrude.children[0].op {
+            ScadOp::Scale { v } => assert!(v == Pt3::new(1.6, 1.6, 1.0)),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn sweep_with_options() {
+        let volume = |p: &Polyhedron| {
+            let mut v = 0.0;
+            for f in p.faces.iter() {
+                for i in 1..f.len() - 1 {
+                    let (a, b, c) = (
+                        p.points[f[0] as usize],
+                        p.points[f[i] as usize],
+                        p.points[f[i + 1] as usize],
+                    );
+                    v -= a.dot(b.cross(c)) / 6.0;
+                }
+            }
+            v
+        };
+        let square = Pt2s::from_pt2s(vec![
+            Pt2::new(-1.0, -1.0),
+            Pt2::new(-1.0, 1.0),
+            Pt2::new(1.0, 1.0),
+            Pt2::new(1.0, -1.0),
+        ]);
+        let vertical = Pt3s::from_pt3s(vec![Pt3::new(0.0, 0.0, 0.0), Pt3::new(0.0, 0.0, 10.0)]);
+        let p = Polyhedron::sweep_with_options(&square, &vertical, &SweepOptions::default());
+        assert!(approx_eq(volume(&p), 40.0, 1.0e-9));
+
+        let options = SweepOptions {
+            scales: vec![1.0, 0.5],
+            ..Default::default()
+        };
+        let p = Polyhedron::sweep_with_options(&square, &vertical, &options);
+        assert!(approx_eq(volume(&p), 70.0 / 3.0, 1.0e-9));
+
+        let options = SweepOptions {
+            caps: false,
+            ..Default::default()
+        };
+        assert!(
+            Polyhedron::sweep_with_options(&square, &vertical, &options)
+                .faces
+                .len()
+                == 4
+        );
+
+        // the profile must not flip where the path turns vertical
+        let bend = Pt3s::from_pt3s(vec![
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(10.0, 0.0, 0.0),
+            Pt3::new(10.0, 0.0, 10.0),
+            Pt3::new(10.0, 0.0, 20.0),
+        ]);
+        for frame in [
+            SweepFrame::Up(Pt3::new(0.0, 0.0, 1.0)),
+            SweepFrame::Frenet,
+            SweepFrame::RotationMinimizing,
+        ] {
+            let options = SweepOptions {
+                frame,
+                ..Default::default()
+            };
+            let p = Polyhedron::sweep_with_options(&square, &bend, &options);
+            for i in 0..4 {
+                assert!(approx_eq(p.points[12 + i].y, p.points[8 + i].y, 1.0e-9));
+            }
+            assert!(volume(&p) > 0.0);
+        }
+    }
+
+    #[test]
+    fn polyhedron_validate() {
+        let cube = Polyhedron::linear_extrude(&dim2::rounded_rect(2.0, 2.0, 0.0, 1, true), 2.0);
+        let report = cube.validate();
+        assert!(!report.duplicate_points.is_empty());
+        assert!(!report.degenerate_faces.is_empty());
+
+        let square = Pt2s::from_pt2s(vec![
+            Pt2::new(-1.0, -1.0),
+            Pt2::new(-1.0, 1.0),
+            Pt2::new(1.0, 1.0),
+            Pt2::new(1.0, -1.0),
+        ]);
+        let mut cube = Polyhedron::linear_extrude(&square, 2.0);
+        assert!(cube.validate().is_valid());
+
+        cube.faces[0].reverse();
+        let report = cube.validate();
+        assert!(report.inconsistent_edges.len() == 3);
+        assert!(!report.is_valid());
+
+        for face in cube.faces.iter_mut() {
+            face.reverse();
+        }
+        cube.faces[0].reverse();
+        let report = cube.validate();
+        assert!(report.inverted && report.inconsistent_edges.is_empty());
+
+        cube.faces.pop();
+        cube.faces.push(Indices::from_indices(vec![0, 1, 9]));
+        let report = cube.validate();
+        assert!(report.non_manifold_edges.len() == 4);
+        assert!(report.invalid_indices == vec![cube.faces.len() - 1]);
+    }
+
+    #[test]
+    fn threaded_rod_options() {
+        let options = metric_thread::ThreadedRodOptions {
+            lead_in_length: 0.5,
+            lead_out_length: 100.0,
+            thread_start: 5.0,
+            thread_length: 1.5,
+            ..Default::default()
+        };
+        let rod = metric_thread::threaded_rod_with_options(6, 20.0, &options);
+        let threads = &rod.children[1];
+        match threads.op {
+            ScadOp::Translate { v } => assert!(v.z == 5.0),
+            _ => panic!(),
+        }
+        match &threads.children[0].op {
+            ScadOp::Polyhedron { points, .. } => {
+                for p in points.iter() {
+                    assert!(p.z >= 0.0 && p.z <= 1.5 + 1.0e-9);
+                }
+                // a stub thread of 3/4 of a turn
+                assert!(points.len() / 4 < 36);
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn rng_streams() {
+        // first output of the reference mt19937ar.c with init_by_array
+        let mut mt = crate::MersenneTwister::with_seed_sequence(&[0x123, 0x234, 0x345, 0x456]);
+        assert!(mt.u32() == 1067595299);
+
+        let mut a = crate::MersenneTwister::with_seed_u64(0x1234_5678_9abc_def0);
+        let mut b = crate::MersenneTwister::with_seed_u64(0x1234_5678_9abc_def0);
+        let mut c = crate::MersenneTwister::with_seed_u64(0x9abc_def0_1234_5678);
+        let first = a.u32();
+        assert!(first == b.u32() && first != c.u32());
+
+        let mut s0 = crate::MersenneTwister::stream(7, 0);
+        let mut s1 = crate::MersenneTwister::stream(7, 1);
+        assert!(s0.u32() != s1.u32());
+        assert!(
+            crate::MersenneTwister::stream(7, 1).u32()
+                == crate::MersenneTwister::stream(7, 1).u32()
+        );
+
+        let mut split_a = a.split();
+        let mut split_b = b.split();
+        assert!(split_a.u32() == split_b.u32());
+
+        let mut jumped = crate::MersenneTwister::with_seed(42);
+        let mut stepped = crate::MersenneTwister::with_seed(42);
+        jumped.jump(1000);
+        for _ in 0..1000 {
+            stepped.u32();
+        }
+        assert!(jumped.u32() == stepped.u32());
+    }
+
+    #[test]
+    fn polyhedron_weld() {
+        let square = Pt2s::from_pt2s(vec![
+            Pt2::new(-1.0, -1.0),
+            Pt2::new(-1.0, 1.0),
+            Pt2::new(1.0, 1.0),
+            Pt2::new(1.0, -1.0),
+        ]);
+        let cube = Polyhedron::linear_extrude(&square, 2.0);
+        // give every face its own points like a mesh with seams
+        let mut points = Pt3s::new();
+        let mut faces = Faces::new();
+        for face in cube.faces.iter() {
+            let start = points.len() as u64;
+            for &i in face.iter() {
+                points.push(cube.points[i as usize]);
+            }
+            faces.push(Indices::from_indices(
+                (start..start + face.len() as u64).collect(),
+            ));
+        }
+        let mut cube = Polyhedron::new(points, faces);
+        cube.set_face_group(0, 3);
+        assert!(!cube.validate().non_manifold_edges.is_empty());
+        cube.weld(1.0e-6);
+        assert!(cube.points.len() == 8);
+        assert!(cube.faces.len() == cube.face_groups.len() && cube.face_groups[0] == 3);
+        assert!(cube.validate().is_valid());
+
+        let mut p = Polyhedron::new(
+            Pt3s::from_pt3s(vec![
+                Pt3::new(0.0, 0.0, 0.0),
+                Pt3::new(1.0, 0.0, 0.0),
+                Pt3::new(1.0e-7, 0.0, 0.0),
+                Pt3::new(0.0, 1.0, 0.0),
+            ]),
+            Faces::from_faces(vec![
+                Indices::from_indices(vec![0, 1, 2]),
+                Indices::from_indices(vec![2, 1, 3]),
+            ]),
+        );
+        p.weld(1.0e-6);
+        assert!(p.points.len() == 3);
+        assert!(p.faces.len() == 1 && p.faces[0] == Indices::from_indices(vec![0, 1, 2]));
+        assert!(p.face_groups.is_empty());
+    }
+
+    #[test]
+    fn polyhedron_fix_winding() {
+        let square = Pt2s::from_pt2s(vec![
+            Pt2::new(-1.0, -1.0),
+            Pt2::new(-1.0, 1.0),
+            Pt2::new(1.0, 1.0),
+            Pt2::new(1.0, -1.0),
+        ]);
+        let good = Polyhedron::linear_extrude(&square, 2.0);
+        let mut cube = good.clone();
+        cube.faces[0].reverse();
+        cube.faces[3].reverse();
+        let mut other = good.clone();
+        for face in other.faces.iter_mut() {
+            face.reverse();
+        }
+        other.translate(Pt3::new(5.0, 0.0, 0.0));
+        cube.merge(other);
+        assert!(!cube.validate().is_valid());
+        cube.fix_winding();
+        assert!(cube.validate().is_valid());
+
+        let mut inverted = good.clone();
+        for face in inverted.faces.iter_mut() {
+            face.reverse();
+        }
+        inverted.fix_winding();
+        assert!(inverted.faces == good.faces);
+    }
+
+    #[test]
+    fn mass_properties_and_test_helpers() {
+        let square = Pt2s::from_pt2s(vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(0.0, 2.0),
+            Pt2::new(2.0, 2.0),
+            Pt2::new(2.0, 0.0),
+        ]);
+        let cube = Polyhedron::linear_extrude(&square, 2.0);
+        assert_manifold!(cube);
+        assert_volume_close!(cube, 8.0, 1.0e-9);
+        assert!(approx_eq(cube.area(), 24.0, 1.0e-9));
+        assert!((cube.centroid() - Pt3::new(1.0, 1.0, 1.0)).len() < 1.0e-9);
+
+        let mut rng = crate::MersenneTwister::with_seed(1);
+        for _ in 0..20 {
+            let profile = testing::random_star_profile(&mut rng, 12, 2.0, 5.0);
+            let extruded = Polyhedron::linear_extrude(&profile, 3.0);
+            assert_manifold!(extruded);
+            let path = Pt3s::from_pt3s(vec![Pt3::new(0.0, 0.0, 0.0), Pt3::new(0.0, 0.0, 3.0)]);
+            let swept = Polyhedron::sweep_with_options(&profile, &path, &SweepOptions::default());
+            assert_manifold!(swept);
+            assert_volume_close!(swept, extruded.volume(), 1.0e-9);
+
+            let profile = testing::random_convex_profile(&mut rng, 8, 1.0);
+            let path = testing::random_path(&mut rng, 6, 4.0);
+            let options = SweepOptions {
+                frame: SweepFrame::RotationMinimizing,
+                ..Default::default()
+            };
+            assert_manifold!(Polyhedron::sweep_with_options(&profile, &path, &options));
+        }
+    }
+
+    #[test]
+    fn scad_snapshots() {
+        assert!(
+            testing::normalize_scad("cube(size=[1.00000001, -0.0000001, 2e-3]);  \r\n\n", 6)
+                == "cube(size=[1, 0, 0.002]);\n"
+        );
+        assert!(testing::normalize_scad("$fn=36; a1 = 2.50;", 6) == "$fn=36; a1 = 2.5;\n");
+
+        assert_scad_snapshot!(
+            difference!(
+                cube!(10.0, true);
+                sphere!(6.5, fn=24);
+            ),
+            "cube_minus_sphere"
+        );
+        assert_scad_snapshot!(mask::chamfer_edge(2.0, 10.0, false), "chamfer_edge_mask");
+
+        // A missing snapshot fails rather than being created.
+        if std::env::var("UPDATE_SNAPSHOTS").map_or(true, |v| v != "1") {
+            let dir = std::env::temp_dir().join(format!("scad_tree_snap_{}", std::process::id()));
+            let dir = dir.to_string_lossy().to_string();
+            let missing = std::panic::catch_unwind(|| {
+                testing::check_scad_snapshot(&cube!(1.0), &dir, "missing");
+            });
+            assert!(missing.is_err());
+            assert!(!std::path::Path::new(&dir).join("missing.scad").exists());
+        }
+    }
+
+    #[cfg(feature = "font")]
+    #[test]
+    fn font_outlines() {
+        fn be16(v: &mut Vec<u8>, x: i32) {
+            v.extend_from_slice(&(x as u16).to_be_bytes());
+        }
+        fn simple_glyph(contours: &[&[(i32, i32, bool)]]) -> Vec<u8> {
+            let mut g = Vec::new();
+            be16(&mut g, contours.len() as i32);
+            for _ in 0..4 {
+                be16(&mut g, 0);
+            }
+            let mut end = -1;
+            for c in contours {
+                end += c.len() as i32;
+                be16(&mut g, end);
+            }
+            be16(&mut g, 0);
+            let points: Vec<_> = contours.iter().flat_map(|c| c.iter()).collect();
+            for p in points.iter() {
+                g.push(u8::from(p.2));
+            }
+            for axis in 0..2 {
+                let mut last = 0;
+                for p in points.iter() {
+                    let v = if axis == 0 { p.0 } else { p.1 };
+                    be16(&mut g, v - last);
+                    last = v;
+                }
+            }
+            if g.len() % 2 == 1 {
+                g.push(0);
+            }
+            g
+        }
+
+        let a = simple_glyph(&[
+            &[
+                (0, 0, true),
+                (0, 1000, true),
+                (1000, 1000, true),
+                (1000, 0, true),
+            ],
+            &[
+                (250, 250, true),
+                (750, 250, true),
+                (750, 750, true),
+                (250, 750, true),
+            ],
+        ]);
+        let o = simple_glyph(&[&[
+            (0, 500, false),
+            (500, 1000, false),
+            (1000, 500, false),
+            (500, 0, false),
+        ]]);
+        let mut glyf = a.clone();
+        glyf.extend_from_slice(&o);
+        let mut loca = Vec::new();
+        for offset in [0, 0, a.len(), a.len() + o.len()] {
+            be16(&mut loca, offset as i32 / 2);
+        }
+        let mut head = vec![0u8; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut maxp = vec![0, 0, 0x50, 0];
+        be16(&mut maxp, 3);
+        let mut hhea = vec![0u8; 34];
+        be16(&mut hhea, 3);
+        let mut hmtx = Vec::new();
+        for advance in [500, 1000, 800] {
+            be16(&mut hmtx, advance);
+            be16(&mut hmtx, 0);
+        }
+        let mut cmap = Vec::new();
+        for v in [0, 1, 3, 1, 0, 12, 4, 40, 0, 6, 4, 1, 2] {
+            be16(&mut cmap, v);
+        }
+        for v in [
+            65,
+            79,
+            0xffff,
+            0,
+            65,
+            79,
+            0xffff,
+            1 - 65,
+            2 - 79,
+            1,
+            0,
+            0,
+            0,
+        ] {
+            be16(&mut cmap, v);
+        }
+
+        let font_data = |cmap: Vec<u8>| {
+            let tables: [(&[u8; 4], &[u8]); 7] = [
+                (b"cmap", &cmap),
+                (b"glyf", &glyf),
+                (b"head", &head),
+                (b"hhea", &hhea),
+                (b"hmtx", &hmtx),
+                (b"loca", &loca),
+                (b"maxp", &maxp),
+            ];
+            let mut data = vec![0, 1, 0, 0];
+            be16(&mut data, tables.len() as i32);
+            data.extend_from_slice(&[0; 6]);
+            let mut offset = 12 + 16 * tables.len();
+            for (tag, table) in tables.iter() {
+                data.extend_from_slice(*tag);
+                data.extend_from_slice(&[0; 4]);
+                data.extend_from_slice(&(offset as u32).to_be_bytes());
+                data.extend_from_slice(&(table.len() as u32).to_be_bytes());
+                offset += table.len();
+            }
+            for (_, table) in tables.iter() {
+                data.extend_from_slice(table);
+            }
+            data
+        };
+
+        // A format 12 map with a group whose glyph ids run past u16.
+        let mut cmap12 = Vec::new();
+        for v in [0, 1, 3, 10, 0, 12, 12, 0] {
+            be16(&mut cmap12, v);
+        }
+        for v in [52u32, 0, 3, 65, 65, 1, 79, 79, 2, 0x100, 0x101, 0xffff_ffff] {
+            cmap12.extend_from_slice(&v.to_be_bytes());
+        }
+        let font = crate::font::Font::from_bytes(font_data(cmap12)).unwrap();
+        assert!(approx_eq(
+            font.text_width("AO", 10.0).unwrap(),
+            18.0,
+            1.0e-9
+        ));
+        assert!(matches!(
+            font.text_width("\u{100}", 10.0),
+            Err(ScadError::Parse(_))
+        ));
+
+        let font = crate::font::Font::from_bytes(font_data(cmap)).unwrap();
+        assert!(approx_eq(
+            font.text_width("AO", 10.0).unwrap(),
+            18.0,
+            1.0e-9
+        ));
+        let outlines = font.text_outlines("AO", 10.0, 4).unwrap();
+        assert!(outlines.len() == 3);
+        let area = |p: &Pt2s| {
+            let mut a = 0.0;
+            for i in 0..p.len() {
+                let (a0, b0) = (p[i], p[(i + 1) % p.len()]);
+                a += a0.x * b0.y - a0.y * b0.x;
+            }
+            a / 2.0
+        };
+        assert!(outlines[0].len() == 4 && approx_eq(area(&outlines[0]), -100.0, 1.0e-9));
+        assert!(approx_eq(area(&outlines[1]), 25.0, 1.0e-9));
+        assert!(outlines[2].len() == 16);
+        for p in outlines[2].iter() {
+            assert!(p.x >= 10.0 && p.x <= 20.0 && p.y >= 0.0 && p.y <= 10.0);
+        }
+        assert!(area(&outlines[2]) < -25.0);
+        assert!(font.char_outlines('Z', 10.0, 4).unwrap().is_empty());
+
+        let (points, paths) = font.text_polygon("A", 10.0, 4).unwrap();
+        assert!(points.len() == 8 && paths.len() == 2);
+        assert!(crate::font::Font::from_bytes(vec![0; 8]).is_err());
+    }
+
+    #[test]
+    fn dxf_profiles() {
+        let dxf = [
+            "0",
+            "SECTION",
+            "2",
+            "HEADER",
+            "9",
+            "$ACADVER",
+            "1",
+            "AC1015",
+            "0",
+            "ENDSEC",
+            "0",
+            "SECTION",
+            "2",
+            "ENTITIES",
+            "0",
+            "LWPOLYLINE",
+            "8",
+            "0",
+            "90",
+            "4",
+            "70",
+            "1",
+            "10",
+            "0",
+            "20",
+            "0",
+            "10",
+            "10",
+            "20",
+            "0",
+            "10",
+            "10",
+            "20",
+            "10",
+            "10",
+            "0",
+            "20",
+            "10",
+            "42",
+            "1.0",
+            "0",
+            "LINE",
+            "10",
+            "20",
+            "20",
+            "0",
+            "11",
+            "30",
+            "21",
+            "0",
+            "0",
+            "LINE",
+            "10",
+            "30",
+            "20",
+            "0",
+            "11",
+            "30",
+            "21",
+            "10",
+            "0",
+            "ARC",
+            "10",
+            "25",
+            "20",
+            "10",
+            "40",
+            "5",
+            "50",
+            "0",
+            "51",
+            "180",
+            "0",
+            "LINE",
+            "10",
+            "20",
+            "20",
+            "0",
+            "11",
+            "20",
+            "21",
+            "10",
+            "0",
+            "CIRCLE",
+            "10",
+            "50",
+            "20",
+            "0",
+            "40",
+            "2",
+            "0",
+            "LINE",
+            "10",
+            "100",
+            "20",
+            "0",
+            "11",
+            "101",
+            "21",
+            "0",
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ]
+        .join("\n");
+        let profiles = dim2::from_dxf_str(&dxf, 360).unwrap();
+        assert!(profiles.len() == 4);
+        let area = |p: &Pt2s| {
+            let mut a = 0.0;
+            for i in 0..p.len() {
+                let (a0, b0) = (p[i], p[(i + 1) % p.len()]);
+                a += a0.x * b0.y - a0.y * b0.x;
+            }
+            a / 2.0
+        };
+        let d_shape = -100.0 - std::f64::consts::PI * 12.5;
+        assert!(approx_eq(area(&profiles[0]), d_shape, 0.01));
+        assert!(profiles[0].iter().any(|p| approx_eq(p.x, -5.0, 1.0e-9)));
+        assert!(approx_eq(
+            area(&profiles[1]),
+            -std::f64::consts::PI * 4.0,
+            0.01
+        ));
+        assert!(approx_eq(area(&profiles[2]), d_shape, 0.01));
+        assert!(profiles[3].len() == 2);
+        assert!(dim2::from_dxf_str("0\nSECTION\nx\n", 36).is_err());
+    }
+
+    #[test]
+    fn dim2_profiles() {
+        let area = |p: &Pt2s| {
+            let mut a = 0.0;
+            for i in 0..p.len() {
+                let (a0, b0) = (p[i], p[(i + 1) % p.len()]);
+                a += a0.x * b0.y - a0.y * b0.x;
+            }
+            -a / 2.0
+        };
+        let rect = dim2::rect(4.0, 2.0, false);
+        assert!(rect.len() == 4 && approx_eq(area(&rect), 8.0, 1.0e-9));
+        assert!(dim2::rect(4.0, 2.0, true)[0] == Pt2::new(-2.0, -1.0));
+
+        let fillet = dim2::fillet(2.0, 0.5, 90);
+        let expected = 2.5 * 2.5 - std::f64::consts::PI;
+        assert!(approx_eq(area(&fillet), expected, 1.0e-3));
+        let chamfer = dim2::chamfer(2.0, 0.5);
+        assert!(approx_eq(area(&chamfer), 3.375, 1.0e-9));
+
+        let ellipse = dim2::ellipse(3.0, 1.0, 360);
+        assert!(approx_eq(
+            area(&ellipse),
+            3.0 * std::f64::consts::PI,
+            1.0e-3
+        ));
+
+        let slot = dim2::slot(10.0, 1.0, 360, true);
+        assert!(approx_eq(area(&slot), 20.0 + std::f64::consts::PI, 1.0e-3));
+        assert!(approx_eq(slot[0].x, 5.0, 1.0e-9) && approx_eq(slot[0].y, 1.0, 1.0e-9));
+    }
+
+    #[test]
+    fn polygon_multi_paths() {
+        let res = polygon_multi!(dim2::rect(10.0, 10.0, true), dim2::circle(2.0, 6));
+        match res.op {
+            ScadOp::Polygon {
+                points,
+                paths,
+                convexity,
+            } => {
+                assert!(points.len() == 10 && convexity == 1);
+                let paths = paths.unwrap();
+                assert!(paths.len() == 2);
+                assert!(paths[0] == Indices::from_indices(vec![0, 1, 2, 3]));
+                assert!(paths[1] == Indices::from_indices(vec![4, 5, 6, 7, 8, 9]));
+            }
+            _ => panic!(),
+        }
+        let res = polygon_multi!(convexity = 3, dim2::rect(1.0, 1.0, false),);
+        match res.op {
+            ScadOp::Polygon { convexity, .. } => assert!(convexity == 3),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn pretty_string() {
+        let scad = translate!([1.0, 2.0, 3.0], union!(cube!(1.0); sphere!(1.0, fn=8);););
+        assert!(
+            scad.to_pretty_string(2)
+                == "translate(v=[1, 2, 3]) {\n  union() {\n    cube(size=[1, 1, 1], center=false);\n    sphere(r=1, $fn=8);\n  }\n}\n"
+        );
+        assert!(scad.to_pretty_string(0) == format!("{}", scad));
+        let tabs = scad.format_with(&ScadFormat {
+            indent: "\t".to_string(),
+            line_break: "\r\n".to_string(),
+            ..Default::default()
+        });
+        assert!(tabs.starts_with("translate(v=[1, 2, 3]) {\r\n\tunion() {\r\n\t\tcube("));
+        assert!(tabs.ends_with("\t}\r\n}\r\n"));
+        assert!(
+            format!(
+                "{}",
+                Scad {
+                    op: ScadOp::Union,
+                    children: Vec::new(),
+                }
+            ) == "union() {\n}\n"
+        );
+    }
+
+    #[test]
+    fn empty_collections_and_validate() {
+        assert!(format!("{}", Pt2s::new()) == "[]");
+        assert!(format!("{}", Pt3s::new()) == "[]");
+        assert!(format!("{}", crate::Pt4s::new()) == "[]");
+        assert!(format!("{}", Indices::new()) == "[]");
+        assert!(format!("{}", Paths::new()) == "[]");
+        assert!(format!("{}", Indices::from_indices(vec![0, 1, 2])) == "[0, 1, 2]");
+
+        let empty = polygon!(Pt2s::new());
+        assert!(format!("{}", empty).starts_with("polygon(points=[], paths=undef"));
+        assert!(matches!(empty.validate(), Err(ScadError::Invalid(_))));
+        let bad_path = polygon!(
+            dim2::rect(1.0, 1.0, false),
+            Paths::from_paths(vec![Indices::from_indices(vec![0, 1, 4])])
+        );
+        assert!(matches!(bad_path.validate(), Err(ScadError::Invalid(_))));
+        let nested = translate!([1.0, 0.0, 0.0], polyhedron!(Pt3s::new(), Faces::new()););
+        assert!(matches!(nested.validate(), Err(ScadError::Invalid(_))));
+        assert!(cube!(1.0).validate().is_ok());
+        assert!(
+            polygon_multi!(dim2::rect(2.0, 2.0, true), dim2::circle(0.5, 8))
+                .validate()
+                .is_ok()
+        );
+
+        let path = std::env::temp_dir().join("scad_tree_validate.scad");
+        let path = path.to_str().unwrap().to_string();
+        assert!(empty.try_save(&path).is_err());
+        let file_path = path.clone();
+        let result = scad_file_result!(4, file_path, empty;);
+        assert!(matches!(result, Err(ScadError::Invalid(_))));
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[test]
+    fn comment_and_raw() {
+        let height = 5.0;
+        let scad = union!(
+            comment!("Base\nheight = {}", height);
+            raw_scad!("linear_extrude({}) square(2);", height);
+        );
+        assert!(
+            format!("{}", scad)
+                == "union() {\n// Base\n// height = 5\nlinear_extrude(5) square(2);\n}\n"
+        );
+        assert!(
+            scad.to_pretty_string(2)
+                == "union() {\n  // Base\n  // height = 5\n  linear_extrude(5) square(2);\n}\n"
+        );
+        assert!(scad.validate().is_ok());
+    }
+
+    #[test]
+    fn face_builders() {
+        let mut faces = Faces::new();
+        faces.push_tri(0, 1, 2);
+        faces.push_quad(0, 1, 2, 3);
+        faces.push_fan(9, &[1, 2, 3]);
+        faces.push_strip(&[0, 1, 2], &[3, 4, 5]);
+        let expected: Vec<Vec<u64>> = vec![
+            vec![0, 1, 2],
+            vec![0, 1, 2, 3],
+            vec![9, 1, 2],
+            vec![9, 2, 3],
+            vec![9, 3, 1],
+            vec![0, 1, 4, 3],
+            vec![1, 2, 5, 4],
+            vec![2, 0, 3, 5],
+        ];
+        assert!(faces.len() == expected.len());
+        for (face, expected) in faces.iter().zip(expected.iter()) {
+            assert!(**face == *expected);
+        }
+
+        // a square pyramid built from a fan and a cap is a valid mesh
+        let points = Pt3s::from_pt3s(vec![
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(0.0, 1.0, 0.0),
+            Pt3::new(1.0, 1.0, 0.0),
+            Pt3::new(1.0, 0.0, 0.0),
+            Pt3::new(0.5, 0.5, 1.0),
+        ]);
+        let mut faces = Faces::new();
+        faces.push_fan(4, &[0, 1, 2, 3]);
+        faces.push_quad(3, 2, 1, 0);
+        let pyramid = Polyhedron::new(points, faces);
+        assert!(pyramid.validate().is_valid());
+        assert!(approx_eq(pyramid.volume(), 1.0 / 3.0, 1e-9));
+    }
+
+    #[test]
+    fn faces_append_offset() {
+        let triangles = Indices::from_indices(vec![0, 1, 2, 2, 3, 0]);
+        let mut faces = Faces::from_triangles(&triangles);
+        assert!(faces.len() == 2);
+        faces.append_offset(Faces::from_triangles(&triangles), 4);
+        assert!(faces.len() == 4);
+        assert!(*faces[2] == vec![4, 5, 6]);
+        assert!(*faces[3] == vec![6, 7, 4]);
+    }
+
+    #[test]
+    fn modifiers() {
+        let scad = difference!(
+            cube!(10.0);
+            translate!([5.0, 5.0, 0.0], cylinder!(20.0, 2.0, fn=8););
+        );
+        let scad = union!(
+            scad.background();
+            cube!(1.0).debug().disable();
+        );
+        assert!(
+            scad.to_pretty_string(2)
+                == "union() {\n  %difference() {\n    cube(size=[10, 10, 10], center=false);\n    translate(v=[5, 5, 0]) {\n      cylinder(h=20, r1=2, r2=2, center=false, $fn=8);\n    }\n  }\n  *#cube(size=[1, 1, 1], center=false);\n}\n"
+        );
+        assert!(format!("{}", sphere!(1.0).root()) == "!sphere(r=1);\n");
+        assert!(scad.validate().is_ok());
+        let empty = Scad {
+            op: ScadOp::Modifier(ScadModifier::Debug),
+            children: Vec::new(),
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn to_polyhedron() {
+        let cube = cube!([2.0, 3.0, 4.0]).to_polyhedron(0).unwrap();
+        assert!(cube.validate().is_valid());
+        assert!(approx_eq(cube.volume(), 24.0, 1e-9));
+
+        let centered = cube!(2.0, true).to_polyhedron(0).unwrap();
+        assert!(approx_eq(centered.centroid().z, 0.0, 1e-9));
+
+        let sphere = sphere!(5.0, fn=64).to_polyhedron(0).unwrap();
+        assert!(sphere.validate().is_valid());
+        let exact = 4.0 / 3.0 * std::f64::consts::PI * 125.0;
+        assert!(sphere.volume() < exact && sphere.volume() > exact * 0.97);
+
+        let cone = cylinder!(h=3.0, r1=1.0, r2=0.0, center=false, fn=4)
+            .to_polyhedron(0)
+            .unwrap();
+        assert!(cone.validate().is_valid());
+        assert!(approx_eq(cone.volume(), 2.0, 1e-9));
+
+        let block = difference!(
+            cube!(10.0);
+            translate!([5.0, 5.0, -1.0], cube!([2.0, 2.0, 12.0]););
+        );
+        let mesh = block.to_polyhedron(0).unwrap();
+        assert!(mesh.validate().is_valid());
+        assert!(approx_eq(mesh.volume(), 960.0, 1e-6));
+
+        let mirrored = mirror!([1.0, 0.0, 0.0], translate!([2.0, 0.0, 0.0], cube!(1.0, true);););
+        let mesh = mirrored.to_polyhedron(0).unwrap();
+        assert!(approx_eq(mesh.volume(), 1.0, 1e-9));
+        assert!(approx_eq(mesh.centroid().x, -2.0, 1e-9));
+
+        // x then y then z like OpenSCAD
+        let rotated = rotate!([90.0, 0.0, 90.0], translate!([2.0, 0.0, 0.0], cube!(1.0, true);););
+        let c = rotated.to_polyhedron(0).unwrap().centroid();
+        assert!(approx_eq(c.x, 0.0, 1e-9) && approx_eq(c.y, 2.0, 1e-9));
+        let rotated =
+            rotate!(90.0, [1.0, 0.0, 0.0], translate!([0.0, 2.0, 0.0], cube!(1.0, true);););
+        let c = rotated.to_polyhedron(0).unwrap().centroid();
+        assert!(approx_eq(c.y, 0.0, 1e-9) && approx_eq(c.z, 2.0, 1e-9));
+
+        let twisted = linear_extrude!(height=10.0, center=false, convexity=1, twist=90.0, scale=0.5, slices=20,
+            square!(4.0, true);
+        );
+        let mesh = twisted.to_polyhedron(0).unwrap();
+        assert!(mesh.validate().is_valid());
+        // the twisted sides cut a little off the frustum
+        assert!(approx_eq(mesh.volume(), 16.0 * 10.0 * 7.0 / 12.0, 3.0));
+
+        let torus = rotate_extrude!(translate!([10.0, 0.0, 0.0], square!(2.0, true);););
+        let mesh = torus.to_polyhedron(128).unwrap();
+        assert!(mesh.validate().is_valid());
+        assert!(approx_eq(
+            mesh.volume(),
+            2.0 * std::f64::consts::PI * 10.0 * 4.0,
+            1.0
+        ));
+
+        let background = union!(cube!(1.0); cube!(5.0).background(););
+        assert!(approx_eq(
+            background.to_polyhedron(0).unwrap().volume(),
+            1.0,
+            1e-9
+        ));
+        assert!(matches!(
+            hull!(cube!(1.0);).to_polyhedron(0),
+            Err(ScadError::Invalid(_))
+        ));
+        assert!(matches!(
+            square!(1.0).to_polyhedron(0),
+            Err(ScadError::Invalid(_))
+        ));
+
+        let stl = cube.to_stl("cube");
+        assert!(stl.starts_with("solid cube\n") && stl.ends_with("endsolid cube\n"));
+        assert!(stl.matches("facet normal").count() == 12);
+        assert!(stl.contains("facet normal 0 0 -1\nouter loop\nvertex 0 0 0\n"));
+    }
+
+    #[test]
+    fn render_and_preview() {
+        let scad = render!(4, difference!(cube!(2.0); sphere!(1.0, fn=8);););
+        assert!(
+            scad.to_pretty_string(2)
+                == "render(convexity=4) {\n  difference() {\n    cube(size=[2, 2, 2], center=false);\n    sphere(r=1, $fn=8);\n  }\n}\n"
+        );
+        let scad = union!(if_preview!(cube!(1.0); sphere!(1.0);).debug(););
+        assert!(
+            scad.to_pretty_string(2)
+                == "union() {\n  if ($preview) {\n    #cube(size=[1, 1, 1], center=false);\n  } else {\n    #sphere(r=1);\n  }\n}\n"
+        );
+        assert!(scad.validate().is_ok());
+        let preview = if_preview!(cube!(1.0); cube!(2.0););
+        assert!(approx_eq(
+            preview.to_polyhedron(0).unwrap().volume(),
+            8.0,
+            1e-9
+        ));
+        let rendered = render!(cube!(3.0););
+        assert!(approx_eq(
+            rendered.to_polyhedron(0).unwrap().volume(),
+            27.0,
+            1e-9
+        ));
+    }
+
+    #[test]
+    fn infill_patterns() {
+        let cells = patterns::honeycomb_profiles(50.0, 30.0, 5.0, 1.0);
+        assert!(!cells.is_empty());
+        let mut min = Pt2::new(f64::MAX, f64::MAX);
+        let mut max = Pt2::new(f64::MIN, f64::MIN);
+        for p in cells.iter().flat_map(|c| c.iter()) {
+            min = Pt2::new(min.x.min(p.x), min.y.min(p.y));
+            max = Pt2::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        assert!(min.x >= 0.0 && min.y >= 0.0 && max.x <= 50.0 && max.y <= 30.0);
+        assert!(approx_eq(min.x, 50.0 - max.x, 1e-9) && approx_eq(min.y, 30.0 - max.y, 1e-9));
+        // neighboring cells are a wall apart across the flats
+        let c0 = cells[0].iter().fold(Pt2::new(0.0, 0.0), |a, p| a + *p) / 6.0;
+        let c1 = cells[1].iter().fold(Pt2::new(0.0, 0.0), |a, p| a + *p) / 6.0;
+        assert!(approx_eq((c1 - c0).len(), 6.0, 1e-9));
+        assert!(dim2::union(&cells, &[]).len() == cells.len());
+
+        let cells = patterns::grid_profiles(23.0, 12.0, 5.0, 1.0);
+        assert!(cells.len() == 4 * 2);
+        let min_x = cells[0].iter().fold(f64::MAX, |m, p| m.min(p.x));
+        let min_y = cells[0].iter().fold(f64::MAX, |m, p| m.min(p.y));
+        assert!(approx_eq(min_x, 0.0, 1e-9) && approx_eq(min_y, 0.5, 1e-9));
+        match patterns::grid(23.0, 12.0, 5.0, 1.0).op {
+            ScadOp::Polygon { paths, .. } => assert!(paths.unwrap().len() == 8),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn convexity_inference() {
+        assert!(polygon!(dim2::rect(2.0, 2.0, false)).estimate_convexity() == 1);
+        // a comb with three teeth
+        let comb = Pt2s::from_pt2s(vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(0.0, 3.0),
+            Pt2::new(1.0, 3.0),
+            Pt2::new(1.0, 1.0),
+            Pt2::new(2.0, 1.0),
+            Pt2::new(2.0, 3.0),
+            Pt2::new(3.0, 3.0),
+            Pt2::new(3.0, 1.0),
+            Pt2::new(4.0, 1.0),
+            Pt2::new(4.0, 3.0),
+            Pt2::new(5.0, 3.0),
+            Pt2::new(5.0, 0.0),
+        ]);
+        let mut scad = linear_extrude!(10.0, polygon!(comb););
+        assert!(scad.estimate_convexity() == 3);
+        scad.infer_convexity();
+        match &scad.op {
+            ScadOp::LinearExtrude { convexity, .. } => assert!(*convexity == 3),
+            _ => panic!(),
+        }
+        match &scad.children[0].op {
+            ScadOp::Polygon { convexity, .. } => assert!(*convexity == 3),
+            _ => panic!(),
+        }
+        assert!(format!("{}", scad).contains("convexity=3"));
+
+        let torus = rotate_extrude!(translate!([5.0, 0.0, 0.0], circle!(1.0);););
+        assert!(torus.estimate_convexity() == 2);
+
+        let mesh = rotate_extrude!(angle=360.0, convexity=1, fn=32,
+            translate!([5.0, 0.0, 0.0], circle!(1.0, fn=16););
+        )
+        .to_polyhedron(0)
+        .unwrap();
+        let mut scad = mesh.into_scad();
+        assert!(scad.estimate_convexity() == 2);
+        scad.infer_convexity();
+        assert!(format!("{}", scad).ends_with("convexity=2);\n"));
+
+        let mut explicit = polygon!(dim2::rect(1.0, 1.0, false), convexity = 7);
+        explicit.infer_convexity();
+        assert!(format!("{}", explicit).contains("convexity=7"));
+
+        let with_paths = polygon_multi!(dim2::rect(4.0, 4.0, true), dim2::circle(1.0, 8));
+        assert!(format!("{}", with_paths).contains("]], convexity=1);"));
+    }
+
+    #[test]
+    fn dim2_constructions() {
+        let close = |a: Pt2, b: Pt2| (a - b).len() < 1e-9;
+        let (center, radius) =
+            dim2::circle_from_points(Pt2::new(1.0, 0.0), Pt2::new(0.0, 1.0), Pt2::new(-1.0, 0.0))
+                .unwrap();
+        assert!(close(center, Pt2::new(0.0, 0.0)) && approx_eq(radius, 1.0, 1e-9));
+        assert!(dim2::circle_from_points(
+            Pt2::new(0.0, 0.0),
+            Pt2::new(1.0, 1.0),
+            Pt2::new(2.0, 2.0)
+        )
+        .is_none());
+
+        // counter clockwise from the left of the chord passes under it
+        let arc = dim2::bulge_arc(Pt2::new(-1.0, 0.0), Pt2::new(1.0, 0.0), 1.0, 4);
+        assert!(arc.len() == 5 && close(arc[2], Pt2::new(0.0, -1.0)));
+        let arc = dim2::bulge_arc(Pt2::new(-1.0, 0.0), Pt2::new(1.0, 0.0), -1.0, 4);
+        assert!(close(arc[2], Pt2::new(0.0, 1.0)));
+
+        let arc = dim2::arc_from_points(
+            Pt2::new(1.0, 0.0),
+            Pt2::new(0.0, -1.0),
+            Pt2::new(-1.0, 0.0),
+            2,
+        );
+        assert!(arc.len() == 3 && close(arc[1], Pt2::new(0.0, -1.0)));
+        let arc = dim2::arc_from_points(
+            Pt2::new(1.0, 0.0),
+            Pt2::new(0.0, 1.0),
+            Pt2::new(-1.0, 0.0),
+            2,
+        );
+        assert!(close(arc[1], Pt2::new(0.0, 1.0)));
+
+        let lines = dim2::tangent_lines(Pt2::new(0.0, 0.0), 1.0, Pt2::new(4.0, 0.0), 1.0);
+        assert!(lines.len() == 4);
+        assert!(close(lines[0].0, Pt2::new(0.0, 1.0)) && close(lines[0].1, Pt2::new(4.0, 1.0)));
+        assert!(close(lines[1].0, Pt2::new(0.0, -1.0)) && close(lines[1].1, Pt2::new(4.0, -1.0)));
+        for (a, b) in lines[2..].iter() {
+            assert!(approx_eq(
+                (a.lerp(*b, 0.5) - Pt2::new(2.0, 0.0)).len(),
+                0.0,
+                1e-9
+            ));
+        }
+        let lines = dim2::tangent_lines(Pt2::new(0.0, 0.0), 1.0, Pt2::new(2.0, 0.0), 0.0);
+        assert!(lines.len() == 2);
+        assert!(approx_eq(lines[0].0.len(), 1.0, 1e-9));
+        assert!(approx_eq(
+            lines[0].0.dot(lines[0].1 - lines[0].0),
+            0.0,
+            1e-9
+        ));
+        let lines = dim2::tangent_lines(Pt2::new(0.0, 0.0), 0.0, Pt2::new(2.0, 0.0), 1.0);
+        assert!(lines.len() == 2);
+        for (a, b) in lines.iter() {
+            assert!(close(*a, Pt2::new(0.0, 0.0)));
+            assert!(approx_eq((*b - Pt2::new(2.0, 0.0)).len(), 1.0, 1e-9));
+            assert!(approx_eq((*b - Pt2::new(2.0, 0.0)).dot(*b - *a), 0.0, 1e-9));
+        }
+        assert!(!close(lines[0].1, lines[1].1));
+        assert!(dim2::tangent_lines(Pt2::new(0.0, 0.0), 3.0, Pt2::new(1.0, 0.0), 1.0).is_empty());
+
+        let corner = dim2::line_intersection(
+            Pt2::new(0.0, 0.0),
+            Pt2::new(1.0, 0.0),
+            Pt2::new(3.0, 1.0),
+            Pt2::new(3.0, 2.0),
+        )
+        .unwrap();
+        assert!(close(corner, Pt2::new(3.0, 0.0)));
+        let fillet = dim2::fillet_lines(
+            Pt2::new(0.0, 0.0),
+            Pt2::new(1.0, 0.0),
+            Pt2::new(3.0, 1.0),
+            Pt2::new(3.0, 5.0),
+            1.0,
+            4,
+        )
+        .unwrap();
+        assert!(close(fillet[0], Pt2::new(2.0, 0.0)));
+        assert!(close(fillet[fillet.len() - 1], Pt2::new(3.0, 1.0)));
+    }
+
+    #[test]
+    fn gear_train_design() {
+        let pair = gears::GearPair::new(2.0, 20, 40);
+        assert!(approx_eq(pair.ratio(), 2.0, 1.0e-9));
+        assert!(approx_eq(pair.center_distance(), 60.0, 1.0e-9));
+
+        let pair = gears::design_gear_pair(2.5, 1.0, 17, 60).unwrap();
+        assert!(approx_eq(pair.ratio(), 2.5, 1.0e-9));
+        assert!(pair.driver_teeth >= 17 && pair.driven_teeth <= 60);
+
+        let profile = gears::gear_profile(1.0, 20, gears::PRESSURE_ANGLE, 4);
+        assert!(profile.len() == 20 * 12);
+        assert!(crate::clip::signed_area(&profile) < 0.0);
+        for p in profile.iter() {
+            let r = p.len();
+            assert!(r > 8.74 && r < 11.01);
+        }
+
+        let stages = gears::design_gear_train(20.0, 1.0, 17, 80, 0.01, 4).unwrap();
+        assert!(stages.len() == 2);
+        assert!((gears::train_ratio(&stages) / 20.0 - 1.0).abs() < 0.01);
+        for stage in stages.iter() {
+            assert!(stage.driver_teeth >= 17 && stage.driven_teeth <= 80);
+        }
+        assert!(gears::design_gear_train(20.0, 1.0, 17, 80, 0.01, 1).is_none());
+
+        let gear = gears::spur_gear(1.0, 20, 5.0, gears::PRESSURE_ANGLE, 4);
+        let polyhedron = gear.to_polyhedron(32).unwrap();
+        assert!(polyhedron.points.len() == 2 * profile.len());
+        assert!(gears::gear_train(&stages, 5.0, gears::PRESSURE_ANGLE, 4)
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn helical_gears() {
+        let twist = gears::helix_twist(1.0, 20, 10.0, 45.0);
+        assert!(approx_eq(twist, 1.0f64.to_degrees(), 1.0e-9));
+
+        let spur = gears::spur_gear(1.0, 20, 6.0, gears::PRESSURE_ANGLE, 4);
+        let volume = spur.to_polyhedron(32).unwrap().volume();
+        let helical = gears::helical_gear(1.0, 20, 6.0, gears::PRESSURE_ANGLE, 20.0, 4);
+        let helical = helical.to_polyhedron(32).unwrap();
+        assert!(helical.validate().non_manifold_edges.is_empty());
+        assert!(approx_eq(helical.volume(), volume, volume * 0.01));
+        let herringbone = gears::herringbone_gear(1.0, 20, 6.0, gears::PRESSURE_ANGLE, 20.0, 4);
+        let s = herringbone.to_string();
+        assert!(s.contains(&format!(
+            "twist={}",
+            Float(gears::helix_twist(1.0, 20, 3.0, 20.0))
+        )));
+        assert!(s.contains("mirror(v=[0, 0, 1])"));
+
+        let pair = gears::GearPair::new(1.0, 20, 30);
+        assert!(pair
+            .into_herringbone_scad(6.0, 20.0, 30.0, 4)
+            .validate()
+            .is_ok());
+        let s = pair.into_helical_scad(6.0, 20.0, 30.0, 4).to_string();
+        assert!(s.contains(&format!(
+            "twist={}",
+            Float(gears::helix_twist(1.0, 20, 6.0, 30.0))
+        )));
+        assert!(s.contains(&format!(
+            "twist={}",
+            Float(gears::helix_twist(1.0, 30, 6.0, -30.0))
+        )));
+    }
+
+    #[test]
+    fn viewer_watch() {
+        let path = std::env::temp_dir().join("scad_tree_viewer_watch.scad");
+        let path = path.to_str().unwrap().to_string();
+        let options = WatchOptions {
+            interval: std::time::Duration::from_millis(1),
+            open: false,
+            restart_on_rebuild: false,
+        };
+        let mut builds = 0;
+        Viewer::watch(&path, &options, || {
+            builds += 1;
+            if builds > 3 {
+                return None;
+            }
+            let mut viewer = Viewer::new(0.5, 0.25, 8);
+            viewer.add_pt3(Pt3::new(builds as f64, 0.0, 0.0), ScadColor::Red);
+            Some(viewer.into_scad())
+        })
+        .unwrap();
+        assert!(builds == 4);
+        let s = std::fs::read_to_string(&path).unwrap();
+        assert!(s.starts_with("translate(v=[3, 0, 0])"));
+
+        let viewer = Viewer::new(0.5, 0.25, 8);
+        assert!(matches!(viewer.save(&path), Err(ScadError::Empty)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn belt_drive() {
+        let pi = std::f64::consts::PI;
+        assert!(approx_eq(
+            belt::belt_length(20.0, 20.0, 50.0),
+            100.0 + 20.0 * pi,
+            1.0e-9
+        ));
+        let length = belt::belt_length(40.0, 16.0, 80.0);
+        assert!(length > 160.0 + 28.0 * pi);
+        let distance = belt::center_distance(16.0, 40.0, length).unwrap();
+        assert!(approx_eq(distance, 80.0, 1.0e-6));
+        assert!(belt::center_distance(16.0, 40.0, 50.0).is_none());
+
+        assert!(approx_eq(
+            belt::pulley_pitch_diameter(20, 2.0),
+            40.0 / pi,
+            1.0e-9
+        ));
+        let distance = belt::toothed_center_distance(20, 20, 100, 2.0).unwrap();
+        assert!(approx_eq(distance, 80.0, 1.0e-6));
+        let (teeth, distance) =
+            belt::nearest_stock_belt(20, 20, 2.0, 83.0, &[100, 110, 122, 158]).unwrap();
+        assert!(teeth == 100 && approx_eq(distance, 80.0, 1.0e-6));
+
+        let mount = belt::pulley_mount(80.0, 5.0, 12.0, 3.0, 6.0, 24);
+        assert!(mount.validate().is_ok());
+        let s = mount.to_string();
+        assert!(s.contains("paths=[[0, ") && s.matches('[').count() > 50);
+    }
+
+    #[test]
+    fn openscad_process() {
+        let stderr = "Parsing design (AST generation)...\n\
+            WARNING: Ignoring unknown variable 'x' in file a.scad, line 1\n\
+            ERROR: Parser error in file a.scad, line 2: syntax error\n";
+        let (warnings, errors) = openscad::messages(stderr);
+        assert!(warnings.len() == 1 && warnings[0].starts_with("WARNING: Ignoring"));
+        assert!(errors.len() == 1 && errors[0].starts_with("ERROR: Parser"));
+
+        let scad = cube!(1.0);
+        if openscad::find_executable().is_some() {
+            assert!(openscad::check_syntax(&scad).unwrap().is_empty());
+        } else {
+            assert!(matches!(
+                openscad::check_syntax(&scad),
+                Err(ScadError::Io(_))
+            ));
+        }
+        let invalid = polygon!(Pt2s::new());
+        assert!(matches!(
+            openscad::check_syntax(&invalid),
+            Err(ScadError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn thread_engagement() {
+        assert!(approx_eq(metric_thread::pitch(6), 1.0, 1.0e-9));
+        assert!(approx_eq(metric_thread::tap_drill_diameter(6), 5.0, 1.0e-9));
+        assert!(approx_eq(
+            metric_thread::pilot_hole_diameter(6, 0.0),
+            6.0,
+            1.0e-9
+        ));
+        assert!(approx_eq(
+            metric_thread::pilot_hole_diameter(6, 100.0),
+            4.917,
+            0.001
+        ));
+        assert!(approx_eq(metric_thread::tensile_stress_area(6), 20.1, 0.05));
+        assert!(approx_eq(metric_thread::tensile_stress_area(10), 58.0, 0.1));
+
+        let internal = metric_thread::internal_shear_area(6, 6.0);
+        let external = metric_thread::external_shear_area(6, 6.0);
+        assert!(internal > external);
+        let steel = metric_thread::min_engagement_length(6, 800.0, 800.0);
+        assert!(steel > 2.5 && steel < 3.5);
+        let plastic = metric_thread::min_engagement_length(6, 800.0, 50.0);
+        assert!(plastic > 10.0 * steel);
+    }
+
+    #[test]
+    fn optimize_tree() {
+        let empty = Scad {
+            op: ScadOp::Union,
+            children: Vec::new(),
+        };
+        let mut scad = union!(
+            union!(
+                translate!([1.0, 0.0, 0.0], translate!([0.0, 2.0, 0.0], cube!(1.0);););
+                translate!([0.0, 0.0, 0.0], sphere!(1.0););
+                empty.clone();
+            );
+            rotate!(0.0, scale!([2.0, 1.0, 1.0], scale!([1.0, 3.0, 1.0], cylinder!(1.0, 1.0););););
+            difference!(cube!(2.0); empty.clone(););
+        );
+        scad.optimize();
+        let expected = union!(
+            translate!([1.0, 2.0, 0.0], cube!(1.0););
+            sphere!(1.0);
+            scale!([2.0, 3.0, 1.0], cylinder!(1.0, 1.0););
+            difference!(cube!(2.0););
+        );
+        assert!(scad.to_string() == expected.to_string());
+
+        let mut scad = difference!(empty.clone(); cube!(1.0););
+        scad.optimize();
+        assert!(scad.to_string() == difference!(empty; cube!(1.0);).to_string());
+
+        let mut scad = rotate!(10.0, rotate!(20.0, cube!(1.0);););
+        scad.optimize();
+        assert!(scad.to_string() == rotate!(30.0, cube!(1.0);).to_string());
+
+        for original in [
+            rotate!([10.0, 20.0, 30.0], rotate!([40.0, -50.0, 60.0], cube!(1.0););),
+            rotate!([0.0, 90.0, 0.0], rotate!([30.0, 0.0, 0.0], cube!(1.0););),
+        ] {
+            let mut scad = original.clone();
+            scad.optimize();
+            assert!(matches!(scad.op, ScadOp::Rotate { a: None, .. }));
+            assert!(matches!(scad.children[0].op, ScadOp::Cube { .. }));
+            let a = original.to_polyhedron(8).unwrap();
+            let b = scad.to_polyhedron(8).unwrap();
+            for (p, q) in a.points.iter().zip(b.points.iter()) {
+                assert!((*p - *q).len() < 1.0e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn screw_head_pockets() {
+        use metric_thread::HeadStyle;
+        let pi = std::f64::consts::PI;
+        let pocket = metric_thread::head_pocket(HeadStyle::Socket, 3, 0.2, 64);
+        let volume = pocket.to_polyhedron(64).unwrap().volume();
+        assert!(approx_eq(volume, pi * 2.85 * 2.85 * 4.2, 0.5));
+
+        let pocket = metric_thread::head_pocket(HeadStyle::Hex, 6, 0.0, 64);
+        let volume = pocket.to_polyhedron(64).unwrap().volume();
+        assert!(approx_eq(volume, 2.0 * 3.0f64.sqrt() * 25.0 * 5.0, 0.01));
+
+        for style in [HeadStyle::Button, HeadStyle::Flat, HeadStyle::Pan] {
+            let mesh = metric_thread::head_pocket(style, 4, 0.3, 32)
+                .to_polyhedron(32)
+                .unwrap();
+            let top = mesh.points.iter().fold(f64::MIN, |z, p| z.max(p.z));
+            let bottom = mesh.points.iter().fold(f64::MAX, |z, p| z.min(p.z));
+            assert!(approx_eq(top, 1.0, 1.0e-9));
+            assert!(bottom < -2.0 && bottom > -3.2);
+        }
+
+        for clearance in [0.0, 0.4] {
+            let mesh = metric_thread::head_pocket(HeadStyle::Flat, 5, clearance, 32)
+                .to_polyhedron(32)
+                .unwrap();
+            let bottom = mesh.points.iter().fold(f64::MAX, |z, p| z.min(p.z));
+            assert!(approx_eq(bottom, -3.1 - clearance, 1.0e-9));
+        }
+    }
+
+    #[test]
+    fn dedup_modules() {
+        let post = translate!([1.0, 0.0, 0.0], cylinder!(5.0, 1.0););
+        let bracket = union!(cube!(4.0); post.clone(););
+        let roots = vec![
+            translate!([10.0, 0.0, 0.0], bracket.clone();),
+            translate!([20.0, 0.0, 0.0], bracket.clone();),
+            translate!([30.0, 0.0, 0.0], bracket.clone();),
+            post.clone(),
+        ];
+        // the posts inside the brackets only count once
+        let (modules, calls) = Scad::extract_modules(&roots, 3);
+        assert!(modules.len() == 1);
+        assert!(modules[0].0 == "shared_0" && modules[0].1 == bracket);
+        assert!(
+            calls[0]
+                == translate!([10.0, 0.0, 0.0], Scad::call("shared_0", Vec::new(), Vec::new());)
+        );
+        assert!(calls[3] == post);
+
+        let (modules, _) = Scad::extract_modules(&roots, 2);
+        assert!(modules.len() == 2);
+        // inner modules come first
+        assert!(modules[0].0 == "shared_1" && modules[0].1 == post);
+        assert!(
+            modules[1].1 == union!(cube!(4.0); Scad::call("shared_1", Vec::new(), Vec::new());)
+        );
+
+        let options = ScadFileOptions {
+            fn_: Some(32),
+            dedup_threshold: 3,
+            ..Default::default()
+        };
+        let code = options.to_code(&roots).unwrap();
+        assert!(code.starts_with("$fn=32;\nmodule shared_0() {\n    union() {\n"));
+        assert!(code.matches("shared_0();").count() == 3);
+
+        let path = std::env::temp_dir().join("scad_tree_dedup_modules.scad");
+        let path = path.to_str().unwrap().to_string();
+        let file = path.clone();
+        scad_file_result!(&file, options=options, roots[0].clone(); roots[1].clone(); roots[2].clone();)
+            .unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("module shared_0()"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn fine_pitch_threads() {
+        assert!(metric_thread::fine_pitches(10) == [1.25, 1.0, 0.75]);
+        assert!(metric_thread::fine_pitches(7).is_empty());
+
+        let coarse = metric_thread::threaded_rod_with_options(8, 10.0, &Default::default());
+        let fine = metric_thread::threaded_rod_with_options(
+            8,
+            10.0,
+            &metric_thread::ThreadedRodOptions {
+                pitch: Some(1.0),
+                ..Default::default()
+            },
+        );
+        let explicit_coarse = metric_thread::threaded_rod_with_options(
+            8,
+            10.0,
+            &metric_thread::ThreadedRodOptions {
+                pitch: Some(1.25),
+                ..Default::default()
+            },
+        );
+        assert!(coarse == explicit_coarse && coarse != fine);
+        // more turns of thread in the same length
+        assert!(fine.to_string().len() > coarse.to_string().len());
+
+        let hole = metric_thread::threaded_hole(
+            10,
+            8.0,
+            &metric_thread::ThreadedHoleOptions {
+                pitch: Some(1.25),
+                ..Default::default()
+            },
+        );
+        assert!(hole.validate().is_ok());
+        let invalid = std::panic::catch_unwind(|| {
+            metric_thread::threaded_rod_with_options(
+                8,
+                10.0,
+                &metric_thread::ThreadedRodOptions {
+                    pitch: Some(0.9),
+                    ..Default::default()
+                },
+            )
+        });
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn turnbuckle_parts() {
+        let fit = metric_thread::ThreadFit::Normal;
+        let body = metric_thread::turnbuckle_body(6, 40.0, 12.0, 24, fit);
+        assert!(body.validate().is_ok());
+        let right = metric_thread::threaded_eye(6, 20.0, 8.0, 24, false, fit);
+        let left = metric_thread::threaded_eye(6, 20.0, 8.0, 24, true, fit);
+        assert!(right.validate().is_ok() && right != left);
+        let hook = metric_thread::threaded_hook(6, 20.0, 10.0, 24, true, fit);
+        assert!(hook.to_string().contains("rotate_extrude(angle=270"));
+        let turnbuckle = metric_thread::turnbuckle(6, 40.0, 12.0, 20.0, 8.0, 24, fit);
+        assert!(turnbuckle.validate().is_ok());
+    }
+
+    #[test]
+    fn anchors_and_attach() {
+        use anchor::{BOTTOM, RIGHT, TOP};
+        let close = |a: Pt3, b: Pt3| (a - b).len() < 1.0e-9;
+        let bounds = |p: &Polyhedron| {
+            let mut min = Pt3::new(f64::MAX, f64::MAX, f64::MAX);
+            let mut max = Pt3::new(f64::MIN, f64::MIN, f64::MIN);
+            for q in p.points.iter() {
+                min = Pt3::new(min.x.min(q.x), min.y.min(q.y), min.z.min(q.z));
+                max = Pt3::new(max.x.max(q.x), max.y.max(q.y), max.z.max(q.z));
+            }
+            (min, max)
+        };
+
+        let base = cube!(10.0);
+        let top = base.anchor(TOP).unwrap();
+        assert!(close(top.position, Pt3::new(5.0, 5.0, 10.0)) && top.direction == TOP);
+        let corner = cube!([10.0, 10.0, 10.0], true).anchor(TOP + RIGHT).unwrap();
+        assert!(close(corner.position, Pt3::new(5.0, 0.0, 5.0)));
+        assert!(close(
+            corner.direction,
+            Pt3::new(1.0, 0.0, 1.0).normalized()
+        ));
+        let cone = cylinder!(10.0, 4.0, 2.0, false);
+        assert!(close(
+            cone.anchor(RIGHT).unwrap().position,
+            Pt3::new(3.0, 0.0, 5.0)
+        ));
+        assert!(close(
+            cone.anchor(TOP).unwrap().position,
+            Pt3::new(0.0, 0.0, 10.0)
+        ));
+        let moved = translate!([1.0, 2.0, 3.0], cube!(2.0););
+        assert!(close(
+            moved.anchor(TOP).unwrap().position,
+            Pt3::new(2.0, 3.0, 5.0)
+        ));
+        let a = union!(cube!(1.0); cube!(2.0);).anchor(TOP).unwrap();
+        assert!(a.position == Pt3::new(1.0, 1.0, 2.0));
+
+        let post = anchor::attach(top, cone.clone(), cone.anchor(BOTTOM).unwrap());
+        let (min, max) = bounds(&post.to_polyhedron(16).unwrap());
+        assert!(close(min, Pt3::new(1.0, 1.0, 10.0)) && close(max, Pt3::new(9.0, 9.0, 20.0)));
+
+        let block = cube!(2.0);
+        let side = anchor::attach(
+            base.anchor(RIGHT).unwrap(),
+            block.clone(),
+            block.anchor(BOTTOM).unwrap(),
+        );
+        let side_mesh = side.to_polyhedron(16).unwrap();
+        let (min, max) = bounds(&side_mesh);
+        assert!(close(min, Pt3::new(10.0, 4.0, 4.0)) && close(max, Pt3::new(12.0, 6.0, 6.0)));
+
+        let polyhedron = block.to_polyhedron(16).unwrap();
+        let attached = anchor::attach_polyhedron(
+            base.anchor(RIGHT).unwrap(),
+            polyhedron.clone(),
+            polyhedron.anchor(BOTTOM),
+        );
+        for (p, q) in attached.points.iter().zip(side_mesh.points.iter()) {
+            assert!(close(*p, *q));
+        }
+    }
+
+    #[test]
+    fn scad_bounds() {
+        let p = Polyhedron::cylinder(2.0, 5.0, 16);
+        let (min, max) = p.bounds();
+        assert!(approx_eq(min.x, -2.0, 1.0e-9) && approx_eq(max.z, 5.0, 1.0e-9));
+
+        let s = translate!([1.0, 2.0, 3.0], cube!([2.0, 4.0, 6.0], true););
+        assert!(s.bounds() == Some((Pt3::new(0.0, 0.0, 0.0), Pt3::new(2.0, 4.0, 6.0))));
+
+        let s = difference!(cube!(10.0); translate!([20.0, 0.0, 0.0], sphere!(5.0);););
+        assert!(s.bounds() == Some((Pt3::new(0.0, 0.0, 0.0), Pt3::new(10.0, 10.0, 10.0))));
+
+        let s = union!(cube!(1.0); translate!([-3.0, 0.0, 0.0], sphere!(1.0);););
+        assert!(s.bounds() == Some((Pt3::new(-4.0, -1.0, -1.0), Pt3::new(1.0, 1.0, 1.0))));
+
+        let (min, max) = rotate!(45.0, cube!(2.0, true);).bounds().unwrap();
+        assert!(approx_eq(max.x, 2.0f64.sqrt(), 1.0e-9) && approx_eq(min.z, -1.0, 1.0e-9));
+
+        let s = linear_extrude!(5.0, square!([2.0, 3.0]););
+        assert!(s.bounds() == Some((Pt3::new(0.0, 0.0, 0.0), Pt3::new(2.0, 3.0, 5.0))));
+
+        let (min, max) = rotate_extrude!(translate!([4.0, 0.0, 0.0], circle!(1.0););)
+            .bounds()
+            .unwrap();
+        assert!(min == Pt3::new(-5.0, -5.0, -1.0) && max == Pt3::new(5.0, 5.0, 1.0));
+
+        assert!(Scad::call("part", Vec::new(), Vec::new())
+            .bounds()
+            .is_none());
+    }
+
+    #[test]
+    fn arrange_parts() {
+        use arrange::ArrangeOptions;
+        let options = ArrangeOptions {
+            spacing: 2.0,
+            plate: Pt2::new(25.0, 100.0),
+            center: false,
+            sort: false,
+        };
+        let parts = vec![cube!(10.0), cube!([10.0, 5.0, 10.0], true), sphere!(5.0)];
+        let (scad, offsets) = arrange::arrange(parts, &options).unwrap();
+        assert!(offsets[0] == Pt3::new(0.0, 0.0, 0.0));
+        assert!(offsets[1] == Pt3::new(17.0, 2.5, 5.0));
+        assert!(offsets[2] == Pt3::new(5.0, 17.0, 5.0));
+        let (min, max) = scad.bounds().unwrap();
+        assert!(min == Pt3::new(0.0, 0.0, 0.0) && max == Pt3::new(22.0, 22.0, 10.0));
+
+        let small = ArrangeOptions {
+            plate: Pt2::new(5.0, 5.0),
+            ..options
+        };
+        assert!(arrange::arrange(vec![cube!(10.0)], &small).is_err());
+        assert!(arrange::arrange(vec![raw_scad!("part();")], &options).is_err());
+
+        let mut polys = vec![
+            Polyhedron::cylinder(2.0, 4.0, 8),
+            Polyhedron::cylinder(3.0, 4.0, 8),
+        ];
+        let centered = ArrangeOptions {
+            center: true,
+            ..ArrangeOptions::default()
+        };
+        arrange::arrange_polyhedrons(&mut polys, &centered).unwrap();
+        let (min, _) = polys[0].bounds();
+        assert!(approx_eq(min.z, 0.0, 1.0e-9) && min.x > 100.0);
+    }
+
+    #[test]
+    fn debug_summary() {
+        let s = difference!(
+            cube!(2.0);
+            translate!([1.0, 1.0, 1.0], Polyhedron::cylinder(0.5, 3.0, 6).into_scad(););
+        );
+        assert!(
+            format!("{:?}", s)
+                == "difference() { cube(size=[2, 2, 2], center=false), translate(v=[1, 1, 1]) { polyhedron(12 points, 14 faces) } }"
+        );
+        assert!(
+            format!("{:#?}", s)
+                == "difference() {\n    cube(size=[2, 2, 2], center=false)\n    translate(v=[1, 1, 1]) {\n        polyhedron(12 points, 14 faces)\n    }\n}"
+        );
+        let wide = Scad {
+            op: ScadOp::Union,
+            children: vec![cube!(1.0); 10],
+        };
+        assert!(format!("{:?}", wide).ends_with(", ... 2 more }"));
+
+        let mut p = Polyhedron::cylinder(0.5, 3.0, 6);
+        assert!(format!("{:?}", p) == "Polyhedron { points: 12, faces: 14, groups: 0 }");
+        p.set_face_group(0, 1);
+        assert!(format!("{:?}", p) == "Polyhedron { points: 12, faces: 14, groups: 2 }");
+    }
+
+    #[test]
+    fn text_measure() {
+        let params = TextParams {
+            text: "Hi".to_string(),
+            size: 7.2,
+            ..Default::default()
+        };
+        let (min, max) = params.measure();
+        assert!(min == Pt2::new(0.0, 0.0));
+        assert!(approx_eq(max.x, 9.44, 1.0e-9) && approx_eq(max.y, 7.16, 1.0e-9));
+
+        let params = TextParams {
+            text: "gg".to_string(),
+            halign: TextHalign::center,
+            valign: TextValign::center,
+            ..params
+        };
+        let (min, max) = params.measure();
+        assert!(approx_eq(min.x, -max.x, 1.0e-9) && approx_eq(min.y, -max.y, 1.0e-9));
+        assert!(approx_eq(max.y - min.y, 9.28, 1.0e-9));
+
+        let (min, max) = params.measure_with(&|_| 1.0);
+        assert!(approx_eq(max.x - min.x, 20.0, 1.0e-9));
+
+        let s = text!("Hi", 7.2);
+        let (min, max) = s.bounds().unwrap();
+        assert!(min == Pt3::new(0.0, 0.0, 0.0) && approx_eq(max.x, 9.44, 1.0e-9));
+    }
+
+    #[test]
+    fn viewport_header() {
+        let viewport = Viewport::framing(Pt3::new(0.0, 0.0, 0.0), Pt3::new(2.0, 4.0, 4.0));
+        assert!(viewport.translation == Pt3::new(1.0, 2.0, 2.0));
+        assert!(approx_eq(
+            viewport.distance * crate::dsin(11.25),
+            3.0,
+            1.0e-9
+        ));
+
+        let options = ScadFileOptions::default().with_frame_model(true);
+        let code = options.to_code(&[cube!([2.0, 4.0, 4.0])]).unwrap();
+        assert!(code.starts_with(&format!("{}cube(", viewport)));
+        assert!(code.starts_with("$vpr=[55, 0, 25];\n$vpt=[1, 2, 2];\n$vpd="));
+
+        let options = ScadFileOptions::default()
+            .with_viewport(Viewport {
+                fov: Some(30.0),
+                ..Default::default()
+            })
+            .with_frame_model(true);
+        let code = options.to_code(&[cube!(1.0)]).unwrap();
+        assert!(code.starts_with("$vpr=[55, 0, 25];\n$vpt=[0, 0, 0];\n$vpd=140;\n$vpf=30;\n"));
+
+        let file = std::env::temp_dir().join("scad_tree_viewport.scad");
+        let file_path = file.clone();
+        scad_file_result!(4, file_path, viewport=viewport, cube!(1.0);).unwrap();
+        let code = std::fs::read_to_string(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert!(code == format!("{}cube(size=[1, 1, 1], center=false);\n", viewport));
+
+        let file = std::env::temp_dir().join("scad_tree_viewport_panic.scad");
+        let file_path = file.clone();
+        scad_file!(4, file_path, viewport=viewport, cube!(1.0););
+        let code = std::fs::read_to_string(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert!(code == format!("{}cube(size=[1, 1, 1], center=false);\n", viewport));
+    }
+
+    #[test]
+    fn chamfered_and_rounded_polyhedrons() {
+        let cube = dim3::chamfered_cube(Pt3::new(10.0, 10.0, 10.0), 1.0, false);
+        assert!(cube.validate().is_valid());
+        assert!(approx_eq(cube.volume(), 945.0 + 1.0 / 3.0, 1.0e-9));
+        assert!(cube.bounds() == (Pt3::new(-5.0, -5.0, 0.0), Pt3::new(5.0, 5.0, 10.0)));
+
+        let cube = dim3::rounded_cube(Pt3::new(10.0, 20.0, 30.0), 2.0, 16, true);
+        assert!(cube.validate().is_valid());
+        let (min, max) = cube.bounds();
+        assert!(approx_eq(min.z, -15.0, 1.0e-9) && approx_eq(max.y, 10.0, 1.0e-9));
+        assert!(cube.volume() < 6000.0 && cube.volume() > 6000.0 - 8.0 * (1.0 - 0.5) * 6.0 * 60.0);
+
+        let cylinder = dim3::chamfered_cylinder(5.0, 10.0, 1.0, 32, false);
+        assert!(cylinder.validate().is_valid());
+        let (min, max) = cylinder.bounds();
+        assert!(approx_eq(min.z, 0.0, 1.0e-9) && approx_eq(max.z, 10.0, 1.0e-9));
+        assert!(cylinder.volume() < std::f64::consts::PI * 250.0);
+    }
+
+    #[test]
+    fn manifest_build_all() {
+        let dir = std::env::temp_dir().join(format!("scad_tree_manifest_{}", std::process::id()));
+        let mut manifest = Manifest::new(&dir);
+        manifest
+            .part("block", || cube!(2.0))
+            .unwrap()
+            .add(BuildTarget {
+                path: Some(dir.join("parts").join("ball")),
+                options: ScadFileOptions {
+                    fn_: Some(64),
+                    ..Default::default()
+                },
+                ..BuildTarget::new("ball", || sphere!(1.0))
+            })
+            .unwrap();
+        assert!(manifest.part("block", || cube!(3.0)).is_err());
+        assert!(manifest.names() == vec!["block", "ball"]);
+        let paths = manifest.build_all().unwrap();
+        assert!(paths == vec![dir.join("block.scad"), dir.join("parts").join("ball.scad")]);
+        let code = std::fs::read_to_string(&paths[1]).unwrap();
+        assert!(code == "$fn=64;\nsphere(r=1);\n");
+        assert!(manifest.build("wheel").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let target = BuildTarget {
+            format: OutputFormat::Stl,
+            ..BuildTarget::new("block", || cube!(2.0))
+        };
+        assert!(target.output_path(&dir) == dir.join("block.stl"));
+        let target = BuildTarget {
+            path: Some(dir.join("ball.txt")),
+            ..BuildTarget::new("ball", || sphere!(1.0))
+        };
+        assert!(target.output_path(&dir) == dir.join("ball.txt"));
+        let target = BuildTarget::new("plate_v1.2", || cube!(1.0));
+        assert!(target.output_path(&dir) == dir.join("plate_v1.2.scad"));
+    }
+
+    #[test]
+    fn const_profiles() {
+        let plate = dim2::rect_points(4.0, 2.0, true);
+        assert!(Pt2s::from_slice(&plate) == dim2::rect(4.0, 2.0, true));
+        assert!(plate[0] == Pt2::new(-2.0, -1.0) && plate[2] == Pt2::new(2.0, 1.0));
+        assert!(dim2::rect(4.0, 2.0, false)[2] == Pt2::new(4.0, 2.0));
+
+        let profile = Pt2s::from_pt2s(vec![Pt2::new(0.1, -2.0), Pt2::new(1.0 / 3.0, 1e-20)]);
+        let source = dim2::to_rust_const("PROFILE", &profile);
+        assert!(
+            source
+                == "pub const PROFILE: [Pt2; 2] = [\n    Pt2::new(0.1, -2.0),\n    Pt2::new(0.3333333333333333, 1e-20),\n];\n"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn rust_const_of_nan() {
+        let profile = Pt2s::from_pt2s(vec![Pt2::new(0.0, f64::NAN)]);
+        dim2::to_rust_const("PROFILE", &profile);
+    }
+
+    #[test]
+    fn heat_set_inserts() {
+        let insert = metric_thread::insert_dimensions(3);
+        assert!(insert.hole_diameter == 4.0 && insert.length == 5.7);
+        let insert = metric_thread::insert_dimensions(12);
+        assert!(approx_eq(insert.hole_diameter, 14.4, 1.0e-9));
+
+        let (min, max) = metric_thread::insert_pocket(4, 24).bounds().unwrap();
+        assert!(approx_eq(min.z, -9.1, 1.0e-9) && approx_eq(max.z, 1.0, 1.0e-9));
+        assert!(max.x > 2.8);
+
+        let boss = metric_thread::heat_set_boss(3, 8.0, 2.0, 24);
+        let (min, max) = boss.bounds().unwrap();
+        assert!(min == Pt3::new(-4.0, -4.0, 0.0) && max == Pt3::new(4.0, 4.0, 8.0));
+        let volume = boss.to_polyhedron(24).unwrap().volume();
+        let solid = Polyhedron::cylinder(4.0, 8.0, 24).volume();
+        assert!(volume < solid - 12.0 * 6.7 && volume > solid - 16.0 * 6.7);
+    }
+
+    #[test]
+    fn nut_trap_and_clearance_hole() {
+        let (min, max) = metric_thread::nut_trap(3, 2.4, 0.3).bounds().unwrap();
+        assert!(approx_eq(max.y, 2.9, 1.0e-9) && approx_eq(min.z, -2.4, 1.0e-9));
+        assert!(approx_eq(max.z, 1.0, 1.0e-9));
+
+        let (min, max) = metric_thread::bolt_clearance_hole(4, 10.0, None, 24)
+            .bounds()
+            .unwrap();
+        assert!(approx_eq(max.x, 2.25, 1.0e-9) && min.z == -10.0 && max.z == 1.0);
+
+        let hole =
+            metric_thread::bolt_clearance_hole(4, 10.0, Some(metric_thread::HeadStyle::Socket), 24);
+        let (_, max) = hole.bounds().unwrap();
+        assert!(max.x > 3.5 && max.z == 1.0);
+    }
+
+    #[test]
+    fn interference_checks() {
+        let a = union!(
+            cube!(10.0).body();
+            translate!([0.0, 0.0, 10.0], cube!(10.0).keep_out(););
+        );
+        let untagged = union!(cube!(10.0); translate!([0.0, 0.0, 10.0], cube!(10.0);););
+        assert!(format!("{}", a) == format!("{}", untagged));
+        let parts = a.tagged_parts();
+        assert!(parts.len() == 2 && parts[1].0 == ScadTag::KeepOut);
+        assert!(parts[1].1.bounds().unwrap().0 == Pt3::new(0.0, 0.0, 10.0));
+        let empty_tag = Scad {
+            op: ScadOp::Tag(ScadTag::KeepOut),
+            children: Vec::new(),
+        };
+        let parts = union!(cube!(1.0).body(); empty_tag;).tagged_parts();
+        assert!(parts.len() == 1 && parts[0].0 == ScadTag::Body);
+        let cut = cube!(10.0).body() - translate!([2.0, 2.0, 2.0], cube!(2.0).keep_out(););
+        let parts = cut.tagged_parts();
+        assert!(parts.len() == 1 && parts[0].0 == ScadTag::Body);
+
+        let beside = translate!([12.0, 0.0, 0.0], cube!(10.0););
+        assert!(Scad::check_interference(&a, &beside, 1.0).is_empty());
+        let found = Scad::check_interference(&a, &beside, 3.0);
+        assert!(found.len() == 2 && approx_eq(found[0].distance.unwrap(), 2.0, 1.0e-9));
+
+        let inside_keep_out = translate!([4.0, 4.0, 14.0], cube!(2.0););
+        let found = Scad::check_interference(&a, &inside_keep_out, 0.5);
+        assert!(found.len() == 1 && found[0].a_tag == ScadTag::KeepOut);
+        assert!(found[0].distance == Some(0.0));
+
+        let crossing = translate!([-5.0, 4.0, 4.0], cube!([20.0, 2.0, 2.0]););
+        let found = Scad::check_interference(&a, &crossing, 0.0);
+        assert!(found.len() == 1 && found[0].distance == Some(0.0));
+
+        let label = translate!([12.0, 0.0, 0.0], text!("label", 5.0););
+        let found = Scad::check_interference(&a, &label, 3.0);
+        assert!(found.len() == 1 && found[0].distance.is_none());
+        assert!(cube!(1.0).keep_out().validate().is_ok());
+    }
+
+    #[test]
+    fn metric_thread_spec() {
+        use metric_thread::MetricThreadSpec;
+        let m6 = MetricThreadSpec::lookup(6);
+        assert!(m6.pitch == 1.0 && m6.external_d_maj == 5.794 && m6.nut_width == 10.0);
+        assert!(MetricThreadSpec::lookup(1) == MetricThreadSpec::lookup(2));
+        assert!(MetricThreadSpec::lookup(13) == MetricThreadSpec::lookup(12));
+        assert!(MetricThreadSpec::lookup(1000).external_d_maj == 99.32);
+        let mut last = 0.0;
+        for m in 2..=100 {
+            let spec = MetricThreadSpec::lookup(m);
+            assert!(spec.external_d_maj >= last && spec.internal_d_maj > spec.external_d_maj);
+            last = spec.external_d_maj;
+        }
+        assert!(metric_thread::pitch(8) == MetricThreadSpec::lookup(8).pitch);
+    }
+
+    #[test]
+    fn xor_of_2() {
+        let s = xor!(cube!(2.0); translate!([1.0, 0.0, 0.0], cube!(2.0);););
+        assert!(
+            s.to_string()
+                == "union() {\ndifference() {\ncube(size=[2, 2, 2], center=false);\ntranslate(v=[1, 0, 0]) {\ncube(size=[2, 2, 2], center=false);\n}\n}\ndifference() {\ntranslate(v=[1, 0, 0]) {\ncube(size=[2, 2, 2], center=false);\n}\ncube(size=[2, 2, 2], center=false);\n}\n}\n"
+        );
+        let volume = s.to_polyhedron(8).unwrap().volume();
+        assert!(approx_eq(volume, 8.0, 1.0e-9));
+
+        let a = Polyhedron::linear_extrude(&dim2::rect(2.0, 2.0, false), 2.0);
+        let mut b = a.clone();
+        b.translate(Pt3::new(1.0, 0.0, 0.0));
+        let x = a.xor(&b);
+        assert!(approx_eq(x.volume(), 8.0, 1.0e-9));
+        assert!(x.validate().is_valid());
+        assert!(xor!(cube!(1.0); cube!(1.0); cube!(1.0);).children.len() == 2);
+    }
+
+    #[test]
+    fn polyhedron_fill_holes() {
+        let closed = Polyhedron::cylinder(5.0, 10.0, 12);
+        let volume = closed.volume();
+        let mut open = closed.clone();
+        // the top cap of a 12 sided extrusion is faces 10..20
+        open.faces.drain(10..20);
+        assert!(!open.validate().is_valid());
+
+        let mut small = open.clone();
+        small.fill_holes(11);
+        assert!(small.faces.len() == open.faces.len());
+
+        open.fill_holes(12);
+        assert!(open.validate().is_valid());
+        assert!(approx_eq(open.volume(), volume, 1e-9));
+    }
+
+    #[test]
+    fn float_format_policy() {
+        let scad = translate!([0.1 + 0.2, 1.0 / 3.0, -0.0], cube!(1234567.0););
+        assert!(scad
+            .to_string()
+            .starts_with("translate(v=[0.3, 0.333333, 0]) {"));
+        assert!(scad
+            .to_string()
+            .contains("cube(size=[1234567, 1234567, 1234567]"));
+        crate::with_float_format(FloatFormat::Shortest, || {
+            assert!(scad
+                .to_string()
+                .starts_with("translate(v=[0.30000000000000004, 0.3333333333333333, -0])"));
+        });
+        let code = scad.format_with(&ScadFormat {
+            float_format: Some(FloatFormat::Decimals(2)),
+            ..Default::default()
+        });
+        assert!(code.starts_with("translate(v=[0.3, 0.33, 0]) {"));
+        assert!(Float(crate::dcos(90.0)).to_string() == "0");
+        assert!(Float(-crate::dcos(90.0)).to_string() == "0");
+        assert!(Float(1.5e-9).to_string() == "0.0000000015");
+
+        // the format of the calling thread is used on the writer thread
+        let file =
+            std::env::temp_dir().join(format!("scad_tree_float_{}.scad", std::process::id()));
+        let file_path = file.clone();
+        crate::with_float_format(FloatFormat::Decimals(2), || {
+            scad_file!(4, file_path, cube!(1.0 / 3.0););
+        });
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .starts_with("cube(size=[0.33, "));
+        let file_path = file.clone();
+        crate::with_float_format(FloatFormat::Decimals(3), || {
+            scad_file_result!(4, file_path, fn=8, cube!(1.0 / 3.0);).unwrap();
+        });
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .starts_with("$fn=8;\ncube(size=[0.333, "));
+        crate::with_float_format(FloatFormat::Decimals(1), || {
+            ScadFileOptions::default()
+                .save(file.to_str().unwrap(), vec![cube!(1.0 / 3.0)])
+                .unwrap();
+        });
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .starts_with("cube(size=[0.3, "));
+        std::fs::remove_file(&file).unwrap();
+        assert!(
+            Pt2s::from_pt2s(vec![Pt2::new(1e-7, 99.99999)]).to_string() == "[[0.0000001, 100]]"
+        );
+    }
+
+    #[test]
+    fn polyhedron_vertex_scalar_export() {
+        let mut p = Polyhedron::cylinder(1.0, 2.0, 4);
+        let plain = p.to_ply();
+        assert!(!plain.contains("property uchar red"));
+        assert!(plain.contains("element vertex 8\n") && plain.contains("element face 8\n"));
+
+        let heights: Vec<f64> = p.points.iter().map(|p| p.z).collect();
+        p.set_vertex_scalars(heights);
+        let colors = p.vertex_colors();
+        assert!(colors[0] == Pt3::new(0.0, 0.0, 1.0) && colors[4] == Pt3::new(1.0, 0.0, 0.0));
+
+        let ply = p.to_ply();
+        assert!(ply.contains("property float quality\n"));
+        assert!(ply.contains(" 0 0 255 0\n") && ply.contains(" 255 0 0 2\n"));
+
+        let obj = p.to_obj();
+        assert!(obj.lines().filter(|l| l.starts_with("v ")).count() == 8);
+        assert!(obj.lines().filter(|l| l.starts_with("f ")).count() == 8);
+        assert!(obj.lines().next().unwrap().ends_with(" 0 0 1"));
+        assert!(obj
+            .lines()
+            .filter(|l| l.starts_with("f "))
+            .all(|l| !l.split(' ').any(|i| i == "0")));
+
+        p.merge(Polyhedron::cylinder(1.0, 2.0, 4));
+        assert!(p.vertex_scalars().len() == 16 && p.vertex_scalar(8).is_nan());
+        assert!(p.vertex_colors()[8] == Pt3::new(0.5, 0.5, 0.5));
+        p.weld(1e-6);
+        assert!(p.vertex_scalars().len() == p.points.len());
+
+        // points added after the scalars are gray with a NaN quality
+        let mut p = Polyhedron::cylinder(1.0, 2.0, 4);
+        let heights: Vec<f64> = p.points.iter().map(|p| p.z).collect();
+        p.set_vertex_scalars(heights);
+        p.points.push(Pt3::new(5.0, 5.0, 5.0));
+        assert!(p.vertex_scalar(8).is_nan() && p.vertex_colors().len() == 9);
+        let ply = p.to_ply();
+        let rows: Vec<&str> = ply.lines().skip_while(|l| *l != "end_header").collect();
+        assert!(rows[1..10].iter().all(|row| row.split(' ').count() == 7));
+        assert!(rows[9] == "5 5 5 128 128 128 NaN");
+        p.weld(1e-6);
+        assert!(p.vertex_scalars().len() == 9 && p.vertex_scalar(8).is_nan());
+    }
+
+    #[test]
+    fn profile_turning_angles() {
+        let angles = dim2::turning_angles(&dim2::rect(2.0, 1.0, false));
+        assert!(angles.iter().all(|&a| approx_eq(a, 90.0, 1e-9)));
+        let angles = dim2::turning_angles(&dim2::circle(1.0, 36));
+        assert!(angles.iter().all(|&a| approx_eq(a, 10.0, 1e-9)));
+
+        let rounded = dim2::rounded_rect(10.0, 5.0, 1.0, 8, false);
+        let filleted = dim2::fillet_sharp_corners(&rounded, 45.0, 0.5, 4);
+        assert!(filleted.len() == rounded.len());
+        let filleted = dim2::fillet_sharp_corners(&dim2::rect(10.0, 5.0, false), 45.0, 0.5, 4);
+        assert!(filleted.len() == 20);
+        assert!(dim2::turning_angles(&filleted)
+            .iter()
+            .all(|&a| a.abs() < 45.0));
+    }
+
+    #[test]
+    fn polyhedron_curvature() {
+        let cube = cube!(2.0).to_polyhedron(8).unwrap();
+        let sharp = cube.sharp_edges(45.0);
+        assert!(sharp.len() == 12);
+        assert!(sharp.iter().all(|&(_, _, a)| approx_eq(a, 90.0, 1e-9)));
+
+        let sphere = sphere!(5.0).to_polyhedron(64).unwrap();
+        assert!(sphere.sharp_edges(30.0).is_empty());
+        let curvature = sphere.mean_curvature();
+        let mean = curvature.iter().sum::<f64>() / curvature.len() as f64;
+        assert!(approx_eq(mean, 0.2, 0.01));
+
+        // a cavity has its faces turned inward so its edges are concave
+        let mut hollow = cube!(4.0, true).to_polyhedron(8).unwrap();
+        let mut cavity = cube!(2.0, true).to_polyhedron(8).unwrap();
+        for face in cavity.faces.iter_mut() {
+            face.reverse();
+        }
+        hollow.merge(cavity);
+        let sharp = hollow.sharp_edges(45.0);
+        assert!(sharp.len() == 24 && sharp.iter().filter(|e| e.2 < 0.0).count() == 12);
+    }
+
+    #[test]
+    fn write_to_writer() {
+        let scad = translate!([1.0, 2.0, 3.0],
+            Polyhedron::cylinder(1.0, 2.0, 6).into_scad();
+            comment!("two\nlines");
+        );
+        let mut bytes = Vec::new();
+        scad.write_to(&mut bytes).unwrap();
+        assert!(String::from_utf8(bytes).unwrap() == scad.to_string());
+
+        let format = ScadFormat {
+            indent: "\t".to_string(),
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        scad.write_to_with(&mut bytes, &format).unwrap();
+        assert!(String::from_utf8(bytes).unwrap() == scad.format_with(&format));
+
+        let options = ScadFileOptions {
+            fn_: Some(16),
+            ..Default::default()
+        };
+        let children = [scad.clone()];
+        let mut bytes = Vec::new();
+        options.write_to(&mut bytes, &children).unwrap();
+        assert!(String::from_utf8(bytes).unwrap() == options.to_code(&children).unwrap());
+
+        // a full writer stops the write with its error
+        let mut full = [0u8; 16];
+        let error = scad.write_to(&mut full.as_mut_slice()).unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn polyhedron_overhangs() {
+        let up = Pt3::new(0.0, 0.0, 1.0);
+        let mut cube = cube!(2.0).to_polyhedron(8).unwrap();
+        assert!(cube.overhangs(up, 45.0).is_empty());
+        let drafts = cube.draft_angles(up);
+        assert!(drafts.iter().any(|&d| approx_eq(d, -90.0, 1e-9)));
+        assert!(drafts.iter().any(|&d| approx_eq(d, 90.0, 1e-9)));
+        assert!(drafts.iter().any(|&d| approx_eq(d, 0.0, 1e-9)));
+        // printed upside down the top is the bed and the old bottom stays supported
+        assert!(cube.overhangs(-up, 45.0).is_empty());
+
+        let mut cap = Polyhedron::cylinder(3.0, 1.0, 16);
+        cap.translate(Pt3::new(0.0, 0.0, 2.0));
+        let mushroom = Polyhedron::cylinder(1.0, 2.5, 16).union(&cap);
+        let overhangs = mushroom.overhangs(up, 45.0);
+        assert!(!overhangs.is_empty());
+        for &i in overhangs.iter() {
+            assert!(mushroom.faces[i].iter().all(|&p| approx_eq(
+                mushroom.points[p as usize].z,
+                2.0,
+                1e-9
+            )));
+        }
+        assert!(mushroom.overhangs(up, 90.0).is_empty());
+        let marker = mushroom.overhang_marker(up, 45.0);
+        assert!(marker.faces.len() == overhangs.len());
+        assert!(marker.into_scad().to_string().contains("color(\"Red\")"));
+
+        cube.rotate_x(60.0);
+        assert!(!cube.overhangs(up, 45.0).is_empty());
+    }
+
+    #[test]
+    fn print_orientation() {
+        let cube = cube!(2.0).to_polyhedron(8).unwrap();
+        let orientation = cube.print_orientation(45.0);
+        assert!(orientation.degrees == 0.0 && orientation.support_area == 0.0);
+        assert!(approx_eq(orientation.base_area, 4.0, 1e-9));
+
+        let mut cap = Polyhedron::cylinder(3.0, 1.0, 16);
+        cap.translate(Pt3::new(0.0, 0.0, 2.0));
+        let mut mushroom = Polyhedron::cylinder(1.0, 2.5, 16).union(&cap);
+        assert!(!mushroom.overhangs(Pt3::new(0.0, 0.0, 1.0), 45.0).is_empty());
+        let orientation = mushroom.orient_for_printing(45.0);
+        assert!(approx_eq(orientation.degrees, 180.0, 1e-9));
+        assert!(orientation.support_area == 0.0);
+        assert!(mushroom.overhangs(Pt3::new(0.0, 0.0, 1.0), 45.0).is_empty());
+        assert!(approx_eq(mushroom.bounds().0.z, 0.0, 1e-9));
+
+        let scad = cylinder!(2.0, 1.0) + translate!([0.0, 0.0, 2.0], cylinder!(1.0, 3.0););
+        let (oriented, orientation) = scad.orient_for_printing(16, 45.0).unwrap();
+        assert!(approx_eq(orientation.degrees, 180.0, 1e-9));
+        let mesh = oriented.to_polyhedron(16).unwrap();
+        assert!(approx_eq(mesh.bounds().0.z, 0.0, 1e-9));
+    }
+
+    #[test]
+    fn parallel_rings_keep_order() {
+        let points = crate::parallel::rings(1000, 2, |i, points| {
+            points.push(Pt3::new(i as f64, 0.0, 0.0));
+            points.push(Pt3::new(i as f64, 0.0, 0.0));
+        });
+        assert!(points.len() == 2000);
+        assert!(points
+            .iter()
+            .enumerate()
+            .all(|(i, p)| p.x == (i / 2) as f64));
+
+        let mut profile = dim2::circle(1.0, 16);
+        profile.translate(Pt2::new(5.0, 0.0));
+        let ring = Polyhedron::rotate_extrude(&profile, 360.0, 256);
+        assert!(ring.points.len() == 256 * 16);
+        let report = ring.validate();
+        assert!(report.non_manifold_edges.is_empty() && report.inconsistent_edges.is_empty());
+        let rod = metric_thread::threaded_rod(6, 40.0, 64, 0.0, 0.0, false, false);
+        assert!(rod.validate().is_ok());
+    }
+
+    #[test]
+    fn path_resample() {
+        let path = CubicBezier3D::new(
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(0.0, 0.0, 20.0),
+            Pt3::new(20.0, 0.0, 0.0),
+            Pt3::new(20.0, 0.0, 20.0),
+            24,
+        )
+        .gen_points();
+        let even = path.resample_by_distance(1.0);
+        let steps: Vec<f64> = even.windows(2).map(|w| (w[1] - w[0]).len()).collect();
+        let step = even.length() / steps.len() as f64;
+        assert!(approx_eq(step, 1.0, 0.05));
+        assert!(steps.iter().all(|&s| approx_eq(s, step, 0.05)));
+        assert!(even[0] == path[0] && even[even.len() - 1] == path[path.len() - 1]);
+        assert!(path.resample(3).len() == 4);
+
+        let square = Pt2s::from_slice(&dim2::rect_points(4.0, 4.0, false));
+        assert!(square.length() == 12.0);
+        let even = square.resample(6);
+        assert!(even.len() == 7 && even[1] == Pt2::new(0.0, 2.0) && even[6] == Pt2::new(4.0, 0.0));
+        assert!(square.point_at_distance(5.0) == Pt2::new(1.0, 4.0));
+        assert!(square.cumulative_lengths() == vec![0.0, 4.0, 8.0, 12.0]);
+        assert!(square.smooth(1).len() == 6);
+    }
+
+    #[test]
+    fn polyhedron_project_outline() {
+        let down = Pt3::new(0.0, 0.0, -1.0);
+        let cube = cube!([2.0, 3.0, 4.0]).to_polyhedron(8).unwrap();
+        let outline = cube.project_outline(down);
+        assert!(outline.len() == 1);
+        assert!(approx_eq(
+            crate::clip::signed_area(&outline[0]),
+            -12.0,
+            1e-9
+        ));
+        let side = cube.project_outline(Pt3::new(0.0, 1.0, 0.0));
+        assert!(approx_eq(crate::clip::signed_area(&side[0]), -16.0, 1e-9));
+        assert!(side[0].iter().all(|p| p.x >= -1e-9 && p.x <= 2.0 + 1e-9));
+
+        let mut hole = Polyhedron::cylinder(1.0, 6.0, 8);
+        hole.translate(Pt3::new(0.0, 0.0, -1.0));
+        let tube = Polyhedron::cylinder(2.0, 4.0, 8).difference(&hole);
+        let outline = tube.project_outline(down);
+        assert!(outline.len() == 2);
+        let area: f64 = outline
+            .iter()
+            .map(|o| crate::clip::signed_area(o) / 2.0)
+            .sum();
+        let octagon = |r: f64| 2.0 * 2.0_f64.sqrt() * r * r;
+        assert!(approx_eq(-area, octagon(2.0) - octagon(1.0), 1e-6));
+    }
+
+    #[test]
+    fn flat_pattern_unroll() {
+        let pattern = FlatPattern::cylinder(10.0, 20.0, 64);
+        let (min, max) = pattern.bounds().unwrap();
+        let perimeter = 64.0 * 2.0 * 10.0 * crate::dsin(180.0 / 64.0);
+        assert!(approx_eq(max.x - min.x, perimeter, 1.0e-9));
+        assert!(approx_eq(max.y - min.y, 20.0, 1.0e-9));
+        assert!(pattern.seams.len() == 2);
+
+        // The slant edges of a cone unroll to arcs around the apex
+        let pattern = FlatPattern::cone(10.0, 0.0, 10.0, 32);
+        let slant = 200.0f64.sqrt();
+        let apex = pattern.seams[0][1];
+        assert!(pattern.outlines[0]
+            .iter()
+            .all(|p| (*p - apex).len() < 1.0e-6 || approx_eq((*p - apex).len(), slant, 1.0e-6)));
+
+        let profiles = dim2::from_dxf_str(&pattern.to_dxf(), 16).unwrap();
+        // The two seams meet at the apex and are read back as one path
+        assert!(profiles.len() == 2);
+        assert!(profiles[0].len() == pattern.outlines[0].len());
+        assert!(pattern.to_svg().contains("stroke-dasharray"));
+
+        let path = Pt3s::from_pt3s(vec![
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(0.0, 0.0, 10.0),
+            Pt3::new(10.0, 0.0, 20.0),
+        ]);
+        let pattern = FlatPattern::sweep(&dim2::circle(2.0, 12), &path, 0.0, false, 1.0);
+        assert!(pattern.outlines.len() == 2);
+        assert!(pattern.seams.len() == 4);
+    }
+
+    #[test]
+    fn helix_paths() {
+        let path = dim3::helix(10.0, 4.0, 2.5, 24);
+        assert!(path.len() == 61);
+        assert!((path[60] - Pt3::new(-10.0, 0.0, 10.0)).len() < 1.0e-9);
+
+        let path = dim3::conical_spiral(10.0, 5.0, 0.0, 1.0, 36);
+        assert!((path[0] - Pt3::new(10.0, 0.0, 0.0)).len() < 1.0e-9);
+        assert!((path[36] - Pt3::new(5.0, 0.0, 0.0)).len() < 1.0e-9);
+
+        let spring = Polyhedron::sweep(
+            &dim2::circle(1.0, 12),
+            &dim3::helix(10.0, 4.0, 3.0, 36),
+            0.0,
+            false,
+        );
+        assert!(spring.validate().is_valid());
+    }
+
+    #[test]
+    fn wheel_generator() {
+        let wheel = Wheel {
+            spoke_style: SpokeStyle::Holes,
+            tire_profile: TireProfile::Chamfered(2.0),
+            segments: 24,
+            ..Wheel::new(50.0, 16.0)
+        };
+        let (min, max) = wheel.to_scad().bounds().unwrap();
+        assert!(approx_eq(max.x - min.x, 50.0, 1.0e-9));
+        assert!(approx_eq(max.z - min.z, 16.0, 1.0e-9));
+
+        let mesh = wheel.to_scad().to_polyhedron(24).unwrap();
+        let volume = mesh.volume();
+        assert!(volume > 0.0 && volume < std::f64::consts::PI * 25.0 * 25.0 * 16.0);
+        assert!(Wheel::default()
+            .to_scad()
+            .to_string()
+            .contains("rotate_extrude"));
+        assert!(format!("{:?}", wheel.tire_profile) == "Chamfered(2.0)");
+        assert!(format!("{:?}", wheel.spoke_style) == "Holes");
+    }
+
+    #[test]
+    fn compression_springs() {
+        let spring = dim3::compression_spring(1.0, 10.0, 20.0, 6.0, 12, SpringEnds::Closed);
+        assert!(spring.validate().is_valid());
+        let (min, max) = spring.bounds();
+        assert!(min.z > 0.0 && approx_eq(min.z, 0.0, 0.1));
+        assert!(max.z < 20.0 && approx_eq(max.z, 20.0, 0.1));
+
+        let spring =
+            dim3::compression_spring(1.0, 10.0, 20.0, 6.0, 12, SpringEnds::ClosedAndGround);
+        let (min, max) = spring.bounds();
+        assert!(approx_eq(min.z, 0.0, 1.0e-9) && approx_eq(max.z, 20.0, 1.0e-9));
+        assert!(spring.volume() > 0.0);
+    }
+
+    #[test]
+    fn profiles_with_holes() {
+        use std::f64::consts::PI;
+        let washer = Profile::new(dim2::circle(10.0, 64)).with_hole(dim2::circle(4.0, 32));
+        let triangles = washer.triangulate();
+        assert!(triangles.len() == (64 + 32) * 3);
+        let mesh = Polyhedron::linear_extrude_profile(&washer, 2.0);
+        assert!(mesh.validate().is_valid());
+        assert!(approx_eq(mesh.volume(), washer.area() * 2.0, 1.0e-6));
+
+        // a plate with four bolt holes from the boolean functions
+        let mut holes = Vec::new();
+        for (x, y) in [(5.0, 5.0), (35.0, 5.0), (5.0, 25.0), (35.0, 25.0)] {
+            let mut hole = dim2::circle(1.5, 24);
+            hole.translate(Pt2::new(x, y));
+            holes.push(hole);
+        }
+        let outlines = dim2::difference(&[dim2::rect(40.0, 30.0, false)], &holes);
+        let plates = Profile::from_outlines(&outlines);
+        assert!(plates.len() == 1 && plates[0].holes.len() == 4);
+        let area = 1200.0 - 4.0 * 12.0 * 1.5 * 1.5 * crate::dsin(15.0);
+        assert!(approx_eq(plates[0].area(), area, 1.0e-6));
+        let mesh = Polyhedron::linear_extrude_profile(&plates[0], 3.0);
+        assert!(mesh.validate().is_valid());
+        assert!(approx_eq(mesh.volume(), area * 3.0, 1.0e-6));
+
+        let path = Pt3s::from_pt3s(vec![
+            Pt3::new(0.0, 0.0, 0.0),
+            Pt3::new(0.0, 0.0, 10.0),
+            Pt3::new(10.0, 0.0, 20.0),
+        ]);
+        let tube = Polyhedron::sweep_profile(&washer, &path, &SweepOptions::default());
+        assert!(tube.validate().is_valid());
+        assert!(tube.volume() > 0.0);
+
+        let scad = linear_extrude!(5.0,
+            difference!(
+                circle!(r=10.0, fn=64);
+                circle!(r=4.0, fn=32);
+            );
+        );
+        let volume = scad.to_polyhedron(0).unwrap().volume();
+        assert!(approx_eq(volume, washer.area() * 5.0, 1.0e-6));
+        assert!(volume < PI * 100.0 * 5.0);
+    }
+
+    #[test]
+    fn vent_patterns() {
+        let slots = patterns::slot_profiles(40.0, 20.0, 40.0, 3.0, 2.0, 16);
+        assert!(slots.len() == 4);
+        let max_x = slots[0].iter().fold(f64::MIN, |m, p| m.max(p.x));
+        assert!(approx_eq(max_x, 40.0, 1e-9));
+
+        let holes = patterns::circle_grid_profiles(30.0, 30.0, 4.0, 2.0, false, 16);
+        assert!(holes.len() == 25);
+        let staggered = patterns::circle_grid_profiles(30.0, 30.0, 4.0, 2.0, true, 16);
+        assert!(staggered.len() > 25);
+        assert!(dim2::union(&staggered, &[]).len() == staggered.len());
+
+        let pattern = patterns::VentPattern::Hexagons { size: 5.0 };
+        let (min, max) = (Pt3::new(0.0, 0.0, 0.0), Pt3::new(60.0, 40.0, 30.0));
+        let cut = patterns::vent(pattern, BoxFace::Front, min, max, 1.0, 5.0, 2.0, 16);
+        let (cut_min, cut_max) = cut.bounds().unwrap();
+        assert!(cut_min.x >= 5.0 && cut_max.x <= 55.0 && cut_min.z >= 5.0 && cut_max.z <= 25.0);
+        assert!(cut_min.y < -0.5 && cut_max.y > 2.5);
+    }
+
+    #[test]
+    fn panel_cutouts() {
+        use crate::clip::signed_area;
+        use panel::PanelCutout;
+        let usb = PanelCutout::UsbC.profiles(0.0, 32);
+        assert!(usb.len() == 1 && signed_area(&usb[0]) < 0.0);
+        let area = 3.26 * (8.94 - 3.26) + std::f64::consts::PI * 1.63 * 1.63;
+        assert!(approx_eq(-signed_area(&usb[0]) / 2.0, area, 0.1));
+
+        let db9 = PanelCutout::Db9.profiles(0.2, 24);
+        assert!(db9.len() == 3);
+        let width = db9[0].iter().fold(f64::MIN, |m, p| m.max(p.x)) * 2.0;
+        assert!(width > 19.2 && width < 19.8);
+        assert!(db9.iter().all(|p| signed_area(p) < 0.0));
+
+        let parts = [
+            (PanelCutout::Hdmi, Pt2::new(10.0, 10.0)),
+            (PanelCutout::BarrelJack, Pt2::new(30.0, 10.0)),
+            (PanelCutout::Oled096, Pt2::new(20.0, 40.0)),
+        ];
+        let (min, max) = panel::cutouts(&parts, 0.1, 2.0, 24).bounds().unwrap();
+        assert!(approx_eq(min.z, -3.0, 1e-9) && approx_eq(max.z, 1.0, 1e-9));
+        assert!(approx_eq(max.x, 34.1, 1e-9));
+        assert!(PanelCutout::RockerSwitch
+            .negative(0.1, 2.0, 24)
+            .to_string()
+            .contains("linear_extrude"));
+    }
+
+    #[test]
+    fn convex_hulls() {
+        let mut points = dim2::circle(5.0, 16).to_vec();
+        points.push(Pt2::new(0.0, 0.0));
+        points.push(Pt2::new(1.0, 2.0));
+        let hull = dim2::convex_hull(&points);
+        assert!(hull.len() == 16);
+        assert!(crate::clip::signed_area(&hull) < 0.0);
+        assert!(dim2::is_convex(&hull));
+
+        let mut rng = crate::MersenneTwister::with_seed(7);
+        let mut points: Vec<Pt3> = (0..200)
+            .map(|_| {
+                Pt3::new(
+                    rng.f64_minmax(-1.0, 1.0),
+                    rng.f64_minmax(-1.0, 1.0),
+                    rng.f64_minmax(-1.0, 1.0),
+                )
+            })
+            .collect();
+        points.extend([-2.0, 2.0].iter().flat_map(|x| {
+            [-2.0, 2.0]
+                .iter()
+                .flat_map(move |y| [-2.0, 2.0].iter().map(move |z| Pt3::new(*x, *y, *z)))
+        }));
+        let hull = dim3::convex_hull(&points);
+        assert!(hull.validate().is_valid());
+        assert!(hull.points.len() == 8);
+        assert!(approx_eq(hull.volume(), 64.0, 1.0e-9));
+
+        let hull = dim3::convex_hull(&Polyhedron::cylinder(3.0, 4.0, 24).points);
+        assert!(hull.validate().is_valid());
+        assert!(approx_eq(
+            hull.volume(),
+            Polyhedron::cylinder(3.0, 4.0, 24).volume(),
+            1.0e-9
+        ));
+
+        // the same points give the same mesh every time
+        let again = dim3::convex_hull(&Polyhedron::cylinder(3.0, 4.0, 24).points);
+        assert!(again.points == hull.points && again.faces == hull.faces);
+    }
+
+    #[test]
+    fn minkowski_sums() {
+        let square = dim2::rect(10.0, 10.0, true);
+        let circle = dim2::circle(1.0, 32);
+        let rounded = dim2::minkowski(&square, &circle);
+        assert!(rounded.len() == 1);
+        let area = -crate::clip::signed_area(&rounded[0]) / 2.0;
+        let circle_area = -crate::clip::signed_area(&circle) / 2.0;
+        assert!(approx_eq(area, 100.0 + 40.0 + circle_area, 1.0e-6));
+
+        // an L shape keeps its inside corner rounded
+        let l = Pt2s::from_pt2s(vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(0.0, 20.0),
+            Pt2::new(5.0, 20.0),
+            Pt2::new(5.0, 5.0),
+            Pt2::new(20.0, 5.0),
+            Pt2::new(20.0, 0.0),
+        ]);
+        assert!(!dim2::is_convex(&l));
+        let grown = dim2::minkowski(&l, &circle);
+        assert!(grown.len() == 1);
+        assert!(!dim2::is_convex(&grown[0]));
+        let (min, max) = grown[0].iter().fold(
+            (Pt2::new(f64::MAX, f64::MAX), Pt2::new(f64::MIN, f64::MIN)),
+            |(min, max), p| {
+                (
+                    Pt2::new(min.x.min(p.x), min.y.min(p.y)),
+                    Pt2::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            },
+        );
+        assert!(approx_eq(min.x, -1.0, 1.0e-9) && approx_eq(max.y, 21.0, 1.0e-9));
+    }
+
+    #[test]
+    fn pcb_standoffs() {
+        let pcb = Pcb {
+            origin: Pt3::new(10.0, 5.0, 2.0),
+            ..Pcb::new(
+                50.0,
+                30.0,
+                vec![PcbHole::new(3.0, 3.0, 3.2), PcbHole::new(47.0, 27.0, 3.2)],
+            )
+        };
+        let (min, max) = pcb.standoffs().bounds().unwrap();
+        assert!(approx_eq(min.x, 10.0 + 3.0 - 3.6, 1e-9) && approx_eq(max.z, 7.0, 1e-9));
+        assert!(approx_eq(min.z, 2.0, 1e-9));
+
+        let (min, max) = pcb.clearance_holes(2.0).bounds().unwrap();
+        assert!(approx_eq(min.z, -1.0, 1e-9) && approx_eq(max.z, 8.0, 1e-9));
+
+        let (min, max) = pcb.keep_out().bounds().unwrap();
+        assert!(approx_eq(min.x, 9.5, 1e-9) && approx_eq(max.y, 35.5, 1e-9));
+        assert!(approx_eq(min.z, 5.0, 1e-9) && approx_eq(max.z, 7.0 + 1.6 + 15.0, 1e-9));
+
+        let inserts = Pcb {
+            insert: Some(3),
+            standoff_height: 8.0,
+            ..pcb.clone()
+        };
+        let mesh = inserts.standoffs().to_polyhedron(24).unwrap();
+        assert!(mesh.validate().is_valid());
+        assert!(pcb.board().to_polyhedron(24).unwrap().volume() < 50.0 * 30.0 * 1.6);
+    }
+
+    #[test]
+    fn fabrication_files() {
+        let drill = "M48
+; DRILL file {KiCad 7.0.0} date 2023-01-01
+; FORMAT={-:-/ absolute / metric / decimal}
+METRIC
+T1C0.800
+T2C3.200
+%
+G90
+G05
+T1
+X12.7Y-3.81
+T2
+X103.5Y-5.0
+X103.5Y-55.0
+T1
+X20.0Y-10.0G85X24.0Y-10.0
+M30
+";
+        let holes = PcbHole::from_excellon_str(drill).unwrap();
+        assert!(holes.len() == 4);
+        assert!(holes[0].position == Pt2::new(12.7, -3.81) && holes[0].diameter == 0.8);
+        assert!(holes[2].diameter == 3.2 && holes[2].position.y == -55.0);
+        assert!(holes[3].position == Pt2::new(22.0, -10.0));
+
+        let drill = "M48\nINCH,LZ\nT01C0.0320\n%\nT01\nX015Y-00125\nM30\n";
+        let holes = PcbHole::from_excellon_str(drill).unwrap();
+        assert!(approx_eq(holes[0].position.x, 1.5 * 25.4, 1e-9));
+        assert!(approx_eq(holes[0].position.y, -0.125 * 25.4, 1e-9));
+        assert!(approx_eq(holes[0].diameter, 0.032 * 25.4, 1e-9));
+
+        // a 50x30 board with one rounded corner
+        let gerber = "G04 Edge.Cuts*
+%FSLAX46Y46*%
+%MOMM*%
+%TF.FileFunction,Profile,NP*%
+%ADD10C,0.100000*%
+D10*
+G01*
+X0Y0D02*
+X50000000Y0D01*
+X50000000Y25000000D01*
+G75*
+G03*
+X45000000Y30000000I-5000000J0D01*
+G01*
+X0Y30000000D01*
+X0Y0D01*
+M02*
+";
+        let outlines = dim2::from_gerber_str(gerber, 36).unwrap();
+        assert!(outlines.len() == 1);
+        let area = -crate::clip::signed_area(&outlines[0]) / 2.0;
+        assert!(approx_eq(
+            area,
+            1500.0 - 25.0 + 25.0 * std::f64::consts::PI / 4.0,
+            0.1
+        ));
+
+        let pcb = Pcb::from_outline(&outlines, vec![PcbHole::new(3.0, 27.0, 3.2)]).unwrap();
+        assert!(approx_eq(pcb.width, 50.0, 1e-9) && approx_eq(pcb.depth, 30.0, 1e-9));
+        assert!(pcb.holes[0].position == Pt2::new(3.0, 27.0));
+        assert!(Pcb::from_outline(&[], Vec::new()).is_err());
+        assert!(dim2::from_gerber_str("X1.5.5Y0D01*", 36).is_err());
+    }
+
+    #[test]
+    fn tiled_regions() {
+        use crate::clip::signed_area;
+        use crate::patterns::{tile_profiles, TileShape};
+        let area = |profiles: &[Pt2s]| -profiles.iter().map(|p| signed_area(p)).sum::<f64>() / 2.0;
+        let square = vec![dim2::rect(10.0, 10.0, false)];
+
+        let cells = tile_profiles(&square, TileShape::Square, 2.0, 0.0, true, |t| {
+            Some(t.points.clone())
+        });
+        assert!(approx_eq(area(&cells), 100.0, 1.0e-6));
+        let cells = tile_profiles(&square, TileShape::Square, 2.0, 0.5, false, |t| {
+            Some(t.points.clone())
+        });
+        assert!(cells.len() == 16);
+        assert!(approx_eq(area(&cells), 16.0 * 1.5 * 1.5, 1.0e-6));
+
+        for shape in [TileShape::Hexagon, TileShape::Triangle] {
+            let cells = tile_profiles(&square, shape, 2.0, 0.0, true, |t| Some(t.points.clone()));
+            assert!(approx_eq(area(&cells), 100.0, 1.0e-6));
+            let cells = tile_profiles(&square, shape, 2.0, 0.3, false, |t| Some(t.points.clone()));
+            assert!(!cells.is_empty() && area(&cells) < 100.0);
+            assert!(cells.iter().all(|c| signed_area(c) < 0.0));
+        }
+
+        let grille = vec![dim2::circle(20.0, 64)];
+        let mut seen = 0;
+        let cells = tile_profiles(&grille, TileShape::Hexagon, 4.0, 1.0, false, |t| {
+            seen += 1;
+            if t.center.len() < 5.0 {
+                return None;
+            }
+            let scale = 1.0 - t.center.len() / 40.0;
+            Some(Pt2s::from_pt2s(
+                t.points
+                    .iter()
+                    .map(|p| t.center + (*p - t.center) * scale)
+                    .collect(),
+            ))
+        });
+        assert!(seen > cells.len() && !cells.is_empty());
+        assert!(cells
+            .iter()
+            .all(|c| c.iter().all(|p| p.len() < 20.0 && p.len() > 3.0)));
+        let _ = crate::patterns::tiles(&grille, TileShape::Triangle, 4.0, 1.0, true, |t| {
+            Some(t.points.clone())
+        });
+    }
+
+    #[test]
+    fn units() {
+        assert!(approx_eq(Length::inches(1.0).to_mm(), 25.4, 1.0e-12));
+        assert!(approx_eq(Length::fraction(5, 16).to_mm(), 7.9375, 1.0e-12));
+        assert!(approx_eq(
+            Length::new(2.0, Unit::Foot).to_inches(),
+            24.0,
+            1.0e-12
+        ));
+        assert!(approx_eq(
+            (Length::mm(10.0) + Length::new(1.0, Unit::Centimeter)).to(Unit::Meter),
+            0.02,
+            1.0e-12
+        ));
+        assert!(approx_eq(
+            Unit::Inch.convert(2.0, Unit::Millimeter),
+            50.8,
+            1.0e-12
+        ));
+        assert!(approx_eq(
+            f64::from(Length::inches(0.5) * 2.0),
+            25.4,
+            1.0e-12
+        ));
+
+        assert!(cube_in!([1.0, 2.0, 0.5]) == cube!([25.4, 50.8, 12.7]));
+        assert!(cube_in!(1.0, true) == cube!(25.4, true));
+        assert!(cylinder_in!(2.0, d = 0.5, fn = 32) == cylinder!(50.8, 6.35, 6.35, false, fn = 32));
+        assert!(cylinder_in!(1.0, 0.25) == cylinder!(25.4, 6.35));
+
+        let (min, max) = cube!(1.0).scaled_from_inches().bounds().unwrap();
+        assert!((min - Pt3::new(0.0, 0.0, 0.0)).len() < 1.0e-9);
+        assert!((max - Pt3::new(25.4, 25.4, 25.4)).len() < 1.0e-9);
+        let (_, max) = cube!(25.4).scaled_to_inches().bounds().unwrap();
+        assert!((max - Pt3::new(1.0, 1.0, 1.0)).len() < 1.0e-9);
+    }
+
+    #[test]
+    fn color_utilities() {
+        let close = |a: Pt4, b: Pt4| (a - b).len() < 1.0e-9 && approx_eq(a.w, b.w, 1.0e-9);
+        assert!(close(
+            Pt4::from_hsv(0.0, 1.0, 1.0, 1.0),
+            Pt4::new(1.0, 0.0, 0.0, 1.0)
+        ));
+        assert!(close(
+            Pt4::from_hsv(120.0, 1.0, 1.0, 0.5),
+            Pt4::new(0.0, 1.0, 0.0, 0.5)
+        ));
+        assert!(close(
+            Pt4::from_hsv(-120.0, 1.0, 0.5, 1.0),
+            Pt4::new(0.0, 0.0, 0.5, 1.0)
+        ));
+        let c = Pt4::new(0.2, 0.6, 0.4, 1.0);
+        let hsv = c.to_hsv();
+        assert!(close(Pt4::from_hsv(hsv.x, hsv.y, hsv.z, hsv.w), c));
+
+        assert!(ScadColor::CornflowerBlue.rgb() == 0x6495ED);
+        assert!(close(
+            Pt4::from(ScadColor::Red),
+            Pt4::new(1.0, 0.0, 0.0, 1.0)
+        ));
+        assert!(close(
+            crate::hex_to_rgba("#ff000080").unwrap(),
+            Pt4::new(1.0, 0.0, 0.0, 128.0 / 255.0)
+        ));
+        assert!(close(
+            crate::hex_to_rgba("0f0").unwrap(),
+            Pt4::new(0.0, 1.0, 0.0, 1.0)
+        ));
+        assert!(crate::hex_to_rgba("#12345").is_none());
+        let colored = color!(c=ScadColor::Navy, alpha=0.5, cube!(1.0););
+        assert!(close(
+            colored.color_rgba().unwrap(),
+            Pt4::new(0.0, 0.0, 128.0 / 255.0, 0.5)
+        ));
+        assert!(close(
+            color!("#ffffff", cube!(1.0);).color_rgba().unwrap(),
+            Pt4::new(1.0, 1.0, 1.0, 1.0)
+        ));
+
+        let palette = crate::color_palette(8);
+        for i in 0..palette.len() {
+            for j in 0..i {
+                assert!((palette[i] - palette[j]).len() > 0.1);
+            }
+        }
+        let parts = (cube!(1.0) + sphere!(1.0) + cylinder!(1.0, 1.0)).color_parts();
+        assert!(parts.op == ScadOp::Union);
+        assert!(parts
+            .children
+            .iter()
+            .all(|c| c.color_rgba().is_some() && c.children.len() == 1));
+        assert!(cube!(1.0).color_parts().color_rgba().is_some());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn image_heightmaps() {
+        use crate::image::Heightmap;
+        let pgm =
+            Heightmap::from_bytes(b"P2\n# gradient\n3 2\n255\n0 255 51\n255 0 102\n").unwrap();
+        assert!(pgm.width == 3 && pgm.height == 2);
+        assert!(approx_eq(pgm.get(2, 0), 0.2, 1.0e-12) && approx_eq(pgm.get(2, 1), 0.4, 1.0e-12));
+        let mut binary = b"P5 3 2 255\n".to_vec();
+        binary.extend_from_slice(&[0, 255, 51, 255, 0, 102]);
+        assert!(Heightmap::from_bytes(&binary).unwrap() == pgm);
+        let ppm = Heightmap::from_bytes(b"P3 1 1 255 255 255 255").unwrap();
+        assert!(approx_eq(ppm.get(0, 0), 1.0, 1.0e-12));
+
+        // A 2x2 24 bit bitmap stored bottom up: black, white over white, black.
+        let mut bmp = b"BM".to_vec();
+        let le32 = |v: &mut Vec<u8>, x: u32| v.extend_from_slice(&x.to_le_bytes());
+        le32(&mut bmp, 70);
+        le32(&mut bmp, 0);
+        le32(&mut bmp, 54);
+        le32(&mut bmp, 40);
+        le32(&mut bmp, 2);
+        le32(&mut bmp, 2);
+        bmp.extend_from_slice(&[1, 0, 24, 0]);
+        for _ in 0..6 {
+            le32(&mut bmp, 0);
+        }
+        bmp.extend_from_slice(&[255, 255, 255, 0, 0, 0, 0, 0]);
+        bmp.extend_from_slice(&[0, 0, 0, 255, 255, 255, 0, 0]);
+        // sizes larger than the file are rejected before anything is allocated
+        let mut huge = bmp.clone();
+        huge[18..26].copy_from_slice(&[255, 255, 255, 127, 255, 255, 255, 127]);
+        assert!(Heightmap::from_bytes(&huge).is_err());
+        let bmp = Heightmap::from_bytes(&bmp).unwrap();
+        assert!(bmp.values == vec![0.0, 1.0, 1.0, 0.0]);
+        assert!(Heightmap::from_bytes(b"GIF89a").is_err());
+        let huge = format!("P5 {} {} 255\n", usize::MAX / 2, 3);
+        assert!(Heightmap::from_bytes(huge.as_bytes()).is_err());
+        assert!(Heightmap::from_bytes(b"P2 100000 100000 255 0 0").is_err());
+        assert!(Heightmap::from_bytes(b"P6 100000 100000 255\n").is_err());
+
+        let dat = pgm.to_surface_dat(1.0, 2.0);
+        assert!(dat.lines().next().unwrap().split_whitespace().count() == 3);
+        assert!(dat.lines().next().unwrap().starts_with("2 1 "));
+        let big = pgm.resized(5, 3);
+        assert!(big.width == 5 && approx_eq(big.get(4, 2), 0.4, 1.0e-12));
+        assert!(approx_eq(big.get(1, 0), 0.5, 1.0e-12));
+
+        let litho = pgm.lithophane(20.0, 0.8, 3.0);
+        assert!(litho.validate().is_valid());
+        let (min, max) = litho.bounds();
+        assert!((min - Pt3::new(0.0, 0.0, 0.0)).len() < 1.0e-9);
+        assert!((max - Pt3::new(20.0, 10.0, 3.0)).len() < 1.0e-9);
+        assert!(litho.volume() > 20.0 * 10.0 * 0.8 && litho.volume() < 20.0 * 10.0 * 3.0);
+    }
+
+    #[test]
+    fn assembly_export() {
+        let dir = std::env::temp_dir().join(format!("scad_tree_assembly_{}", std::process::id()));
+        let mut assembly = Assembly::new("box");
+        assembly
+            .add_part("base", cube!([10.0, 10.0, 5.0]))
+            .add_part(
+                "lid",
+                translate!([0.0, 0.0, 5.0], cube!([10.0, 10.0, 1.0]);),
+            )
+            .set_color("lid", ScadColor::Red.into());
+        assembly.explode = Pt3::new(0.0, 0.0, 10.0);
+        assert!(assembly.parts().len() == 2 && assembly.part("lid").is_some());
+
+        let preview = assembly.preview();
+        assert!(preview.children.len() == 2);
+        assert!(preview.children[0].color_rgba().is_some());
+        let lid = &preview.children[1];
+        assert!(
+            lid.op
+                == ScadOp::Translate {
+                    v: Pt3::new(0.0, 0.0, 10.0)
+                }
+        );
+        assert!(lid.children[0].color_rgba() == Some(Pt4::new(1.0, 0.0, 0.0, 1.0)));
+
+        let paths = assembly.export(&dir).unwrap();
+        assert!(
+            paths
+                == vec![
+                    dir.join("base.scad"),
+                    dir.join("lid.scad"),
+                    dir.join("box.scad")
+                ]
+        );
+        let base = std::fs::read_to_string(&paths[0]).unwrap();
+        assert!(base == "cube(size=[10, 10, 5], center=false);\n");
+        let code = std::fs::read_to_string(&paths[2]).unwrap();
+        assert!(code.contains("color(") && code.contains("translate(v=[0, 0, 10])"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assembly.format = OutputFormat::Stl;
+        let manifest = assembly.manifest(&dir).unwrap();
+        assert!(manifest.names() == vec!["base", "lid", "box"]);
+        assembly.name = "lid".to_string();
+        assert!(assembly.manifest(&dir).is_err());
+    }
+
+    #[test]
+    fn roughened_surfaces() {
+        let path = Pt3s::from_pt3s(
+            (0..=40)
+                .map(|i| Pt3::new(0.0, 0.0, i as f64 * 0.5))
+                .collect(),
+        );
+        let handle = Polyhedron::sweep(&dim2::circle(5.0, 48), &path, 0.0, false);
+        let rough = |group: Option<u64>, seed: u32, handle: &Polyhedron| {
+            let mut result = handle.clone();
+            result.roughen(
+                0.4,
+                2.0,
+                group,
+                &mut crate::MersenneTwister::with_seed(seed),
+            );
+            result
+        };
+
+        let all = rough(None, 7, &handle);
+        assert!(all.points.len() == handle.points.len() && all.validate().is_valid());
+        let moved: Vec<f64> = all
+            .points
+            .iter()
+            .zip(handle.points.iter())
+            .map(|(a, b)| (*a - *b).len())
+            .collect();
+        assert!(moved.iter().all(|d| *d <= 0.4 + 1.0e-9));
+        assert!(moved.iter().filter(|d| **d > 0.05).count() > moved.len() / 4);
+        assert!(rough(None, 7, &handle).points == all.points);
+        assert!(rough(None, 8, &handle).points != all.points);
+
+        // Only the middle of the handle is textured.
+        let mut grouped = handle.clone();
+        grouped.set_group(0);
+        for (f, face) in handle.faces.iter().enumerate() {
+            let z = face
+                .iter()
+                .map(|&i| handle.points[i as usize].z)
+                .sum::<f64>()
+                / face.len() as f64;
+            if (5.0..15.0).contains(&z) && face.len() < 48 {
+                grouped.set_face_group(f, 1);
+            }
+        }
+        let middle = rough(Some(1), 7, &grouped);
+        for (a, b) in middle.points.iter().zip(handle.points.iter()) {
+            if b.z < 5.0 || b.z > 15.0 {
+                assert!(*a == *b);
+            }
+        }
+        assert!(middle
+            .points
+            .iter()
+            .zip(handle.points.iter())
+            .any(|(a, b)| (*a - *b).len() > 0.05));
+    }
+
+    #[test]
+    fn bill_of_materials() {
+        let pattern = metric_thread::bolt_circle(3, 4, 40.0);
+        let plate = cylinder!(3.0, 25.0) - pattern.holes(3.0, 16);
+        let hinge = (cube!(10.0) + cube!(2.0).bom_item("2x20 pin", 1)).bom_item("hinge", 2);
+        let tree = plate
+            + pattern.bolts(10.0, 2.0, 16)
+            + pattern.nuts(3.0, 2.4, 16)
+            + hinge
+            + metric_thread::hex_nut(5, 4.0, 16, false, true, false).disable();
+        let bom = tree.bom();
+        assert!(bom.lines().len() == 4);
+        assert!(bom.quantity("M3x10 hex bolt") == 4 && bom.quantity("M3 hex nut") == 4);
+        assert!(bom.quantity("hinge") == 2 && bom.quantity("2x20 pin") == 2);
+        assert!(bom.quantity("M5 hex nut, left hand") == 0);
+        assert!(
+            bom.to_csv() == "quantity,item\n4,M3x10 hex bolt\n4,M3 hex nut\n2,hinge\n2,2x20 pin\n"
+        );
+        assert!(bom.to_markdown().lines().nth(2) == Some("| 4 | M3x10 hex bolt |"));
+        let mut quoted = Bom::new();
+        quoted.add("bolt, \"long\"", 1);
+        assert!(quoted.to_csv().ends_with("1,\"bolt, \"\"long\"\"\"\n"));
+
+        // Marking leaves the output unchanged.
+        assert!(cube!(1.0).bom_item("block", 1).to_string() == cube!(1.0).to_string());
+        assert!(cube!(1.0).bom_item("block", 1).validate().is_ok());
+
+        let dir = std::env::temp_dir().join(format!("scad_tree_bom_{}", std::process::id()));
+        let mut assembly = Assembly::new("clamp");
+        assembly
+            .add_part("base", cube!(5.0))
+            .add_part("bolts", pattern.bolts(10.0, 2.0, 16));
+        assembly.bom_format = Some(BomFormat::Markdown);
+        assert!(assembly.bom().to_string() == "1x base\n1x bolts\n4x M3x10 hex bolt\n");
+        let paths = assembly.export(&dir).unwrap();
+        assert!(paths.last() == Some(&dir.join("clamp_bom.md")));
+        let md = std::fs::read_to_string(paths.last().unwrap()).unwrap();
+        assert!(md.contains("| 4 | M3x10 hex bolt |"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tapered_custom_threads() {
+        use crate::clip::signed_area;
+        use custom_thread::{CustomThread, ThreadProfile};
+        let pco = CustomThread::pco_1881();
+        let profile = pco.profile;
+        for inward in [false, true] {
+            let points = profile.points(12.0, inward);
+            assert!(signed_area(&points) < 0.0 && points.len() > 8);
+            assert!(points.iter().all(|p| p.y >= 0.0 && p.y < profile.pitch));
+        }
+        let outward = profile.points(12.0, false);
+        assert!(outward
+            .iter()
+            .all(|p| p.x >= 12.0 - 0.2 && p.x <= 12.0 + profile.depth + 1.0e-9));
+        let mate = profile.complement();
+        assert!(approx_eq(
+            mate.base_width() + profile.crest_width,
+            profile.pitch,
+            1.0e-9
+        ));
+        assert!(approx_eq(
+            mate.complement().crest_width,
+            profile.crest_width,
+            1.0e-9
+        ));
+        let sharp = ThreadProfile::new(2.0, 1.2).points(5.0, false);
+        assert!(sharp.len() == 6);
+
+        // The ridge follows the cone of the root.
+        let ridge = crate::thread::tapered_helical_ridge(
+            &outward, 12.0, -0.1, 8.1, 7.0, 36, 60.0, 60.0, false,
+        );
+        assert!(ridge.validate().is_valid());
+        for p in ridge.points.iter() {
+            let r = Pt2::new(p.x, p.y).len() + 0.1 * p.z;
+            assert!(r > 12.0 - 0.2 && r < 12.0 + profile.depth + 1.0e-6);
+        }
+
+        let neck = pco.external();
+        assert!(neck.children.len() == 4 && neck.validate().is_ok());
+        let (min, max) = neck.bounds().unwrap();
+        assert!(approx_eq(max.x, 27.43 / 2.0, 0.05) && approx_eq(min.z, 0.0, 1.0e-9));
+        assert!(max.z <= 10.0 + 1.0e-9);
+        let cap = pco.internal(32.0);
+        assert!(cap.children.len() == 4 && cap.validate().is_ok());
+        let (_, max) = cap.bounds().unwrap();
+        assert!(approx_eq(max.x, 16.0, 1.0e-9));
+
+        let hose = CustomThread {
+            taper: -1.0 / 16.0,
+            fit: crate::metric_thread::ThreadFit::Normal,
+            ..CustomThread::garden_hose()
+        };
+        assert!(approx_eq(hose.profile.pitch, 25.4 / 11.5, 1.0e-12));
+        assert!(hose.external().validate().is_ok() && hose.internal(34.0).validate().is_ok());
+    }
+
+    #[test]
+    fn dial_faces() {
+        let clock = Dial::default();
+        let face = clock.into_scad();
+        assert!(face.op == ScadOp::Difference && face.children[0].op == ScadOp::Difference);
+        assert!(face.children[1].to_string() == "circle(r=4, $fn=128);\n");
+        let marks = clock.marks().to_string();
+        assert!(marks.matches("text(").count() == 12);
+        assert!(marks.contains("text(text=\"12\"") && marks.contains("text(text=\"11\""));
+        assert!(marks.contains("square(size=[1.5, 6], center=true)"));
+        assert!(marks.contains("square(size=[0.6, 3], center=true)"));
+
+        // A 270 degree gauge puts the last numeral at 9 o'clock.
+        let gauge = Dial {
+            degrees: 270.0,
+            start_degrees: -135.0,
+            major_ticks: 7,
+            minor_divisions: 0,
+            numerals: (0..7).map(|i| (i * 10).to_string()).collect(),
+            bore_diameter: 0.0,
+            ..Default::default()
+        };
+        let face = gauge.into_scad();
+        assert!(face.op == ScadOp::Difference);
+        assert!(matches!(face.children[0].op, ScadOp::Circle { radius, .. } if radius == 50.0));
+        let marks = gauge.marks();
+        assert!(
+            marks.op
+                == ScadOp::Rotate {
+                    a: Some(135.0),
+                    a_is_scalar: true,
+                    v: Pt3::new(0.0, 0.0, 0.0),
+                }
+        );
+        let marks = marks.to_string();
+        assert!(marks.matches("text(").count() == 7);
+        assert!(!marks.contains("square(size=[0.6, 3]"));
+        assert!(marks.contains("translate(v=[-35, "));
+
+        let extruded = clock.into_scad_extruded(3.0, 1.0);
+        assert!(extruded.validate().is_ok());
+        let (min, max) = extruded.bounds().unwrap();
+        assert!(approx_eq(min.z, 0.0, 1.0e-9) && approx_eq(max.z, 3.0, 1.0e-9));
+        assert!(approx_eq(max.x, 50.0, 1.0e-6));
+    }
+
+    #[test]
+    fn gridfinity_bins() {
+        let bin = GridfinityBin::default().into_scad();
+        assert!(bin.validate().is_ok());
+        let (min, max) = bin.bounds().unwrap();
+        assert!(approx_eq(min.x, -20.75, 1.0e-9) && approx_eq(max.y, 20.75, 1.0e-9));
+        assert!(approx_eq(min.z, 0.0, 1.0e-9) && approx_eq(max.z, 21.0 + 4.4, 1.0e-9));
+
+        let flat = GridfinityBin {
+            grid_x: 2,
+            height_units: 2,
+            stacking_lip: false,
+            ..Default::default()
+        }
+        .into_scad();
+        let (min, max) = flat.bounds().unwrap();
+        assert!(approx_eq(min.x, -41.75, 1.0e-9) && approx_eq(max.x, 41.75, 1.0e-9));
+        assert!(approx_eq(max.y, 20.75, 1.0e-9) && approx_eq(max.z, 14.0, 1.0e-9));
+
+        // Four holes under each cell, each a magnet pocket and a screw hole.
+        let holes = GridfinityBin {
+            grid_x: 2,
+            grid_y: 2,
+            magnet_holes: true,
+            screw_holes: true,
+            ..Default::default()
+        }
+        .into_scad();
+        assert!(holes.op == ScadOp::Difference && holes.children.len() == 17);
+        assert!(holes.children[1..]
+            .iter()
+            .all(|hole| hole.children[0].children.len() == 2));
+        let magnets = GridfinityBin {
+            magnet_holes: true,
+            ..Default::default()
+        }
+        .into_scad();
+        assert!(magnets.children.len() == 5 && magnets.children[1].children[0].children.len() == 1);
+
+        let tabbed = GridfinityBin {
+            label_tab: true,
+            ..Default::default()
+        }
+        .into_scad();
+        assert!(tabbed.validate().is_ok());
+        assert!(tabbed.to_string().len() > bin.to_string().len());
+    }
+
+    #[test]
+    fn polyhedron_face_groups() {
+        let mut tube = Polyhedron::cylinder(5.0, 10.0, 8);
+        let face_count = tube.faces.len();
+        assert!(tube.face_groups().is_empty() && tube.face_group(3) == 0);
+        tube.set_face_group(1, 2).set_group_color(2, ScadColor::Red);
+        assert!(tube.face_groups().len() == face_count && tube.face_group(1) == 2);
+        assert!(tube.group_colors().get(&2) == Some(&ScadColor::Red));
+
+        // One child per group, faces missing from a short group list are in group 0.
+        tube.face_groups.truncate(2);
+        let split = tube.clone().into_scad();
+        assert!(split.op == ScadOp::Union && split.children.len() == 2);
+        let face_counts: Vec<usize> = split
+            .children
+            .iter()
+            .map(|child| match &child.op {
+                ScadOp::Polyhedron { faces, .. } => faces.len(),
+                _ => match &child.children[0].op {
+                    ScadOp::Polyhedron { faces, .. } => faces.len(),
+                    _ => 0,
+                },
+            })
+            .collect();
+        assert!(face_counts == vec![face_count - 1, 1]);
+        assert!(matches!(split.children[1].op, ScadOp::Color { .. }));
+
+        // Uncolored groups are written as a single polyhedron.
+        let mut plain = Polyhedron::cylinder(5.0, 10.0, 8);
+        plain.set_group(4);
+        assert!(matches!(plain.into_scad().op, ScadOp::Polyhedron { .. }));
+
+        // Merging keeps the groups of both sides and the colors of the first.
+        let mut merged = Polyhedron::cylinder(5.0, 10.0, 8);
+        merged.set_group_color(2, ScadColor::Blue);
+        let mut other = Polyhedron::cylinder(2.0, 20.0, 8);
+        other.set_group(2).set_group_color(2, ScadColor::Green);
+        merged.merge(other);
+        assert!(merged.faces.len() == face_count * 2);
+        assert!(merged.face_group(0) == 0 && merged.face_group(face_count) == 2);
+        assert!(merged.group_colors().get(&2) == Some(&ScadColor::Blue));
+        merged.translate(Pt3::new(1.0, 0.0, 0.0));
+        merged.rotate_z(90.0);
+        assert!(merged.face_groups().len() == face_count * 2);
+    }
+
+    #[test]
+    fn scad_errors() {
+        use std::error::Error;
+        let missing = ScadError::from(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no such file",
+        ));
+        assert!(missing.to_string() == "io error: no such file" && missing.source().is_some());
+        assert!(ScadError::Empty.to_string() == "nothing to output");
+        assert!(ScadError::Parse("bad".to_string()).to_string() == "parse error: bad");
+        assert!(ScadError::Invalid("bad".to_string()).source().is_none());
+        let message = |payload: Box<dyn std::any::Any + Send>| match ScadError::from_panic(payload)
+        {
+            ScadError::ThreadPanic(message) => message,
+            _ => String::new(),
+        };
+        assert!(message(Box::new("static")) == "static");
+        assert!(message(Box::new("owned".to_string())) == "owned");
+        assert!(message(Box::new(42)) == "unknown panic");
+
+        let dir = std::env::temp_dir().join(format!("scad_tree_errors_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cube.scad").to_string_lossy().to_string();
+        cube!(2.0).try_save(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap() == cube!(2.0).to_string());
+        let nowhere = dir.join("missing").join("cube.scad");
+        let nowhere = nowhere.to_string_lossy().to_string();
+        assert!(cube!(2.0).try_save(&nowhere).unwrap_err().kind() == std::io::ErrorKind::NotFound);
+
+        let file_path = path.clone();
+        scad_file_result!(4, file_path, fn=32, cube!(2.0);).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap() == format!("$fn=32;\n{}", cube!(2.0)));
+        let file_path = nowhere.clone();
+        assert!(matches!(
+            scad_file_result!(4, file_path, cube!(2.0);),
+            Err(ScadError::Io(_))
+        ));
+        fn explode() -> Scad {
+            panic!("boom")
+        }
+        let file_path = path.clone();
+        match scad_file_result!(4, file_path, explode();) {
+            Err(ScadError::ThreadPanic(message)) => assert!(message == "boom"),
+            _ => panic!("expected a thread panic"),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn blocks_follow_the_operation() {
+        let plain_color = Scad {
+            op: ScadOp::Color {
+                rgba: None,
+                color: None,
+                hex: None,
+                alpha: None,
+            },
+            children: vec![cube!(1.0)],
+        };
+        assert!(plain_color.to_string() == "color() {\ncube(size=[1, 1, 1], center=false);\n}\n");
+        let plain_offset = Scad {
+            op: ScadOp::Offset {
+                r: None,
+                delta: None,
+                chamfer: false,
+            },
+            children: vec![square!(1.0)],
+        };
+        assert!(
+            plain_offset.to_string()
+                == "offset(delta=0, chamfer=false) {\nsquare(size=[1, 1], center=false);\n}\n"
+        );
+
+        // Braces inside comments and raw code don't open a block.
+        let open = union!(comment!("x {{"); raw_scad!("module m() {{"); cube!(1.0););
+        assert!(
+            open.to_string()
+                == "union() {\n// x {\nmodule m() {\ncube(size=[1, 1, 1], center=false);\n}\n"
+        );
+        assert!(union!(cube!(1.0);).has_block() && !raw_scad!("x").has_block());
+        let call = Scad::call("m", Vec::new(), Vec::new());
+        assert!(!call.has_block() && call.to_string() == "m();\n");
+        assert!(Scad::call("m", Vec::new(), vec![cube!(1.0)]).has_block());
+    }
+
+    #[test]
+    fn helical_ridge_faces() {
+        let profile = Pt2s::from_pt2s(vec![
+            Pt2::new(5.0, 0.0),
+            Pt2::new(5.0, 0.8),
+            Pt2::new(5.6, 0.5),
+            Pt2::new(5.6, 0.3),
+        ]);
+        for left_hand_thread in [false, true] {
+            let ridge = crate::thread::helical_ridge(
+                &profile,
+                5.0,
+                1.0,
+                4.0,
+                24,
+                90.0,
+                90.0,
+                left_hand_thread,
+            );
+            let report = ridge.validate();
+            assert!(report.is_valid() && !report.inverted);
+            let rings = ridge.points.len() / profile.len();
+            assert!(ridge.faces.len() == (rings - 1) * profile.len() + 4);
+            assert!(ridge.volume() > 0.0);
+        }
+    }
+
+    #[test]
+    fn rotation_about_axis() {
+        use crate::Mt4;
+        let close = |a: Pt3, b: Pt3| (a - b).len() < 1.0e-9;
+        let rotated = |m: &Mt4, p: Pt3| (*m * p.as_pt4(1.0)).as_pt3();
+        let points = [
+            Pt3::new(1.0, 0.0, 0.0),
+            Pt3::new(0.0, 1.0, 0.0),
+            Pt3::new(0.0, 0.0, 1.0),
+            Pt3::new(1.0, -2.0, 3.0),
+        ];
+        for degrees in [30.0, 90.0, 200.0] {
+            let pairs = [
+                (
+                    Mt4::rot_vec(1.0, 0.0, 0.0, degrees),
+                    Mt4::rot_x_matrix(degrees),
+                ),
+                (
+                    Mt4::rot_vec(0.0, 1.0, 0.0, degrees),
+                    Mt4::rot_y_matrix(degrees),
+                ),
+                (
+                    Mt4::rot_vec(0.0, 0.0, 1.0, degrees),
+                    Mt4::rot_z_matrix(degrees),
+                ),
+            ];
+            for (about_axis, expected) in pairs.iter() {
+                for p in points {
+                    assert!(close(rotated(about_axis, p), rotated(expected, p)));
+                }
+            }
+        }
+        // A third of a turn about the diagonal cycles the axes.
+        let k = 1.0 / 3.0_f64.sqrt();
+        let diagonal = Mt4::rot_vec(k, k, k, 120.0);
+        assert!(close(rotated(&diagonal, points[0]), points[1]));
+        assert!(close(rotated(&diagonal, points[1]), points[2]));
+        assert!(close(rotated(&diagonal, points[2]), points[0]));
+    }
+
+    #[test]
+    fn rotate_extrude_full_turn() {
+        let mut square = dim2::rect(2.0, 2.0, false);
+        square.translate(Pt2::new(4.0, 0.0));
+        square.reverse();
+        let ring = Polyhedron::rotate_extrude(&square, 360.0, 36);
+        // No seam ring is added so the ring is closed without welding.
+        assert!(ring.points.len() == 36 * 4);
+        assert!(ring.faces.len() == 36 * 4);
+        assert!(ring.validate().is_valid());
+        let expected = 4.0 * std::f64::consts::TAU * 5.0;
+        assert!(approx_eq(ring.volume(), expected, expected * 0.01));
+    }
+
+    #[test]
+    fn polygon_with_paths_display() {
+        let points = Pt2s::from_pt2s(vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(0.0, 1.0),
+            Pt2::new(1.0, 0.0),
+        ]);
+        let paths = Paths::from_paths(vec![Indices::from_indices(vec![0, 1, 2])]);
+        let res = polygon!(points, paths, 3);
+        assert!(
+            res.to_string()
+                == "polygon(points=[[0, 0],[0, 1],[1, 0]], paths=[[0, 1, 2]], convexity=3);\n"
+        );
+    }
+
+    #[test]
+    fn degenerate_holes() {
+        let outline = dim2::rect(10.0, 10.0, false);
+        let area = |holes: &[Pt2s]| {
+            let mut points = outline.clone();
+            for hole in holes.iter() {
+                points.extend(hole.iter());
+            }
+            let triangles = crate::triangulate2d_holes(&outline, holes);
+            let areas: Vec<f64> = triangles
+                .chunks_exact(3)
+                .map(|t| {
+                    let (a, b, c) = (
+                        points[t[0] as usize],
+                        points[t[1] as usize],
+                        points[t[2] as usize],
+                    );
+                    ((c - a).x * (b - a).y - (c - a).y * (b - a).x) / 2.0
+                })
+                .collect();
+            assert!(areas.iter().all(|&a| a >= 0.0));
+            areas.iter().sum::<f64>()
+        };
+        // a hole touching the outline can't see a vertex past the touching point
+        let touching = Pt2s::from_pt2s(vec![
+            Pt2::new(6.0, 4.0),
+            Pt2::new(10.0, 5.0),
+            Pt2::new(6.0, 6.0),
+        ]);
+        assert!(approx_eq(area(&[touching]), 96.0, 1.0e-9));
+        // overlapping holes leave the polygon without ears before it is done
+        let mut a = dim2::rect(4.0, 4.0, false);
+        a.translate(Pt2::new(2.0, 2.0));
+        let mut b = dim2::rect(4.0, 4.0, false);
+        b.translate(Pt2::new(4.0, 4.0));
+        assert!(approx_eq(area(&[a, b]), 76.0, 1.0e-9));
+        // a hole that crosses itself runs out of ears, the old clipping stopped
+        // there with 79.25 of the outline filled
+        let crossing = Pt2s::from_pt2s(vec![
+            Pt2::new(6.0, 7.5),
+            Pt2::new(4.5, 6.0),
+            Pt2::new(2.5, 5.0),
+            Pt2::new(4.0, 8.5),
+            Pt2::new(4.0, 7.0),
+            Pt2::new(2.5, 7.5),
+        ]);
+        assert!(area(&[crossing]) > 90.0);
+        // a hole outside the outline is still bridged without panicking
+        let outside = Pt2s::from_pt2s(vec![
+            Pt2::new(16.0, 4.0),
+            Pt2::new(18.0, 5.0),
+            Pt2::new(16.0, 6.0),
+        ]);
+        area(&[outside]);
+    }
 }
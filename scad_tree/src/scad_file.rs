@@ -0,0 +1,450 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Writes whole .scad files with repeated subtrees moved into modules.
+
+use {
+    crate::{float_format, prelude::*, scad::write_io},
+    std::collections::HashMap,
+};
+
+/// The camera OpenSCAD starts with when it opens a file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// The rotation of the camera in degrees, $vpr.
+    pub rotation: Pt3,
+    /// The point the camera looks at, $vpt.
+    pub translation: Pt3,
+    /// The distance from the camera to the point it looks at, $vpd.
+    pub distance: f64,
+    /// The field of view in degrees, $vpf.
+    pub fov: Option<f64>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            rotation: Pt3::new(55.0, 0.0, 25.0),
+            translation: Pt3::new(0.0, 0.0, 0.0),
+            distance: 140.0,
+            fov: None,
+        }
+    }
+}
+
+impl Viewport {
+    /// A viewport looking at the center of a box from far enough away to see all of it.
+    ///
+    /// min: The minimum corner of the box.
+    ///
+    /// max: The maximum corner of the box.
+    ///
+    /// return: The viewport with the default OpenSCAD rotation.
+    pub fn framing(min: Pt3, max: Pt3) -> Self {
+        let radius = (max - min).len() / 2.0;
+        // the default field of view of OpenSCAD
+        let fov: f64 = 22.5;
+        Self {
+            translation: (min + max) / 2.0,
+            distance: (radius / (fov / 2.0).to_radians().sin()).max(1.0),
+            ..Default::default()
+        }
+    }
+
+    /// A viewport framing the bounds of Scads.
+    ///
+    /// scads: The Scads to look at.
+    ///
+    /// return: The viewport or None if none of the Scads have bounds.
+    pub fn frame(scads: &[Scad]) -> Option<Self> {
+        let (min, max) = scads.iter().filter_map(|scad| scad.bounds()).reduce(
+            |(min0, max0), (min1, max1)| {
+                (
+                    Pt3::new(min0.x.min(min1.x), min0.y.min(min1.y), min0.z.min(min1.z)),
+                    Pt3::new(max0.x.max(max1.x), max0.y.max(max1.y), max0.z.max(max1.z)),
+                )
+            },
+        )?;
+        Some(Self::framing(min, max))
+    }
+}
+
+/// Writes the viewport variables each on its own line.
+impl std::fmt::Display for Viewport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "$vpr={};", self.rotation)?;
+        writeln!(f, "$vpt={};", self.translation)?;
+        writeln!(f, "$vpd={};", Float(self.distance))?;
+        if let Some(fov) = self.fov {
+            writeln!(f, "$vpf={};", Float(fov))?;
+        }
+        Ok(())
+    }
+}
+
+/// The options for writing a .scad file with ScadFileOptions::save.
+///
+/// Start from ScadFileOptions::default() and use the with_ methods, more
+/// options may be added.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct ScadFileOptions {
+    /// The $fa written at the top of the file.
+    pub fa: Option<f64>,
+    /// The $fs written at the top of the file.
+    pub fs: Option<f64>,
+    /// The $fn written at the top of the file.
+    pub fn_: Option<u64>,
+    /// Variables written at the top of the file for the customizer.
+    pub vars: Vec<ScadVar>,
+    /// The camera written at the top of the file.
+    pub viewport: Option<Viewport>,
+    /// Write a camera framing the bounds of the children when viewport is None.
+    pub frame_model: bool,
+    /// Subtrees that appear at least this many times are written once as a
+    /// module and called from each place, 0 turns this off.
+    pub dedup_threshold: usize,
+    /// The indentation and line breaks.
+    pub format: ScadFormat,
+    /// The stack size in MB of the thread that writes the file.
+    pub stack_size: usize,
+}
+
+impl Default for ScadFileOptions {
+    fn default() -> Self {
+        Self {
+            fa: None,
+            fs: None,
+            fn_: None,
+            vars: Vec::new(),
+            viewport: None,
+            frame_model: false,
+            dedup_threshold: 0,
+            format: ScadFormat::default(),
+            stack_size: 512,
+        }
+    }
+}
+
+impl ScadFileOptions {
+    /// Sets the $fa written at the top of the file.
+    pub fn with_fa(mut self, fa: f64) -> Self {
+        self.fa = Some(fa);
+        self
+    }
+
+    /// Sets the $fs written at the top of the file.
+    pub fn with_fs(mut self, fs: f64) -> Self {
+        self.fs = Some(fs);
+        self
+    }
+
+    /// Sets the $fn written at the top of the file.
+    pub fn with_fn(mut self, fn_: u64) -> Self {
+        self.fn_ = Some(fn_);
+        self
+    }
+
+    /// Sets the variables written at the top of the file for the customizer.
+    pub fn with_vars(mut self, vars: Vec<ScadVar>) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// Sets the camera written at the top of the file.
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    /// Writes a camera framing the bounds of the children when no viewport is set.
+    pub fn with_frame_model(mut self, frame_model: bool) -> Self {
+        self.frame_model = frame_model;
+        self
+    }
+
+    /// Sets how many times a subtree must appear to be written as a module, 0 turns this off.
+    pub fn with_dedup_threshold(mut self, dedup_threshold: usize) -> Self {
+        self.dedup_threshold = dedup_threshold;
+        self
+    }
+
+    /// Sets the indentation and line breaks.
+    pub fn with_format(mut self, format: ScadFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the stack size in MB of the thread that writes the file.
+    pub fn with_stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = stack_size;
+        self
+    }
+
+    /// Creates the OpenSCAD code of a file.
+    ///
+    /// children: The top level Scads of the file.
+    ///
+    /// return: The code or an error if a Scad is invalid.
+    pub fn to_code(&self, children: &[Scad]) -> Result<String, ScadError> {
+        for child in children.iter() {
+            child.validate()?;
+        }
+        let mut code = String::new();
+        self.format
+            .scoped(|| self.write_code(&mut code, children))
+            .unwrap();
+        Ok(code)
+    }
+
+    /// Streams the OpenSCAD code of a file to a writer.
+    ///
+    /// Nothing is buffered beyond one node at a time so very large polyhedrons
+    /// don't need to fit in memory twice.
+    ///
+    /// w: The writer, wrap files in a BufWriter.
+    ///
+    /// children: The top level Scads of the file.
+    ///
+    /// return: An error if a Scad is invalid or the writer fails.
+    pub fn write_to<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        children: &[Scad],
+    ) -> Result<(), ScadError> {
+        for child in children.iter() {
+            child.validate()?;
+        }
+        self.format
+            .scoped(|| write_io(w, |f| self.write_code(f, children)))?;
+        Ok(())
+    }
+
+    fn write_code<F: std::fmt::Write>(&self, f: &mut F, children: &[Scad]) -> std::fmt::Result {
+        let line_break = &self.format.line_break;
+        if let Some(fa) = self.fa {
+            write!(f, "$fa={};{}", Float(fa), line_break)?;
+        }
+        if let Some(fs) = self.fs {
+            write!(f, "$fs={};{}", Float(fs), line_break)?;
+        }
+        if let Some(fn_) = self.fn_ {
+            write!(f, "$fn={};{}", fn_, line_break)?;
+        }
+        let viewport = match self.viewport {
+            Some(viewport) => Some(viewport),
+            None if self.frame_model => Viewport::frame(children),
+            None => None,
+        };
+        if let Some(viewport) = viewport {
+            f.write_str(&viewport.to_string().replace('\n', line_break))?;
+        }
+        for var in self.vars.iter() {
+            f.write_str(&var.to_string().replace('\n', line_break))?;
+        }
+        if !self.vars.is_empty() {
+            f.write_str(line_break)?;
+        }
+        if self.dedup_threshold == 0 {
+            for child in children.iter() {
+                child.write_tree(f, &self.format, 0, "")?;
+            }
+            return Ok(());
+        }
+        let (modules, children) = Scad::extract_modules(children, self.dedup_threshold);
+        for (name, body) in modules.iter() {
+            write!(f, "module {}() {{{}", name, line_break)?;
+            body.write_tree(f, &self.format, 1, "")?;
+            write!(f, "}}{}", line_break)?;
+        }
+        for child in children.iter() {
+            child.write_tree(f, &self.format, 0, "")?;
+        }
+        Ok(())
+    }
+
+    /// Writes a .scad file on a thread with a large stack.
+    ///
+    /// path: The path of the file.
+    ///
+    /// children: The top level Scads of the file.
+    ///
+    /// return: An error if a Scad is invalid or the file could not be written.
+    pub fn save(&self, path: &str, children: Vec<Scad>) -> Result<(), ScadError> {
+        let mut options = self.clone();
+        // the float format is thread local so carry it over to the new thread
+        options.format.float_format = Some(self.format.float_format.unwrap_or_else(float_format));
+        let path = path.to_string();
+        let thread = std::thread::Builder::new()
+            .stack_size(self.stack_size * 1024 * 1024)
+            .spawn(move || -> Result<(), ScadError> {
+                let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                options.write_to(&mut file, &children)?;
+                file.flush()?;
+                Ok(())
+            })?;
+        match thread.join() {
+            Ok(result) => result,
+            Err(payload) => Err(ScadError::from_panic(payload)),
+        }
+    }
+}
+
+/// A distinct subtree found while deduplicating.
+struct Node {
+    children: Vec<usize>,
+    size: usize,
+    count: usize,
+    eligible: bool,
+}
+
+/// Gives identical subtrees the same id.
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<String, usize>,
+    nodes: Vec<Node>,
+}
+
+impl Interner {
+    /// Returns the id of the subtree, counting it when count is true.
+    fn intern(&mut self, scad: &Scad, count: bool) -> usize {
+        let children: Vec<usize> = scad
+            .children
+            .iter()
+            .map(|child| self.intern(child, count))
+            .collect();
+        let mut key = match &scad.op {
+            ScadOp::Modifier(modifier) => modifier.to_string(),
+            ScadOp::Tag(tag) => format!("{:?}", tag),
+            ScadOp::BomItem { name, quantity } => format!("bom {} {}", quantity, name),
+            _ => {
+                let mut key = String::new();
+                scad.write_op(&mut key).unwrap();
+                key
+            }
+        };
+        key += &format!("{:?}", children);
+        let next = self.nodes.len();
+        let id = *self.ids.entry(key).or_insert(next);
+        if id == next {
+            let size = 1 + children.iter().map(|c| self.nodes[*c].size).sum::<usize>();
+            // leaves are as short as a call and modifiers and comments need their place
+            let eligible = !children.is_empty()
+                && !matches!(
+                    scad.op,
+                    ScadOp::Modifier(_)
+                        | ScadOp::Tag(_)
+                        | ScadOp::BomItem { .. }
+                        | ScadOp::Comment(_)
+                        | ScadOp::Raw(_)
+                );
+            self.nodes.push(Node {
+                children,
+                size,
+                count: 0,
+                eligible,
+            });
+        }
+        if count {
+            self.nodes[id].count += 1;
+        }
+        id
+    }
+
+    /// Takes the copies of a subtree inside other copies out of its count.
+    fn discount(&mut self, id: usize, copies: usize) {
+        for i in 0..self.nodes[id].children.len() {
+            let child = self.nodes[id].children[i];
+            self.nodes[child].count -= copies;
+            self.discount(child, copies);
+        }
+    }
+
+    /// Rebuilds the tree with the chosen subtrees replaced by calls.
+    fn rewrite(
+        &mut self,
+        scad: &Scad,
+        names: &HashMap<usize, String>,
+        modules: &mut Vec<(String, Scad)>,
+        defined: &mut Vec<bool>,
+    ) -> (usize, Scad) {
+        let mut children = Vec::with_capacity(scad.children.len());
+        for child in scad.children.iter() {
+            children.push(self.rewrite(child, names, modules, defined).1);
+        }
+        let id = self.intern(scad, false);
+        let rebuilt = Scad {
+            op: scad.op.clone(),
+            children,
+        };
+        match names.get(&id) {
+            Some(name) => {
+                if !defined[id] {
+                    defined[id] = true;
+                    modules.push((name.clone(), rebuilt));
+                }
+                (id, Scad::call(name, Vec::new(), Vec::new()))
+            }
+            None => (id, rebuilt),
+        }
+    }
+}
+
+impl Scad {
+    /// Moves subtrees that appear many times into modules.
+    ///
+    /// Subtrees inside a repeated subtree only count once for all its copies.
+    /// Primitives without children are never moved.
+    ///
+    /// roots: The top level Scads.
+    ///
+    /// threshold: The least number of times a subtree must appear to become a module.
+    ///
+    /// return: The modules as names and bodies and the roots with calls in place of the subtrees.
+    pub fn extract_modules(roots: &[Scad], threshold: usize) -> (Vec<(String, Scad)>, Vec<Scad>) {
+        let threshold = threshold.max(2);
+        let mut interner = Interner::default();
+        for root in roots.iter() {
+            interner.intern(root, true);
+        }
+        // parents are larger than their children so they are chosen first
+        let mut order: Vec<usize> = (0..interner.nodes.len()).collect();
+        order.sort_by_key(|id| std::cmp::Reverse(interner.nodes[*id].size));
+        let mut names = HashMap::new();
+        for id in order {
+            let node = &interner.nodes[id];
+            if node.eligible && node.count >= threshold {
+                let copies = node.count - 1;
+                names.insert(id, format!("shared_{}", names.len()));
+                interner.discount(id, copies);
+            }
+        }
+        let mut modules = Vec::new();
+        let mut defined = vec![false; interner.nodes.len()];
+        let roots = roots
+            .iter()
+            .map(|root| interner.rewrite(root, &names, &mut modules, &mut defined).1)
+            .collect();
+        (modules, roots)
+    }
+}
@@ -0,0 +1,305 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::{dcos, dsin, MersenneTwister, Pt2, Pt2s, Pt3, Pt3s, Scad};
+
+/// Create a random clockwise convex profile.
+///
+/// rng: The random number generator.
+///
+/// n_points: The number of points in the profile.
+///
+/// radius: The radius of the circle the points lie on.
+///
+/// return: The profile.
+pub fn random_convex_profile(rng: &mut MersenneTwister, n_points: usize, radius: f64) -> Pt2s {
+    assert!(n_points > 2);
+    // keep the points at least a little apart so the profile never degenerates
+    let slot = 360.0 / n_points as f64;
+    Pt2s::from_pt2s(
+        (0..n_points)
+            .map(|i| {
+                let a = -(i as f64 + rng.f64_minmax(0.1, 0.9)) * slot;
+                Pt2::new(dcos(a) * radius, dsin(a) * radius)
+            })
+            .collect(),
+    )
+}
+
+/// Create a random clockwise star shaped profile.
+///
+/// Every point can be seen from the origin so the profile never crosses itself
+/// but it is usually concave.
+///
+/// rng: The random number generator.
+///
+/// n_points: The number of points in the profile.
+///
+/// inner_radius: The smallest distance of a point from the origin.
+///
+/// outer_radius: The largest distance of a point from the origin.
+///
+/// return: The profile.
+pub fn random_star_profile(
+    rng: &mut MersenneTwister,
+    n_points: usize,
+    inner_radius: f64,
+    outer_radius: f64,
+) -> Pt2s {
+    assert!(n_points > 2);
+    assert!(inner_radius > 0.0 && outer_radius >= inner_radius);
+    let step = -360.0 / n_points as f64;
+    Pt2s::from_pt2s(
+        (0..n_points)
+            .map(|i| {
+                let r = rng.f64_minmax(inner_radius, outer_radius);
+                Pt2::new(dcos(step * i as f64) * r, dsin(step * i as f64) * r)
+            })
+            .collect(),
+    )
+}
+
+/// Create a random path that never doubles back on itself.
+///
+/// Each step turns at most 60 degrees from the previous one.
+///
+/// rng: The random number generator.
+///
+/// n_points: The number of points in the path.
+///
+/// step: The distance between points.
+///
+/// return: The path.
+pub fn random_path(rng: &mut MersenneTwister, n_points: usize, step: f64) -> Pt3s {
+    assert!(n_points > 1);
+    let mut points = Pt3s::with_capacity(n_points);
+    let mut p = Pt3::new(0.0, 0.0, 0.0);
+    let mut dir = Pt3::new(0.0, 0.0, 1.0);
+    points.push(p);
+    for _ in 1..n_points {
+        p += dir * step;
+        points.push(p);
+        let wobble = Pt3::new(
+            rng.f64_minmax(-0.5, 0.5),
+            rng.f64_minmax(-0.5, 0.5),
+            rng.f64_minmax(-0.5, 0.5),
+        );
+        dir = (dir + wobble).normalized();
+    }
+    points
+}
+
+/// Normalizes OpenSCAD code so insignificant differences don't fail a comparison.
+///
+/// Numbers are rounded to the given number of decimal places, trailing zeros
+/// and trailing whitespace are removed and line endings become \n.
+///
+/// code: The OpenSCAD code.
+///
+/// precision: The number of decimal places to keep.
+///
+/// return: The normalized code.
+pub fn normalize_scad(code: &str, precision: usize) -> String {
+    let chars: Vec<char> = code.chars().collect();
+    let mut result = String::with_capacity(code.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let in_word = i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_');
+        let starts_number = c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()));
+        if starts_number && !in_word {
+            let start = i;
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit()
+                    || chars[i] == '.'
+                    || ((chars[i] == 'e' || chars[i] == 'E')
+                        && chars
+                            .get(i + 1)
+                            .is_some_and(|n| n.is_ascii_digit() || *n == '-' || *n == '+'))
+                    || ((chars[i] == '-' || chars[i] == '+')
+                        && (chars[i - 1] == 'e' || chars[i - 1] == 'E')))
+            {
+                i += 1;
+            }
+            let token: String = chars[start..i].iter().collect();
+            match token.parse::<f64>() {
+                Ok(value) => {
+                    let mut number = format!("{:.*}", precision, value);
+                    if number.contains('.') {
+                        number = number
+                            .trim_end_matches('0')
+                            .trim_end_matches('.')
+                            .to_string();
+                    }
+                    if number == "-0" {
+                        number = "0".to_string();
+                    }
+                    result.push_str(&number);
+                }
+                Err(_) => result.push_str(&token),
+            }
+        } else {
+            result.push(c);
+            i += 1;
+        }
+    }
+    let mut lines: Vec<&str> = result
+        .split('\n')
+        .map(|line| line.trim_end_matches(['\r', ' ', '\t']))
+        .collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Compares a Scad with a stored .scad file, see assert_scad_snapshot!.
+///
+/// scad: The Scad to compare.
+///
+/// dir: The directory of the snapshots.
+///
+/// name: The name of the snapshot file without the extension.
+pub fn check_scad_snapshot(scad: &Scad, dir: &str, name: &str) {
+    let actual = normalize_scad(&scad.to_string(), 6);
+    let path = std::path::Path::new(dir).join(format!("{}.scad", name));
+    let update = std::env::var("UPDATE_SNAPSHOTS").is_ok_and(|v| v == "1");
+    if update {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    if !path.exists() {
+        panic!(
+            "snapshot {} does not exist, rerun with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        );
+    }
+    let expected = normalize_scad(&std::fs::read_to_string(&path).unwrap(), 6);
+    if actual != expected {
+        let mut diff = String::new();
+        let expected_lines: Vec<&str> = expected.lines().collect();
+        let actual_lines: Vec<&str> = actual.lines().collect();
+        for i in 0..expected_lines.len().max(actual_lines.len()) {
+            let e = expected_lines.get(i).copied();
+            let a = actual_lines.get(i).copied();
+            if e != a {
+                if let Some(e) = e {
+                    diff.push_str(&format!("{:>5} - {}\n", i + 1, e));
+                }
+                if let Some(a) = a {
+                    diff.push_str(&format!("{:>5} + {}\n", i + 1, a));
+                }
+            }
+        }
+        panic!(
+            "snapshot {} does not match, rerun with UPDATE_SNAPSHOTS=1 to accept\n{}",
+            path.display(),
+            diff
+        );
+    }
+}
+
+/// Asserts that a Polyhedron is closed, consistently wound and not inside out.
+///
+/// #params
+///
+/// polyhedron: The Polyhedron to check.
+///
+/// #patterns
+///
+/// assert_manifold!('polyhedron: Polyhedron');
+#[macro_export]
+macro_rules! assert_manifold {
+    ($polyhedron:expr) => {{
+        let report = $polyhedron.validate();
+        assert!(
+            report.non_manifold_edges.is_empty()
+                && report.inconsistent_edges.is_empty()
+                && report.invalid_indices.is_empty()
+                && !report.inverted,
+            "polyhedron is not manifold: {} non manifold edges, {} inconsistent edges, {} invalid faces, inverted: {}",
+            report.non_manifold_edges.len(),
+            report.inconsistent_edges.len(),
+            report.invalid_indices.len(),
+            report.inverted
+        );
+    }};
+}
+
+/// Asserts that the volume of a Polyhedron is close to the expected volume.
+///
+/// #params
+///
+/// polyhedron: The Polyhedron to check.
+///
+/// volume: The expected volume.
+///
+/// tolerance: The largest allowed difference from the expected volume.
+///
+/// #patterns
+///
+/// assert_volume_close!('polyhedron: Polyhedron', 'volume: f64', 'tolerance: f64');
+#[macro_export]
+macro_rules! assert_volume_close {
+    ($polyhedron:expr, $volume:expr, $tolerance:expr) => {{
+        let volume = $polyhedron.volume();
+        let expected: f64 = $volume;
+        assert!(
+            (volume - expected).abs() <= $tolerance,
+            "polyhedron volume {} is not within {} of {}",
+            volume,
+            $tolerance,
+            expected
+        );
+    }};
+}
+
+/// Asserts that a Scad matches a stored .scad snapshot.
+///
+/// Snapshots live in tests/snapshots of the crate being tested. A missing
+/// snapshot fails the test, snapshots are only created or rewritten when the
+/// UPDATE_SNAPSHOTS environment variable is 1. Numbers are compared to 6
+/// decimal places.
+///
+/// #params
+///
+/// scad: The Scad to compare.
+///
+/// name: The name of the snapshot file without the extension.
+///
+/// #patterns
+///
+/// assert_scad_snapshot!('scad: Scad', 'name: &str');
+#[macro_export]
+macro_rules! assert_scad_snapshot {
+    ($scad:expr, $name:expr) => {
+        $crate::testing::check_scad_snapshot(
+            &$scad,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+            $name,
+        )
+    };
+}
@@ -0,0 +1,196 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! The helical sweep shared by the thread modules.
+
+use crate::{dcos, dsin, parallel, triangulate2d, triangulate2d_rev, Faces, Polyhedron, Pt2s, Pt3};
+
+/// Sweeps the profile of a thread ridge along a helix starting at the XY plane.
+///
+/// During the lead in and lead out the ridge grows out of the root so points of
+/// the profile at the root radius never move.
+///
+/// profile: The clockwise (radius, z) outline of the ridge for one start.
+///
+/// root_radius: The radius of the root of the thread.
+///
+/// lead: The distance the helix advances in one revolution.
+///
+/// length: The distance the helix climbs.
+///
+/// segments: The number of segments in a full revolution.
+///
+/// lead_in_degrees: The total angle of lead in.
+///
+/// lead_out_degrees: The total angle of lead out.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// return: The ridge.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn helical_ridge(
+    profile: &Pt2s,
+    root_radius: f64,
+    lead: f64,
+    length: f64,
+    segments: u64,
+    lead_in_degrees: f64,
+    lead_out_degrees: f64,
+    left_hand_thread: bool,
+) -> Polyhedron {
+    tapered_helical_ridge(
+        profile,
+        root_radius,
+        0.0,
+        lead,
+        length,
+        segments,
+        lead_in_degrees,
+        lead_out_degrees,
+        left_hand_thread,
+    )
+}
+
+/// Sweeps the profile of a thread ridge along a conical helix starting at the XY plane.
+///
+/// The same as helical_ridge with every point of the profile moved out by
+/// taper times its height, so the root is a cone.
+///
+/// profile: The clockwise (radius, z) outline of the ridge for one start.
+///
+/// root_radius: The radius of the root of the thread at the XY plane.
+///
+/// taper: The change of the radius for each unit of height.
+///
+/// lead: The distance the helix advances in one revolution.
+///
+/// length: The distance the helix climbs.
+///
+/// segments: The number of segments in a full revolution.
+///
+/// lead_in_degrees: The total angle of lead in.
+///
+/// lead_out_degrees: The total angle of lead out.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// return: The ridge.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn tapered_helical_ridge(
+    profile: &Pt2s,
+    root_radius: f64,
+    taper: f64,
+    lead: f64,
+    length: f64,
+    segments: u64,
+    lead_in_degrees: f64,
+    lead_out_degrees: f64,
+    left_hand_thread: bool,
+) -> Polyhedron {
+    assert!(profile.len() >= 3);
+    let n_steps = (length / lead * segments as f64) as usize;
+    assert!(n_steps > 1);
+    let z_step = length / n_steps as f64;
+    let step_angle = 360.0 / segments as f64;
+    let fractions = ridge_fractions(n_steps, segments, lead_in_degrees, lead_out_degrees);
+
+    let n = profile.len();
+    let points = parallel::rings(fractions.len(), n, |ring, points| {
+        let mut angle = step_angle * ring as f64;
+        if left_hand_thread {
+            angle *= -1.0;
+        }
+        let c = dcos(angle);
+        let s = dsin(angle);
+        let z = z_step * ring.saturating_sub(1) as f64;
+        for p in profile.iter() {
+            let r = root_radius + (p.x - root_radius) * fractions[ring] + taper * (z + p.y);
+            points.push(Pt3::new(c * r, s * r, z + p.y));
+        }
+    });
+
+    let mut faces = Faces::with_capacity(fractions.len() * n + 2);
+    let rings: Vec<Vec<u64>> = (0..fractions.len())
+        .map(|ring| {
+            let a = (ring * n) as u64;
+            (a..a + n as u64).collect()
+        })
+        .collect();
+    for pair in rings.windows(2) {
+        if left_hand_thread {
+            faces.push_strip(&pair[0], &pair[1]);
+        } else {
+            faces.push_strip(&pair[1], &pair[0]);
+        }
+    }
+    let (start_cap, end_cap) = if left_hand_thread {
+        (triangulate2d_rev(profile), triangulate2d(profile))
+    } else {
+        (triangulate2d(profile), triangulate2d_rev(profile))
+    };
+    faces.append_offset(Faces::from_triangles(&start_cap), 0);
+    let end = ((fractions.len() - 1) * n) as u64;
+    faces.append_offset(Faces::from_triangles(&end_cap), end);
+
+    Polyhedron::new(points, faces)
+}
+
+/// How far each ring of the ridge sticks out from the root, from 0 to 1.
+fn ridge_fractions(
+    n_steps: usize,
+    segments: u64,
+    lead_in_degrees: f64,
+    lead_out_degrees: f64,
+) -> Vec<f64> {
+    let lead_in = lead_in_degrees > 0.0;
+    let lead_out = lead_out_degrees > 0.0;
+    let n_lead_in_steps = (segments as f64 * lead_in_degrees / 360.0 + 2.0) as usize;
+    let n_lead_out_steps = (segments as f64 * lead_out_degrees / 360.0) as usize;
+
+    let start_fraction = 2.0 / n_lead_in_steps as f64;
+    let mut fractions = Vec::with_capacity(n_steps);
+    fractions.push(start_fraction);
+
+    let mut lead_in_step = 3;
+    let mut lead_in_fraction = start_fraction;
+    let mut lead_out_step = n_lead_out_steps;
+    let mut lead_out_fraction = 1.0;
+    for step in 0..(n_steps - 1) {
+        if lead_in_step < n_lead_in_steps && lead_in {
+            fractions.push(lead_in_fraction);
+            lead_in_step += 1;
+            lead_in_fraction = start_fraction
+                + (1.0 - start_fraction) * lead_in_step as f64 / n_lead_in_steps as f64;
+        } else if lead_out_step > 0 && step >= n_steps - n_lead_out_steps && lead_out {
+            fractions.push(lead_out_fraction);
+            lead_out_step -= 1;
+            let end_fraction = 1.0 / n_lead_out_steps as f64;
+            lead_out_fraction = 1.0
+                + (end_fraction - 1.0) * (n_lead_out_steps - lead_out_step) as f64
+                    / n_lead_out_steps as f64;
+        } else {
+            fractions.push(1.0);
+        }
+    }
+    fractions
+}
@@ -0,0 +1,234 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use crate::{dtan, prelude::*, thread::helical_ridge};
+
+/// Returns the ISO 2904 crest clearance for the given pitch.
+fn crest_clearance(pitch: f64) -> f64 {
+    if pitch <= 1.5 {
+        0.15
+    } else if pitch <= 5.0 {
+        0.25
+    } else if pitch <= 12.0 {
+        0.5
+    } else {
+        1.0
+    }
+}
+
+/// Creates a 30 degree trapezoidal thread around a core cylinder.
+///
+/// root_radius: The radius of the core.
+///
+/// crest_radius: The radius of the crest of the thread.
+///
+/// pitch_radius: The radius where the ridge is half the pitch wide.
+///
+/// pitch: The distance between neighboring ridges.
+///
+/// starts: The number of thread starts.
+///
+/// length: The length of the threaded cylinder.
+///
+/// segments: The number of segments in a full revolution.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// return: The threaded cylinder.
+#[allow(clippy::too_many_arguments)]
+fn trapezoidal_cylinder(
+    root_radius: f64,
+    crest_radius: f64,
+    pitch_radius: f64,
+    pitch: f64,
+    starts: u64,
+    length: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    center: bool,
+) -> Scad {
+    assert!(starts > 0);
+    let width = |r: f64| pitch / 2.0 - 2.0 * (r - pitch_radius) * dtan(15.0);
+    let root_width = width(root_radius);
+    let crest_width = width(crest_radius);
+    assert!(root_width < pitch && crest_width > 0.0);
+    let profile = Pt2s::from_pt2s(vec![
+        Pt2::new(root_radius, root_width),
+        Pt2::new(crest_radius, (root_width + crest_width) / 2.0),
+        Pt2::new(crest_radius, (root_width - crest_width) / 2.0),
+        Pt2::new(root_radius, 0.0),
+    ]);
+    let lead = pitch * starts as f64;
+    let convexity = (length / pitch) as u64 + 1;
+    let ridge = helical_ridge(
+        &profile,
+        root_radius,
+        lead,
+        length - root_width,
+        segments,
+        0.0,
+        0.0,
+        left_hand_thread,
+    );
+
+    let mut children = Vec::with_capacity(starts as usize + 1);
+    for start in 0..starts {
+        let mut ridge = ridge.clone();
+        ridge.rotate_z(360.0 * start as f64 / starts as f64);
+        children.push(ridge.into_scad_with_convexity(convexity));
+    }
+    children.push(Polyhedron::cylinder(root_radius + 0.0001, length, segments).into_scad());
+    let mut result = Scad {
+        op: ScadOp::Union,
+        children,
+    };
+    if center {
+        result = translate!([0.0, 0.0, -length / 2.0], result;);
+    }
+    result
+}
+
+/// Creates a trapezoidal leadscrew at the world origin e.g. Tr8x2 with 4 starts.
+///
+/// diameter: The nominal diameter of the screw.
+///
+/// pitch: The distance between neighboring ridges, the lead is pitch * starts.
+///
+/// starts: The number of thread starts.
+///
+/// length: The length of the screw.
+///
+/// segments: The number of segments in a full revolution.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// return: The leadscrew.
+#[allow(clippy::too_many_arguments)]
+pub fn leadscrew(
+    diameter: f64,
+    pitch: f64,
+    starts: u64,
+    length: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    center: bool,
+) -> Scad {
+    let thread_depth = pitch / 2.0 + crest_clearance(pitch);
+    trapezoidal_cylinder(
+        diameter / 2.0 - thread_depth,
+        diameter / 2.0,
+        diameter / 2.0 - pitch / 4.0,
+        pitch,
+        starts,
+        length,
+        segments,
+        left_hand_thread,
+        center,
+    )
+}
+
+/// Create a tap for making trapezoidal threaded holes in things.
+///
+/// diameter: The nominal diameter of the mating screw.
+///
+/// pitch: The distance between neighboring ridges, the lead is pitch * starts.
+///
+/// starts: The number of thread starts.
+///
+/// length: The length of the tap.
+///
+/// segments: The number of segments in a full revolution.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// return: The tap.
+#[allow(clippy::too_many_arguments)]
+pub fn leadscrew_tap(
+    diameter: f64,
+    pitch: f64,
+    starts: u64,
+    length: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    center: bool,
+) -> Scad {
+    trapezoidal_cylinder(
+        diameter / 2.0 - pitch / 2.0,
+        diameter / 2.0 + crest_clearance(pitch),
+        diameter / 2.0 - pitch / 4.0,
+        pitch,
+        starts,
+        length,
+        segments,
+        left_hand_thread,
+        center,
+    )
+}
+
+/// Create a round nut for a trapezoidal leadscrew.
+///
+/// diameter: The nominal diameter of the mating screw.
+///
+/// pitch: The distance between neighboring ridges, the lead is pitch * starts.
+///
+/// starts: The number of thread starts.
+///
+/// outer_diameter: The outside diameter of the nut.
+///
+/// height: The height of the nut.
+///
+/// segments: The number of segments in a full revolution.
+///
+/// left_hand_thread: lefty tighty?
+///
+/// center: Center vertically.
+///
+/// return: The nut.
+#[allow(clippy::too_many_arguments)]
+pub fn leadscrew_nut(
+    diameter: f64,
+    pitch: f64,
+    starts: u64,
+    outer_diameter: f64,
+    height: f64,
+    segments: u64,
+    left_hand_thread: bool,
+    center: bool,
+) -> Scad {
+    assert!(outer_diameter > diameter + 2.0 * crest_clearance(pitch));
+    let nut_tap = translate!([0.0, 0.0, -1.0],
+        leadscrew_tap(diameter, pitch, starts, height + 2.0, segments, left_hand_thread, false);
+    );
+    let mut nut =
+        Polyhedron::cylinder(outer_diameter / 2.0, height, segments).into_scad() - nut_tap;
+    if center {
+        nut = translate!([0.0, 0.0, -height / 2.0], nut;);
+    }
+    nut
+}
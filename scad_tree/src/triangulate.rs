@@ -23,7 +23,11 @@
 //! A Rust implementation of the ear clipping algorithm described, and coded in C++, at
 //! <https://abitwise.blogspot.com/2013/09/triangulating-concave-and-convex.html>
 
-use crate::{approx_eq, Indices, Pt2, Pt2s, Pt3, Pt3s};
+use crate::{
+    approx_eq,
+    clip::{cross, signed_area},
+    Indices, Pt2, Pt2s, Pt3, Pt3s,
+};
 
 /// Test if winding order is counter clockwise.
 ///
@@ -261,6 +265,204 @@ pub fn triangulate2d_rev(vertices: &Pt2s) -> Indices {
     triangulate(polygon)
 }
 
+/// Triangulate a 2D polygon with holes.
+///
+/// Each hole is joined to the outline by a bridge to a vertex it can see and the
+/// resulting polygon is ear clipped. The triangles are clockwise like triangulate2d.
+/// Holes that touch or cross the outline, each other or themselves are still
+/// bridged and clipped, but the triangles may not cover exactly the area inside.
+///
+/// outline: The vertices of the outer boundary.
+///
+/// holes: The vertices of the holes, inside the outline and not touching each other.
+///
+/// return: An array of indices into the outline followed by the holes.
+pub fn triangulate2d_holes(outline: &Pt2s, holes: &[Pt2s]) -> Indices {
+    assert!(outline.len() > 2);
+    let mut polygon: Vec<(u64, Pt2)> = outline
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i as u64, *p))
+        .collect();
+    if signed_area(outline) > 0.0 {
+        polygon.reverse();
+    }
+    let mut offset = outline.len() as u64;
+    let mut loops = Vec::with_capacity(holes.len());
+    for hole in holes.iter() {
+        let mut points: Vec<(u64, Pt2)> = hole
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (offset + i as u64, *p))
+            .collect();
+        if signed_area(hole) < 0.0 {
+            points.reverse();
+        }
+        offset += hole.len() as u64;
+        if points.len() > 2 {
+            loops.push(points);
+        }
+    }
+    // bridging from the right keeps the bridges from crossing holes not yet joined
+    let max_x = |points: &[(u64, Pt2)]| points.iter().fold(f64::MIN, |x, p| x.max(p.1.x));
+    loops.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    for hole in loops {
+        bridge(&mut polygon, hole);
+    }
+    clip_ears(polygon)
+}
+
+/// The clockwise angle in radians from u to v in the range 0 to 2 pi.
+fn clockwise_angle(u: Pt2, v: Pt2) -> f64 {
+    let a = (-cross(u, v)).atan2(u.dot(v));
+    if a < 0.0 {
+        a + std::f64::consts::TAU
+    } else {
+        a
+    }
+}
+
+/// True if the segments a0 a1 and b0 b1 cross at a point inside both.
+fn segments_cross(a0: Pt2, a1: Pt2, b0: Pt2, b1: Pt2) -> bool {
+    let d0 = cross(a1 - a0, b0 - a0);
+    let d1 = cross(a1 - a0, b1 - a0);
+    let d2 = cross(b1 - b0, a0 - b0);
+    let d3 = cross(b1 - b0, a1 - b0);
+    d0 * d1 < 0.0 && d2 * d3 < 0.0
+}
+
+/// Splice a counter clockwise hole into a clockwise polygon with a bridge from
+/// the rightmost point of the hole to the closest polygon vertex it can see.
+///
+/// A hole that touches or crosses the outline may not see any vertex, then the
+/// bridge goes to the closest vertex it leaves on the inside of the corner, or
+/// failing that the closest vertex.
+fn bridge(polygon: &mut Vec<(u64, Pt2)>, hole: Vec<(u64, Pt2)>) {
+    let mut m = 0;
+    for k in 1..hole.len() {
+        if hole[k].1.x > hole[m].1.x {
+            m = k;
+        }
+    }
+    let start = hole[m].1;
+    let n = polygon.len();
+    let edges = || {
+        (0..n)
+            .map(|i| (polygon[i].1, polygon[(i + 1) % n].1))
+            .chain((0..hole.len()).map(|i| (hole[i].1, hole[(i + 1) % hole.len()].1)))
+    };
+    // the closest vertex that is visible, inside the corner and any at all
+    let mut best: [Option<(usize, f64)>; 3] = [None; 3];
+    let closer =
+        |best: Option<(usize, f64)>, distance: f64| best.map_or(true, |(_, d)| distance < d);
+    for i in 0..n {
+        let v = polygon[i].1;
+        let distance = (v - start).len();
+        if closer(best[2], distance) {
+            best[2] = Some((i, distance));
+        }
+        if !closer(best[0], distance) {
+            continue;
+        }
+        // the bridge must leave v on the inside of the corner
+        let prev = polygon[(i + n - 1) % n].1;
+        let next = polygon[(i + 1) % n].1;
+        let inside = clockwise_angle(next - v, start - v);
+        if inside <= 0.0 || inside >= clockwise_angle(next - v, prev - v) {
+            continue;
+        }
+        if closer(best[1], distance) {
+            best[1] = Some((i, distance));
+        }
+        let same = |p: Pt2| (p - v).len() < 1.0e-12 || (p - start).len() < 1.0e-12;
+        if edges().any(|(a, b)| !same(a) && !same(b) && segments_cross(start, v, a, b)) {
+            continue;
+        }
+        best[0] = Some((i, distance));
+    }
+    let (i, _) = best[0]
+        .or(best[1])
+        .or(best[2])
+        .expect("the polygon has vertices");
+    let mut spliced = Vec::with_capacity(n + hole.len() + 2);
+    spliced.extend_from_slice(&polygon[..=i]);
+    spliced.extend((0..=hole.len()).map(|k| hole[(m + k) % hole.len()]));
+    spliced.extend_from_slice(&polygon[i..]);
+    *polygon = spliced;
+}
+
+/// Ear clip a clockwise polygon that may touch itself along bridges.
+///
+/// A polygon that crosses itself, e.g. from a hole that crosses itself, can run
+/// out of ears. Then the sharpest convex corner is clipped even though it
+/// contains other points. Clipping stops when only corners that turn the wrong
+/// way are left, they are inside out and are left unfilled.
+fn clip_ears(mut polygon: Vec<(u64, Pt2)>) -> Indices {
+    const EPSILON: f64 = 1.0e-12;
+    let mut triangles = Indices::from_indices(Vec::with_capacity(polygon.len() * 3));
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        let corner = |i: usize| {
+            (
+                polygon[(i + n - 1) % n].1,
+                polygon[i].1,
+                polygon[(i + 1) % n].1,
+            )
+        };
+        let ear = (0..n).find(|i| {
+            let (a, b, c) = corner(*i);
+            // clockwise polygons turn right at convex corners
+            if cross(b - a, c - b) > -EPSILON {
+                return false;
+            }
+            !polygon.iter().any(|(_, p)| {
+                let p = *p;
+                let corner =
+                    (p - a).len() < EPSILON || (p - b).len() < EPSILON || (p - c).len() < EPSILON;
+                !corner
+                    && cross(b - a, p - a) <= 0.0
+                    && cross(c - b, p - b) <= 0.0
+                    && cross(a - c, p - c) <= 0.0
+            })
+        });
+        let sharpest = || {
+            (0..n)
+                .filter(|i| {
+                    let (a, b, c) = corner(*i);
+                    cross(b - a, c - b) < -EPSILON
+                })
+                .map(|i| {
+                    let (a, b, c) = corner(i);
+                    (i, cross(b - a, c - b).atan2((b - a).dot(c - b)))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        };
+        let Some(i) = ear
+            .or_else(|| {
+                (0..n).find(|i| {
+                    let (a, b, c) = corner(*i);
+                    cross(b - a, c - b).abs() <= EPSILON
+                })
+            })
+            .or_else(sharpest)
+        else {
+            break;
+        };
+        let (a, b, c) = corner(i);
+        if cross(b - a, c - b) < -EPSILON {
+            triangles.push(polygon[(i + n - 1) % n].0);
+            triangles.push(polygon[i].0);
+            triangles.push(polygon[(i + 1) % n].0);
+        }
+        polygon.remove(i);
+    }
+    if polygon.len() == 3 && cross(polygon[1].1 - polygon[0].1, polygon[2].1 - polygon[1].1) < 0.0 {
+        triangles.extend(polygon.iter().map(|p| p.0));
+    }
+    triangles
+}
+
 // triangulates clockwise
 fn triangulate(mut polygon: Vec<(u64, Pt2)>) -> Indices {
     let mut triangles = Indices::from_indices(Vec::with_capacity((polygon.len() - 2) * 3));
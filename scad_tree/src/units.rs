@@ -0,0 +1,187 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Lengths in millimeters and inches.
+//!
+//! OpenSCAD has no units but slicers and most other tools read its output as
+//! millimeters, so the library works in millimeters. Use Length or the _in
+//! macros to give imperial sizes and Scad::scaled_from_inches for models that
+//! are drawn in inches.
+
+use crate::prelude::*;
+
+/// The number of millimeters in an inch.
+pub const MM_PER_INCH: f64 = 25.4;
+
+/// A unit of length.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    /// Millimeters, the unit of the library.
+    Millimeter,
+    /// Centimeters.
+    Centimeter,
+    /// Meters.
+    Meter,
+    /// Inches.
+    Inch,
+    /// Feet.
+    Foot,
+}
+
+impl Unit {
+    /// The number of millimeters in one of the unit.
+    pub fn mm(self) -> f64 {
+        match self {
+            Unit::Millimeter => 1.0,
+            Unit::Centimeter => 10.0,
+            Unit::Meter => 1000.0,
+            Unit::Inch => MM_PER_INCH,
+            Unit::Foot => MM_PER_INCH * 12.0,
+        }
+    }
+
+    /// Converts a value from one unit to another.
+    ///
+    /// value: The value in this unit.
+    ///
+    /// to: The unit to convert to.
+    ///
+    /// return: The value in the to unit.
+    pub fn convert(self, value: f64, to: Unit) -> f64 {
+        value * self.mm() / to.mm()
+    }
+}
+
+/// A length stored in millimeters.
+///
+/// Construct it in any unit and read it in any unit, e.g.
+/// `cylinder!(Length::inches(1.0).to_mm(), Length::inches(0.25).to_mm() / 2.0)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Length(f64);
+
+impl Length {
+    /// Creates a length in a unit.
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self(value * unit.mm())
+    }
+
+    /// Creates a length in millimeters.
+    pub fn mm(value: f64) -> Self {
+        Self(value)
+    }
+
+    /// Creates a length in inches.
+    pub fn inches(value: f64) -> Self {
+        Self(value * MM_PER_INCH)
+    }
+
+    /// Creates a length from a fraction of an inch, e.g. 5/16 for hardware sizes.
+    pub fn fraction(numerator: u64, denominator: u64) -> Self {
+        Self::inches(numerator as f64 / denominator as f64)
+    }
+
+    /// The length in millimeters, the value to hand to the library.
+    pub fn to_mm(self) -> f64 {
+        self.0
+    }
+
+    /// The length in inches.
+    pub fn to_inches(self) -> f64 {
+        self.0 / MM_PER_INCH
+    }
+
+    /// The length in a unit.
+    pub fn to(self, unit: Unit) -> f64 {
+        self.0 / unit.mm()
+    }
+}
+
+impl From<Length> for f64 {
+    fn from(length: Length) -> Self {
+        length.0
+    }
+}
+
+impl std::ops::Add for Length {
+    type Output = Length;
+
+    fn add(self, rhs: Length) -> Self::Output {
+        Length(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for Length {
+    type Output = Length;
+
+    fn sub(self, rhs: Length) -> Self::Output {
+        Length(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::Mul<f64> for Length {
+    type Output = Length;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Length(self.0 * rhs)
+    }
+}
+
+impl std::ops::Div<f64> for Length {
+    type Output = Length;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Length(self.0 / rhs)
+    }
+}
+
+impl std::ops::Neg for Length {
+    type Output = Length;
+
+    fn neg(self) -> Self::Output {
+        Length(-self.0)
+    }
+}
+
+impl Scad {
+    /// Scales the object from one unit to another.
+    ///
+    /// from: The unit the object is drawn in.
+    ///
+    /// to: The unit of the result.
+    ///
+    /// return: The scaled object.
+    pub fn convert_units(self, from: Unit, to: Unit) -> Self {
+        let s = from.convert(1.0, to);
+        scale!([s, s, s], self;)
+    }
+
+    /// Scales an object drawn in inches to millimeters for OpenSCAD and slicers.
+    pub fn scaled_from_inches(self) -> Self {
+        self.convert_units(Unit::Inch, Unit::Millimeter)
+    }
+
+    /// Scales an object in millimeters to inches for tools that read inches.
+    pub fn scaled_to_inches(self) -> Self {
+        self.convert_units(Unit::Millimeter, Unit::Inch)
+    }
+}
@@ -0,0 +1,307 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Flattens developable surfaces into patterns for cutting from sheet material.
+
+use crate::{clip::signed_area, prelude::*};
+
+const EPSILON: f64 = 1.0e-9;
+
+/// A flattened surface as cut outlines and the seam lines that are joined when
+/// the sheet is rolled back up.
+#[derive(Clone, Default, PartialEq)]
+pub struct FlatPattern {
+    /// The closed outlines to cut, clockwise.
+    pub outlines: Vec<Pt2s>,
+    /// The edges of the outlines that are joined to each other, as two point lines.
+    pub seams: Vec<Pt2s>,
+}
+
+/// Places the third corner of a triangle from its distances to p and q, on the
+/// right of the line from p to q.
+fn place(p: Pt2, q: Pt2, to_p: f64, to_q: f64) -> Pt2 {
+    let base = (q - p).len();
+    if base < EPSILON {
+        return p + Pt2::new(to_p, 0.0);
+    }
+    let u = (q - p) / base;
+    let along = (to_p * to_p - to_q * to_q + base * base) / (2.0 * base);
+    let h = (to_p * to_p - along * along).max(0.0).sqrt();
+    p + u * along + Pt2::new(u.y, -u.x) * h
+}
+
+impl FlatPattern {
+    /// Unroll the ruled surface between two polylines.
+    ///
+    /// Each quad between the polylines is split into two triangles that are laid
+    /// flat in turn, so the pattern is exact for developable surfaces like
+    /// cylinders, cones and the bands of a swept tube.
+    ///
+    /// bottom: The points of the first edge of the surface.
+    ///
+    /// top: The points of the second edge, one for each point of bottom.
+    ///
+    /// closed: True if the polylines are loops, the seam is cut at the first point.
+    ///
+    /// return: The flat pattern with the first ruling at x = 0.
+    pub fn ruled(bottom: &Pt3s, top: &Pt3s, closed: bool) -> Self {
+        assert!(bottom.len() == top.len());
+        assert!(bottom.len() > 1);
+        let n = if closed {
+            bottom.len() + 1
+        } else {
+            bottom.len()
+        };
+        let a = |i: usize| bottom[i % bottom.len()];
+        let b = |i: usize| top[i % top.len()];
+
+        let mut flat_a = vec![Pt2::new(0.0, 0.0)];
+        let mut flat_b = vec![Pt2::new(0.0, (b(0) - a(0)).len())];
+        for i in 1..n {
+            let (pa, pb) = (flat_a[i - 1], flat_b[i - 1]);
+            let next_a = place(pa, pb, (a(i) - a(i - 1)).len(), (a(i) - b(i - 1)).len());
+            let next_b = place(next_a, pb, (b(i) - a(i)).len(), (b(i) - b(i - 1)).len());
+            flat_a.push(next_a);
+            flat_b.push(next_b);
+        }
+
+        let seams = if closed {
+            vec![
+                Pt2s::from_pt2s(vec![flat_a[0], flat_b[0]]),
+                Pt2s::from_pt2s(vec![flat_a[n - 1], flat_b[n - 1]]),
+            ]
+        } else {
+            Vec::new()
+        };
+        let mut outline: Vec<Pt2> = flat_a;
+        outline.extend(flat_b.into_iter().rev());
+        outline.dedup_by(|p, q| (*p - *q).len() < EPSILON);
+        if (outline[0] - outline[outline.len() - 1]).len() < EPSILON {
+            outline.pop();
+        }
+        if signed_area(&outline) > 0.0 {
+            outline.reverse();
+        }
+        Self {
+            outlines: vec![Pt2s::from_pt2s(outline)],
+            seams,
+        }
+    }
+
+    /// Unroll the side of a cylinder.
+    ///
+    /// radius: The radius of the cylinder.
+    ///
+    /// height: The height of the cylinder.
+    ///
+    /// segments: The number of segments in the circumference.
+    ///
+    /// return: The flat pattern.
+    pub fn cylinder(radius: f64, height: f64, segments: u64) -> Self {
+        Self::cone(radius, radius, height, segments)
+    }
+
+    /// Unroll the side of a cone or frustum.
+    ///
+    /// radius1: The radius at the bottom.
+    ///
+    /// radius2: The radius at the top, may be 0.
+    ///
+    /// height: The height of the cone.
+    ///
+    /// segments: The number of segments in the circumference.
+    ///
+    /// return: The flat pattern.
+    pub fn cone(radius1: f64, radius2: f64, height: f64, segments: u64) -> Self {
+        let ring = |radius: f64, z: f64| {
+            Pt3s::from_pt3s(
+                dim2::circle(radius, segments)
+                    .iter()
+                    .map(|p| p.as_pt3(z))
+                    .collect(),
+            )
+        };
+        Self::ruled(&ring(radius1, 0.0), &ring(radius2, height), true)
+    }
+
+    /// Unroll a swept tube into one band for each segment of the path.
+    ///
+    /// Bands are stacked along y so that they can be cut from a single sheet. Each
+    /// band is seamed along the first point of the profile.
+    ///
+    /// profile: The closed profile that is swept.
+    ///
+    /// path: The path that the profile follows.
+    ///
+    /// twist_degrees: The twist of the profile along the path.
+    ///
+    /// closed: True if the path is a loop.
+    ///
+    /// gap: The space between the bands.
+    ///
+    /// return: The flat pattern.
+    pub fn sweep(profile: &Pt2s, path: &Pt3s, twist_degrees: f64, closed: bool, gap: f64) -> Self {
+        let mesh = Polyhedron::sweep(profile, path, twist_degrees, closed);
+        let rings: Vec<Pt3s> = mesh.points[..path.len() * profile.len()]
+            .chunks(profile.len())
+            .map(Pt3s::from_slice)
+            .collect();
+        let n_bands = if closed { rings.len() } else { rings.len() - 1 };
+        let bands: Vec<FlatPattern> = (0..n_bands)
+            .map(|i| Self::ruled(&rings[i], &rings[(i + 1) % rings.len()], true))
+            .collect();
+        Self::stack(&bands, gap)
+    }
+
+    /// Lay patterns out one above the other.
+    ///
+    /// patterns: The patterns to lay out, the first at the bottom.
+    ///
+    /// gap: The space between the patterns.
+    ///
+    /// return: The combined pattern.
+    pub fn stack(patterns: &[FlatPattern], gap: f64) -> Self {
+        let mut result = Self::default();
+        let mut y = 0.0;
+        for pattern in patterns.iter() {
+            let Some((min, max)) = pattern.bounds() else {
+                continue;
+            };
+            let mut pattern = pattern.clone();
+            pattern.translate(Pt2::new(-min.x, y - min.y));
+            result.outlines.append(&mut pattern.outlines);
+            result.seams.append(&mut pattern.seams);
+            y += max.y - min.y + gap;
+        }
+        result
+    }
+
+    /// Move the pattern.
+    ///
+    /// offset: The amount to move by.
+    pub fn translate(&mut self, offset: Pt2) -> &mut Self {
+        for path in self.outlines.iter_mut().chain(self.seams.iter_mut()) {
+            path.translate(offset);
+        }
+        self
+    }
+
+    /// The corners of the box around the outlines.
+    ///
+    /// return: The minimum and maximum points or None if the pattern is empty.
+    pub fn bounds(&self) -> Option<(Pt2, Pt2)> {
+        let mut points = self.outlines.iter().flat_map(|path| path.iter());
+        let first = *points.next()?;
+        Some(points.fold((first, first), |(min, max), p| {
+            (
+                Pt2::new(min.x.min(p.x), min.y.min(p.y)),
+                Pt2::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        }))
+    }
+
+    /// Format the pattern as an SVG drawing in millimeters.
+    ///
+    /// Outlines are drawn in black and seams as dashed red lines.
+    ///
+    /// return: The SVG text.
+    pub fn to_svg(&self) -> String {
+        let (min, max) = self
+            .bounds()
+            .unwrap_or((Pt2::new(0.0, 0.0), Pt2::new(0.0, 0.0)));
+        let (width, height) = (max.x - min.x, max.y - min.y);
+        // SVG y points down
+        let xy = |p: &Pt2| format!("{},{}", Float(p.x - min.x), Float(max.y - p.y));
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}mm\" height=\"{}mm\" viewBox=\"0 0 {} {}\">\n",
+            Float(width),
+            Float(height),
+            Float(width),
+            Float(height)
+        );
+        for outline in self.outlines.iter() {
+            let points: Vec<String> = outline.iter().map(xy).collect();
+            svg.push_str(&format!(
+                "<polygon points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"0.1\"/>\n",
+                points.join(" ")
+            ));
+        }
+        for seam in self.seams.iter() {
+            let points: Vec<String> = seam.iter().map(xy).collect();
+            svg.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"red\" stroke-width=\"0.1\" stroke-dasharray=\"1,1\"/>\n",
+                points.join(" ")
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Format the pattern as an ASCII DXF drawing.
+    ///
+    /// Outlines are closed POLYLINE entities on the CUT layer and seams are LINE
+    /// entities on the SEAM layer.
+    ///
+    /// return: The DXF text.
+    pub fn to_dxf(&self) -> String {
+        let mut dxf = String::from("0\nSECTION\n2\nENTITIES\n");
+        for outline in self.outlines.iter() {
+            dxf.push_str("0\nPOLYLINE\n8\nCUT\n66\n1\n70\n1\n");
+            for p in outline.iter() {
+                dxf.push_str(&format!(
+                    "0\nVERTEX\n8\nCUT\n10\n{}\n20\n{}\n",
+                    Float(p.x),
+                    Float(p.y)
+                ));
+            }
+            dxf.push_str("0\nSEQEND\n8\nCUT\n");
+        }
+        for seam in self.seams.iter() {
+            for line in seam.windows(2) {
+                dxf.push_str(&format!(
+                    "0\nLINE\n8\nSEAM\n10\n{}\n20\n{}\n11\n{}\n21\n{}\n",
+                    Float(line[0].x),
+                    Float(line[0].y),
+                    Float(line[1].x),
+                    Float(line[1].y)
+                ));
+            }
+        }
+        dxf.push_str("0\nENDSEC\n0\nEOF\n");
+        dxf
+    }
+
+    /// Save the pattern as an SVG file.
+    ///
+    /// path: The path of the file.
+    pub fn save_svg(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_svg())
+    }
+
+    /// Save the pattern as a DXF file.
+    ///
+    /// path: The path of the file.
+    pub fn save_dxf(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_dxf())
+    }
+}
@@ -21,10 +21,40 @@
 // SOFTWARE.
 //
 
+use std::{
+    path::Path,
+    process::{Child, Command},
+    time::Duration,
+};
+
 use scad_tree_math::Mt4;
 
 use crate::prelude::*;
 
+/// Set on a program restarted by Viewer::watch so it does not open OpenSCAD again.
+const WATCH_ENV: &str = "SCAD_TREE_WATCH";
+
+/// The options for Viewer::watch.
+#[derive(Clone, Debug)]
+pub struct WatchOptions {
+    /// The time between rebuilding the tree.
+    pub interval: Duration,
+    /// Launch OpenSCAD with the file after it is first written.
+    pub open: bool,
+    /// Restart the program when its executable is rebuilt.
+    pub restart_on_rebuild: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(500),
+            open: true,
+            restart_on_rebuild: true,
+        }
+    }
+}
+
 /// Viewer struct is used to view points, edges, and curves in OpenSCAD.
 pub struct Viewer {
     point_radius: f64,
@@ -289,4 +319,87 @@ impl Viewer {
     pub fn into_scad(self) -> Scad {
         self.scad.unwrap()
     }
+
+    /// Like into_scad but returns an error instead of panicking when nothing was added.
+    pub fn try_into_scad(self) -> Result<Scad, ScadError> {
+        self.scad.ok_or(ScadError::Empty)
+    }
+
+    /// Writes the viewer to a file.
+    ///
+    /// path: The path of the file.
+    ///
+    /// return: An error when nothing was added or the file could not be written.
+    pub fn save(&self, path: &str) -> Result<(), ScadError> {
+        let scad = self.scad.as_ref().ok_or(ScadError::Empty)?;
+        scad.try_save(path)?;
+        Ok(())
+    }
+
+    /// Launches OpenSCAD with a file.
+    ///
+    /// Turn on Design > Automatic Reload and Preview in OpenSCAD to see the
+    /// file change while watching.
+    ///
+    /// path: The path of the file to open.
+    ///
+    /// return: The OpenSCAD process.
+    pub fn open(path: &str) -> Result<Child, ScadError> {
+        Ok(openscad::command()?.arg(path).spawn()?)
+    }
+
+    /// Rebuilds and writes a tree until build returns None.
+    ///
+    /// The file is only written when the tree changes so OpenSCAD only reloads
+    /// when there is something new to see. When the program is rebuilt, by cargo
+    /// build in another terminal for example, the new program is started with
+    /// the same arguments and watch returns.
+    ///
+    /// path: The path of the file to write.
+    ///
+    /// options: How often to rebuild, whether to launch OpenSCAD and whether to
+    /// restart on rebuild.
+    ///
+    /// build: Creates the tree, usually from a Viewer with into_scad. Return None
+    /// to stop watching.
+    ///
+    /// return: An error when the tree is invalid or a file or process failed.
+    pub fn watch<F>(path: &str, options: &WatchOptions, mut build: F) -> Result<(), ScadError>
+    where
+        F: FnMut() -> Option<Scad>,
+    {
+        let exe = std::env::current_exe().ok();
+        let modified = |exe: &Path| std::fs::metadata(exe).and_then(|m| m.modified()).ok();
+        let built = exe.as_deref().and_then(modified);
+        let mut opened = std::env::var_os(WATCH_ENV).is_some();
+        let mut last = String::new();
+        while let Some(scad) = build() {
+            scad.validate()?;
+            let s = scad.to_string();
+            if s != last {
+                // write then rename so OpenSCAD never reads half a file
+                let tmp = format!("{}.tmp", path);
+                std::fs::write(&tmp, &s)?;
+                std::fs::rename(&tmp, path)?;
+                last = s;
+            }
+            if options.open && !opened {
+                Self::open(path)?;
+                opened = true;
+            }
+            std::thread::sleep(options.interval);
+            if let (true, Some(exe), Some(built)) = (options.restart_on_rebuild, &exe, built) {
+                if modified(exe).is_some_and(|m| m != built) {
+                    // give the linker time to finish writing
+                    std::thread::sleep(options.interval);
+                    Command::new(exe)
+                        .args(std::env::args_os().skip(1))
+                        .env(WATCH_ENV, "1")
+                        .spawn()?;
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
 }
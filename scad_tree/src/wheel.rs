@@ -0,0 +1,234 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! Wheels and caster wheels with spokes, hubs and bearing pockets.
+
+use crate::{dcos, dsin, prelude::*};
+
+/// The extra diameter of a bearing pocket so the bearing can be pressed in.
+const BEARING_CLEARANCE: f64 = 0.2;
+
+/// The shape of the outer edge of a wheel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TireProfile {
+    /// Square edges.
+    Flat,
+    /// Both edges rounded with the radius.
+    Rounded(f64),
+    /// Both edges chamfered by the size.
+    Chamfered(f64),
+}
+
+/// How the hub is joined to the rim.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpokeStyle {
+    /// A solid web.
+    Solid,
+    /// Straight radial spokes.
+    Straight,
+    /// A web with round lightening holes.
+    Holes,
+}
+
+/// A wheel lying on the XY plane with its axle along the Z axis.
+#[derive(Clone)]
+pub struct Wheel {
+    /// The outside diameter of the tire.
+    pub diameter: f64,
+    /// The width of the tire.
+    pub width: f64,
+    /// The shape of the outer edge of the tire.
+    pub tire_profile: TireProfile,
+    /// The radial thickness of the rim.
+    pub rim_thickness: f64,
+    /// How the hub is joined to the rim.
+    pub spoke_style: SpokeStyle,
+    /// The number of spokes or holes.
+    pub spoke_count: u64,
+    /// The width of straight spokes.
+    pub spoke_width: f64,
+    /// The thickness of the spokes or web, centered on the width.
+    pub web_thickness: f64,
+    /// The outside diameter of the hub.
+    pub hub_diameter: f64,
+    /// The length of the hub, centered on the width.
+    pub hub_width: f64,
+    /// The diameter of the axle hole.
+    pub bore: f64,
+    /// The outside diameter of the bearings, 0 for no bearing pockets.
+    pub bearing_diameter: f64,
+    /// The width of the bearings. A pocket is cut into each side of the hub.
+    pub bearing_width: f64,
+    /// The number of segments in a circle.
+    pub segments: u64,
+}
+
+impl Default for Wheel {
+    fn default() -> Self {
+        Self {
+            diameter: 60.0,
+            width: 20.0,
+            tire_profile: TireProfile::Rounded(4.0),
+            rim_thickness: 5.0,
+            spoke_style: SpokeStyle::Straight,
+            spoke_count: 6,
+            spoke_width: 5.0,
+            web_thickness: 6.0,
+            hub_diameter: 26.0,
+            hub_width: 20.0,
+            bore: 8.0,
+            bearing_diameter: 22.0,
+            bearing_width: 7.0,
+            segments: 72,
+        }
+    }
+}
+
+impl Wheel {
+    /// Create a wheel with the given size and the default hub and spokes.
+    ///
+    /// diameter: The outside diameter of the tire.
+    ///
+    /// width: The width of the tire.
+    ///
+    /// return: The wheel.
+    pub fn new(diameter: f64, width: f64) -> Self {
+        Self {
+            diameter,
+            width,
+            hub_width: width,
+            ..Default::default()
+        }
+    }
+
+    /// Create the wheel.
+    pub fn to_scad(&self) -> Scad {
+        let outer = self.diameter / 2.0;
+        let inner = outer - self.rim_thickness;
+        let hub = self.hub_diameter / 2.0;
+        assert!(inner > hub && hub > self.bore / 2.0);
+        assert!(self.web_thickness <= self.width && self.hub_width >= self.web_thickness);
+        if self.bearing_diameter > 0.0 {
+            assert!(self.bearing_diameter + BEARING_CLEARANCE < self.hub_diameter);
+            assert!(self.bearing_width * 2.0 < self.hub_width);
+        }
+
+        let rim = rotate_extrude!(angle=360.0, convexity=2, fn=self.segments,
+            polygon!(self.rim_profile(inner, outer));
+        );
+        let web = self.web(inner, hub);
+        let hub_cylinder = translate!([0.0, 0.0, (self.width - self.hub_width) / 2.0],
+            cylinder!(h=self.hub_width, r1=hub, r2=hub, center=false, fn=self.segments);
+        );
+
+        let mut holes = translate!([0.0, 0.0, -1.0],
+            cylinder!(h=self.width + 2.0, d1=self.bore, d2=self.bore, center=false,
+                fn=self.segments);
+        );
+        if self.bearing_diameter > 0.0 {
+            let d = self.bearing_diameter + BEARING_CLEARANCE;
+            let z = (self.width - self.hub_width) / 2.0;
+            holes = holes
+                + translate!([0.0, 0.0, z - 1.0],
+                    cylinder!(h=self.bearing_width + 1.0, d1=d, d2=d, center=false,
+                        fn=self.segments);
+                )
+                + translate!([0.0, 0.0, z + self.hub_width - self.bearing_width],
+                    cylinder!(h=self.bearing_width + 1.0, d1=d, d2=d, center=false,
+                        fn=self.segments);
+                );
+        }
+
+        rim + web + hub_cylinder - holes
+    }
+
+    /// The cross section of the rim in the XZ plane, clockwise.
+    fn rim_profile(&self, inner: f64, outer: f64) -> Pt2s {
+        let w = self.width;
+        let mut points = vec![Pt2::new(inner, 0.0), Pt2::new(inner, w)];
+        match self.tire_profile {
+            TireProfile::Flat => {
+                points.push(Pt2::new(outer, w));
+                points.push(Pt2::new(outer, 0.0));
+            }
+            TireProfile::Chamfered(size) => {
+                let size = size.min(w / 2.0).min(self.rim_thickness);
+                points.push(Pt2::new(outer - size, w));
+                points.push(Pt2::new(outer, w - size));
+                points.push(Pt2::new(outer, size));
+                points.push(Pt2::new(outer - size, 0.0));
+            }
+            TireProfile::Rounded(radius) => {
+                let radius = radius.min(w / 2.0).min(self.rim_thickness);
+                let segments = (self.segments / 4).max(2);
+                let top = Pt2::new(outer - radius, w - radius);
+                let bottom = Pt2::new(outer - radius, radius);
+                for i in 0..=segments {
+                    let a = 90.0 - 90.0 * i as f64 / segments as f64;
+                    points.push(top + Pt2::new(dcos(a), dsin(a)) * radius);
+                }
+                for i in 0..=segments {
+                    let a = -90.0 * i as f64 / segments as f64;
+                    points.push(bottom + Pt2::new(dcos(a), dsin(a)) * radius);
+                }
+                points.dedup_by(|a, b| (*a - *b).len() < 1.0e-9);
+            }
+        }
+        Pt2s::from_pt2s(points)
+    }
+
+    /// The spokes or web between the hub and the rim.
+    fn web(&self, inner: f64, hub: f64) -> Scad {
+        let z = (self.width - self.web_thickness) / 2.0;
+        // overlap the hub and rim so the union is solid
+        let span = inner - hub + 2.0;
+        match self.spoke_style {
+            SpokeStyle::Solid => self.disc(z, inner + 1.0),
+            SpokeStyle::Straight => {
+                assert!(self.spoke_count > 0);
+                let spoke = translate!([hub - 1.0, -self.spoke_width / 2.0, z],
+                    cube!([span, self.spoke_width, self.web_thickness]);
+                );
+                Scad::polar_array(&spoke, self.spoke_count, 360.0)
+            }
+            SpokeStyle::Holes => {
+                assert!(self.spoke_count > 0);
+                let middle = (inner + hub) / 2.0;
+                let d = ((inner - hub) * 0.7)
+                    .min(std::f64::consts::TAU * middle / self.spoke_count as f64 * 0.7);
+                let hole = translate!([middle, 0.0, z - 1.0],
+                    cylinder!(h=self.web_thickness + 2.0, d1=d, d2=d, center=false,
+                        fn=self.segments / 2);
+                );
+                self.disc(z, inner + 1.0) - Scad::polar_array(&hole, self.spoke_count, 360.0)
+            }
+        }
+    }
+
+    fn disc(&self, z: f64, radius: f64) -> Scad {
+        translate!([0.0, 0.0, z],
+            cylinder!(h=self.web_thickness, r1=radius, r2=radius, center=false,
+                fn=self.segments);
+        )
+    }
+}
@@ -0,0 +1,152 @@
+// MIT License
+//
+// Copyright (c) 2023 Michael H. Phillips
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//! The policy for writing floats in the Display output of points and Scads.
+
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// How floats are written by Display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest text that reads back as the same f64, e.g. 0.30000000000000004.
+    Shortest,
+    /// Rounded to this many significant digits. The digits left of the decimal
+    /// point are always kept and at most 10 digits are written after it, so
+    /// rounding noise like cos(90) is written as 0.
+    Significant(u32),
+    /// Rounded to this many digits after the decimal point.
+    Decimals(u32),
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        Self::Significant(6)
+    }
+}
+
+impl FloatFormat {
+    fn encode(self) -> u64 {
+        match self {
+            Self::Shortest => 0,
+            Self::Significant(digits) => 1 << 32 | digits as u64,
+            Self::Decimals(digits) => 2 << 32 | digits as u64,
+        }
+    }
+
+    fn decode(bits: u64) -> Self {
+        let digits = bits as u32;
+        match bits >> 32 {
+            1 => Self::Significant(digits),
+            2 => Self::Decimals(digits),
+            _ => Self::Shortest,
+        }
+    }
+
+    /// Writes value with this format.
+    ///
+    /// Trailing zeros are removed and zero is always written as 0.
+    fn write(self, f: &mut std::fmt::Formatter<'_>, value: f64) -> std::fmt::Result {
+        if !value.is_finite() {
+            return write!(f, "{}", value);
+        }
+        let decimals = match self {
+            Self::Shortest => return write!(f, "{}", value),
+            Self::Significant(_) if value == 0.0 => 0,
+            Self::Significant(digits) => {
+                let magnitude = value.abs().log10().floor() as i64;
+                ((digits.max(1) as i64 - 1 - magnitude).max(0) as usize)
+                    .min(MAX_SIGNIFICANT_DECIMALS)
+            }
+            Self::Decimals(digits) => digits as usize,
+        };
+        let text = format!("{:.*}", decimals, value);
+        let text = if text.contains('.') {
+            text.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &text
+        };
+        if text == "-0" {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", text)
+        }
+    }
+}
+
+/// The most digits FloatFormat::Significant writes after the decimal point.
+const MAX_SIGNIFICANT_DECIMALS: usize = 10;
+
+static GLOBAL_FORMAT: AtomicU64 = AtomicU64::new(1 << 32 | 6);
+
+thread_local! {
+    static SCOPED_FORMAT: Cell<Option<FloatFormat>> = const { Cell::new(None) };
+}
+
+/// The format used for floats on this thread.
+///
+/// return: The format of the innermost with_float_format or else the global format.
+pub fn float_format() -> FloatFormat {
+    SCOPED_FORMAT
+        .with(|scoped| scoped.get())
+        .unwrap_or_else(|| FloatFormat::decode(GLOBAL_FORMAT.load(Ordering::Relaxed)))
+}
+
+/// Sets the format used for floats on all threads.
+///
+/// format: The new format, the default is 6 significant digits.
+pub fn set_float_format(format: FloatFormat) {
+    GLOBAL_FORMAT.store(format.encode(), Ordering::Relaxed);
+}
+
+/// Runs code with a different float format on this thread only.
+///
+/// format: The format used while code runs.
+///
+/// code: The code to run.
+///
+/// return: The result of code.
+pub fn with_float_format<R>(format: FloatFormat, code: impl FnOnce() -> R) -> R {
+    let previous = SCOPED_FORMAT.with(|scoped| scoped.replace(Some(format)));
+    // restore on unwind too so a panic doesn't leak the format
+    struct Restore(Option<FloatFormat>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_FORMAT.with(|scoped| scoped.set(self.0));
+        }
+    }
+    let _restore = Restore(previous);
+    code()
+}
+
+/// Displays an f64 with the current float format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Float(pub f64);
+
+impl std::fmt::Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        float_format().write(f, self.0)
+    }
+}
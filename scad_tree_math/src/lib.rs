@@ -21,6 +21,7 @@
 // SOFTWARE.
 //
 
+mod float;
 mod mt4;
 mod pt2;
 mod pt3;
@@ -28,6 +29,7 @@ mod pt4;
 mod rng;
 
 pub use crate::{
+    float::{float_format, set_float_format, with_float_format, Float, FloatFormat},
     mt4::Mt4,
     pt2::{Pt2, Pt2s},
     pt3::{Pt3, Pt3s},
@@ -21,7 +21,7 @@
 // SOFTWARE.
 //
 
-use crate::{dcos, dsin, dtan, Pt3, Pt4};
+use crate::{dcos, dsin, dtan, Float, Pt3, Pt4};
 
 /// A matrix with column major order.
 #[derive(Clone, Copy, Default, PartialEq)]
@@ -34,10 +34,38 @@ pub struct Mt4 {
 
 impl std::fmt::Display for Mt4 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "| {} {} {} {} |", self.x.x, self.y.x, self.z.x, self.w.x)?;
-        writeln!(f, "| {} {} {} {} |", self.x.y, self.y.y, self.z.y, self.w.y)?;
-        writeln!(f, "| {} {} {} {} |", self.x.z, self.y.z, self.z.z, self.w.z)?;
-        writeln!(f, "| {} {} {} {} |", self.x.w, self.y.w, self.z.w, self.w.w)
+        writeln!(
+            f,
+            "| {} {} {} {} |",
+            Float(self.x.x),
+            Float(self.y.x),
+            Float(self.z.x),
+            Float(self.w.x)
+        )?;
+        writeln!(
+            f,
+            "| {} {} {} {} |",
+            Float(self.x.y),
+            Float(self.y.y),
+            Float(self.z.y),
+            Float(self.w.y)
+        )?;
+        writeln!(
+            f,
+            "| {} {} {} {} |",
+            Float(self.x.z),
+            Float(self.y.z),
+            Float(self.z.z),
+            Float(self.w.z)
+        )?;
+        writeln!(
+            f,
+            "| {} {} {} {} |",
+            Float(self.x.w),
+            Float(self.y.w),
+            Float(self.z.w),
+            Float(self.w.w)
+        )
     }
 }
 
@@ -133,7 +161,7 @@ impl Mt4 {
                 0.0,
             ),
             Pt4::new(
-                z * x * (1.0 - c) - z * s,
+                z * x * (1.0 - c) - y * s,
                 z * y * (1.0 - c) + x * s,
                 c + z * z * (1.0 - c),
                 0.0,
@@ -281,7 +309,7 @@ impl Mt4 {
         } else {
             det = 1.0 / det;
             for i in 0..16 {
-                out[i] = out[i] * det;
+                out[i] *= det;
             }
             Some(out)
         }
@@ -21,7 +21,7 @@
 // SOFTWARE.
 //
 
-use crate::{dcos, dsin, Pt3};
+use crate::{dcos, dsin, Float, Pt3};
 
 /// Wraps a `Vec<Pt2>`.
 #[derive(Clone, PartialEq)]
@@ -46,15 +46,24 @@ impl std::ops::DerefMut for Pt2s {
 impl std::fmt::Display for Pt2s {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        for i in 0..self.len() - 1 {
-            write!(f, "{},", self[i])?
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", item)?;
         }
-        write!(f, "{}]", self[self.len() - 1])
+        write!(f, "]")
+    }
+}
+
+impl Default for Pt2s {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Pt2s {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self { inner: Vec::new() }
     }
 
@@ -68,9 +77,15 @@ impl Pt2s {
         Self { inner: pt2s }
     }
 
+    pub fn from_slice(pt2s: &[Pt2]) -> Self {
+        Self {
+            inner: pt2s.to_vec(),
+        }
+    }
+
     pub fn translate(&mut self, point: Pt2) {
         for pt in self.iter_mut() {
-            *pt = *pt + point
+            *pt += point
         }
     }
 
@@ -80,6 +95,105 @@ impl Pt2s {
         }
         self
     }
+
+    /// The length of the path through the points.
+    pub fn length(&self) -> f64 {
+        self.windows(2).map(|w| (w[1] - w[0]).len()).sum()
+    }
+
+    /// The distance along the path to each point, starting with 0.0.
+    pub fn cumulative_lengths(&self) -> Vec<f64> {
+        let mut lengths = Vec::with_capacity(self.len());
+        let mut total = 0.0;
+        for i in 0..self.len() {
+            if i > 0 {
+                total += (self[i] - self[i - 1]).len();
+            }
+            lengths.push(total);
+        }
+        lengths
+    }
+
+    /// The point at distance along the path, clamped to the ends of the path.
+    pub fn point_at_distance(&self, distance: f64) -> Pt2 {
+        assert!(!self.is_empty());
+        if distance <= 0.0 {
+            return self[0];
+        }
+        let mut total = 0.0;
+        for i in 1..self.len() {
+            let segment = (self[i] - self[i - 1]).len();
+            if total + segment >= distance && segment > 0.0 {
+                return self[i - 1].lerp(self[i], (distance - total) / segment);
+            }
+            total += segment;
+        }
+        self[self.len() - 1]
+    }
+
+    /// Evenly spaced points along the path, the ends are kept.
+    ///
+    /// Use this to even out paths from beziers, whose points bunch up where
+    /// the curve is tight.
+    pub fn resample(&self, segments: usize) -> Pt2s {
+        assert!(segments > 0);
+        if self.len() < 2 {
+            return self.clone();
+        }
+        let length = self.length();
+        let mut points = Pt2s::with_capacity(segments + 1);
+        points.push(self[0]);
+        let mut i = 1;
+        let mut start = 0.0;
+        for j in 1..segments {
+            let distance = length * j as f64 / segments as f64;
+            while i < self.len() - 1 && start + (self[i] - self[i - 1]).len() < distance {
+                start += (self[i] - self[i - 1]).len();
+                i += 1;
+            }
+            let segment = (self[i] - self[i - 1]).len();
+            if segment > 0.0 {
+                points.push(self[i - 1].lerp(self[i], ((distance - start) / segment).min(1.0)));
+            } else {
+                points.push(self[i]);
+            }
+        }
+        points.push(self[self.len() - 1]);
+        points
+    }
+
+    /// Points along the path about step apart, the ends are kept.
+    ///
+    /// The step is adjusted so all the segments are the same length.
+    pub fn resample_by_distance(&self, step: f64) -> Pt2s {
+        assert!(step > 0.0);
+        self.resample(((self.length() / step).round() as usize).max(1))
+    }
+
+    /// Smooth the path with Chaikin's corner cutting, the end points are kept.
+    pub fn smooth(&self, iterations: u64) -> Pt2s {
+        let mut points = self.clone();
+        for _ in 0..iterations {
+            if points.len() < 3 {
+                break;
+            }
+            let mut smoothed = Pt2s::with_capacity(points.len() * 2);
+            smoothed.push(points[0]);
+            for i in 0..points.len() - 1 {
+                let a = points[i];
+                let b = points[i + 1];
+                if i > 0 {
+                    smoothed.push(a.lerp(b, 0.25));
+                }
+                if i < points.len() - 2 {
+                    smoothed.push(a.lerp(b, 0.75));
+                }
+            }
+            smoothed.push(points[points.len() - 1]);
+            points = smoothed;
+        }
+        points
+    }
 }
 
 /// A 2D point.
@@ -91,7 +205,7 @@ pub struct Pt2 {
 
 impl std::fmt::Display for Pt2 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}, {}]", self.x, self.y)
+        write!(f, "[{}, {}]", Float(self.x), Float(self.y))
     }
 }
 
@@ -182,7 +296,7 @@ impl std::ops::Neg for Pt2 {
 }
 
 impl Pt2 {
-    pub fn new(x: f64, y: f64) -> Self {
+    pub const fn new(x: f64, y: f64) -> Self {
         Self { x, y }
     }
 
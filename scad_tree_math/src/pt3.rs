@@ -21,7 +21,7 @@
 // SOFTWARE.
 //
 
-use crate::{dcos, dsin, Mt4, Pt2s, Pt4};
+use crate::{dcos, dsin, Float, Mt4, Pt2s, Pt4};
 
 /// Wraps a `Vec<Pt3>`.
 #[derive(Clone, PartialEq)]
@@ -46,15 +46,24 @@ impl std::ops::DerefMut for Pt3s {
 impl std::fmt::Display for Pt3s {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        for i in 0..self.len() - 1 {
-            write!(f, "{},", self[i])?
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", item)?;
         }
-        write!(f, "{}]", self[self.len() - 1])
+        write!(f, "]")
+    }
+}
+
+impl Default for Pt3s {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Pt3s {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self { inner: Vec::new() }
     }
 
@@ -68,6 +77,12 @@ impl Pt3s {
         Self { inner: pt3s }
     }
 
+    pub fn from_slice(pt3s: &[Pt3]) -> Self {
+        Self {
+            inner: pt3s.to_vec(),
+        }
+    }
+
     pub fn from_pt2s(pt2s: &Pt2s, z: f64) -> Self {
         let mut pt3s = Pt3s::with_capacity(pt2s.len());
         for i in 0..pt2s.len() {
@@ -78,7 +93,7 @@ impl Pt3s {
 
     pub fn translate(&mut self, point: Pt3) {
         for pt in self.iter_mut() {
-            *pt = *pt + point
+            *pt += point
         }
     }
 
@@ -108,6 +123,173 @@ impl Pt3s {
         }
         self
     }
+
+    /// The length of the path through the points.
+    pub fn length(&self) -> f64 {
+        self.windows(2).map(|w| (w[1] - w[0]).len()).sum()
+    }
+
+    /// The distance along the path to each point, starting with 0.0.
+    pub fn cumulative_lengths(&self) -> Vec<f64> {
+        let mut lengths = Vec::with_capacity(self.len());
+        let mut total = 0.0;
+        for i in 0..self.len() {
+            if i > 0 {
+                total += (self[i] - self[i - 1]).len();
+            }
+            lengths.push(total);
+        }
+        lengths
+    }
+
+    /// The point at distance along the path, clamped to the ends of the path.
+    pub fn point_at_distance(&self, distance: f64) -> Pt3 {
+        assert!(!self.is_empty());
+        if distance <= 0.0 {
+            return self[0];
+        }
+        let mut total = 0.0;
+        for i in 1..self.len() {
+            let segment = (self[i] - self[i - 1]).len();
+            if total + segment >= distance && segment > 0.0 {
+                return self[i - 1].lerp(self[i], (distance - total) / segment);
+            }
+            total += segment;
+        }
+        self[self.len() - 1]
+    }
+
+    /// Evenly spaced points along the path, the ends are kept.
+    ///
+    /// Use this to even out paths from beziers, whose points bunch up where
+    /// the curve is tight.
+    pub fn resample(&self, segments: usize) -> Pt3s {
+        assert!(segments > 0);
+        if self.len() < 2 {
+            return self.clone();
+        }
+        let length = self.length();
+        let mut points = Pt3s::with_capacity(segments + 1);
+        points.push(self[0]);
+        let mut i = 1;
+        let mut start = 0.0;
+        for j in 1..segments {
+            let distance = length * j as f64 / segments as f64;
+            while i < self.len() - 1 && start + (self[i] - self[i - 1]).len() < distance {
+                start += (self[i] - self[i - 1]).len();
+                i += 1;
+            }
+            let segment = (self[i] - self[i - 1]).len();
+            if segment > 0.0 {
+                points.push(self[i - 1].lerp(self[i], ((distance - start) / segment).min(1.0)));
+            } else {
+                points.push(self[i]);
+            }
+        }
+        points.push(self[self.len() - 1]);
+        points
+    }
+
+    /// Points along the path about step apart, the ends are kept.
+    ///
+    /// The step is adjusted so all the segments are the same length.
+    pub fn resample_by_distance(&self, step: f64) -> Pt3s {
+        assert!(step > 0.0);
+        self.resample(((self.length() / step).round() as usize).max(1))
+    }
+
+    /// Smooth the path with Chaikin's corner cutting, the end points are kept.
+    pub fn smooth(&self, iterations: u64) -> Pt3s {
+        let mut points = self.clone();
+        for _ in 0..iterations {
+            if points.len() < 3 {
+                break;
+            }
+            let mut smoothed = Pt3s::with_capacity(points.len() * 2);
+            smoothed.push(points[0]);
+            for i in 0..points.len() - 1 {
+                let a = points[i];
+                let b = points[i + 1];
+                if i > 0 {
+                    smoothed.push(a.lerp(b, 0.25));
+                }
+                if i < points.len() - 2 {
+                    smoothed.push(a.lerp(b, 0.75));
+                }
+            }
+            smoothed.push(points[points.len() - 1]);
+            points = smoothed;
+        }
+        points
+    }
+
+    /// Replace the interior corners of the path with circular arcs.
+    ///
+    /// The radius is reduced at corners where the neighboring segments are
+    /// too short to fit it.
+    pub fn fillet_corners(&self, radius: f64, segments: u64) -> Pt3s {
+        assert!(segments > 0);
+        if self.len() < 3 {
+            return self.clone();
+        }
+        let mut points = Pt3s::with_capacity(self.len() + (self.len() - 2) * segments as usize);
+        points.push(self[0]);
+        for i in 1..self.len() - 1 {
+            let p = self[i];
+            let to_prev = self[i - 1] - p;
+            let to_next = self[i + 1] - p;
+            let (prev_len, next_len) = (to_prev.len(), to_next.len());
+            if prev_len == 0.0 || next_len == 0.0 {
+                points.push(p);
+                continue;
+            }
+            let u = to_prev / prev_len;
+            let v = to_next / next_len;
+            let theta = u.dot(v).clamp(-1.0, 1.0).acos();
+            if !(1.0e-6..=std::f64::consts::PI - 1.0e-6).contains(&theta) {
+                points.push(p);
+                continue;
+            }
+            let half_tan = (theta / 2.0).tan();
+            let d = (radius / half_tan).min(prev_len / 2.0).min(next_len / 2.0);
+            let r = d * half_tan;
+            let center = p + (u + v).normalized() * (r / (theta / 2.0).sin());
+            let e1 = (p + u * d - center).normalized();
+            let mut e2 = p + v * d - center;
+            e2 = (e2 - e1 * e2.dot(e1)).normalized();
+            let sweep = std::f64::consts::PI - theta;
+            for j in 0..=segments {
+                let a = sweep * j as f64 / segments as f64;
+                points.push(center + (e1 * a.cos() + e2 * a.sin()) * r);
+            }
+        }
+        points.push(self[self.len() - 1]);
+        points
+    }
+
+    /// The point on the path closest to point.
+    pub fn closest_point(&self, point: Pt3) -> Pt3 {
+        assert!(!self.is_empty());
+        let mut closest = self[0];
+        let mut closest_len2 = (point - closest).len2();
+        for i in 1..self.len() {
+            let a = self[i - 1];
+            let ab = self[i] - a;
+            let ab_len2 = ab.len2();
+            let t = if ab_len2 > 0.0 {
+                ((point - a).dot(ab) / ab_len2).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let candidate = a + ab * t;
+            let len2 = (point - candidate).len2();
+            if len2 < closest_len2 {
+                closest = candidate;
+                closest_len2 = len2;
+            }
+        }
+        closest
+    }
 }
 
 /// A 3D point.
@@ -120,7 +302,13 @@ pub struct Pt3 {
 
 impl std::fmt::Display for Pt3 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}, {}, {}]", self.x, self.y, self.z)
+        write!(
+            f,
+            "[{}, {}, {}]",
+            Float(self.x),
+            Float(self.y),
+            Float(self.z)
+        )
     }
 }
 
@@ -213,7 +401,7 @@ impl std::ops::Neg for Pt3 {
 }
 
 impl Pt3 {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
     }
 
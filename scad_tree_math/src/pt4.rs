@@ -21,7 +21,7 @@
 // SOFTWARE.
 //
 
-use crate::Pt3;
+use crate::{Float, Pt3};
 
 /// Wraps a `Vec<Pt4>`.
 #[derive(Clone, PartialEq)]
@@ -46,15 +46,24 @@ impl std::ops::DerefMut for Pt4s {
 impl std::fmt::Display for Pt4s {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        for i in 0..self.len() - 1 {
-            write!(f, "{},", self[i])?
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", item)?;
         }
-        write!(f, "{}]", self[self.len() - 1])
+        write!(f, "]")
+    }
+}
+
+impl Default for Pt4s {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Pt4s {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self { inner: Vec::new() }
     }
 
@@ -67,6 +76,12 @@ impl Pt4s {
     pub fn from_pt4s(pt4s: Vec<Pt4>) -> Self {
         Self { inner: pt4s }
     }
+
+    pub fn from_slice(pt4s: &[Pt4]) -> Self {
+        Self {
+            inner: pt4s.to_vec(),
+        }
+    }
 }
 
 /// A 4D point.
@@ -80,7 +95,14 @@ pub struct Pt4 {
 
 impl std::fmt::Display for Pt4 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}, {}, {}, {}]", self.x, self.y, self.z, self.w)
+        write!(
+            f,
+            "[{}, {}, {}, {}]",
+            Float(self.x),
+            Float(self.y),
+            Float(self.z),
+            Float(self.w)
+        )
     }
 }
 
@@ -185,7 +207,7 @@ impl std::ops::Neg for Pt4 {
 }
 
 impl Pt4 {
-    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+    pub const fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
         Self { x, y, z, w }
     }
 
@@ -226,4 +248,49 @@ impl Pt4 {
     pub fn as_pt3(&self) -> Pt3 {
         Pt3::new(self.x, self.y, self.z)
     }
+
+    /// An RGBA color from hue, saturation and value.
+    ///
+    /// h: The hue in degrees, 0 is red, 120 green and 240 blue.
+    ///
+    /// s: The saturation from 0 to 1.
+    ///
+    /// v: The value from 0 to 1.
+    ///
+    /// a: The alpha from 0 to 1.
+    ///
+    /// return: The color with red, green, blue and alpha from 0 to 1.
+    pub fn from_hsv(h: f64, s: f64, v: f64, a: f64) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let c = v * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u64 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = v - c;
+        Self::new(r + m, g + m, b + m, a)
+    }
+
+    /// The hue in degrees, saturation, value and alpha of an RGBA color.
+    pub fn to_hsv(self) -> Self {
+        let max = self.x.max(self.y).max(self.z);
+        let min = self.x.min(self.y).min(self.z);
+        let c = max - min;
+        let h = if c == 0.0 {
+            0.0
+        } else if max == self.x {
+            60.0 * ((self.y - self.z) / c).rem_euclid(6.0)
+        } else if max == self.y {
+            60.0 * ((self.z - self.x) / c + 2.0)
+        } else {
+            60.0 * ((self.x - self.y) / c + 4.0)
+        };
+        let s = if max == 0.0 { 0.0 } else { c / max };
+        Self::new(h, s, max, self.w)
+    }
 }
@@ -39,11 +39,17 @@ pub struct MersenneTwister {
     index: usize,
 }
 
+impl Default for MersenneTwister {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MersenneTwister {
-    fn next(&mut self) -> u32 {
+    fn twist(&mut self) {
         let mut y: u32;
         let mag: [u32; 2] = [0x0, 0x9908b0df]; // mag[x] = x * 0x9908b0df for x = 0,1
-        if self.index >= STATE_VECTOR_LENGTH {
+        {
             let mut kk: usize = 0;
             while kk < STATE_VECTOR_LENGTH - STATE_VECTOR_M {
                 y = (self.buffer[kk] & UPPER_MASK) | (self.buffer[kk + 1] & LOWER_MASK);
@@ -64,7 +70,13 @@ impl MersenneTwister {
                 self.buffer[STATE_VECTOR_M - 1] ^ (y >> 1) ^ mag[(y & 0x1) as usize];
             self.index = 0;
         }
-        y = self.buffer[self.index];
+    }
+
+    fn next(&mut self) -> u32 {
+        if self.index >= STATE_VECTOR_LENGTH {
+            self.twist();
+        }
+        let mut y = self.buffer[self.index];
         self.index += 1;
         y ^= y >> 11;
         y ^= (y << 7) & TEMPERING_MASK_B;
@@ -94,7 +106,7 @@ impl MersenneTwister {
 
     /// Yeilds a random f32 in the range [min..max).
     pub fn f32_minmax(&mut self, min: f32, max: f32) -> f32 {
-        min + (max - min) * self.f32_0_1() as f32
+        min + (max - min) * self.f32_0_1()
     }
 
     /// Yeilds a random f64 in the range [min..max).
@@ -118,7 +130,7 @@ impl MersenneTwister {
         unsafe {
             result.buffer.set_len(STATE_VECTOR_LENGTH);
         }
-        result.buffer[0] = seed & 0xffffffff;
+        result.buffer[0] = seed;
         while result.index < STATE_VECTOR_LENGTH {
             result.buffer[result.index] =
                 ((6069 * result.buffer[result.index - 1] as usize) & 0xffffffff) as u32;
@@ -126,4 +138,89 @@ impl MersenneTwister {
         }
         result
     }
+
+    /// Create a MersenneTwister with a 64 bit seed.
+    pub fn with_seed_u64(seed: u64) -> Self {
+        Self::with_seed_sequence(&[seed as u32, (seed >> 32) as u32])
+    }
+
+    /// Create a MersenneTwister seeded from a sequence of numbers.
+    ///
+    /// Uses the init_by_array method of the reference implementation.
+    pub fn with_seed_sequence(seeds: &[u32]) -> Self {
+        assert!(!seeds.is_empty());
+        let mut mt = vec![0u32; STATE_VECTOR_LENGTH];
+        mt[0] = 19650218;
+        for i in 1..STATE_VECTOR_LENGTH {
+            mt[i] = 1812433253u32
+                .wrapping_mul(mt[i - 1] ^ (mt[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+        let mut i = 1;
+        let mut j = 0;
+        for _ in 0..STATE_VECTOR_LENGTH.max(seeds.len()) {
+            mt[i] = (mt[i] ^ (mt[i - 1] ^ (mt[i - 1] >> 30)).wrapping_mul(1664525))
+                .wrapping_add(seeds[j])
+                .wrapping_add(j as u32);
+            i += 1;
+            j += 1;
+            if i >= STATE_VECTOR_LENGTH {
+                mt[0] = mt[STATE_VECTOR_LENGTH - 1];
+                i = 1;
+            }
+            if j >= seeds.len() {
+                j = 0;
+            }
+        }
+        for _ in 0..STATE_VECTOR_LENGTH - 1 {
+            mt[i] = (mt[i] ^ (mt[i - 1] ^ (mt[i - 1] >> 30)).wrapping_mul(1566083941))
+                .wrapping_sub(i as u32);
+            i += 1;
+            if i >= STATE_VECTOR_LENGTH {
+                mt[0] = mt[STATE_VECTOR_LENGTH - 1];
+                i = 1;
+            }
+        }
+        mt[0] = 0x80000000;
+        Self {
+            buffer: mt,
+            index: STATE_VECTOR_LENGTH,
+        }
+    }
+
+    /// Create the generator for one of many independent streams sharing a seed.
+    ///
+    /// The same seed and stream always give the same numbers no matter how
+    /// many other streams are used or in what order.
+    pub fn stream(seed: u64, stream: u64) -> Self {
+        Self::with_seed_sequence(&[
+            seed as u32,
+            (seed >> 32) as u32,
+            stream as u32,
+            (stream >> 32) as u32,
+        ])
+    }
+
+    /// Create a new generator seeded from this one.
+    ///
+    /// Useful for giving each part of a model its own generator so adding
+    /// random calls to one part does not change the others.
+    pub fn split(&mut self) -> Self {
+        let seeds = [self.next(), self.next(), self.next(), self.next()];
+        Self::with_seed_sequence(&seeds)
+    }
+
+    /// Advance the generator as if n numbers were drawn.
+    pub fn jump(&mut self, n: u64) {
+        let mut n = n;
+        loop {
+            let remaining = (STATE_VECTOR_LENGTH - self.index) as u64;
+            if n <= remaining {
+                self.index += n as usize;
+                return;
+            }
+            n -= remaining;
+            self.twist();
+        }
+    }
 }